@@ -0,0 +1,58 @@
+use std::path::PathBuf;
+
+use clap::{Arg, ArgMatches};
+use ga4ghphetools::dto::orcid::validate_orcid;
+
+use crate::commands::util::Progress;
+
+
+/// Returns the `clap::Command` for this subcommand
+pub fn command() -> clap::Command {
+    clap::Command::new("batch-hpoa")
+        .about("Write the HPO annotations (HPOA format) of all Mendelian cohorts of a repository, one file per disease")
+        .arg(
+            Arg::new("store")
+                .short('s')
+                .long("store")
+                .required(true)
+                .help("Root directory of the repository (all *_individuals.json files are included)"),
+        )
+        .arg(Arg::new("hpoa-dir").long("hpoa-dir").required(true).help("Output directory"))
+        .arg(Arg::new("hpo").long("hpo"))
+        .arg(
+            Arg::new("biocurator")
+                .short('b')
+                .long("biocurator")
+                .help("ORCID of the biocurator, e.g., ORCID:0000-0002-0736-9199 (default: orcid in phetools.toml)"),
+        )
+        .arg(
+            Arg::new("merged")
+                .long("merged")
+                .help("Also write all annotations to this file, sorted by disease ID"),
+        )
+}
+
+/// Handler for the subcommand
+pub fn handle(sub_matches: &ArgMatches) -> Result<(), Box<dyn std::error::Error>> {
+    let store = PathBuf::from(sub_matches.get_one::<String>("store").unwrap());
+    let hpoa_dir = PathBuf::from(sub_matches.get_one::<String>("hpoa-dir").unwrap());
+    let hpo_path = crate::config::get().hpo_path(sub_matches)?;
+    let biocurator = crate::config::get().orcid(sub_matches, "biocurator")?;
+    let biocurator = validate_orcid(&biocurator)?;
+    let hpo = crate::load_hpo(&hpo_path)?;
+    let progress = Progress::new("Cohorts");
+    let n_files = ga4ghphetools::hpoa::write_hpoa_for_all_cohorts_with_progress(
+        &store,
+        hpo.clone(),
+        &biocurator,
+        &hpoa_dir,
+        |p, q| progress.update(p, q))?;
+    progress.finish();
+    println!("Wrote {n_files} HPOA files to {}", hpoa_dir.display());
+    if let Some(merged) = sub_matches.get_one::<String>("merged") {
+        let merged_path = crate::config::get().output_path(merged);
+        let n_rows = ga4ghphetools::hpoa::write_merged_hpoa_for_all_cohorts(&store, hpo, &biocurator, &merged_path)?;
+        println!("Wrote {n_rows} annotations to {}", merged_path.display());
+    }
+    Ok(())
+}
@@ -1,4 +1,4 @@
-use clap::{Arg, ArgMatches};
+use clap::{Arg, ArgAction, ArgMatches};
 
 
 #[cfg(feature = "excel_export")]
@@ -7,11 +7,18 @@ use ga4ghphetools::export::output_excel_comparison;
 /// Returns the `clap::Command` for this subcommand
 pub fn command() -> clap::Command {
     clap::Command::new("compare")
-        .about("Compare two cohorts and export to Excel")
-        .arg(Arg::new("cohort1").long("cohort1").required(true))
-        .arg(Arg::new("cohort2").long("cohort2").required(true))
+        .about("Compare the HPO term frequencies of cohorts (two cohorts to Excel, or any number to TSV)")
+        .arg(Arg::new("cohort1").long("cohort1").required_unless_present("cohort"))
+        .arg(Arg::new("cohort2").long("cohort2").required_unless_present("cohort"))
+        .arg(
+            Arg::new("cohort")
+                .long("cohort")
+                .action(ArgAction::Append)
+                .conflicts_with_all(["cohort1", "cohort2"])
+                .help("Cohort file (repeat for each cohort); writes a TSV table with one column per cohort"),
+        )
         .arg(Arg::new("output").long("output").required(true))
-        .arg(Arg::new("hpo").long("hpo").required(true))
+        .arg(Arg::new("hpo").long("hpo"))
         .arg(
             Arg::new("threshold")
                 .long("threshold")
@@ -20,20 +27,37 @@ pub fn command() -> clap::Command {
 }
 
 /// Handler for the subcommand
-#[cfg(feature = "excel_export")]
 pub fn handle(sub_matches: &ArgMatches) -> Result<(), Box<dyn std::error::Error>> {
+    let Some(cohorts) = sub_matches.get_many::<String>("cohort") else {
+        return handle_excel(sub_matches);
+    };
+    let cohort_paths: Vec<&str> = cohorts.map(|c| c.as_str()).collect();
+    if cohort_paths.len() < 2 {
+        return Err("At least two cohorts (--cohort) are required".into());
+    }
+    let output = sub_matches.get_one::<String>("output").unwrap();
+    let hpo_path = crate::config::get().hpo_path(sub_matches)?;
+    let threshold: usize = sub_matches.get_one::<String>("threshold").unwrap().parse()?;
+    let hpo = crate::load_hpo(&hpo_path)?;
+    ga4ghphetools::export::compare_multiple_cohorts(&cohort_paths, output, hpo, threshold)?;
+    println!("Wrote comparison of {} cohorts to {}", cohort_paths.len(), output);
+    Ok(())
+}
+
+#[cfg(feature = "excel_export")]
+fn handle_excel(sub_matches: &ArgMatches) -> Result<(), Box<dyn std::error::Error>> {
     let cohort_1 = sub_matches.get_one::<String>("cohort1").unwrap();
     let cohort_2 = sub_matches.get_one::<String>("cohort2").unwrap();
     let output = sub_matches.get_one::<String>("output").unwrap();
-    let hpo_path = sub_matches.get_one::<String>("hpo").unwrap();
+    let hpo_path = crate::config::get().hpo_path(sub_matches)?;
     let threshold: usize = sub_matches.get_one::<String>("threshold").unwrap().parse()?;
-    let hpo = crate::load_hpo(hpo_path)?;
+    let hpo = crate::load_hpo(&hpo_path)?;
 
     output_excel_comparison(cohort_1, cohort_2, output, hpo, threshold).map_err(|e| e.into())
 }
 
 #[cfg(not(feature = "excel_export"))]
-pub fn handle(_sub_matches: &ArgMatches) -> Result<(), Box<dyn std::error::Error>> {
+fn handle_excel(_sub_matches: &ArgMatches) -> Result<(), Box<dyn std::error::Error>> {
     eprintln!("This binary was built without the `excel_export` feature");
     Ok(())
 }
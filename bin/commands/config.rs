@@ -0,0 +1,38 @@
+use clap::{Arg, ArgAction, ArgMatches};
+
+use crate::config::PhetoolsConfig;
+
+
+/// Returns the `clap::Command` for this subcommand
+pub fn command() -> clap::Command {
+    clap::Command::new("config")
+        .about("Show or change the settings in phetools.toml")
+        .arg(
+            Arg::new("set")
+                .long("set")
+                .action(ArgAction::Append)
+                .help("Set a value, e.g., hpo_path=/path/to/hp.json (keys: hpo_path, orcid, default_output_dir, log_level)"),
+        )
+}
+
+/// Handler for the subcommand
+pub fn handle(sub_matches: &ArgMatches) -> Result<(), Box<dyn std::error::Error>> {
+    let path = PhetoolsConfig::config_file_path();
+    let mut config = if path.is_file() {
+        PhetoolsConfig::from_file(&path)?
+    } else {
+        PhetoolsConfig::default()
+    };
+    let assignments: Vec<&String> = sub_matches.get_many::<String>("set").unwrap_or_default().collect();
+    if assignments.is_empty() {
+        println!("# {}", path.display());
+        print!("{}", toml::to_string_pretty(&config)?);
+        return Ok(());
+    }
+    for assignment in assignments {
+        config.set(assignment)?;
+    }
+    config.write(&path)?;
+    println!("Updated {}", path.display());
+    Ok(())
+}
@@ -0,0 +1,40 @@
+use std::path::PathBuf;
+
+use clap::{Arg, ArgAction, ArgMatches};
+
+
+/// Returns the `clap::Command` for this subcommand
+pub fn command() -> clap::Command {
+    clap::Command::new("dashboard")
+        .about("Create an HTML dashboard with the cohorts and Q/C status of a repository")
+        .arg(
+            Arg::new("store")
+                .short('s')
+                .long("store")
+                .required(true)
+                .help("Root directory of the repository (one subdirectory per cohort)"),
+        )
+        .arg(Arg::new("hpo").long("hpo"))
+        .arg(Arg::new("output").short('o').long("output").required(true))
+        .arg(
+            Arg::new("open")
+                .long("open")
+                .action(ArgAction::SetTrue)
+                .help("Open the dashboard in the default browser"),
+        )
+}
+
+/// Handler for the subcommand
+pub fn handle(sub_matches: &ArgMatches) -> Result<(), Box<dyn std::error::Error>> {
+    let store = PathBuf::from(sub_matches.get_one::<String>("store").unwrap());
+    let hpo_path = crate::config::get().hpo_path(sub_matches)?;
+    let output_path = crate::config::get().output_path(sub_matches.get_one::<String>("output").unwrap());
+    let hpo = crate::load_hpo(&hpo_path)?;
+    let html = ga4ghphetools::repo::generate_repo_dashboard(&store, hpo)?;
+    std::fs::write(&output_path, html)?;
+    println!("Wrote dashboard for {} to {}", store.display(), output_path.display());
+    if sub_matches.get_flag("open") {
+        open::that(&output_path)?;
+    }
+    Ok(())
+}
@@ -0,0 +1,41 @@
+use std::path::PathBuf;
+
+use clap::{Arg, ArgMatches};
+
+use crate::commands::util::{get_output_format, output_format_arg, OutputFormat};
+
+
+/// Returns the `clap::Command` for this subcommand
+pub fn command() -> clap::Command {
+    clap::Command::new("disease-terms")
+        .about("List the HPO terms annotated to a disease in an HPOA file")
+        .arg(Arg::new("disease").short('d').long("disease").required(true).help("Disease id, e.g., OMIM:154700"))
+        .arg(Arg::new("hpoa").long("hpoa").required(true).help("HPOA file, e.g., phenotype.hpoa"))
+        .arg(Arg::new("hpo").long("hpo"))
+        .arg(output_format_arg("text"))
+}
+
+/// Handler for the subcommand
+pub fn handle(sub_matches: &ArgMatches) -> Result<(), Box<dyn std::error::Error>> {
+    let disease_id = sub_matches.get_one::<String>("disease").unwrap();
+    let hpoa_path = PathBuf::from(sub_matches.get_one::<String>("hpoa").unwrap());
+    let hpo_path = crate::config::get().hpo_path(sub_matches)?;
+    let hpo = crate::load_hpo(&hpo_path)?;
+    let terms = ga4ghphetools::hpo::get_hpo_terms_for_disease(hpo, disease_id, &hpoa_path)?;
+    match get_output_format(sub_matches) {
+        OutputFormat::Json => println!("{}", serde_json::to_string_pretty(&terms)?),
+        OutputFormat::Tsv => {
+            println!("hpo_id\thpo_label");
+            for term in &terms {
+                println!("{}\t{}", term.hpo_id(), term.hpo_label());
+            }
+        }
+        OutputFormat::Text => {
+            for term in &terms {
+                println!("{}\t{}", term.hpo_id(), term.hpo_label());
+            }
+            println!("{} HPO terms annotated to {}", terms.len(), disease_id);
+        }
+    }
+    Ok(())
+}
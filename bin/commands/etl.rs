@@ -1,19 +1,32 @@
-use clap::{Arg, ArgMatches};
-use ga4ghphetools::dto::etl_dto::EtlDto;
+use std::{path::Path, sync::Arc};
+
+use clap::{Arg, ArgAction, ArgMatches};
+use ga4ghphetools::{dto::{cohort_dto::CohortData, etl_dto::{EtlCellStatus, EtlColumnType, EtlDto}}, error::PheToolsError, etl::FailureMode};
+use ontolius::ontology::csr::FullCsrOntology;
+
+use crate::commands::util::{get_output_format, output_format_arg, OutputFormat, Progress};
 
 
 pub fn command() -> clap::Command {
     clap::Command::new("etl")
         .about("Test converting an EtlDto to CohortData")
         .arg(Arg::new("input").short('i').long("input").required(true))
-        .arg(Arg::new("hpo").short('o').long("hpo").required(true))
+        .arg(Arg::new("hpo").short('o').long("hpo"))
+        .arg(Arg::new("collect-all")
+            .long("collect-all")
+            .action(ArgAction::SetTrue)
+            .help("Report all errors of the table instead of stopping at the first one"))
+        .arg(Arg::new("checkpoint")
+            .long("checkpoint")
+            .help("Validate the variant columns first, saving the progress to this file; resume from it if it exists"))
+        .arg(output_format_arg("json"))
 }
 
 /// Handler for the subcommand
 pub fn handle(sub_matches: &ArgMatches) -> Result<(), Box<dyn std::error::Error>> {
     let input = sub_matches.get_one::<String>("input").unwrap();
-    let hpo_path = sub_matches.get_one::<String>("hpo").unwrap();
-    let hpo = crate::load_hpo(hpo_path)?;
+    let hpo_path = crate::config::get().hpo_path(sub_matches)?;
+    let hpo = crate::load_hpo(&hpo_path)?;
 
     let contents = std::fs::read_to_string(input)
         .map_err(|e| format!("Failed to read file: {}", e))?;
@@ -21,9 +34,87 @@ pub fn handle(sub_matches: &ArgMatches) -> Result<(), Box<dyn std::error::Error>
     let dto: EtlDto = serde_json::from_str(&contents)
         .map_err(|e| format!("Failed to deserialize JSON: {}", e))?;
 
-    let cohort = ga4ghphetools::etl::get_cohort_data_from_etl_dto(hpo.clone(), dto)?;
-    let json = serde_json::to_string_pretty(&cohort)?;
-    println!("{}", json);
+    let dto = match sub_matches.get_one::<String>("checkpoint") {
+        Some(checkpoint) => validate_variants_with_checkpoint(hpo.clone(), dto, Path::new(checkpoint))?,
+        None => dto,
+    };
+    let failure_mode = if sub_matches.get_flag("collect-all") {
+        FailureMode::CollectAll
+    } else {
+        FailureMode::FailFast
+    };
+    let cohort = match ga4ghphetools::etl::get_cohort_data_from_etl_dto(hpo.clone(), dto, failure_mode) {
+        Ok(cohort) => cohort,
+        Err(PheToolsError::EtlQcFailed(errors)) => {
+            for e in &errors {
+                eprintln!("{e}");
+            }
+            return Err(format!("Could not convert {input}: {} errors", errors.len()).into());
+        }
+        Err(e) => return Err(e.into()),
+    };
+    match get_output_format(sub_matches) {
+        OutputFormat::Json => println!("{}", serde_json::to_string_pretty(&cohort)?),
+        OutputFormat::Tsv => print!("{}", cohort_to_tsv(&cohort)),
+        OutputFormat::Text => println!("Converted {} individuals with {} HPO terms and {} variants",
+            cohort.rows.len(),
+            cohort.hpo_headers.len(),
+            cohort.hgvs_variants.len() + cohort.structural_variants.len() + cohort.intergenic_variants.len()),
+    }
 
     Ok(())
 }
+
+/// Validate the alleles of all Variant columns that have cells that were not transformed yet. The table is saved to the
+/// checkpoint after each batch of alleles; if the checkpoint exists, we resume from it instead of the input table, so
+/// that variants that were validated before an interruption are not validated again.
+fn validate_variants_with_checkpoint(
+    hpo: Arc<FullCsrOntology>,
+    input_dto: EtlDto,
+    checkpoint: &Path,
+) -> Result<EtlDto, Box<dyn std::error::Error>> {
+    let mut dto = if checkpoint.exists() {
+        eprintln!("Resuming from checkpoint {}", checkpoint.display());
+        ga4ghphetools::etl::load_etl_checkpoint(hpo.clone(), checkpoint)?
+    } else {
+        input_dto
+    };
+    let variant_columns: Vec<usize> = dto.table.columns
+        .iter()
+        .enumerate()
+        .filter(|(_, col)| col.header.column_type == EtlColumnType::Variant)
+        .filter(|(_, col)| col.values.iter().any(|cell| cell.status != EtlCellStatus::Transformed))
+        .map(|(i, _)| i)
+        .collect();
+    for column in variant_columns {
+        let progress = Progress::new(&format!("Validating {}", dto.table.columns[column].header.original));
+        dto = ga4ghphetools::etl::process_allele_column_with_checkpoint(
+            hpo.clone(), dto, column, checkpoint, |p, q| progress.update(p, q))?;
+        progress.finish();
+    }
+    Ok(dto)
+}
+
+/// One line per individual with the demographic data followed by one column per HPO term
+fn cohort_to_tsv(cohort: &CohortData) -> String {
+    let mut header: Vec<String> = ["pmid", "individual_id", "age_of_onset", "age_at_last_encounter", "deceased", "sex"]
+        .iter()
+        .map(|h| h.to_string())
+        .collect();
+    header.extend(cohort.hpo_headers.iter().map(|duplet| duplet.hpo_id().to_string()));
+    let mut lines = vec![header.join("\t")];
+    for row in &cohort.rows {
+        let individual = &row.individual_data;
+        let mut fields = vec![
+            individual.pmid.clone(),
+            individual.individual_id.clone(),
+            individual.age_of_onset.clone(),
+            individual.age_at_last_encounter.clone(),
+            individual.deceased.clone(),
+            individual.sex.clone(),
+        ];
+        fields.extend(row.hpo_data.iter().map(|cell| cell.to_string()));
+        lines.push(fields.join("\t"));
+    }
+    lines.join("\n") + "\n"
+}
@@ -1,15 +1,17 @@
-use std::{fs, io::{self, Write}, path::{Path, PathBuf}, sync::Arc};
+use std::{fs, path::{Path, PathBuf}, sync::Arc};
 
 use clap::{Arg, ArgMatches, value_parser};
 use ontolius::ontology::csr::FullCsrOntology;
 
+use crate::commands::util::{extract_file_name, Progress};
+
 
 pub fn command() -> clap::Command {
     clap::Command::new("extract")
         .about("Extract phenopackets from Cohort files")
         .arg(Arg::new("input").short('i').long("input").required(true).value_parser(value_parser!(PathBuf)))
         .arg(Arg::new("output").short('o').long("output").required(true).value_parser(value_parser!(PathBuf)))
-        .arg(clap::Arg::new("hpo").long("hpo").required(true).value_parser(value_parser!(PathBuf)))
+        .arg(clap::Arg::new("hpo").long("hpo"))
 }
 
 
@@ -21,8 +23,8 @@ pub fn handle(sub_matches: &ArgMatches) -> Result<(), Box<dyn std::error::Error>
         eprintln!("[ERROR] '{}' is not a directory. Pass an existing directory with the -o/--output argument.", output.to_string_lossy());
         return Ok(());
     }
-    let hpo_path = sub_matches.get_one::<PathBuf>("hpo").unwrap();
-    let hpo = crate::load_hpo(&hpo_path.to_string_lossy())?;
+    let hpo_path = crate::config::get().hpo_path(sub_matches)?;
+    let hpo = crate::load_hpo(&hpo_path)?;
     let path = Path::new(input);
     if path.is_dir() {
         process_cohort_dir(path, output, hpo.clone())?;
@@ -39,27 +41,34 @@ pub fn handle(sub_matches: &ArgMatches) -> Result<(), Box<dyn std::error::Error>
 
 fn process_cohort_file(input_file: &PathBuf, output_dir: &Path, hpo: Arc<FullCsrOntology>) -> Result<usize, String> {
     let cohort = ga4ghphetools::factory::load_json_cohort(&input_file.to_string_lossy()).expect("Could not load Cohort JSON file");
+    // the curation history stores the bare ORCID identifier
     let orcid = cohort.get_latest_biocurator_id()?;
+    let orcid = if orcid.starts_with("ORCID:") { orcid } else { format!("ORCID:{orcid}") };
     let overwrite = true;
-    let n_processed = ga4ghphetools::ppkt::write_phenopackets(cohort, output_dir.to_path_buf(), orcid, hpo.clone(), overwrite).map_err(|e|e.to_string())?;
+    let progress = Progress::new(&extract_file_name(&input_file.to_string_lossy()));
+    let n_processed = ga4ghphetools::ppkt::write_phenopackets_with_progress(
+        cohort, 
+        output_dir.to_path_buf(), 
+        orcid, 
+        hpo.clone(), 
+        overwrite,
+        |p, q| progress.update(p, q)).map_err(|e|e.to_string())?;
+    progress.finish();
     Ok(n_processed)
 }
 
 fn process_cohort_dir(input_dir: &Path, output_dir: &Path, hpo: Arc<FullCsrOntology>) -> Result<(), String> {
     let entries = fs::read_dir(input_dir).map_err(|e|e.to_string())?;
     let mut total_processed = 0 as usize;
-    print!("\rProcessed {} phenopackets...", total_processed);
-            io::stdout().flush().map_err(|e| e.to_string())?;
     for entry in entries.flatten() {
         let path = entry.path();
         if path.extension().and_then(|s| s.to_str()) == Some("json") {
             // If one file fails, we stop and return the error
             let n = process_cohort_file(&path, output_dir, hpo.clone())?;
             total_processed += n;
-            print!("\rProcessed {} phenopackets.", total_processed);
-            io::stdout().flush().map_err(|e| e.to_string())?;
         }
     }
+    println!("Processed {} phenopackets.", total_processed);
 
     Ok(())
 }
\ No newline at end of file
@@ -0,0 +1,48 @@
+use clap::{Arg, ArgAction, ArgMatches};
+
+
+/// Returns the `clap::Command` for this subcommand
+pub fn command() -> clap::Command {
+    clap::Command::new("family")
+        .about("Export a proband and their parents as a GA4GH Family message")
+        .arg(Arg::new("input").short('i').long("input").required(true))
+        .arg(
+            Arg::new("proband")
+                .short('p')
+                .long("proband")
+                .required(true)
+                .help("Individual id of the proband"),
+        )
+        .arg(
+            Arg::new("parent")
+                .long("parent")
+                .action(ArgAction::Append)
+                .help("Individual id of a parent (may be given twice)"),
+        )
+        .arg(Arg::new("hpo").long("hpo"))
+        .arg(
+            Arg::new("orcid")
+                .long("orcid")
+                .help("ORCID of the biocurator (default: orcid in phetools.toml)"),
+        )
+        .arg(Arg::new("output").short('o').long("output").required(true))
+}
+
+/// Handler for the subcommand
+pub fn handle(sub_matches: &ArgMatches) -> Result<(), Box<dyn std::error::Error>> {
+    let input = sub_matches.get_one::<String>("input").unwrap();
+    let proband = sub_matches.get_one::<String>("proband").unwrap();
+    let parents: Vec<String> = sub_matches
+        .get_many::<String>("parent")
+        .map(|values| values.cloned().collect())
+        .unwrap_or_default();
+    let hpo_path = crate::config::get().hpo_path(sub_matches)?;
+    let orcid = crate::config::get().orcid(sub_matches, "orcid")?;
+    let output_path = crate::config::get().output_path(sub_matches.get_one::<String>("output").unwrap());
+    let hpo = crate::load_hpo(&hpo_path)?;
+    let cohort = ga4ghphetools::factory::load_json_cohort(input)?;
+    let family = ga4ghphetools::ppkt::export_as_family(proband, &parents, &cohort, hpo, &orcid)?;
+    ga4ghphetools::ppkt::write_family_phenopacket(&family, &output_path)?;
+    println!("Wrote family {} ({} relatives) to {}", family.id, family.relatives.len(), output_path.display());
+    Ok(())
+}
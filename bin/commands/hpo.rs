@@ -0,0 +1,67 @@
+use clap::{Arg, ArgGroup, ArgMatches};
+use ga4ghphetools::hpo::HpoTermInfo;
+
+
+/// Returns the `clap::Command` for this subcommand
+pub fn command() -> clap::Command {
+    clap::Command::new("hpo")
+        .about("Look up HPO terms by id, label, or synonym")
+        .arg(Arg::new("hpo").long("hpo"))
+        .arg(Arg::new("search").short('s').long("search").help("Beginning of a label or synonym"))
+        .arg(Arg::new("id").long("id").help("HPO id, e.g., HP:0001166"))
+        .arg(Arg::new("synonyms").long("synonyms").help("List the synonyms of this HPO id"))
+        .group(
+            ArgGroup::new("query")
+                .args(["search", "id", "synonyms"])
+                .required(true),
+        )
+        .arg(
+            Arg::new("format")
+                .long("format")
+                .value_parser(["text", "json"])
+                .default_value("text"),
+        )
+}
+
+/// Handler for the subcommand
+pub fn handle(sub_matches: &ArgMatches) -> Result<(), Box<dyn std::error::Error>> {
+    let hpo_path = crate::config::get().hpo_path(sub_matches)?;
+    let json = sub_matches.get_one::<String>("format").unwrap() == "json";
+    let hpo = crate::load_hpo(&hpo_path)?;
+    if let Some(query) = sub_matches.get_one::<String>("search") {
+        let terms = ga4ghphetools::hpo::search_hpo_terms(hpo, query);
+        if json {
+            println!("{}", serde_json::to_string_pretty(&terms)?);
+        } else {
+            for term in &terms {
+                print_term(term);
+            }
+            println!("{} matching terms", terms.len());
+        }
+    } else if let Some(hpo_id) = sub_matches.get_one::<String>("id") {
+        let term = ga4ghphetools::hpo::get_hpo_term_info(hpo, hpo_id)?;
+        if json {
+            println!("{}", serde_json::to_string_pretty(&term)?);
+        } else {
+            print_term(&term);
+        }
+    } else if let Some(hpo_id) = sub_matches.get_one::<String>("synonyms") {
+        let term = ga4ghphetools::hpo::get_hpo_term_info(hpo, hpo_id)?;
+        if json {
+            println!("{}", serde_json::to_string_pretty(&term.synonyms)?);
+        } else {
+            for synonym in &term.synonyms {
+                println!("{synonym}");
+            }
+        }
+    }
+    Ok(())
+}
+
+fn print_term(term: &HpoTermInfo) {
+    if term.synonyms.is_empty() {
+        println!("{}\t{}", term.hpo_id, term.label);
+    } else {
+        println!("{}\t{}\t{}", term.hpo_id, term.label, term.synonyms.join("; "));
+    }
+}
@@ -0,0 +1,81 @@
+use std::io::{self, Write};
+
+use clap::{Arg, ArgAction, ArgMatches};
+use ga4ghphetools::{dto::orcid::validate_orcid, hpoa::HpoaExportSummary};
+
+use crate::commands::util::{get_output_format, output_format_arg, OutputFormat};
+
+
+/// Returns the `clap::Command` for this subcommand
+pub fn command() -> clap::Command {
+    clap::Command::new("hpoa")
+        .about("Write the HPO annotations (HPOA format) of a Mendelian cohort")
+        .arg(Arg::new("input").short('i').long("input").required(true))
+        .arg(Arg::new("hpo").long("hpo"))
+        .arg(
+            Arg::new("biocurator")
+                .short('b')
+                .long("biocurator")
+                .help("ORCID of the biocurator, e.g., ORCID:0000-0002-0736-9199 (default: orcid in phetools.toml)"),
+        )
+        .arg(Arg::new("output").short('o').long("output").required(true))
+        .arg(
+            Arg::new("append")
+                .long("append")
+                .action(ArgAction::SetTrue)
+                .help("Append the annotations to an existing file"),
+        )
+        .arg(
+            Arg::new("force")
+                .short('f')
+                .long("force")
+                .action(ArgAction::SetTrue)
+                .help("Overwrite an existing output file without asking"),
+        )
+        .arg(output_format_arg("text"))
+}
+
+/// Handler for the subcommand
+pub fn handle(sub_matches: &ArgMatches) -> Result<(), Box<dyn std::error::Error>> {
+    let input = sub_matches.get_one::<String>("input").unwrap();
+    let hpo_path = crate::config::get().hpo_path(sub_matches)?;
+    let biocurator = crate::config::get().orcid(sub_matches, "biocurator")?;
+    let output_path = crate::config::get().output_path(sub_matches.get_one::<String>("output").unwrap());
+    let output = output_path.to_string_lossy().to_string();
+    let append = sub_matches.get_flag("append");
+    let force = sub_matches.get_flag("force");
+    let biocurator = validate_orcid(&biocurator)?;
+    let exists = output_path.exists();
+    if exists && !append && !force && !confirm_overwrite(&output)? {
+        println!("Not overwriting {output}");
+        return Ok(());
+    }
+    let hpo = crate::load_hpo(&hpo_path)?;
+    let cohort = ga4ghphetools::factory::load_json_cohort(input)?;
+    let n_annotations = ga4ghphetools::hpoa::write_hpoa_tsv(cohort, hpo, &biocurator, &output_path, append)?;
+    let summary = HpoaExportSummary {
+        output_path: output.clone(),
+        n_annotations,
+        appended: append,
+    };
+    match get_output_format(sub_matches) {
+        OutputFormat::Json => println!("{}", serde_json::to_string_pretty(&summary)?),
+        OutputFormat::Tsv => {
+            println!("output_path\tn_annotations\tappended");
+            println!("{}\t{}\t{}", summary.output_path, summary.n_annotations, summary.appended);
+        }
+        OutputFormat::Text => {
+            let action = if append { "Appended" } else { "Wrote" };
+            println!("{action} {} disease-HPO annotations to {output}", summary.n_annotations);
+        }
+    }
+    Ok(())
+}
+
+fn confirm_overwrite(output: &str) -> Result<bool, io::Error> {
+    print!("{output} already exists. Overwrite? [y/N] ");
+    io::stdout().flush()?;
+    let mut answer = String::new();
+    io::stdin().read_line(&mut answer)?;
+    Ok(matches!(answer.trim().to_ascii_lowercase().as_str(), "y" | "yes"))
+}
@@ -0,0 +1,39 @@
+use std::path::PathBuf;
+
+use clap::{Arg, ArgMatches};
+
+use crate::commands::util::{get_output_format, output_format_arg, OutputFormat};
+
+
+/// Returns the `clap::Command` for this subcommand
+pub fn command() -> clap::Command {
+    clap::Command::new("hpoa-diff")
+        .about("Compare two versions of an HPOA file, e.g., before and after an HPO release")
+        .arg(Arg::new("old").long("old").required(true).help("Previous version of the HPOA file"))
+        .arg(Arg::new("new").long("new").required(true).help("Current version of the HPOA file"))
+        .arg(output_format_arg("text"))
+}
+
+/// Handler for the subcommand
+pub fn handle(sub_matches: &ArgMatches) -> Result<(), Box<dyn std::error::Error>> {
+    let old_path = PathBuf::from(sub_matches.get_one::<String>("old").unwrap());
+    let new_path = PathBuf::from(sub_matches.get_one::<String>("new").unwrap());
+    let diff = ga4ghphetools::hpoa::diff_hpoa_files(&old_path, &new_path)?;
+    match get_output_format(sub_matches) {
+        OutputFormat::Json => println!("{}", serde_json::to_string_pretty(&diff)?),
+        OutputFormat::Tsv => {
+            println!("added_rows\tremoved_rows\tmodified_rows");
+            println!("{}\t{}\t{}", diff.added_rows, diff.removed_rows, diff.modified_rows.len());
+        }
+        OutputFormat::Text => {
+            println!("Added rows: {}", diff.added_rows);
+            println!("Removed rows: {}", diff.removed_rows);
+            println!("Modified rows: {}", diff.modified_rows.len());
+            for (old, new) in &diff.modified_rows {
+                println!("- {old}");
+                println!("+ {new}");
+            }
+        }
+    }
+    Ok(())
+}
@@ -0,0 +1,45 @@
+use std::path::Path;
+
+use clap::{Arg, ArgAction, ArgMatches};
+
+
+/// Returns the `clap::Command` for this subcommand
+pub fn command() -> clap::Command {
+    clap::Command::new("html")
+        .about("Create an HTML report for a cohort")
+        .arg(Arg::new("input").short('i').long("input").required(true))
+        .arg(Arg::new("hpo").long("hpo"))
+        .arg(Arg::new("output").short('o').long("output").required(true))
+        .arg(
+            Arg::new("template")
+                .short('t')
+                .long("template")
+                .help("Custom Tera template to use instead of the bundled report template"),
+        )
+        .arg(
+            Arg::new("open")
+                .long("open")
+                .action(ArgAction::SetTrue)
+                .help("Open the report in the default browser"),
+        )
+}
+
+/// Handler for the subcommand
+pub fn handle(sub_matches: &ArgMatches) -> Result<(), Box<dyn std::error::Error>> {
+    let input = sub_matches.get_one::<String>("input").unwrap();
+    let hpo_path = crate::config::get().hpo_path(sub_matches)?;
+    let output_path = crate::config::get().output_path(sub_matches.get_one::<String>("output").unwrap());
+    let template = sub_matches.get_one::<String>("template");
+    let hpo = crate::load_hpo(&hpo_path)?;
+    let cohort = ga4ghphetools::factory::load_json_cohort(input)?;
+    let n_individuals = cohort.rows.len();
+    match template {
+        Some(template) => ga4ghphetools::export::render_html_with_template(cohort, hpo, Path::new(template), &output_path)?,
+        None => ga4ghphetools::export::render_html(cohort, hpo, &output_path)?,
+    }
+    println!("Wrote HTML report with {n_individuals} individuals to {}", output_path.display());
+    if sub_matches.get_flag("open") {
+        open::that(&output_path)?;
+    }
+    Ok(())
+}
@@ -1,25 +1,73 @@
-use crate::commands::util::extract_file_name;
+use std::io::IsTerminal;
+
+use crate::commands::util::{extract_file_name, get_output_format, output_format_arg, OutputFormat};
 
 use clap::ArgMatches;
+use ga4ghphetools::dto::validation_errors::{QcSeverity, QcViolation};
+
+const ANSI_RED: &str = "\x1b[31m";
+const ANSI_YELLOW: &str = "\x1b[33m";
+const ANSI_RESET: &str = "\x1b[0m";
 
 pub fn command() -> clap::Command {
     clap::Command::new("json")
         .about("Q/C Cohort JSON file")
-        .arg(clap::Arg::new("cohort").short('c').long("cohort"))
-        .arg(clap::Arg::new("hpo").short('o').long("hpo").required(true))
+        .arg(clap::Arg::new("cohort").short('c').long("cohort").required(true))
+        .arg(clap::Arg::new("hpo").short('o').long("hpo"))
+        .arg(output_format_arg("text"))
 }
 
 pub fn handle(sub_matches: &ArgMatches) -> Result<(), Box<dyn std::error::Error>> {
-    let json_input_path = sub_matches.get_one::<String>("input").expect("Could not read JSON input");
-    let hpo_path = sub_matches.get_one::<String>("hpo").expect("Could not retrieve hp.json path");
-    let hpo = crate::load_hpo(hpo_path).expect("Could not construct HPO ontology");
+    let json_input_path = sub_matches.get_one::<String>("cohort").expect("Could not read JSON input");
+    let hpo_path = crate::config::get().hpo_path(sub_matches)?;
+    let hpo = crate::load_hpo(&hpo_path).expect("Could not construct HPO ontology");
     let cohort = ga4ghphetools::factory::load_json_cohort(json_input_path).expect("Could not load Cohort JSON file");
     let cohort_file_name = extract_file_name(json_input_path);
-    match ga4ghphetools::factory::qc_assessment(hpo, &cohort) {
-        Ok(_) => println!("No Q/C issues identified for {cohort_file_name}."),
-        Err(e) => eprint!("Error for {cohort_file_name}: {e}"),
+    let report = ga4ghphetools::factory::qc_report(hpo, &cohort, &cohort_file_name);
+    match get_output_format(sub_matches) {
+        OutputFormat::Json => println!("{}", serde_json::to_string_pretty(&report)?),
+        OutputFormat::Tsv => {
+            println!("cohort\tpassed\terror");
+            println!("{}\t{}\t{}", report.cohort, report.passed, report.error.as_deref().unwrap_or(""));
+        }
+        OutputFormat::Text => {
+            if report.violations.is_empty() {
+                println!("No Q/C issues identified for {cohort_file_name}.");
+            }
+            let use_color = std::io::stderr().is_terminal();
+            for violation in &report.violations {
+                eprintln!("{}", format_violation(violation, &cohort_file_name, use_color));
+            }
+        }
     }
     
     Ok(())
 }
 
+/// Errors are shown in red and warnings in yellow if `use_color` is true (i.e., if the output is a terminal)
+fn format_violation(violation: &QcViolation, cohort_file_name: &str, use_color: bool) -> String {
+    let line = format!("{} for {cohort_file_name}: {}", violation.severity, violation.message);
+    if !use_color {
+        return line;
+    }
+    let color = match violation.severity {
+        QcSeverity::Error => ANSI_RED,
+        QcSeverity::Warning => ANSI_YELLOW,
+    };
+    format!("{color}{line}{ANSI_RESET}")
+}
+
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_format_violation() {
+        let warning = QcViolation::warning("redundant annotation");
+        assert_eq!("Warning for cohort.json: redundant annotation", format_violation(&warning, "cohort.json", false));
+        assert_eq!("\x1b[33mWarning for cohort.json: redundant annotation\x1b[0m", format_violation(&warning, "cohort.json", true));
+        let error = QcViolation::error("conflict");
+        assert_eq!("\x1b[31mError for cohort.json: conflict\x1b[0m", format_violation(&error, "cohort.json", true));
+    }
+}
@@ -1,6 +1,21 @@
+pub mod batchhpoa;
 pub mod compare;
+pub mod config;
+pub mod dashboard;
+pub mod diseaseterms;
 pub mod etl;
 pub mod extract;
+pub mod family;
+pub mod hpo;
+pub mod hpoa;
+pub mod hpoadiff;
+pub mod html;
 pub mod json;
+pub mod overlap;
 pub mod removeterm;
+pub mod report;
+pub mod repoqc;
+pub mod repostats;
+pub mod stats;
+pub mod validate;
 mod util;
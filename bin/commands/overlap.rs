@@ -0,0 +1,38 @@
+use std::{fs::File, io::{BufWriter, Write}, path::PathBuf};
+
+use clap::{Arg, ArgMatches};
+
+
+/// Returns the `clap::Command` for this subcommand
+pub fn command() -> clap::Command {
+    clap::Command::new("overlap")
+        .about("Write the observed HPO terms shared by each pair of diseases of a repository")
+        .arg(
+            Arg::new("store")
+                .short('s')
+                .long("store")
+                .required(true)
+                .help("Root directory of the repository (all *_individuals.json files are included)"),
+        )
+        .arg(Arg::new("hpo").long("hpo"))
+        .arg(Arg::new("output").short('o').long("output").help("TSV file (default: standard output)"))
+}
+
+/// Handler for the subcommand
+pub fn handle(sub_matches: &ArgMatches) -> Result<(), Box<dyn std::error::Error>> {
+    let store = PathBuf::from(sub_matches.get_one::<String>("store").unwrap());
+    let hpo_path = crate::config::get().hpo_path(sub_matches)?;
+    let hpo = crate::load_hpo(&hpo_path)?;
+    let cohorts = ga4ghphetools::repo::load_repository_cohorts(&store)?;
+    let overlap = ga4ghphetools::export::compute_cross_disease_overlap(&cohorts, hpo);
+    let mut writer: Box<dyn Write> = match sub_matches.get_one::<String>("output") {
+        Some(output) => Box::new(BufWriter::new(File::create(crate::config::get().output_path(output))?)),
+        None => Box::new(std::io::stdout().lock()),
+    };
+    writeln!(writer, "{}", ga4ghphetools::export::CrossDiseaseOverlap::tsv_header())?;
+    for row in overlap.to_tsv_rows() {
+        writeln!(writer, "{}", row)?;
+    }
+    writer.flush()?;
+    Ok(())
+}
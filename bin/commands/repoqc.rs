@@ -0,0 +1,43 @@
+use std::path::PathBuf;
+
+use clap::{Arg, ArgAction, ArgMatches};
+use ga4ghphetools::repo::repo_qc::RepoQc;
+
+
+/// Returns the `clap::Command` for this subcommand
+pub fn command() -> clap::Command {
+    clap::Command::new("repo-qc")
+        .about("Q/C of all cohorts and phenopackets of a repository")
+        .arg(
+            Arg::new("store")
+                .short('s')
+                .long("store")
+                .required(true)
+                .help("Root directory of the repository (one subdirectory per cohort)"),
+        )
+        .arg(
+            Arg::new("check-duplicates")
+                .long("check-duplicates")
+                .action(ArgAction::SetTrue)
+                .help("Also list individuals (same PMID and individual id) found in several cohort files"),
+        )
+}
+
+/// Handler for the subcommand
+pub fn handle(sub_matches: &ArgMatches) -> Result<(), Box<dyn std::error::Error>> {
+    let store = PathBuf::from(sub_matches.get_one::<String>("store").unwrap());
+    let repo_qc = ga4ghphetools::repo::get_repo_qc(&store)?;
+    println!("{} cohorts, {} phenopackets", repo_qc.cohort_count, repo_qc.phenopacket_count);
+    for error in &repo_qc.errors {
+        println!("[{}] {}: {}", error.error_type.label(), error.cohort_name, error.message);
+    }
+    println!("{} Q/C error(s)", repo_qc.errors.len());
+    if sub_matches.get_flag("check-duplicates") {
+        let duplicates = RepoQc::find_duplicate_individuals(&store)?;
+        for dup in &duplicates {
+            println!("[Warning] {} ({}) found in: {}", dup.individual_id, dup.pmid, dup.found_in.join(", "));
+        }
+        println!("{} duplicate individual(s)", duplicates.len());
+    }
+    Ok(())
+}
@@ -0,0 +1,33 @@
+use clap::{Arg, ArgMatches};
+
+
+/// Returns the `clap::Command` for this subcommand
+pub fn command() -> clap::Command {
+    clap::Command::new("report")
+        .about("Create a summary report for a cohort")
+        .arg(Arg::new("input").short('i').long("input").required(true))
+        .arg(Arg::new("output").short('o').long("output").required(true))
+        .arg(Arg::new("hpo").long("hpo"))
+        .arg(
+            Arg::new("format")
+                .long("format")
+                .value_parser(["markdown"])
+                .default_value("markdown"),
+        )
+}
+
+/// Handler for the subcommand
+pub fn handle(sub_matches: &ArgMatches) -> Result<(), Box<dyn std::error::Error>> {
+    let input = sub_matches.get_one::<String>("input").unwrap();
+    let output_path = crate::config::get().output_path(sub_matches.get_one::<String>("output").unwrap());
+    let hpo_path = crate::config::get().hpo_path(sub_matches)?;
+    let format = sub_matches.get_one::<String>("format").unwrap();
+    let hpo = crate::load_hpo(&hpo_path)?;
+    let cohort = ga4ghphetools::factory::load_json_cohort(input)?;
+    match format.as_str() {
+        "markdown" => ga4ghphetools::export::export_as_markdown(&cohort, hpo, &output_path)?,
+        other => return Err(format!("Unsupported report format: {other}").into()),
+    }
+    println!("Wrote {format} report to {}", output_path.display());
+    Ok(())
+}
@@ -0,0 +1,33 @@
+use std::{fs::File, io::{BufWriter, Write}, path::PathBuf};
+
+use clap::{Arg, ArgMatches};
+
+
+/// Returns the `clap::Command` for this subcommand
+pub fn command() -> clap::Command {
+    clap::Command::new("repo-stats")
+        .about("Write the number of cohorts, individuals, and HPO terms per disease of a repository")
+        .arg(
+            Arg::new("store")
+                .short('s')
+                .long("store")
+                .required(true)
+                .help("Root directory of the repository (one subdirectory per cohort)"),
+        )
+        .arg(Arg::new("output").short('o').long("output").required(true))
+}
+
+/// Handler for the subcommand
+pub fn handle(sub_matches: &ArgMatches) -> Result<(), Box<dyn std::error::Error>> {
+    let store = PathBuf::from(sub_matches.get_one::<String>("store").unwrap());
+    let output_path = crate::config::get().output_path(sub_matches.get_one::<String>("output").unwrap());
+    let summaries = ga4ghphetools::repo::get_disease_summary(&store)?;
+    let mut writer = BufWriter::new(File::create(&output_path)?);
+    writeln!(writer, "{}", ga4ghphetools::repo::DiseaseSummary::tsv_header())?;
+    for summary in &summaries {
+        writeln!(writer, "{}", summary.to_tsv_row())?;
+    }
+    writer.flush()?;
+    println!("Wrote statistics for {} diseases to {}", summaries.len(), output_path.display());
+    Ok(())
+}
@@ -0,0 +1,91 @@
+use std::path::Path;
+
+use clap::{Arg, ArgMatches};
+
+use crate::commands::util::OutputFormat;
+
+
+/// Output formats of the stats command; `csv` exports the cohort itself as a table for R or Python
+#[derive(Clone, Copy, Debug, PartialEq, Eq, clap::ValueEnum)]
+enum StatsFormat {
+    Text,
+    Json,
+    Tsv,
+    Csv,
+}
+
+/// Returns the `clap::Command` for this subcommand
+pub fn command() -> clap::Command {
+    clap::Command::new("stats")
+        .about("Show summary statistics and the most frequent HPO terms of a cohort")
+        .arg(
+            Arg::new("input")
+                .short('i')
+                .long("input")
+                .short_alias('c')
+                .alias("cohort")
+                .required(true),
+        )
+        .arg(Arg::new("hpo").long("hpo"))
+        .arg(
+            Arg::new("output-format")
+                .long("output-format")
+                .alias("format")
+                .value_parser(clap::value_parser!(StatsFormat))
+                .default_value("text"),
+        )
+        .arg(
+            Arg::new("output")
+                .short('o')
+                .long("output")
+                .required_if_eq("output-format", "csv")
+                .help("CSV file to write (required for --format csv)"),
+        )
+        .arg(
+            Arg::new("top")
+                .long("top")
+                .value_parser(clap::value_parser!(usize))
+                .default_value("10"),
+        )
+        .arg(
+            Arg::new("min-ascertainment")
+                .long("min-ascertainment")
+                .value_parser(clap::value_parser!(usize))
+                .default_value("1"),
+        )
+}
+
+/// Handler for the subcommand
+pub fn handle(sub_matches: &ArgMatches) -> Result<(), Box<dyn std::error::Error>> {
+    let input = sub_matches.get_one::<String>("input").unwrap();
+    let hpo_path = crate::config::get().hpo_path(sub_matches)?;
+    let top = *sub_matches.get_one::<usize>("top").unwrap();
+    let min_ascertainment = *sub_matches.get_one::<usize>("min-ascertainment").unwrap();
+    let hpo = crate::load_hpo(&hpo_path)?;
+    let cohort = ga4ghphetools::factory::load_json_cohort(input)?;
+    ga4ghphetools::hpo::check_hpo_duplets(hpo, &cohort.hpo_headers)?;
+    // CSV exports the cohort itself; the other formats are the shared output formats of the statistics
+    let format = match *sub_matches.get_one::<StatsFormat>("output-format").unwrap() {
+        StatsFormat::Csv => {
+            let output = sub_matches.get_one::<String>("output").unwrap();
+            let n_rows = ga4ghphetools::export::export_cohort_as_csv(&cohort, Path::new(output))?;
+            println!("Wrote {} rows to {}", n_rows, output);
+            return Ok(());
+        }
+        StatsFormat::Text => OutputFormat::Text,
+        StatsFormat::Json => OutputFormat::Json,
+        StatsFormat::Tsv => OutputFormat::Tsv,
+    };
+    let stats = ga4ghphetools::export::compute_cohort_stats(&cohort, top, min_ascertainment);
+    match format {
+        OutputFormat::Json => println!("{}", serde_json::to_string_pretty(&stats)?),
+        OutputFormat::Tsv => {
+            println!("hpo_id\thpo_label\tobserved\tascertained\tpercentage");
+            for term in &stats.top_hpo_terms {
+                println!("{}\t{}\t{}\t{}\t{:.1}", term.hpo_id, term.hpo_label, term.observed, term.ascertained, term.percentage);
+            }
+        }
+        OutputFormat::Text => println!("{}", stats.to_text()),
+    }
+    Ok(())
+}
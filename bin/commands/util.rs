@@ -1,3 +1,4 @@
+use std::io::IsTerminal;
 use std::path::Path;
 
 /// Get the file name at the end of the path
@@ -10,4 +11,89 @@ pub(crate) fn extract_file_name(input_path: &str) -> String {
         }       
     }
    return input_path.to_string();
-}
\ No newline at end of file
+}
+
+/// Output format of subcommands whose results may be consumed by other tools (e.g., jq or awk)
+#[derive(Clone, Copy, Debug, PartialEq, Eq, clap::ValueEnum)]
+pub(crate) enum OutputFormat {
+    Text,
+    Json,
+    Tsv,
+}
+
+/// The `--output-format` argument, shared by all subcommands that support it
+pub(crate) fn output_format_arg(default: &'static str) -> clap::Arg {
+    clap::Arg::new("output-format")
+        .long("output-format")
+        .value_parser(clap::value_parser!(OutputFormat))
+        .default_value(default)
+}
+
+pub(crate) fn get_output_format(sub_matches: &clap::ArgMatches) -> OutputFormat {
+    *sub_matches.get_one::<OutputFormat>("output-format").unwrap()
+}
+
+/// Progress bar (current/total and elapsed time) for long-running steps such as variant validation.
+/// The bar is hidden if stdout is not a terminal, e.g., when the output is piped to a file.
+pub(crate) struct Progress {
+    bar: indicatif::ProgressBar,
+}
+
+impl Progress {
+    pub(crate) fn new(message: &str) -> Self {
+        Self::with_tty(message, std::io::stdout().is_terminal())
+    }
+
+    fn with_tty(message: &str, is_tty: bool) -> Self {
+        if !is_tty {
+            return Self { bar: indicatif::ProgressBar::hidden() };
+        }
+        let bar = indicatif::ProgressBar::new(0);
+        let style = indicatif::ProgressStyle::with_template("{msg} [{bar:40}] {pos}/{len} [{elapsed_precise}]")
+            .unwrap()
+            .progress_chars("=> ");
+        bar.set_style(style);
+        bar.set_message(message.to_string());
+        Self { bar }
+    }
+
+    /// Update the bar with `current` of `total` items; this is the signature of the library's progress callbacks
+    pub(crate) fn update(&self, current: u32, total: u32) {
+        self.bar.set_length(total as u64);
+        self.bar.set_position(current as u64);
+    }
+
+    pub(crate) fn finish(&self) {
+        self.bar.finish();
+    }
+}
+
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_progress_callback() {
+        let progress = Progress::with_tty("Validating", true);
+        let mut n_calls = 0;
+        let mut callback = |current, total| {
+            n_calls += 1;
+            progress.update(current, total);
+        };
+        for i in 1..=5 {
+            callback(i, 5);
+        }
+        assert_eq!(5, n_calls);
+        assert_eq!(5, progress.bar.position());
+        assert_eq!(Some(5), progress.bar.length());
+        progress.finish();
+        assert!(progress.bar.is_finished());
+    }
+
+    #[test]
+    fn test_hidden_without_tty() {
+        let progress = Progress::with_tty("Validating", false);
+        assert!(progress.bar.is_hidden());
+    }
+}
@@ -0,0 +1,45 @@
+use clap::{Arg, ArgMatches};
+
+use crate::commands::util::{extract_file_name, get_output_format, output_format_arg, OutputFormat};
+
+
+/// Returns the `clap::Command` for this subcommand.
+/// `validate` used to be an alias of `json`, so the arguments of `json` (`-c/--cohort`, `-o/--hpo`) are still accepted.
+pub fn command() -> clap::Command {
+    clap::Command::new("validate")
+        .about("Check whether the phenopackets of a cohort can be exported (all Q/C errors and warnings)")
+        .arg(
+            Arg::new("input")
+                .short('i')
+                .long("input")
+                .short_alias('c')
+                .alias("cohort")
+                .required(true)
+                .help("Cohort JSON file"),
+        )
+        .arg(Arg::new("hpo").long("hpo").short_alias('o'))
+        .arg(output_format_arg("text"))
+}
+
+/// Handler for the subcommand. Exits with an error if the cohort is not ready for export.
+pub fn handle(sub_matches: &ArgMatches) -> Result<(), Box<dyn std::error::Error>> {
+    let input = sub_matches.get_one::<String>("input").unwrap();
+    let hpo_path = crate::config::get().hpo_path(sub_matches)?;
+    let hpo = crate::load_hpo(&hpo_path)?;
+    let cohort = ga4ghphetools::factory::load_json_cohort(input)?;
+    let summary = ga4ghphetools::factory::run_full_qc(hpo, &cohort);
+    let cohort_file_name = extract_file_name(input);
+    match get_output_format(sub_matches) {
+        OutputFormat::Json => println!("{}", serde_json::to_string_pretty(&summary)?),
+        OutputFormat::Tsv => {
+            println!("cohort\tindividuals\terrors\twarnings\texport_ready");
+            println!("{}\t{}\t{}\t{}\t{}", cohort_file_name, summary.n_individuals, summary.n_errors(),
+                summary.n_warnings(), summary.is_export_ready());
+        }
+        OutputFormat::Text => print!("{}", summary.to_text_report()),
+    }
+    if !summary.is_export_ready() {
+        return Err(format!("{cohort_file_name} has {} Q/C errors", summary.n_errors()).into());
+    }
+    Ok(())
+}
@@ -0,0 +1,201 @@
+//! Configuration file (phetools.toml)
+//!
+//! Settings that would otherwise have to be passed with every command, such as the path to hp.json.
+//! The file is searched in the current directory and then in `~/.config/phetools/`; the first one found is used.
+//! Command-line arguments always take precedence over the values in the file.
+//!
+//! ```toml
+//! hpo_path = "/path/to/hp.json"
+//! orcid = "ORCID:0000-0002-0736-9199"
+//! default_output_dir = "/path/to/output"
+//! log_level = "info"
+//! ```
+use std::{fs, path::{Path, PathBuf}, sync::OnceLock};
+
+use clap::ArgMatches;
+use serde::{Deserialize, Serialize};
+
+
+const CONFIG_FILE_NAME: &str = "phetools.toml";
+
+static CONFIG: OnceLock<PhetoolsConfig> = OnceLock::new();
+
+
+#[derive(Clone, Debug, Default, Deserialize, Serialize, PartialEq)]
+#[serde(deny_unknown_fields)]
+pub struct PhetoolsConfig {
+    pub hpo_path: Option<String>,
+    pub orcid: Option<String>,
+    pub default_output_dir: Option<String>,
+    pub log_level: Option<String>,
+}
+
+impl PhetoolsConfig {
+    /// Candidate locations of the configuration file, in the order in which they are searched
+    pub fn search_paths() -> Vec<PathBuf> {
+        let mut paths = vec![PathBuf::from(CONFIG_FILE_NAME)];
+        if let Some(home) = home::home_dir() {
+            paths.push(home.join(".config").join("phetools").join(CONFIG_FILE_NAME));
+        }
+        paths
+    }
+
+    /// The existing configuration file, or the location in the user's config directory if there is none
+    pub fn config_file_path() -> PathBuf {
+        let paths = Self::search_paths();
+        paths.iter()
+            .find(|p| p.is_file())
+            .or(paths.last())
+            .cloned()
+            .unwrap_or_else(|| PathBuf::from(CONFIG_FILE_NAME))
+    }
+
+    /// Read the first configuration file that exists, or the default (empty) configuration if there is none
+    pub fn load() -> Result<Self, String> {
+        match Self::search_paths().iter().find(|p| p.is_file()) {
+            Some(path) => Self::from_file(path),
+            None => Ok(Self::default()),
+        }
+    }
+
+    pub fn from_file(path: &Path) -> Result<Self, String> {
+        let contents = fs::read_to_string(path)
+            .map_err(|e| format!("Could not read {}: {}", path.display(), e))?;
+        toml::from_str(&contents)
+            .map_err(|e| format!("Could not parse {}: {}", path.display(), e))
+    }
+
+    pub fn write(&self, path: &Path) -> Result<(), String> {
+        if let Some(parent) = path.parent().filter(|p| !p.as_os_str().is_empty()) {
+            fs::create_dir_all(parent).map_err(|e| e.to_string())?;
+        }
+        let contents = toml::to_string_pretty(self).map_err(|e| e.to_string())?;
+        fs::write(path, contents).map_err(|e| format!("Could not write {}: {}", path.display(), e))
+    }
+
+    /// Set a value from a `key=value` string, as passed to `phetools config --set`
+    pub fn set(&mut self, assignment: &str) -> Result<(), String> {
+        let (key, value) = assignment
+            .split_once('=')
+            .ok_or_else(|| format!("Expected key=value but got '{assignment}'"))?;
+        let value = Some(value.trim().to_string());
+        match key.trim() {
+            "hpo_path" => self.hpo_path = value,
+            "orcid" => self.orcid = value,
+            "default_output_dir" => self.default_output_dir = value,
+            "log_level" => self.log_level = value,
+            other => return Err(format!("Unknown configuration key '{other}' \
+                (valid keys: hpo_path, orcid, default_output_dir, log_level)")),
+        }
+        Ok(())
+    }
+
+    /// The value of the `--hpo` argument, or else `hpo_path` from the configuration file
+    pub fn hpo_path(&self, sub_matches: &ArgMatches) -> Result<String, String> {
+        sub_matches.get_one::<String>("hpo")
+            .or(self.hpo_path.as_ref())
+            .cloned()
+            .ok_or_else(|| "Pass the path to hp.json with --hpo or set hpo_path in phetools.toml".to_string())
+    }
+
+    /// The value of the argument `arg_id`, or else `orcid` from the configuration file
+    pub fn orcid(&self, sub_matches: &ArgMatches, arg_id: &str) -> Result<String, String> {
+        sub_matches.get_one::<String>(arg_id)
+            .or(self.orcid.as_ref())
+            .cloned()
+            .ok_or_else(|| format!("Pass the ORCID with --{arg_id} or set orcid in phetools.toml"))
+    }
+
+    /// A bare file name (e.g., `report.html`) is placed in `default_output_dir`, if configured.
+    /// Paths with a directory component are used as given.
+    pub fn output_path(&self, output: &str) -> PathBuf {
+        let path = PathBuf::from(output);
+        let is_bare_file_name = path.parent().is_none_or(|p| p.as_os_str().is_empty());
+        match &self.default_output_dir {
+            Some(dir) if is_bare_file_name => Path::new(dir).join(path),
+            _ => path,
+        }
+    }
+}
+
+
+/// Install the configuration that was read at startup
+pub fn init(config: PhetoolsConfig) {
+    let _ = CONFIG.set(config);
+}
+
+/// The configuration that was read at startup (the default configuration if [`init`] was not called)
+pub fn get() -> &'static PhetoolsConfig {
+    CONFIG.get_or_init(PhetoolsConfig::default)
+}
+
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use clap::{Arg, Command};
+
+    fn command() -> Command {
+        Command::new("test")
+            .arg(Arg::new("hpo").long("hpo"))
+            .arg(Arg::new("biocurator").long("biocurator"))
+    }
+
+    fn config_from_toml(file_name: &str, contents: &str) -> PhetoolsConfig {
+        let path = std::env::temp_dir().join(file_name);
+        fs::write(&path, contents).unwrap();
+        let config = PhetoolsConfig::from_file(&path);
+        fs::remove_file(&path).unwrap();
+        config.unwrap()
+    }
+
+    #[test]
+    fn test_read_config_file() {
+        let config = config_from_toml("phetools_test_read.toml",
+            "hpo_path = \"/data/hp.json\"\norcid = \"ORCID:0000-0002-0736-9199\"\nlog_level = \"debug\"\n");
+        assert_eq!(Some("/data/hp.json".to_string()), config.hpo_path);
+        assert_eq!(Some("debug".to_string()), config.log_level);
+        assert_eq!(None, config.default_output_dir);
+        let matches = command().get_matches_from(["test"]);
+        assert_eq!("/data/hp.json", config.hpo_path(&matches).unwrap());
+        assert_eq!("ORCID:0000-0002-0736-9199", config.orcid(&matches, "biocurator").unwrap());
+    }
+
+    #[test]
+    fn test_cli_flag_takes_precedence() {
+        let config = config_from_toml("phetools_test_precedence.toml", "hpo_path = \"/data/hp.json\"\n");
+        let matches = command().get_matches_from(["test", "--hpo", "/other/hp.json"]);
+        assert_eq!("/other/hp.json", config.hpo_path(&matches).unwrap());
+    }
+
+    #[test]
+    fn test_missing_value() {
+        let matches = command().get_matches_from(["test"]);
+        assert!(PhetoolsConfig::default().hpo_path(&matches).is_err());
+    }
+
+    #[test]
+    fn test_unknown_key_is_rejected() {
+        let path = std::env::temp_dir().join("phetools_test_unknown.toml");
+        fs::write(&path, "hpo = \"/data/hp.json\"\n").unwrap();
+        let result = PhetoolsConfig::from_file(&path);
+        fs::remove_file(&path).unwrap();
+        assert!(result.is_err());
+    }
+
+    #[test]
+    fn test_set_and_write() {
+        let mut config = PhetoolsConfig::default();
+        config.set("hpo_path=/path/to/hp.json.gz").unwrap();
+        config.set("default_output_dir = out").unwrap();
+        assert!(config.set("hpo").is_err());
+        assert!(config.set("color=blue").is_err());
+        let path = std::env::temp_dir().join("phetools_test_write").join(CONFIG_FILE_NAME);
+        config.write(&path).unwrap();
+        let reread = PhetoolsConfig::from_file(&path).unwrap();
+        fs::remove_dir_all(path.parent().unwrap()).unwrap();
+        assert_eq!(config, reread);
+        assert_eq!(Path::new("out").join("report.html"), reread.output_path("report.html"));
+        assert_eq!(PathBuf::from("results/report.html"), reread.output_path("results/report.html"));
+    }
+}
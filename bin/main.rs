@@ -1,6 +1,7 @@
 mod commands;
+mod config;
 
-use clap::Command;
+use clap::{Arg, ArgAction, Command};
 use ontolius::{io::OntologyLoaderBuilder, ontology::csr::FullCsrOntology};
 use std::sync::Arc;
 
@@ -8,25 +9,89 @@ fn main() -> Result<(), Box<dyn std::error::Error>> {
      let mut cmd = Command::new("phetools")
         .about("GA4GH Phenopacket Schema: phetools")
         .version(env!("CARGO_PKG_VERSION"))
+        .arg(
+            Arg::new("verbose")
+                .short('v')
+                .long("verbose")
+                .action(ArgAction::SetTrue)
+                .global(true)
+                .help("Show debug messages (ETL steps, Variant Validator calls, HPO lookups, Q/C checks)"),
+        )
         .subcommand(commands::extract::command())
+        .subcommand(commands::family::command())
         .subcommand(commands::etl::command())
         .subcommand(commands::compare::command())
         .subcommand(commands::json::command())
-        .subcommand(commands::removeterm::command());
+        .subcommand(commands::validate::command())
+        .subcommand(commands::removeterm::command())
+        .subcommand(commands::report::command())
+        .subcommand(commands::stats::command())
+        .subcommand(commands::hpoa::command())
+        .subcommand(commands::hpoadiff::command())
+        .subcommand(commands::batchhpoa::command())
+        .subcommand(commands::html::command())
+        .subcommand(commands::dashboard::command())
+        .subcommand(commands::repostats::command())
+        .subcommand(commands::repoqc::command())
+        .subcommand(commands::overlap::command())
+        .subcommand(commands::hpo::command())
+        .subcommand(commands::diseaseterms::command())
+        .subcommand(commands::config::command());
 
     let matches = cmd.clone().get_matches();
-    
+    let config = config::PhetoolsConfig::load()?;
+    init_tracing(matches.get_flag("verbose"), config.log_level.as_deref());
+    config::init(config);
+
     match matches.subcommand() {
         Some(("compare", sub_matches)) => commands::compare::handle(sub_matches)?,
         Some(("extract", sub_matches)) => commands::extract::handle(sub_matches)?,
+        Some(("family", sub_matches)) => commands::family::handle(sub_matches)?,
         Some(("etl", sub_matches)) => commands::etl::handle(sub_matches)?,
         Some(("json", sub_matches)) => commands::json::handle(sub_matches)?,
+        Some(("validate", sub_matches)) => commands::validate::handle(sub_matches)?,
         Some(("remove-term", sub_matches)) => commands::removeterm::handle(sub_matches)?,
+        Some(("report", sub_matches)) => commands::report::handle(sub_matches)?,
+        Some(("stats", sub_matches)) => commands::stats::handle(sub_matches)?,
+        Some(("hpoa", sub_matches)) => commands::hpoa::handle(sub_matches)?,
+        Some(("hpoa-diff", sub_matches)) => commands::hpoadiff::handle(sub_matches)?,
+        Some(("batch-hpoa", sub_matches)) => commands::batchhpoa::handle(sub_matches)?,
+        Some(("html", sub_matches)) => commands::html::handle(sub_matches)?,
+        Some(("dashboard", sub_matches)) => commands::dashboard::handle(sub_matches)?,
+        Some(("repo-stats", sub_matches)) => commands::repostats::handle(sub_matches)?,
+        Some(("repo-qc", sub_matches)) => commands::repoqc::handle(sub_matches)?,
+        Some(("overlap", sub_matches)) => commands::overlap::handle(sub_matches)?,
+        Some(("hpo", sub_matches)) => commands::hpo::handle(sub_matches)?,
+        Some(("disease-terms", sub_matches)) => commands::diseaseterms::handle(sub_matches)?,
+        Some(("config", sub_matches)) => commands::config::handle(sub_matches)?,
         _ => cmd.print_help()?,
     }
     Ok(())
 }
 
+/// With --verbose, we show debug messages of this crate (but not of dependencies such as reqwest);
+/// otherwise, the level from the configuration file (default: info)
+fn tracing_filter(verbose: bool, configured_level: Option<&str>) -> String {
+    if verbose {
+        "info,ga4ghphetools=debug,phetools=debug".to_string()
+    } else {
+        configured_level.unwrap_or("info").to_string()
+    }
+}
+
+/// Library code reports progress and warnings as `tracing` events; write them to stderr so that
+/// stdout can be used in pipelines. Unless --verbose is passed, RUST_LOG overrides the configured level.
+fn init_tracing(verbose: bool, configured_level: Option<&str>) {
+    let filter = match tracing_subscriber::EnvFilter::try_from_default_env() {
+        Ok(env_filter) if !verbose => env_filter,
+        _ => tracing_subscriber::EnvFilter::new(tracing_filter(verbose, configured_level)),
+    };
+    tracing_subscriber::fmt()
+        .with_env_filter(filter)
+        .with_writer(std::io::stderr)
+        .init();
+}
+
 /// Load HPO JSON
 pub fn load_hpo(json_path: &str) -> Result<Arc<FullCsrOntology>, Box<dyn std::error::Error>> {
     let loader = OntologyLoaderBuilder::new().obographs_parser().build();
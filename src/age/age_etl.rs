@@ -64,6 +64,130 @@ static RE_DAY: LazyLock<Regex> = LazyLock::new(|| {
     Regex::new(r"(?i)(\d+)\s*d").unwrap()
 });
 
+/// Phrases for life stages (after removing prepositions such as "at" or "during") and the corresponding HPO onset terms
+static LIFE_STAGE_PHRASES: LazyLock<HashMap<&'static str, &'static str>> = LazyLock::new(|| {
+    HashMap::from([
+        ("birth", "Congenital onset"),
+        ("prenatal", "Antenatal onset"),
+        ("prenatally", "Antenatal onset"),
+        ("antenatal", "Antenatal onset"),
+        ("antenatally", "Antenatal onset"),
+        ("utero", "Antenatal onset"),
+        ("embryonic period", "Embryonal onset"),
+        ("fetal period", "Fetal onset"),
+        ("second trimester", "Second trimester onset"),
+        ("third trimester", "Third trimester onset"),
+        ("neonatal period", "Neonatal onset"),
+        ("newborn period", "Neonatal onset"),
+        ("neonate", "Neonatal onset"),
+        ("newborn", "Neonatal onset"),
+        ("infancy", "Infantile onset"),
+        ("early infancy", "Infantile onset"),
+        ("late infancy", "Infantile onset"),
+        ("childhood", "Childhood onset"),
+        ("early childhood", "Childhood onset"),
+        ("late childhood", "Juvenile onset"),
+        ("adolescence", "Juvenile onset"),
+        ("puberty", "Juvenile onset"),
+        ("teens", "Juvenile onset"),
+        ("teenage years", "Juvenile onset"),
+        ("adulthood", "Adult onset"),
+        ("adult life", "Adult onset"),
+        ("early adulthood", "Young adult onset"),
+        ("young adulthood", "Young adult onset"),
+        ("middle age", "Middle age onset"),
+        ("midlife", "Middle age onset"),
+        ("late adulthood", "Late onset"),
+        ("old age", "Late onset"),
+    ])
+});
+
+static NUMBER_WORDS: [&str; 21] = [
+    "zero", "one", "two", "three", "four", "five", "six", "seven", "eight", "nine", "ten", "eleven",
+    "twelve", "thirteen", "fourteen", "fifteen", "sixteen", "seventeen", "eighteen", "nineteen", "twenty",
+];
+
+/// Prepositions and articles that can precede the description of the age
+static RE_LEADING_WORDS: LazyLock<Regex> = LazyLock::new(|| {
+    Regex::new(r"^(?:(?:at|in|during|from|since|by|around|about|the|an|a|his|her|their)\s+)*").unwrap()
+});
+
+/// e.g., "age 3", "age of 18 months", "aged 3 years", "3 years old", "3-year-old", "6 months of age"
+/// (after removing the leading words)
+static RE_NUMERIC_AGE: LazyLock<Regex> = LazyLock::new(|| {
+    Regex::new(r"^(age\s+(?:of\s+)?|aged\s+)?(\d+(?:\.\d+)?|[a-z]+)(?:\s*-\s*|\s+)?(years?|yrs?|y|months?|mos?|weeks?|wks?|days?|d)?(?:[-\s]+old)?(?:\s+of\s+age)?$").unwrap()
+});
+
+/// e.g., "in his 40s", "in their 20's", "in her early 30s"
+static RE_DECADE: LazyLock<Regex> = LazyLock::new(|| {
+    Regex::new(r"^(?:in\s+)?(?:his|her|their|the)?\s*(?:(early|mid|late)[\s-]+)?([1-9])0'?s$").unwrap()
+});
+
+/// Map a natural language description of an age to an ISO8601 duration or an HPO onset term.
+///
+/// Examples: "at age 3" -> P3Y, "at 18 months" -> P1Y6M, "in infancy" -> Infantile onset,
+/// "at birth" -> Congenital onset, "in his 40s" -> P40Y-P49Y (an age range), "in her late 30s" -> P37Y-P39Y.
+/// Numbers without a unit are only accepted after "age" or "aged" and are interpreted as years.
+pub fn parse_natural_language_age(input: &str) -> Option<String> {
+    let text = input.trim().trim_end_matches(['.', ',', ';']).to_lowercase();
+    let text = text.split_whitespace().collect::<Vec<_>>().join(" ");
+    if text.is_empty() {
+        return None;
+    }
+    if let Some(captures) = RE_DECADE.captures(&text) {
+        let decade: u32 = captures[2].parse::<u32>().ok()? * 10;
+        let (first, last) = match captures.get(1).map(|q| q.as_str()) {
+            Some("early") => (0, 3),
+            Some("mid") => (4, 6),
+            Some("late") => (7, 9),
+            _ => (0, 9),
+        };
+        return Some(format!("P{}Y-P{}Y", decade + first, decade + last));
+    }
+    let text = RE_LEADING_WORDS.replace(&text, "");
+    if let Some(iso) = parse_numeric_age(&text) {
+        return Some(iso);
+    }
+    LIFE_STAGE_PHRASES.get(text.as_ref()).map(|label| label.to_string())
+}
+
+fn parse_numeric_age(text: &str) -> Option<String> {
+    let captures = RE_NUMERIC_AGE.captures(text)?;
+    let amount = match captures[2].parse::<f64>() {
+        Ok(n) => n,
+        Err(_) => NUMBER_WORDS.iter().position(|w| *w == &captures[2])? as f64,
+    };
+    let unit = match captures.get(3) {
+        Some(unit) => unit.as_str(),
+        // a number without unit is only an age if it follows "age" or "aged"
+        None if captures.get(1).is_some() => "years",
+        None => return None,
+    };
+    age_to_iso(amount, unit)
+}
+
+/// An amount of years, months, weeks, or days as ISO8601 duration (P0D for zero)
+fn age_to_iso(amount: f64, unit: &str) -> Option<String> {
+    let (years, months, days) = match unit.chars().next()? {
+        // round the total number of months first, so that 2.99 years are P3Y rather than P2Y12M
+        'y' => {
+            let months = (amount * 12.0).round() as u32;
+            (months / 12, months % 12, 0)
+        }
+        'm' => {
+            let months = amount.round() as u32;
+            (months / 12, months % 12, 0)
+        }
+        'w' => (0, 0, (amount * 7.0).round() as u32),
+        _ => (0, 0, amount.round() as u32),
+    };
+    let mut iso = String::from("P");
+    if years > 0 { iso.push_str(&format!("{}Y", years)); }
+    if months > 0 { iso.push_str(&format!("{}M", months)); }
+    if days > 0 || iso == "P" { iso.push_str(&format!("{}D", days)); }
+    Some(iso)
+}
+
 pub fn map_age_string_to_symbolic(input: &str) -> Option<String> {
     let lower = input.to_lowercase();
     AGE_TERM_MAP.get(lower.as_str())
@@ -139,6 +263,49 @@ mod tests {
         assert_eq!(map_ymd_to_iso(input).as_deref(), expected);
     }
 
+    #[rstest]
+    #[case("at birth", Some("Congenital onset"))]
+    #[case("At birth.", Some("Congenital onset"))]
+    #[case("prenatally", Some("Antenatal onset"))]
+    #[case("in utero", Some("Antenatal onset"))]
+    #[case("in the neonatal period", Some("Neonatal onset"))]
+    #[case("in infancy", Some("Infantile onset"))]
+    #[case("in early infancy", Some("Infantile onset"))]
+    #[case("during childhood", Some("Childhood onset"))]
+    #[case("in early childhood", Some("Childhood onset"))]
+    #[case("during adolescence", Some("Juvenile onset"))]
+    #[case("at puberty", Some("Juvenile onset"))]
+    #[case("in adulthood", Some("Adult onset"))]
+    #[case("in young adulthood", Some("Young adult onset"))]
+    #[case("in middle age", Some("Middle age onset"))]
+    #[case("in old age", Some("Late onset"))]
+    #[case("at age 3", Some("P3Y"))]
+    #[case("at the age of 12", Some("P12Y"))]
+    #[case("aged 45 years", Some("P45Y"))]
+    #[case("at age three", Some("P3Y"))]
+    #[case("3 years old", Some("P3Y"))]
+    #[case("a 7-year-old", Some("P7Y"))]
+    #[case("7-year-old", Some("P7Y"))]
+    #[case("at 18 months", Some("P1Y6M"))]
+    #[case("at 6 months of age", Some("P6M"))]
+    #[case("at 2 weeks", Some("P14D"))]
+    #[case("at 10 days of age", Some("P10D"))]
+    #[case("at age 2.5 years", Some("P2Y6M"))]
+    #[case("at age 2.99 years", Some("P3Y"))]
+    #[case("in his 40s", Some("P40Y-P49Y"))]
+    #[case("in her early 30s", Some("P30Y-P33Y"))]
+    #[case("in his mid-50s", Some("P54Y-P56Y"))]
+    #[case("in their late 20s", Some("P27Y-P29Y"))]
+    #[case("in her 20's", Some("P20Y-P29Y"))]
+    #[case("in their 60s", Some("P60Y-P69Y"))]
+    #[case("at 3", None)]
+    #[case("at age", None)]
+    #[case("sometime later", None)]
+    #[case("", None)]
+    fn test_parse_natural_language_age(#[case] input: &str, #[case] expected: Option<&str>) {
+        assert_eq!(expected, parse_natural_language_age(input).as_deref());
+    }
+
      #[rstest]
     #[case("5y6m", Some("P5Y6M"))] // 10.5 rounded to 10 or 11 depending on rounding pref
  // Handles gestational style if it matches the week regex
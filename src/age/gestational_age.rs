@@ -37,6 +37,17 @@ impl GestationalAgeValidator {
         PRENATAL_ONSET_TERM_DICT.contains_key(cell_value)
     }
 
+    /// Convert a Gestational Age string such as G32w3d to (negative) years before a birth at 40 weeks
+    pub fn to_years(gestational_age: &str) -> Result<f64, String> {
+        let captures = GESTATIONAL_AGE_RE
+            .captures(gestational_age)
+            .ok_or_else(|| format!("Could not parse Gestational Age string: '{}'", gestational_age))?;
+        let component = |i: usize| -> f64 {
+            captures.get(i).and_then(|m| m.as_str().parse::<f64>().ok()).unwrap_or(0.0)
+        };
+        Ok(((component(1) - 40.0) * 7.0 + component(2)) / 365.25)
+    }
+
      /// Derive an HPO onset term from a Gestational Age string such as G32w3d
     pub fn get_duplet(gestational_age: &str) -> Result<HpoTermDuplet, String> {
         let captures = GESTATIONAL_AGE_RE
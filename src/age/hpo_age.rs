@@ -52,4 +52,57 @@ impl HpoTermAge {
             None => Err(format!("Could not retrieve HPO term for '{cell_contents}'")),
         }
     }
+
+    /// The earliest age (in years, negative before birth) covered by an HPO onset term, as given in the term definitions.
+    /// Prenatal ages are counted back from a 40-week pregnancy.
+    pub fn lower_bound_years(cell_contents: &str) -> Option<f64> {
+        let weeks_before_birth = |weeks: f64| (weeks - 40.0) * 7.0 / 365.25;
+        let years = match cell_contents {
+            "Antenatal onset" | "Embryonal onset" => weeks_before_birth(0.0),
+            "Fetal onset" | "Late first trimester onset" => weeks_before_birth(11.0),
+            "Second trimester onset" => weeks_before_birth(14.0),
+            "Third trimester onset" => weeks_before_birth(28.0),
+            "Congenital onset" | "Neonatal onset" => 0.0,
+            "Infantile onset" => 28.0 / 365.25,
+            "Childhood onset" => 1.0,
+            "Juvenile onset" => 5.0,
+            "Adult onset" | "Young adult onset" | "Early young adult onset" => 16.0,
+            "Intermediate young adult onset" => 19.0,
+            "Late young adult onset" => 25.0,
+            "Middle age onset" => 40.0,
+            "Late onset" => 60.0,
+            _ => return None,
+        };
+        Some(years)
+    }
+
+    /// The latest age (in years) covered by an HPO onset term. The open-ended terms Adult onset and
+    /// Late onset are capped at 80 years.
+    pub fn upper_bound_years(cell_contents: &str) -> Option<f64> {
+        let weeks_before_birth = |weeks: f64| (weeks - 40.0) * 7.0 / 365.25;
+        let years = match cell_contents {
+            "Embryonal onset" => weeks_before_birth(8.0),
+            "Late first trimester onset" => weeks_before_birth(14.0),
+            "Second trimester onset" => weeks_before_birth(28.0),
+            "Antenatal onset" | "Fetal onset" | "Third trimester onset" | "Congenital onset" => 0.0,
+            "Neonatal onset" => 28.0 / 365.25,
+            "Infantile onset" => 1.0,
+            "Childhood onset" => 5.0,
+            "Juvenile onset" => 15.0,
+            "Early young adult onset" => 19.0,
+            "Intermediate young adult onset" => 25.0,
+            "Young adult onset" | "Late young adult onset" => 40.0,
+            "Middle age onset" => 60.0,
+            "Adult onset" | "Late onset" => 80.0,
+            _ => return None,
+        };
+        Some(years)
+    }
+
+    /// The midpoint (in years) of the age range covered by an HPO onset term
+    pub fn midpoint_years(cell_contents: &str) -> Option<f64> {
+        let lower = Self::lower_bound_years(cell_contents)?;
+        let upper = Self::upper_bound_years(cell_contents)?;
+        Some((lower + upper) / 2.0)
+    }
 }
\ No newline at end of file
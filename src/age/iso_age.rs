@@ -65,6 +65,71 @@ impl Iso8601Age {
 
 
 
+    /// Convert an ISO8601 duration such as P3Y6M to fractional years (3.5).
+    /// Months are counted as 1/12 year and days as 1/365.25 year.
+    pub fn to_years(isostring: &str) -> Result<f64, String> {
+        let captures = ISO8601_RE
+            .captures(isostring)
+            .ok_or_else(|| format!("Could not parse ISO8601 string: {}", isostring))?;
+        let component = |i: usize| -> Result<f64, String> {
+            captures
+                .get(i)
+                .map_or(Ok(0.0), |m| m.as_str().parse::<f64>())
+                .map_err(|_| format!("Invalid ISO8601 string: {}", isostring))
+        };
+        Ok(component(1)? + component(2)? / 12.0 + component(3)? / 365.25)
+    }
+
+    /// Split an age range such as P3Y-P5Y into its lower and upper bound.
+    /// Returns None unless both bounds are valid ISO8601 ages and the lower bound is not above the upper bound.
+    pub fn split_range(cell_value: &str) -> Option<(&str, &str)> {
+        let (lower, upper) = cell_value.split_once('-')?;
+        let (lower, upper) = (lower.trim(), upper.trim());
+        match (Self::to_years(lower), Self::to_years(upper)) {
+            (Ok(a), Ok(b)) if a <= b => Some((lower, upper)),
+            _ => None,
+        }
+    }
+
+    pub fn is_valid_range(cell_value: &str) -> bool {
+        Self::split_range(cell_value).is_some()
+    }
+
+    /// The age halfway between two ISO8601 ages, e.g., P4Y for P3Y and P5Y or P3Y6M for P3Y and P4Y.
+    /// An odd number of months contributes 15 days.
+    pub fn midpoint(lower: &str, upper: &str) -> Result<String, String> {
+        let (months_a, days_a) = Self::months_and_days(lower)?;
+        let (months_b, days_b) = Self::months_and_days(upper)?;
+        let months = (months_a + months_b) / 2;
+        let days = (days_a + days_b) / 2 + if (months_a + months_b) % 2 == 1 { 15 } else { 0 };
+        let (years, months) = (months / 12, months % 12);
+        let mut iso = "P".to_string();
+        if years > 0 {
+            iso.push_str(&format!("{years}Y"));
+        }
+        if months > 0 {
+            iso.push_str(&format!("{months}M"));
+        }
+        if days > 0 || iso == "P" {
+            iso.push_str(&format!("{days}D"));
+        }
+        Ok(iso)
+    }
+
+    /// Total number of months (years and months) and days of an ISO8601 age
+    fn months_and_days(isostring: &str) -> Result<(u32, u32), String> {
+        let captures = ISO8601_RE
+            .captures(isostring)
+            .ok_or_else(|| format!("Could not parse ISO8601 string: {}", isostring))?;
+        let component = |i: usize| -> Result<u32, String> {
+            captures
+                .get(i)
+                .map_or(Ok(0), |m| m.as_str().parse::<u32>())
+                .map_err(|_| format!("Invalid ISO8601 string: {}", isostring))
+        };
+        Ok((component(1)? * 12 + component(2)?, component(3)?))
+    }
+
     pub fn get_duplet(cell_value: &str) -> Result<HpoTermDuplet, String> {
         let hpo_label = Self::get_hpo_onset_term_from_iso8601(cell_value)?;
         return hpo_age::HpoTermAge::get_duplet(&hpo_label);
@@ -95,6 +160,37 @@ mod tests {
         assert_eq!(onset_term, duplet.hpo_label());
     }
 
+    #[rstest]
+    #[case("P3Y6M", 3.5)]
+    #[case("P40Y", 40.0)]
+    #[case("P0D", 0.0)]
+    #[case("P1Y365D", 1.0 + 365.0 / 365.25)]
+    fn test_to_years(#[case] age_string: &str, #[case] expected: f64) {
+        let years = Iso8601Age::to_years(age_string).unwrap();
+        assert!((years - expected).abs() < 1e-9);
+    }
+
+    #[rstest]
+    #[case("P3Y-P5Y", Some(("P3Y", "P5Y")))]
+    #[case("P6M - P1Y", Some(("P6M", "P1Y")))]
+    #[case("P5Y-P3Y", None)]
+    #[case("P3Y", None)]
+    #[case("P3Y-Childhood onset", None)]
+    fn test_split_range(#[case] cell_value: &str, #[case] expected: Option<(&str, &str)>) {
+        assert_eq!(expected, Iso8601Age::split_range(cell_value));
+    }
+
+    #[rstest]
+    #[case("P3Y", "P5Y", "P4Y")]
+    #[case("P3Y", "P4Y", "P3Y6M")]
+    #[case("P1M", "P2M", "P1M15D")]
+    #[case("P2D", "P10D", "P6D")]
+    #[case("P0D", "P0D", "P0D")]
+    #[case("P11M", "P1Y1M", "P1Y")]
+    fn test_midpoint(#[case] lower: &str, #[case] upper: &str, #[case] expected: &str) {
+        assert_eq!(Ok(expected.to_string()), Iso8601Age::midpoint(lower, upper));
+    }
+
 
     
 }
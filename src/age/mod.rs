@@ -13,6 +13,7 @@
 //! assert!(is_valid_age_string("P3Y6M4D")); // ISO 8601
 //! assert!(is_valid_age_string("Congenital onset")); // HPO onset
 //! assert!(is_valid_age_string("G20w1d")); // gestational age
+//! assert!(is_valid_age_string("P3Y-P5Y")); // range of ISO 8601 ages
 //! assert!(is_valid_age_string("na")); // allowed special case
 //! ```
 
@@ -49,13 +50,40 @@ pub fn is_valid_age_string(cell_value: &str) -> bool {
         return true;
     }
 
+    if Iso8601Age::is_valid_range(cell_value) {
+        return true;
+    }
+
     false
 }
 
 
+/// Resolve an age that may be imprecise into a single age string for export.
+///
+/// If `age` is a range such as `P3Y-P5Y`, or `age_max` is an ISO8601 upper bound for the ISO8601 age `age`,
+/// the midpoint of the range is returned (`P4Y`); otherwise `age` is returned unchanged.
+pub fn resolve_age(age: &str, age_max: Option<&str>) -> Result<String, String> {
+    if let Some((lower, upper)) = Iso8601Age::split_range(age) {
+        return Iso8601Age::midpoint(lower, upper);
+    }
+    match age_max.map(str::trim).filter(|m| !m.is_empty() && *m != "na") {
+        Some(upper) => {
+            let range = format!("{age}-{upper}");
+            let (lower, upper) = Iso8601Age::split_range(&range)
+                .ok_or_else(|| format!("Invalid age range '{range}'"))?;
+            Iso8601Age::midpoint(lower, upper)
+        }
+        None => Ok(age.to_string()),
+    }
+}
+
+
 /// Get an HPO Onset term from one of the valid age string values.
 /// Note that we assume that we are only getting candidate ages here, not "na", "observed", "expected"
 pub fn get_onset_term(cell_value: &str) -> Result<HpoTermDuplet, String> {
+   if let Some((lower, upper)) = Iso8601Age::split_range(cell_value) {
+        return Iso8601Age::get_duplet(&Iso8601Age::midpoint(lower, upper)?);
+   }
    if HpoTermAge::is_valid(cell_value) {
         return HpoTermAge::get_duplet(cell_value);
     } else if  Iso8601Age::is_valid(cell_value) {
@@ -68,6 +96,46 @@ pub fn get_onset_term(cell_value: &str) -> Result<HpoTermDuplet, String> {
 }
 
 
+/// Convert one of the valid age string values to years, so that ages can be compared.
+/// Prenatal ages are negative; HPO onset terms are represented by the earliest age they cover and age ranges by their midpoint.
+pub fn onset_to_years(cell_value: &str) -> Result<f64, String> {
+    if let Some(years) = HpoTermAge::lower_bound_years(cell_value) {
+        Ok(years)
+    } else if Iso8601Age::is_valid(cell_value) {
+        Iso8601Age::to_years(cell_value)
+    } else if GestationalAgeValidator::is_valid(cell_value) {
+        GestationalAgeValidator::to_years(cell_value)
+    } else if let Some((lower, upper)) = Iso8601Age::split_range(cell_value) {
+        Iso8601Age::to_years(&Iso8601Age::midpoint(lower, upper)?)
+    } else {
+        Err(format!("Malformed age string '{}'", cell_value))
+    }
+}
+
+
+/// Compute the duration of the disease in fractional years from the age of onset to the age at the last encounter.
+///
+/// Both ages can be ISO8601 durations, ranges of ISO8601 durations, gestational ages, or HPO onset terms;
+/// HPO onset terms are represented by the midpoint of the age range they cover, and ranges by their midpoint.
+/// Returns an error if an age cannot be parsed or if the onset is after the last encounter.
+pub fn compute_disease_duration_years(onset: &str, last_encounter: &str) -> Result<f64, String> {
+    let onset_years = age_to_midpoint_years(onset)?;
+    let last_encounter_years = age_to_midpoint_years(last_encounter)?;
+    if onset_years > last_encounter_years {
+        return Err(format!("Onset ({onset}) is after the last encounter ({last_encounter})"));
+    }
+    Ok(last_encounter_years - onset_years)
+}
+
+fn age_to_midpoint_years(cell_value: &str) -> Result<f64, String> {
+    if let Some(years) = HpoTermAge::midpoint_years(cell_value) {
+        Ok(years)
+    } else {
+        onset_to_years(cell_value)
+    }
+}
+
+
 /// Processes a raw age string into a standardized clinical format.
 ///
 /// This is a "waterfall" parser that attempts to resolve the input in the following order:
@@ -108,3 +176,56 @@ pub fn convert_raw_age_string_to_validated_age_string(age_string: &str) -> Optio
         .or_else(|| age_etl::map_ymd_to_iso(age_string))
 }
 
+/// Map a natural language description of an age, as often found in the text of publications,
+/// to an ISO8601 duration or an HPO onset term.
+///
+/// Returns `None` if the expression is not recognized.
+///
+/// # Examples
+///
+/// ```
+/// use ga4ghphetools::age::parse_natural_language_age;
+/// assert_eq!(parse_natural_language_age("at age 3"), Some("P3Y".to_string()));
+/// assert_eq!(parse_natural_language_age("at 18 months of age"), Some("P1Y6M".to_string()));
+/// assert_eq!(parse_natural_language_age("in infancy"), Some("Infantile onset".to_string()));
+/// assert_eq!(parse_natural_language_age("sometime later"), None);
+/// ```
+pub fn parse_natural_language_age(s: &str) -> Option<String> {
+    age_etl::parse_natural_language_age(s)
+}
+
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rstest::rstest;
+
+    #[rstest]
+    #[case("P2Y", "P5Y6M", 3.5)]
+    #[case("P3Y", "P3Y", 0.0)]
+    #[case("P1Y-P3Y", "P10Y", 8.0)]
+    #[case("Childhood onset", "P10Y", 7.0)]
+    #[case("Juvenile onset", "P30Y", 20.0)]
+    #[case("Congenital onset", "P1Y6M", 1.5)]
+    #[case("Middle age onset", "Late onset", 20.0)]
+    fn test_compute_disease_duration_years(#[case] onset: &str, #[case] last_encounter: &str, #[case] expected: f64) {
+        let duration = compute_disease_duration_years(onset, last_encounter).unwrap();
+        assert!((expected - duration).abs() < 1e-9, "expected {expected} but got {duration}");
+    }
+
+    #[rstest]
+    fn test_neonatal_onset_duration() {
+        let duration = compute_disease_duration_years("Neonatal onset", "P1Y").unwrap();
+        assert!((1.0 - 14.0 / 365.25 - duration).abs() < 1e-9);
+    }
+
+    #[rstest]
+    #[case::onset_after_last_encounter("P5Y", "P2Y")]
+    #[case::term_after_last_encounter("Adult onset", "P20Y")]
+    #[case::not_available("na", "P2Y")]
+    #[case::malformed("P2Y", "two years")]
+    fn test_invalid_disease_duration(#[case] onset: &str, #[case] last_encounter: &str) {
+        assert!(compute_disease_duration_years(onset, last_encounter).is_err());
+    }
+}
@@ -3,7 +3,7 @@
 //! This file contains definitions of structure with data about a cohort. They are used as Data Transfer Objects between the front and back end and the CohortData is used to serialize the data
 //! about the entire cohort.
 
-use std::collections::HashMap;
+use std::collections::{HashMap, HashSet};
 use std::str::FromStr;
 
 use chrono::Local;
@@ -11,6 +11,7 @@ use serde::{Deserialize, Serialize};
 use crate::dto::hgvs_variant::HgvsVariant;
 use crate::dto::hpo_term_dto::{CellValue};
 use crate::dto::intergenic_variant::IntergenicHgvsVariant;
+use crate::dto::orcid::with_orcid_prefix;
 use crate::dto::structural_variant::StructuralVariant;
 use crate::dto::hpo_term_dto::HpoTermDuplet;
 use crate::ppkt::ppkt_row::PpktRow;
@@ -27,8 +28,37 @@ pub struct IndividualData {
     pub comment: String,
     pub age_of_onset: String,
     pub age_at_last_encounter: String,
+    /// Upper bound if the age of onset was reported as a range, e.g., P5Y for "3-5 years" (the lower bound is `age_of_onset`)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub age_of_onset_max: Option<String>,
+    /// Upper bound if the age at last encounter was reported as a range
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub age_at_last_encounter_max: Option<String>,
     pub deceased: String,
-    pub sex: String
+    pub sex: String,
+    /// Treatments of the individual, e.g., CHEBI:45783 (optionally followed by the label of the agent)
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub treatment_list: Vec<String>,
+    /// Samples (e.g., tumor tissue) that were taken from the individual
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub biosamples: Vec<BiosampleData>,
+    /// Quantitative phenotypes such as lab values or IQ scores
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub measurements: Vec<MeasurementData>,
+}
+
+/// A biological sample taken from an individual, such as a biopsy.
+#[derive(Clone, Debug, Deserialize, Serialize, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct BiosampleData {
+    pub biosample_id: String,
+    /// UBERON term for the sampled tissue, e.g., UBERON:0002107
+    pub tissue_id: String,
+    #[serde(default)]
+    pub tissue_label: String,
+    /// NCIT term for the histological diagnosis, e.g., NCIT:C3058
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub histology_id: Option<String>,
 }
 
 impl IndividualData {
@@ -48,10 +78,61 @@ impl IndividualData {
                 comment: comment.to_string(),
                 age_of_onset: age_of_onset.to_string(),
                 age_at_last_encounter: age_at_last_encounter.to_string(),
+                age_of_onset_max: None,
+                age_at_last_encounter_max: None,
                 deceased: deceased.to_string(),
                 sex: sex.to_string(),
+                treatment_list: vec![],
+                biosamples: vec![],
+                measurements: vec![],
             }
     }
+
+    /// Duration of the disease in years from the age of onset to the age at the last encounter,
+    /// or None if either age is not available or the onset is after the last encounter.
+    /// See [`crate::age::compute_disease_duration_years`].
+    pub fn disease_duration_years(&self) -> Option<f64> {
+        let onset = crate::age::resolve_age(&self.age_of_onset, self.age_of_onset_max.as_deref()).ok()?;
+        let last_encounter = crate::age::resolve_age(&self.age_at_last_encounter, self.age_at_last_encounter_max.as_deref()).ok()?;
+        crate::age::compute_disease_duration_years(&onset, &last_encounter).ok()
+    }
+}
+
+/// A quantitative measurement, e.g., serum creatinine (LOINC:2160-0) of 1.2 mg/dL
+#[derive(Clone, Debug, Deserialize, Serialize, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct MeasurementData {
+    /// LOINC or HPO term for the assay, e.g., LOINC:2160-0
+    pub assay_id: String,
+    pub value: f64,
+    /// UCUM term for the unit, e.g., UCUM:mg/dL
+    pub unit_id: String,
+    /// Age at which the measurement was taken (e.g., P3Y)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub time_observed: Option<String>,
+}
+
+impl FromStr for MeasurementData {
+    type Err = String;
+
+    /// Parse a measurement written as `assay value unit [age]`, e.g., `LOINC:2160-0 1.2 UCUM:mg/dL P3Y`
+    fn from_str(s: &str) -> std::result::Result<Self, String> {
+        let fields: Vec<&str> = s.split_whitespace().collect();
+        if fields.len() != 3 && fields.len() != 4 {
+            return Err(format!("Expected 'assay value unit [age]' but got '{s}'"));
+        }
+        let value = fields[1].parse::<f64>()
+            .map_err(|_| format!("Invalid measurement value '{}' in '{s}'", fields[1]))?;
+        if !fields[0].contains(':') || !fields[2].contains(':') {
+            return Err(format!("Assay and unit must be CURIEs (e.g., LOINC:2160-0 and UCUM:mg/dL) in '{s}'"));
+        }
+        Ok(Self {
+            assay_id: fields[0].to_string(),
+            value,
+            unit_id: fields[2].to_string(),
+            time_observed: fields.get(3).map(|t| t.to_string()),
+        })
+    }
 }
 
 /// This structure contains information about the 
@@ -278,6 +359,36 @@ impl CurationEvent {
 }
 
 
+/// A proband and both parents, used to detect de novo variants.
+/// The parents can be individuals of the cohort or be given by their alleles only
+/// (e.g., if the genotypes of the parents were imported from FatherVariant and MotherVariant columns).
+#[derive(Clone, Debug, Deserialize, Serialize, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+pub struct TrioData {
+    pub proband_id: String,
+    pub father_id: String,
+    pub mother_id: String,
+    /// Alleles (variant keys) of the father if he is not an individual of the cohort
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub father_alleles: Option<Vec<String>>,
+    /// Alleles (variant keys) of the mother if she is not an individual of the cohort
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub mother_alleles: Option<Vec<String>>,
+}
+
+impl TrioData {
+    /// A trio whose parents are individuals of the cohort
+    pub fn new(proband_id: &str, father_id: &str, mother_id: &str) -> Self {
+        Self {
+            proband_id: proband_id.to_string(),
+            father_id: father_id.to_string(),
+            mother_id: mother_id.to_string(),
+            father_alleles: None,
+            mother_alleles: None,
+        }
+    }
+}
+
 
 /// This is the representation of the cohort (source of truth)
 /// There is a corresponding typescript DTO in the front-end
@@ -308,6 +419,9 @@ pub struct CohortData {
     /// History of biocuration events in chronological order
     #[serde(default, skip_serializing_if = "Vec::is_empty")]
     pub curation_history: Vec<CurationEvent>,
+    /// Trios (proband and parents) for the detection of de novo variants
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub trios: Vec<TrioData>,
 }
 
 /// Version of the Cohort JSON schema
@@ -345,6 +459,7 @@ impl CohortData {
             hpo_version: hpo_version.to_string(),
             cohort_acronym: None,
             curation_history: vec![],
+            trios: vec![],
         }
     }
 
@@ -380,6 +495,7 @@ impl CohortData {
             hpo_version: hpo_version.to_string(),
             cohort_acronym: None,
             curation_history: vec![Self::legacy_curation()],
+            trios: vec![],
         }
     }
 
@@ -399,10 +515,12 @@ impl CohortData {
         PHETOOLS_SCHEMA_VERSION.to_string()
     }
 
+    /// ORCID of the most recent curation event, in the form `ORCID:XXXX-XXXX-XXXX-XXXX` that is expected by
+    /// the HPOA and phenopacket exporters (the curation history stores the bare identifier)
     pub fn get_latest_biocurator_id(&self) -> Result<String, String> {
         self.curation_history
             .last()
-            .map(|curation| curation.orcid.to_string())
+            .map(|curation| with_orcid_prefix(&curation.orcid))
             .ok_or_else(|| 
                 format!("No biocuration identifier available for cohort {}", self.acronym()))
     }
@@ -461,5 +579,379 @@ impl CohortData {
             None => 0
         }
     }
-    
-}
\ No newline at end of file
+
+    /// Number of individuals (rows) in the cohort
+    pub fn individual_count(&self) -> usize {
+        self.rows.len()
+    }
+
+    /// Number of distinct HPO terms used to annotate the cohort (a term in several columns is counted once)
+    pub fn hpo_term_count(&self) -> usize {
+        self.hpo_headers
+            .iter()
+            .map(|duplet| duplet.hpo_id())
+            .collect::<HashSet<_>>()
+            .len()
+    }
+
+    /// Number of distinct variants (variant keys) carried by at least one individual
+    pub fn unique_variant_count(&self) -> usize {
+        self.rows
+            .iter()
+            .flat_map(|row| row.allele_count_map
+                .iter()
+                .filter(|(_, count)| **count > 0)
+                .map(|(key, _)| key))
+            .collect::<HashSet<_>>()
+            .len()
+    }
+
+    /// The row of the individual with the given identifier (the first one if the identifier is not unique)
+    pub fn get_individual_by_id(&self, id: &str) -> Option<&RowData> {
+        self.rows
+            .iter()
+            .find(|row| row.individual_data.individual_id == id)
+    }
+
+    pub fn has_individual(&self, id: &str) -> bool {
+        self.get_individual_by_id(id).is_some()
+    }
+
+    /// True if the cohort does not have any individuals
+    pub fn is_empty(&self) -> bool {
+        self.rows.is_empty()
+    }
+
+    pub fn iter_rows(&self) -> impl Iterator<Item = &RowData> {
+        self.rows.iter()
+    }
+
+    pub fn iter_rows_mut(&mut self) -> impl Iterator<Item = &mut RowData> {
+        self.rows.iter_mut()
+    }
+
+    /// The rows for which `predicate` is true, in the order of the cohort
+    pub fn filter_rows<F: Fn(&RowData) -> bool>(&self, predicate: F) -> Vec<&RowData> {
+        self.rows
+            .iter()
+            .filter(|row| predicate(row))
+            .collect()
+    }
+
+    /// Sort the individuals by the key returned by `key_fn`.
+    /// The sort is stable, i.e., individuals with equal keys keep their relative order.
+    pub fn sort_by<K: Ord, F: Fn(&RowData) -> K>(&mut self, key_fn: F) {
+        self.rows.sort_by_key(|row| key_fn(row));
+    }
+
+    pub fn sort_by_individual_id(&mut self) {
+        self.sort_by(|row| row.individual_data.individual_id.clone());
+    }
+
+    pub fn sort_by_pmid(&mut self) {
+        self.sort_by(|row| row.individual_data.pmid.clone());
+    }
+
+    /// The HPO terms of the individual whose cells satisfy `predicate`, in the order of the HPO columns
+    fn terms_for_individual<F: Fn(&CellValue) -> bool>(&self, individual_id: &str, predicate: F) -> Result<Vec<HpoTermDuplet>, String> {
+        let row = self.get_individual_by_id(individual_id)
+            .ok_or_else(|| format!("Could not find individual '{individual_id}' in cohort {}", self.acronym()))?;
+        Ok(self.hpo_headers
+            .iter()
+            .zip(row.hpo_data.iter())
+            .filter(|(_, cell)| predicate(cell))
+            .map(|(duplet, _)| duplet.clone())
+            .collect())
+    }
+
+    /// HPO terms that were observed in the individual (including terms annotated with an age of onset)
+    pub fn get_observed_terms_for_individual(&self, individual_id: &str) -> Result<Vec<HpoTermDuplet>, String> {
+        self.terms_for_individual(individual_id, |cell| cell.is_present())
+    }
+
+    /// HPO terms that were explicitly excluded in the individual
+    pub fn get_excluded_terms_for_individual(&self, individual_id: &str) -> Result<Vec<HpoTermDuplet>, String> {
+        self.terms_for_individual(individual_id, |cell| cell.is_excluded())
+    }
+
+    /// Compact multi-line summary, e.g., for log messages
+    pub fn to_summary_string(&self) -> String {
+        let diseases: Vec<String> = self.disease_list
+            .iter()
+            .map(|d| format!("{} ({})", d.disease_label, d.disease_id))
+            .collect();
+        let genes: Vec<&str> = self.disease_list
+            .iter()
+            .flat_map(|d| d.gene_transcript_list.iter().map(|gt| gt.gene_symbol.as_str()))
+            .collect();
+        let n_variants = self.hgvs_variants.len() + self.structural_variants.len() + self.intergenic_variants.len();
+        [
+            format!("Cohort: {}", self.acronym()),
+            format!("Disease: {}", diseases.join("; ")),
+            format!("Gene: {}", genes.join("; ")),
+            format!("Individuals: {}", self.individual_count()),
+            format!("HPO terms: {}", self.hpo_term_count()),
+            format!("Variants: {}", n_variants),
+        ].join("\n")
+    }
+
+    /// Check that the parts of the cohort fit together: every row has one value per HPO column,
+    /// every variant key of a row refers to a validated variant, and Mendelian cohorts have a disease.
+    /// Returns all problems that were found.
+    pub fn validate_structural_integrity(&self) -> Result<(), Vec<String>> {
+        let mut errors = Vec::new();
+        if self.is_mendelian() && self.disease_list.is_empty() {
+            errors.push("Mendelian cohort without disease".to_string());
+        }
+        for row in &self.rows {
+            let individual_id = &row.individual_data.individual_id;
+            if row.hpo_data.len() != self.hpo_headers.len() {
+                errors.push(format!("{individual_id}: {} HPO values but {} HPO columns", 
+                    row.hpo_data.len(), self.hpo_headers.len()));
+            }
+            let mut variant_keys: Vec<&String> = row.allele_count_map.keys().collect();
+            variant_keys.sort();
+            for key in variant_keys {
+                if !self.hgvs_variants.contains_key(key) 
+                    && !self.structural_variants.contains_key(key) 
+                    && !self.intergenic_variants.contains_key(key) {
+                    errors.push(format!("{individual_id}: unknown variant key '{key}'"));
+                }
+            }
+        }
+        if errors.is_empty() {
+            Ok(())
+        } else {
+            Err(errors)
+        }
+    }
+}
+
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use rstest::rstest;
+    use crate::test_utils::fixtures::fbn1_cohort;
+
+    #[rstest]
+    fn test_counts(fbn1_cohort: CohortData) {
+        assert_eq!(3, fbn1_cohort.individual_count());
+        assert_eq!(4, fbn1_cohort.hpo_term_count());
+        assert_eq!(2, fbn1_cohort.unique_variant_count());
+        assert!(!fbn1_cohort.is_empty());
+    }
+
+    #[rstest]
+    fn test_hpo_term_count_duplicate_column(mut fbn1_cohort: CohortData) {
+        let duplicate = fbn1_cohort.hpo_headers[0].clone();
+        fbn1_cohort.hpo_headers.push(duplicate);
+        assert_eq!(4, fbn1_cohort.hpo_term_count());
+    }
+
+    #[rstest]
+    fn test_disease_duration_years() {
+        let mut individual = IndividualData::new("PMID:1", "title", "1", "", "P2Y", "P10Y6M", "no", "M");
+        assert_eq!(Some(8.5), individual.disease_duration_years());
+        individual.age_of_onset_max = Some("P4Y".to_string());
+        assert_eq!(Some(7.5), individual.disease_duration_years());
+        individual.age_at_last_encounter = "na".to_string();
+        assert_eq!(None, individual.disease_duration_years());
+    }
+
+    #[rstest]
+    fn test_get_latest_biocurator_id(mut fbn1_cohort: CohortData) {
+        assert_eq!("ORCID:0000-0002-0736-9199", fbn1_cohort.get_latest_biocurator_id().unwrap());
+        fbn1_cohort.curation_history.push(CurationEvent::new("ORCID:0000-0002-1825-0097"));
+        assert_eq!("ORCID:0000-0002-1825-0097", fbn1_cohort.get_latest_biocurator_id().unwrap());
+        fbn1_cohort.curation_history.clear();
+        assert!(fbn1_cohort.get_latest_biocurator_id().is_err());
+    }
+
+    #[rstest]
+    fn test_get_individual_by_id(fbn1_cohort: CohortData) {
+        let row = fbn1_cohort.get_individual_by_id("Individual 2").unwrap();
+        assert_eq!("F", row.individual_data.sex);
+        assert!(fbn1_cohort.has_individual("Individual 3"));
+        assert!(!fbn1_cohort.has_individual("Individual 4"));
+        assert!(fbn1_cohort.get_individual_by_id("individual 2").is_none());
+    }
+
+    #[rstest]
+    fn test_single_individual(mut fbn1_cohort: CohortData) {
+        fbn1_cohort.rows.truncate(1);
+        assert_eq!(1, fbn1_cohort.individual_count());
+        assert_eq!(4, fbn1_cohort.hpo_term_count());
+        assert_eq!(1, fbn1_cohort.unique_variant_count());
+        assert!(fbn1_cohort.has_individual("Individual 1"));
+        assert!(!fbn1_cohort.has_individual("Individual 2"));
+        assert!(!fbn1_cohort.is_empty());
+    }
+
+    #[rstest]
+    fn test_filter_rows(fbn1_cohort: CohortData) {
+        let females = fbn1_cohort.filter_rows(|row| row.individual_data.sex == "F");
+        let ids: Vec<&str> = females.iter().map(|row| row.individual_data.individual_id.as_str()).collect();
+        assert_eq!(vec!["Individual 2", "Individual 3"], ids);
+        assert!(fbn1_cohort.filter_rows(|row| row.individual_data.sex == "O").is_empty());
+        // the cohort itself is not changed
+        assert_eq!(3, fbn1_cohort.iter_rows().count());
+    }
+
+    #[rstest]
+    fn test_iter_rows_mut(mut fbn1_cohort: CohortData) {
+        for row in fbn1_cohort.iter_rows_mut() {
+            row.individual_data.comment = "checked".to_string();
+        }
+        assert!(fbn1_cohort.iter_rows().all(|row| row.individual_data.comment == "checked"));
+    }
+
+    fn individual_ids(cohort: &CohortData) -> Vec<&str> {
+        cohort.iter_rows().map(|row| row.individual_data.individual_id.as_str()).collect()
+    }
+
+    #[rstest]
+    fn test_sort_by_individual_id(mut fbn1_cohort: CohortData) {
+        fbn1_cohort.rows.reverse();
+        fbn1_cohort.sort_by_individual_id();
+        assert_eq!(vec!["Individual 1", "Individual 2", "Individual 3"], individual_ids(&fbn1_cohort));
+    }
+
+    #[rstest]
+    fn test_sort_by_pmid_is_stable(mut fbn1_cohort: CohortData) {
+        // Individuals 1 and 2 were reported in the same publication
+        fbn1_cohort.rows.reverse();
+        fbn1_cohort.sort_by_pmid();
+        assert_eq!(vec!["Individual 2", "Individual 1", "Individual 3"], individual_ids(&fbn1_cohort));
+    }
+
+    #[rstest]
+    fn test_sort_by_key(mut fbn1_cohort: CohortData) {
+        fbn1_cohort.sort_by(|row| std::cmp::Reverse(row.individual_data.age_at_last_encounter.clone()));
+        // P5Y, P30Y, P10Y (descending lexicographic order)
+        assert_eq!(vec!["Individual 2", "Individual 3", "Individual 1"], individual_ids(&fbn1_cohort));
+        // each row keeps its own data
+        let row = fbn1_cohort.get_individual_by_id("Individual 3").unwrap();
+        assert_eq!("P30Y", row.individual_data.age_at_last_encounter);
+        assert!(row.hpo_data[3].is_observed());
+    }
+
+    fn hpo_ids(terms: &[HpoTermDuplet]) -> Vec<&str> {
+        terms.iter().map(|duplet| duplet.hpo_id()).collect()
+    }
+
+    #[rstest]
+    #[case("Individual 1", vec!["HP:0001166", "HP:0001631"], vec!["HP:0001847"])]
+    #[case("Individual 2", vec!["HP:0001166", "HP:0001847"], vec!["HP:0001631", "HP:0004322"])]
+    #[case("Individual 3", vec!["HP:0001166", "HP:0004322"], vec![])]
+    fn test_observed_and_excluded_terms(
+        fbn1_cohort: CohortData,
+        #[case] individual_id: &str,
+        #[case] observed: Vec<&str>,
+        #[case] excluded: Vec<&str>,
+    ) {
+        let observed_terms = fbn1_cohort.get_observed_terms_for_individual(individual_id).unwrap();
+        let excluded_terms = fbn1_cohort.get_excluded_terms_for_individual(individual_id).unwrap();
+        assert_eq!(observed, hpo_ids(&observed_terms));
+        assert_eq!(excluded, hpo_ids(&excluded_terms));
+    }
+
+    #[rstest]
+    fn test_terms_for_unknown_individual(fbn1_cohort: CohortData) {
+        assert!(fbn1_cohort.get_observed_terms_for_individual("Individual 4").is_err());
+        assert!(fbn1_cohort.get_excluded_terms_for_individual("Individual 4").is_err());
+    }
+
+    #[rstest]
+    fn test_summary_string(fbn1_cohort: CohortData) {
+        let summary = fbn1_cohort.to_summary_string();
+        assert_eq!("Cohort: MFS\n\
+            Disease: Marfan syndrome (OMIM:154700)\n\
+            Gene: FBN1\n\
+            Individuals: 3\n\
+            HPO terms: 4\n\
+            Variants: 2", summary);
+    }
+
+    #[rstest]
+    fn test_structural_integrity(fbn1_cohort: CohortData) {
+        assert!(fbn1_cohort.validate_structural_integrity().is_ok());
+    }
+
+    #[rstest]
+    fn test_structural_integrity_errors(mut fbn1_cohort: CohortData) {
+        fbn1_cohort.rows[0].hpo_data.pop();
+        fbn1_cohort.rows[1].allele_count_map.insert("c.1A>G_FBN1_NM_000138v5".to_string(), 1);
+        fbn1_cohort.disease_list.clear();
+        let errors = fbn1_cohort.validate_structural_integrity().unwrap_err();
+        assert_eq!(3, errors.len());
+        assert_eq!("Mendelian cohort without disease", errors[0]);
+        assert_eq!("Individual 1: 3 HPO values but 4 HPO columns", errors[1]);
+        assert!(errors[2].starts_with("Individual 2: unknown variant key"));
+    }
+
+    #[rstest]
+    fn test_row_with_missing_hpo_value(mut fbn1_cohort: CohortData) {
+        fbn1_cohort.rows[1].hpo_data.pop();
+        let errors = fbn1_cohort.validate_structural_integrity().unwrap_err();
+        assert_eq!(vec!["Individual 2: 3 HPO values but 4 HPO columns".to_string()], errors);
+    }
+
+    #[rstest]
+    fn test_header_without_values(mut fbn1_cohort: CohortData) {
+        fbn1_cohort.hpo_headers.push(HpoTermDuplet::new("Ectopia lentis", "HP:0001083"));
+        let errors = fbn1_cohort.validate_structural_integrity().unwrap_err();
+        assert_eq!(3, errors.len());
+        assert_eq!("Individual 1: 4 HPO values but 5 HPO columns", errors[0]);
+    }
+
+    #[rstest]
+    fn test_unknown_variant_key(mut fbn1_cohort: CohortData) {
+        fbn1_cohort.hgvs_variants.clear();
+        let errors = fbn1_cohort.validate_structural_integrity().unwrap_err();
+        // Individuals 1 and 2 carry the HGVS variant
+        assert_eq!(2, errors.len());
+        assert!(errors.iter().all(|e| e.contains("unknown variant key 'c8242GtoT_FBN1_NM_000138v5'")));
+    }
+
+    #[rstest]
+    fn test_mendelian_cohort_without_disease(mut fbn1_cohort: CohortData) {
+        fbn1_cohort.disease_list.clear();
+        let errors = fbn1_cohort.validate_structural_integrity().unwrap_err();
+        assert_eq!(vec!["Mendelian cohort without disease".to_string()], errors);
+    }
+
+    #[test]
+    fn test_empty_cohort() {
+        let cohort = CohortData::melded(vec![DiseaseData::new("OMIM:154700", "Marfan syndrome")], "2025-03-03");
+        assert_eq!(0, cohort.individual_count());
+        assert_eq!(0, cohort.hpo_term_count());
+        assert_eq!(0, cohort.unique_variant_count());
+        assert!(cohort.get_individual_by_id("Individual 1").is_none());
+        assert!(!cohort.has_individual("Individual 1"));
+        assert!(cohort.is_empty());
+    }
+
+    #[rstest]
+    #[case("LOINC:2160-0 1.2 UCUM:mg/dL", "LOINC:2160-0", 1.2, None)]
+    #[case("HP:0001249 65 UCUM:1 P8Y", "HP:0001249", 65.0, Some("P8Y"))]
+    fn test_parse_measurement(
+        #[case] input: &str,
+        #[case] assay_id: &str,
+        #[case] value: f64,
+        #[case] time_observed: Option<&str>
+    ) {
+        let m = MeasurementData::from_str(input).unwrap();
+        assert_eq!(assay_id, m.assay_id);
+        assert_eq!(value, m.value);
+        assert_eq!(time_observed.map(String::from), m.time_observed);
+    }
+
+    #[rstest]
+    #[case("LOINC:2160-0 high UCUM:mg/dL")]
+    #[case("LOINC:2160-0 1.2")]
+    #[case("creatinine 1.2 mg/dL")]
+    fn test_parse_measurement_error(#[case] input: &str) {
+        assert!(MeasurementData::from_str(input).is_err());
+    }
+}
@@ -12,10 +12,19 @@ use crate::dto::{cohort_dto::DiseaseData, hgvs_variant::HgvsVariant, hpo_term_dt
 pub enum EtlCellStatus {
     Raw,
     Transformed,
+    /// The transformation succeeded in part; the value can be used but should be reviewed manually
+    PartiallyTransformed,
     Error,
     Ignored,
 }
 
+impl EtlCellStatus {
+    /// True if the value of the cell can be ingested (possibly with a warning)
+    pub fn is_usable(&self) -> bool {
+        matches!(self, EtlCellStatus::Transformed | EtlCellStatus::PartiallyTransformed)
+    }
+}
+
 #[derive(Serialize, Deserialize, Clone, Debug)]
 #[serde(rename_all = "camelCase")]
 pub struct EtlCellValue {
@@ -139,13 +148,62 @@ pub enum EtlColumnType {
     GeneSymbol,
     Variant,
     AgeOfOnset,
+    /// Age of onset reported as a range of ISO8601 ages (e.g., P3Y-P5Y)
+    AgeRange,
     AgeAtLastEncounter,
     Sex,
     Deceased,
     HpoTextMining,
+    /// Curation confidence (high, medium, low, or na) for all HPO annotations of the row
+    Confidence,
+    /// Laterality (bilateral, unilateral, right, left, or na) for all observed HPO annotations of the row
+    Laterality,
+    /// Severity (mild, moderate, severe, profound, or na) for all observed HPO annotations of the row
+    SeverityModifier,
+    /// Treatments (e.g., CHEBI:45783), separated by semicolons
+    Treatment,
+    /// Sampled tissue as an UBERON term, optionally followed by the label (UBERON:0002107 liver)
+    TissueType,
+    /// Measurements written as `assay value unit [age]` (e.g., LOINC:2160-0 1.2 UCUM:mg/dL), separated by semicolons
+    Measurement,
+    /// Alleles of the father of the individual (variant keys as in Variant columns, na if none)
+    FatherVariant,
+    /// Alleles of the mother of the individual (variant keys as in Variant columns, na if none)
+    MotherVariant,
     Ignore
 }
 
+impl EtlColumnType {
+    /// The values that are allowed in the transformed cells of columns of this type (in addition to "na"),
+    /// or None if the values are free text or are checked when the row is converted (e.g., laterality)
+    pub fn allowed_values(&self) -> Option<&'static [&'static str]> {
+        match self {
+            EtlColumnType::Sex => Some(&["M", "F", "O", "U"]),
+            EtlColumnType::Deceased => Some(&["yes", "no", "na"]),
+            _ => None,
+        }
+    }
+}
+
+/// A step of the column-by-column transformation, so that the GUI can send several steps in a single call.
+/// Column indices are zero-based.
+#[derive(Clone, Debug, Deserialize, Serialize, PartialEq)]
+#[serde(tag = "type", rename_all = "camelCase", rename_all_fields = "camelCase")]
+pub enum EtlTransform {
+    /// Assign the type of a column (e.g., Sex or AgeOfOnset) whose values were already transformed
+    SetColumnType { col: usize, column_type: EtlColumnType },
+    /// Validate the alleles of a column and replace them by variant keys (this calls Variant Validator)
+    ProcessAlleles { col: usize },
+    /// Column with the values observed, excluded, or na for one HPO term
+    SetSingleHpoTerm { col: usize, term: HpoTermDuplet },
+    /// Column whose values refer to several HPO terms
+    SetMultipleHpoTerms { col: usize, terms: Vec<HpoTermDuplet> },
+    /// Column with the individual identifiers; a previous PatientId column is reset to Raw
+    SetPatientId { col: usize },
+    /// Replace the usual representations of missing data by "na"
+    NormalizeMissingValues,
+}
+
 /// Allowed values for sex
 #[derive(Clone, Debug, Deserialize, Serialize, PartialEq, Eq)]
 #[serde(rename_all = "UPPERCASE")]
@@ -174,6 +232,9 @@ pub struct EtlColumnHeader {
     pub current: Option<String>,
     pub column_type: EtlColumnType,
     pub hpo_terms: Option<Vec<HpoTermDuplet>>,
+    /// For Variant columns of digenic cohorts: the index of the gene in the `gene_transcript_list` of the disease
+    #[serde(default)]
+    pub gene_index: Option<usize>,
 }
 
 impl EtlColumnHeader {
@@ -182,7 +243,8 @@ impl EtlColumnHeader {
             original: original_column_header.to_string(), 
             current: None, 
             column_type: EtlColumnType::Raw, 
-            hpo_terms: None 
+            hpo_terms: None,
+            gene_index: None,
         }
     }
 
@@ -191,7 +253,8 @@ impl EtlColumnHeader {
             original: "HPO Text Mining".to_string(), 
             current: None, 
             column_type: EtlColumnType::HpoTextMining, 
-            hpo_terms: None 
+            hpo_terms: None,
+            gene_index: None,
         }
     }
 }
@@ -251,6 +314,32 @@ pub struct EtlDto {
     pub intergenic_variants: HashMap<String, IntergenicHgvsVariant>,
 }
 
+impl EtlDto {
+    /// The first column of the given type (e.g., the PatientId column)
+    pub fn get_column_by_type(&self, col_type: EtlColumnType) -> Option<&ColumnDto> {
+        self.table.columns.iter().find(|col| col.header.column_type == col_type)
+    }
+
+    /// All columns of the given type (e.g., all SingleHpoTerm columns), in table order
+    pub fn get_columns_by_type(&self, col_type: EtlColumnType) -> Vec<&ColumnDto> {
+        self.table.columns.iter().filter(|col| col.header.column_type == col_type).collect()
+    }
+
+    /// Index of the first column of the given type
+    pub fn get_column_index_by_type(&self, col_type: EtlColumnType) -> Option<usize> {
+        self.table.columns.iter().position(|col| col.header.column_type == col_type)
+    }
+
+    /// Number of rows (individuals), i.e., the number of values in the first column (zero if there are no columns)
+    pub fn row_count(&self) -> usize {
+        self.table.columns.first().map_or(0, |col| col.values.len())
+    }
+
+    pub fn column_count(&self) -> usize {
+        self.table.columns.len()
+    }
+}
+
 
 
 #[cfg(test)]
@@ -299,6 +388,49 @@ mod tests {
         assert_eq!(v.original, "");
     }
 
+    fn column(header: &str, column_type: EtlColumnType, n_rows: usize) -> ColumnDto {
+        let mut col = ColumnDto::new_raw(header, n_rows);
+        col.header.column_type = column_type;
+        col.values = vec![EtlCellValue::new(); n_rows];
+        col
+    }
+
+    fn etl_dto(columns: Vec<ColumnDto>) -> EtlDto {
+        EtlDto {
+            table: ColumnTableDto { file_name: "table.xlsx".to_string(), columns },
+            disease: None,
+            pmid: None,
+            title: None,
+            hgvs_variants: HashMap::new(),
+            structural_variants: HashMap::new(),
+            intergenic_variants: HashMap::new(),
+        }
+    }
+
+    #[test]
+    fn column_access_by_type() {
+        let dto = etl_dto(vec![
+            column("Patient", EtlColumnType::PatientId, 3),
+            column("Seizures", EtlColumnType::SingleHpoTerm, 3),
+            column("Ptosis", EtlColumnType::SingleHpoTerm, 3),
+        ]);
+        assert_eq!("Patient", dto.get_column_by_type(EtlColumnType::PatientId).unwrap().header.original);
+        assert_eq!(Some(1), dto.get_column_index_by_type(EtlColumnType::SingleHpoTerm));
+        let hpo_columns = dto.get_columns_by_type(EtlColumnType::SingleHpoTerm);
+        assert_eq!(vec!["Seizures", "Ptosis"], hpo_columns.iter().map(|c| c.header.original.as_str()).collect::<Vec<_>>());
+        assert!(dto.get_column_by_type(EtlColumnType::Sex).is_none());
+        assert_eq!(None, dto.get_column_index_by_type(EtlColumnType::Sex));
+        assert_eq!(3, dto.row_count());
+        assert_eq!(3, dto.column_count());
+    }
+
+    #[test]
+    fn counts_of_empty_table() {
+        let dto = etl_dto(vec![]);
+        assert_eq!(0, dto.row_count());
+        assert_eq!(0, dto.column_count());
+    }
+
     #[test]
     fn mixed_realistic_excel_input() {
         let v = EtlCellValue::from_string(
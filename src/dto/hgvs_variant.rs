@@ -4,14 +4,14 @@
 //! The information in our implementation is taken from the wonderful
 //! VariantValidator API.
 
-use std::cmp::Ordering;
+use std::{cmp::Ordering, fmt};
 
 use serde::{Deserialize, Serialize};
 
 
 use crate::variant::vcf_var::VcfVar;
 
-#[derive(Clone, Debug, Serialize, Deserialize, PartialEq, Eq)]
+#[derive(Clone, Debug, Serialize, Deserialize, PartialEq)]
 #[serde(rename_all = "camelCase")]
 pub struct HgvsVariant {
     /// Genome build, e.g., hg38
@@ -37,7 +37,19 @@ pub struct HgvsVariant {
     /// Protein level HGVS, if available
     p_hgvs: Option<String>,
     /// Key to specify this variant in the HGVS HashMap of the CohortDto
-    variant_key: String 
+    variant_key: String,
+    /// Sequence Ontology consequence(s) from VEP, e.g., missense_variant
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    consequence: Option<String>,
+    /// VEP impact category (HIGH, MODERATE, LOW, MODIFIER)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    impact: Option<String>,
+    /// Allele frequency in gnomAD, from VEP or from the gnomAD API
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    gnomad_af: Option<f64>,
+    /// Highest allele frequency of the gnomAD genetic ancestry groups
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    gnomad_popmax_af: Option<f64>,
 }
 
 impl HgvsVariant {
@@ -69,7 +81,11 @@ impl HgvsVariant {
             p_hgvs,
             transcript,
             g_hgvs,
-            variant_key: v_key
+            variant_key: v_key,
+            consequence: None,
+            impact: None,
+            gnomad_af: None,
+            gnomad_popmax_af: None,
         }
     }
 
@@ -136,10 +152,51 @@ impl HgvsVariant {
         self.g_hgvs.as_ref()
     }
 
+    pub fn consequence(&self) -> Option<&str> {
+        self.consequence.as_deref()
+    }
+
+    pub fn impact(&self) -> Option<&str> {
+        self.impact.as_deref()
+    }
+
+    pub fn gnomad_af(&self) -> Option<f64> {
+        self.gnomad_af
+    }
+
+    pub fn gnomad_popmax_af(&self) -> Option<f64> {
+        self.gnomad_popmax_af
+    }
+
+    /// Set the allele frequencies retrieved from gnomAD; non-finite values (NaN, inf) are discarded
+    pub fn set_gnomad_frequency(&mut self, gnomad_af: Option<f64>, gnomad_popmax_af: Option<f64>) {
+        self.gnomad_af = gnomad_af.filter(|af| af.is_finite());
+        self.gnomad_popmax_af = gnomad_popmax_af.filter(|af| af.is_finite());
+    }
+
+    /// Set the annotations from Variant Effect Predictor (VEP); existing values are replaced.
+    /// A non-finite frequency (NaN, inf) is discarded.
+    pub fn set_vep_annotation(&mut self, consequence: Option<String>, impact: Option<String>, gnomad_af: Option<f64>) {
+        self.consequence = consequence;
+        self.impact = impact;
+        self.gnomad_af = gnomad_af.filter(|af| af.is_finite());
+    }
+
     pub fn is_x_chromosomal(&self) -> bool {
         return self.chr.contains("X");
     }
 
+    /// Transcript and HGVS expression, e.g., NM_007294.3:c.185_186ins11
+    pub fn to_minimal_string(&self) -> String {
+        format!("{}:{}", self.transcript, self.hgvs)
+    }
+
+    /// Gene symbol, transcript, and HGVS expression, e.g., FBN1(NM_000138.5):c.8242G>T.
+    /// The `hgvs` field already contains the `c.` (or `n.`) prefix.
+    pub fn to_full_string(&self) -> String {
+        format!("{}({}):{}", self.symbol, self.transcript, self.hgvs)
+    }
+
     /// returns a String key that can be used in HashMaps to unambiguously identify this variant
     pub fn variant_key(&self) -> String {
         HgvsVariant::generate_variant_key(self.hgvs(), self.symbol(), self.transcript())
@@ -168,6 +225,9 @@ impl HgvsVariant {
 }
 
 
+/// The setters discard non-finite gnomAD frequencies (and JSON has no NaN), so the derived PartialEq is an equivalence relation
+impl Eq for HgvsVariant {}
+
 /// Sort by chromosome, then SvType, then label (e.g., Ex 5 DEL, )
 impl Ord for HgvsVariant {
     fn cmp(&self, other: &Self) -> Ordering {
@@ -184,12 +244,53 @@ impl PartialOrd for HgvsVariant {
     }
 }
 
+impl fmt::Display for HgvsVariant {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.to_minimal_string())
+    }
+}
+
 
 #[cfg(test)]
 mod tests {
 
     use crate::{dto::variant_dto::VariantDto, variant::hgvs_variant_validator::HgvsVariantValidator};
     use rstest::rstest;
+    use super::HgvsVariant;
+
+    fn variant(symbol: &str, transcript: &str, hgvs: &str) -> HgvsVariant {
+        HgvsVariant::new_from_parts(
+            "hg38".to_string(),
+            "17".to_string(),
+            43124027,
+            "A".to_string(),
+            "G".to_string(),
+            symbol.to_string(),
+            "HGNC:1100".to_string(),
+            hgvs.to_string(),
+            transcript.to_string(),
+            "NC_000017.11:g.43124027A>G".to_string(),
+        )
+    }
+
+    #[rstest]
+    #[case::missense("BRCA1", "NM_007294.4", "c.181T>G")]
+    #[case::nonsense("FBN1", "NM_000138.5", "c.8242G>T")]
+    #[case::frameshift("BRCA1", "NM_007294.3", "c.185_186ins11")]
+    #[case::deletion("BRCA1", "NM_007294.4", "c.68_69del")]
+    fn test_display_strings(#[case] symbol: &str, #[case] transcript: &str, #[case] hgvs: &str) {
+        let var = variant(symbol, transcript, hgvs);
+        assert_eq!(format!("{transcript}:{hgvs}"), var.to_minimal_string());
+        assert_eq!(format!("{symbol}({transcript}):{hgvs}"), var.to_full_string());
+        assert_eq!(var.to_minimal_string(), var.to_string());
+    }
+
+    #[rstest]
+    fn test_full_string_fbn1() {
+        let var = variant("FBN1", "NM_000138.5", "c.8242G>T");
+        assert_eq!("NM_000138.5:c.8242G>T", var.to_minimal_string());
+        assert_eq!("FBN1(NM_000138.5):c.8242G>T", var.to_full_string());
+    }
 
     // test NM_000138.5(FBN1):c.8242G>T (p.Glu2748Ter)
     // We expect to get this back
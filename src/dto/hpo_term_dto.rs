@@ -5,8 +5,16 @@
 //! The HpoTermDuplet structure contains the term id and label and is designed for the header. The HpoTermData structure additionally
 //! has a value and represents the value of an individual with respect to an HPO term (e.g., observed, P32Y2M, etc.)
 
+use std::collections::HashMap;
 use std::str::FromStr;
-use ontolius::TermId;
+use std::sync::{Arc, LazyLock};
+use ontolius::ontology::csr::FullCsrOntology;
+use ontolius::ontology::{HierarchyQueries, OntologyTerms};
+use ontolius::term::MinimalTerm;
+use ontolius::{Identified, TermId};
+use phenopacket_tools::builders::builder::Builder;
+use phenopacket_tools::builders::time_elements::time_element_from_str;
+use phenopackets::schema::v2::core::{OntologyClass, PhenotypicFeature};
 use regex::Regex;
 use serde::{Deserialize, Serialize};
 use std::fmt;
@@ -17,6 +25,44 @@ use crate::age;
 
 static HPO_REGEX: OnceLock<Regex> = OnceLock::new();
 
+/// HPO term Clinical modifier (HP:0012823)
+static CLINICAL_MODIFIER: LazyLock<TermId> = LazyLock::new(|| {
+    "HP:0012823".parse().expect("Failed to parse static HP:0012823")
+});
+/// HPO term Severity (HP:0012824)
+static SEVERITY: LazyLock<TermId> = LazyLock::new(||{
+    "HP:0012824".parse().expect("Failed to parse static HP:0012824")
+});
+/// All valid severity terms
+static SEVERITY_MAP: LazyLock<HashMap<String, OntologyClass>> = LazyLock::new(||{
+    let mut smap = HashMap::new();
+    for (label, id) in [
+        ("Borderline","HP:0012827"),
+        ("Mild", "HP:0012825"),
+        ("Moderate", "HP:0012826"),
+        ("Severe", "HP:0012828"),
+        ("Profound", "HP:0012829")
+    ] {
+        smap.insert(id.to_string(), OntologyClass{id: id.to_string(), label: label.to_string()});
+    }
+    smap
+});
+
+/// The HPO frequency classes (children of Frequency, HP:0040279) that can be used for
+/// population-level annotations, e.g., if a publication reports that a feature is "very frequent".
+pub const HPO_FREQUENCY_TERMS: [(&str, &str); 6] = [
+    ("HP:0040280", "Obligate"),
+    ("HP:0040281", "Very frequent"),
+    ("HP:0040282", "Frequent"),
+    ("HP:0040283", "Occasional"),
+    ("HP:0040284", "Very rare"),
+    ("HP:0040285", "Excluded"),
+];
+
+pub fn is_hpo_frequency_term(hpo_id: &str) -> bool {
+    HPO_FREQUENCY_TERMS.iter().any(|(id, _)| *id == hpo_id)
+}
+
 
 /// A structure to represent an HPO term (id and label) in a simple way
 #[derive(Clone, Debug, Default, Hash, PartialEq, Eq, Serialize, Deserialize)]
@@ -49,6 +95,122 @@ impl HpoTermDuplet {
 
 
 
+/// How confident the curator is about an annotation. Uncertain annotations can be marked for later review
+/// and optionally left out of the exported phenopackets. The order is Low < Medium < High.
+#[derive(Clone, Copy, Debug, Deserialize, Serialize, PartialEq, Eq, PartialOrd, Ord, Hash)]
+#[serde(rename_all = "camelCase")]
+pub enum CurationConfidence {
+    Low,
+    Medium,
+    High,
+}
+
+impl FromStr for CurationConfidence {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.trim().to_ascii_lowercase().as_str() {
+            "high" => Ok(CurationConfidence::High),
+            "medium" => Ok(CurationConfidence::Medium),
+            "low" => Ok(CurationConfidence::Low),
+            _ => Err(format!("Invalid curation confidence '{s}' (expected high, medium, or low)")),
+        }
+    }
+}
+
+
+/// Laterality of an annotation; exported as the corresponding HPO term below Laterality (HP:0012831)
+#[derive(Clone, Copy, Debug, Deserialize, Serialize, PartialEq, Eq, Hash)]
+#[serde(rename_all = "camelCase")]
+pub enum LateralityCode {
+    Bilateral,
+    Unilateral,
+    Right,
+    Left,
+}
+
+impl LateralityCode {
+    pub fn hpo_id(&self) -> &'static str {
+        match self {
+            LateralityCode::Bilateral => "HP:0012832",
+            LateralityCode::Unilateral => "HP:0012833",
+            LateralityCode::Right => "HP:0012834",
+            LateralityCode::Left => "HP:0012835",
+        }
+    }
+
+    pub fn hpo_label(&self) -> &'static str {
+        match self {
+            LateralityCode::Bilateral => "Bilateral",
+            LateralityCode::Unilateral => "Unilateral",
+            LateralityCode::Right => "Right",
+            LateralityCode::Left => "Left",
+        }
+    }
+}
+
+/// Parses the name (e.g., bilateral) or the HPO id (e.g., HP:0012832) of the laterality
+impl FromStr for LateralityCode {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.trim().to_ascii_lowercase().as_str() {
+            "bilateral" | "hp:0012832" => Ok(LateralityCode::Bilateral),
+            "unilateral" | "hp:0012833" => Ok(LateralityCode::Unilateral),
+            "right" | "hp:0012834" => Ok(LateralityCode::Right),
+            "left" | "hp:0012835" => Ok(LateralityCode::Left),
+            _ => Err(format!("Invalid laterality '{s}' (expected bilateral, unilateral, right, or left)")),
+        }
+    }
+}
+
+
+/// Severity of an annotation; exported as the severity of the phenotypic feature (a term below Severity, HP:0012824)
+#[derive(Clone, Copy, Debug, Deserialize, Serialize, PartialEq, Eq, Hash)]
+#[serde(rename_all = "camelCase")]
+pub enum SeverityCode {
+    Mild,
+    Moderate,
+    Severe,
+    Profound,
+}
+
+impl SeverityCode {
+    pub fn hpo_id(&self) -> &'static str {
+        match self {
+            SeverityCode::Mild => "HP:0012825",
+            SeverityCode::Moderate => "HP:0012826",
+            SeverityCode::Severe => "HP:0012828",
+            SeverityCode::Profound => "HP:0012829",
+        }
+    }
+
+    pub fn hpo_label(&self) -> &'static str {
+        match self {
+            SeverityCode::Mild => "Mild",
+            SeverityCode::Moderate => "Moderate",
+            SeverityCode::Severe => "Severe",
+            SeverityCode::Profound => "Profound",
+        }
+    }
+}
+
+/// Parses the name (e.g., severe) or the HPO id (e.g., HP:0012828) of the severity
+impl FromStr for SeverityCode {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.trim().to_ascii_lowercase().as_str() {
+            "mild" | "hp:0012825" => Ok(SeverityCode::Mild),
+            "moderate" | "hp:0012826" => Ok(SeverityCode::Moderate),
+            "severe" | "hp:0012828" => Ok(SeverityCode::Severe),
+            "profound" | "hp:0012829" => Ok(SeverityCode::Profound),
+            _ => Err(format!("Invalid severity '{s}' (expected mild, moderate, severe, or profound)")),
+        }
+    }
+}
+
+
 #[derive(Clone, Debug, Deserialize, Serialize, PartialEq, Eq)]
 #[serde(rename_all = "camelCase")]
 pub struct CellValue {
@@ -59,6 +221,17 @@ pub struct CellValue {
     // 👇 Safely defaults to empty if missing from old files
     #[serde(default)]
     pub modifiers: Vec<String>,
+
+    /// Curation confidence of the annotation (not shown in the text representation)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub confidence: Option<CurationConfidence>,
+
+    /// Laterality of the annotation (not shown in the text representation)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub laterality: Option<LateralityCode>,
+    /// Severity of the annotation (not shown in the text representation)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub severity: Option<SeverityCode>,
 }
 
 /// HpoCell will automatically act like a CellValue when you 
@@ -76,25 +249,68 @@ impl From<CellValueInner> for CellValue {
         CellValue {
             entry,
             modifiers: Vec::new(),
+            confidence: None,
+            laterality: None,
+            severity: None,
         }
     }
 }
 
+/// Parses the output of `Display`, i.e., the entry optionally followed by a semicolon and
+/// a comma-separated list of modifiers (e.g., `observed;HP:0012828`).
 impl FromStr for CellValue {
     type Err = String;
 
     fn from_str(s: &str) -> Result<Self, Self::Err> {
-       let cv_inner = match s {
-            "observed" => Ok(CellValueInner::Observed),
-            "excluded" => Ok(CellValueInner::Excluded),
-            "na" => Ok(CellValueInner::Na),
-            _ if age::is_valid_age_string(s) =>  Ok(CellValueInner::OnsetAge(s.to_string())),
-            _ => Err(format!("Malformed HPO cell contents: '{s}'")),
-        };
-        Ok(CellValue { entry: cv_inner?, modifiers: Vec::default()})
+        match s.split_once(';') {
+            Some((entry_part, modifiers_part)) => Ok(CellValue { 
+                entry: CellValueInner::from_str(entry_part)?, 
+                modifiers: parse_modifiers(modifiers_part)?,
+                confidence: None,
+                laterality: None,
+                severity: None,
+            }),
+            None => Ok(CellValue {
+                entry: CellValueInner::from_str(s)?,
+                modifiers: Vec::default(),
+                confidence: None,
+                laterality: None,
+                severity: None,
+            }),
+        }
     }
 }
 
+impl PartialEq<str> for CellValue {
+    fn eq(&self, other: &str) -> bool {
+        self.to_string() == other
+    }
+}
+
+impl PartialEq<&str> for CellValue {
+    fn eq(&self, other: &&str) -> bool {
+        self.to_string() == *other
+    }
+}
+
+/// Parse a comma-separated list of modifier/severity term ids (e.g., `HP:0012828,HP:0012832`)
+fn parse_modifiers(modifiers_part: &str) -> Result<Vec<String>, String> {
+    let re = HPO_REGEX.get_or_init(|| {
+        Regex::new(r"^HP:\d{7}$").unwrap()
+    });
+    let mut modifiers = Vec::new();
+    for s in modifiers_part.split(',') {
+        if s.is_empty() {
+            continue;
+        }
+        if !re.is_match(s) {
+            return Err(format!("Invalid HPO modifier/severity term id: '{}'", s));
+        }
+        modifiers.push(s.to_string());
+    }
+    Ok(modifiers)
+}
+
 
 #[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
 #[serde(tag = "type", content = "data")] 
@@ -103,6 +319,8 @@ pub enum CellValueInner {
     Excluded,
     Na,
     OnsetAge(String),   // e.g. "P10Y"
+    /// Population-level annotation with an HPO frequency class, e.g., HP:0040281 (Very frequent)
+    HpoFrequency(String),
 }
 
 impl CellValueInner {
@@ -122,12 +340,29 @@ impl CellValueInner {
        ! matches!(self, CellValueInner::Na)
     }
 
+    pub fn is_hpo_frequency(&self) -> bool {
+        matches!(self, CellValueInner::HpoFrequency(_))
+    }
+
+    /// True if the term was observed in the individual, with or without an onset age. Population-level
+    /// frequencies are not observations of an individual (not even "Excluded (0%)") and are never counted.
+    pub fn is_present(&self) -> bool {
+        matches!(self, CellValueInner::Observed | CellValueInner::OnsetAge(_))
+    }
+
+    /// True if the term was observed (see [`Self::is_present`]) or excluded in the individual,
+    /// i.e., if the cell counts for the denominator of a frequency
+    pub fn is_measured(&self) -> bool {
+        self.is_present() || self.is_excluded()
+    }
+
     pub fn is_valid_cell_value(s: &str) -> bool {
           match s {
             "observed" => true,
             "excluded" => true,
             "na" => true,
             _ if age::is_valid_age_string(s) =>  true,
+            _ if is_hpo_frequency_term(s) => true,
             _ => false,
         }
     }
@@ -143,6 +378,7 @@ impl FromStr for CellValueInner {
             "excluded" => Ok(CellValueInner::Excluded),
             "na" => Ok(CellValueInner::Na),
             _ if age::is_valid_age_string(s) =>  Ok(CellValueInner::OnsetAge(s.to_string())),
+            _ if is_hpo_frequency_term(s) => Ok(CellValueInner::HpoFrequency(s.to_string())),
             _ => Err(format!("Malformed HPO cell contents: '{s}'")),
         }
     }
@@ -155,6 +391,7 @@ impl fmt::Display for CellValueInner {
             CellValueInner::Excluded => write!(f, "excluded"),
             CellValueInner::Na => write!(f, "na"),
             CellValueInner::OnsetAge(age) => write!(f, "{}", age),
+            CellValueInner::HpoFrequency(freq) => write!(f, "{}", freq),
         }
     }
 }
@@ -166,13 +403,13 @@ impl fmt::Display for CellValue {
             CellValueInner::Excluded => "excluded".to_string(),
             CellValueInner::Na => "na".to_string(),
             CellValueInner::OnsetAge(age) => age.clone(),
+            CellValueInner::HpoFrequency(freq) => freq.clone(),
         };
         if self.modifiers.is_empty() {
             write!(f, "{}", entry_str)
         } else {
-            // Joins ["mod1", "mod2"] into "mod1, mod2"
-            let mods_str = self.modifiers.join(", "); 
-            write!(f, "{} ({})", entry_str, mods_str)
+            // Same syntax as the input, so that the string can be parsed again, e.g., "observed;HP:0012828,HP:0012832"
+            write!(f, "{};{}", entry_str, self.modifiers.join(","))
         }
     }
 }
@@ -184,6 +421,7 @@ impl CellValue {
             CellValueInner::Excluded => "excluded",
             CellValueInner::Na => "na",
             CellValueInner::OnsetAge(onset) => onset.as_str(),
+            CellValueInner::HpoFrequency(freq) => freq.as_str(),
         }
     }
 }   
@@ -196,6 +434,9 @@ impl CellValue {
         Self {
             entry: CellValueInner::Na,
             modifiers: Vec::default(),
+            confidence: None,
+            laterality: None,
+            severity: None,
         }
     }
 
@@ -203,6 +444,9 @@ impl CellValue {
         Self {
             entry: CellValueInner::Observed,
             modifiers: Vec::default(),
+            confidence: None,
+            laterality: None,
+            severity: None,
         }
     }
 
@@ -210,6 +454,9 @@ impl CellValue {
         Self {
             entry: CellValueInner::Excluded,
             modifiers: Vec::default(),
+            confidence: None,
+            laterality: None,
+            severity: None,
         }
     }
 
@@ -217,6 +464,9 @@ impl CellValue {
         Self {
             entry: CellValueInner::OnsetAge(onset),
             modifiers: Vec::default(),
+            confidence: None,
+            laterality: None,
+            severity: None,
         }
     }
 
@@ -229,37 +479,84 @@ impl CellValue {
     /// modifier/severity terms separated by commas (",")
     /// For instance, this is observed and severe: 'observed;HP:0012828'
     pub fn from_string(cell_contents: impl Into<String>) -> Result<Self, String> {
-        let re = HPO_REGEX.get_or_init(|| {
-            Regex::new(r"^HP:\d{7}$").unwrap()
-        });
         let input = cell_contents.into();
         let Some((entry_part, modifiers_part)) = input.split_once(';') else {
             return Ok(Self::onset(input));
         };
-        let mut modifiers = Vec::new();
-        for s in modifiers_part.split(',') {
-            if s.is_empty() {
-                continue;
-            }
-            if !re.is_match(s) {
-                return Err(format!("Invalid HPO modifier/severity term id: '{}'", s));
-            }
-            modifiers.push(s.to_string());
-        }
+        let modifiers = parse_modifiers(modifiers_part)?;
         let entry = match entry_part {
             "observed" => CellValueInner::Observed,
             _ => CellValueInner::OnsetAge(entry_part.to_string()),
         };
-        Ok(Self { entry, modifiers })
+        Ok(Self { entry, modifiers, confidence: None, laterality: None, severity: None })
     }
 
     pub fn has_modifier(&self) -> bool {
         ! self.modifiers.is_empty()
     }
 
+    /// The severity can be given by `severity` or by a severity term in the modifiers (e.g., HP:0012828 for
+    /// `observed;HP:0012828`). Returns an error if the cell has more than one distinct severity.
+    pub fn check_severity(&self) -> Result<(), String> {
+        let mut severity_ids: Vec<&str> = self.modifiers
+            .iter()
+            .map(String::as_str)
+            .filter(|id| SEVERITY_MAP.contains_key(*id))
+            .chain(self.severity.map(|code| code.hpo_id()))
+            .collect();
+        severity_ids.sort();
+        severity_ids.dedup();
+        if severity_ids.len() > 1 {
+            return Err(format!("Conflicting severities in cell '{}': {}", self, severity_ids.join(", ")));
+        }
+        Ok(())
+    }
+
+    /// The age of onset (e.g., P10Y or Childhood onset), without copying
+    pub fn as_str(&self) -> Option<&str> {
+        match &self.entry {
+            CellValueInner::OnsetAge(onset) => Some(onset.as_str()),
+            _ => None,
+        }
+    }
+
     pub fn modifers(&self) -> &[String] {
         &self.modifiers
     }
+
+    /// Rank of the entry in the hierarchy OnsetAge > Observed (or population frequency) > Excluded > Na
+    fn information_rank(&self) -> u8 {
+        match &self.entry {
+            CellValueInner::Na => 0,
+            CellValueInner::Excluded => 1,
+            CellValueInner::Observed | CellValueInner::HpoFrequency(_) => 2,
+            CellValueInner::OnsetAge(_) => 3,
+        }
+    }
+
+    /// Choose the more informative of two values for the same HPO term (OnsetAge > Observed > Excluded > Na).
+    /// If both are onsets, the earlier one is chosen; if the onsets cannot be compared or the values are
+    /// equally informative, `a` is chosen. If the result is observed or an onset, the modifiers of both are kept.
+    pub fn most_informative(a: &CellValue, b: &CellValue) -> CellValue {
+        let choose_b = match (a.as_str(), b.as_str()) {
+            (Some(onset_a), Some(onset_b)) => {
+                match (age::onset_to_years(onset_a), age::onset_to_years(onset_b)) {
+                    (Ok(years_a), Ok(years_b)) => years_b < years_a,
+                    _ => false,
+                }
+            }
+            _ => b.information_rank() > a.information_rank(),
+        };
+        let (mut chosen, other) = if choose_b { (b.clone(), a) } else { (a.clone(), b) };
+        if chosen.is_present() {
+            for modifier in &other.modifiers {
+                if !chosen.modifiers.contains(modifier) {
+                    chosen.modifiers.push(modifier.clone());
+                }
+            }
+        }
+        chosen
+    }
 }
 
 
@@ -280,6 +577,7 @@ impl HpoTermData {
         term_duplet: HpoTermDuplet,
         entry: CellValue
     ) -> Result<Self, String> {
+        entry.check_severity()?;
         Ok(Self { 
             term_duplet: term_duplet,
             entry,
@@ -292,7 +590,7 @@ impl HpoTermData {
     ) -> Result<Self, String> {
         Ok(Self { 
             term_duplet: duplet, 
-            entry: CellValue::from_str(entry)?
+            entry: CellValue::from_str(entry)?,
         })
     }
 
@@ -360,6 +658,144 @@ impl HpoTermData {
        ! self.entry.modifiers.is_empty()
     }
 
+    pub fn has_modifiers(&self) -> bool {
+        self.has_modifier()
+    }
+
+    /// Add a modifier (e.g., Severe, HP:0012828) to the annotation
+    pub fn with_modifier(mut self, modifier_id: String) -> Self {
+        self.entry.modifiers.push(modifier_id);
+        self
+    }
+
+    pub fn with_modifiers(mut self, modifier_ids: Vec<String>) -> Self {
+        self.entry.modifiers.extend(modifier_ids);
+        self
+    }
+
+    /// Curation confidence of the annotation (stored in the cell value)
+    pub fn confidence(&self) -> Option<CurationConfidence> {
+        self.entry.confidence
+    }
+
+    /// Mark the curation confidence of the annotation; it is kept in the cell value of the cohort row
+    pub fn with_confidence(mut self, confidence: CurationConfidence) -> Self {
+        self.entry.confidence = Some(confidence);
+        self
+    }
+
+    /// Laterality of the annotation (stored in the cell value)
+    pub fn laterality(&self) -> Option<LateralityCode> {
+        self.entry.laterality
+    }
+
+    /// Mark the laterality of the annotation (e.g., Bilateral); it is exported as a modifier
+    pub fn with_laterality(mut self, code: LateralityCode) -> Self {
+        self.entry.laterality = Some(code);
+        self
+    }
+
+    /// Severity of the annotation (stored in the cell value)
+    pub fn severity(&self) -> Option<SeverityCode> {
+        self.entry.severity
+    }
+
+    /// Mark the severity of the annotation (e.g., Severe); it is exported as the severity of the feature
+    pub fn with_severity(mut self, code: SeverityCode) -> Self {
+        self.entry.severity = Some(code);
+        self
+    }
+
+    /// The value to store in the cohort row, including the curation confidence, the laterality, and the severity
+    pub fn cell_value(&self) -> CellValue {
+        self.entry.clone()
+    }
+
+    /// Create the GA4GH PhenotypicFeature for this annotation. Excluded terms are exported with `excluded`
+    /// set, onsets as a TimeElement, and modifiers as severity or (for descendants of Clinical modifier) modifiers.
+    /// The laterality is added to the modifiers unless it is already one of them. The severity may be given either
+    /// as `severity` or as a severity term in the modifiers; cells in which the two disagree result in an error.
+    /// Not ascertained (na) terms and population frequencies cannot be represented and result in an error.
+    pub fn to_phenotypic_feature(&self, hpo: Arc<FullCsrOntology>) -> Result<PhenotypicFeature, String> {
+        if self.is_not_ascertained() || self.entry.is_hpo_frequency() {
+            return Err(format!("Cannot create PhenotypicFeature for {} ({}) with value '{}'",
+                self.label(), self.term_id(), self.entry));
+        }
+        self.entry.check_severity()
+            .map_err(|e| format!("{} ({}): {e}", self.label(), self.term_id()))?;
+        let mut pf = PhenotypicFeature {
+            description: String::default(),
+            r#type: Some(self.ontology_class(&hpo)?),
+            excluded: self.is_excluded(),
+            severity: None,
+            modifiers: vec![],
+            onset: None,
+            resolution: None,
+            evidence: vec![]
+        };
+        if let Some(onset) = self.onset_value() {
+            let onset = &age::resolve_age(onset, None)?;
+            let ost = time_element_from_str(onset)
+                .map_err(|e| format!("malformed time_element for cell '{}': {}", self.entry, e))?;
+            pf.onset = Some(ost);
+        }
+        for mod_str in self.entry.modifers() {
+            let mod_id: TermId = mod_str.parse().map_err(|_| format!("Could not create TermId from modifier String '{mod_str}'"))?;
+            let term = hpo.term_by_id(&mod_id)
+                .ok_or_else(|| format!("Could not retrieve Modifier term for id '{mod_id}'"))?;
+            if let Some(severity_term) = SEVERITY_MAP.get(mod_str) {
+                pf.severity = Some(severity_term.clone());
+            } else if hpo.is_descendant_of(term.identifier(), &*CLINICAL_MODIFIER) {
+                pf.modifiers.push(OntologyClass {
+                    id: mod_str.clone(),
+                    label: term.name().to_string(),
+                });
+            }
+        }
+        if let Some(laterality) = self.entry.laterality {
+            if !pf.modifiers.iter().any(|m| m.id == laterality.hpo_id()) {
+                pf.modifiers.push(OntologyClass {
+                    id: laterality.hpo_id().to_string(),
+                    label: laterality.hpo_label().to_string(),
+                });
+            }
+        }
+        if let Some(severity) = self.entry.severity {
+            pf.severity = Some(OntologyClass {
+                id: severity.hpo_id().to_string(),
+                label: severity.hpo_label().to_string(),
+            });
+        }
+        Ok(pf)
+    }
+
+    /// OntologyClass for the HPO term; the id must be the primary id of a term in the current HPO
+    fn ontology_class(&self, hpo: &FullCsrOntology) -> Result<OntologyClass, String> {
+        let hpo_term_id = self.ontolius_term_id()?;
+        let hpo_term = hpo.term_by_id(&hpo_term_id)
+            .ok_or_else(|| format!("Could not find HPO term for {}", self.term_id()))?;
+        if hpo_term.identifier() != &hpo_term_id {
+            return Err(format!("{} is not the primary id ({}) for {}",
+                hpo_term_id, hpo_term.identifier(), self.label()));
+        }
+        Builder::ontology_class(self.term_id(), self.label())
+            .map_err(|_| format!("termid_parse_error '{:?}'", self.term_duplet))
+    }
+
+    /// Merge two annotations of the same HPO term (e.g., from two columns of a source table),
+    /// keeping the most informative value (see [`CellValue::most_informative`]).
+    pub fn merge_with(&self, other: &HpoTermData) -> Result<HpoTermData, String> {
+        if self.term_id() != other.term_id() {
+            return Err(format!("Cannot merge annotations of different HPO terms: {} and {}",
+                self.term_id(), other.term_id()));
+        }
+        let entry = CellValue::most_informative(&self.entry, &other.entry);
+        Ok(Self {
+            term_duplet: self.term_duplet.clone(),
+            entry,
+        })
+    }
+
 }
 
 
@@ -367,6 +803,7 @@ impl HpoTermData {
 mod test {
     use super::*;
     use rstest::rstest;
+    use crate::test_utils::fixtures::hpo;
 
 
 
@@ -457,9 +894,38 @@ mod test {
             CellValueInner::Excluded => assert_eq!(entry, "excluded"),
             CellValueInner::Na => assert_eq!(entry, "na"),
             CellValueInner::OnsetAge(ref age) => assert_eq!(entry, age),
+            CellValueInner::HpoFrequency(ref freq) => assert_eq!(entry, freq),
         }
     }
 
+    #[rstest]
+    #[case("HP:0040280")]
+    #[case("HP:0040281")]
+    #[case("HP:0040285")]
+    fn test_hpo_frequency_roundtrip(#[case] curie: &str) {
+        let cell = CellValue::from_str(curie).unwrap();
+        assert!(cell.is_hpo_frequency());
+        assert!(cell.is_ascertained());
+        assert!(!cell.is_observed());
+        // population-level frequencies are not counted as observations of an individual
+        assert!(!cell.is_present());
+        assert!(!cell.is_measured());
+        assert_eq!(curie, cell.to_string());
+        let json = serde_json::to_value(&cell).unwrap();
+        assert_eq!(serde_json::json!({"type": "HpoFrequency", "data": curie, "modifiers": []}), json);
+        let deserialized: CellValue = serde_json::from_value(json).unwrap();
+        assert_eq!(cell, deserialized);
+    }
+
+    /// Only the frequency classes can be used as cell values, not arbitrary HPO terms
+    #[rstest]
+    #[case("HP:0040279")]
+    #[case("HP:0001166")]
+    fn test_not_a_frequency_class(#[case] curie: &str) {
+        assert!(CellValue::from_str(curie).is_err());
+        assert!(!CellValueInner::is_valid_cell_value(curie));
+    }
+
     /// Our EtlDto will include cells that have JSON content similar to the below
     /// Here we just perform a sanity test.
     #[rstest]
@@ -480,10 +946,10 @@ mod test {
     fn test_modifier() {
         // Severe  HP:0012828
         let duplet = HpoTermDuplet::new("Cardiomyopathy", "HP:0001638");
-        let cval = CellValue{ entry: CellValueInner::Observed, modifiers: vec!["HP:0012828".to_string()]  };
+        let cval = CellValue{ entry: CellValueInner::Observed, modifiers: vec!["HP:0012828".to_string()], confidence: None, laterality: None, severity: None };
         let hpo_data = HpoTermData { 
             term_duplet: duplet, 
-            entry: cval
+            entry: cval,
         };
         assert!(hpo_data.has_modifier());
         let modfr = hpo_data.modifiers();
@@ -492,6 +958,74 @@ mod test {
     }
 
 
+    #[rstest]
+    fn test_with_modifiers() {
+        let duplet = HpoTermDuplet::new("Cardiomyopathy", "HP:0001638");
+        let hpo_data = HpoTermData::new(duplet, CellValue::observed()).unwrap();
+        assert!(!hpo_data.has_modifiers());
+        let hpo_data = hpo_data
+            .with_modifier("HP:0012828".to_string())
+            .with_modifiers(vec!["HP:0012832".to_string(), "HP:0031375".to_string()]);
+        assert!(hpo_data.has_modifiers());
+        assert_eq!(vec!["HP:0012828", "HP:0012832", "HP:0031375"], hpo_data.modifiers());
+        let json = serde_json::to_value(&hpo_data).unwrap();
+        assert_eq!(serde_json::json!({
+            "termDuplet": { "hpoLabel": "Cardiomyopathy", "hpoId": "HP:0001638" },
+            "entry": { "type": "Observed", "modifiers": ["HP:0012828", "HP:0012832", "HP:0031375"] }
+        }), json);
+    }
+
+    /// Display and FromStr must round-trip for every kind of cell value
+    #[rstest]
+    #[case("observed")]
+    #[case("excluded")]
+    #[case("na")]
+    #[case("P10Y")]
+    #[case("P2Y3M")]
+    #[case("Childhood onset")]
+    #[case("Congenital onset")]
+    #[case("HP:0040282")]
+    #[case("observed;HP:0012828")]
+    #[case("P2Y;HP:0012825,HP:0012832")]
+    #[case("Childhood onset;HP:0012828")]
+    fn test_cell_value_roundtrip(#[case] input: &str) {
+        let cell = CellValue::from_str(input).unwrap();
+        assert_eq!(input, cell.to_string());
+        assert_eq!(cell, CellValue::from_str(&cell.to_string()).unwrap());
+        assert_eq!(cell, input);
+    }
+
+    #[rstest]
+    fn test_roundtrip_from_struct() {
+        let cells = vec![
+            CellValue::observed(),
+            CellValue::excluded(),
+            CellValue::na(),
+            CellValue::onset("Childhood onset".to_string()),
+            CellValueInner::HpoFrequency("HP:0040280".to_string()).into(),
+            CellValue { entry: CellValueInner::Observed, modifiers: vec!["HP:0012828".to_string(), "HP:0012832".to_string()], confidence: None, laterality: None, severity: None },
+        ];
+        for cell in cells {
+            assert_eq!(cell, CellValue::from_str(&cell.to_string()).unwrap());
+        }
+    }
+
+    #[rstest]
+    fn test_as_str() {
+        assert_eq!(Some("Childhood onset"), CellValue::from_str("Childhood onset").unwrap().as_str());
+        assert_eq!(Some("P2Y"), CellValue::from_str("P2Y;HP:0012825").unwrap().as_str());
+        assert_eq!(None, CellValue::observed().as_str());
+        assert_eq!(None, CellValue::na().as_str());
+    }
+
+    #[rstest]
+    #[case("observed;severe")]
+    #[case("observed;HP:12828")]
+    #[case("unknown;HP:0012828")]
+    fn test_malformed_modifiers(#[case] input: &str) {
+        assert!(CellValue::from_str(input).is_err());
+    }
+
     /// Severe  HP:0012828
     /// Mild HP:0012825
     #[rstest]
@@ -516,6 +1050,226 @@ mod test {
         assert_eq!(result.modifiers, expected_modifiers);
     }
 
- 
+    #[rstest]
+    #[case("na", "na", "na")]
+    #[case("na", "excluded", "excluded")]
+    #[case("na", "observed", "observed")]
+    #[case("na", "P2Y", "P2Y")]
+    #[case("excluded", "na", "excluded")]
+    #[case("excluded", "excluded", "excluded")]
+    #[case("excluded", "observed", "observed")]
+    #[case("excluded", "P2Y", "P2Y")]
+    #[case("observed", "na", "observed")]
+    #[case("observed", "excluded", "observed")]
+    #[case("observed", "observed", "observed")]
+    #[case("observed", "P2Y", "P2Y")]
+    #[case("P2Y", "na", "P2Y")]
+    #[case("P2Y", "excluded", "P2Y")]
+    #[case("P2Y", "observed", "P2Y")]
+    #[case("P2Y", "P2Y", "P2Y")]
+    #[case("P2Y", "P1Y6M", "P1Y6M")]
+    #[case("P1Y6M", "P2Y", "P1Y6M")]
+    #[case("Childhood onset", "Congenital onset", "Congenital onset")]
+    #[case("P3Y", "Childhood onset", "Childhood onset")]
+    #[case("Infantile onset", "P3M", "Infantile onset")]
+    #[case("G30w", "Congenital onset", "G30w")]
+    #[case("HP:0040282", "excluded", "HP:0040282")]
+    fn test_most_informative(#[case] a: &str, #[case] b: &str, #[case] expected: &str) {
+        let a = CellValue::from_str(a).unwrap();
+        let b = CellValue::from_str(b).unwrap();
+        assert_eq!(expected, CellValue::most_informative(&a, &b).to_string());
+    }
+
+    #[rstest]
+    #[case("observed;HP:0012828", "P2Y;HP:0012832", "P2Y;HP:0012832,HP:0012828")]
+    #[case("observed;HP:0012828", "observed;HP:0012828", "observed;HP:0012828")]
+    #[case("excluded", "observed;HP:0012828", "observed;HP:0012828")]
+    fn test_most_informative_modifiers(#[case] a: &str, #[case] b: &str, #[case] expected: &str) {
+        let a = CellValue::from_str(a).unwrap();
+        let b = CellValue::from_str(b).unwrap();
+        assert_eq!(expected, CellValue::most_informative(&a, &b).to_string());
+    }
+
+    #[rstest]
+    #[case("observed", false)]
+    #[case("excluded", true)]
+    fn test_phenotypic_feature_without_onset(hpo: Arc<FullCsrOntology>, #[case] entry: &str, #[case] excluded: bool) {
+        let data = HpoTermData::from_str("HP:0001166", "Arachnodactyly", entry).unwrap();
+        let pf = data.to_phenotypic_feature(hpo).unwrap();
+        let term = pf.r#type.unwrap();
+        assert_eq!("HP:0001166", term.id);
+        assert_eq!("Arachnodactyly", term.label);
+        assert_eq!(excluded, pf.excluded);
+        assert!(pf.onset.is_none());
+    }
+
+    #[rstest]
+    #[case("na")]
+    #[case("HP:0040282")]
+    fn test_phenotypic_feature_not_representable(hpo: Arc<FullCsrOntology>, #[case] entry: &str) {
+        let data = HpoTermData::from_str("HP:0001166", "Arachnodactyly", entry).unwrap();
+        assert!(data.to_phenotypic_feature(hpo).is_err());
+    }
+
+    #[rstest]
+    #[case("P3Y")]
+    #[case("Childhood onset")]
+    fn test_phenotypic_feature_with_onset(hpo: Arc<FullCsrOntology>, #[case] onset: &str) {
+        let data = HpoTermData::from_str("HP:0001166", "Arachnodactyly", onset).unwrap();
+        let pf = data.to_phenotypic_feature(hpo).unwrap();
+        assert!(!pf.excluded);
+        assert_eq!(Some(time_element_from_str(onset).unwrap()), pf.onset);
+    }
+
+    #[rstest]
+    fn test_phenotypic_feature_modifiers(hpo: Arc<FullCsrOntology>) {
+        let data = HpoTermData::from_str("HP:0001166", "Arachnodactyly", "P3Y;HP:0012828,HP:0012832").unwrap();
+        let pf = data.to_phenotypic_feature(hpo).unwrap();
+        assert_eq!("HP:0012828", pf.severity.unwrap().id);
+        assert_eq!(1, pf.modifiers.len());
+        assert_eq!("HP:0012832", pf.modifiers[0].id);
+        assert_eq!("Bilateral", pf.modifiers[0].label);
+    }
+
+    #[rstest]
+    fn test_confidence_serialization() {
+        let data = HpoTermData::from_str("HP:0001166", "Arachnodactyly", "observed").unwrap()
+            .with_confidence(CurationConfidence::Low);
+        let json = serde_json::to_value(&data).unwrap();
+        // the confidence is only stored in the cell value
+        assert!(json.get("confidence").is_none());
+        assert_eq!("low", json["entry"]["confidence"]);
+        assert_eq!(Some(CurationConfidence::Low), data.confidence());
+        let without: HpoTermData = serde_json::from_value(serde_json::json!({
+            "termDuplet": { "hpoLabel": "Arachnodactyly", "hpoId": "HP:0001166" },
+            "entry": { "type": "Observed" }
+        })).unwrap();
+        assert_eq!(None, without.confidence());
+        assert!(serde_json::to_value(&without).unwrap()["entry"].get("confidence").is_none());
+    }
+
+    #[rstest]
+    #[case("high", CurationConfidence::High)]
+    #[case("Medium", CurationConfidence::Medium)]
+    #[case(" LOW ", CurationConfidence::Low)]
+    fn test_confidence_from_str(#[case] input: &str, #[case] expected: CurationConfidence) {
+        assert_eq!(expected, CurationConfidence::from_str(input).unwrap());
+        assert!(CurationConfidence::Low < CurationConfidence::Medium);
+        assert!(CurationConfidence::from_str("certain").is_err());
+    }
 
+    #[rstest]
+    fn test_merge_with() {
+        let first = HpoTermData::from_str("HP:0001166", "Arachnodactyly", "observed").unwrap();
+        let second = HpoTermData::from_str("HP:0001166", "Arachnodactyly", "Childhood onset").unwrap();
+        let merged = first.merge_with(&second).unwrap();
+        assert_eq!("HP:0001166", merged.term_id());
+        assert_eq!(Some("Childhood onset"), merged.onset_value());
+        let other_term = HpoTermData::from_str("HP:0001631", "Atrial septal defect", "observed").unwrap();
+        assert!(first.merge_with(&other_term).is_err());
+    }
+
+    #[rstest]
+    #[case("bilateral", LateralityCode::Bilateral, "HP:0012832")]
+    #[case("Unilateral", LateralityCode::Unilateral, "HP:0012833")]
+    #[case(" RIGHT ", LateralityCode::Right, "HP:0012834")]
+    #[case("HP:0012835", LateralityCode::Left, "HP:0012835")]
+    fn test_laterality_from_str(#[case] input: &str, #[case] expected: LateralityCode, #[case] hpo_id: &str) {
+        let code = LateralityCode::from_str(input).unwrap();
+        assert_eq!(expected, code);
+        assert_eq!(hpo_id, code.hpo_id());
+        assert!(LateralityCode::from_str("both").is_err());
+    }
+
+    #[rstest]
+    #[case(LateralityCode::Bilateral)]
+    #[case(LateralityCode::Unilateral)]
+    #[case(LateralityCode::Right)]
+    #[case(LateralityCode::Left)]
+    fn test_laterality_is_exported_as_modifier(hpo: Arc<FullCsrOntology>, #[case] code: LateralityCode) {
+        let data = HpoTermData::from_str("HP:0001166", "Arachnodactyly", "observed").unwrap()
+            .with_laterality(code);
+        assert_eq!(Some(code), data.cell_value().laterality);
+        assert_eq!(Some(code), data.laterality());
+        let pf = data.to_phenotypic_feature(hpo.clone()).unwrap();
+        assert_eq!(1, pf.modifiers.len());
+        assert_eq!(code.hpo_id(), pf.modifiers[0].id);
+        assert_eq!(code.hpo_label(), pf.modifiers[0].label);
+        // the label of the HPO term must match the one we export
+        let term = hpo.term_by_id(&code.hpo_id().parse::<TermId>().unwrap()).unwrap();
+        assert_eq!(term.name(), code.hpo_label());
+    }
+
+    #[rstest]
+    fn test_laterality_is_not_duplicated(hpo: Arc<FullCsrOntology>) {
+        let data = HpoTermData::from_str("HP:0001166", "Arachnodactyly", "observed;HP:0012832").unwrap()
+            .with_laterality(LateralityCode::Bilateral);
+        let pf = data.to_phenotypic_feature(hpo).unwrap();
+        assert_eq!(1, pf.modifiers.len());
+    }
+
+    #[rstest]
+    fn test_laterality_serialization() {
+        let mut cell = CellValue::observed();
+        cell.laterality = Some(LateralityCode::Left);
+        let json = serde_json::to_value(&cell).unwrap();
+        assert_eq!(serde_json::json!("left"), json["laterality"]);
+        let restored: CellValue = serde_json::from_value(json).unwrap();
+        assert_eq!(cell, restored);
+        assert!(serde_json::to_value(CellValue::observed()).unwrap().get("laterality").is_none());
+    }
+
+    #[rstest]
+    #[case("mild", SeverityCode::Mild)]
+    #[case("Moderate", SeverityCode::Moderate)]
+    #[case(" SEVERE ", SeverityCode::Severe)]
+    #[case("HP:0012829", SeverityCode::Profound)]
+    fn test_severity_from_str(#[case] input: &str, #[case] expected: SeverityCode) {
+        assert_eq!(expected, SeverityCode::from_str(input).unwrap());
+        assert!(SeverityCode::from_str("very severe").is_err());
+    }
+
+    #[rstest]
+    #[case(SeverityCode::Mild)]
+    #[case(SeverityCode::Moderate)]
+    #[case(SeverityCode::Severe)]
+    #[case(SeverityCode::Profound)]
+    fn test_severity_is_exported(hpo: Arc<FullCsrOntology>, #[case] code: SeverityCode) {
+        let data = HpoTermData::from_str("HP:0001166", "Arachnodactyly", "observed").unwrap()
+            .with_severity(code);
+        assert_eq!(Some(code), data.cell_value().severity);
+        assert_eq!(Some(code), data.severity());
+        let pf = data.to_phenotypic_feature(hpo.clone()).unwrap();
+        let severity = pf.severity.unwrap();
+        assert_eq!(code.hpo_id(), severity.id);
+        assert_eq!(code.hpo_label(), severity.label);
+        assert!(pf.modifiers.is_empty());
+        // the term is a current HPO term below Severity with the label we export
+        let tid: TermId = code.hpo_id().parse().unwrap();
+        assert_eq!(hpo.term_by_id(&tid).unwrap().name(), code.hpo_label());
+        assert!(SEVERITY_MAP.contains_key(code.hpo_id()));
+    }
+
+    #[rstest]
+    fn test_conflicting_severity_is_rejected(hpo: Arc<FullCsrOntology>) {
+        // the same severity in both places is fine
+        let data = HpoTermData::from_str("HP:0001166", "Arachnodactyly", "observed;HP:0012828").unwrap()
+            .with_severity(SeverityCode::Severe);
+        assert_eq!("HP:0012828", data.to_phenotypic_feature(hpo.clone()).unwrap().severity.unwrap().id);
+        let data = HpoTermData::from_str("HP:0001166", "Arachnodactyly", "observed;HP:0012828").unwrap()
+            .with_severity(SeverityCode::Mild);
+        let err = data.to_phenotypic_feature(hpo).unwrap_err();
+        assert!(err.contains("Conflicting severities"), "unexpected error: {err}");
+        assert!(HpoTermData::new(data.term_duplet.clone(), data.cell_value()).is_err());
+        let two_terms: CellValue = "observed;HP:0012825,HP:0012828".parse().unwrap();
+        assert!(two_terms.check_severity().is_err());
+    }
+
+    #[rstest]
+    fn test_explicit_severity_is_applied(hpo: Arc<FullCsrOntology>) {
+        let data = HpoTermData::from_str("HP:0001166", "Arachnodactyly", "observed").unwrap()
+            .with_severity(SeverityCode::Severe);
+        let pf = data.to_phenotypic_feature(hpo).unwrap();
+        assert_eq!("HP:0012828", pf.severity.unwrap().id);
+    }
 }
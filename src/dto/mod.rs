@@ -10,6 +10,7 @@ pub mod hgvs_variant;
 pub mod hpo_term_dto;
 pub mod intergenic_variant;
 pub mod mitochondrial_variant;
+pub mod orcid;
 pub mod structural_variant;
 pub mod validation_errors;
 pub mod variant_dto;
\ No newline at end of file
@@ -0,0 +1,104 @@
+//! Validation of ORCID identifiers
+//!
+//! Biocurators are identified by their [ORCID](https://orcid.org) in HPOA files and in the metadata of
+//! phenopackets. We require the form `ORCID:XXXX-XXXX-XXXX-XXXX`, where the last character is a check digit
+//! (0-9 or X) computed with ISO 7064 MOD 11-2 from the other fifteen digits.
+
+const ORCID_PREFIX: &str = "ORCID:";
+
+
+/// Check that `s` is an ORCID of the form `ORCID:XXXX-XXXX-XXXX-XXXX` with a valid check digit
+/// and return it without surrounding whitespace.
+pub fn validate_orcid(s: &str) -> Result<String, String> {
+    let orcid = s.trim();
+    let expected = "expected ORCID:XXXX-XXXX-XXXX-XXXX, e.g., ORCID:0000-0002-1825-0097";
+    let Some(identifier) = orcid.strip_prefix(ORCID_PREFIX) else {
+        return Err(format!("Invalid ORCID '{s}': missing 'ORCID:' prefix ({expected})"));
+    };
+    let segments: Vec<&str> = identifier.split('-').collect();
+    if segments.len() != 4 || segments.iter().any(|seg| seg.len() != 4) {
+        return Err(format!("Invalid ORCID '{s}': must have four segments of four characters ({expected})"));
+    }
+    let mut digits: Vec<char> = segments.concat().chars().collect();
+    let check = digits.pop().unwrap_or_default();
+    if !digits.iter().all(char::is_ascii_digit) || !(check.is_ascii_digit() || check == 'X') {
+        return Err(format!("Invalid ORCID '{s}': only digits are allowed, except for a final X ({expected})"));
+    }
+    let expected_check = check_digit(&digits);
+    if check != expected_check {
+        return Err(format!("Invalid ORCID '{s}': wrong check digit (expected {expected_check})"));
+    }
+    Ok(orcid.to_string())
+}
+
+/// Add the `ORCID:` prefix to a bare identifier such as `0000-0002-0736-9199` (the form that is used
+/// in the curation history of the cohort files); identifiers that already have the prefix are returned unchanged.
+pub fn with_orcid_prefix(s: &str) -> String {
+    let orcid = s.trim();
+    if orcid.starts_with(ORCID_PREFIX) {
+        orcid.to_string()
+    } else {
+        format!("{ORCID_PREFIX}{orcid}")
+    }
+}
+
+/// ISO 7064 MOD 11-2 check digit of the first fifteen digits of an ORCID
+fn check_digit(base: &[char]) -> char {
+    let total = base
+        .iter()
+        .filter_map(|c| c.to_digit(10))
+        .fold(0, |total, digit| (total + digit) * 2 % 11);
+    match (12 - total) % 11 {
+        10 => 'X',
+        d => char::from_digit(d, 10).unwrap_or('0'),
+    }
+}
+
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use rstest::rstest;
+
+    #[rstest]
+    #[case("ORCID:0000-0002-1825-0097")]
+    #[case("ORCID:0000-0002-0736-9199")]
+    #[case("ORCID:0000-0001-5109-3700")]
+    #[case("ORCID:0000-0002-1694-233X")]
+    #[case(" ORCID:0000-0002-0736-9199\n")]
+    fn test_valid_orcid(#[case] s: &str) {
+        assert_eq!(s.trim(), validate_orcid(s).unwrap());
+    }
+
+    #[rstest]
+    #[case::missing_prefix("0000-0002-0736-9199")]
+    #[case::lower_case_prefix("orcid:0000-0002-0736-9199")]
+    #[case::url("https://orcid.org/0000-0002-0736-9199")]
+    #[case::wrong_checksum("ORCID:0000-0002-0736-9198")]
+    #[case::three_segments("ORCID:0000-0002-07369199")]
+    #[case::five_segments("ORCID:0000-0002-0736-9199-0000")]
+    #[case::short_segment("ORCID:0000-0002-0736-919")]
+    #[case::no_hyphens("ORCID:0000000207369199")]
+    #[case::letter("ORCID:0000-000A-0736-9199")]
+    #[case::lower_case_x("ORCID:0000-0002-1694-233x")]
+    #[case::empty("")]
+    fn test_invalid_orcid(#[case] s: &str) {
+        assert!(validate_orcid(s).is_err());
+    }
+
+    #[rstest]
+    #[case("0000-0002-0736-9199")]
+    #[case("ORCID:0000-0002-0736-9199")]
+    #[case(" 0000-0002-0736-9199 ")]
+    fn test_with_orcid_prefix(#[case] s: &str) {
+        assert_eq!("ORCID:0000-0002-0736-9199", with_orcid_prefix(s));
+    }
+
+    #[rstest]
+    fn test_error_message() {
+        let err = validate_orcid("0000-0002-0736-9199").unwrap_err();
+        assert!(err.contains("ORCID:XXXX-XXXX-XXXX-XXXX"));
+        let err = validate_orcid("ORCID:0000-0002-0736-9198").unwrap_err();
+        assert!(err.contains("check digit (expected 9)"));
+    }
+}
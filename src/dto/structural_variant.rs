@@ -290,6 +290,10 @@ impl StructuralVariant {
         &self.variant_key
     }
 
+    pub fn sv_type(&self) -> SvType {
+        self.sv_type
+    }
+
     /// Return true iff the variant is X chromosomal
     /// We use this to determine if the variant is hemizygous
     pub fn is_x_chromosomal(&self) -> bool {
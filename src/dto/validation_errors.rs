@@ -11,11 +11,13 @@
 //! ```
 //! The final line will either return Ok(()) or the Error
 //!
+//! The Q/C of a cohort ([`QcSummary`]) additionally distinguishes between errors that require manual
+//! review and warnings that can be fixed automatically (see [`crate::factory::sanitize_cohort_data`]).
 
 
 use std::fmt;
 
-use serde::Serialize;
+use serde::{Deserialize, Serialize};
 
 
 #[derive(Debug, Default, Serialize)]
@@ -113,3 +115,217 @@ impl std::error::Error for ValidationErrors {
     }
 }
 
+
+
+/// Severity of a Q/C issue
+#[derive(Clone, Copy, Debug, Deserialize, Serialize, PartialEq, Eq, Hash)]
+#[serde(rename_all = "camelCase")]
+pub enum QcSeverity {
+    /// Requires manual review
+    Error,
+    /// Can be fixed automatically by sanitizing the cohort
+    Warning,
+}
+
+impl fmt::Display for QcSeverity {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            QcSeverity::Error => write!(f, "Error"),
+            QcSeverity::Warning => write!(f, "Warning"),
+        }
+    }
+}
+
+/// One issue found by the Q/C of a cohort
+#[derive(Clone, Debug, Deserialize, Serialize, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct QcViolation {
+    pub severity: QcSeverity,
+    pub message: String,
+}
+
+impl QcViolation {
+    pub fn error(message: impl Into<String>) -> Self {
+        Self { severity: QcSeverity::Error, message: message.into() }
+    }
+
+    pub fn warning(message: impl Into<String>) -> Self {
+        Self { severity: QcSeverity::Warning, message: message.into() }
+    }
+}
+
+impl fmt::Display for QcViolation {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}: {}", self.severity, self.message)
+    }
+}
+
+/// All Q/C issues of a cohort, in the order in which they were found.
+/// The JSON representation lists the errors and the warnings separately, together with their counts.
+#[derive(Clone, Debug, Default, Deserialize, Serialize, PartialEq)]
+#[serde(from = "QcSummaryJson", into = "QcSummaryJson")]
+pub struct QcSummary {
+    /// Number of individuals (rows) of the cohort
+    pub n_individuals: usize,
+    violations: Vec<QcViolation>,
+}
+
+/// Serialized form of [`QcSummary`]; the counts are derived from the violations
+#[derive(Deserialize, Serialize)]
+#[serde(rename_all = "camelCase")]
+struct QcSummaryJson {
+    n_individuals: usize,
+    n_errors: usize,
+    n_warnings: usize,
+    errors: Vec<QcViolation>,
+    warnings: Vec<QcViolation>,
+}
+
+impl From<QcSummary> for QcSummaryJson {
+    fn from(summary: QcSummary) -> Self {
+        let (errors, warnings): (Vec<QcViolation>, Vec<QcViolation>) = summary.violations
+            .into_iter()
+            .partition(|v| v.severity == QcSeverity::Error);
+        Self {
+            n_individuals: summary.n_individuals,
+            n_errors: errors.len(),
+            n_warnings: warnings.len(),
+            errors,
+            warnings,
+        }
+    }
+}
+
+impl From<QcSummaryJson> for QcSummary {
+    fn from(json: QcSummaryJson) -> Self {
+        Self {
+            n_individuals: json.n_individuals,
+            violations: json.errors.into_iter().chain(json.warnings).collect(),
+        }
+    }
+}
+
+impl QcSummary {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn push(&mut self, violation: QcViolation) {
+        self.violations.push(violation);
+    }
+
+    pub fn extend(&mut self, violations: impl IntoIterator<Item = QcViolation>) {
+        for violation in violations {
+            self.push(violation);
+        }
+    }
+
+    /// All issues, in the order in which they were found
+    pub fn violations(&self) -> Vec<QcViolation> {
+        self.violations.clone()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.violations.is_empty()
+    }
+
+    pub fn has_errors(&self) -> bool {
+        self.errors().next().is_some()
+    }
+
+    pub fn has_warnings(&self) -> bool {
+        self.warnings().next().is_some()
+    }
+
+    pub fn errors(&self) -> impl Iterator<Item = &QcViolation> {
+        self.violations.iter().filter(|v| v.severity == QcSeverity::Error)
+    }
+
+    pub fn warnings(&self) -> impl Iterator<Item = &QcViolation> {
+        self.violations.iter().filter(|v| v.severity == QcSeverity::Warning)
+    }
+
+    pub fn n_errors(&self) -> usize {
+        self.errors().count()
+    }
+
+    pub fn n_warnings(&self) -> usize {
+        self.warnings().count()
+    }
+
+    /// Phenopackets can be exported if there are no errors; warnings are resolved by sanitizing the cohort
+    pub fn is_export_ready(&self) -> bool {
+        !self.has_errors()
+    }
+
+    /// Human-readable report with the counts followed by one line per error and per warning
+    pub fn to_text_report(&self) -> String {
+        let mut report = format!("Individuals: {}\nErrors: {}\nWarnings: {}\n",
+            self.n_individuals, self.n_errors(), self.n_warnings());
+        for violation in self.errors().chain(self.warnings()) {
+            report.push_str(&format!("{violation}\n"));
+        }
+        if self.is_export_ready() {
+            report.push_str("Ready for export\n");
+        } else {
+            report.push_str("Not ready for export: the errors must be fixed manually\n");
+        }
+        report
+    }
+}
+
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use rstest::rstest;
+
+    #[rstest]
+    fn test_qc_summary() {
+        let mut summary = QcSummary::new();
+        assert!(summary.is_empty());
+        assert!(!summary.has_errors());
+        assert!(!summary.has_warnings());
+        summary.push(QcViolation::warning("redundant"));
+        assert!(!summary.has_errors());
+        assert!(summary.has_warnings());
+        summary.push(QcViolation::error("conflict"));
+        assert!(summary.has_errors());
+        assert_eq!(1, summary.errors().count());
+        assert_eq!("Warning: redundant", summary.warnings().next().unwrap().to_string());
+    }
+
+    #[rstest]
+    fn test_export_ready() {
+        let mut summary = QcSummary::new();
+        summary.n_individuals = 3;
+        summary.push(QcViolation::warning("redundant"));
+        assert!(summary.is_export_ready());
+        assert_eq!("Individuals: 3\nErrors: 0\nWarnings: 1\nWarning: redundant\nReady for export\n", summary.to_text_report());
+        summary.push(QcViolation::error("conflict"));
+        summary.push(QcViolation::warning("redundant 2"));
+        assert!(!summary.is_export_ready());
+        assert_eq!((1, 2), (summary.n_errors(), summary.n_warnings()));
+        // violations() keeps the order in which the issues were found
+        let messages: Vec<String> = summary.violations().into_iter().map(|v| v.message).collect();
+        assert_eq!(vec!["redundant", "conflict", "redundant 2"], messages);
+        let report = summary.to_text_report();
+        assert!(report.starts_with("Individuals: 3\nErrors: 1\nWarnings: 2\nError: conflict\nWarning: redundant\n"));
+        assert!(report.ends_with("Not ready for export: the errors must be fixed manually\n"));
+    }
+
+    #[rstest]
+    fn test_serialize_summary() {
+        let mut summary = QcSummary::new();
+        summary.push(QcViolation::error("conflict"));
+        let json = serde_json::to_string(&summary).unwrap();
+        assert_eq!(r#"{"nIndividuals":0,"nErrors":1,"nWarnings":0,"errors":[{"severity":"error","message":"conflict"}],"warnings":[]}"#, json);
+        assert_eq!(summary, serde_json::from_str(&json).unwrap());
+    }
+
+    #[rstest]
+    fn test_serialize_severity() {
+        let json = serde_json::to_string(&QcViolation::error("conflict")).unwrap();
+        assert_eq!(r#"{"severity":"error","message":"conflict"}"#, json);
+    }
+}
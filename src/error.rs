@@ -0,0 +1,102 @@
+//! Error type of the public API
+//!
+//! Most internal functions return `Result<T, String>`. The public functions of the [`factory`](crate::factory),
+//! [`etl`](crate::etl), and [`ppkt`](crate::ppkt) modules return [`PheToolsError`], so that callers can
+//! distinguish the kind of problem. The message of a variant is the message of the underlying error,
+//! and `PheToolsError` converts into `String`, so that `?` still works in functions that return `Result<T, String>`.
+//! [`ErrorContext`] prefixes such `String` errors with the location of the problem, e.g., the row of a table.
+
+use thiserror::Error;
+
+use crate::etl::EtlQcError;
+
+
+#[derive(Debug, Error)]
+pub enum PheToolsError {
+    /// An HPO term is not in the current HPO
+    #[error("{0}")]
+    HpoTermNotFound(String),
+    /// A variant (HGVS, structural, or intergenic) could not be validated
+    #[error("{0}")]
+    VariantValidationFailed(String),
+    /// An external table could not be read or transformed into a cohort
+    #[error("{0}")]
+    EtlTransformFailed(String),
+    /// All problems of an ETL table (see [`crate::etl::FailureMode::CollectAll`])
+    #[error("{}", .0.iter().map(|e| e.to_string()).collect::<Vec<_>>().join("; "))]
+    EtlQcFailed(Vec<EtlQcError>),
+    /// The cohort data are inconsistent or cannot be created or updated as requested
+    #[error("{0}")]
+    InvalidCohort(String),
+    /// A phenopacket could not be created from the cohort data
+    #[error("{0}")]
+    PhenopacketExportFailed(String),
+    #[error(transparent)]
+    IoError(#[from] std::io::Error),
+    #[error(transparent)]
+    SerdeError(#[from] serde_json::Error),
+    /// The schema version of a cohort file is not supported
+    #[error("{0}")]
+    SchemaVersionError(String),
+}
+
+impl From<PheToolsError> for String {
+    fn from(e: PheToolsError) -> Self {
+        e.to_string()
+    }
+}
+
+
+/// Add context to `String` errors, e.g., `Row 5: Could not extract individual ID: patient ID column is empty`
+pub trait ErrorContext<T> {
+    /// Prefix the error message (if any) with the context returned by `f`, separated by ": ".
+    /// `f` is only called if there is an error.
+    fn with_context(self, f: impl FnOnce() -> String) -> Result<T, String>;
+}
+
+impl<T> ErrorContext<T> for Result<T, String> {
+    fn with_context(self, f: impl FnOnce() -> String) -> Result<T, String> {
+        self.map_err(|e| format!("{}: {}", f(), e))
+    }
+}
+
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use rstest::rstest;
+
+    fn fails_with_string() -> Result<(), String> {
+        Err(PheToolsError::HpoTermNotFound("HP:9999999".to_string()))?;
+        Ok(())
+    }
+
+    #[rstest]
+    fn test_into_string() {
+        assert_eq!(Err("HP:9999999".to_string()), fails_with_string());
+    }
+
+    #[rstest]
+    fn test_io_error() {
+        let e: PheToolsError = std::io::Error::new(std::io::ErrorKind::NotFound, "cohort.json: not found").into();
+        assert!(matches!(e, PheToolsError::IoError(_)));
+        assert_eq!("cohort.json: not found", String::from(e));
+    }
+
+    #[rstest]
+    fn test_with_context() {
+        let err: Result<(), String> = Err("patient ID column is empty".to_string());
+        let err = err
+            .with_context(|| "Could not extract individual ID".to_string())
+            .with_context(|| "Row 5".to_string());
+        assert_eq!(Err("Row 5: Could not extract individual ID: patient ID column is empty".to_string()), err);
+        let ok: Result<usize, String> = Ok(42);
+        assert_eq!(Ok(42), ok.with_context(|| panic!("context must not be computed for Ok")));
+    }
+
+    #[rstest]
+    fn test_serde_error() {
+        let e: PheToolsError = serde_json::from_str::<serde_json::Value>("{").unwrap_err().into();
+        assert!(matches!(e, PheToolsError::SerdeError(_)));
+    }
+}
@@ -1,18 +1,27 @@
 use std::collections::hash_map::Entry;
+use std::str::FromStr;
 use std::collections::HashSet;
-use std::{collections::HashMap, fmt, fs, sync::Arc};
+use std::{collections::HashMap, fmt, fs, path::Path, sync::Arc};
 use ontolius::ontology::{csr::FullCsrOntology, MetadataAware};
 use regex::Regex;
 
-use crate::dto::cohort_dto::DiseaseData;
-use crate::dto::etl_dto::{ColumnDto, EtlCellStatus, EtlCellValue};
+use crate::dto::cohort_dto::{BiosampleData, DiseaseData, GeneTranscriptData, MeasurementData, TrioData};
+use crate::age::iso_age::Iso8601Age;
+use crate::dto::hgvs_variant::HgvsVariant;
+use crate::dto::intergenic_variant::IntergenicHgvsVariant;
+use crate::dto::structural_variant::StructuralVariant;
+use crate::dto::etl_dto::{ColumnDto, EtlCellStatus, EtlCellValue, EtlColumnHeader, EtlTransform};
 use crate::dto::etl_dto::{EtlColumnType::{self, *}, EtlDto};
-use crate::dto::hpo_term_dto::{CellValue, HpoTermData};
+use crate::dto::hpo_term_dto::{CellValue, CurationConfidence, HpoTermData, LateralityCode, SeverityCode};
+use crate::error::ErrorContext;
+use crate::etl::EtlQcError;
 use crate::variant::variant_manager::VariantManager;
 use crate::{dto::{cohort_dto::{CohortData, CohortType, IndividualData, RowData}, etl_dto::ColumnTableDto, hpo_term_dto::HpoTermDuplet}, hpo};
 
 const UNKNOWN_SEX: &str = "U";
 const NOT_AVAILABLE: &str = "na";
+/// Number of alleles validated between two checkpoints (see [`EtlTools::process_allele_column_with_checkpoint`])
+pub(crate) const CHECKPOINT_BATCH_SIZE: usize = 25;
 
 pub struct EtlTools {
     /// Reference to the Ontolius Human Phenotype Ontology Full CSR object
@@ -62,6 +71,33 @@ impl EtlTools {
         &self.dto
     }
 
+    /// Save the current state of the table, including the validated variants and the variant keys of the cells, so that
+    /// a long transformation (e.g., the validation of many variants) can be resumed with [`Self::from_checkpoint`].
+    /// The state is written to a temporary file that then replaces the checkpoint, so that an interruption while
+    /// writing does not corrupt an existing checkpoint.
+    pub fn save_checkpoint(&self, path: &Path) -> Result<(), String> {
+        Self::write_checkpoint(&self.dto, path)
+    }
+
+    fn write_checkpoint(dto: &EtlDto, path: &Path) -> Result<(), String> {
+        let json = serde_json::to_string_pretty(dto).map_err(|e| e.to_string())?;
+        let tmp_path = path.with_extension("tmp");
+        fs::write(&tmp_path, json)
+            .map_err(|e| format!("Could not write checkpoint {}: {}", tmp_path.display(), e))?;
+        fs::rename(&tmp_path, path)
+            .map_err(|e| format!("Could not write checkpoint {}: {}", path.display(), e))
+    }
+
+    /// Resume from a state saved with [`Self::save_checkpoint`]. Alleles that were validated before the checkpoint
+    /// are not validated again (see [`Self::process_allele_column`]).
+    pub fn from_checkpoint(path: &Path, hpo: Arc<FullCsrOntology>) -> Result<Self, String> {
+        let contents = fs::read_to_string(path)
+            .map_err(|e| format!("Could not read checkpoint {}: {}", path.display(), e))?;
+        let dto: EtlDto = serde_json::from_str(&contents)
+            .map_err(|e| format!("Could not parse checkpoint {}: {}", path.display(), e))?;
+        Ok(Self::from_etl(dto, hpo))
+    }
+
     // Function to load JSON file and deserialize to ColumnTableDto
     pub fn load_etl_dto_from_json(file_path: &str) -> Result<EtlDto, String> {
         let json_content = fs::read_to_string(file_path)
@@ -122,6 +158,21 @@ impl EtlTools {
         }
     }
 
+    /// Replace natural language ages such as "at age 3" or "in infancy" by the ISO8601 duration or the
+    /// HPO onset term. Other values are left unchanged, so that they are reported by the validation.
+    fn preprocess_age(value: String) -> String {
+        if value == NOT_AVAILABLE || crate::age::is_valid_age_string(&value) {
+            return value;
+        }
+        match crate::age::parse_natural_language_age(&value) {
+            Some(age) => {
+                tracing::debug!(original = %value, age = %age, "Mapped natural language age");
+                age
+            }
+            None => value,
+        }
+    }
+
     /// Get the individual Data for row i
     fn get_individual(&self, i: usize) -> Result<IndividualData, String> {
          let pmid = self.dto.pmid.clone().ok_or_else(|| format!("Could not extract pmid for individual {i}"))?;
@@ -133,22 +184,43 @@ impl EtlTools {
             comment: String::default(), 
             age_of_onset: NOT_AVAILABLE.to_string(), 
             age_at_last_encounter: NOT_AVAILABLE.to_string(), 
+            age_of_onset_max: None,
+            age_at_last_encounter_max: None,
             deceased: NOT_AVAILABLE.to_string(), 
             sex: UNKNOWN_SEX.to_string(), 
+            treatment_list: vec![],
+            biosamples: vec![],
+            measurements: vec![],
          };
    
          for col in &self.dto.table.columns {
             match &col.header.column_type {
                 Raw | FamilyId | SingleHpoTerm | MultipleHpoTerm | HpoTextMining |
-                GeneSymbol | Variant | Ignore  => {}
+                GeneSymbol | Variant | FatherVariant | MotherVariant | Confidence | Laterality | SeverityModifier | Ignore  => {}
                 PatientId => {
                     individual.individual_id = Self::extract_value(&col.values, i, "individual ID")?;
+                    if individual.individual_id.trim().is_empty() {
+                        return Err("patient ID column is empty".to_string())
+                            .with_context(|| "Could not extract individual ID".to_string());
+                    }
                 }
                 AgeOfOnset => {
-                    individual.age_of_onset = Self::extract_value_or_default(&col.values, i, "age_of_onset", NOT_AVAILABLE)?;
+                    let value = Self::extract_value_or_default(&col.values, i, "age_of_onset", NOT_AVAILABLE)?;
+                    individual.age_of_onset = Self::preprocess_age(value);
+                }
+                AgeRange => {
+                    let value = Self::preprocess_age(Self::extract_value_or_default(&col.values, i, "age_of_onset", NOT_AVAILABLE)?);
+                    match Iso8601Age::split_range(&value) {
+                        Some((lower, upper)) => {
+                            individual.age_of_onset = lower.to_string();
+                            individual.age_of_onset_max = Some(upper.to_string());
+                        }
+                        None => individual.age_of_onset = value,
+                    }
                 }
                 AgeAtLastEncounter => {
-                    individual.age_at_last_encounter = Self::extract_value_or_default(&col.values, i, "age_at_last_encounter", NOT_AVAILABLE)?;
+                    let value = Self::extract_value_or_default(&col.values, i, "age_at_last_encounter", NOT_AVAILABLE)?;
+                    individual.age_at_last_encounter = Self::preprocess_age(value);
                 }
                 Sex => {
                     individual.sex = Self::extract_value_or_default(&col.values, i, "sex", UNKNOWN_SEX)?;
@@ -156,15 +228,55 @@ impl EtlTools {
                 Deceased => {
                     individual.deceased = Self::extract_value_or_default(&col.values, i, "deceased", NOT_AVAILABLE)?;
                 }
+                Treatment => {
+                    let value = Self::extract_value_or_default(&col.values, i, "treatment", NOT_AVAILABLE)?;
+                    individual.treatment_list.extend(value
+                        .split(';')
+                        .map(str::trim)
+                        .filter(|t| !t.is_empty() && *t != NOT_AVAILABLE)
+                        .map(String::from));
+                }
+                TissueType => {
+                    let value = Self::extract_value_or_default(&col.values, i, "tissue type", NOT_AVAILABLE)?;
+                    if value != NOT_AVAILABLE {
+                        individual.biosamples.push(Self::parse_tissue_type(&value)?);
+                    }
+                }
+                Measurement => {
+                    let value = Self::extract_value_or_default(&col.values, i, "measurement", NOT_AVAILABLE)?;
+                    for entry in value.split(';').map(str::trim).filter(|m| !m.is_empty() && *m != NOT_AVAILABLE) {
+                        individual.measurements.push(MeasurementData::from_str(entry)?);
+                    }
+                }
             }
         }
         if individual.individual_id.len() < 1 {
             return Err(format!("Invalid individual without identifier: {:?}", individual));
         }
+        for (k, biosample) in individual.biosamples.iter_mut().enumerate() {
+            biosample.biosample_id = format!("{}-biosample-{}", individual.individual_id, k + 1);
+        }
         Ok(individual)
     }
 
 
+    /// Parse a cell such as `UBERON:0002107 liver` into a biosample (the id is assigned later)
+    fn parse_tissue_type(value: &str) -> Result<BiosampleData, String> {
+        let value = value.trim();
+        let (tissue_id, tissue_label) = value.split_once(char::is_whitespace).unwrap_or((value, ""));
+        let is_uberon = tissue_id.strip_prefix("UBERON:")
+            .is_some_and(|number| !number.is_empty() && number.chars().all(|c| c.is_ascii_digit()));
+        if !is_uberon {
+            return Err(format!("Tissue type must be an UBERON term (e.g., UBERON:0002107) but got '{value}'"));
+        }
+        Ok(BiosampleData {
+            biosample_id: String::default(),
+            tissue_id: tissue_id.to_string(),
+            tissue_label: tissue_label.trim().to_string(),
+            histology_id: None,
+        })
+    }
+
     fn resolve_hpo_conflict(val1: &str, val2: &str) -> Result<String, String> {
         if val1 == "na" {
             return Ok(val2.to_string());
@@ -193,6 +305,179 @@ impl EtlTools {
         Err(format!("Conflicting HPO entries: '{}' and '{}'", val1, val2))
     }
 
+    /// Replace the cells whose value is one of `missing_aliases` (e.g., "N/A" or "not reported") by "na", so that
+    /// the later transformation steps do not need to handle the many representations of missing data. The current
+    /// value is compared if it has been set, the original value otherwise; the comparison is case-sensitive after
+    /// trimming. Patient ids, ignored columns, and the text mining column are left unchanged.
+    pub fn normalize_missing_values(mut dto: EtlDto, missing_aliases: &[&str]) -> EtlDto {
+        for col in dto.table.columns.iter_mut() {
+            if matches!(col.header.column_type, PatientId | Ignore | HpoTextMining) {
+                continue;
+            }
+            for (i, cell) in col.values.iter_mut().enumerate() {
+                let value = if cell.current.is_empty() { &cell.original } else { &cell.current };
+                let value = value.trim();
+                if value != NOT_AVAILABLE && missing_aliases.contains(&value) {
+                    tracing::debug!(column = %col.header.original, row = i, before = %value, "Normalized missing value to na");
+                    cell.current = NOT_AVAILABLE.to_string();
+                    cell.status = EtlCellStatus::Transformed;
+                    cell.error = None;
+                }
+            }
+        }
+        dto
+    }
+
+    /// Apply the transformations in the given order, each to the result of the previous one.
+    /// If one of them fails, the error names the transformation and none of the changes is returned.
+    pub fn apply_transformations(&self, transforms: Vec<EtlTransform>) -> Result<EtlDto, String> {
+        let mut etl_tools = Self::from_etl(self.dto.clone(), self.hpo.clone());
+        for (i, transform) in transforms.into_iter().enumerate() {
+            let dto = etl_tools.apply_transformation(&transform)
+                .map_err(|e| format!("Transformation {} ({:?}) failed: {}", i + 1, transform, e))?;
+            etl_tools = Self::from_etl(dto, self.hpo.clone());
+        }
+        Ok(etl_tools.dto)
+    }
+
+    fn apply_transformation(&self, transform: &EtlTransform) -> Result<EtlDto, String> {
+        let n_columns = self.dto.table.columns.len();
+        let check_column = |col: usize| if col < n_columns {
+            Ok(col)
+        } else {
+            Err(format!("Column index {col} is out of range (the table has {n_columns} columns)"))
+        };
+        let mut dto = self.dto.clone();
+        match transform {
+            EtlTransform::SetColumnType { col, column_type } => {
+                dto.table.columns[check_column(*col)?].header.column_type = column_type.clone();
+            }
+            EtlTransform::ProcessAlleles { col } => {
+                let col = check_column(*col)?;
+                return match dto.table.columns[col].header.gene_index {
+                    Some(gene_index) => self.process_allele_column_for_gene(col, gene_index, |_, _| {}),
+                    None => self.process_allele_column(col, |_, _| {}),
+                };
+            }
+            EtlTransform::SetSingleHpoTerm { col, term } => {
+                hpo::check_hpo_duplets(self.hpo.clone(), &vec![term.clone()])?;
+                let header = &mut dto.table.columns[check_column(*col)?].header;
+                header.column_type = SingleHpoTerm;
+                header.hpo_terms = Some(vec![term.clone()]);
+            }
+            EtlTransform::SetMultipleHpoTerms { col, terms } => {
+                if terms.is_empty() {
+                    return Err("No HPO terms given for MultipleHpoTerm column".to_string());
+                }
+                hpo::check_hpo_duplets(self.hpo.clone(), terms)?;
+                let header = &mut dto.table.columns[check_column(*col)?].header;
+                header.column_type = MultipleHpoTerm;
+                header.hpo_terms = Some(terms.clone());
+            }
+            EtlTransform::SetPatientId { col } => {
+                let col = check_column(*col)?;
+                for (i, column) in dto.table.columns.iter_mut().enumerate() {
+                    if i != col && column.header.column_type == PatientId {
+                        column.header.column_type = Raw;
+                    }
+                }
+                dto.table.columns[col].header.column_type = PatientId;
+            }
+            EtlTransform::NormalizeMissingValues => {
+                return Ok(Self::normalize_missing_values(self.dto.clone(), &crate::etl::DEFAULT_MISSING_VALUE_ALIASES));
+            }
+        }
+        Ok(dto)
+    }
+
+    /// Reorder the columns of the table (e.g., after drag-and-drop in the GUI): column `i` of the result is
+    /// column `new_order[i]` of the current table. `new_order` must be a permutation of `0..n_columns`.
+    pub fn reorder_columns(&self, new_order: &[usize]) -> Result<EtlDto, String> {
+        let columns = &self.dto.table.columns;
+        let n_columns = columns.len();
+        if new_order.len() != n_columns {
+            return Err(format!("Column order has {} indices but the table has {} columns", new_order.len(), n_columns));
+        }
+        let mut seen = vec![false; n_columns];
+        for &idx in new_order {
+            if idx >= n_columns {
+                return Err(format!("Column index {idx} is out of range (the table has {n_columns} columns)"));
+            }
+            if seen[idx] {
+                return Err(format!("Column index {idx} appears more than once in the column order"));
+            }
+            seen[idx] = true;
+        }
+        let mut reordered_dto = self.dto.clone();
+        reordered_dto.table.columns = new_order.iter().map(|&idx| columns[idx].clone()).collect();
+        Ok(reordered_dto)
+    }
+
+    /// Some tables list the same patient in several rows (e.g., one row per phenotype). Combine all rows with the
+    /// same value in the PatientId column into one row (in the order in which the patients first appear).
+    /// HPO values are merged (the union of observations), as are the entries of the text mining column.
+    /// Other columns, including the variant columns, may only differ if one of the values is empty or na.
+    pub fn merge_duplicate_patient_ids(&self) -> Result<EtlDto, String> {
+        let id_column = self.dto.get_column_by_type(PatientId)
+            .ok_or_else(|| "Cannot merge rows without PatientId column".to_string())?;
+        let mut row_groups: Vec<(String, Vec<usize>)> = Vec::new();
+        for (i, cell) in id_column.values.iter().enumerate() {
+            let patient_id = cell.current.trim();
+            match row_groups.iter_mut().find(|(id, _)| id == patient_id) {
+                Some((_, rows)) => rows.push(i),
+                None => row_groups.push((patient_id.to_string(), vec![i])),
+            }
+        }
+        let mut merged_dto = self.dto.clone();
+        for col in merged_dto.table.columns.iter_mut() {
+            let mut merged_values: Vec<EtlCellValue> = Vec::with_capacity(row_groups.len());
+            for (patient_id, rows) in &row_groups {
+                let mut merged = col.values[rows[0]].clone();
+                for &i in &rows[1..] {
+                    let value = &col.values[i].current;
+                    merged.current = Self::merge_cell_values(&col.header, &merged.current, value)
+                        .map_err(|e| format!("{patient_id}: {e}"))?;
+                }
+                merged_values.push(merged);
+            }
+            col.values = merged_values;
+        }
+        Ok(merged_dto)
+    }
+
+    fn merge_cell_values(header: &EtlColumnHeader, val1: &str, val2: &str) -> Result<String, String> {
+        let is_missing = |v: &str| v.is_empty() || v == NOT_AVAILABLE;
+        if val1 == val2 || is_missing(val2) {
+            return Ok(val1.to_string());
+        } else if is_missing(val1) {
+            return Ok(val2.to_string());
+        }
+        match header.column_type {
+            SingleHpoTerm => Self::resolve_hpo_conflict(val1, val2),
+            MultipleHpoTerm | Treatment | Measurement => {
+                let mut observations: Vec<&str> = val1.split(';').filter(|obs| !obs.is_empty()).collect();
+                for obs in val2.split(';').filter(|obs| !obs.is_empty()) {
+                    if !observations.contains(&obs) {
+                        observations.push(obs);
+                    }
+                }
+                Ok(observations.join(";"))
+            }
+            HpoTextMining => {
+                let mut hpo_data = Self::get_hpo_term_data_from_json(val1)?;
+                for htd in Self::get_hpo_term_data_from_json(val2)? {
+                    match hpo_data.iter_mut().find(|h| h.term_duplet == htd.term_duplet) {
+                        Some(existing) => *existing = existing.merge_with(&htd)?,
+                        None => hpo_data.push(htd),
+                    }
+                }
+                serde_json::to_string(&hpo_data).map_err(|e| e.to_string())
+            }
+            Raw | Ignore => Ok(val1.to_string()),
+            _ => Err(format!("Conflicting values for {}: '{}' and '{}'", header.original, val1, val2)),
+        }
+    }
+
     /// We check if there is already an entry for some HPO term in some row. If yes, we throw an
     /// error if the two values disagree.
     fn insert_or_validate(
@@ -288,16 +573,30 @@ impl EtlTools {
     /// more onset columns such as "P32Y"
     /// The onset and the observed may optionally have a ":" followed by additional HPO
     /// terms that represent severity terms or other modifiers; these terms are split by ";"
+    /// Errors are prefixed with the row index, e.g., "Row 5: ..."
     fn get_row(
         &self, 
         i: usize, 
         all_hpo_duplets: &[HpoTermDuplet], 
         disease: &DiseaseData) 
+    -> Result<RowData, String> {
+        self.extract_row(i, all_hpo_duplets, disease)
+            .with_context(|| format!("Row {i}"))
+    }
+
+    fn extract_row(
+        &self, 
+        i: usize, 
+        all_hpo_duplets: &[HpoTermDuplet], 
+        disease: &DiseaseData) 
     -> Result<RowData, String> {
          let individual = self.get_individual(i)?;
          let mut hpo_to_status_map: HashMap<HpoTermDuplet, String> = HashMap::new();
          let mut allele_count_map: HashMap<String, usize> = HashMap::new();
          let mut text_mining_column: Option<ColumnDto> = None;
+         let mut confidence: Option<CurationConfidence> = None;
+         let mut laterality: Option<LateralityCode> = None;
+         let mut severity: Option<SeverityCode> = None;
          for col in &self.dto.table.columns {
             if col.header.column_type == SingleHpoTerm {
                 if let Some(hpo_terms) = &col.header.hpo_terms {
@@ -321,9 +620,24 @@ impl EtlTools {
                 }
             } else if col.header.column_type == HpoTextMining {
                 text_mining_column = Some(col.clone());
+            } else if col.header.column_type == Confidence {
+                let value = col.values[i].current.trim();
+                if ! value.is_empty() && value != NOT_AVAILABLE {
+                    confidence = Some(CurationConfidence::from_str(value)?);
+                }
+            } else if col.header.column_type == Laterality {
+                let value = col.values[i].current.trim();
+                if ! value.is_empty() && value != NOT_AVAILABLE {
+                    laterality = Some(LateralityCode::from_str(value)?);
+                }
+            } else if col.header.column_type == SeverityModifier {
+                let value = col.values[i].current.trim();
+                if ! value.is_empty() && value != NOT_AVAILABLE {
+                    severity = Some(SeverityCode::from_str(value)?);
+                }
             } else if col.header.column_type == Variant {
                 if col.values[i].current != "na" {
-                    allele_count_map.entry(col.values[i].current.clone())
+                    allele_count_map.entry(Self::allele_key(&col.header, &col.values[i].current))
                     .and_modify(|count| *count += 1)
                     .or_insert(1);
                 }
@@ -356,7 +670,7 @@ impl EtlTools {
             if ! hpo_hits.is_empty() {
                 let hpo_map: HashMap<HpoTermDuplet, CellValue> =
                     hpo_hits.into_iter()
-                        .map(|htd| (htd.term_duplet, htd.entry))
+                        .map(|htd| (htd.term_duplet.clone(), htd.cell_value()))
                         .collect();
                 for (i, hpo_duplet) in all_hpo_duplets.iter().enumerate() { 
                     if let Some(cell_val) = hpo_map.get(hpo_duplet) {
@@ -365,6 +679,18 @@ impl EtlTools {
                 }
             }
          }
+         // a confidence column applies to all annotations of the row that do not have their own confidence
+         if confidence.is_some() {
+            for value in values.iter_mut().filter(|v| v.is_ascertained() && v.confidence.is_none()) {
+                value.confidence = confidence;
+            }
+         }
+         // likewise, laterality and severity columns apply to the observed annotations (with or without onset)
+         for value in values.iter_mut().filter(|v| v.is_present()) {
+            value.laterality = value.laterality.or(laterality);
+            value.severity = value.severity.or(severity);
+            value.check_severity().map_err(|e| format!("{}: {e}", individual.individual_id))?;
+         }
          let row = RowData{
             individual_data: individual,
             disease_id_list: vec![disease.disease_id.clone()],
@@ -407,29 +733,46 @@ impl EtlTools {
         Ok(())
     }
 
-    /// Throw an error if a table cell has a leading/trailing whitespace or has an invalid character
-    fn qc_table_cells(&self) -> Result<(), String>{
-        for col in &self.raw_table().table.columns {
-            if col.header.column_type == EtlColumnType::Ignore {
-                continue; // Don't worry about columns that will not be ingested (Ignore)
-            }
-            for cell in &col.values {
-                let cell_val = &cell.current;
-                if cell_val.starts_with(char::is_whitespace) {
-                    return Err(format!("{}: leading whitespace - '{}'", col.header.original, cell.current));
-                }
-                if cell_val.ends_with(char::is_whitespace) {
-                    return Err(format!("{}: trailing whitespace - '{}'", col.header.original, cell.current));
-                    
-                }
-                for ch in cell_val.chars() {
-                    if !Self::is_valid_char(ch) {
-                        return Err(format!("{}: Invalid character: U+{:04X} '{}'", col.header.original, ch as u32, ch));
+    /// Zero-width and directional marks (U+200B–U+200F) are invisible in spreadsheets
+    fn is_invisible_control_char(ch: char) -> bool {
+        ('\u{0000}'..='\u{001F}').contains(&ch) || ('\u{200B}'..='\u{200F}').contains(&ch)
+    }
+
+    /// Check the loaded table for obvious problems before the column-by-column transformation is started.
+    pub fn validate_before_transform(&self) -> Result<(), Vec<String>> {
+        Self::validate_dto_before_transform(&self.dto)
+    }
+
+    /// Report all problems at once: duplicate ids in a PatientId column (if one was already set),
+    /// cells with invisible control characters, and columns in which all values are empty.
+    pub(crate) fn validate_dto_before_transform(dto: &EtlDto) -> Result<(), Vec<String>> {
+        let mut errors: Vec<String> = Vec::new();
+        for col in &dto.table.columns {
+            let column_name = &col.header.original;
+            if col.header.column_type == PatientId {
+                let mut seen: HashSet<&str> = HashSet::new();
+                let mut reported: HashSet<&str> = HashSet::new();
+                for cell in &col.values {
+                    let id = cell.current.trim();
+                    if !id.is_empty() && !seen.insert(id) && reported.insert(id) {
+                        errors.push(format!("{column_name}: duplicate patient id '{id}'"));
                     }
                 }
             }
+            for (i, cell) in col.values.iter().enumerate() {
+                if let Some(ch) = cell.current.chars().find(|c| Self::is_invisible_control_char(*c)) {
+                    errors.push(format!("{column_name}: invisible control character U+{:04X} in row {}", ch as u32, i + 1));
+                }
+            }
+            if col.values.iter().all(|cell| cell.current.trim().is_empty()) {
+                errors.push(format!("{column_name}: all values are empty"));
+            }
+        }
+        if errors.is_empty() {
+            Ok(())
+        } else {
+            Err(errors)
         }
-        Ok(())
     }
 
     fn is_mapped_variant(&self, allele: &str) -> bool {
@@ -441,7 +784,7 @@ impl EtlTools {
     /// Our strategy for dealing with variants here is to
     /// mark mapped variants in the cell and leave the other cells as error.
     /// We do not need to return an error to the GUI
-    fn qc_variants(&mut self) -> Result<(), String> {
+    fn mark_variant_cells(&mut self) {
         let table = &mut self.dto;
 
         table
@@ -452,7 +795,7 @@ impl EtlTools {
             .flat_map(|c| &mut c.values)
             .for_each(|cell| {
                 let allele = cell.current.as_str();
-            let valid = allele != "na"
+                let valid = allele != "na"
                     && (table.hgvs_variants.contains_key(allele)
                         || table.structural_variants.contains_key(allele)
                         || table.intergenic_variants.contains_key(allele));
@@ -463,8 +806,6 @@ impl EtlTools {
                     EtlCellStatus::Error
                 };
             });
-
-        Ok(())
     }
 
     /// We need to have at least one of individualId and at least one HPO term.
@@ -521,79 +862,247 @@ impl EtlTools {
         Ok(())
     }
 
-    /// Check that no unprocessed columns (Raw) are left. Any remaining column must either be
-    /// Ignored or not have any untransformed cells at all.
-    fn check_is_completely_transformed(&self) -> Result<(), String> {
-         if self.raw_table().table.columns.is_empty() {
-            return Err("EtlDto table with no columns".to_string());
-        }
+    /// Show the value of each transformed cell before and after the transformation (debug level)
+    fn log_transformations(&self) {
         for col in &self.raw_table().table.columns {
-            if col.header.column_type == EtlColumnType::Raw {
-                return Err(format!("'{}' column type not set (Raw)", col.header.original))
+            tracing::debug!(column = %col.header.original, column_type = ?col.header.column_type, "ETL column");
+            for cell in col.values.iter().filter(|cell| cell.original != cell.current) {
+                tracing::debug!(column = %col.header.original, before = %cell.original, after = %cell.current, "ETL transformation");
             }
-            if col.header.column_type == EtlColumnType::Ignore ||
-                col.header.column_type == EtlColumnType::HpoTextMining {
-                continue; // text mining column can be left empty
-            }
-            for etl_cell in &col.values {
-                if etl_cell.status != EtlCellStatus::Transformed {
-                    return Err(format!("Item '{}' is column {} was not transformed", 
-                    col.header.original,
-                    etl_cell.original))
+        }
+    }
+
+    /// Key of an allele in the allele_count_map of a row. Alleles of variant columns that are associated
+    /// with one gene of a digenic cohort are prefixed with the gene index (e.g., 1:c123AtoG_GENE_NM_000123v1)
+    fn allele_key(header: &EtlColumnHeader, allele: &str) -> String {
+        match header.gene_index {
+            Some(gene_index) => format!("{gene_index}:{allele}"),
+            None => allele.to_string(),
+        }
+    }
+
+    /// The variants of the cohort, keyed as in the allele_count_map of the rows: the variants of gene-indexed variant
+    /// columns are keyed by the prefixed key only (unless the same variant is also used by a column without gene index)
+    fn get_variant_maps(&self) -> (HashMap<String, HgvsVariant>, HashMap<String, StructuralVariant>, HashMap<String, IntergenicHgvsVariant>) {
+        let mut hgvs_variants = self.dto.hgvs_variants.clone();
+        let mut structural_variants = self.dto.structural_variants.clone();
+        let mut intergenic_variants = self.dto.intergenic_variants.clone();
+        let variant_columns: Vec<&ColumnDto> = self.dto.table.columns.iter()
+            .filter(|col| matches!(col.header.column_type, Variant | FatherVariant | MotherVariant))
+            .collect();
+        let unindexed_keys: HashSet<&str> = variant_columns.iter()
+            .filter(|col| col.header.gene_index.is_none())
+            .flat_map(|col| col.values.iter().map(|cell| cell.current.as_str()))
+            .collect();
+        for col in variant_columns.iter().filter(|col| col.header.gene_index.is_some()) {
+            for cell in &col.values {
+                let key = Self::allele_key(&col.header, &cell.current);
+                if let Some(hgvs) = self.dto.hgvs_variants.get(&cell.current) {
+                    hgvs_variants.insert(key, hgvs.clone());
+                } else if let Some(sv) = self.dto.structural_variants.get(&cell.current) {
+                    structural_variants.insert(key, sv.clone());
+                } else if let Some(ig) = self.dto.intergenic_variants.get(&cell.current) {
+                    intergenic_variants.insert(key, ig.clone());
+                } else {
+                    continue;
+                }
+                if !unindexed_keys.contains(cell.current.as_str()) {
+                    hgvs_variants.remove(&cell.current);
+                    structural_variants.remove(&cell.current);
+                    intergenic_variants.remove(&cell.current);
                 }
             }
         }
-        Ok(())
+        (hgvs_variants, structural_variants, intergenic_variants)
     }
 
+    /// If the table has FatherVariant and MotherVariant columns, each individual is the proband of a trio whose
+    /// parents are given by their alleles only (cells with "na" mean that the parent does not carry a variant).
+    fn get_trios(&self, rows: &[RowData]) -> Result<Vec<TrioData>, String> {
+        let father_columns = self.dto.get_columns_by_type(FatherVariant);
+        let mother_columns = self.dto.get_columns_by_type(MotherVariant);
+        if father_columns.is_empty() && mother_columns.is_empty() {
+            return Ok(vec![]);
+        }
+        if father_columns.is_empty() || mother_columns.is_empty() {
+            return Err("Trios require both a FatherVariant and a MotherVariant column".to_string());
+        }
+        let parental_alleles = |columns: &[&ColumnDto], i: usize| -> Vec<String> {
+            columns.iter()
+                .filter_map(|col| {
+                    let allele = col.values.get(i)?.current.trim();
+                    (!allele.is_empty() && allele != NOT_AVAILABLE).then(|| Self::allele_key(&col.header, allele))
+                })
+                .collect()
+        };
+        let trios = rows.iter().enumerate().map(|(i, row)| {
+            let proband_id = &row.individual_data.individual_id;
+            TrioData {
+                proband_id: proband_id.clone(),
+                father_id: format!("{proband_id} father"),
+                mother_id: format!("{proband_id} mother"),
+                father_alleles: Some(parental_alleles(&father_columns, i)),
+                mother_alleles: Some(parental_alleles(&mother_columns, i)),
+            }
+        }).collect();
+        Ok(trios)
+    }
 
-    fn qc(&mut self) -> Result<(), String> {
-        if self.raw_table().table.columns.is_empty() {
-            return Err("EtlDto table with no columns".to_string());
+    /// Note that only Mendelian is supported for Excel file bulk imports
+    /// Other MOIs are too complicated to be reliably imported in this way.
+    pub fn get_cohort_data(&mut self) -> Result<CohortData, String> {
+        self.log_transformations();
+        if let Some(error) = self.collect_all_qc_errors().into_iter().next() {
+            return Err(error.to_string());
         }
-        for col in &self.raw_table().table.columns {
-            if col.header.column_type == EtlColumnType::Raw {
-                return Err(format!("'{}' column type not set (Raw)", col.header.original))
+        self.mark_variant_cells();
+        let (arranged_duplets, disease) = self.get_hpo_headers_and_disease()?;
+        let mut row_list: Vec<RowData> = Vec::new();
+        let n_rows = self.get_row_count()?;
+        for row_index in 0..n_rows {
+            let row = self.get_row(row_index, &arranged_duplets, &disease)?;
+            row_list.push(row);
+        }
+        self.assemble_cohort_data(arranged_duplets, disease, row_list)
+    }
+
+    /// Same as [`Self::get_cohort_data`], but we report all Q/C problems of the table and all rows
+    /// that cannot be transformed rather than stopping at the first error.
+    pub fn get_cohort_data_collect_all(&mut self) -> Result<CohortData, Vec<EtlQcError>> {
+        self.log_transformations();
+        let errors = self.collect_all_qc_errors();
+        if !errors.is_empty() {
+            return Err(errors);
+        }
+        self.mark_variant_cells();
+        let (arranged_duplets, disease) = self.get_hpo_headers_and_disease()
+            .map_err(|e| vec![EtlQcError::table(e)])?;
+        let mut row_list: Vec<RowData> = Vec::new();
+        let mut errors: Vec<EtlQcError> = Vec::new();
+        let n_rows = self.get_row_count().map_err(|e| vec![EtlQcError::table(e)])?;
+        for row_index in 0..n_rows {
+            match self.extract_row(row_index, &arranged_duplets, &disease) {
+                Ok(row) => row_list.push(row),
+                Err(e) => errors.push(EtlQcError::row(row_index, e)),
             }
         }
-        self.qc_table_cells()?;
-        self.qc_variants()?;
-        self.qc_check_required_columns()?;
-        self.qc_pmid()?;
-        Ok(())
+        if !errors.is_empty() {
+            return Err(errors);
+        }
+        self.assemble_cohort_data(arranged_duplets, disease, row_list)
+            .map_err(|e| vec![EtlQcError::table(e)])
     }
 
+    /// Check the transformed cells of columns with a fixed set of values (see [`EtlColumnType::allowed_values`]).
+    /// Cells that are "na" or that were not transformed yet are skipped; the latter are reported by
+    /// [`Self::collect_all_qc_errors`] anyway.
+    pub fn validate_cell_values_for_typed_columns(&self) -> Result<(), Vec<EtlQcError>> {
+        let mut errors = Vec::new();
+        for col in &self.raw_table().table.columns {
+            let Some(allowed) = col.header.column_type.allowed_values() else {
+                continue;
+            };
+            for (i, cell) in col.values.iter().enumerate() {
+                let value = cell.current.as_str();
+                if value == NOT_AVAILABLE || !cell.status.is_usable() || allowed.contains(&value) {
+                    continue;
+                }
+                errors.push(EtlQcError::cell(&col.header.original, i,
+                    format!("'{}' is not an allowed value (allowed: {})", value, allowed.join(", "))));
+            }
+        }
+        if errors.is_empty() {
+            Ok(())
+        } else {
+            Err(errors)
+        }
+    }
 
+    /// All Q/C problems of the table, with one entry per offending cell, in the order in which they are found.
+    /// This is the only place where the Q/C rules live: [`Self::get_cohort_data`] reports the first entry,
+    /// [`Self::get_cohort_data_collect_all`] all of them. Partially transformed cells are accepted with a warning.
+    pub fn collect_all_qc_errors(&self) -> Vec<EtlQcError> {
+        let columns = &self.raw_table().table.columns;
+        if columns.is_empty() {
+            return vec![EtlQcError::table("EtlDto table with no columns")];
+        }
+        let mut errors = Vec::new();
+        if let Err(e) = self.get_row_count() {
+            errors.push(EtlQcError::table(e));
+        }
+        for col in columns {
+            let column_name = &col.header.original;
+            if col.header.column_type == EtlColumnType::Raw {
+                errors.push(EtlQcError::column(column_name, "column type not set (Raw)"));
+                continue;
+            }
+            if col.header.column_type == EtlColumnType::Ignore {
+                continue;
+            }
+            for (i, cell) in col.values.iter().enumerate() {
+                if cell.current.starts_with(char::is_whitespace) {
+                    errors.push(EtlQcError::cell(column_name, i, format!("leading whitespace - '{}'", cell.current)));
+                }
+                if cell.current.ends_with(char::is_whitespace) {
+                    errors.push(EtlQcError::cell(column_name, i, format!("trailing whitespace - '{}'", cell.current)));
+                }
+                if let Some(ch) = cell.current.chars().find(|ch| !Self::is_valid_char(*ch)) {
+                    errors.push(EtlQcError::cell(column_name, i, format!("Invalid character: U+{:04X} '{}'", ch as u32, ch)));
+                }
+                if col.header.column_type == EtlColumnType::HpoTextMining {
+                    continue; // text mining column can be left empty
+                }
+                if cell.status == EtlCellStatus::PartiallyTransformed {
+                    tracing::warn!(column = %column_name, value = %cell.current,
+                        "Partially transformed cell needs manual review");
+                } else if !cell.status.is_usable() {
+                    errors.push(EtlQcError::cell(column_name, i, format!("'{}' was not transformed", cell.original)));
+                }
+            }
+        }
+        if let Err(e) = self.qc_check_required_columns() {
+            errors.push(EtlQcError::table(e));
+        }
+        if let Err(e) = self.qc_pmid() {
+            errors.push(EtlQcError::table(e));
+        }
+        if let Err(value_errors) = self.validate_cell_values_for_typed_columns() {
+            errors.extend(value_errors);
+        }
+        errors
+    }
 
-    /// Note that only Mendelian is supported for Excel file bulk imports
-    /// Other MOIs are too complicated to be reliably imported in this way.
-    pub fn get_cohort_data(&mut self) -> Result<CohortData, String> {
-        self.check_is_completely_transformed()?;
-        self.qc()?;
+    fn get_hpo_headers_and_disease(&self) -> Result<(Vec<HpoTermDuplet>, DiseaseData), String> {
         let hpo_duplets = Self::all_hpo_duplets(self);
         let arranged_duplets = hpo::arrange_hpo_duplets(self.hpo.clone(), &hpo_duplets)?;
         let disease = match &self.dto.disease {
             Some(d) => d.clone(),
             None => { return Err("Cannot create CohortData if ETL does not have disease data".to_string())},
         };
-        let mut row_list: Vec<RowData> = Vec::new();
-        let n_rows = self.get_row_count()?;
-        for row_index in 0..n_rows {
-            let row = self.get_row(row_index, &arranged_duplets, &disease)?;
-            row_list.push(row);
-        }
+        Ok((arranged_duplets, disease))
+    }
+
+    fn assemble_cohort_data(
+        &self,
+        arranged_duplets: Vec<HpoTermDuplet>,
+        disease: DiseaseData,
+        row_list: Vec<RowData>
+    ) -> Result<CohortData, String> {
+        let (hgvs_variants, structural_variants, intergenic_variants) = self.get_variant_maps();
+        let trios = self.get_trios(&row_list)?;
         Ok(CohortData { 
             cohort_type: CohortType::Mendelian, 
             disease_list: vec![disease], 
             hpo_headers: arranged_duplets, 
             rows: row_list, 
-            hgvs_variants: self.dto.hgvs_variants.clone(), 
-            structural_variants: self.dto.structural_variants.clone(), 
-            intergenic_variants: self.dto.intergenic_variants.clone(),
+            hgvs_variants, 
+            structural_variants, 
+            intergenic_variants,
             phetools_schema_version: CohortData::phenopackets_schema_version(), 
             hpo_version: self.hpo.version().to_string(), 
             cohort_acronym: None,
-            curation_history: vec![]
+            curation_history: vec![],
+            trios,
         })
     }
 
@@ -604,52 +1113,154 @@ impl EtlTools {
     on_progress: F) 
         -> Result<EtlDto, String> 
         where F: FnMut(u32, u32) {
-    let item_count = self.dto.table.columns[column].values.len() as u32;
-    let all_alleles: HashSet<String> = self.dto.table.columns[column].values.iter()
+    let gt = self.gene_transcript(None)?;
+    self.process_alleles(column, &gt, None, on_progress, None)
+   }
+
+   /// Process a variant column of a digenic cohort whose alleles belong to the gene with index `gene_index`
+   /// in the `gene_transcript_list` of the disease. The validated variants are added to those of the other columns.
+   pub fn process_allele_column_for_gene<F>(
+    &self,
+    column: usize,
+    gene_index: usize,
+    on_progress: F)
+        -> Result<EtlDto, String>
+        where F: FnMut(u32, u32) {
+    let gt = self.gene_transcript(Some(gene_index))?;
+    self.process_alleles(column, &gt, Some(gene_index), on_progress, None)
+   }
+
+   /// Process a variant column (for the gene of its `gene_index`, if any) and save the table to `checkpoint` after
+   /// each batch of [`CHECKPOINT_BATCH_SIZE`] alleles and at the end, so that an interrupted validation of a
+   /// large column can be resumed with [`Self::from_checkpoint`].
+   pub fn process_allele_column_with_checkpoint<F>(
+    &self,
+    column: usize,
+    checkpoint: &Path,
+    on_progress: F)
+        -> Result<EtlDto, String>
+        where F: FnMut(u32, u32) {
+    let gene_index = self.dto.table.columns.get(column)
+        .ok_or_else(|| format!("Invalid column index {column}"))?
+        .header.gene_index;
+    let gt = self.gene_transcript(gene_index)?;
+    let mut save = |dto: &EtlDto| Self::write_checkpoint(dto, checkpoint);
+    let dto = self.process_alleles(column, &gt, gene_index, on_progress, Some(&mut save))?;
+    save(&dto)?;
+    Ok(dto)
+   }
+
+   /// The gene of a variant column: the only gene of the disease, or the gene with index `gene_index` for digenic cohorts
+   fn gene_transcript(&self, gene_index: Option<usize>) -> Result<GeneTranscriptData, String> {
+    let disease = self.dto.disease.as_ref().ok_or_else(|| "No disease data available".to_string())?;
+    match gene_index {
+        Some(gene_index) => disease.gene_transcript_list.get(gene_index)
+            .cloned()
+            .ok_or_else(|| format!("Invalid gene index {gene_index} for {} genes", disease.gene_transcript_list.len())),
+        None if disease.gene_transcript_list.len() == 1 => Ok(disease.gene_transcript_list[0].clone()),
+        None => Err("Could not extract symbol/HGNC/transcript information".to_string()),
+    }
+   }
+
+   /// True if the cell refers to one of the validated variants of `dto`
+   fn is_validated_cell(dto: &EtlDto, cell: &EtlCellValue) -> bool {
+    cell.status == EtlCellStatus::Transformed
+        && (dto.hgvs_variants.contains_key(&cell.current)
+            || dto.structural_variants.contains_key(&cell.current)
+            || dto.intergenic_variants.contains_key(&cell.current))
+   }
+
+   /// Validate the alleles of the column and replace them by the variant keys. Cells that already refer to a validated
+   /// variant (e.g., after resuming from a checkpoint) are not validated again. If `on_batch` is given, the alleles are
+   /// validated in batches and the intermediate table is passed to `on_batch` after each batch.
+   fn process_alleles<F>(
+    &self,
+    column: usize,
+    gt: &GeneTranscriptData,
+    gene_index: Option<usize>,
+    mut on_progress: F,
+    mut on_batch: Option<&mut dyn FnMut(&EtlDto) -> Result<(), String>>)
+        -> Result<EtlDto, String>
+        where F: FnMut(u32, u32) {
+    let mut etl_n = self.dto.clone();
+    let col = etl_n.table.columns.get_mut(column).ok_or_else(|| format!("Invalid column index {column}"))?;
+    col.header.column_type = EtlColumnType::Variant;
+    col.header.gene_index = gene_index;
+    let is_validated = |cell: &EtlCellValue| cell.status == EtlCellStatus::Transformed && self.is_mapped_variant(&cell.current);
+    let cells = &self.dto.table.columns[column].values;
+    let item_count = cells.iter().filter(|cell| !is_validated(cell)).count() as u32;
+    let mut all_alleles: Vec<String> = cells.iter()
+        .filter(|cell| !is_validated(cell))
         .map(|cell| cell.original.clone())
+        .collect::<HashSet<String>>()
+        .into_iter()
         .collect();
-    let (symbol, hgnc, transcript) = if let Some(disease) = &self.dto.disease {
-        if disease.gene_transcript_list.len() == 1 {
-            let gt = &disease.gene_transcript_list[0];
-            ( gt.gene_symbol.clone(), gt.hgnc_id.clone(), gt.transcript.clone(), )
-        } else {
-            return Err("Could not extract symbol/HGNC/transcript information".to_string());
-        }
-    } else {
-        return Err("No disease data available".to_string());
+    all_alleles.sort();
+    let batch_size = match on_batch {
+        Some(_) => CHECKPOINT_BATCH_SIZE,
+        None => all_alleles.len().max(1),
     };
-    let mut vmanager = VariantManager::new(&symbol, &hgnc, &transcript);
-    vmanager.validate_all_variants(&all_alleles,  on_progress, item_count)?;
+    let mut vmanager = VariantManager::new(&gt.gene_symbol, &gt.hgnc_id, &gt.transcript);
+    let mut n_done: u32 = 0;
+    for batch in all_alleles.chunks(batch_size) {
+        let batch: HashSet<String> = batch.iter().cloned().collect();
+        vmanager.validate_all_variants(&batch, |p, _| on_progress(n_done + p, item_count), item_count)?;
+        n_done += batch.len() as u32;
+        Self::add_validated_alleles(&mut etl_n, column, &mut vmanager);
+        if let Some(on_batch) = on_batch.as_mut() {
+            on_batch(&etl_n)?;
+        }
+    }
+    let mut cells = std::mem::take(&mut etl_n.table.columns[column].values);
+    for cell in cells.iter_mut().filter(|cell| !Self::is_validated_cell(&etl_n, cell)) {
+        cell.status = EtlCellStatus::Error;
+        cell.error = Some(format!("Unknown allele: '{}'", cell.original));
+    }
+    etl_n.table.columns[column].values = cells;
+    // Keep the variants of all variant columns, e.g., of the other allele column or of the other gene of a digenic cohort
+    let referenced: HashSet<String> = etl_n.table.columns.iter()
+        .filter(|col| matches!(col.header.column_type, EtlColumnType::Variant | FatherVariant | MotherVariant))
+        .flat_map(|col| col.values.iter())
+        .filter(|cell| cell.status == EtlCellStatus::Transformed)
+        .map(|cell| cell.current.clone())
+        .collect();
+    etl_n.hgvs_variants.retain(|key, _| referenced.contains(key));
+    etl_n.structural_variants.retain(|key, _| referenced.contains(key));
+    etl_n.intergenic_variants.retain(|key, _| referenced.contains(key));
+    Ok(etl_n)
+   }
+
+   /// Move the variants validated so far from `vmanager` to `dto` and transform the cells of `column` with these alleles
+   fn add_validated_alleles(dto: &mut EtlDto, column: usize, vmanager: &mut VariantManager) {
     let hgvs_d = vmanager.hgvs_map();
     let sv_d = vmanager.sv_map();
     let intergenic_d = vmanager.intergenic_map();
     let mut allele_key_map: HashMap<String, String> = HashMap::new();
-    for (key, val) in hgvs_d.iter() {
+    for val in hgvs_d.values() {
         allele_key_map.insert(val.hgvs().to_string(), val.variant_key().to_string());
     };
-    for (key, val) in sv_d.iter() {
+    for val in sv_d.values() {
         allele_key_map.insert(val.label().to_string(), val.variant_key().to_string());
     }
-    for (key, val) in intergenic_d.iter() {
+    for val in intergenic_d.values() {
         allele_key_map.insert(val.g_hgvs().to_string(), val.variant_key().to_string());
     }
-    let mut etl_n = self.dto.clone();
-       for cell in etl_n.table.columns[column].values.iter_mut() {
-        let allele = &cell.original;
-        if let Some(new_val) = allele_key_map.get(allele) {
+    dto.hgvs_variants.extend(hgvs_d);
+    dto.structural_variants.extend(sv_d);
+    dto.intergenic_variants.extend(intergenic_d);
+    let mut cells = std::mem::take(&mut dto.table.columns[column].values);
+    for cell in cells.iter_mut() {
+        if Self::is_validated_cell(dto, cell) {
+            continue;
+        }
+        if let Some(new_val) = allele_key_map.get(&cell.original) {
+            tracing::debug!(before = %cell.original, after = %new_val, "ETL allele transformation");
             cell.current = new_val.to_string();
             cell.status = EtlCellStatus::Transformed;
             cell.error = None;
-        } else {
-            cell.status = EtlCellStatus::Error;
-            cell.error = Some(format!("Unknown allele: '{}'", allele));
         }
     }
-    etl_n.table.columns[column].header.column_type = EtlColumnType::Variant;
-    etl_n.hgvs_variants = hgvs_d;
-    etl_n.structural_variants = sv_d;
-    etl_n.intergenic_variants = intergenic_d;
-    Ok(etl_n)
+    dto.table.columns[column].values = cells;
    }
   
 }
@@ -32,21 +32,158 @@
 //! formats found in real-world supplemental datasets, while still converging
 //! to a consistent internal representation.
 //!
+//! ## Undo
+//!
+//! The transformation functions of this module take an [`EtlDto`] and return the transformed table; they do not
+//! keep any history. To be able to undo a step, apply it through an [`EtlSession`], e.g.,
+//! `session.apply(|dto| apply_transformations(hpo.clone(), dto, transforms))`.
+//!
 //! ## Key Types
 //!
 //! - [`EtlDto`]: The intermediate representation of an external table.
 //! - [`CohortData`]: The normalized, final representation of a cohort.
 
-use std::sync::Arc;
+use std::{fmt, path::Path, sync::Arc};
 
 use ontolius::ontology::csr::FullCsrOntology;
+use serde::Serialize;
 
-use crate::{dto::{cohort_dto::CohortData, etl_dto::EtlDto}, etl::etl_tools::EtlTools};
+use crate::{dto::{cohort_dto::CohortData, etl_dto::{EtlDto, EtlTransform}}, error::PheToolsError, etl::etl_tools::EtlTools};
 
 
 
 mod etl_tools;
 
+/// Representations of missing data in external tables that [`normalize_missing_values`] replaces by "na" by default
+pub const DEFAULT_MISSING_VALUE_ALIASES: [&str; 9] = ["N/A", "NA", "ND", "n/a", "unknown", "Unknown", "--", "-", "not reported"];
+
+/// How [`get_cohort_data_from_etl_dto`] handles problems in the table
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum FailureMode {
+    /// Stop at the first problem ([`PheToolsError::EtlTransformFailed`])
+    #[default]
+    FailFast,
+    /// Report all problems of the table, or of all rows, at once ([`PheToolsError::EtlQcFailed`])
+    CollectAll,
+}
+
+/// A problem in an ETL table; the column and the (zero-based) row are given if the problem is specific to them
+#[derive(Clone, Debug, PartialEq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct EtlQcError {
+    pub column: Option<String>,
+    pub row: Option<usize>,
+    pub message: String,
+}
+
+impl EtlQcError {
+    pub(crate) fn table(message: impl Into<String>) -> Self {
+        Self { column: None, row: None, message: message.into() }
+    }
+
+    pub(crate) fn column(column: &str, message: impl Into<String>) -> Self {
+        Self { column: Some(column.to_string()), row: None, message: message.into() }
+    }
+
+    pub(crate) fn row(row: usize, message: impl Into<String>) -> Self {
+        Self { column: None, row: Some(row), message: message.into() }
+    }
+
+    pub(crate) fn cell(column: &str, row: usize, message: impl Into<String>) -> Self {
+        Self { column: Some(column.to_string()), row: Some(row), message: message.into() }
+    }
+}
+
+impl fmt::Display for EtlQcError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match (&self.column, self.row) {
+            (Some(column), Some(row)) => write!(f, "{column} (row {row}): {}", self.message),
+            (Some(column), None) => write!(f, "{column}: {}", self.message),
+            (None, Some(row)) => write!(f, "Row {row}: {}", self.message),
+            (None, None) => write!(f, "{}", self.message),
+        }
+    }
+}
+
+/// Undo/redo history of the states of an ETL table during curation in the GUI.
+///
+/// This is the only place where a history is kept: calling a transformation function of this module directly
+/// changes the table without a snapshot, so a step can only be undone if it was applied through the session.
+///
+/// The session holds the states from the oldest to the newest and the index of the current state. Transformations
+/// are applied with [`EtlSession::apply`] (or their result is recorded with [`EtlSession::push_state`]), which keeps
+/// the previous state so that it can be restored with [`EtlSession::undo`]. Recording a new state after an undo
+/// discards the states that could have been redone. At most `max_history` states are kept; the oldest ones are dropped.
+#[derive(Clone, Debug)]
+pub struct EtlSession {
+    history: Vec<EtlDto>,
+    current: usize,
+    max_history: usize,
+}
+
+impl EtlSession {
+    /// Start a session with the initial state of the table; `max_history` is at least 1 (the current state)
+    pub fn new(initial: EtlDto, max_history: usize) -> Self {
+        Self {
+            history: vec![initial],
+            current: 0,
+            max_history: max_history.max(1),
+        }
+    }
+
+    /// The current state of the table
+    pub fn current(&self) -> &EtlDto {
+        &self.history[self.current]
+    }
+
+    /// Record a new state (e.g., after the user assigned a column type in the GUI)
+    pub fn push_state(&mut self, dto: EtlDto) {
+        self.history.truncate(self.current + 1);
+        self.history.push(dto);
+        if self.history.len() > self.max_history {
+            let n_dropped = self.history.len() - self.max_history;
+            self.history.drain(..n_dropped);
+        }
+        self.current = self.history.len() - 1;
+    }
+
+    /// Apply a transformation (e.g., [`process_allele_column`] or [`reorder_columns`]) to the current state and
+    /// record the result; the state before the transformation remains available to [`Self::undo`].
+    /// If the transformation fails, the history is not changed.
+    pub fn apply<F>(&mut self, transform: F) -> Result<&EtlDto, PheToolsError>
+    where F: FnOnce(EtlDto) -> Result<EtlDto, PheToolsError> {
+        let transformed = transform(self.current().clone())?;
+        self.push_state(transformed);
+        Ok(self.current())
+    }
+
+    /// Go back to the previous state and return it, or None if there is no previous state
+    pub fn undo(&mut self) -> Option<EtlDto> {
+        if !self.can_undo() {
+            return None;
+        }
+        self.current -= 1;
+        Some(self.current().clone())
+    }
+
+    /// Go forward to the state that was undone last and return it, or None if there is none
+    pub fn redo(&mut self) -> Option<EtlDto> {
+        if !self.can_redo() {
+            return None;
+        }
+        self.current += 1;
+        Some(self.current().clone())
+    }
+
+    pub fn can_undo(&self) -> bool {
+        self.current > 0
+    }
+
+    pub fn can_redo(&self) -> bool {
+        self.current + 1 < self.history.len()
+    }
+}
+
 /// Transform an [`EtlDto`] into a [`CohortData`] structure.
 ///
 /// This function takes as input:
@@ -59,8 +196,10 @@ mod etl_tools;
 ///
 /// # Errors
 ///
-/// Returns an `Err(String)` if the transformation fails (e.g., due to
-/// malformed input or missing ontology references).
+/// With [`FailureMode::FailFast`], returns [`PheToolsError::EtlTransformFailed`] for the first problem that
+/// prevents the transformation (e.g., malformed input or missing ontology references). With
+/// [`FailureMode::CollectAll`], returns [`PheToolsError::EtlQcFailed`] with all problems of the table
+/// (e.g., untransformed or malformed cells) or, if the table passes the Q/C, with all rows that cannot be transformed.
 ///
 /// # Note
 /// 
@@ -70,17 +209,157 @@ mod etl_tools;
 pub fn get_cohort_data_from_etl_dto(
     hpo: Arc<FullCsrOntology>,
     dto: EtlDto,
-) -> Result<CohortData, String> {
+    failure_mode: FailureMode,
+) -> Result<CohortData, PheToolsError> {
     let mut etl_tools = EtlTools::from_dto(hpo, &dto);
-    etl_tools.get_cohort_data()
+    match failure_mode {
+        FailureMode::FailFast => etl_tools.get_cohort_data().map_err(PheToolsError::EtlTransformFailed),
+        FailureMode::CollectAll => etl_tools.get_cohort_data_collect_all().map_err(PheToolsError::EtlQcFailed),
+    }
+}
+
+
+/// Check an [`EtlDto`] for obvious problems before the stepwise transformation is started.
+///
+/// # Errors
+///
+/// Returns all problems that were found: duplicate ids in the PatientId column (if it was already set),
+/// cells with invisible control characters (U+0000–U+001F, U+200B–U+200F), and columns without any values.
+pub fn validate_etl_dto_before_transform(dto: &EtlDto) -> Result<(), Vec<String>> {
+    EtlTools::validate_dto_before_transform(dto)
+}
+
+
+/// Replace the many representations of missing data in an external table (e.g., "N/A", "ND", or "unknown";
+/// see [`DEFAULT_MISSING_VALUE_ALIASES`]) by "na".
+///
+/// The pipeline does not call this function by itself: the caller must call it (or apply
+/// [`EtlTransform::NormalizeMissingValues`]) as one of the first steps of the transformation, because the later
+/// steps (e.g., of HPO or age columns) reject values they cannot parse. Cells that are normalized are marked as
+/// transformed. No undo snapshot is taken unless the call is wrapped in [`EtlSession::apply`].
+pub fn normalize_missing_values(
+    etl: EtlDto,
+    missing_aliases: &[&str],
+) -> EtlDto {
+    EtlTools::normalize_missing_values(etl, missing_aliases)
+}
+
+
+/// Save the state of an ETL transformation, including the validated variants, to a JSON file, so that the
+/// transformation can be resumed with [`load_etl_checkpoint`] if it is interrupted (e.g., while validating
+/// the variants of a large cohort).
+pub fn save_etl_checkpoint(
+    hpo: Arc<FullCsrOntology>,
+    etl: &EtlDto,
+    path: &Path,
+) -> Result<(), PheToolsError> {
+    let etl_tools = EtlTools::from_dto(hpo, etl);
+    etl_tools.save_checkpoint(path).map_err(PheToolsError::EtlTransformFailed)
+}
+
+/// Load a state saved with [`save_etl_checkpoint`]. When the variant columns are processed again
+/// ([`process_allele_column`]), alleles that were already validated are skipped.
+pub fn load_etl_checkpoint(
+    hpo: Arc<FullCsrOntology>,
+    path: &Path,
+) -> Result<EtlDto, PheToolsError> {
+    let etl_tools = EtlTools::from_checkpoint(path, hpo).map_err(PheToolsError::EtlTransformFailed)?;
+    Ok(etl_tools.raw_table().clone())
 }
 
 
+/// Validate the alleles of a variant column; returns [`PheToolsError::VariantValidationFailed`] if this is not possible.
+/// Cells that already refer to one of the validated variants of the table are skipped.
+/// Wrap the call in [`EtlSession::apply`] to be able to undo it.
 pub fn process_allele_column<F>(
     hpo: Arc<FullCsrOntology>,
     etl: EtlDto,
     col: usize,
-    on_progress: F) -> Result<EtlDto, String> where F: FnMut(u32, u32) {
+    on_progress: F) -> Result<EtlDto, PheToolsError> where F: FnMut(u32, u32) {
+    let etl_tools = EtlTools::from_etl(etl, hpo);
+    etl_tools.process_allele_column(col, on_progress).map_err(PheToolsError::VariantValidationFailed)
+}
+
+
+/// Validate the alleles of a variant column of a digenic cohort. `gene_index` is the index of the gene
+/// in the `gene_transcript_list` of the disease; the allele keys of this column will be prefixed with it.
+/// As for [`process_allele_column`], undo requires [`EtlSession::apply`].
+pub fn process_allele_column_for_gene<F>(
+    hpo: Arc<FullCsrOntology>,
+    etl: EtlDto,
+    col: usize,
+    gene_index: usize,
+    on_progress: F) -> Result<EtlDto, PheToolsError> where F: FnMut(u32, u32) {
+    let etl_tools = EtlTools::from_etl(etl, hpo);
+    etl_tools.process_allele_column_for_gene(col, gene_index, on_progress)
+        .map_err(PheToolsError::VariantValidationFailed)
+}
+
+
+/// Validate the alleles of a variant column (for the gene of its `gene_index`, if set) and save the table to
+/// `checkpoint` after every batch of alleles and at the end. If the validation is interrupted, the table can be
+/// loaded with [`load_etl_checkpoint`], and processing the column again skips the alleles validated before.
+/// The checkpoint is not an undo history; use [`EtlSession::apply`] for that.
+pub fn process_allele_column_with_checkpoint<F>(
+    hpo: Arc<FullCsrOntology>,
+    etl: EtlDto,
+    col: usize,
+    checkpoint: &Path,
+    on_progress: F) -> Result<EtlDto, PheToolsError> where F: FnMut(u32, u32) {
+    let etl_tools = EtlTools::from_etl(etl, hpo);
+    etl_tools.process_allele_column_with_checkpoint(col, checkpoint, on_progress)
+        .map_err(PheToolsError::VariantValidationFailed)
+}
+
+
+/// Apply several transformation steps (e.g., all column assignments the user made in the GUI) in one call.
+/// The steps are applied in the given order; [`EtlTransform::ProcessAlleles`] validates the alleles with
+/// Variant Validator, as [`process_allele_column`] does. All steps of one call form a single undo step if the
+/// call is wrapped in [`EtlSession::apply`]; a direct call keeps no history.
+///
+/// # Errors
+///
+/// Returns [`PheToolsError::EtlTransformFailed`] naming the first step that failed (e.g., a column index out of
+/// range or an HPO term with an outdated label); the changes of the previous steps are not returned in this case.
+pub fn apply_transformations(
+    hpo: Arc<FullCsrOntology>,
+    etl: EtlDto,
+    transforms: Vec<EtlTransform>,
+) -> Result<EtlDto, PheToolsError> {
+    let etl_tools = EtlTools::from_etl(etl, hpo);
+    etl_tools.apply_transformations(transforms).map_err(PheToolsError::EtlTransformFailed)
+}
+
+
+/// Reorder the columns of the external table; column `i` of the result is column `new_order[i]` of `etl`.
+/// Use [`EtlSession::apply`] to make the reordering undoable.
+///
+/// # Errors
+///
+/// Returns [`PheToolsError::EtlTransformFailed`] if `new_order` is not a permutation of the column indices
+/// (wrong length, an index out of range, or an index that appears more than once).
+pub fn reorder_columns(
+    hpo: Arc<FullCsrOntology>,
+    etl: EtlDto,
+    new_order: &[usize],
+) -> Result<EtlDto, PheToolsError> {
+    let etl_tools = EtlTools::from_etl(etl, hpo);
+    etl_tools.reorder_columns(new_order).map_err(PheToolsError::EtlTransformFailed)
+}
+
+
+/// Combine the rows of patients that are listed in more than one row of the external table
+/// (e.g., one row per phenotype), merging their HPO annotations. Use [`EtlSession::apply`] to make the merge
+/// undoable.
+///
+/// # Errors
+///
+/// Returns [`PheToolsError::EtlTransformFailed`] if there is no PatientId column or if the rows of a patient have
+/// conflicting values in other columns (e.g., sex or age of onset).
+pub fn merge_duplicate_patient_ids(
+    hpo: Arc<FullCsrOntology>,
+    etl: EtlDto,
+) -> Result<EtlDto, PheToolsError> {
     let etl_tools = EtlTools::from_etl(etl, hpo);
-    etl_tools.process_allele_column(col, on_progress)
+    etl_tools.merge_duplicate_patient_ids().map_err(PheToolsError::EtlTransformFailed)
 }
@@ -3,7 +3,7 @@ use std::{collections::{HashMap, HashSet}, sync::Arc};
 use ontolius::ontology::csr::FullCsrOntology;
 use serde::{Deserialize,Serialize};
 
-use crate::dto::{cohort_dto::{CohortData, DiseaseData, RowData}, hpo_term_dto::HpoTermDuplet};
+use crate::{dto::{cohort_dto::{CohortData, DiseaseData, RowData}, hpo_term_dto::HpoTermDuplet}, export::{onset_distribution::OnsetDistribution, sex_statistics::SexStatistics, variant_distribution::VariantTypeDistribution}};
 
 
 #[derive(Clone, Debug, Deserialize, Serialize)]
@@ -107,6 +107,36 @@ impl TopLevelHpoRenderer  {
 
 
 
+/// Frequency of one HPO term in the cohort (observed/measured)
+#[derive(Clone, Debug, Deserialize, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct HpoFrequency {
+    pub hpo_id: String,
+    pub hpo_label: String,
+    /// Number of individuals in whom the term was observed
+    pub observed: usize,
+    /// Number of individuals in whom the term was explicitly observed or excluded
+    pub measured: usize,
+    pub percentage: f64,
+}
+
+impl HpoFrequency {
+    pub fn new(duplet: &HpoTermDuplet, observed: usize, measured: usize) -> Self {
+        let percentage = if measured == 0 {
+            0.0
+        } else {
+            100.0 * observed as f64 / measured as f64
+        };
+        Self {
+            hpo_id: duplet.hpo_id().to_string(),
+            hpo_label: duplet.hpo_label().to_string(),
+            observed,
+            measured,
+            percentage,
+        }
+    }
+}
+
 /// Data class for rendering HTML
 #[derive(Clone, Debug, Deserialize, Serialize)]
 #[serde(rename_all = "camelCase")]
@@ -117,7 +147,17 @@ pub struct CohortRenderer {
     pub cohort_type: String,
     pub n_phenopackets: usize,
     pub n_distinct_hpo_terms: usize,
+    pub n_variants: usize,
+    /// Mean pairwise phenotypic dissimilarity of the individuals (0-1)
+    pub phenotypic_heterogeneity: f64,
+    pub onset_distribution: OnsetDistribution,
+    pub onset_summary: String,
+    pub sex_statistics: SexStatistics,
+    pub sex_summary: String,
+    pub variant_distribution: VariantTypeDistribution,
     pub disease_list: Vec<DiseaseData>,
+    /// Frequencies of the HPO terms, sorted by descending frequency
+    pub hpo_frequencies: Vec<HpoFrequency>,
     pub top_level_list: Vec<TopLevelHpoRenderer>,
     pub individuals: Vec<IndividualRow>
 }
@@ -139,7 +179,7 @@ impl CohortRenderer {
         let top_level_map = match crate::hpo::get_hpo_terms_by_toplevel(cohort.clone(), hpo.clone()){
             Ok(tlmap) => tlmap,
             Err(e) => {
-                eprint!("{}", e);
+                tracing::warn!("Could not group HPO terms by top-level term: {}", e);
                 HashMap::<String, Vec<HpoTermDuplet>>::new()
             }
         };
@@ -150,7 +190,11 @@ impl CohortRenderer {
             top_level_list.push(top_level);
         }
         let individuals = Self::get_individuals(cohort);
-        
+        let hpo_frequencies = Self::get_hpo_frequencies(cohort);
+        let phenotypic_heterogeneity = crate::hpo::compute_phenotypic_heterogeneity(cohort, hpo.clone());
+        let onset_distribution = OnsetDistribution::from_cohort(cohort);
+        let sex_statistics = SexStatistics::from_cohort(cohort);
+        let variant_distribution = VariantTypeDistribution::from_cohort(cohort);
     
         Ok(Self {  
             acronym: acronym,
@@ -159,13 +203,40 @@ impl CohortRenderer {
             cohort_type: cohort.cohort_type.to_string(),
             n_phenopackets: cohort.rows.len(),
             n_distinct_hpo_terms: cohort.hpo_headers.len(),
+            n_variants: cohort.hgvs_variants.len() + cohort.structural_variants.len() + cohort.intergenic_variants.len(),
             disease_list: cohort.disease_list.clone(),
+            phenotypic_heterogeneity,
+            onset_summary: onset_distribution.to_summary_string(),
+            onset_distribution,
+            sex_summary: sex_statistics.to_summary_string(),
+            sex_statistics,
+            variant_distribution,
+            hpo_frequencies,
             top_level_list,
             individuals
         })
     }
 
 
+    /// Count how often each HPO term was observed/measured. Terms are sorted by descending frequency,
+    /// then by the number of measurements, and then by label.
+    fn get_hpo_frequencies(cohort: &CohortData) -> Vec<HpoFrequency> {
+        let mut frequencies: Vec<HpoFrequency> = cohort.hpo_headers
+            .iter()
+            .enumerate()
+            .map(|(i, duplet)| {
+                let (observed, measured) = crate::hpo::count_observed_and_measured(cohort, i);
+                HpoFrequency::new(duplet, observed, measured)
+            })
+            .collect();
+        frequencies.sort_by(|a, b| {
+            b.percentage.total_cmp(&a.percentage)
+                .then(b.measured.cmp(&a.measured))
+                .then(a.hpo_label.cmp(&b.hpo_label))
+        });
+        frequencies
+    }
+
     fn get_individuals(cohort: &CohortData) -> Vec<IndividualRow> {
         let mut individuals = Vec::new();
         for row in &cohort.rows {
@@ -255,6 +326,7 @@ impl CohortRenderer {
                         crate::dto::hpo_term_dto::CellValueInner::Excluded => data_row.push(RenderCell::excluded()),
                         crate::dto::hpo_term_dto::CellValueInner::Na => data_row.push(RenderCell::na()),
                         crate::dto::hpo_term_dto::CellValueInner::OnsetAge(onset) => data_row.push(RenderCell::onset(onset.to_string())),
+                        crate::dto::hpo_term_dto::CellValueInner::HpoFrequency(_) => data_row.push(RenderCell::na()),
                     }
                 }
             }
@@ -0,0 +1,132 @@
+//! CohortStats: Machine-readable summary statistics of a cohort
+//!
+//! This is the structure written by `phetools stats --format json`. Since the JSON is meant to be
+//! consumed by CI pipelines, any change to the fields must be accompanied by a new [`COHORT_STATS_SCHEMA_VERSION`].
+use serde::{Deserialize, Serialize};
+
+use crate::{dto::cohort_dto::CohortData, export::{onset_distribution::OnsetDistribution, sex_statistics::SexStatistics, variant_distribution::VariantTypeDistribution}, hpo};
+
+
+/// Version of the JSON representation of [`CohortStats`]
+pub const COHORT_STATS_SCHEMA_VERSION: &str = "1.0";
+
+
+/// One of the most frequent HPO terms of the cohort
+#[derive(Clone, Debug, Deserialize, Serialize, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct RankedHpoTerm {
+    pub hpo_id: String,
+    pub hpo_label: String,
+    pub observed: usize,
+    pub ascertained: usize,
+    pub percentage: f64,
+}
+
+
+#[derive(Clone, Debug, Deserialize, Serialize, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct CohortStats {
+    pub schema_version: String,
+    pub cohort_acronym: String,
+    pub n_individuals: usize,
+    pub n_hpo_terms: usize,
+    pub n_variants: usize,
+    pub sex: SexStatistics,
+    pub variant_types: VariantTypeDistribution,
+    /// The most frequent HPO terms, in descending order of frequency
+    pub top_hpo_terms: Vec<RankedHpoTerm>,
+    pub onset: OnsetDistribution,
+}
+
+impl CohortStats {
+    pub fn from_cohort(cohort: &CohortData, n_top_terms: usize, min_ascertainment: usize) -> Self {
+        let top_hpo_terms = hpo::top_n_hpo_terms(cohort, n_top_terms, min_ascertainment)
+            .into_iter()
+            .map(|(duplet, freq)| RankedHpoTerm {
+                hpo_id: duplet.hpo_id().to_string(),
+                hpo_label: duplet.hpo_label().to_string(),
+                observed: freq.observed,
+                ascertained: freq.ascertained,
+                percentage: freq.percentage,
+            })
+            .collect();
+        Self {
+            schema_version: COHORT_STATS_SCHEMA_VERSION.to_string(),
+            cohort_acronym: cohort.acronym(),
+            n_individuals: cohort.rows.len(),
+            n_hpo_terms: cohort.hpo_headers.len(),
+            n_variants: cohort.hgvs_variants.len() + cohort.structural_variants.len() + cohort.intergenic_variants.len(),
+            sex: SexStatistics::from_cohort(cohort),
+            variant_types: VariantTypeDistribution::from_cohort(cohort),
+            top_hpo_terms,
+            onset: OnsetDistribution::from_cohort(cohort),
+        }
+    }
+
+    /// Human-readable, multi-line summary
+    pub fn to_text(&self) -> String {
+        let mut lines = vec![
+            format!("Cohort: {}", self.cohort_acronym),
+            format!("Individuals: {}", self.n_individuals),
+            format!("HPO terms: {}", self.n_hpo_terms),
+            format!("Variants: {}", self.n_variants),
+            self.sex.to_summary_string(),
+            format!("Variant types: missense {}, nonsense {}, frameshift {}, splice {}, deletion {}, insertion {}, duplication {}, SV {}, other {}",
+                self.variant_types.missense, self.variant_types.nonsense, self.variant_types.frameshift,
+                self.variant_types.splice, self.variant_types.deletion, self.variant_types.insertion,
+                self.variant_types.duplication, self.variant_types.sv, self.variant_types.other),
+            self.onset.to_summary_string(),
+            String::new(),
+            format!("{:<12}{:<50}{:>12}{:>10}", "HPO id", "HPO term", "Observed", "%"),
+        ];
+        for term in &self.top_hpo_terms {
+            lines.push(format!("{:<12}{:<50}{:>12}{:>10.1}",
+                term.hpo_id,
+                term.hpo_label,
+                format!("{}/{}", term.observed, term.ascertained),
+                term.percentage));
+        }
+        lines.join("\n")
+    }
+}
+
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use rstest::rstest;
+    use crate::test_utils::fixtures::fbn1_cohort;
+
+    #[rstest]
+    fn test_cohort_stats(fbn1_cohort: CohortData) {
+        let stats = CohortStats::from_cohort(&fbn1_cohort, 10, 1);
+        assert_eq!(COHORT_STATS_SCHEMA_VERSION, stats.schema_version);
+        assert_eq!(3, stats.n_individuals);
+        assert_eq!(4, stats.n_hpo_terms);
+        assert_eq!(2, stats.n_variants);
+        assert_eq!(4, stats.top_hpo_terms.len());
+        assert_eq!("HP:0001166", stats.top_hpo_terms[0].hpo_id);
+        // three terms were ascertained in only two individuals
+        assert_eq!(1, CohortStats::from_cohort(&fbn1_cohort, 10, 3).top_hpo_terms.len());
+    }
+
+    #[rstest]
+    fn test_json_fields(fbn1_cohort: CohortData) {
+        let stats = CohortStats::from_cohort(&fbn1_cohort, 2, 1);
+        let json = serde_json::to_value(&stats).unwrap();
+        assert_eq!("1.0", json["schemaVersion"]);
+        assert_eq!(3, json["nIndividuals"]);
+        assert_eq!(1, json["sex"]["nMale"]);
+        assert_eq!(1, json["variantTypes"]["sv"]);
+        assert_eq!(2.0, json["onset"]["medianYears"]);
+        assert_eq!(2, json["topHpoTerms"].as_array().unwrap().len());
+    }
+
+    #[rstest]
+    fn test_text(fbn1_cohort: CohortData) {
+        let text = CohortStats::from_cohort(&fbn1_cohort, 10, 1).to_text();
+        assert!(text.contains("Individuals: 3"));
+        assert!(text.contains("Sex: 1 male, 2 female"));
+        assert!(text.contains("Arachnodactyly"));
+    }
+}
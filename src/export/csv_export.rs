@@ -0,0 +1,123 @@
+//! CSV export
+//!
+//! Write a cohort as a wide-format CSV table (one row per individual, one column per HPO term) that can be read
+//! directly with `read.csv` in R or `pandas.read_csv` in Python. Fields are quoted as described in
+//! [RFC 4180](https://www.rfc-editor.org/rfc/rfc4180): fields with commas, double quotes, or line breaks are
+//! enclosed in double quotes, and double quotes within a field are doubled.
+
+use crate::dto::{cohort_dto::{CohortData, RowData}, hpo_term_dto::{CellValue, CellValueInner}};
+
+/// The columns that precede the HPO columns
+const FIXED_COLUMNS: [&str; 8] = [
+    "individual_id",
+    "pmid",
+    "sex",
+    "age_of_onset",
+    "age_at_last_encounter",
+    "deceased",
+    "disease_id",
+    "variant_keys",
+];
+
+
+/// Quote a field if needed (RFC 4180)
+fn escape_field(field: &str) -> String {
+    if field.contains([',', '"', '\n', '\r']) {
+        format!("\"{}\"", field.replace('"', "\"\""))
+    } else {
+        field.to_string()
+    }
+}
+
+fn csv_line(fields: &[String]) -> String {
+    fields.iter().map(|f| escape_field(f)).collect::<Vec<_>>().join(",")
+}
+
+/// Cells with an age of onset are observed; population-level cells keep their frequency term
+fn cell_text(cell: &CellValue) -> String {
+    match &cell.entry {
+        CellValueInner::Observed | CellValueInner::OnsetAge(_) => "observed".to_string(),
+        other => other.to_string(),
+    }
+}
+
+/// The variant keys of an individual, sorted, with biallelic variants listed twice
+fn variant_keys(row: &RowData) -> String {
+    let mut keys: Vec<&String> = row.allele_count_map.keys().collect();
+    keys.sort();
+    keys.into_iter()
+        .flat_map(|key| std::iter::repeat(key.as_str()).take(row.allele_count_map[key]))
+        .collect::<Vec<_>>()
+        .join(";")
+}
+
+/// The lines of the CSV table (header first), without line terminators
+pub(crate) fn cohort_csv_lines(cohort: &CohortData) -> Vec<String> {
+    let mut header: Vec<String> = FIXED_COLUMNS.iter().map(|c| c.to_string()).collect();
+    header.extend(cohort.hpo_headers.iter().map(|d| format!("{} ({})", d.hpo_label(), d.hpo_id())));
+    let mut lines = vec![csv_line(&header)];
+    for row in &cohort.rows {
+        let individual = &row.individual_data;
+        let mut fields = vec![
+            individual.individual_id.clone(),
+            individual.pmid.clone(),
+            individual.sex.clone(),
+            individual.age_of_onset.clone(),
+            individual.age_at_last_encounter.clone(),
+            individual.deceased.clone(),
+            row.disease_id_list.join(";"),
+            variant_keys(row),
+        ];
+        fields.extend(row.hpo_data.iter().map(cell_text));
+        lines.push(csv_line(&fields));
+    }
+    lines
+}
+
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use rstest::rstest;
+    use crate::dto::hpo_term_dto::HpoTermDuplet;
+    use crate::test_utils::fixtures::fbn1_cohort;
+
+    #[rstest]
+    #[case("Arachnodactyly", "Arachnodactyly")]
+    #[case("Abnormality of the eye, unspecified", "\"Abnormality of the eye, unspecified\"")]
+    #[case("a \"quoted\" label", "\"a \"\"quoted\"\" label\"")]
+    #[case("two\nlines", "\"two\nlines\"")]
+    fn test_escape_field(#[case] field: &str, #[case] expected: &str) {
+        assert_eq!(expected, escape_field(field));
+    }
+
+    #[rstest]
+    fn test_cohort_csv_lines(fbn1_cohort: CohortData) {
+        let lines = cohort_csv_lines(&fbn1_cohort);
+        assert_eq!(1 + fbn1_cohort.rows.len(), lines.len());
+        assert_eq!("individual_id,pmid,sex,age_of_onset,age_at_last_encounter,deceased,disease_id,variant_keys,\
+            Arachnodactyly (HP:0001166),Atrial septal defect (HP:0001631),Long hallux (HP:0001847),Short stature (HP:0004322)",
+            lines[0]);
+        let fields: Vec<&str> = lines[1].split(',').collect();
+        assert_eq!(FIXED_COLUMNS.len() + 4, fields.len());
+        assert_eq!("Individual 1", fields[0]);
+        assert_eq!("OMIM:154700", fields[6]);
+        assert!(fields[8..].iter().all(|f| ["observed", "excluded", "na"].contains(f)));
+    }
+
+    #[rstest]
+    fn test_label_with_comma(mut fbn1_cohort: CohortData) {
+        fbn1_cohort.hpo_headers[0] = HpoTermDuplet::new("Arachnodactyly, severe", "HP:0001166");
+        let lines = cohort_csv_lines(&fbn1_cohort);
+        assert!(lines[0].contains(",\"Arachnodactyly, severe (HP:0001166)\","));
+    }
+
+    #[rstest]
+    fn test_biallelic_variant_keys(mut fbn1_cohort: CohortData) {
+        let row = &mut fbn1_cohort.rows[0];
+        row.allele_count_map.clear();
+        row.allele_count_map.insert("b_key".to_string(), 1);
+        row.allele_count_map.insert("a_key".to_string(), 2);
+        assert_eq!("a_key;a_key;b_key", variant_keys(row));
+    }
+}
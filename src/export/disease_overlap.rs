@@ -0,0 +1,140 @@
+//! CrossDiseaseOverlap: HPO terms shared by the individuals with different diseases
+//!
+//! For each disease, we collect the HPO terms that were observed in at least one individual with the disease
+//! (across all cohorts), and for each pair of diseases the intersection of these sets. Excluded terms are
+//! not considered. The labels are taken from the current HPO version if the term is found.
+use std::collections::{BTreeMap, BTreeSet, HashMap};
+use std::str::FromStr;
+use std::sync::Arc;
+
+use ontolius::{ontology::{csr::FullCsrOntology, OntologyTerms}, term::MinimalTerm, TermId};
+
+use crate::dto::{cohort_dto::CohortData, hpo_term_dto::HpoTermDuplet};
+
+
+#[derive(Clone, Debug, Default)]
+pub struct CrossDiseaseOverlap {
+    /// Key: pair of disease ids (the smaller id first); value: the shared observed HPO terms, sorted by id
+    pub overlap: HashMap<(String, String), Vec<HpoTermDuplet>>,
+}
+
+impl CrossDiseaseOverlap {
+    pub fn from_cohorts(cohorts: &[CohortData], hpo: Arc<FullCsrOntology>) -> Self {
+        // key: disease id, value: observed terms (key: HPO id)
+        let mut observed: BTreeMap<String, BTreeMap<String, HpoTermDuplet>> = BTreeMap::new();
+        for cohort in cohorts {
+            for row in &cohort.rows {
+                for (duplet, cell) in cohort.hpo_headers.iter().zip(row.hpo_data.iter()) {
+                    if !cell.is_present() {
+                        continue;
+                    }
+                    for disease_id in &row.disease_id_list {
+                        observed
+                            .entry(disease_id.clone())
+                            .or_default()
+                            .entry(duplet.hpo_id().to_string())
+                            .or_insert_with(|| Self::current_duplet(duplet, &hpo));
+                    }
+                }
+            }
+        }
+        let disease_ids: Vec<&String> = observed.keys().collect();
+        let mut overlap = HashMap::new();
+        for (i, disease_1) in disease_ids.iter().enumerate() {
+            let terms_1: BTreeSet<&String> = observed[*disease_1].keys().collect();
+            for disease_2 in &disease_ids[i + 1..] {
+                let terms_2 = &observed[*disease_2];
+                let shared: Vec<HpoTermDuplet> = terms_2
+                    .iter()
+                    .filter(|(hpo_id, _)| terms_1.contains(hpo_id))
+                    .map(|(_, duplet)| duplet.clone())
+                    .collect();
+                overlap.insert(((*disease_1).clone(), (*disease_2).clone()), shared);
+            }
+        }
+        Self { overlap }
+    }
+
+    /// The duplet with the label of the current HPO version (or the original duplet if the term is not found)
+    fn current_duplet(duplet: &HpoTermDuplet, hpo: &FullCsrOntology) -> HpoTermDuplet {
+        TermId::from_str(duplet.hpo_id())
+            .ok()
+            .and_then(|tid| hpo.term_by_id(&tid))
+            .map(|term| HpoTermDuplet::new(term.name(), duplet.hpo_id()))
+            .unwrap_or_else(|| duplet.clone())
+    }
+
+    pub fn tsv_header() -> String {
+        ["disease_1", "disease_2", "n_shared_terms", "shared_term_ids"].join("\t")
+    }
+
+    /// One line per pair of diseases, sorted by the disease ids; the term ids are separated by ";"
+    pub fn to_tsv_rows(&self) -> Vec<String> {
+        let sorted: BTreeMap<&(String, String), &Vec<HpoTermDuplet>> = self.overlap.iter().collect();
+        sorted
+            .into_iter()
+            .map(|((disease_1, disease_2), terms)| {
+                let term_ids: Vec<&str> = terms.iter().map(|t| t.hpo_id()).collect();
+                format!("{}\t{}\t{}\t{}", disease_1, disease_2, terms.len(), term_ids.join(";"))
+            })
+            .collect()
+    }
+}
+
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use rstest::rstest;
+    use crate::dto::hpo_term_dto::CellValue;
+    use crate::test_utils::fixtures::{fbn1_cohort, hpo};
+
+    /// A cohort for a second disease, annotated with Ectopia lentis instead of Arachnodactyly
+    /// and with Short stature excluded in all individuals
+    fn second_cohort(mut cohort: CohortData, disease_id: &str) -> CohortData {
+        cohort.hpo_headers[0] = HpoTermDuplet::new("Ectopia lentis", "HP:0001083");
+        for row in cohort.rows.iter_mut() {
+            row.disease_id_list = vec![disease_id.to_string()];
+            row.hpo_data[3] = "excluded".parse::<CellValue>().unwrap();
+        }
+        cohort
+    }
+
+    #[rstest]
+    fn test_shared_terms(hpo: Arc<FullCsrOntology>, fbn1_cohort: CohortData) {
+        let other = second_cohort(fbn1_cohort.clone(), "OMIM:999999");
+        let overlap = CrossDiseaseOverlap::from_cohorts(&[other, fbn1_cohort], hpo);
+        assert_eq!(1, overlap.overlap.len());
+        let shared = &overlap.overlap[&("OMIM:154700".to_string(), "OMIM:999999".to_string())];
+        let shared_ids: Vec<&str> = shared.iter().map(|t| t.hpo_id()).collect();
+        // Atrial septal defect and Long hallux are observed in both cohorts
+        assert_eq!(vec!["HP:0001631", "HP:0001847"], shared_ids);
+        assert_eq!(vec!["OMIM:154700\tOMIM:999999\t2\tHP:0001631;HP:0001847"], overlap.to_tsv_rows());
+    }
+
+    /// Population-level frequencies such as "Excluded (0%)" are not observations
+    #[rstest]
+    fn test_hpo_frequency_is_not_observed(hpo: Arc<FullCsrOntology>, fbn1_cohort: CohortData) {
+        let mut other = second_cohort(fbn1_cohort.clone(), "OMIM:999999");
+        for row in other.rows.iter_mut() {
+            row.hpo_data[1] = "HP:0040285".parse::<CellValue>().unwrap();
+        }
+        let overlap = CrossDiseaseOverlap::from_cohorts(&[other, fbn1_cohort], hpo);
+        assert_eq!(vec!["OMIM:154700\tOMIM:999999\t1\tHP:0001847"], overlap.to_tsv_rows());
+    }
+
+    #[rstest]
+    fn test_three_diseases(hpo: Arc<FullCsrOntology>, fbn1_cohort: CohortData) {
+        let cohorts = [
+            fbn1_cohort.clone(),
+            second_cohort(fbn1_cohort.clone(), "OMIM:999998"),
+            second_cohort(fbn1_cohort, "OMIM:999999"),
+        ];
+        let overlap = CrossDiseaseOverlap::from_cohorts(&cohorts, hpo);
+        assert_eq!(3, overlap.overlap.len());
+        let rows = overlap.to_tsv_rows();
+        assert!(rows[0].starts_with("OMIM:154700\tOMIM:999998\t2"));
+        // the two other cohorts have the same observed terms
+        assert!(rows[2].starts_with("OMIM:999998\tOMIM:999999\t3"));
+    }
+}
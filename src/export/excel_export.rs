@@ -74,7 +74,7 @@ pub fn create_excel_with_merged_cells(out_filename: &str,
          worksheet2.write_string(j, 1 as u16, cite.title()).map_err(|e|e.to_string())?;
     }
 
-    println!("Saving excel file to {out_filename}");
+    tracing::info!("Saving excel file to {out_filename}");
     workbook.save(out_filename).map_err(|e|e.to_string())?;
     Ok(())
 }
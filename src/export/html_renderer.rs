@@ -1,6 +1,11 @@
-use std::{path::PathBuf};
+use std::path::{Path, PathBuf};
 use tera::{Tera, Context};
 
+/// Name under which the cohort report template is registered
+const REPORT_TEMPLATE: &str = "cohort_data/cohort_report.html";
+/// Name under which the repository dashboard template is registered
+const DASHBOARD_TEMPLATE: &str = "repo/dashboard.html";
+
 pub struct HtmlRenderer {
      tera: Tera,
 }
@@ -18,24 +23,39 @@ impl HtmlRenderer {
         Self{ tera }
     }
 
-    pub fn render_html(&self, context: Context) -> String {
-       
-        let html = self.tera
-            .render("cohort_data/cohort_report.html", &context).map_err(|e| {
-            eprintln!("❌ Template render failed!");
-            eprintln!("Error: {:?}", e); // detailed debug output
-            e
-        });
-        match html {
-            Ok(html) => { html },
-            Err(e) => {
-                panic!("{:?}",e);
-            }
-        }
+    /// Use a custom Tera template instead of the bundled cohort report.
+    /// The template receives the same `cohort` context as the default template.
+    pub fn from_template_file(template_path: &Path) -> Result<Self, String> {
+        let mut tera = Tera::default();
+        tera.add_template_file(template_path, Some(REPORT_TEMPLATE))
+            .map_err(|e| format!("Could not load template {}: {}", template_path.display(), format_tera_error(&e)))?;
+        Ok(Self { tera })
     }
 
+    pub fn render_html(&self, context: Context) -> Result<String, String> {
+        self.tera
+            .render(REPORT_TEMPLATE, &context)
+            .map_err(|e| format!("Template render failed: {}", format_tera_error(&e)))
+    }
 
+    /// Render the repository dashboard; the template expects a `dashboard` context
+    pub fn render_dashboard(&self, context: Context) -> Result<String, String> {
+        self.tera
+            .render(DASHBOARD_TEMPLATE, &context)
+            .map_err(|e| format!("Template render failed: {}", format_tera_error(&e)))
+    }
+}
 
+/// The message of a Tera error is generic (e.g., "Failed to parse 'x.html'"); the location of the problem
+/// (line and column) is only given by the underlying errors, so we include the entire chain.
+fn format_tera_error(error: &tera::Error) -> String {
+    let mut message = error.to_string();
+    let mut source = std::error::Error::source(error);
+    while let Some(e) = source {
+        message.push_str(&format!("\n{e}"));
+        source = e.source();
+    }
+    message
 }
 
 
@@ -64,12 +84,22 @@ mod tests {
         let mut context = Context::new();
         context.insert("cohort", &cohort_render);
         println!("{:?}", cohort_render);
-        let html = renderer.render_html(context);
+        let html = renderer.render_html(context).unwrap();
        // assert!(html.contains("ADA-SCID"));
         std::fs::write(output_path, html).unwrap();
         println!("✅ Rendered to {}", output_path.display());
         
     }
 
-
+    #[rstest]
+    fn test_template_parse_error_has_line_number() {
+        let template_path = std::env::temp_dir().join(format!("html_renderer_broken_template_{}.html", std::process::id()));
+        std::fs::write(&template_path, "<html>\n<body>\n<h1>{{ cohort.acronym </h1>\n</body>\n").unwrap();
+        let result = HtmlRenderer::from_template_file(&template_path);
+        std::fs::remove_file(&template_path).unwrap();
+        let message = result.err().unwrap();
+        assert!(message.contains(&template_path.display().to_string()), "no template path in: {message}");
+        // the parser error points to the unterminated expression on line 3
+        assert!(message.contains(" 3:"), "no line number in: {message}");
+    }
 }
\ No newline at end of file
@@ -0,0 +1,191 @@
+//! LatexRenderer: Create a publication-ready LaTeX table with the frequencies of the HPO terms in a cohort
+//!
+//! The table is a `longtable` with one section per organ system. The cells with the frequencies are
+//! shaded according to the percentage of individuals in whom the term was observed. The document
+//! that includes the table needs the `longtable` package and the `colortbl` package (or `xcolor` with the `table` option).
+use std::sync::Arc;
+
+use ontolius::ontology::csr::FullCsrOntology;
+
+use crate::dto::{cohort_dto::CohortData, hpo_term_dto::HpoTermDuplet};
+
+
+/// Observed/measured counts for one HPO term in the cohort
+struct LatexRow {
+    duplet: HpoTermDuplet,
+    observed: usize,
+    measured: usize,
+}
+
+impl LatexRow {
+    fn percentage(&self) -> Option<f64> {
+        if self.measured == 0 {
+            None
+        } else {
+            Some(100.0 * self.observed as f64 / self.measured as f64)
+        }
+    }
+
+    /// Shade the cell according to the frequency; terms that were never measured are not shaded
+    fn cell_color(&self) -> Option<&'static str> {
+        match self.percentage() {
+            Some(p) if p >= 75.0 => Some("red!40"),
+            Some(p) if p >= 50.0 => Some("orange!40"),
+            Some(p) if p >= 25.0 => Some("yellow!40"),
+            Some(_) => Some("gray!15"),
+            None => None,
+        }
+    }
+
+    fn to_latex(&self) -> String {
+        let label = escape_latex(self.duplet.hpo_label());
+        let hpo_id = escape_latex(self.duplet.hpo_id());
+        let counts = match self.percentage() {
+            Some(p) => format!("{}/{} ({:.1}\\%)", self.observed, self.measured, p),
+            None => "0/0".to_string(),
+        };
+        let counts = match self.cell_color() {
+            Some(color) => format!("\\cellcolor{{{color}}}{counts}"),
+            None => counts,
+        };
+        format!("{label} & {hpo_id} & {counts} \\\\")
+    }
+}
+
+
+pub struct LatexRenderer {
+    /// Acronym of the cohort, used for the caption
+    acronym: String,
+    /// Pairs of organ system label and the rows for the terms of the cohort in that organ system
+    sections: Vec<(String, Vec<LatexRow>)>,
+}
+
+impl LatexRenderer {
+    pub fn new(
+        cohort: &CohortData,
+        hpo: Arc<FullCsrOntology>
+    ) -> Result<Self, String> {
+        let organ_map = crate::hpo::group_hpo_terms_by_organ_system(cohort, hpo)?;
+        let mut sections = Vec::new();
+        for (organ_system, duplets) in organ_map {
+            let rows = duplets
+                .into_iter()
+                .map(|duplet| Self::count_term(cohort, duplet))
+                .collect::<Result<Vec<_>, _>>()?;
+            sections.push((organ_system, rows));
+        }
+        Ok(Self {
+            acronym: cohort.acronym(),
+            sections,
+        })
+    }
+
+    fn count_term(cohort: &CohortData, duplet: HpoTermDuplet) -> Result<LatexRow, String> {
+        let idx = cohort
+            .hpo_headers
+            .iter()
+            .position(|h| h.hpo_id == duplet.hpo_id)
+            .ok_or_else(|| format!("Could not find column for {}", duplet.hpo_id))?;
+        if let Some(row) = cohort.rows.iter().find(|row| row.hpo_data.len() <= idx) {
+            return Err(format!("Malformed row for {}", row.individual_data.individual_id));
+        }
+        let (observed, measured) = crate::hpo::count_observed_and_measured(cohort, idx);
+        Ok(LatexRow { duplet, observed, measured })
+    }
+
+    pub fn render(&self) -> String {
+        let mut lines: Vec<String> = vec![
+            "\\begin{longtable}{llr}".to_string(),
+            format!("\\caption{{Phenotypic features of the {} cohort}} \\\\", escape_latex(&self.acronym)),
+            "\\hline".to_string(),
+            "HPO term & HPO id & Observed/total (\\%) \\\\".to_string(),
+            "\\hline".to_string(),
+            "\\endfirsthead".to_string(),
+            "\\hline".to_string(),
+            "HPO term & HPO id & Observed/total (\\%) \\\\".to_string(),
+            "\\hline".to_string(),
+            "\\endhead".to_string(),
+        ];
+        for (organ_system, rows) in &self.sections {
+            lines.push(format!("\\multicolumn{{3}}{{l}}{{\\textbf{{{}}}}} \\\\", escape_latex(organ_system)));
+            for row in rows {
+                lines.push(row.to_latex());
+            }
+            lines.push("\\hline".to_string());
+        }
+        lines.push("\\end{longtable}".to_string());
+        lines.join("\n") + "\n"
+    }
+}
+
+
+/// Escape the characters that have a special meaning in LaTeX.
+pub fn escape_latex(text: &str) -> String {
+    let mut escaped = String::with_capacity(text.len());
+    for c in text.chars() {
+        match c {
+            '\\' => escaped.push_str("\\textbackslash{}"),
+            '%' | '&' | '_' | '$' | '#' | '{' | '}' => {
+                escaped.push('\\');
+                escaped.push(c);
+            }
+            '~' => escaped.push_str("\\textasciitilde{}"),
+            '^' => escaped.push_str("\\textasciicircum{}"),
+            _ => escaped.push(c),
+        }
+    }
+    escaped
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rstest::rstest;
+    use crate::test_utils::fixtures::{fbn1_cohort, hpo};
+
+    #[rstest]
+    #[case("Abnormality of the eye", "Abnormality of the eye")]
+    #[case("50% & more", "50\\% \\& more")]
+    #[case("a_b $c$ #1", "a\\_b \\$c\\$ \\#1")]
+    #[case("{x}", "\\{x\\}")]
+    fn test_escape_latex(#[case] input: &str, #[case] expected: &str) {
+        assert_eq!(expected, escape_latex(input));
+    }
+
+    /// Check that environments are closed and that braces are balanced (ignoring escaped braces)
+    fn is_balanced(latex: &str) -> bool {
+        let mut depth: i64 = 0;
+        let mut previous = ' ';
+        for c in latex.chars() {
+            if previous != '\\' {
+                match c {
+                    '{' => depth += 1,
+                    '}' => depth -= 1,
+                    _ => {}
+                }
+            }
+            if depth < 0 {
+                return false;
+            }
+            previous = if previous == '\\' && c == '\\' { ' ' } else { c };
+        }
+        depth == 0 && latex.matches("\\begin{").count() == latex.matches("\\end{").count()
+    }
+
+    #[rstest]
+    fn test_render_latex(fbn1_cohort: CohortData, hpo: Arc<FullCsrOntology>) {
+        let renderer = LatexRenderer::new(&fbn1_cohort, hpo).unwrap();
+        let latex = renderer.render();
+        assert!(latex.starts_with("\\begin{longtable}"));
+        assert!(latex.trim_end().ends_with("\\end{longtable}"));
+        assert!(is_balanced(&latex));
+        // Arachnodactyly was observed in all three individuals
+        assert!(latex.contains("Arachnodactyly & HP:0001166 & \\cellcolor{red!40}3/3 (100.0\\%) \\\\"));
+        assert!(latex.contains("\\textbf{Abnormality of the cardiovascular system}"));
+        // every data row has three columns
+        for line in latex.lines().filter(|l| l.contains(" & HP:")) {
+            assert_eq!(2, line.matches(" & ").count());
+        }
+    }
+}
@@ -0,0 +1,165 @@
+//! MarkdownRenderer: Summarize a cohort as a GitHub-Flavored Markdown document
+//!
+//! The document has three tables: a summary of the cohort, the HPO term frequencies
+//! (taken from the [`CohortRenderer`]), and the variants with the number of alleles observed in the cohort.
+use std::collections::HashMap;
+
+use crate::{dto::cohort_dto::CohortData, export::cohort_renderer::CohortRenderer};
+
+
+/// One row of the variant table
+struct VariantRow {
+    variant: String,
+    gene: String,
+    category: String,
+    allele_count: usize,
+}
+
+
+pub struct MarkdownRenderer {
+    cohort_renderer: CohortRenderer,
+    variants: Vec<VariantRow>,
+}
+
+impl MarkdownRenderer {
+    pub fn new(cohort: &CohortData, cohort_renderer: CohortRenderer) -> Self {
+        Self {
+            cohort_renderer,
+            variants: Self::get_variant_rows(cohort),
+        }
+    }
+
+    /// Count the alleles of each variant across all individuals and sort by descending count
+    fn get_variant_rows(cohort: &CohortData) -> Vec<VariantRow> {
+        let mut allele_counts: HashMap<&str, usize> = HashMap::new();
+        for row in &cohort.rows {
+            for (allele, count) in &row.allele_count_map {
+                *allele_counts.entry(allele.as_str()).or_insert(0) += count;
+            }
+        }
+        let mut variants: Vec<VariantRow> = allele_counts
+            .into_iter()
+            .filter_map(|(key, allele_count)| {
+                if let Some(hgvs) = cohort.hgvs_variants.get(key) {
+                    let variant = match hgvs.p_hgvs() {
+                        Some(p_hgvs) => format!("{}:{} ({})", hgvs.transcript(), hgvs.hgvs(), p_hgvs),
+                        None => format!("{}:{}", hgvs.transcript(), hgvs.hgvs()),
+                    };
+                    Some(VariantRow { variant, gene: hgvs.symbol().to_string(), category: "HGVS".to_string(), allele_count })
+                } else if let Some(sv) = cohort.structural_variants.get(key) {
+                    Some(VariantRow {
+                        variant: sv.label().to_string(),
+                        gene: sv.gene_symbol().to_string(),
+                        category: sv.get_sequence_ontology_term().label,
+                        allele_count,
+                    })
+                } else {
+                    cohort.intergenic_variants.get(key).map(|ig| VariantRow {
+                        variant: ig.g_hgvs().to_string(),
+                        gene: ig.symbol().unwrap_or_else(|| "n/a".to_string()),
+                        category: "intergenic".to_string(),
+                        allele_count,
+                    })
+                }
+            })
+            .collect();
+        variants.sort_by(|a, b| b.allele_count.cmp(&a.allele_count).then(a.variant.cmp(&b.variant)));
+        variants
+    }
+
+    pub fn render(&self) -> String {
+        let cr = &self.cohort_renderer;
+        let mut lines: Vec<String> = Vec::new();
+        lines.push(format!("# {} cohort", escape_markdown(&cr.acronym)));
+        lines.push(String::new());
+        lines.push("## Summary".to_string());
+        lines.push(String::new());
+        lines.push("| Gene | Disease | Individuals | HPO terms | Variants |".to_string());
+        lines.push("|------|---------|-------------|-----------|----------|".to_string());
+        let genes: Vec<&str> = cr.disease_list
+            .iter()
+            .flat_map(|dx| dx.gene_transcript_list.iter().map(|gt| gt.gene_symbol.as_str()))
+            .collect();
+        let diseases: Vec<String> = cr.disease_list
+            .iter()
+            .map(|dx| format!("{} ({})", dx.disease_label, dx.disease_id))
+            .collect();
+        lines.push(format!("| {} | {} | {} | {} | {} |",
+            escape_markdown(&genes.join(", ")),
+            escape_markdown(&diseases.join("; ")),
+            cr.n_phenopackets,
+            cr.n_distinct_hpo_terms,
+            cr.n_variants));
+        lines.push(String::new());
+        lines.push(cr.onset_summary.clone());
+        lines.push(String::new());
+        lines.push("## Phenotypic features".to_string());
+        lines.push(String::new());
+        lines.push("| HPO term | HPO id | Observed | Measured | Frequency |".to_string());
+        lines.push("|----------|--------|---------:|---------:|----------:|".to_string());
+        for freq in &cr.hpo_frequencies {
+            lines.push(format!("| {} | {} | {} | {} | {:.1}% |",
+                escape_markdown(&freq.hpo_label),
+                freq.hpo_id,
+                freq.observed,
+                freq.measured,
+                freq.percentage));
+        }
+        lines.push(String::new());
+        lines.push("## Variants".to_string());
+        lines.push(String::new());
+        lines.push("| Variant | Gene | Type | Alleles |".to_string());
+        lines.push("|---------|------|------|--------:|".to_string());
+        for var in &self.variants {
+            lines.push(format!("| {} | {} | {} | {} |",
+                escape_markdown(&var.variant),
+                escape_markdown(&var.gene),
+                escape_markdown(&var.category),
+                var.allele_count));
+        }
+        lines.join("\n") + "\n"
+    }
+}
+
+/// Escape characters that would break a Markdown table cell or be interpreted as formatting.
+fn escape_markdown(text: &str) -> String {
+    let mut escaped = String::with_capacity(text.len());
+    for c in text.chars() {
+        if matches!(c, '|' | '*' | '_' | '`' | '\\' | '<' | '>' | '[' | ']') {
+            escaped.push('\\');
+        }
+        escaped.push(c);
+    }
+    escaped
+}
+
+
+#[cfg(test)]
+mod tests {
+    use std::sync::Arc;
+    use ontolius::ontology::csr::FullCsrOntology;
+    use rstest::rstest;
+    use super::*;
+    use crate::test_utils::fixtures::{fbn1_cohort, hpo};
+
+    #[rstest]
+    fn test_render_markdown(fbn1_cohort: CohortData, hpo: Arc<FullCsrOntology>) {
+        let cohort_renderer = CohortRenderer::new(&fbn1_cohort, hpo).unwrap();
+        let markdown = MarkdownRenderer::new(&fbn1_cohort, cohort_renderer).render();
+        assert!(markdown.contains("| Gene | Disease | Individuals | HPO terms | Variants |"));
+        assert!(markdown.contains("| FBN1 | Marfan syndrome (OMIM:154700) | 3 | 4 | 2 |"));
+        assert!(markdown.contains("Onset (n=2): mean 2.0 y"));
+        assert!(markdown.contains("| HPO term | HPO id | Observed | Measured | Frequency |"));
+        assert!(markdown.contains("| Arachnodactyly | HP:0001166 | 3 | 3 | 100.0% |"));
+        assert!(markdown.contains("| Variant | Gene | Type | Alleles |"));
+        assert!(markdown.contains("| NM\\_000138.5:c.8242G\\>T | FBN1 | HGVS | 2 |"));
+    }
+
+    #[rstest]
+    fn test_frequencies_sorted_descending(fbn1_cohort: CohortData, hpo: Arc<FullCsrOntology>) {
+        let cohort_renderer = CohortRenderer::new(&fbn1_cohort, hpo).unwrap();
+        let percentages: Vec<f64> = cohort_renderer.hpo_frequencies.iter().map(|f| f.percentage).collect();
+        assert!(percentages.windows(2).all(|w| w[0] >= w[1]));
+        assert_eq!("HP:0001166", cohort_renderer.hpo_frequencies[0].hpo_id);
+    }
+}
@@ -6,15 +6,30 @@ use std::{path::Path, sync::Arc};
 use ontolius::ontology::csr::FullCsrOntology;
 use tera::Context;
 
-use crate::{dto::cohort_dto::CohortData, export::{cohort_renderer::CohortRenderer, html_renderer::HtmlRenderer, table_compare::TableCompare}, factory};
+use crate::{dto::cohort_dto::CohortData, export::{cohort_renderer::CohortRenderer, html_renderer::HtmlRenderer, latex_renderer::LatexRenderer, markdown_renderer::MarkdownRenderer, multi_cohort_compare::MultiCohortCompare, table_compare::TableCompare}, factory};
 
 
 mod cohort_renderer;
-mod html_renderer;
+mod cohort_stats;
+mod csv_export;
+mod disease_overlap;
+pub(crate) mod html_renderer;
+mod latex_renderer;
+mod markdown_renderer;
+mod multi_cohort_compare;
+mod onset_distribution;
+mod sex_statistics;
+mod variant_distribution;
 mod table_compare;
 #[cfg(feature = "excel_export")]
 mod excel_export;
 
+pub use cohort_stats::{CohortStats, RankedHpoTerm, COHORT_STATS_SCHEMA_VERSION};
+pub use disease_overlap::CrossDiseaseOverlap;
+pub use onset_distribution::OnsetDistribution;
+pub use sex_statistics::SexStatistics;
+pub use variant_distribution::{HgvsVariantType, VariantTypeDistribution};
+
 
 /// Render a cohort report as an HTML file.
 ///
@@ -55,12 +70,194 @@ pub fn render_html(
     let mut context = Context::new();
     context.insert("cohort", &cohort_renderer);
     let renderer = HtmlRenderer::new();
-    let html = renderer.render_html(context);
+    let html = renderer.render_html(context)?;
+    std::fs::write(output_path, html).map_err(|e|e.to_string())?;
+    Ok(())
+}
+
+/// Render a cohort report as an HTML file using a custom [Tera](https://keats.github.io/tera/) template.
+///
+/// The template is rendered with the same `cohort` context as the bundled report template
+/// (`templates/cohort_data/cohort_report.html`), which is a good starting point for customization.
+///
+/// # Errors
+///
+/// Returns an error if the template cannot be read or parsed (the message includes the line and column
+/// of syntax errors), if rendering fails, or if the output file cannot be written.
+pub fn render_html_with_template(
+    cohort: CohortData,
+    hpo: Arc<FullCsrOntology>,
+    template_path: &Path,
+    output_path: &Path) -> Result<(), String> {
+    let renderer = HtmlRenderer::from_template_file(template_path)?;
+    let cohort_renderer = CohortRenderer::new(&cohort, hpo)?;
+    let mut context = Context::new();
+    context.insert("cohort", &cohort_renderer);
+    let html = renderer.render_html(context)?;
     std::fs::write(output_path, html).map_err(|e|e.to_string())?;
     Ok(())
 }
 
 
+/// Export the HPO term frequencies of a cohort as a publication-ready LaTeX table.
+///
+/// The output is a `longtable` environment with one row per HPO term of the cohort and
+/// the columns *HPO term*, *HPO id*, and *Observed/total (%)*. The rows are grouped by organ
+/// system (see [`crate::hpo::group_hpo_terms_by_organ_system`]) and the frequency cells are
+/// color-coded with `\cellcolor` according to the percentage of individuals in whom the term was observed.
+///
+/// # Arguments
+///
+/// * `cohort` — The cohort whose HPO annotations will be summarized.
+/// * `hpo` — A shared [`FullCsrOntology`] instance used to determine the organ systems.
+/// * `output_path` — The filesystem path where the `.tex` file will be written.
+///
+/// # Returns
+///
+/// * `Ok(())` on success.
+/// * `Err(String)` if the HPO terms cannot be grouped or the file cannot be written.
+///
+/// # Notes
+///
+/// - The file contains only the table, so that it can be included in a manuscript with `\input`.
+///   The manuscript must load the `longtable` and `colortbl` packages.
+/// - LaTeX special characters in HPO labels are escaped.
+pub fn export_as_latex_table(
+    cohort: &CohortData,
+    hpo: Arc<FullCsrOntology>,
+    output_path: &Path) -> Result<(), String> {
+    let renderer = LatexRenderer::new(cohort, hpo)?;
+    std::fs::write(output_path, renderer.render()).map_err(|e| e.to_string())?;
+    Ok(())
+}
+
+
+/// Export a summary of a cohort as a GitHub-Flavored Markdown document.
+///
+/// The document contains
+/// - a summary table (gene, disease, number of individuals, number of HPO terms, number of variants),
+/// - a phenotype frequency table sorted by descending frequency, and
+/// - a variant table with the number of alleles of each variant in the cohort.
+///
+/// The frequencies are computed by the same [`CohortRenderer`] that is used for the HTML report.
+///
+/// # Arguments
+///
+/// * `cohort` — The cohort to be summarized.
+/// * `hpo` — A shared [`FullCsrOntology`] instance.
+/// * `output_path` — The filesystem path where the Markdown file will be written.
+///
+/// # Returns
+///
+/// * `Ok(())` on success.
+/// * `Err(String)` if the cohort cannot be rendered or the file cannot be written.
+pub fn export_as_markdown(
+    cohort: &CohortData,
+    hpo: Arc<FullCsrOntology>,
+    output_path: &Path) -> Result<(), String> {
+    let cohort_renderer = CohortRenderer::new(cohort, hpo)?;
+    let renderer = MarkdownRenderer::new(cohort, cohort_renderer);
+    std::fs::write(output_path, renderer.render()).map_err(|e| e.to_string())?;
+    Ok(())
+}
+
+
+/// Export a cohort as a wide-format CSV table for analysis in R or Python.
+///
+/// There is one row per individual. The columns are `individual_id`, `pmid`, `sex`, `age_of_onset`,
+/// `age_at_last_encounter`, `deceased`, `disease_id`, and `variant_keys`, followed by one column per HPO term
+/// (header `label (HP:nnnnnnn)`) with the values `observed`, `excluded`, or `na`. Multiple diseases and variant
+/// keys are separated by semicolons; the key of a biallelic variant is listed twice. Fields are quoted according
+/// to RFC 4180, so labels that contain commas are read correctly by `read.csv` and `pandas.read_csv`.
+///
+/// # Arguments
+///
+/// * `cohort` — The cohort to be exported.
+/// * `output_path` — The filesystem path where the CSV file will be written.
+///
+/// # Returns
+///
+/// * `Ok(usize)` with the number of data rows (individuals) that were written.
+/// * `Err(String)` if the file cannot be written.
+pub fn export_cohort_as_csv(cohort: &CohortData, output_path: &Path) -> Result<usize, String> {
+    let lines = csv_export::cohort_csv_lines(cohort);
+    let mut contents = lines.join("\n");
+    contents.push('\n');
+    std::fs::write(output_path, contents)
+        .map_err(|e| format!("Could not write {}: {}", output_path.display(), e))?;
+    Ok(lines.len() - 1)
+}
+
+
+/// Compute summary statistics for the age of onset of the individuals in a cohort.
+///
+/// ISO8601 ages (e.g., `P3Y6M`) are converted to fractional years and used to calculate the mean,
+/// median, standard deviation, and range. Individuals whose onset is given as an HPO onset term
+/// (e.g., *Congenital onset*) or a gestational age are counted in `n_with_onset`, but cannot be
+/// included in the numerical statistics. Individuals with `na` are counted in `n_without_onset`.
+///
+/// # Arguments
+///
+/// * `cohort` — The cohort to be analyzed.
+///
+/// # Returns
+///
+/// An [`OnsetDistribution`]; the numerical fields are zero if no ISO8601 onset is available.
+pub fn compute_onset_distribution(cohort: &CohortData) -> OnsetDistribution {
+    OnsetDistribution::from_cohort(cohort)
+}
+
+
+/// Compute the sex distribution of a cohort.
+///
+/// Individuals are counted according to the sex codes of the template (`M`, `F`, `O`);
+/// all other values are counted as unknown. If there are at least five males and five females,
+/// a chi-square test against a 1:1 ratio is performed.
+///
+/// # Arguments
+///
+/// * `cohort` — The cohort to be analyzed.
+///
+/// # Returns
+///
+/// A [`SexStatistics`] object; `chi_square_pvalue` is `None` if the test was not performed.
+pub fn compute_sex_statistics(cohort: &CohortData) -> SexStatistics {
+    SexStatistics::from_cohort(cohort)
+}
+
+
+/// Compute the summary statistics that are shown by `phetools stats`.
+///
+/// # Arguments
+///
+/// * `cohort` — The cohort to be analyzed.
+/// * `n_top_terms` — Number of HPO terms to report, in descending order of frequency.
+/// * `min_ascertainment` — Terms that were observed or excluded in fewer individuals are not ranked.
+///
+/// # Returns
+///
+/// A [`CohortStats`] object. Its JSON representation is versioned (see [`COHORT_STATS_SCHEMA_VERSION`]).
+pub fn compute_cohort_stats(cohort: &CohortData, n_top_terms: usize, min_ascertainment: usize) -> CohortStats {
+    CohortStats::from_cohort(cohort, n_top_terms, min_ascertainment)
+}
+
+
+/// Find the HPO terms shared by the individuals with different diseases.
+///
+/// # Arguments
+///
+/// * `cohorts` — The cohorts to be analyzed; individuals with the same disease may come from several cohorts.
+/// * `hpo` — The HPO, used to report the current labels of the terms.
+///
+/// # Returns
+///
+/// A [`CrossDiseaseOverlap`] with the observed terms shared by each pair of diseases;
+/// use [`CrossDiseaseOverlap::to_tsv_rows`] to export it as a table.
+pub fn compute_cross_disease_overlap(cohorts: &[CohortData], hpo: Arc<FullCsrOntology>) -> CrossDiseaseOverlap {
+    CrossDiseaseOverlap::from_cohorts(cohorts, hpo)
+}
+
+
 /// Generate a tab-separated comparison table summarizing HPO term frequencies between two cohorts.
 ///
 /// This function compares two cohorts annotated with HPO terms and produces
@@ -123,6 +320,37 @@ pub fn output_comparison_table(
     }
 
 
+/// Write a tab-separated table with the frequencies of the HPO terms in any number of cohorts.
+///
+/// The table has one column per cohort (named after the file name of the cohort) and a "Total" column.
+/// As in [`output_comparison_table`], the terms are grouped by top-level category, the counts include
+/// the ancestors of the annotated terms, and only terms with at least `threshold` measurements
+/// (observed + excluded, across all cohorts) are written. Terms that were not measured in a cohort
+/// are shown as "0/0 (n/a)".
+///
+/// # Errors
+///
+/// * If no cohort is given, or a cohort JSON file cannot be read or parsed.
+/// * If any HPO terms in the cohorts cannot be resolved in the provided ontology.
+/// * If the output file cannot be created or written.
+pub fn compare_multiple_cohorts(
+    cohort_paths: &[&str],
+    output_path: &str,
+    hpo: Arc<FullCsrOntology>,
+    threshold: usize
+) -> Result<(), String> {
+    let mut cohorts = Vec::with_capacity(cohort_paths.len());
+    for cohort_path in cohort_paths {
+        let name = Path::new(cohort_path)
+            .file_stem()
+            .map(|s| s.to_string_lossy().to_string())
+            .unwrap_or_else(|| cohort_path.to_string());
+        cohorts.push((name, factory::load_json_cohort(cohort_path)?));
+    }
+    let compare = MultiCohortCompare::new(cohorts, hpo)?;
+    compare.output_table(output_path, threshold)
+}
+
 
 /// Export a comparison of two cohorts to an Excel file.
 ///
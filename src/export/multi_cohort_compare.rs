@@ -0,0 +1,193 @@
+//! MultiCohortCompare: Compare the distribution of HPO terms in any number of cohorts
+//!
+//! In contrast to [`TableCompare`], which is limited to two cohorts, the table has one column per cohort
+//! and a "Total" column. Terms that are not annotated in a cohort are shown as "0/0 (n/a)" in its column.
+//! As in [`TableCompare`], the counts include the ancestors of the annotated terms, and the terms are grouped
+//! by their top-level category (children of Phenotypic abnormality).
+use std::{
+    collections::{BTreeMap, HashMap},
+    fs::File,
+    io::{BufWriter, Write},
+    str::FromStr,
+    sync::Arc,
+};
+
+use ontolius::{
+    ontology::{csr::FullCsrOntology, HierarchyQueries, HierarchyWalks, OntologyTerms},
+    term::MinimalTerm,
+    Identified, TermId,
+};
+
+use crate::dto::{cohort_dto::CohortData, hpo_term_dto::HpoTermDuplet};
+use crate::export::table_compare::{TableCompare, TermCounter};
+
+
+/// One row of the table: the counts of an HPO term in each cohort and in total
+#[derive(Clone, Debug)]
+struct MultiRowCounter {
+    duplet: HpoTermDuplet,
+    /// (observed, measured) for each cohort, in the order of the cohorts
+    counts: Vec<(usize, usize)>,
+}
+
+impl MultiRowCounter {
+    fn total(&self) -> (usize, usize) {
+        self.counts
+            .iter()
+            .fold((0, 0), |(o, m), (observed, measured)| (o + observed, m + measured))
+    }
+
+    fn format_counts((observed, measured): (usize, usize)) -> String {
+        if measured == 0 {
+            return "0/0 (n/a)".to_string();
+        }
+        let percent = 100.0 * (observed as f64) / (measured as f64);
+        format!("{}/{} ({:.1}%)", observed, measured, percent)
+    }
+
+    fn get_row(&self) -> Vec<String> {
+        let mut row = vec![self.duplet.hpo_label().to_string(), self.duplet.hpo_id().to_string()];
+        row.extend(self.counts.iter().map(|c| Self::format_counts(*c)));
+        row.push(Self::format_counts(self.total()));
+        row
+    }
+}
+
+
+pub struct MultiCohortCompare {
+    cohort_names: Vec<String>,
+    /// Key: label and id of a top-level term; value: the rows of the terms in this category
+    category_map: BTreeMap<(String, String), Vec<MultiRowCounter>>,
+}
+
+impl MultiCohortCompare {
+    /// `cohorts` holds the name (column header) and data of each cohort
+    pub fn new(cohorts: Vec<(String, CohortData)>, hpo: Arc<FullCsrOntology>) -> Result<Self, String> {
+        if cohorts.is_empty() {
+            return Err("At least one cohort is required for the comparison".to_string());
+        }
+        let mut cohort_names = Vec::with_capacity(cohorts.len());
+        let mut count_maps: Vec<HashMap<HpoTermDuplet, TermCounter>> = Vec::with_capacity(cohorts.len());
+        for (name, cohort) in cohorts {
+            cohort_names.push(name);
+            count_maps.push(TableCompare::term_count_map(cohort, hpo.clone())?);
+        }
+        let mut all_terms: Vec<HpoTermDuplet> = count_maps
+            .iter()
+            .flat_map(|m| m.keys().cloned())
+            .collect();
+        all_terms.sort_by(|a, b| a.hpo_label().cmp(b.hpo_label()).then(a.hpo_id().cmp(b.hpo_id())));
+        all_terms.dedup();
+        let phenotypic_abn = TermId::from_str("HP:0000118").unwrap();
+        let mut top_level_terms = Vec::new();
+        for hpo_id in hpo.iter_child_ids(&phenotypic_abn) {
+            let term = hpo.term_by_id(hpo_id).ok_or_else(|| format!("Could not find term for {}", hpo_id))?;
+            top_level_terms.push(HpoTermDuplet::new(term.name(), term.identifier().to_string()));
+        }
+        let mut category_map: BTreeMap<(String, String), Vec<MultiRowCounter>> = BTreeMap::new();
+        for duplet in all_terms {
+            let tid = duplet.to_term_id()?;
+            let counts: Vec<(usize, usize)> = count_maps
+                .iter()
+                .map(|m| m.get(&duplet).map_or((0, 0), |c| (c.observed(), c.measured())))
+                .collect();
+            for top_duplet in &top_level_terms {
+                if hpo.is_descendant_of(&tid, &top_duplet.to_term_id()?) {
+                    category_map
+                        .entry((top_duplet.hpo_label().to_string(), top_duplet.hpo_id().to_string()))
+                        .or_default()
+                        .push(MultiRowCounter { duplet: duplet.clone(), counts: counts.clone() });
+                }
+            }
+        }
+        Ok(Self { cohort_names, category_map })
+    }
+
+    pub fn get_header(&self) -> Vec<String> {
+        let mut header = vec!["HPO".to_string(), "HPO.id".to_string()];
+        header.extend(self.cohort_names.iter().cloned());
+        header.push("Total".to_string());
+        header
+    }
+
+    /// The table rows: for each category (if any of its terms is over the threshold), a subheader followed
+    /// by the terms whose total number of measurements (observed + excluded) is at least `threshold`
+    pub fn get_rows(&self, threshold: usize) -> Vec<Vec<String>> {
+        let mut rows = Vec::new();
+        for ((top_label, top_id), row_counters) in &self.category_map {
+            let selected: Vec<&MultiRowCounter> = row_counters
+                .iter()
+                .filter(|r| r.total().1 >= threshold)
+                .collect();
+            if selected.is_empty() {
+                continue;
+            }
+            let mut subheader = vec![top_label.clone(), top_id.clone()];
+            subheader.resize(self.cohort_names.len() + 3, String::default());
+            rows.push(subheader);
+            rows.extend(selected.iter().map(|r| r.get_row()));
+        }
+        rows
+    }
+
+    pub fn output_table(&self, output_path: &str, threshold: usize) -> Result<(), String> {
+        let file = File::create(output_path).map_err(|e| e.to_string())?;
+        let mut writer = BufWriter::new(file);
+        writeln!(writer, "{}", self.get_header().join("\t")).map_err(|e| e.to_string())?;
+        for row in self.get_rows(threshold) {
+            writeln!(writer, "{}", row.join("\t")).map_err(|e| e.to_string())?;
+        }
+        writer.flush().map_err(|e| e.to_string())
+    }
+}
+
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use rstest::rstest;
+    use crate::test_utils::fixtures::{fbn1_cohort, hpo};
+
+    fn find_row<'a>(rows: &'a [Vec<String>], hpo_id: &str) -> &'a Vec<String> {
+        rows.iter().find(|r| r[1] == hpo_id).unwrap()
+    }
+
+    #[rstest]
+    fn test_three_cohorts(hpo: Arc<FullCsrOntology>, fbn1_cohort: CohortData) {
+        // the third cohort was annotated with Ectopia lentis instead of Arachnodactyly
+        let mut other = fbn1_cohort.clone();
+        other.hpo_headers[0] = HpoTermDuplet::new("Ectopia lentis", "HP:0001083");
+        let cohorts = vec![
+            ("A".to_string(), fbn1_cohort.clone()),
+            ("B".to_string(), fbn1_cohort),
+            ("C".to_string(), other),
+        ];
+        let compare = MultiCohortCompare::new(cohorts, hpo).unwrap();
+        assert_eq!(vec!["HPO", "HPO.id", "A", "B", "C", "Total"], compare.get_header());
+        let rows = compare.get_rows(1);
+        assert!(rows.iter().all(|r| r.len() == 6));
+        let arachnodactyly = find_row(&rows, "HP:0001166");
+        assert_eq!("3/3 (100.0%)", arachnodactyly[2]);
+        assert_eq!("0/0 (n/a)", arachnodactyly[4]);
+        assert_eq!("6/6 (100.0%)", arachnodactyly[5]);
+        let ectopia_lentis = find_row(&rows, "HP:0001083");
+        assert_eq!("0/0 (n/a)", ectopia_lentis[2]);
+        assert_eq!("3/3 (100.0%)", ectopia_lentis[5]);
+        // Long hallux: observed once, excluded once in each cohort
+        assert_eq!("3/6 (50.0%)", find_row(&rows, "HP:0001847")[5]);
+    }
+
+    #[rstest]
+    fn test_threshold(hpo: Arc<FullCsrOntology>, fbn1_cohort: CohortData) {
+        let compare = MultiCohortCompare::new(vec![("A".to_string(), fbn1_cohort)], hpo).unwrap();
+        let rows = compare.get_rows(3);
+        assert!(rows.iter().any(|r| r[1] == "HP:0001166"));
+        // Atrial septal defect was measured in two individuals only
+        assert!(!rows.iter().any(|r| r[1] == "HP:0001631"));
+    }
+
+    #[rstest]
+    fn test_no_cohorts(hpo: Arc<FullCsrOntology>) {
+        assert!(MultiCohortCompare::new(vec![], hpo).is_err());
+    }
+}
@@ -0,0 +1,142 @@
+//! OnsetDistribution: Summary statistics for the age of onset in a cohort
+//!
+//! Only ISO8601 ages (e.g., P3Y6M) can be converted to years. Individuals whose onset is recorded
+//! as an HPO onset term (e.g., Congenital onset) or as a gestational age are counted as having
+//! onset data, but do not contribute to the numerical statistics.
+use serde::{Deserialize, Serialize};
+
+use crate::{age::iso_age::Iso8601Age, dto::cohort_dto::CohortData};
+
+
+#[derive(Clone, Debug, Default, Deserialize, Serialize, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct OnsetDistribution {
+    pub mean_years: f64,
+    pub median_years: f64,
+    /// Sample standard deviation (zero if fewer than two numerical ages are available)
+    pub std_dev: f64,
+    pub min_years: f64,
+    pub max_years: f64,
+    /// Individuals with any valid onset (ISO8601, HPO onset term, or gestational age)
+    pub n_with_onset: usize,
+    /// Individuals whose onset is not available (na)
+    pub n_without_onset: usize,
+    /// Individuals whose onset is an ISO8601 age and was used for the numerical statistics
+    pub n_numeric_onset: usize,
+}
+
+impl OnsetDistribution {
+    pub fn from_cohort(cohort: &CohortData) -> Self {
+        let mut years: Vec<f64> = Vec::new();
+        let mut n_with_onset = 0;
+        let mut n_without_onset = 0;
+        for row in &cohort.rows {
+            let onset = row.individual_data.age_of_onset.trim();
+            if onset.is_empty() || onset == "na" || !crate::age::is_valid_age_string(onset) {
+                n_without_onset += 1;
+                continue;
+            }
+            n_with_onset += 1;
+            if Iso8601Age::is_valid(onset) {
+                if let Ok(y) = Iso8601Age::to_years(onset) {
+                    years.push(y);
+                }
+            }
+        }
+        let mut distribution = Self {
+            n_with_onset,
+            n_without_onset,
+            n_numeric_onset: years.len(),
+            ..Default::default()
+        };
+        if years.is_empty() {
+            return distribution;
+        }
+        years.sort_by(|a, b| a.total_cmp(b));
+        let n = years.len();
+        let mean = years.iter().sum::<f64>() / n as f64;
+        distribution.mean_years = mean;
+        distribution.median_years = if n % 2 == 0 {
+            (years[n / 2 - 1] + years[n / 2]) / 2.0
+        } else {
+            years[n / 2]
+        };
+        distribution.std_dev = if n > 1 {
+            (years.iter().map(|y| (y - mean).powi(2)).sum::<f64>() / (n - 1) as f64).sqrt()
+        } else {
+            0.0
+        };
+        distribution.min_years = years[0];
+        distribution.max_years = years[n - 1];
+        distribution
+    }
+
+    /// One-line, human-readable summary, e.g.,
+    /// `Onset (n=3): mean 4.2 y (SD 1.3), median 4.0 y, range 3.0-5.5 y; 1 without onset`
+    pub fn to_summary_string(&self) -> String {
+        let numeric = if self.n_numeric_onset == 0 {
+            "no ISO8601 ages".to_string()
+        } else {
+            format!("mean {:.1} y (SD {:.1}), median {:.1} y, range {:.1}-{:.1} y",
+                self.mean_years, self.std_dev, self.median_years, self.min_years, self.max_years)
+        };
+        let mut summary = format!("Onset (n={}): {}", self.n_with_onset, numeric);
+        if self.n_numeric_onset < self.n_with_onset {
+            summary.push_str(&format!("; {} with onset term only", self.n_with_onset - self.n_numeric_onset));
+        }
+        summary.push_str(&format!("; {} without onset", self.n_without_onset));
+        summary
+    }
+}
+
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use rstest::rstest;
+    use crate::test_utils::fixtures::fbn1_cohort;
+
+    #[rstest]
+    fn test_mixed_onset_strings(fbn1_cohort: CohortData) {
+        // P2Y, Congenital onset, na
+        let distribution = OnsetDistribution::from_cohort(&fbn1_cohort);
+        assert_eq!(2, distribution.n_with_onset);
+        assert_eq!(1, distribution.n_without_onset);
+        assert_eq!(1, distribution.n_numeric_onset);
+        assert_eq!(2.0, distribution.mean_years);
+        assert_eq!(2.0, distribution.median_years);
+        assert_eq!(0.0, distribution.std_dev);
+        assert_eq!(
+            "Onset (n=2): mean 2.0 y (SD 0.0), median 2.0 y, range 2.0-2.0 y; 1 with onset term only; 1 without onset",
+            distribution.to_summary_string());
+    }
+
+    #[rstest]
+    fn test_iso_statistics(mut fbn1_cohort: CohortData) {
+        let mut extra = fbn1_cohort.rows[0].clone();
+        extra.individual_data.age_of_onset = "P6M".to_string();
+        fbn1_cohort.rows.push(extra);
+        fbn1_cohort.rows[1].individual_data.age_of_onset = "P3Y6M".to_string();
+        fbn1_cohort.rows[2].individual_data.age_of_onset = "P8Y".to_string();
+        // 0.5, 2, 3.5, 8
+        let distribution = OnsetDistribution::from_cohort(&fbn1_cohort);
+        assert_eq!(4, distribution.n_numeric_onset);
+        assert_eq!(0, distribution.n_without_onset);
+        assert!((distribution.mean_years - 3.5).abs() < 1e-9);
+        assert!((distribution.median_years - 2.75).abs() < 1e-9);
+        assert!((distribution.std_dev - 3.240370).abs() < 1e-6);
+        assert_eq!(0.5, distribution.min_years);
+        assert_eq!(8.0, distribution.max_years);
+    }
+
+    #[rstest]
+    fn test_no_onset(mut fbn1_cohort: CohortData) {
+        for row in fbn1_cohort.rows.iter_mut() {
+            row.individual_data.age_of_onset = "na".to_string();
+        }
+        let distribution = OnsetDistribution::from_cohort(&fbn1_cohort);
+        assert_eq!(0, distribution.n_with_onset);
+        assert_eq!(3, distribution.n_without_onset);
+        assert_eq!("Onset (n=0): no ISO8601 ages; 3 without onset", distribution.to_summary_string());
+    }
+}
@@ -0,0 +1,103 @@
+//! SexStatistics: Sex distribution of a cohort
+//!
+//! The chi-square test compares the numbers of males and females against a 1:1 expectation.
+//! It is only computed if there are at least five males and five females, since the
+//! chi-square approximation is unreliable for small expected counts.
+use serde::{Deserialize, Serialize};
+
+use crate::{dto::cohort_dto::CohortData, factory::statistics};
+
+
+/// Minimum number of males and of females required to perform the chi-square test
+const MIN_COUNT_FOR_TEST: usize = 5;
+
+
+#[derive(Clone, Debug, Default, Deserialize, Serialize, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct SexStatistics {
+    pub n_male: usize,
+    pub n_female: usize,
+    pub n_other: usize,
+    pub n_unknown: usize,
+    /// p-value of the chi-square test for a 1:1 male:female ratio
+    pub chi_square_pvalue: Option<f64>,
+}
+
+impl SexStatistics {
+    pub fn from_cohort(cohort: &CohortData) -> Self {
+        let mut stats = Self::default();
+        for row in &cohort.rows {
+            match row.individual_data.sex.trim() {
+                "M" => stats.n_male += 1,
+                "F" => stats.n_female += 1,
+                "O" => stats.n_other += 1,
+                _ => stats.n_unknown += 1,
+            }
+        }
+        if stats.n_male >= MIN_COUNT_FOR_TEST && stats.n_female >= MIN_COUNT_FOR_TEST {
+            stats.chi_square_pvalue = Some(statistics::chi_square_equal_proportions(stats.n_male, stats.n_female));
+        }
+        stats
+    }
+
+    pub fn to_summary_string(&self) -> String {
+        let mut summary = format!("Sex: {} male, {} female, {} other, {} unknown",
+            self.n_male, self.n_female, self.n_other, self.n_unknown);
+        if let Some(p) = self.chi_square_pvalue {
+            summary.push_str(&format!(" (chi-square test for 1:1 ratio: p={:.3})", p));
+        }
+        summary
+    }
+}
+
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use rstest::rstest;
+    use crate::test_utils::fixtures::fbn1_cohort;
+
+    fn cohort_with_sexes(template: CohortData, n_male: usize, n_female: usize) -> CohortData {
+        let mut cohort = template.clone();
+        let row = template.rows[0].clone();
+        cohort.rows.clear();
+        for i in 0..(n_male + n_female) {
+            let mut r = row.clone();
+            r.individual_data.individual_id = format!("Individual {i}");
+            r.individual_data.sex = if i < n_male { "M" } else { "F" }.to_string();
+            cohort.rows.push(r);
+        }
+        cohort
+    }
+
+    #[rstest]
+    fn test_fixture_cohort(fbn1_cohort: CohortData) {
+        let stats = SexStatistics::from_cohort(&fbn1_cohort);
+        assert_eq!(1, stats.n_male);
+        assert_eq!(2, stats.n_female);
+        assert_eq!(None, stats.chi_square_pvalue);
+    }
+
+    #[rstest]
+    fn test_male_only_cohort(fbn1_cohort: CohortData) {
+        let stats = SexStatistics::from_cohort(&cohort_with_sexes(fbn1_cohort, 12, 0));
+        assert_eq!(12, stats.n_male);
+        assert_eq!(0, stats.n_female);
+        assert_eq!(None, stats.chi_square_pvalue);
+    }
+
+    #[rstest]
+    fn test_balanced_cohort(fbn1_cohort: CohortData) {
+        let stats = SexStatistics::from_cohort(&cohort_with_sexes(fbn1_cohort, 10, 10));
+        assert_eq!(Some(1.0), stats.chi_square_pvalue);
+    }
+
+    #[rstest]
+    fn test_three_to_one_cohort(fbn1_cohort: CohortData) {
+        // chi-square = 5, R: pchisq(5, df=1, lower.tail=FALSE) = 0.02534732
+        let stats = SexStatistics::from_cohort(&cohort_with_sexes(fbn1_cohort, 15, 5));
+        let p = stats.chi_square_pvalue.unwrap();
+        assert!((p - 0.02534732).abs() < 1e-6);
+        assert!(stats.to_summary_string().contains("p=0.025"));
+    }
+}
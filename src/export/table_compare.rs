@@ -75,6 +75,14 @@ impl TermCounter {
         Self::new(duplet)
     }
 
+    pub(crate) fn observed(&self) -> usize {
+        self.observed
+    }
+
+    pub(crate) fn measured(&self) -> usize {
+        self.measured
+    }
+
     /// Combine the counts from two cohorts to get the totals
     pub fn from_pair(counter_1: TermCounter, counter_2: TermCounter) -> Result<Self, String> {
         let total_o = counter_1.observed + counter_2.observed;
@@ -240,7 +248,7 @@ impl TableCompare {
 
     /// Get a Map with key, an HpoTermDuplet, value -- number of times term is annotated in cohort
     /// We include ancestors of direct terms
-    fn term_count_map(
+    pub(crate) fn term_count_map(
         cohort: CohortData,
         hpo: Arc<FullCsrOntology>,
     ) -> Result<HashMap<HpoTermDuplet, TermCounter>, String> {
@@ -251,12 +259,13 @@ impl TableCompare {
                 .entry(term_duplet.clone())
                 .or_insert_with(|| TermCounter::new(term_duplet));
             for row in &cohort.rows {
-                let increment_action = match &row.hpo_data[i].entry {
-                    CellValueInner::Observed | CellValueInner::OnsetAge(_)  => {
-                        TermCounter::increment_observed
-                    }
-                    CellValueInner::Excluded => TermCounter::increment_excluded,
-                    CellValueInner::Na => continue,
+                let cell = &row.hpo_data[i];
+                let increment_action = if cell.is_present() {
+                    TermCounter::increment_observed
+                } else if cell.is_measured() {
+                    TermCounter::increment_excluded
+                } else {
+                    continue;
                 };
                 increment_action(count_map.get_mut(term_duplet).unwrap());
                 // increment ancestors
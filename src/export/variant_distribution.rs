@@ -0,0 +1,160 @@
+//! VariantTypeDistribution: Count the distinct variants of a cohort by (predicted) type
+//!
+//! The type of an HGVS variant is inferred from its nomenclature. The protein-level HGVS is used to
+//! recognize frameshift and nonsense variants, intronic offsets (e.g., c.123+1G>A) indicate splice variants,
+//! and the transcript-level HGVS is used for deletions, insertions, and duplications.
+//! This is a heuristic summary for the cohort report and does not replace a proper variant annotation.
+use serde::{Deserialize, Serialize};
+
+use crate::dto::{cohort_dto::CohortData, hgvs_variant::HgvsVariant};
+
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum HgvsVariantType {
+    Missense,
+    Nonsense,
+    Frameshift,
+    Splice,
+    Deletion,
+    Insertion,
+    Duplication,
+    Other,
+}
+
+impl HgvsVariantType {
+    pub fn classify(variant: &HgvsVariant) -> Self {
+        let hgvs = variant.hgvs();
+        let p_hgvs = variant.p_hgvs().unwrap_or_default();
+        if p_hgvs.contains("fs") {
+            HgvsVariantType::Frameshift
+        } else if p_hgvs.contains("Ter") || p_hgvs.ends_with('*') || p_hgvs.ends_with("*)") {
+            HgvsVariantType::Nonsense
+        } else if Self::is_splice(hgvs) {
+            HgvsVariantType::Splice
+        } else if hgvs.contains("dup") {
+            HgvsVariantType::Duplication
+        } else if hgvs.contains("del") {
+            HgvsVariantType::Deletion
+        } else if hgvs.contains("ins") {
+            HgvsVariantType::Insertion
+        } else if hgvs.contains('>') && Self::is_amino_acid_change(&p_hgvs) {
+            HgvsVariantType::Missense
+        } else {
+            HgvsVariantType::Other
+        }
+    }
+
+    /// A position with an intronic offset, e.g., c.123+1G>A or c.124-2A>G (but not 5' UTR positions such as c.-12C>T)
+    fn is_splice(hgvs: &str) -> bool {
+        let Some(position) = hgvs.strip_prefix("c.") else {
+            return false;
+        };
+        let position = position.trim_start_matches(['-', '*']);
+        position
+            .char_indices()
+            .find(|(_, c)| !c.is_ascii_digit())
+            .is_some_and(|(i, c)| i > 0 && (c == '+' || c == '-'))
+    }
+
+    /// True for substitutions such as p.(Arg123Cys) or p.Arg123Cys but not for synonymous (p.Arg123=)
+    /// or unknown (p.?) consequences
+    fn is_amino_acid_change(p_hgvs: &str) -> bool {
+        !p_hgvs.is_empty() && !p_hgvs.contains('=') && !p_hgvs.contains('?')
+    }
+}
+
+
+/// Counts of the distinct variants of the cohort by type
+#[derive(Clone, Debug, Default, Deserialize, Serialize, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct VariantTypeDistribution {
+    pub missense: usize,
+    pub nonsense: usize,
+    pub frameshift: usize,
+    pub splice: usize,
+    pub deletion: usize,
+    pub insertion: usize,
+    pub duplication: usize,
+    /// Structural variants
+    pub sv: usize,
+    /// Other HGVS variants (e.g., synonymous or UTR) and intergenic variants
+    pub other: usize,
+}
+
+impl VariantTypeDistribution {
+    pub fn from_cohort(cohort: &CohortData) -> Self {
+        let mut distribution = Self::default();
+        for variant in cohort.hgvs_variants.values() {
+            distribution.add(HgvsVariantType::classify(variant));
+        }
+        distribution.sv = cohort.structural_variants.len();
+        distribution.other += cohort.intergenic_variants.len();
+        distribution
+    }
+
+    fn add(&mut self, variant_type: HgvsVariantType) {
+        match variant_type {
+            HgvsVariantType::Missense => self.missense += 1,
+            HgvsVariantType::Nonsense => self.nonsense += 1,
+            HgvsVariantType::Frameshift => self.frameshift += 1,
+            HgvsVariantType::Splice => self.splice += 1,
+            HgvsVariantType::Deletion => self.deletion += 1,
+            HgvsVariantType::Insertion => self.insertion += 1,
+            HgvsVariantType::Duplication => self.duplication += 1,
+            HgvsVariantType::Other => self.other += 1,
+        }
+    }
+
+    pub fn total(&self) -> usize {
+        self.missense + self.nonsense + self.frameshift + self.splice + self.deletion
+            + self.insertion + self.duplication + self.sv + self.other
+    }
+}
+
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use rstest::rstest;
+    use crate::test_utils::fixtures::fbn1_cohort;
+    use crate::variant::vcf_var::VcfVar;
+
+    fn hgvs_variant(hgvs: &str, p_hgvs: Option<&str>) -> HgvsVariant {
+        HgvsVariant::new(
+            "hg38".to_string(),
+            VcfVar::new("15".to_string(), 48411364, "C".to_string(), "A".to_string()),
+            "FBN1".to_string(),
+            "HGNC:3603".to_string(),
+            hgvs.to_string(),
+            p_hgvs.map(|p| p.to_string()),
+            "NM_000138.5".to_string(),
+            "NC_000015.10:g.48411364C>A".to_string(),
+        )
+    }
+
+    #[rstest]
+    #[case("c.8242G>T", Some("NP_000129.3:p.(Glu2748Ter)"), HgvsVariantType::Nonsense)]
+    #[case("c.8242G>T", Some("NP_000129.3:p.(Glu2748*)"), HgvsVariantType::Nonsense)]
+    #[case("c.1A>G", Some("NP_000129.3:p.(Cys1Arg)"), HgvsVariantType::Missense)]
+    #[case("c.1A>G", Some("NP_000129.3:p.(Cys1=)"), HgvsVariantType::Other)]
+    #[case("c.1A>G", None, HgvsVariantType::Other)]
+    #[case("c.5788+1G>A", None, HgvsVariantType::Splice)]
+    #[case("c.5789-2A>G", Some("NP_000129.3:p.?"), HgvsVariantType::Splice)]
+    #[case("c.-12C>T", None, HgvsVariantType::Other)]
+    #[case("c.100del", Some("NP_000129.3:p.(Leu34SerfsTer5)"), HgvsVariantType::Frameshift)]
+    #[case("c.100_102del", Some("NP_000129.3:p.(Leu34del)"), HgvsVariantType::Deletion)]
+    #[case("c.100_102dup", Some("NP_000129.3:p.(Leu34dup)"), HgvsVariantType::Duplication)]
+    #[case("c.100_101insGGC", Some("NP_000129.3:p.(Leu34_Ser35insGly)"), HgvsVariantType::Insertion)]
+    fn test_classify(#[case] hgvs: &str, #[case] p_hgvs: Option<&str>, #[case] expected: HgvsVariantType) {
+        assert_eq!(expected, HgvsVariantType::classify(&hgvs_variant(hgvs, p_hgvs)));
+    }
+
+    #[rstest]
+    fn test_distribution(fbn1_cohort: CohortData) {
+        // one HGVS substitution without protein HGVS and one deletion (SV)
+        let distribution = VariantTypeDistribution::from_cohort(&fbn1_cohort);
+        assert_eq!(1, distribution.sv);
+        assert_eq!(1, distribution.other);
+        assert_eq!(2, distribution.total());
+    }
+}
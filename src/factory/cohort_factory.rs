@@ -7,12 +7,13 @@
 use std::{collections::{HashMap, HashSet}, str::FromStr, sync::Arc, vec};
 use ontolius::{
     ontology::{csr::FullCsrOntology, MetadataAware, OntologyTerms},
-    term::{simple::{SimpleMinimalTerm, SimpleTerm}, MinimalTerm},
+    term::{simple::SimpleTerm, MinimalTerm},
     Identified, TermId,
 };
 use phenopackets::schema::v2::Phenopacket;
 
-use crate::{dto::{cohort_dto::{CohortData, CohortType, DiseaseData, GeneTranscriptData, IndividualData, RowData}, hgvs_variant::HgvsVariant, hpo_term_dto::{CellValue, HpoTermData, HpoTermDuplet}, structural_variant::{StructuralVariant, SvType}}, factory::header_duplet_row::HeaderDupletRow, hpo, ppkt::ppkt_row::PpktRow, variant::variant_manager::VariantManager};
+use crate::error::ErrorContext;
+use crate::{dto::{cohort_dto::{CohortData, CohortType, DiseaseData, GeneTranscriptData, IndividualData, RowData}, hgvs_variant::HgvsVariant, hpo_term_dto::{CellValue, CellValueInner, HpoTermData, HpoTermDuplet}, structural_variant::{StructuralVariant, SvType}}, factory::header_duplet_row::HeaderDupletRow, hpo::{self, TermStatus}, ppkt::ppkt_row::PpktRow, variant::variant_manager::VariantManager};
 
 
 
@@ -50,7 +51,7 @@ impl CohortFactory {
         for hdd in &cohort_dto.hpo_headers {
             match hdd.to_term_id() {
                 Ok(tid) => tid_list.push(tid),
-                Err(e) => { return Err(format!("Could not extract TermIf from {:?}", hdd)); }
+                Err(e) => { return Err(format!("Could not extract TermId from {:?}: {e}", hdd)); }
             }
         }
         Ok(tid_list)
@@ -110,26 +111,20 @@ impl CohortFactory {
         
         // 3b. Update the existing PpktRow objects
         let mut updated_row_dto_list: Vec<RowData> = Vec::new();
-        let mut term_id_map: HashMap<TermId, String> = HashMap::new();
-        // Make a map and add "na" as the default value for all terms
-        for term in &arranged_terms {
-            term_id_map.insert(term.to_term_id()?, "na".to_string());
-        }
         let previous_hpo_id_list = Self::get_previous_hpo_id_list(&cohort_dto)?;
         for row in cohort_dto.rows {
-            // make a copy of the default map and add the actual values for terms for which we have data
-            let tid_map = term_id_map.clone();
-            match Self::update_row_dto(row, &tid_map, &arranged_terms, &previous_hpo_id_list) {
+            match Self::update_row_dto(row, &arranged_terms, &previous_hpo_id_list) {
                 Ok(updated_row) => {updated_row_dto_list.push(updated_row);},
                 Err(err) => { return Err(err); },
             }
         }
         // Now add the new RowDto object
         // 1. get map with TermId and Value (e.g., observed) for the new terms
-        let mut tid_to_value_map: HashMap<TermId, String> = HashMap::new();
+        // We keep the complete CellValue so that modifiers (e.g., severity) entered in the GUI are retained
+        let mut tid_to_value_map: HashMap<TermId, CellValue> = HashMap::new();
         for dto in   hpo_annotations {
             match dto.ontolius_term_id() {
-                Ok(tid) => { tid_to_value_map.insert(tid, dto.entry().to_string()); },
+                Ok(tid) => { tid_to_value_map.insert(tid, dto.cell_value()); },
                 Err(_) => { return Err(format!("Could not create TermId from {:?}", &dto)); },
             }
         }
@@ -159,8 +154,10 @@ impl CohortFactory {
             phetools_schema_version: cohort_dto.phetools_schema_version,
             hpo_version: self.hpo.version().to_string(),
             cohort_acronym: cohort_dto.cohort_acronym,
-            curation_history: cohort_dto.curation_history
+            curation_history: cohort_dto.curation_history,
+            trios: cohort_dto.trios,
         };
+        debug_assert_eq!(Ok(()), updated_cohort_dto.validate_structural_integrity());
         Ok(updated_cohort_dto)
         
        
@@ -168,7 +165,6 @@ impl CohortFactory {
 
     fn update_row_dto(
         row: RowData, 
-        tid_to_value_map: &HashMap<TermId, String>,
         updated_header: &Vec<HpoTermDuplet>,
         previous_hpo_id_list: &[TermId]
     ) -> Result<RowData, String> {
@@ -201,21 +197,20 @@ impl CohortFactory {
     /// * `header` - Header with all HPO terms in previous cohort and new phenopacket, ordered by DFS
     /// * `individual_dto` - DTO with demographic information about the new individual
     /// * `variant_key_list` - List of variant keys (one per allele) for this individual
-    /// * `tid_to_value_map` - this has values (e.g., observed, na, P32Y2M) for which we have information in the new phenopacket
+    /// * `tid_to_value_map` - this has values (e.g., observed, na, P32Y2M, with any modifiers) for which we have information in the new phenopacket
     /// * `dg_data`- list of diseases diagnosed in this cohort/individual
     fn new_row_dto(
         header_dto_list:  &Vec<HpoTermDuplet>, 
         individual_dto: IndividualData,
         variant_key_list: Vec<String>,
-        tid_to_value_map: HashMap<TermId, String>, 
+        tid_to_value_map: HashMap<TermId, CellValue>, 
         disease_data_list: &Vec<DiseaseData>
     ) -> std::result::Result<RowData, String> {
         // Create a list of CellDto objects that matches the new order of HPO headers
         let mut hpo_cell_list: Vec<CellValue> = Vec::with_capacity(header_dto_list.len());
         for hduplet in header_dto_list {
             let tid = hduplet.to_term_id()?;
-            let value: String =  tid_to_value_map.get(&tid).map_or("na", |v| v).to_string();
-            let cell_value = CellValue::from_str(&value)?;
+            let cell_value = tid_to_value_map.get(&tid).cloned().unwrap_or_else(CellValue::na);
             hpo_cell_list.push(cell_value);
         }
         let disease_id_list: Vec<String> = disease_data_list.iter().map(|d| d.disease_id.clone()).collect();
@@ -303,7 +298,6 @@ impl CohortFactory {
         disease_data: DiseaseData,
         hpo: Arc<FullCsrOntology>,
     ) -> std::result::Result<CohortData, String> {
-        let smt_list: Vec<SimpleMinimalTerm> = Vec::new();
         if template_type == CohortType::Mendelian {
             let cohort_dto = Self::create_pyphetools_template_mendelian(hpo, disease_data)?;
             Ok(cohort_dto)
@@ -376,13 +370,13 @@ impl CohortFactory {
         let header_hpo_count = header.hpo_count();
         const HEADER_ROWS: usize = 2; // first two rows of template are header
         let hdr_arc = Arc::new(header);
-        let ppt_rows: Vec<PpktRow> = Vec::new();
         let dg_dto = Self::get_disease_dto_from_excel(&matrix)?;
         let mut vmanager = VariantManager::from_mendelian_matrix(&matrix, progress_cb)?;
         let mut row_dto_list: Vec<RowData> = Vec::new();
-        for row in matrix.into_iter().skip(HEADER_ROWS) {
+        for (row_idx, row) in matrix.into_iter().enumerate().skip(HEADER_ROWS) {
             let hdr_clone = hdr_arc.clone();
-            let ppkt_row = PpktRow::from_mendelian_row(hdr_clone, row)?;
+            let ppkt_row = PpktRow::from_mendelian_row(hdr_clone, row.clone())
+                .with_context(|| Self::template_cell_context(row_idx, &row))?;
             if ppkt_row.hpo_count() != header_hpo_count {
                 return Err(format!("Error ({}:l.{}) - PPKtRow has {} HPO columns, but the header has {} HPO columns",
                     file!(), line!(), ppkt_row.hpo_count(), header_hpo_count));
@@ -399,7 +393,7 @@ impl CohortFactory {
                         let allele_key = StructuralVariant::generate_variant_key(&gv_dto.allele1, &gv_dto.gene_symbol, SvType::Sv);
                         allele_key_list.push(allele_key);
                     } else {
-                        return Err(format!("Unknown allele1 type {:?}", gv_dto));
+                        return Err(format!("Row {row_idx}: Unknown allele1 type {:?}", gv_dto));
                     }
                 }
                 if gv_dto.allele2_is_present() {
@@ -411,11 +405,12 @@ impl CohortFactory {
                         let allele_key = StructuralVariant::generate_variant_key(&gv_dto.allele2, &gv_dto.gene_symbol, SvType::Sv);
                         allele_key_list.push(allele_key);
                     }else {
-                        return Err(format!("Unknown allele2 type {:?}", gv_dto));
+                        return Err(format!("Row {row_idx}: Unknown allele2 type {:?}", gv_dto));
                     }
                 }
             }
-            let row_dto = RowData::from_ppkt_row(&ppkt_row, allele_key_list)?;
+            let row_dto = RowData::from_ppkt_row(&ppkt_row, allele_key_list)
+                .with_context(|| format!("Row {row_idx}"))?;
             if row_dto.hpo_data.len() != header_hpo_count {
                 return Err(format!("Error ({}:l.{}) - RowDto has {} HPO columns, but the header has {} HPO columns",
                     file!(), line!(),row_dto.hpo_data.len(), header_hpo_count));
@@ -435,23 +430,55 @@ impl CohortFactory {
         Ok(cohort_dto)
     }
 
+    /// Location of a parsing failure in a row of the template, e.g., "Row 4, column 19" if an HPO cell is invalid.
+    /// The row and column indices are zero-based, as in the errors about the header rows.
+    fn template_cell_context(row_idx: usize, row: &[String]) -> String {
+        let invalid_hpo_cell = row
+            .iter()
+            .enumerate()
+            .skip(HeaderDupletRow::n_mendelian_contant_fields())
+            .find(|(_, cell)| {
+                let cell = if cell.trim().is_empty() { "na" } else { cell.as_str() };
+                !CellValueInner::is_valid_cell_value(cell)
+            });
+        match invalid_hpo_cell {
+            Some((col_idx, _)) => format!("Row {row_idx}, column {col_idx}"),
+            None => format!("Row {row_idx}"),
+        }
+    }
+
+    /// Check the HPO headers of a cohort against the current ontology.
+    ///
+    /// Obsolete identifiers that were replaced by another term are only reported as warnings, because
+    /// they can be updated automatically. Deleted identifiers and outdated labels are errors.
+    pub fn validate_hpo_headers(&self, cohort: &CohortData) -> std::result::Result<(), String> {
+        let errors: Vec<String> = cohort.hpo_headers
+            .iter()
+            .filter_map(|duplet| self.check_duplet(duplet).err())
+            .collect();
+        if errors.is_empty() {
+            Ok(())
+        } else {
+            Err(errors.join("; "))
+        }
+    }
+
     fn check_duplet(&self, duplet: &HpoTermDuplet) -> std::result::Result<(), String> {
-        let term_id = match TermId::from_str(duplet.hpo_id()) {
-            Ok(tid) => tid,
-            Err(e) => { return Err(format!("Could not create TermId from '{}': {}", duplet.hpo_id(), e)); },
-        };
-        match self.hpo.term_by_id(&term_id) {
-            Some(term) => {
-                if term.identifier().to_string() != duplet.hpo_id() {
-                    return Err(format!("Identifier '{}' did not match primary ID {}", duplet.hpo_id(), term.identifier()));
-                } else if term.name() != duplet.hpo_label() {
-                    return Err(format!("HPO Label '{}' did not match expected label {}", duplet.hpo_label(), term.name()));
+        match hpo::get_term_status(self.hpo.clone(), duplet.hpo_id()) {
+            TermStatus::Current => {
+                let term_id = duplet.to_term_id()?;
+                match self.hpo.term_by_id(&term_id) {
+                    Some(term) if term.name() != duplet.hpo_label() => 
+                        Err(format!("HPO Label '{}' did not match expected label {}", duplet.hpo_label(), term.name())),
+                    _ => Ok(()),
                 }
             },
-            None => { return Err(format!("Could not retrieve Term from TermId '{:?}'", term_id)); },
+            TermStatus::Replaced(new_tid) => {
+                tracing::warn!("HPO term {} ({}) is obsolete and was replaced by {}", duplet.hpo_id(), duplet.hpo_label(), new_tid);
+                Ok(())
+            },
+            TermStatus::Deleted => Err(format!("HPO term {} ({}) is not in the current HPO", duplet.hpo_id(), duplet.hpo_label())),
         }
-
-        Ok(())
     }
 
     
@@ -515,6 +542,7 @@ impl CohortFactory {
         orcid: &str) 
     -> std::result::Result<Vec<Phenopacket>, String> {
         crate::ppkt::get_phenopackets(cohort_dto, orcid.to_string(), self.hpo.clone())
+            .map_err(String::from)
     }
 
 
@@ -564,7 +592,14 @@ impl CohortFactory {
     -> std::result::Result<CohortData, String> {
         let new_tid = TermId::from_str(hpo_id)
                 .map_err(|_| format!("Could not create TermId from: '{}'", hpo_id))?;
-        let term = self.hpo
+        match hpo::get_term_status(self.hpo.clone(), hpo_id) {
+            TermStatus::Current => {},
+            TermStatus::Replaced(primary_tid) => 
+                tracing::warn!("HPO term {hpo_id} is obsolete; adding its replacement {primary_tid}"),
+            TermStatus::Deleted => 
+                return Err(format!("Cannot add {hpo_id} ({hpo_label}) because it is not in the current HPO")),
+        }
+        self.hpo
             .term_by_id(&new_tid)
             .ok_or_else(|| format!("could not retrieve HPO term for '{hpo_id}'"))?;
         // === STEP 1: Add new HPO term to existing terms and arrange TIDs ===
@@ -594,6 +629,7 @@ impl CohortFactory {
             updated_ppkt_rows.push(newrow);
         }
         updated_cohort.rows = updated_ppkt_rows;
+        debug_assert_eq!(Ok(()), updated_cohort.validate_structural_integrity());
         Ok(updated_cohort)
     }
 
@@ -672,7 +708,7 @@ impl CohortFactory {
         updated_cohort.hgvs_variants.extend(transformed.hgvs_variants);
         updated_cohort.structural_variants.extend(transformed.structural_variants); 
         updated_cohort.intergenic_variants.extend(transformed.intergenic_variants);
-
+        debug_assert_eq!(Ok(()), updated_cohort.validate_structural_integrity());
         Ok(updated_cohort)
     }
 }
@@ -681,7 +717,7 @@ impl CohortFactory {
 #[cfg(test)]
 mod test {
     use crate::{dto::cohort_dto::{DiseaseData, GeneTranscriptData}};
-    use crate::test_utils::fixtures::hpo;
+    use crate::test_utils::fixtures::{fbn1_cohort, hpo};
     use super::*;
     use rstest::{fixture, rstest};
 
@@ -763,6 +799,14 @@ mod test {
  
 
 
+    #[rstest]
+    fn test_template_cell_context(mut original_matrix: Vec<Vec<String>>) {
+        assert_eq!("Row 2", CohortFactory::template_cell_context(2, &original_matrix[2]));
+        original_matrix[2][19] = "obsreved".to_string();
+        assert_eq!("Row 2, column 19", CohortFactory::template_cell_context(2, &original_matrix[2]));
+    }
+
+
     /// Test that we detect errors in labels of headings
     #[rstest]
     #[case(0, "PMI", "PMID")]
@@ -801,7 +845,19 @@ mod test {
         assert_eq!(expected, err_msg); 
     }
 
-   
- 
-
+    #[rstest]
+    fn test_validate_hpo_headers(fbn1_cohort: CohortData, hpo: Arc<FullCsrOntology>) {
+        let factory = CohortFactory::new(hpo);
+        assert_eq!(Ok(()), factory.validate_hpo_headers(&fbn1_cohort));
+        // an obsolete alternative id of Arachnodactyly is only a warning
+        let mut replaced = fbn1_cohort.clone();
+        replaced.hpo_headers[0] = HpoTermDuplet::new("Arachnodactyly", "HP:0001505");
+        assert_eq!(Ok(()), factory.validate_hpo_headers(&replaced));
+        let mut deleted = fbn1_cohort.clone();
+        deleted.hpo_headers[0] = HpoTermDuplet::new("Made-up term", "HP:9999999");
+        deleted.hpo_headers[1] = HpoTermDuplet::new("Atrial septum defect", "HP:0001631");
+        let err = factory.validate_hpo_headers(&deleted).unwrap_err();
+        assert!(err.contains("HP:9999999 (Made-up term) is not in the current HPO"));
+        assert!(err.contains("did not match expected label Atrial septal defect"));
+    }
 }
@@ -3,7 +3,7 @@ use std::{collections::{HashMap, HashSet}, str::FromStr, sync::Arc};
 use ontolius::{Identified, TermId, ontology::{HierarchyQueries, OntologyTerms, csr::FullCsrOntology}, term::MinimalTerm};
 
 
-use crate::{dto::{cohort_dto::{CohortData, RowData}, hpo_term_dto::HpoTermDuplet}, factory::CohortError};
+use crate::{dto::{cohort_dto::{CohortData, RowData}, hpo_term_dto::{CellValueInner, HpoTermDuplet}, validation_errors::{QcSummary, QcViolation}}, factory::CohortError};
 
 
 /// Locally used struct for convenience
@@ -37,6 +37,16 @@ pub struct CohortDataQc {
 }
 
 
+/// Report the outcome of one Q/C rule at debug level (shown by the CLI with --verbose)
+fn log_rule(rule: &str, result: Result<(), CohortError>) -> Result<(), CohortError> {
+    match &result {
+        Ok(()) => tracing::debug!(rule, "Q/C check passed"),
+        Err(e) => tracing::debug!(rule, error = %e, "Q/C check failed"),
+    }
+    result
+}
+
+
 impl CohortDataQc {
 
     pub fn new(
@@ -58,15 +68,27 @@ impl CohortDataQc {
     pub fn qc_check(
         &self, 
         cohort: &CohortData) -> Result<(), CohortError> {
+        log_rule("row_length", self.check_row_lengths(cohort))?;
+        log_rule("duplicate_header", self.check_for_duplicate_headers(cohort))?;
+        log_rule("hpo_ids_and_labels", self.check_hpo_ids_and_labels(cohort).map_err(|e| CohortError::format(e)))?;
+        log_rule("duplicate_rows", self.check_for_duplicate_rows(cohort))?;
+        log_rule("biocuration", self.check_biocuration(cohort))?;
+        Ok(())
+    }
+
+    /// Check that all of the rows have one value per HPO column
+    fn check_row_lengths(&self, cohort: &CohortData) -> Result<(), CohortError> {
         let n_hpos = cohort.hpo_headers.len();
-        // check correct length
         for row in &cohort.rows {
             if row.hpo_data.len() != n_hpos {
                 let msg = format!("Length mismatch: Header: {} vs. row: {}", n_hpos, row.hpo_data.len());
                 return Err(CohortError::format(msg));
             }
         }
-        // check for duplicates
+        Ok(())
+    }
+
+    fn check_for_duplicate_headers(&self, cohort: &CohortData) -> Result<(), CohortError> {
         let mut seen = HashSet::new();
         for duplet in &cohort.hpo_headers {
             if seen.contains(duplet) {
@@ -76,10 +98,6 @@ impl CohortDataQc {
                 seen.insert(duplet);
             }
         }
-        self.check_hpo_ids_and_labels(cohort)
-            .map_err(|e| CohortError::format(e))?;
-        self.check_for_duplicate_rows(cohort)?;
-        self.check_biocuration(cohort)?;
         Ok(())
     }
 
@@ -120,11 +138,12 @@ impl CohortDataQc {
     pub fn qc_conflicting_pairs(&self, cohort: &CohortData) -> Result<(), CohortError> {
         let conflicting_pairs = self.get_conflicting_termid_pairs(cohort)
         .map_err(|e| CohortError::format(e))?;
-        if conflicting_pairs.no_conflict() {
+        let result = if conflicting_pairs.no_conflict() {
             Ok(())
         } else {
             Err(CohortError::format(conflicting_pairs.report()))
-        }   
+        };
+        log_rule("conflicting_pairs", result)
     }
 
     /// Check that the TermId and labels are up to date. Fail on the first error.
@@ -132,8 +151,9 @@ impl CohortDataQc {
         for hpo_duplet in &cohort.hpo_headers {
             let hpo_term_id = TermId::from_str(&hpo_duplet.hpo_id)
                 .map_err(|e| e.to_string())?;
-            let term = self.hpo.term_by_id(&hpo_term_id)
-                    .ok_or_else(|| format!("Could not find HPO term for {}", hpo_term_id))?;
+            let term = self.hpo.term_by_id(&hpo_term_id);
+            tracing::debug!(hpo_id = %hpo_term_id, result = term.map(|t| t.name()), "HPO lookup");
+            let term = term.ok_or_else(|| format!("Could not find HPO term for {}", hpo_term_id))?;
             if term.identifier() != &hpo_term_id {
                 return Err(format!("{} is not the primary id ({}) for {}",
                     hpo_term_id, term.identifier(), hpo_duplet.hpo_label()));
@@ -148,6 +168,10 @@ impl CohortDataQc {
 
 
     pub fn check_metadata(&self, cohort: &CohortData) -> Result<(), CohortError> {
+        log_rule("metadata", self.check_diseases_and_moi(cohort))
+    }
+
+    fn check_diseases_and_moi(&self, cohort: &CohortData) -> Result<(), CohortError> {
         let diseases = &cohort.disease_list;
         if diseases.is_empty() {
             return Err(CohortError::format("Disease list empty"));
@@ -166,6 +190,68 @@ impl CohortDataQc {
     }
 
 
+    /// Collect all Q/C issues of the cohort. The checks of [`Self::qc_check`] and [`Self::check_metadata`] are errors.
+    /// Ontology redundancies (a term and its ancestor are both observed or both excluded) are warnings, because
+    /// [`Self::sanitize`] resolves them; an observed term with an excluded ancestor is an error that requires manual review.
+    pub fn qc_summary(&self, cohort: &CohortData) -> QcSummary {
+        let mut summary = QcSummary::new();
+        if let Err(e) = self.qc_check(cohort) {
+            summary.push(QcViolation::error(e.to_string()));
+        }
+        if let Err(e) = self.check_metadata(cohort) {
+            summary.push(QcViolation::error(e.to_string()));
+        }
+        for row in &cohort.rows {
+            match self.get_ontology_violations_for_row(row, &cohort.hpo_headers) {
+                Ok(violations) => summary.extend(violations),
+                Err(e) => summary.push(QcViolation::error(e)),
+            }
+        }
+        summary
+    }
+
+    fn get_ontology_violations_for_row(&self, row: &RowData, hpo_terms: &[HpoTermDuplet])
+    -> Result<Vec<QcViolation>, String> {
+        let individual_id = &row.individual_data.individual_id;
+        let mut observed: Vec<(TermId, &HpoTermDuplet)> = Vec::new();
+        let mut excluded: Vec<(TermId, &HpoTermDuplet)> = Vec::new();
+        for (header, val) in hpo_terms.iter().zip(&row.hpo_data) {
+            match &val.entry {
+                CellValueInner::Observed | CellValueInner::OnsetAge(_) => observed.push((header.to_term_id()?, header)),
+                CellValueInner::Excluded => excluded.push((header.to_term_id()?, header)),
+                CellValueInner::Na | CellValueInner::HpoFrequency(_) => {},
+            }
+        }
+        let mut violations = Vec::new();
+        for (tid1, duplet1) in &observed {
+            for (tid2, duplet2) in &observed {
+                if self.hpo.is_ancestor_of(tid1, tid2) {
+                    violations.push(QcViolation::warning(format!(
+                        "{individual_id}: observed {} ({}) is redundant with its observed descendant {} ({})",
+                        duplet1.hpo_label(), tid1, duplet2.hpo_label(), tid2)));
+                }
+            }
+            for (tid2, duplet2) in &excluded {
+                if self.hpo.is_ancestor_of(tid2, tid1) {
+                    violations.push(QcViolation::error(format!(
+                        "{individual_id}: {} ({}) is observed but its ancestor {} ({}) is excluded",
+                        duplet1.hpo_label(), tid1, duplet2.hpo_label(), tid2)));
+                }
+            }
+        }
+        for (tid1, duplet1) in &excluded {
+            for (tid2, duplet2) in &excluded {
+                if self.hpo.is_descendant_of(tid1, tid2) {
+                    violations.push(QcViolation::warning(format!(
+                        "{individual_id}: excluded {} ({}) is redundant with its excluded ancestor {} ({})",
+                        duplet1.hpo_label(), tid1, duplet2.hpo_label(), tid2)));
+                }
+            }
+        }
+        Ok(violations)
+    }
+
+
     pub fn sanitize_header(&self, duplets: &Vec<HpoTermDuplet>) -> Result<Vec<HpoTermDuplet>, String> {
         let mut sanitized: Vec<HpoTermDuplet> = Vec::new();
         for duplet in duplets {
@@ -201,6 +287,7 @@ impl CohortDataQc {
         // One more check!
         self.sanitize_curation_history(&mut cohort)?;
         self.qc_check(&cohort).map_err(|e|e.to_string())?;
+        debug_assert_eq!(Ok(()), cohort.validate_structural_integrity());
         Ok(cohort)
     }
 
@@ -263,8 +350,8 @@ impl CohortDataQc {
                     let tid = header.to_term_id()?;
                     excluded.push(tid);
                 },
-                crate::dto::hpo_term_dto::CellValueInner::Na => {},
-                crate::dto::hpo_term_dto::CellValueInner::OnsetAge(onset) => {
+                crate::dto::hpo_term_dto::CellValueInner::Na | crate::dto::hpo_term_dto::CellValueInner::HpoFrequency(_) => {},
+                crate::dto::hpo_term_dto::CellValueInner::OnsetAge(_) => {
                     let tid = header.to_term_id()?;
                     observed.push(tid);
                 },
@@ -312,7 +399,7 @@ impl CohortDataQc {
 #[cfg(test)]
 mod tests {
     use rstest::{fixture, rstest};
-    use crate::test_utils::fixtures::hpo;
+    use crate::test_utils::fixtures::{fbn1_cohort, hpo};
     use super::*;
 
 
@@ -349,5 +436,43 @@ mod tests {
         assert_eq!(duplets_with_outdated_hpo_id[2], sanitized[2]);
     }
 
+    /// Add Long fingers (HP:0100807), an ancestor of Arachnodactyly, with the same value in all rows
+    fn with_long_fingers(mut cohort: CohortData, value: &str) -> CohortData {
+        cohort.hpo_headers.push(HpoTermDuplet::new("Long fingers", "HP:0100807"));
+        for row in cohort.rows.iter_mut() {
+            row.hpo_data.push(value.parse().unwrap());
+        }
+        cohort
+    }
+
+    #[rstest]
+    fn test_qc_summary_without_conflicts(hpo: Arc<FullCsrOntology>, fbn1_cohort: CohortData) {
+        let summary = CohortDataQc::new(hpo).qc_summary(&fbn1_cohort);
+        // the fixture has no curation history
+        assert_eq!(vec![QcViolation::error("Format error: No curation recorded")], summary.violations());
+        assert!(!summary.has_warnings());
+    }
 
-}
\ No newline at end of file
+    #[rstest]
+    fn test_observed_ancestor_is_warning(hpo: Arc<FullCsrOntology>, fbn1_cohort: CohortData) {
+        let cohort = with_long_fingers(fbn1_cohort, "observed");
+        let summary = CohortDataQc::new(hpo).qc_summary(&cohort);
+        let warnings: Vec<&QcViolation> = summary.warnings().collect();
+        // Arachnodactyly is observed in all three individuals
+        assert_eq!(3, warnings.len());
+        assert_eq!("Individual 1: observed Long fingers (HP:0100807) is redundant with its observed descendant Arachnodactyly (HP:0001166)",
+            warnings[0].message);
+        assert_eq!(1, summary.errors().count());
+    }
+
+    #[rstest]
+    fn test_excluded_ancestor_is_error(hpo: Arc<FullCsrOntology>, fbn1_cohort: CohortData) {
+        let cohort = with_long_fingers(fbn1_cohort, "excluded");
+        let summary = CohortDataQc::new(hpo).qc_summary(&cohort);
+        assert!(!summary.has_warnings());
+        let errors: Vec<&QcViolation> = summary.errors().collect();
+        assert_eq!(4, errors.len());
+        assert_eq!("Individual 1: Arachnodactyly (HP:0001166) is observed but its ancestor Long fingers (HP:0100807) is excluded",
+            errors[1].message);
+    }
+}
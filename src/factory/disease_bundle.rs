@@ -115,7 +115,6 @@ mod test {
     #[case( "OMIM: 135100", "Contains stray whitespace: 'OMIM: 135100'")]
     #[case( "OMIM:13510", "OMIM identifiers must have 6 digits: 'OMIM:13510'")]
     fn test_malformed_disease_id(
-        disease_id: &str,
         disease_label: &str,
         #[case] entry: &str,
         #[case] expected_error_msg: &str) 
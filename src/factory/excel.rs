@@ -193,7 +193,6 @@ pub fn read_external_excel_to_dto(
     let headers = matrix[0].clone();
     let data_rows = &matrix[1..];
     let total_rows = data_rows.len();
-    let total_columns = headers.len();
     let mut columns: Vec<ColumnDto> = headers
         .iter()
         .map(|h| ColumnDto::new_raw(h, total_rows))
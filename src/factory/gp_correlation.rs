@@ -0,0 +1,214 @@
+//! Genotype-phenotype correlation
+//!
+//! For each variant that is carried by a sufficient number of individuals, and for each HPO term of the cohort,
+//! we compare the frequency of the term in carriers and non-carriers with Fisher's exact test.
+//! Only individuals in whom the term was explicitly observed or excluded are counted.
+//! The p-values are corrected for multiple testing with the Bonferroni method.
+use std::{collections::BTreeSet, fs::File, io::{BufWriter, Write}, path::Path};
+
+use serde::{Deserialize, Serialize};
+
+use crate::{dto::{cohort_dto::CohortData, hpo_term_dto::HpoTermDuplet}, factory::statistics};
+
+
+/// Corrected p-value threshold used to report significant correlations
+const SIGNIFICANCE_THRESHOLD: f64 = 0.05;
+
+
+/// Result of testing one variant against one HPO term
+#[derive(Clone, Debug, Deserialize, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct GpCorrelation {
+    pub variant_key: String,
+    pub hpo_term: HpoTermDuplet,
+    /// Carriers with the term observed
+    pub carrier_observed: usize,
+    /// Carriers with the term excluded
+    pub carrier_excluded: usize,
+    /// Non-carriers with the term observed
+    pub noncarrier_observed: usize,
+    /// Non-carriers with the term excluded
+    pub noncarrier_excluded: usize,
+    pub p_value_raw: f64,
+    /// Bonferroni-corrected p-value
+    pub p_value_corrected: f64,
+    pub odds_ratio: f64,
+}
+
+
+/// Test all variants carried by at least `min_carriers` individuals against all HPO terms.
+/// Returns all results, sorted by ascending raw p-value.
+pub fn compute_all_genotype_phenotype_correlations(
+    cohort: &CohortData,
+    min_carriers: usize
+) -> Vec<GpCorrelation> {
+    let variant_keys: BTreeSet<&str> = cohort.rows
+        .iter()
+        .flat_map(|row| row.allele_count_map
+            .iter()
+            .filter(|(_, count)| **count > 0)
+            .map(|(key, _)| key.as_str()))
+        .collect();
+    let mut correlations = Vec::new();
+    for variant_key in variant_keys {
+        let is_carrier: Vec<bool> = cohort.rows
+            .iter()
+            .map(|row| row.allele_count_map.get(variant_key).is_some_and(|c| *c > 0))
+            .collect();
+        let n_carriers = is_carrier.iter().filter(|c| **c).count();
+        if n_carriers < min_carriers {
+            continue;
+        }
+        for (idx, duplet) in cohort.hpo_headers.iter().enumerate() {
+            let (mut a, mut b, mut c, mut d) = (0, 0, 0, 0);
+            for (row, carrier) in cohort.rows.iter().zip(&is_carrier) {
+                let Some(cell) = row.hpo_data.get(idx) else {
+                    continue;
+                };
+                let observed = cell.is_present();
+                match (carrier, observed, cell.is_excluded()) {
+                    (true, true, _) => a += 1,
+                    (true, false, true) => b += 1,
+                    (false, true, _) => c += 1,
+                    (false, false, true) => d += 1,
+                    _ => {}
+                }
+            }
+            // there is nothing to test if nobody (or only one group) was assessed for this term
+            if a + b == 0 || c + d == 0 {
+                continue;
+            }
+            correlations.push(GpCorrelation {
+                variant_key: variant_key.to_string(),
+                hpo_term: duplet.clone(),
+                carrier_observed: a,
+                carrier_excluded: b,
+                noncarrier_observed: c,
+                noncarrier_excluded: d,
+                p_value_raw: statistics::fisher_exact_two_sided(a, b, c, d),
+                p_value_corrected: 1.0,
+                odds_ratio: statistics::odds_ratio(a, b, c, d),
+            });
+        }
+    }
+    let n_tests = correlations.len() as f64;
+    for corr in correlations.iter_mut() {
+        corr.p_value_corrected = (corr.p_value_raw * n_tests).min(1.0);
+    }
+    correlations.sort_by(|x, y| x.p_value_raw.total_cmp(&y.p_value_raw));
+    correlations
+}
+
+/// Genotype-phenotype correlations with a Bonferroni-corrected p-value below 0.05,
+/// sorted by ascending p-value. See [`compute_all_genotype_phenotype_correlations`].
+pub fn compute_genotype_phenotype_correlation(
+    cohort: &CohortData,
+    min_carriers: usize
+) -> Vec<GpCorrelation> {
+    compute_all_genotype_phenotype_correlations(cohort, min_carriers)
+        .into_iter()
+        .filter(|corr| corr.p_value_corrected < SIGNIFICANCE_THRESHOLD)
+        .collect()
+}
+
+/// Write the correlations to a tab-separated file (with header).
+pub fn export_gp_correlation_tsv(
+    correlations: &[GpCorrelation],
+    path: &Path
+) -> Result<(), String> {
+    let file = File::create(path).map_err(|e| e.to_string())?;
+    let mut writer = BufWriter::new(file);
+    let header = [
+        "variant", "hpo_id", "hpo_label",
+        "carrier_observed", "carrier_excluded", "noncarrier_observed", "noncarrier_excluded",
+        "odds_ratio", "p_value", "p_value_bonferroni",
+    ];
+    writeln!(writer, "{}", header.join("\t")).map_err(|e| e.to_string())?;
+    for corr in correlations {
+        writeln!(writer, "{}\t{}\t{}\t{}\t{}\t{}\t{}\t{:.3}\t{:.3e}\t{:.3e}",
+            corr.variant_key,
+            corr.hpo_term.hpo_id(),
+            corr.hpo_term.hpo_label(),
+            corr.carrier_observed,
+            corr.carrier_excluded,
+            corr.noncarrier_observed,
+            corr.noncarrier_excluded,
+            corr.odds_ratio,
+            corr.p_value_raw,
+            corr.p_value_corrected).map_err(|e| e.to_string())?;
+    }
+    writer.flush().map_err(|e| e.to_string())
+}
+
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use std::collections::HashMap;
+    use rstest::{fixture, rstest};
+    use crate::dto::hpo_term_dto::CellValue;
+    use crate::test_utils::fixtures::fbn1_cohort;
+
+    /// Twelve individuals. The six carriers of the HGVS variant have an atrial septal defect,
+    /// the six carriers of the deletion do not. Arachnodactyly is observed in everybody.
+    #[fixture]
+    fn gp_cohort(fbn1_cohort: CohortData) -> CohortData {
+        let mut cohort = fbn1_cohort.clone();
+        let hgvs_key = cohort.hgvs_variants.keys().next().unwrap().clone();
+        let sv_key = cohort.structural_variants.keys().next().unwrap().clone();
+        let template = cohort.rows[0].clone();
+        cohort.rows.clear();
+        for i in 0..12 {
+            let mut row = template.clone();
+            row.individual_data.individual_id = format!("Individual {}", i + 1);
+            let (key, asd) = if i < 6 { (&hgvs_key, "observed") } else { (&sv_key, "excluded") };
+            row.allele_count_map = HashMap::from([(key.clone(), 1)]);
+            row.hpo_data = ["observed", asd, "na", "na"]
+                .iter()
+                .map(|v| v.parse::<CellValue>().unwrap())
+                .collect();
+            cohort.rows.push(row);
+        }
+        cohort
+    }
+
+    #[rstest]
+    fn test_known_association(gp_cohort: CohortData) {
+        let correlations = compute_genotype_phenotype_correlation(&gp_cohort, 3);
+        // both variants are (inversely) associated with ASD
+        assert_eq!(2, correlations.len());
+        for corr in &correlations {
+            assert_eq!("HP:0001631", corr.hpo_term.hpo_id());
+            assert!((corr.p_value_raw - 0.002165).abs() < 1e-5);
+            assert!(corr.p_value_corrected < 0.05);
+        }
+        let hgvs_corr = correlations.iter().find(|c| c.carrier_observed == 6).unwrap();
+        assert!(hgvs_corr.odds_ratio > 1.0);
+    }
+
+    #[rstest]
+    fn test_bonferroni_and_min_carriers(gp_cohort: CohortData) {
+        let all = compute_all_genotype_phenotype_correlations(&gp_cohort, 3);
+        // two variants x two assessed terms
+        assert_eq!(4, all.len());
+        for corr in &all {
+            assert!((corr.p_value_corrected - (corr.p_value_raw * 4.0).min(1.0)).abs() < 1e-12);
+        }
+        assert!(compute_all_genotype_phenotype_correlations(&gp_cohort, 7).is_empty());
+    }
+
+    #[rstest]
+    fn test_export_tsv(gp_cohort: CohortData) {
+        let correlations = compute_genotype_phenotype_correlation(&gp_cohort, 3);
+        let path = std::env::temp_dir().join(format!("gp_correlation_test_{}.tsv", std::process::id()));
+        let written = export_gp_correlation_tsv(&correlations, &path);
+        let contents = std::fs::read_to_string(&path);
+        let _ = std::fs::remove_file(&path);
+        written.unwrap();
+        let contents = contents.unwrap();
+        let lines: Vec<&str> = contents.lines().collect();
+        assert_eq!(3, lines.len());
+        assert!(lines[0].starts_with("variant\thpo_id\thpo_label"));
+        assert!(lines[1].contains("HP:0001631\tAtrial septal defect"));
+    }
+}
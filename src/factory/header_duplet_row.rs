@@ -74,8 +74,8 @@ impl HeaderDupletRow {
         Self::qc_matrix_dimensions(matrix)?;
         // first Q/C the constant part of the Mendelian header
         let iheader = IndividualHeader::from_matrix(matrix, MENDELIAN_DEMOGRAPHIC_IDX)?;
-        let dheader = DiseaseHeader::from_matrix(matrix, MENDELIAN_DISEASE_IDX)?;
-        let gheader = GeneVariantHeader::from_matrix(matrix, MENDELIAN_GENE_VAR_IDX)?;
+        DiseaseHeader::from_matrix(matrix, MENDELIAN_DISEASE_IDX)?;
+        GeneVariantHeader::from_matrix(matrix, MENDELIAN_GENE_VAR_IDX)?;
         // If we get here, the constant part is OK and we can check the HPO columns
         let mut hpo_duplet_list: Vec<HpoTermDuplet> = Vec::new();
         let n = matrix[0].len(); // previously checked in qc_matrix_dimensions
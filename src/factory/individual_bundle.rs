@@ -180,7 +180,6 @@ mod test {
     #[case("PMID: 29482508", "Contains stray whitespace: 'PMID: 29482508'")]
     #[case("", "Empty CURIE")]
     fn test_malformed_pmid(
-        pmid: &str,
         title: &str,
         individual_id: &str,
         comment: &str,
@@ -204,7 +203,6 @@ mod test {
         "Consecutive whitespace in 'Difficult diagnosis and genetic analysis of fibrodysplasia  ossificans progressiva: a case report'")]
     fn test_malformed_title(
         pmid: &str,
-        title: &str,
         individual_id: &str,
         comment: &str,
         age_of_onset: &str,
@@ -228,7 +226,6 @@ mod test {
     fn test_malformed_individual(
         pmid: &str,
         title: &str,
-        individual_id: &str,
         comment: &str,
         age_of_onset: &str,
         age_at_last_encounter: &str,
@@ -252,7 +249,6 @@ mod test {
         title: &str,
         individual_id: &str,
         comment: &str,
-        age_of_onset: &str,
         age_at_last_encounter: &str,
         deceased: &str,
         sex: &str,
@@ -275,7 +271,6 @@ mod test {
         individual_id: &str,
         comment: &str,
         age_of_onset: &str,
-        age_at_last_encounter: &str,
         deceased: &str,
         sex: &str,
         #[case] entry: &str,
@@ -298,7 +293,6 @@ mod test {
         comment: &str,
         age_of_onset: &str,
         age_at_last_encounter: &str,
-        deceased: &str,
         sex: &str,
         #[case] entry: &str,
         #[case] expected_error_msg: &str) 
@@ -321,7 +315,6 @@ mod test {
         age_of_onset: &str,
         age_at_last_encounter: &str,
         deceased: &str,
-        sex: &str,
         #[case] entry: &str,
         #[case] expected_error_msg: &str) 
     {
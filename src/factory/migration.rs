@@ -0,0 +1,226 @@
+//! Migration of cohort files with legacy schema versions
+//!
+//! The first JSON cohort files (schema version 0.1, or no `phetoolsSchemaVersion` at all) stored the diseases and genes
+//! of the cohort in a single `diseaseGeneDto` object (`diseaseDtoList` and `geneTranscriptDtoList`), the individual of each
+//! row as `individualDto`, and the HPO cells as plain strings (e.g., `"observed"` or `"P3Y"`). Later versions use
+//! `diseaseList` (with the genes and modes of inheritance of each disease), `individualData`, and tagged cell values
+//! (e.g., `{"type": "Observed"}`). Files that already use the current structure (schema versions 0.2 and later) can be
+//! deserialized directly, because the fields that were added since then have default values.
+use serde_json::{Map, Value};
+
+use crate::dto::{cohort_dto::CohortData, hpo_term_dto::CellValue};
+
+
+/// Value of `key` (camelCase) or `snake_key` (snake_case); the latter was used by some early files
+fn get_field<'a>(obj: &'a Map<String, Value>, key: &str, snake_key: &str) -> Option<&'a Value> {
+    obj.get(key).or_else(|| obj.get(snake_key))
+}
+
+fn get_str(obj: &Map<String, Value>, key: &str, snake_key: &str) -> Option<String> {
+    get_field(obj, key, snake_key).and_then(Value::as_str).map(str::to_string)
+}
+
+fn get_array<'a>(obj: &'a Map<String, Value>, key: &str, snake_key: &str) -> &'a [Value] {
+    get_field(obj, key, snake_key)
+        .and_then(Value::as_array)
+        .map(Vec::as_slice)
+        .unwrap_or_default()
+}
+
+/// True if the cohort uses the legacy (v1) structure with a `diseaseGeneDto`
+fn is_legacy_cohort(cohort: &Value) -> bool {
+    cohort.get("diseaseGeneDto").is_some() || cohort.get("disease_gene_dto").is_some()
+}
+
+fn migrate_gene(gene: &Value) -> Result<Value, String> {
+    let obj = gene.as_object().ok_or_else(|| format!("Malformed legacy gene entry: {gene}"))?;
+    let field = |key: &str, snake_key: &str| get_str(obj, key, snake_key)
+        .ok_or_else(|| format!("Legacy gene entry lacks {key}: {gene}"));
+    Ok(serde_json::json!({
+        "hgncId": field("hgncId", "hgnc_id")?,
+        "geneSymbol": field("geneSymbol", "gene_symbol")?,
+        "transcript": field("transcript", "transcript")?,
+    }))
+}
+
+/// Legacy disease entries only have an id and a label; the genes of the cohort are assigned to each disease
+fn migrate_disease(disease: &Value, genes: &[Value]) -> Result<Value, String> {
+    let obj = disease.as_object().ok_or_else(|| format!("Malformed legacy disease entry: {disease}"))?;
+    let field = |key: &str, snake_key: &str| get_str(obj, key, snake_key)
+        .ok_or_else(|| format!("Legacy disease entry lacks {key}: {disease}"));
+    let moi_list = get_field(obj, "modeOfInheritanceList", "mode_of_inheritance_list")
+        .cloned()
+        .unwrap_or_else(|| Value::Array(vec![]));
+    Ok(serde_json::json!({
+        "diseaseId": field("diseaseId", "disease_id")?,
+        "diseaseLabel": field("diseaseLabel", "disease_label")?,
+        "modeOfInheritanceList": moi_list,
+        "geneTranscriptList": genes,
+    }))
+}
+
+/// Legacy cells are plain strings such as "observed", "na", or "P3Y"
+fn migrate_cell(cell: &Value) -> Result<Value, String> {
+    match cell {
+        Value::String(s) => {
+            let cell_value: CellValue = s.trim().parse()?;
+            serde_json::to_value(cell_value).map_err(|e| e.to_string())
+        }
+        Value::Object(_) => Ok(cell.clone()),
+        _ => Err(format!("Malformed legacy HPO cell: {cell}")),
+    }
+}
+
+fn migrate_row(row: &Value, default_disease_ids: &[String]) -> Result<Value, String> {
+    let obj = row.as_object().ok_or_else(|| format!("Malformed legacy row: {row}"))?;
+    let individual = get_field(obj, "individualData", "individual_data")
+        .or_else(|| get_field(obj, "individualDto", "individual_dto"))
+        .cloned()
+        .ok_or_else(|| "Legacy row lacks the individual".to_string())?;
+    let disease_id_list: Vec<String> = match get_field(obj, "diseaseIdList", "disease_id_list") {
+        Some(ids) => serde_json::from_value(ids.clone()).map_err(|e| e.to_string())?,
+        None => {
+            let ids: Vec<String> = get_array(obj, "diseaseDtoList", "disease_dto_list")
+                .iter()
+                .filter_map(|d| d.get("diseaseId").or_else(|| d.get("disease_id")).and_then(Value::as_str))
+                .map(str::to_string)
+                .collect();
+            if ids.is_empty() { default_disease_ids.to_vec() } else { ids }
+        }
+    };
+    let hpo_data = get_array(obj, "hpoData", "hpo_data")
+        .iter()
+        .map(migrate_cell)
+        .collect::<Result<Vec<Value>, String>>()?;
+    Ok(serde_json::json!({
+        "individualData": individual,
+        "diseaseIdList": disease_id_list,
+        "alleleCountMap": get_field(obj, "alleleCountMap", "allele_count_map").cloned().unwrap_or_else(|| Value::Object(Map::new())),
+        "hpoData": hpo_data,
+    }))
+}
+
+/// Migrate a cohort in the legacy (v1) structure, in which the diseases and genes are stored in a
+/// `diseaseGeneDto`, to the current [`CohortData`]. The cohort acronym is not part of the legacy files
+/// and must be set by the curator; the schema version is set to the current version.
+pub fn migrate_cohort_v1_to_v2(cohort: Value) -> Result<CohortData, String> {
+    let obj = cohort.as_object().ok_or_else(|| "Cohort JSON is not an object".to_string())?;
+    let dg = get_field(obj, "diseaseGeneDto", "disease_gene_dto")
+        .and_then(Value::as_object)
+        .ok_or_else(|| "Legacy cohort lacks the diseaseGeneDto".to_string())?;
+    let genes = get_array(dg, "geneTranscriptDtoList", "gene_transcript_dto_list")
+        .iter()
+        .map(migrate_gene)
+        .collect::<Result<Vec<Value>, String>>()?;
+    let diseases = get_array(dg, "diseaseDtoList", "disease_dto_list")
+        .iter()
+        .map(|d| migrate_disease(d, &genes))
+        .collect::<Result<Vec<Value>, String>>()?;
+    if diseases.is_empty() {
+        return Err("Legacy cohort has no diseases".to_string());
+    }
+    let disease_ids: Vec<String> = diseases
+        .iter()
+        .filter_map(|d| d["diseaseId"].as_str().map(str::to_string))
+        .collect();
+    let rows = get_array(obj, "rows", "rows")
+        .iter()
+        .map(|row| migrate_row(row, &disease_ids))
+        .collect::<Result<Vec<Value>, String>>()?;
+    let cohort_type = get_str(obj, "cohortType", "cohort_type")
+        .or_else(|| get_str(dg, "templateType", "template_type"))
+        .unwrap_or_else(|| "mendelian".to_string())
+        .to_ascii_lowercase();
+    let empty_map = || Value::Object(Map::new());
+    let migrated = serde_json::json!({
+        "cohortType": cohort_type,
+        "diseaseList": diseases,
+        "hpoHeaders": get_field(obj, "hpoHeaders", "hpo_headers").cloned().unwrap_or_else(|| Value::Array(vec![])),
+        "rows": rows,
+        "hgvsVariants": get_field(obj, "hgvsVariants", "hgvs_variants").cloned().unwrap_or_else(empty_map),
+        "structuralVariants": get_field(obj, "structuralVariants", "structural_variants").cloned().unwrap_or_else(empty_map),
+        "phetoolsSchemaVersion": CohortData::get_phetools_schema_version(),
+        "hpoVersion": get_str(obj, "hpoVersion", "hpo_version")
+            .ok_or_else(|| "Legacy cohort lacks the hpoVersion".to_string())?,
+        "cohortAcronym": get_field(obj, "cohortAcronym", "cohort_acronym").cloned().unwrap_or(Value::Null),
+        "curationHistory": get_field(obj, "curationHistory", "curation_history").cloned().unwrap_or_else(|| Value::Array(vec![])),
+    });
+    serde_json::from_value(migrated).map_err(|e| format!("Could not migrate legacy cohort: {e}"))
+}
+
+/// Parse a cohort of any schema version, applying the migrations needed for legacy versions
+pub fn migrate_cohort(cohort: Value) -> Result<CohortData, String> {
+    if is_legacy_cohort(&cohort) {
+        tracing::info!("Migrating legacy (v1) cohort to schema version {}", CohortData::get_phetools_schema_version());
+        return migrate_cohort_v1_to_v2(cohort);
+    }
+    let version = cohort.get("phetoolsSchemaVersion").and_then(Value::as_str).unwrap_or_default();
+    if is_newer_version(version, &CohortData::get_phetools_schema_version()) {
+        return Err(format!("Cohort schema version {version} is newer than the supported version {}",
+            CohortData::get_phetools_schema_version()));
+    }
+    serde_json::from_value(cohort).map_err(|e| e.to_string())
+}
+
+/// Compare dotted version strings such as "0.4" and "0.10" numerically
+pub(crate) fn is_newer_version(version: &str, current: &str) -> bool {
+    let parse = |v: &str| -> Vec<u32> { v.split('.').filter_map(|p| p.parse().ok()).collect() };
+    parse(version) > parse(current)
+}
+
+/// Load a JSON cohort file of any schema version (see [`migrate_cohort`])
+pub fn load_json_cohort_any_version(json_path: &str) -> Result<CohortData, String> {
+    let contents = std::fs::read_to_string(json_path)
+        .map_err(|e| format!("Could not read {json_path}: {e}"))?;
+    let cohort: Value = serde_json::from_str(&contents)
+        .map_err(|e| format!("Could not parse {json_path}: {e}"))?;
+    migrate_cohort(cohort)
+}
+
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use rstest::rstest;
+    use crate::dto::hpo_term_dto::CellValueInner;
+    use crate::test_utils::fixtures::fbn1_cohort;
+
+    #[rstest]
+    fn test_migrate_cells() {
+        assert_eq!(serde_json::json!({"type": "Observed", "modifiers": []}), migrate_cell(&Value::from("observed")).unwrap());
+        assert_eq!(serde_json::json!({"type": "OnsetAge", "data": "P3Y", "modifiers": []}), migrate_cell(&Value::from("P3Y")).unwrap());
+        assert!(migrate_cell(&Value::from("obsreved")).is_err());
+        assert!(migrate_cell(&Value::from(1)).is_err());
+    }
+
+    #[rstest]
+    fn test_current_version_is_not_migrated(fbn1_cohort: CohortData) {
+        let value = serde_json::to_value(&fbn1_cohort).unwrap();
+        let cohort = migrate_cohort(value).unwrap();
+        assert_eq!(fbn1_cohort.rows.len(), cohort.rows.len());
+        assert_eq!(fbn1_cohort.cohort_acronym, cohort.cohort_acronym);
+    }
+
+    #[rstest]
+    fn test_missing_acronym(fbn1_cohort: CohortData) {
+        let mut value = serde_json::to_value(&fbn1_cohort).unwrap();
+        value.as_object_mut().unwrap().remove("cohortAcronym");
+        value["phetoolsSchemaVersion"] = Value::from("0.2");
+        let cohort = migrate_cohort(value).unwrap();
+        assert_eq!(None, cohort.cohort_acronym);
+        assert_eq!(CellValueInner::Observed, cohort.rows[0].hpo_data[0].entry);
+    }
+
+    #[rstest]
+    fn test_newer_version_is_rejected(fbn1_cohort: CohortData) {
+        let mut value = serde_json::to_value(&fbn1_cohort).unwrap();
+        value["phetoolsSchemaVersion"] = Value::from("0.10");
+        assert!(migrate_cohort(value).is_err());
+    }
+
+    #[rstest]
+    fn test_legacy_cohort_without_diseases() {
+        let value = serde_json::json!({"diseaseGeneDto": {"diseaseDtoList": []}, "hpoVersion": "2024-04-26"});
+        assert!(migrate_cohort(value).is_err());
+    }
+}
@@ -2,9 +2,9 @@
 //! 
 
 
-use std::{fmt, sync::Arc};
+use std::{collections::{BTreeMap, HashSet}, fmt, sync::Arc};
 use ontolius::ontology::csr::FullCsrOntology;
-use crate::{dto::{cohort_dto::{CohortData, CohortType, DiseaseData, IndividualData}, etl_dto::ColumnTableDto, hpo_term_dto::HpoTermData}, factory::{cohort_factory::CohortFactory, cohort_qc::CohortDataQc}};
+use crate::{dto::{cohort_dto::{CohortData, CohortType, DiseaseData, IndividualData, TrioData}, etl_dto::ColumnTableDto, hpo_term_dto::HpoTermData, validation_errors::{QcSummary, QcViolation}}, error::PheToolsError, factory::{cohort_factory::CohortFactory, cohort_qc::CohortDataQc}, hpo::TermStatus};
 
 pub(crate) mod disease_bundle;
 pub mod excel;
@@ -13,6 +13,14 @@ pub mod header_duplet_row;
 pub(crate) mod individual_bundle;
 pub mod cohort_factory;
 mod cohort_qc;
+pub mod gp_correlation;
+pub mod migration;
+pub mod penetrance;
+pub(crate) mod statistics;
+
+pub use migration::load_json_cohort_any_version;
+pub use gp_correlation::{compute_genotype_phenotype_correlation, export_gp_correlation_tsv, GpCorrelation};
+pub use penetrance::{estimate_all_penetrances, estimate_penetrance, PenetranceEstimate};
 
 #[derive(serde::Serialize, Debug)]
 #[serde(tag = "type", content = "data")]
@@ -75,8 +83,9 @@ impl fmt::Display for CohortError {
 ///
 /// # Returns
 /// * `Ok(String)` containing the template filename (generated in the front end)
-/// * `Err(String)` if:
-///   - the cohort acronym is missing.
+/// * `Err(PheToolsError::InvalidCohort)` if:
+///   - the cohort acronym is missing (the message suggests an acronym inferred with [`infer_cohort_acronym`]), or
+///   - the cohort acronym is not valid (see [`validate_cohort_acronym`]).
 ///
 /// # Errors
 /// This function returns an error with a descriptive message when the filename
@@ -87,22 +96,71 @@ impl fmt::Display for CohortError {
 /// let filename = extract_template_name(&cohort_data)?;
 /// // e.g., "ACVR1_FOP_individuals.json"
 /// ```
-pub fn extract_template_name(cohort_dto: &CohortData) -> Result<String, String> {
+pub fn extract_template_name(cohort_dto: &CohortData) -> Result<String, PheToolsError> {
     let acronym = match &cohort_dto.cohort_acronym {
         Some(name) => name.to_string(),
-        None => {return Err("No cohort acronym found".to_string());}
+        None => {
+            let message = match infer_cohort_acronym(cohort_dto) {
+                Some(inferred) => format!("No cohort acronym found (suggested: {inferred})"),
+                None => "No cohort acronym found".to_string(),
+            };
+            return Err(PheToolsError::InvalidCohort(message));
+        }
     };
-
+    validate_cohort_acronym(&acronym).map_err(PheToolsError::InvalidCohort)?;
     Ok(format!("{}_individuals.json", acronym))
 }
 
+/// Words of disease labels that do not contribute to an acronym
+const ACRONYM_STOP_WORDS: [&str; 14] = [
+    "a", "an", "the", "and", "or", "of", "in", "on", "at", "to", "for", "with", "by", "due",
+];
+
+/// Derive an acronym from the label of the (first) disease of the cohort: the first letters of the major words
+/// (e.g., "Fibrodysplasia ossificans progressiva" → "FOP"); words that start with a digit are kept in full, so that
+/// "Loeys-Dietz syndrome 1" becomes "LDS1". Returns None if the cohort has no disease or if the result
+/// is not a valid acronym (see [`validate_cohort_acronym`]), e.g., for one-word labels.
+pub fn infer_cohort_acronym(cohort: &CohortData) -> Option<String> {
+    let label = &cohort.disease_list.first()?.disease_label;
+    let acronym: String = label
+        .split(|c: char| !c.is_alphanumeric())
+        .filter(|word| !word.is_empty())
+        .filter(|word| !ACRONYM_STOP_WORDS.contains(&word.to_lowercase().as_str()))
+        .map(|word| {
+            if word.starts_with(|c: char| c.is_ascii_digit()) {
+                word.to_uppercase()
+            } else {
+                word.chars().next().map(|c| c.to_uppercase().to_string()).unwrap_or_default()
+            }
+        })
+        .collect();
+    validate_cohort_acronym(&acronym).ok().map(|_| acronym)
+}
+
+/// Check that a cohort acronym can be used in file names: 2–12 characters, only ASCII letters, digits,
+/// and underscores, and no leading or trailing underscore.
+pub fn validate_cohort_acronym(acronym: &str) -> Result<(), String> {
+    let n_chars = acronym.chars().count();
+    if !(2..=12).contains(&n_chars) {
+        return Err(format!("Cohort acronym '{acronym}' must have 2 to 12 characters (found {n_chars})"));
+    }
+    if let Some(c) = acronym.chars().find(|c| !c.is_ascii_alphanumeric() && *c != '_') {
+        return Err(format!("Cohort acronym '{acronym}' contains the invalid character '{c}'"));
+    }
+    if acronym.starts_with('_') || acronym.ends_with('_') {
+        return Err(format!("Cohort acronym '{acronym}' must not start or end with an underscore"));
+    }
+    Ok(())
+}
+
 
 /// For melded cohorts, we will name them according to the involved
 /// genes. For instance, if we have ["TP53", "BRCA2", "BRCA1"], then 
 /// the cohort name will be "BRCA1-BRCA2-TP53" (alphabetical).
-pub fn melded_cohort_name(cohort_data: &CohortData) -> Result<String, String> {
+pub fn melded_cohort_name(cohort_data: &CohortData) -> Result<String, PheToolsError> {
     if cohort_data.disease_list.len() < 2 {
-        return Err(format!("Insufficient disease count ({}) for melded cohort", cohort_data.disease_list.len()));
+        return Err(PheToolsError::InvalidCohort(
+            format!("Insufficient disease count ({}) for melded cohort", cohort_data.disease_list.len())));
     };
     let mut genes = cohort_data
         .disease_list
@@ -143,16 +201,201 @@ pub fn sort_rows(
 }
 
 
+/// Check that no two rows have the same `individual_id`.
+/// Returns an error listing every duplicated identifier (in alphabetical order).
+pub fn validate_ids_unique(cohort: &CohortData) -> Result<(), PheToolsError> {
+    duplicate_ids(cohort, |id| id.to_string())
+}
+
+/// Same as [`validate_ids_unique`], but identifiers that differ only in case (e.g., "P1" and "p1")
+/// are also regarded as duplicates.
+pub fn validate_ids_unique_case_insensitive(cohort: &CohortData) -> Result<(), PheToolsError> {
+    duplicate_ids(cohort, |id| id.to_lowercase())
+}
+
+fn duplicate_ids<F>(cohort: &CohortData, normalize: F) -> Result<(), PheToolsError>
+where F: Fn(&str) -> String {
+    let mut id_map: BTreeMap<String, Vec<&str>> = BTreeMap::new();
+    for row in cohort.iter_rows() {
+        let id = row.individual_data.individual_id.as_str();
+        id_map.entry(normalize(id)).or_default().push(id);
+    }
+    let duplicates: Vec<String> = id_map
+        .values()
+        .filter(|ids| ids.len() > 1)
+        .map(|ids| format!("{} ({}x)", ids.join("/"), ids.len()))
+        .collect();
+    if duplicates.is_empty() {
+        Ok(())
+    } else {
+        Err(PheToolsError::InvalidCohort(format!("Duplicate individual ids: {}", duplicates.join(", "))))
+    }
+}
+
+
+/// Map common spellings of the sex of an individual to the canonical codes M, F, O, and U.
+/// Returns None if the string is not recognized.
+pub fn canonicalize_sex_code(s: &str) -> Option<&'static str> {
+    match s.trim().to_ascii_lowercase().as_str() {
+        "m" | "male" | "1" => Some("M"),
+        "f" | "female" | "2" => Some("F"),
+        "o" | "other" => Some("O"),
+        "u" | "unknown" | "" => Some("U"),
+        _ => None,
+    }
+}
+
+/// Replace non-canonical sex codes (e.g., "male" or "1") by the canonical codes (M, F, O, U)
+/// that are expected by the phenopacket export. Unrecognized values are left unchanged.
+pub fn normalize_sex_codes(mut cohort: CohortData) -> CohortData {
+    for row in cohort.iter_rows_mut() {
+        let individual = &mut row.individual_data;
+        if let Some(code) = canonicalize_sex_code(&individual.sex) {
+            if individual.sex != code {
+                tracing::warn!("Normalized sex '{}' to '{}' for {}", individual.sex, code, individual.individual_id);
+                individual.sex = code.to_string();
+            }
+        }
+    }
+    cohort
+}
+
+
+/// Identify de novo variants, i.e., alleles of the proband of a trio that neither parent has.
+/// Returns pairs of proband id and variant key, in the order of the trios and sorted by variant key.
+/// A parent that is not an individual of the cohort must be given by his or her alleles in the [`TrioData`].
+pub fn detect_de_novo_variants(cohort: &CohortData) -> Result<Vec<(String, String)>, PheToolsError> {
+    let mut de_novo = Vec::new();
+    for trio in &cohort.trios {
+        let proband = cohort.get_individual_by_id(&trio.proband_id)
+            .ok_or_else(|| PheToolsError::InvalidCohort(format!("Proband '{}' not found in cohort", trio.proband_id)))?;
+        let father_alleles = parental_alleles(cohort, trio, &trio.father_id, &trio.father_alleles)?;
+        let mother_alleles = parental_alleles(cohort, trio, &trio.mother_id, &trio.mother_alleles)?;
+        let mut proband_alleles: Vec<&String> = proband.allele_count_map.keys().collect();
+        proband_alleles.sort();
+        for allele in proband_alleles {
+            if !father_alleles.contains(allele.as_str()) && !mother_alleles.contains(allele.as_str()) {
+                de_novo.push((trio.proband_id.clone(), allele.clone()));
+            }
+        }
+    }
+    Ok(de_novo)
+}
+
+fn parental_alleles<'a>(
+    cohort: &'a CohortData,
+    trio: &TrioData,
+    parent_id: &str,
+    alleles: &'a Option<Vec<String>>
+) -> Result<HashSet<&'a str>, PheToolsError> {
+    if let Some(row) = cohort.get_individual_by_id(parent_id) {
+        return Ok(row.allele_count_map.keys().map(String::as_str).collect());
+    }
+    alleles
+        .as_ref()
+        .map(|alleles| alleles.iter().map(String::as_str).collect())
+        .ok_or_else(|| PheToolsError::InvalidCohort(
+            format!("Parent '{}' of proband '{}' not found in cohort", parent_id, trio.proband_id)))
+}
+
+
 pub fn qc_assessment(
     hpo: Arc<FullCsrOntology>,
     cohort_dto: &CohortData)
 -> Result<(), CohortError> {
+    validate_ids_unique(cohort_dto).map_err(CohortError::format)?;
     let cohort_qc = CohortDataQc::new(hpo);
     cohort_qc.qc_check(cohort_dto)?;
     cohort_qc.check_metadata(cohort_dto)?;
     cohort_qc.qc_conflicting_pairs(cohort_dto)
 }
 
+/// All Q/C issues of the cohort, classified as errors (manual review required) or
+/// warnings (fixed by [`sanitize_cohort_data`])
+pub fn qc_summary(
+    hpo: Arc<FullCsrOntology>,
+    cohort_dto: &CohortData
+) -> QcSummary {
+    let mut summary = QcSummary::new();
+    if let Err(e) = validate_ids_unique(cohort_dto) {
+        summary.push(QcViolation::error(CohortError::format(e.to_string()).to_string()));
+    }
+    summary.extend(CohortDataQc::new(hpo).qc_summary(cohort_dto).violations());
+    summary
+}
+
+/// All Q/C issues of the cohort (see [`qc_summary`]) together with the checks that are otherwise only applied when
+/// terms are added or phenopackets are exported: HPO columns with obsolete or deleted ids, invalid modifiers, and
+/// individual ids that differ only in case (a warning, because some file systems do not distinguish them).
+///
+/// Use [`QcSummary::is_export_ready`] to decide whether phenopackets can be exported.
+pub fn run_full_qc(
+    hpo: Arc<FullCsrOntology>,
+    cohort: &CohortData
+) -> QcSummary {
+    let mut summary = qc_summary(hpo.clone(), cohort);
+    summary.n_individuals = cohort.rows.len();
+    if validate_ids_unique(cohort).is_ok() {
+        if let Err(e) = validate_ids_unique_case_insensitive(cohort) {
+            summary.push(QcViolation::warning(e.to_string()));
+        }
+    }
+    for duplet in &cohort.hpo_headers {
+        match crate::hpo::get_term_status(hpo.clone(), duplet.hpo_id()) {
+            TermStatus::Current => {},
+            TermStatus::Replaced(tid) => summary.push(QcViolation::warning(format!(
+                "{} ({}) is obsolete; use {tid}", duplet.hpo_label(), duplet.hpo_id()))),
+            TermStatus::Deleted => summary.push(QcViolation::error(format!(
+                "{} ({}) is not in the current HPO", duplet.hpo_label(), duplet.hpo_id()))),
+        }
+    }
+    for row in &cohort.rows {
+        for (duplet, cell) in cohort.hpo_headers.iter().zip(&row.hpo_data) {
+            if cell.modifiers.is_empty() {
+                continue;
+            }
+            let Ok(data) = HpoTermData::new(duplet.clone(), cell.clone()) else {
+                continue;
+            };
+            if let Err(errors) = crate::hpo::validate_modifier_ids(hpo.clone(), &data) {
+                for e in errors {
+                    summary.push(QcViolation::error(format!("{}: {e}", row.individual_data.individual_id)));
+                }
+            }
+        }
+    }
+    summary
+}
+
+/// Outcome of the Q/C of one cohort (see [`qc_summary`]), in a form that can be serialized (e.g., by `phetools validate`).
+/// The cohort passes if there are no errors; warnings are fixed by [`sanitize_cohort_data`].
+#[derive(Clone, Debug, serde::Deserialize, serde::Serialize, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct QcReport {
+    /// Name of the cohort (typically, the file name)
+    pub cohort: String,
+    pub passed: bool,
+    /// Description of the first Q/C error, if any
+    pub error: Option<String>,
+    /// All Q/C issues with their severity (see [`qc_summary`])
+    #[serde(default)]
+    pub violations: Vec<QcViolation>,
+}
+
+pub fn qc_report(
+    hpo: Arc<FullCsrOntology>,
+    cohort_dto: &CohortData,
+    cohort_name: &str
+) -> QcReport {
+    let summary = qc_summary(hpo, cohort_dto);
+    QcReport {
+        cohort: cohort_name.to_string(),
+        passed: !summary.has_errors(),
+        error: summary.errors().next().map(|e| e.message.clone()),
+        violations: summary.violations(),
+    }
+}
+
 /// Sanitizes and validates cohort data using HPO ontology validation rules.
 ///
 /// This function attempts to clean the provided cohort data by applying
@@ -187,9 +430,9 @@ pub fn qc_assessment(
 pub fn sanitize_cohort_data(
     hpo: Arc<FullCsrOntology>,
     cohort_dto: &CohortData)
--> Result<CohortData, String> {
+-> Result<CohortData, PheToolsError> {
     let cohort_qc = CohortDataQc::new(hpo);
-    cohort_qc.sanitize(cohort_dto)
+    cohort_qc.sanitize(cohort_dto).map_err(PheToolsError::InvalidCohort)
 }
 
 
@@ -214,16 +457,17 @@ pub fn sanitize_cohort_data(
 /// # Returns
 ///
 /// * `Ok(CohortData)` - The updated cohort, if successful.  
-/// * `Err(String)` - An error message if the operation fails (e.g., unsupported cohort type).  
+/// * `Err(PheToolsError)` - An error if the operation fails (e.g., unsupported cohort type).  
 pub fn add_new_row_to_cohort(
     hpo: Arc<FullCsrOntology>,
     individual_data: IndividualData, 
     hpo_annotations: Vec<HpoTermData>,
     variant_key_list: Vec<String>,
     cohort_data: CohortData) 
--> Result<CohortData, String> {
+-> Result<CohortData, PheToolsError> {
     let mut builder = CohortFactory::new(hpo);
     builder.add_new_row_to_cohort(individual_data, hpo_annotations, variant_key_list, cohort_data)
+        .map_err(PheToolsError::InvalidCohort)
 }
 
 /// Reads an **external Excel file** for ETL purposes and converts it into
@@ -255,14 +499,14 @@ pub fn add_new_row_to_cohort(
 /// - `columns`: vector of `ColumnDto`, each representing a column with header and values.
 ///
 /// # Errors
-/// Returns `Err(String)` if:
+/// Returns `Err(PheToolsError::EtlTransformFailed)` if:
 /// - The file cannot be opened or read (from `get_list_of_rows_from_excel`)
 /// - The file has fewer than 3 rows
 pub fn read_external_excel_file(
     file_path: &str, 
     row_based: bool
-) -> Result<ColumnTableDto, String> {
-    excel::read_external_excel_to_dto(file_path, row_based)
+) -> Result<ColumnTableDto, PheToolsError> {
+    excel::read_external_excel_to_dto(file_path, row_based).map_err(PheToolsError::EtlTransformFailed)
 }
 
 
@@ -287,7 +531,7 @@ pub fn read_external_excel_file(
 ///
 /// # Errors
 ///
-/// Returns an `Err(String)` if the Excel file cannot be read or if the template format is invalid.
+/// Returns an `Err(PheToolsError::InvalidCohort)` if the Excel file cannot be read or if the template format is invalid.
 ///
 /// # Example
 ///
@@ -304,23 +548,26 @@ pub fn load_pyphetools_excel_template<F>(
     update_hpo_labels: bool,
     hpo: Arc<FullCsrOntology>,
     progress_cb: F
-) -> Result<CohortData, String> 
+) -> Result<CohortData, PheToolsError> 
 where F: FnMut(u32,u32) {
-    let matrix = excel::read_excel_to_dataframe( phetools_template_path)?;
+    let matrix = excel::read_excel_to_dataframe( phetools_template_path)
+        .map_err(PheToolsError::InvalidCohort)?;
     CohortFactory::dto_from_mendelian_template(matrix, hpo.clone(), update_hpo_labels, progress_cb)
+        .map_err(PheToolsError::InvalidCohort)
 }
 
 
  /// Load JSON serialization of a cohort.
+ ///
+ /// Returns [`PheToolsError::IoError`] (with the path in the message) if the file cannot be read
+ /// and [`PheToolsError::SerdeError`] if it is not a valid cohort. Use [`load_json_cohort_any_version`]
+ /// for files with a legacy schema version.
 pub fn load_json_cohort(
     json_template_path: &str
-) -> Result<CohortData, String> {
+) -> Result<CohortData, PheToolsError> {
     let file_data = std::fs::read_to_string(json_template_path)
-        .map_err(|e| 
-            format!("Could not extract string data from {}: {}", json_template_path, e.to_string()))?;
-    let cohort: CohortData = serde_json::from_str(&file_data)
-        .map_err(|e| format!("Could not transform string {} to CohortDto: {}",
-            file_data, e.to_string()))?;
+        .map_err(|e| std::io::Error::new(e.kind(), format!("Could not read {json_template_path}: {e}")))?;
+    let cohort: CohortData = serde_json::from_str(&file_data)?;
     Ok(cohort)
 }
 
@@ -335,18 +582,24 @@ pub fn load_json_cohort(
     ///
     /// # Returns
     ///
-    /// ``Ok(())`` if successful, otherwise ``Err(String)``
+    /// ``Ok(())`` if successful, otherwise ``Err(PheToolsError)``
     /// # Notes
     /// 
-    /// The method returns an error if an attempt is made to add an existing HPO term. The method rearranged terms in DFS order
+    /// The method returns an error if an attempt is made to add an existing HPO term
+    /// ([`PheToolsError::HpoTermNotFound`] if the term is not in the current HPO). The method rearranged terms in DFS order
     pub fn add_hpo_term_to_cohort(
         hpo_id: &str,
         hpo_label: &str,
         hpo: Arc<FullCsrOntology>,
         cohort_dto: CohortData) 
-    -> std::result::Result<CohortData, String> {
+    -> std::result::Result<CohortData, PheToolsError> {
+        if crate::hpo::get_term_status(hpo.clone(), hpo_id) == TermStatus::Deleted {
+            return Err(PheToolsError::HpoTermNotFound(
+                format!("Cannot add {hpo_id} ({hpo_label}) because it is not in the current HPO")));
+        }
         let mut builder = CohortFactory::new(hpo.clone());
-        let newcohort = builder.add_hpo_term_to_cohort(hpo_id, hpo_label, cohort_dto)?;
+        let newcohort = builder.add_hpo_term_to_cohort(hpo_id, hpo_label, cohort_dto)
+            .map_err(PheToolsError::InvalidCohort)?;
         Ok(newcohort)
     }
 
@@ -372,23 +625,24 @@ pub fn load_json_cohort(
 ///
 /// * `Ok(CohortData)` - A new cohort data object ready for adding phenopacket 
 ///   information.
-/// * `Err(String)` - An error message if template generation fails or if 
+/// * `Err(PheToolsError::InvalidCohort)` - An error if template generation fails or if 
 ///   the requested `template_type` is not supported.
 pub fn create_new_cohort_data(
     template_type: CohortType,
     disease_data: DiseaseData,
     acronym: String,
     hpo: Arc<FullCsrOntology>,
-) -> std::result::Result<CohortData, String> {
+) -> std::result::Result<CohortData, PheToolsError> {
     if template_type != CohortType::Mendelian {
-        return Err(format!("CohortData generation not supported for {:?} cohorts", template_type));
+        return Err(PheToolsError::InvalidCohort(format!("CohortData generation not supported for {:?} cohorts", template_type)));
     }
     let mut cohort_dto = CohortFactory::create_pyphetools_template(
         template_type, 
         disease_data,
         hpo.clone()
-    ).map_err(|e| e.to_string())?;
+    ).map_err(PheToolsError::InvalidCohort)?;
     cohort_dto.cohort_acronym = Some(acronym);
+    debug_assert_eq!(Ok(()), cohort_dto.validate_structural_integrity());
     Ok(cohort_dto)
 }
 
@@ -412,8 +666,164 @@ pub fn merge_cohort_data_from_etl_dto(
     previous: CohortData,
     transformed: CohortData,
     hpo: Arc<FullCsrOntology>,
-) -> Result<CohortData, String> {
+) -> Result<CohortData, PheToolsError> {
     let factory = CohortFactory::new(hpo);
-    CohortFactory::disease_data_identity_validation(&previous, &transformed)?;
-    factory.merge_cohort_data(previous, transformed)
-}
\ No newline at end of file
+    CohortFactory::disease_data_identity_validation(&previous, &transformed)
+        .map_err(PheToolsError::InvalidCohort)?;
+    factory.merge_cohort_data(previous, transformed).map_err(PheToolsError::InvalidCohort)
+}
+
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use rstest::rstest;
+    use crate::dto::cohort_dto::CurationEvent;
+    use crate::test_utils::fixtures::{fbn1_cohort, hpo};
+
+    #[rstest]
+    #[case("M", "M")]
+    #[case("Male", "M")]
+    #[case("male", "M")]
+    #[case("m", "M")]
+    #[case("1", "M")]
+    #[case("F", "F")]
+    #[case("Female", "F")]
+    #[case("female", "F")]
+    #[case("f", "F")]
+    #[case("2", "F")]
+    #[case("O", "O")]
+    #[case("other", "O")]
+    #[case("Other", "O")]
+    #[case("U", "U")]
+    #[case("unknown", "U")]
+    #[case("", "U")]
+    #[case(" male ", "M")]
+    fn test_canonicalize_sex_code(#[case] input: &str, #[case] expected: &str) {
+        assert_eq!(Some(expected), canonicalize_sex_code(input));
+    }
+
+    #[rstest]
+    #[case("3")]
+    #[case("mal")]
+    #[case("na")]
+    fn test_unrecognized_sex_code(#[case] input: &str) {
+        assert_eq!(None, canonicalize_sex_code(input));
+    }
+
+    #[rstest]
+    fn test_normalize_sex_codes(mut fbn1_cohort: CohortData) {
+        fbn1_cohort.rows[0].individual_data.sex = "male".to_string();
+        fbn1_cohort.rows[1].individual_data.sex = "2".to_string();
+        fbn1_cohort.rows[2].individual_data.sex = "?".to_string();
+        let cohort = normalize_sex_codes(fbn1_cohort);
+        let sexes: Vec<&str> = cohort.iter_rows().map(|row| row.individual_data.sex.as_str()).collect();
+        assert_eq!(vec!["M", "F", "?"], sexes);
+    }
+
+    #[rstest]
+    fn test_detect_de_novo_variants(mut fbn1_cohort: CohortData) {
+        let hgvs_key = fbn1_cohort.rows[0].allele_count_map.keys().next().unwrap().clone();
+        let sv_key = fbn1_cohort.rows[2].allele_count_map.keys().next().unwrap().clone();
+        // the variant of Individual 1 was inherited from Individual 2
+        fbn1_cohort.trios.push(TrioData::new("Individual 1", "Individual 2", "Individual 3"));
+        // the deletion of Individual 3 is not found in either parent
+        fbn1_cohort.trios.push(TrioData::new("Individual 3", "Individual 1", "Individual 2"));
+        let mut trio = TrioData::new("Individual 2", "Father 2", "Mother 2");
+        trio.father_alleles = Some(vec![]);
+        trio.mother_alleles = Some(vec![sv_key.clone()]);
+        fbn1_cohort.trios.push(trio);
+        let de_novo = detect_de_novo_variants(&fbn1_cohort).unwrap();
+        assert_eq!(vec![
+            ("Individual 3".to_string(), sv_key),
+            ("Individual 2".to_string(), hgvs_key),
+        ], de_novo);
+    }
+
+    #[rstest]
+    #[case::unknown_proband(TrioData::new("Individual 4", "Individual 1", "Individual 2"))]
+    #[case::unknown_parent(TrioData::new("Individual 3", "Individual 1", "Mother 3"))]
+    fn test_detect_de_novo_variants_unknown_individual(mut fbn1_cohort: CohortData, #[case] trio: TrioData) {
+        fbn1_cohort.trios.push(trio);
+        assert!(detect_de_novo_variants(&fbn1_cohort).is_err());
+    }
+
+    #[rstest]
+    fn test_unique_ids(fbn1_cohort: CohortData) {
+        assert!(validate_ids_unique(&fbn1_cohort).is_ok());
+        assert!(validate_ids_unique_case_insensitive(&fbn1_cohort).is_ok());
+    }
+
+    #[rstest]
+    fn test_duplicate_id(mut fbn1_cohort: CohortData, hpo: Arc<FullCsrOntology>) {
+        fbn1_cohort.rows[2].individual_data.individual_id = "Individual 1".to_string();
+        let err = validate_ids_unique(&fbn1_cohort).unwrap_err();
+        assert_eq!("Duplicate individual ids: Individual 1/Individual 1 (2x)", err.to_string());
+        let err = qc_assessment(hpo, &fbn1_cohort).unwrap_err();
+        assert_eq!("Format error: Duplicate individual ids: Individual 1/Individual 1 (2x)", err.to_string());
+    }
+
+    #[rstest]
+    fn test_duplicate_id_case_insensitive(mut fbn1_cohort: CohortData) {
+        fbn1_cohort.rows[2].individual_data.individual_id = "individual 1".to_string();
+        assert!(validate_ids_unique(&fbn1_cohort).is_ok());
+        let err = validate_ids_unique_case_insensitive(&fbn1_cohort).unwrap_err();
+        assert!(matches!(err, PheToolsError::InvalidCohort(_)));
+        assert_eq!("Duplicate individual ids: Individual 1/individual 1 (2x)", err.to_string());
+    }
+
+    #[rstest]
+    #[case("Fibrodysplasia ossificans progressiva", Some("FOP"))]
+    #[case("Marfan syndrome", Some("MS"))]
+    #[case("Loeys-Dietz syndrome 1", Some("LDS1"))]
+    #[case("Neurodevelopmental disorder with variable familial hypercholanemia", Some("NDVFH"))]
+    #[case("Ehlers-Danlos syndrome, classic type, 1", Some("EDSCT1"))]
+    #[case("Deafness, autosomal recessive 1A", Some("DAR1A"))]
+    #[case("Achondroplasia", None)]
+    fn test_infer_cohort_acronym(mut fbn1_cohort: CohortData, #[case] label: &str, #[case] expected: Option<&str>) {
+        fbn1_cohort.disease_list[0].disease_label = label.to_string();
+        assert_eq!(expected.map(str::to_string), infer_cohort_acronym(&fbn1_cohort));
+    }
+
+    #[rstest]
+    #[case("MFS", true)]
+    #[case("LDS_1", true)]
+    #[case("FBN1_MFS2025", true)]
+    #[case("M", false)]
+    #[case("ABCDEFGHIJKLM", false)]
+    #[case("LDS-1", false)]
+    #[case("LDS 1", false)]
+    #[case("_MFS", false)]
+    #[case("MFS_", false)]
+    fn test_validate_cohort_acronym(#[case] acronym: &str, #[case] is_valid: bool) {
+        assert_eq!(is_valid, validate_cohort_acronym(acronym).is_ok());
+    }
+
+    #[rstest]
+    fn test_extract_template_name(mut fbn1_cohort: CohortData) {
+        assert_eq!("MFS_individuals.json", extract_template_name(&fbn1_cohort).unwrap());
+        fbn1_cohort.cohort_acronym = Some("MFS-1".to_string());
+        assert!(extract_template_name(&fbn1_cohort).is_err());
+        fbn1_cohort.cohort_acronym = None;
+        let err = extract_template_name(&fbn1_cohort).unwrap_err();
+        assert!(err.to_string().contains("suggested: MS"), "unexpected error: {err}");
+    }
+
+    #[rstest]
+    fn test_run_full_qc(mut fbn1_cohort: CohortData, hpo: Arc<FullCsrOntology>) {
+        fbn1_cohort.curation_history.push(CurationEvent::new("0000-0002-0736-9199"));
+        let summary = run_full_qc(hpo.clone(), &fbn1_cohort);
+        assert_eq!(3, summary.n_individuals);
+        assert!(summary.is_empty());
+        assert!(summary.is_export_ready());
+        // Individual ids that differ only in case and a phenotype term used as a modifier
+        fbn1_cohort.rows[2].individual_data.individual_id = "individual 1".to_string();
+        fbn1_cohort.rows[0].hpo_data[0].modifiers = vec!["HP:0000343".to_string()];
+        let summary = run_full_qc(hpo, &fbn1_cohort);
+        assert_eq!((1, 1), (summary.n_errors(), summary.n_warnings()));
+        let error = summary.errors().next().unwrap();
+        assert!(error.message.starts_with("Individual 1: HP:0001166"));
+        assert!(error.message.ends_with("is not a clinical modifier"));
+        assert!(!summary.is_export_ready());
+    }
+}
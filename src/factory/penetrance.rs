@@ -0,0 +1,155 @@
+//! Penetrance
+//!
+//! If a variant is carried by N individuals who were assessed for an HPO term, and the term was
+//! observed in M of them, we estimate the penetrance of the phenotype for that variant as M/N.
+//! Carriers in whom the term was not ascertained (na) are not counted.
+//! The 95% confidence interval is the Wilson score interval, which behaves well for the small
+//! numbers of carriers typical of our cohorts.
+use std::collections::BTreeSet;
+
+use serde::{Deserialize, Serialize};
+
+use crate::{dto::{cohort_dto::CohortData, hpo_term_dto::HpoTermDuplet}, factory::statistics};
+
+
+#[derive(Clone, Debug, Deserialize, Serialize, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct PenetranceEstimate {
+    pub variant_key: String,
+    pub hpo_term: HpoTermDuplet,
+    /// Carriers of the variant in whom the term was observed or excluded
+    pub n_carriers: usize,
+    /// Carriers of the variant in whom the term was observed
+    pub n_affected: usize,
+    pub penetrance: f64,
+    /// Lower and upper bound of the 95% Wilson score interval
+    pub confidence_interval: (f64, f64),
+}
+
+
+/// Estimate the penetrance of the HPO term in column `idx` for the carriers of `variant_key`.
+/// Returns None if fewer than `min_carriers` carriers were assessed for the term.
+fn estimate_for_column(
+    variant_key: &str,
+    idx: usize,
+    cohort: &CohortData,
+    min_carriers: usize
+) -> Option<PenetranceEstimate> {
+    let duplet = cohort.hpo_headers.get(idx)?;
+    let mut n_carriers = 0;
+    let mut n_affected = 0;
+    for row in &cohort.rows {
+        if !row.allele_count_map.get(variant_key).is_some_and(|c| *c > 0) {
+            continue;
+        }
+        let Some(cell) = row.hpo_data.get(idx) else {
+            continue;
+        };
+        if cell.is_present() {
+            n_carriers += 1;
+            n_affected += 1;
+        } else if cell.is_excluded() {
+            n_carriers += 1;
+        }
+    }
+    if n_carriers == 0 || n_carriers < min_carriers {
+        return None;
+    }
+    Some(PenetranceEstimate {
+        variant_key: variant_key.to_string(),
+        hpo_term: duplet.clone(),
+        n_carriers,
+        n_affected,
+        penetrance: n_affected as f64 / n_carriers as f64,
+        confidence_interval: statistics::wilson_score_interval(n_affected, n_carriers),
+    })
+}
+
+/// Estimate the penetrance of the phenotype `hpo_id` for the variant `variant_key`.
+/// Returns None if the term is not part of the cohort or if none of the carriers was assessed for it.
+pub fn estimate_penetrance(
+    variant_key: &str,
+    hpo_id: &str,
+    cohort: &CohortData
+) -> Option<PenetranceEstimate> {
+    let idx = cohort.hpo_headers.iter().position(|duplet| duplet.hpo_id() == hpo_id)?;
+    estimate_for_column(variant_key, idx, cohort, 1)
+}
+
+/// Estimate the penetrance of every HPO term for every variant, skipping variant/term combinations
+/// with fewer than `min_carriers` assessed carriers. The results are ordered by variant key and then
+/// by the order of the HPO columns.
+pub fn estimate_all_penetrances(cohort: &CohortData, min_carriers: usize) -> Vec<PenetranceEstimate> {
+    let variant_keys: BTreeSet<&str> = cohort.rows
+        .iter()
+        .flat_map(|row| row.allele_count_map
+            .iter()
+            .filter(|(_, count)| **count > 0)
+            .map(|(key, _)| key.as_str()))
+        .collect();
+    variant_keys
+        .into_iter()
+        .flat_map(|key| (0..cohort.hpo_headers.len())
+            .filter_map(move |idx| estimate_for_column(key, idx, cohort, min_carriers)))
+        .collect()
+}
+
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use rstest::rstest;
+    use crate::test_utils::fixtures::fbn1_cohort;
+
+    fn hgvs_key(cohort: &CohortData) -> String {
+        cohort.hgvs_variants.keys().next().unwrap().clone()
+    }
+
+    // The HGVS variant is carried by individuals 1 and 2:
+    // Arachnodactyly obs/obs, Atrial septal defect obs/excl, Long hallux excl/obs, Short stature na/excl
+
+    #[rstest]
+    fn test_complete_penetrance(fbn1_cohort: CohortData) {
+        let estimate = estimate_penetrance(&hgvs_key(&fbn1_cohort), "HP:0001166", &fbn1_cohort).unwrap();
+        assert_eq!(2, estimate.n_carriers);
+        assert_eq!(2, estimate.n_affected);
+        assert_eq!(1.0, estimate.penetrance);
+        let (lower, upper) = estimate.confidence_interval;
+        assert!((lower - 0.3423802).abs() < 1e-6);
+        assert_eq!(1.0, upper);
+    }
+
+    #[rstest]
+    fn test_partial_penetrance(fbn1_cohort: CohortData) {
+        let estimate = estimate_penetrance(&hgvs_key(&fbn1_cohort), "HP:0001631", &fbn1_cohort).unwrap();
+        assert_eq!(2, estimate.n_carriers);
+        assert_eq!(1, estimate.n_affected);
+        assert_eq!(0.5, estimate.penetrance);
+        let (lower, upper) = estimate.confidence_interval;
+        assert!((lower - 0.0945312).abs() < 1e-6);
+        assert!((upper - 0.9054688).abs() < 1e-6);
+    }
+
+    #[rstest]
+    fn test_not_ascertained_carriers_are_ignored(fbn1_cohort: CohortData) {
+        let estimate = estimate_penetrance(&hgvs_key(&fbn1_cohort), "HP:0004322", &fbn1_cohort).unwrap();
+        assert_eq!(1, estimate.n_carriers);
+        assert_eq!(0, estimate.n_affected);
+        assert_eq!(0.0, estimate.penetrance);
+    }
+
+    #[rstest]
+    fn test_unknown_term_or_variant(fbn1_cohort: CohortData) {
+        assert!(estimate_penetrance(&hgvs_key(&fbn1_cohort), "HP:0000001", &fbn1_cohort).is_none());
+        assert!(estimate_penetrance("not_a_variant", "HP:0001166", &fbn1_cohort).is_none());
+    }
+
+    #[rstest]
+    fn test_all_penetrances(fbn1_cohort: CohortData) {
+        // HGVS variant: four terms, SV (one carrier): Arachnodactyly and Short stature
+        assert_eq!(6, estimate_all_penetrances(&fbn1_cohort, 1).len());
+        let estimates = estimate_all_penetrances(&fbn1_cohort, 2);
+        assert_eq!(3, estimates.len());
+        assert!(estimates.iter().all(|e| e.n_carriers >= 2));
+    }
+}
@@ -0,0 +1,167 @@
+//! Statistics
+//!
+//! Small, dependency-free implementations of the statistical tests used for cohort analyses.
+
+
+/// Natural logarithm of n!
+fn ln_factorial(n: usize) -> f64 {
+    (2..=n).map(|k| (k as f64).ln()).sum()
+}
+
+/// Natural logarithm of the binomial coefficient (n choose k)
+fn ln_choose(n: usize, k: usize) -> f64 {
+    ln_factorial(n) - ln_factorial(k) - ln_factorial(n - k)
+}
+
+/// Hypergeometric probability of the 2x2 table whose upper-left cell is `a`,
+/// given the row sums `r1`, `r2` and the first column sum `c1`.
+fn table_probability(a: usize, r1: usize, r2: usize, c1: usize) -> f64 {
+    let n = r1 + r2;
+    (ln_choose(r1, a) + ln_choose(r2, c1 - a) - ln_choose(n, c1)).exp()
+}
+
+/// Two-sided Fisher's exact test for the 2x2 contingency table
+///
+/// ```text
+///  a | b
+///  --+--
+///  c | d
+/// ```
+///
+/// The p-value is the sum of the probabilities of all tables with the same margins
+/// that are at most as probable as the observed table.
+pub(crate) fn fisher_exact_two_sided(a: usize, b: usize, c: usize, d: usize) -> f64 {
+    let r1 = a + b;
+    let r2 = c + d;
+    let c1 = a + c;
+    let n = r1 + r2;
+    if n == 0 {
+        return 1.0;
+    }
+    let a_min = c1.saturating_sub(r2);
+    let a_max = r1.min(c1);
+    let p_observed = table_probability(a, r1, r2, c1);
+    // relative tolerance to avoid excluding tables with equal probability due to rounding
+    let cutoff = p_observed * (1.0 + 1e-7);
+    let p: f64 = (a_min..=a_max)
+        .map(|x| table_probability(x, r1, r2, c1))
+        .filter(|p| *p <= cutoff)
+        .sum();
+    p.min(1.0)
+}
+
+/// Odds ratio (a*d)/(b*c) of a 2x2 table. If any cell is zero, we apply the
+/// Haldane-Anscombe correction (add 0.5 to each cell) to obtain a finite value.
+pub(crate) fn odds_ratio(a: usize, b: usize, c: usize, d: usize) -> f64 {
+    let (a, b, c, d) = if a == 0 || b == 0 || c == 0 || d == 0 {
+        (a as f64 + 0.5, b as f64 + 0.5, c as f64 + 0.5, d as f64 + 0.5)
+    } else {
+        (a as f64, b as f64, c as f64, d as f64)
+    };
+    (a * d) / (b * c)
+}
+
+
+/// Complementary error function, erfc(x), with fractional error below 1.2e-7
+/// (Chebyshev approximation from Numerical Recipes).
+fn erfc(x: f64) -> f64 {
+    let z = x.abs();
+    let t = 1.0 / (1.0 + 0.5 * z);
+    let poly = -z * z - 1.26551223
+        + t * (1.00002368
+        + t * (0.37409196
+        + t * (0.09678418
+        + t * (-0.18628806
+        + t * (0.27886807
+        + t * (-1.13520398
+        + t * (1.48851587
+        + t * (-0.82215223
+        + t * 0.17087277))))))));
+    let ans = t * poly.exp();
+    if x >= 0.0 { ans } else { 2.0 - ans }
+}
+
+/// Upper-tail p-value of the chi-square distribution with one degree of freedom
+pub(crate) fn chi_square_1df_pvalue(chi_square: f64) -> f64 {
+    if chi_square <= 0.0 {
+        return 1.0;
+    }
+    erfc((chi_square / 2.0).sqrt()).min(1.0)
+}
+
+/// Chi-square goodness-of-fit test of two counts against a 1:1 expectation.
+/// Returns the p-value (one degree of freedom).
+pub(crate) fn chi_square_equal_proportions(n1: usize, n2: usize) -> f64 {
+    let total = (n1 + n2) as f64;
+    if total == 0.0 {
+        return 1.0;
+    }
+    let expected = total / 2.0;
+    let chi_square = (n1 as f64 - expected).powi(2) / expected + (n2 as f64 - expected).powi(2) / expected;
+    chi_square_1df_pvalue(chi_square)
+}
+
+/// Two-sided 95% Wilson score interval for a binomial proportion of `k` successes in `n` trials.
+/// Unlike the normal approximation, the interval stays within [0, 1] and is informative for
+/// proportions of zero or one.
+pub(crate) fn wilson_score_interval(k: usize, n: usize) -> (f64, f64) {
+    if n == 0 {
+        return (0.0, 1.0);
+    }
+    const Z: f64 = 1.959963984540054;
+    let n = n as f64;
+    let p = k as f64 / n;
+    let z2 = Z * Z;
+    let denominator = 1.0 + z2 / n;
+    let center = (p + z2 / (2.0 * n)) / denominator;
+    let half_width = Z / denominator * (p * (1.0 - p) / n + z2 / (4.0 * n * n)).sqrt();
+    ((center - half_width).max(0.0), (center + half_width).min(1.0))
+}
+
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use rstest::rstest;
+
+    /// Reference values were calculated with R, fisher.test(matrix(c(a,c,b,d), nrow=2))
+    #[rstest]
+    #[case(1, 9, 11, 3, 0.002759)]
+    #[case(3, 1, 1, 3, 0.4857)]
+    #[case(6, 0, 0, 6, 0.002165)]
+    #[case(5, 5, 5, 5, 1.0)]
+    fn test_fisher_exact(#[case] a: usize, #[case] b: usize, #[case] c: usize, #[case] d: usize, #[case] expected: f64) {
+        let p = fisher_exact_two_sided(a, b, c, d);
+        assert!((p - expected).abs() < 1e-4, "expected {expected} but got {p}");
+    }
+
+    #[rstest]
+    fn test_odds_ratio() {
+        assert!((odds_ratio(4, 2, 1, 4) - 8.0).abs() < 1e-9);
+        // Haldane-Anscombe correction: (6.5*6.5)/(0.5*0.5)
+        assert!((odds_ratio(6, 0, 0, 6) - 169.0).abs() < 1e-9);
+    }
+
+    /// Reference values from R, pchisq(x, df=1, lower.tail=FALSE)
+    #[rstest]
+    #[case(0.0, 1.0)]
+    #[case(1.0, 0.3173105)]
+    #[case(3.841459, 0.05)]
+    #[case(5.0, 0.02534732)]
+    #[case(20.0, 7.744216e-06)]
+    fn test_chi_square_pvalue(#[case] chi_square: f64, #[case] expected: f64) {
+        let p = chi_square_1df_pvalue(chi_square);
+        assert!((p - expected).abs() < 1e-6, "expected {expected} but got {p}");
+    }
+
+    #[rstest]
+    #[case(5, 10, 0.2365931, 0.7634069)]
+    #[case(0, 10, 0.0, 0.2775328)]
+    #[case(10, 10, 0.7224672, 1.0)]
+    #[case(1, 2, 0.0945312, 0.9054688)]
+    fn test_wilson_score_interval(#[case] k: usize, #[case] n: usize, #[case] lower: f64, #[case] upper: f64) {
+        let (lo, hi) = wilson_score_interval(k, n);
+        assert!((lo - lower).abs() < 1e-6, "expected {lower} but got {lo}");
+        assert!((hi - upper).abs() < 1e-6, "expected {upper} but got {hi}");
+    }
+}
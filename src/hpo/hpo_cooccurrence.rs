@@ -0,0 +1,171 @@
+//! HPO co-occurrence
+//!
+//! Test whether pairs of HPO terms are observed together in the individuals of a cohort more often than expected
+//! by chance. With N individuals, of whom n have term A and m have term B, the number of individuals with both
+//! terms follows a hypergeometric distribution under the null hypothesis of independence. The p-value is the
+//! probability of k or more co-occurrences, and we apply a Bonferroni correction for the number of tested pairs.
+use std::{collections::{HashMap, HashSet}, str::FromStr, sync::Arc};
+
+use ontolius::{ontology::{csr::FullCsrOntology, HierarchyQueries}, TermId};
+use statrs::distribution::{DiscreteCDF, Hypergeometric};
+
+use crate::dto::{cohort_dto::CohortData, hpo_term_dto::HpoTermDuplet};
+
+/// Threshold for the Bonferroni-corrected p-value
+const SIGNIFICANCE_LEVEL: f64 = 0.05;
+
+
+/// Probability of `k` or more co-occurrences among `population` individuals, of whom `n` have one term and `m` the other.
+/// Returns None if `n` or `m` exceeds the population.
+fn hypergeometric_pvalue(population: u64, n: u64, m: u64, k: u64) -> Option<f64> {
+    let dist = Hypergeometric::new(population, n, m).ok()?;
+    if k == 0 {
+        return Some(1.0);
+    }
+    // sf(x) is P(X > x)
+    Some(dist.sf(k - 1))
+}
+
+/// True if one term is an ancestor of the other; such pairs co-occur by definition and are not tested
+fn are_related(hpo: &FullCsrOntology, a: &HpoTermDuplet, b: &HpoTermDuplet) -> bool {
+    match (TermId::from_str(a.hpo_id()), TermId::from_str(b.hpo_id())) {
+        (Ok(a), Ok(b)) => hpo.is_descendant_of(&a, &b) || hpo.is_descendant_of(&b, &a),
+        _ => false,
+    }
+}
+
+/// Significant co-occurrences of the HPO columns of the cohort.
+///
+/// An individual has a term if it was observed (including cells with an onset age, but not population frequencies). Pairs of terms in which one
+/// term is an ancestor of the other are skipped, and only pairs in which both terms were observed at least once
+/// count as tests for the Bonferroni correction. The keys of the map are in the order of the HPO columns.
+pub fn co_occurrence_pvalues(
+    cohort: &CohortData,
+    hpo: Arc<FullCsrOntology>
+) -> HashMap<(HpoTermDuplet, HpoTermDuplet), f64> {
+    let population = cohort.rows.len() as u64;
+    // the individuals (row indices) with each term
+    let individuals: Vec<HashSet<usize>> = (0..cohort.hpo_headers.len())
+        .map(|idx| {
+            cohort.rows
+                .iter()
+                .enumerate()
+                .filter(|(_, row)| row.hpo_data.get(idx).is_some_and(|cell| cell.is_present()))
+                .map(|(i, _)| i)
+                .collect()
+        })
+        .collect();
+    let mut pvalues: Vec<((HpoTermDuplet, HpoTermDuplet), f64)> = Vec::new();
+    for (i, term_a) in cohort.hpo_headers.iter().enumerate() {
+        for (j, term_b) in cohort.hpo_headers.iter().enumerate().skip(i + 1) {
+            if individuals[i].is_empty() || individuals[j].is_empty() || are_related(&hpo, term_a, term_b) {
+                continue;
+            }
+            let k = individuals[i].intersection(&individuals[j]).count() as u64;
+            let n = individuals[i].len() as u64;
+            let m = individuals[j].len() as u64;
+            if let Some(p) = hypergeometric_pvalue(population, n, m, k) {
+                pvalues.push(((term_a.clone(), term_b.clone()), p));
+            }
+        }
+    }
+    let n_tests = pvalues.len() as f64;
+    pvalues
+        .into_iter()
+        .map(|(pair, p)| (pair, (p * n_tests).min(1.0)))
+        .filter(|(_, corrected)| *corrected < SIGNIFICANCE_LEVEL)
+        .collect()
+}
+
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use rstest::rstest;
+    use crate::dto::{cohort_dto::{DiseaseData, IndividualData, RowData}, hpo_term_dto::CellValue};
+    use crate::test_utils::fixtures::hpo;
+
+    fn arachnodactyly() -> HpoTermDuplet {
+        HpoTermDuplet::new("Arachnodactyly", "HP:0001166")
+    }
+
+    fn ectopia_lentis() -> HpoTermDuplet {
+        HpoTermDuplet::new("Ectopia lentis", "HP:0001083")
+    }
+
+    fn short_stature() -> HpoTermDuplet {
+        HpoTermDuplet::new("Short stature", "HP:0004322")
+    }
+
+    /// Cohort of 20 individuals with one column per header; `has_term(row, column)` gives the observed cells,
+    /// all other cells are excluded
+    fn cohort(headers: Vec<HpoTermDuplet>, has_term: impl Fn(usize, usize) -> bool) -> CohortData {
+        let rows = (0..20)
+            .map(|i| RowData {
+                individual_data: IndividualData::new("PMID:29999999", "Study A", &format!("Individual {i}"),
+                    "", "na", "na", "na", "U"),
+                disease_id_list: vec!["OMIM:154700".to_string()],
+                allele_count_map: HashMap::new(),
+                hpo_data: (0..headers.len())
+                    .map(|j| if has_term(i, j) { CellValue::observed() } else { CellValue::excluded() })
+                    .collect(),
+            })
+            .collect();
+        CohortData::mendelian(DiseaseData::new("OMIM:154700", "Marfan syndrome"), headers, rows, "2025-03-03")
+    }
+
+    /// Arachnodactyly and Ectopia lentis in the first five individuals, Short stature in every other individual
+    fn co_occurrence_cohort() -> CohortData {
+        cohort(vec![arachnodactyly(), ectopia_lentis(), short_stature()], |i, j| match j {
+            0 | 1 => i < 5,
+            _ => i % 2 == 0,
+        })
+    }
+
+    #[rstest]
+    fn test_hypergeometric_pvalue() {
+        // all five individuals with A also have B: 1 / C(20,5)
+        let p = hypergeometric_pvalue(20, 5, 5, 5).unwrap();
+        assert!((p - 1.0 / 15504.0).abs() < 1e-12);
+        // k or more co-occurrences with k=0 is certain
+        assert_eq!(1.0, hypergeometric_pvalue(20, 5, 10, 0).unwrap());
+        // symmetric distribution: P(X >= 3) for N=20, n=5, m=10 is 0.5
+        assert!((hypergeometric_pvalue(20, 5, 10, 3).unwrap() - 0.5).abs() < 1e-12);
+        assert_eq!(None, hypergeometric_pvalue(4, 5, 2, 1));
+    }
+
+    #[rstest]
+    fn test_significant_pairs(hpo: Arc<FullCsrOntology>) {
+        let pvalues = co_occurrence_pvalues(&co_occurrence_cohort(), hpo);
+        assert_eq!(1, pvalues.len());
+        let p = pvalues[&(arachnodactyly(), ectopia_lentis())];
+        // three tested pairs
+        assert!((p - 3.0 / 15504.0).abs() < 1e-12);
+    }
+
+    #[rstest]
+    fn test_related_terms_are_skipped(hpo: Arc<FullCsrOntology>) {
+        let long_fingers = HpoTermDuplet::new("Long fingers", "HP:0100807");
+        let cohort = cohort(vec![long_fingers, arachnodactyly()], |i, _| i < 5);
+        assert!(co_occurrence_pvalues(&cohort, hpo).is_empty());
+    }
+
+    #[rstest]
+    fn test_unobserved_terms_are_not_tested(hpo: Arc<FullCsrOntology>) {
+        let cohort = cohort(vec![arachnodactyly(), ectopia_lentis(), short_stature()], |i, j| j < 2 && i < 5);
+        let pvalues = co_occurrence_pvalues(&cohort, hpo);
+        // only one tested pair, so there is no correction
+        assert!((pvalues[&(arachnodactyly(), ectopia_lentis())] - 1.0 / 15504.0).abs() < 1e-12);
+    }
+
+    /// Population-level frequencies such as "Excluded (0%)" do not count as having the term
+    #[rstest]
+    fn test_hpo_frequency_cells_are_not_observed(hpo: Arc<FullCsrOntology>) {
+        let mut cohort = cohort(vec![arachnodactyly(), ectopia_lentis(), short_stature()], |i, j| j < 2 && i < 5);
+        for row in cohort.rows.iter_mut().skip(5) {
+            row.hpo_data[1] = "HP:0040285".parse().unwrap();
+        }
+        let pvalues = co_occurrence_pvalues(&cohort, hpo);
+        assert!((pvalues[&(arachnodactyly(), ectopia_lentis())] - 1.0 / 15504.0).abs() < 1e-12);
+    }
+}
@@ -0,0 +1,143 @@
+//! HPO Frequency
+//!
+//! Count how often each HPO term of a cohort was observed and rank the terms by frequency.
+use serde::{Deserialize, Serialize};
+
+use crate::dto::{cohort_dto::CohortData, hpo_term_dto::HpoTermDuplet};
+
+
+/// Frequency of one HPO term in a cohort
+#[derive(Clone, Debug, Deserialize, Serialize, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct HpoTermFrequency {
+    /// Number of individuals in whom the term was observed (including terms with an onset age)
+    pub observed: usize,
+    /// Number of individuals in whom the term was explicitly observed or excluded (population frequencies are skipped)
+    pub ascertained: usize,
+    /// Observed as a percentage of ascertained (zero if the term was never ascertained)
+    pub percentage: f64,
+}
+
+impl HpoTermFrequency {
+    pub fn new(observed: usize, ascertained: usize) -> Self {
+        let percentage = if ascertained == 0 {
+            0.0
+        } else {
+            100.0 * observed as f64 / ascertained as f64
+        };
+        Self { observed, ascertained, percentage }
+    }
+}
+
+
+/// Number of individuals in whom the term of HPO column `idx` was observed and number in whom it was observed
+/// or excluded. All frequency calculations use this function, so that they agree on what counts as observed
+/// (see [`CellValueInner::is_present`](crate::dto::hpo_term_dto::CellValueInner::is_present)).
+pub(crate) fn count_observed_and_measured(cohort: &CohortData, idx: usize) -> (usize, usize) {
+    cohort.rows
+        .iter()
+        .filter_map(|row| row.hpo_data.get(idx))
+        .fold((0, 0), |(observed, measured), cell| {
+            (observed + cell.is_present() as usize, measured + cell.is_measured() as usize)
+        })
+}
+
+/// Frequencies of all HPO terms of the cohort, in the order of the HPO columns
+pub fn hpo_term_frequencies(cohort: &CohortData) -> Vec<(HpoTermDuplet, HpoTermFrequency)> {
+    cohort.hpo_headers
+        .iter()
+        .enumerate()
+        .map(|(idx, duplet)| {
+            let (observed, ascertained) = count_observed_and_measured(cohort, idx);
+            (duplet.clone(), HpoTermFrequency::new(observed, ascertained))
+        })
+        .collect()
+}
+
+/// Rank the terms with at least `min_ascertainment` ascertained individuals.
+/// Ties are broken by the number of ascertained individuals (more first) and then by HPO id,
+/// so that the order does not depend on the order of the columns.
+fn ranked(cohort: &CohortData, min_ascertainment: usize, descending: bool) -> Vec<(HpoTermDuplet, HpoTermFrequency)> {
+    let mut frequencies: Vec<(HpoTermDuplet, HpoTermFrequency)> = hpo_term_frequencies(cohort)
+        .into_iter()
+        .filter(|(_, freq)| freq.ascertained > 0 && freq.ascertained >= min_ascertainment)
+        .collect();
+    frequencies.sort_by(|(dup_a, freq_a), (dup_b, freq_b)| {
+        let by_percentage = if descending {
+            freq_b.percentage.total_cmp(&freq_a.percentage)
+        } else {
+            freq_a.percentage.total_cmp(&freq_b.percentage)
+        };
+        by_percentage
+            .then(freq_b.ascertained.cmp(&freq_a.ascertained))
+            .then(dup_a.hpo_id().cmp(dup_b.hpo_id()))
+    });
+    frequencies
+}
+
+pub fn top_n(cohort: &CohortData, n: usize, min_ascertainment: usize) -> Vec<(HpoTermDuplet, HpoTermFrequency)> {
+    ranked(cohort, min_ascertainment, true).into_iter().take(n).collect()
+}
+
+pub fn bottom_n(cohort: &CohortData, n: usize, min_ascertainment: usize) -> Vec<(HpoTermDuplet, HpoTermFrequency)> {
+    ranked(cohort, min_ascertainment, false).into_iter().take(n).collect()
+}
+
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use rstest::rstest;
+    use crate::test_utils::fixtures::fbn1_cohort;
+
+    // Frequencies in the fixture cohort:
+    // Arachnodactyly 3/3, Atrial septal defect 1/2, Long hallux 1/2, Short stature 1/2
+
+    #[rstest]
+    fn test_frequencies(fbn1_cohort: CohortData) {
+        let frequencies = hpo_term_frequencies(&fbn1_cohort);
+        assert_eq!(4, frequencies.len());
+        assert_eq!(HpoTermFrequency::new(3, 3), frequencies[0].1);
+        assert_eq!(HpoTermFrequency::new(1, 2), frequencies[1].1);
+        assert_eq!(50.0, frequencies[1].1.percentage);
+    }
+
+    #[rstest]
+    fn test_top_n(fbn1_cohort: CohortData) {
+        let top = top_n(&fbn1_cohort, 2, 0);
+        assert_eq!(2, top.len());
+        assert_eq!("HP:0001166", top[0].0.hpo_id());
+        // tie among the three terms with 1/2, broken by HPO id
+        assert_eq!("HP:0001631", top[1].0.hpo_id());
+    }
+
+    #[rstest]
+    fn test_ranking_is_stable(mut fbn1_cohort: CohortData) {
+        let expected: Vec<String> = bottom_n(&fbn1_cohort, 4, 0).iter().map(|(d, _)| d.hpo_id.clone()).collect();
+        assert_eq!(vec!["HP:0001631", "HP:0001847", "HP:0004322", "HP:0001166"], expected);
+        // reversing the column order must not change the ranking
+        fbn1_cohort.hpo_headers.reverse();
+        for row in fbn1_cohort.rows.iter_mut() {
+            row.hpo_data.reverse();
+        }
+        let reordered: Vec<String> = bottom_n(&fbn1_cohort, 4, 0).iter().map(|(d, _)| d.hpo_id.clone()).collect();
+        assert_eq!(expected, reordered);
+    }
+
+    /// Population-level frequencies, including "Excluded (0%)", are neither observed nor ascertained
+    #[rstest]
+    fn test_hpo_frequency_cells_are_skipped(mut fbn1_cohort: CohortData) {
+        fbn1_cohort.rows[0].hpo_data[0] = "HP:0040285".parse().unwrap();
+        fbn1_cohort.rows[1].hpo_data[0] = "HP:0040281".parse().unwrap();
+        let frequencies = hpo_term_frequencies(&fbn1_cohort);
+        assert_eq!(HpoTermFrequency::new(1, 1), frequencies[0].1);
+    }
+
+    #[rstest]
+    fn test_min_ascertainment(fbn1_cohort: CohortData) {
+        let top = top_n(&fbn1_cohort, 10, 3);
+        assert_eq!(1, top.len());
+        assert_eq!("HP:0001166", top[0].0.hpo_id());
+        assert!(bottom_n(&fbn1_cohort, 10, 4).is_empty());
+    }
+}
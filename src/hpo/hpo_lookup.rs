@@ -0,0 +1,253 @@
+//! HPO Lookup
+//!
+//! Look up HPO terms by identifier or by (the beginning of) their label or synonyms,
+//! e.g., for the `phetools hpo` command.
+//!
+//! For autocompletion, the prefix searches use a sorted index of the case-folded labels and synonyms that
+//! is built at the first call and reused as long as the same HPO version is searched.
+use std::{collections::HashSet, str::FromStr, sync::{Arc, OnceLock}};
+
+use ontolius::{ontology::{csr::FullCsrOntology, MetadataAware, OntologyTerms}, term::{MinimalTerm, Synonymous}, Identified, TermId};
+use serde::{Deserialize, Serialize};
+
+use crate::dto::hpo_term_dto::HpoTermDuplet;
+
+
+#[derive(Clone, Debug, Deserialize, Serialize, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct HpoTermInfo {
+    pub hpo_id: String,
+    pub label: String,
+    pub synonyms: Vec<String>,
+}
+
+impl HpoTermInfo {
+    fn from_term<T: MinimalTerm + Synonymous>(term: &T) -> Self {
+        Self {
+            hpo_id: term.identifier().to_string(),
+            label: term.name().to_string(),
+            synonyms: term.synonyms().iter().map(|s| s.name.clone()).collect(),
+        }
+    }
+}
+
+
+/// Retrieve a term by its primary or alternative identifier.
+pub fn lookup_term(hpo: &FullCsrOntology, hpo_id: &str) -> Result<HpoTermInfo, String> {
+    let tid = TermId::from_str(hpo_id).map_err(|_| format!("Invalid HPO id: '{hpo_id}'"))?;
+    let info = hpo.term_by_id(&tid).map(HpoTermInfo::from_term);
+    tracing::debug!(hpo_id, result = info.as_ref().map(|i| i.label.as_str()), "HPO lookup");
+    info.ok_or_else(|| format!("Could not find HPO term {hpo_id}"))
+}
+
+/// Case-insensitive prefix search over the labels and synonyms of all terms.
+/// Terms whose label matches are listed before terms that match only by synonym; otherwise sorted by label.
+pub fn search_terms(hpo: &FullCsrOntology, query: &str) -> Vec<HpoTermInfo> {
+    let query = query.trim().to_lowercase();
+    if query.is_empty() {
+        return vec![];
+    }
+    let mut matches: Vec<(bool, HpoTermInfo)> = hpo.iter_terms()
+        .filter_map(|term| {
+            let label_match = term.name().to_lowercase().starts_with(&query);
+            let synonym_match = term.synonyms().iter().any(|s| s.name.to_lowercase().starts_with(&query));
+            if label_match || synonym_match {
+                Some((label_match, HpoTermInfo::from_term(term)))
+            } else {
+                None
+            }
+        })
+        .collect();
+    matches.sort_by(|(label_a, a), (label_b, b)| label_b.cmp(label_a).then(a.label.cmp(&b.label)));
+    matches.into_iter().map(|(_, info)| info).collect()
+}
+
+
+/// Case-folded labels and synonyms, sorted for binary search, with the index of the term in `terms`
+struct PrefixIndex {
+    hpo_version: String,
+    terms: Vec<HpoTermDuplet>,
+    labels: Vec<(String, usize)>,
+    synonyms: Vec<(String, usize)>,
+}
+
+static PREFIX_INDEX: OnceLock<Arc<PrefixIndex>> = OnceLock::new();
+
+impl PrefixIndex {
+    fn new(hpo: &FullCsrOntology) -> Self {
+        let mut terms = Vec::new();
+        let mut labels = Vec::new();
+        let mut synonyms = Vec::new();
+        for (i, term) in hpo.iter_terms().enumerate() {
+            terms.push(HpoTermDuplet::new(term.name(), term.identifier().to_string()));
+            labels.push((term.name().to_lowercase(), i));
+            synonyms.extend(term.synonyms().iter().map(|s| (s.name.to_lowercase(), i)));
+        }
+        labels.sort();
+        synonyms.sort();
+        Self { hpo_version: hpo.version().to_string(), terms, labels, synonyms }
+    }
+
+    /// The cached index if it was built for the version of `hpo`, otherwise a new (uncached) index
+    fn get(hpo: &FullCsrOntology) -> Arc<PrefixIndex> {
+        let index = PREFIX_INDEX.get_or_init(|| Arc::new(PrefixIndex::new(hpo)));
+        if index.hpo_version == hpo.version() {
+            index.clone()
+        } else {
+            tracing::debug!(cached = %index.hpo_version, requested = %hpo.version(), "Building HPO prefix index for another HPO version");
+            Arc::new(PrefixIndex::new(hpo))
+        }
+    }
+
+    /// Indices of the terms whose entries in `sorted` start with `prefix`, in alphabetical order of the entries
+    fn matches<'a>(sorted: &'a [(String, usize)], prefix: &'a str) -> impl Iterator<Item = usize> + 'a {
+        let start = sorted.partition_point(|(s, _)| s.as_str() < prefix);
+        sorted[start..].iter().take_while(move |(s, _)| s.starts_with(prefix)).map(|(_, i)| *i)
+    }
+}
+
+/// Whether an identifier is the primary identifier of a term, an alternative identifier, or unknown
+#[derive(Clone, Debug, PartialEq)]
+pub enum TermStatus {
+    /// The identifier is the primary identifier of a term
+    Current,
+    /// The identifier is obsolete; the term now has the given primary identifier
+    Replaced(TermId),
+    /// The identifier is malformed or no longer part of the ontology
+    Deleted,
+}
+
+pub fn term_status(hpo: &FullCsrOntology, hpo_id: &str) -> TermStatus {
+    let Ok(tid) = TermId::from_str(hpo_id.trim()) else {
+        return TermStatus::Deleted;
+    };
+    match hpo.term_by_id(&tid) {
+        Some(term) if *term.identifier() == tid => TermStatus::Current,
+        Some(term) => TermStatus::Replaced(term.identifier().clone()),
+        None => TermStatus::Deleted,
+    }
+}
+
+/// The term with the identifier (primary or alternative) or, if there is none, with the exact label `query`
+pub fn lookup_by_id_or_label(hpo: &FullCsrOntology, query: &str) -> Option<HpoTermDuplet> {
+    let query = query.trim();
+    let by_id = TermId::from_str(query).ok()
+        .and_then(|tid| hpo.term_by_id(&tid))
+        .map(|term| HpoTermDuplet::new(term.name(), term.identifier().to_string()));
+    if by_id.is_some() {
+        return by_id;
+    }
+    let index = PrefixIndex::get(hpo);
+    PrefixIndex::matches(&index.labels, &query.to_lowercase())
+        .map(|i| &index.terms[i])
+        .find(|t| t.hpo_label() == query)
+        .cloned()
+}
+
+/// Terms whose label (and, if `include_synonyms`, one of whose synonyms) starts with `prefix` (case-insensitive).
+/// Label matches come first; each group is sorted alphabetically.
+pub fn search_by_prefix(hpo: &FullCsrOntology, prefix: &str, max_results: usize, include_synonyms: bool) -> Vec<HpoTermDuplet> {
+    let prefix = prefix.trim().to_lowercase();
+    if prefix.is_empty() {
+        return vec![];
+    }
+    let index = PrefixIndex::get(hpo);
+    let label_matches = PrefixIndex::matches(&index.labels, &prefix);
+    let synonym_matches = PrefixIndex::matches(&index.synonyms, &prefix).filter(|_| include_synonyms);
+    let mut seen = HashSet::new();
+    label_matches
+        .chain(synonym_matches)
+        .filter(|i| seen.insert(*i))
+        .take(max_results)
+        .map(|i| index.terms[i].clone())
+        .collect()
+}
+
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use std::sync::Arc;
+    use rstest::rstest;
+    use crate::test_utils::fixtures::hpo;
+
+    #[rstest]
+    fn test_lookup_root(hpo: Arc<FullCsrOntology>) {
+        let info = lookup_term(&hpo, "HP:0000001").unwrap();
+        assert_eq!("HP:0000001", info.hpo_id);
+        assert_eq!("All", info.label);
+    }
+
+    #[rstest]
+    fn test_lookup_synonyms(hpo: Arc<FullCsrOntology>) {
+        let info = lookup_term(&hpo, "HP:0001166").unwrap();
+        assert_eq!("Arachnodactyly", info.label);
+        assert!(info.synonyms.contains(&"Spider fingers".to_string()));
+    }
+
+    #[rstest]
+    #[case("HP:9999999")]
+    #[case("not an id")]
+    fn test_lookup_error(hpo: Arc<FullCsrOntology>, #[case] hpo_id: &str) {
+        assert!(lookup_term(&hpo, hpo_id).is_err());
+    }
+
+    #[rstest]
+    fn test_search(hpo: Arc<FullCsrOntology>) {
+        let results = search_terms(&hpo, "arachnodact");
+        assert_eq!("HP:0001166", results[0].hpo_id);
+        // found by synonym only
+        let results = search_terms(&hpo, "spider fing");
+        assert!(results.iter().any(|info| info.hpo_id == "HP:0001166"));
+        assert!(search_terms(&hpo, "  ").is_empty());
+    }
+
+    #[rstest]
+    #[case::primary_id("HP:0001166")]
+    #[case::alternative_id("HP:0001505")]
+    #[case::label("Arachnodactyly")]
+    fn test_lookup_by_id_or_label(hpo: Arc<FullCsrOntology>, #[case] query: &str) {
+        let expected = HpoTermDuplet::new("Arachnodactyly", "HP:0001166");
+        assert_eq!(Some(expected), lookup_by_id_or_label(&hpo, query));
+    }
+
+    #[rstest]
+    fn test_term_status(hpo: Arc<FullCsrOntology>) {
+        assert_eq!(TermStatus::Current, term_status(&hpo, "HP:0001166"));
+        // HP:0001505 was merged into Arachnodactyly
+        assert_eq!(TermStatus::Replaced(TermId::from_str("HP:0001166").unwrap()), term_status(&hpo, "HP:0001505"));
+        assert_eq!(TermStatus::Deleted, term_status(&hpo, "HP:9999999"));
+        assert_eq!(TermStatus::Deleted, term_status(&hpo, "Arachnodactyly"));
+    }
+
+    #[rstest]
+    #[case::unknown_id("HP:9999999")]
+    #[case::label_prefix("Arachnodact")]
+    #[case::synonym("Spider fingers")]
+    fn test_lookup_by_id_or_label_no_match(hpo: Arc<FullCsrOntology>, #[case] query: &str) {
+        assert_eq!(None, lookup_by_id_or_label(&hpo, query));
+    }
+
+    #[rstest]
+    fn test_prefix_search(hpo: Arc<FullCsrOntology>) {
+        let results = search_by_prefix(&hpo, "ARACHNOD", 10, false);
+        assert_eq!(vec![HpoTermDuplet::new("Arachnodactyly", "HP:0001166")], results);
+        // the synonym "Spider fingers" matches only if synonyms are included
+        assert!(search_by_prefix(&hpo, "spider fing", 10, false).is_empty());
+        let results = search_by_prefix(&hpo, "spider fing", 10, true);
+        assert_eq!("HP:0001166", results[0].hpo_id());
+        assert!(search_by_prefix(&hpo, "", 10, true).is_empty());
+    }
+
+    #[rstest]
+    fn test_prefix_search_max_results(hpo: Arc<FullCsrOntology>) {
+        let results = search_by_prefix(&hpo, "abnormal", 5, false);
+        assert_eq!(5, results.len());
+        assert!(results.iter().all(|t| t.hpo_label().to_lowercase().starts_with("abnormal")));
+        let labels: Vec<String> = results.iter().map(|t| t.hpo_label().to_lowercase()).collect();
+        let mut sorted = labels.clone();
+        sorted.sort();
+        assert_eq!(sorted, labels);
+        assert!(search_by_prefix(&hpo, "abnormal", 0, true).is_empty());
+    }
+}
@@ -1,8 +1,8 @@
 use std::{collections::HashSet, sync::Arc};
 use std::sync::LazyLock;
-use ontolius::{TermId, ontology::{HierarchyWalks, OntologyTerms, csr::FullCsrOntology}, term::MinimalTerm};
+use ontolius::{Identified, TermId, ontology::{HierarchyQueries, HierarchyWalks, OntologyTerms, csr::FullCsrOntology}, term::MinimalTerm};
 
-use crate::dto::hpo_term_dto::HpoTermDuplet;
+use crate::dto::hpo_term_dto::{HpoTermData, HpoTermDuplet};
 
  pub static CLINICAL_MODIFIER: LazyLock<TermId> = LazyLock::new(|| {
     let term_id: TermId = "HP:0012823"
@@ -72,11 +72,39 @@ use crate::dto::hpo_term_dto::HpoTermDuplet;
 }
 
 
+/// Check that each modifier of the annotation is the primary id of a current HPO term below
+/// Clinical modifier (HP:0012823). Returns one message for each invalid modifier.
+pub fn validate_modifier_ids(hpo: Arc<FullCsrOntology>, data: &HpoTermData) -> Result<(), Vec<String>> {
+    let errors: Vec<String> = data.modifiers()
+        .iter()
+        .filter_map(|mod_str| {
+            let Ok(mod_id) = mod_str.parse::<TermId>() else {
+                return Some(format!("{}: malformed modifier id '{mod_str}'", data.term_id()));
+            };
+            match hpo.term_by_id(&mod_id) {
+                None => Some(format!("{}: unknown modifier {mod_str}", data.term_id())),
+                Some(term) if term.identifier() != &mod_id => Some(format!("{}: modifier {mod_str} is obsolete (use {})",
+                    data.term_id(), term.identifier())),
+                Some(term) if !hpo.is_descendant_of(&mod_id, &*CLINICAL_MODIFIER) => Some(format!(
+                    "{}: {} ({mod_str}) is not a clinical modifier", data.term_id(), term.name())),
+                Some(_) => None,
+            }
+        })
+        .collect();
+    if errors.is_empty() {
+        Ok(())
+    } else {
+        Err(errors)
+    }
+}
+
+
 #[cfg(test)]
 mod tests {
     use ontolius::ontology::csr::FullCsrOntology;
     use std::sync::Arc;
-    use crate::hpo::hpo_modifiers::get_modifiers;
+    use crate::dto::hpo_term_dto::{CellValue, HpoTermData, HpoTermDuplet};
+    use crate::hpo::hpo_modifiers::{get_modifiers, validate_modifier_ids};
     use crate::test_utils::fixtures::hpo;
     use rstest::rstest;
 
@@ -108,5 +136,24 @@ mod tests {
             "{}: modifier status {} but we got {}", label, is_valid_modifier, included);
     }
 
+    #[rstest]
+    #[case::severity("HP:0012826", true)]
+    #[case::clinical_modifier("HP:0033815", true)]
+    #[case::phenotype("HP:0000343", false)]
+    #[case::unknown("HP:9999999", false)]
+    #[case::malformed("Severe", false)]
+    fn test_validate_modifier_ids(hpo: Arc<FullCsrOntology>, #[case] modifier: &str, #[case] is_valid: bool) {
+        let mut cell = CellValue::observed();
+        cell.modifiers = vec![modifier.to_string()];
+        let data = HpoTermData::new(HpoTermDuplet::new("Arachnodactyly", "HP:0001166"), cell).unwrap();
+        assert_eq!(is_valid, validate_modifier_ids(hpo, &data).is_ok());
+    }
 
-}
\ No newline at end of file
+    #[rstest]
+    fn test_validate_modifier_ids_reports_all(hpo: Arc<FullCsrOntology>) {
+        let mut cell = CellValue::observed();
+        cell.modifiers = vec!["HP:0012826".to_string(), "HP:0000343".to_string(), "HP:9999999".to_string()];
+        let data = HpoTermData::new(HpoTermDuplet::new("Arachnodactyly", "HP:0001166"), cell).unwrap();
+        assert_eq!(2, validate_modifier_ids(hpo, &data).unwrap_err().len());
+    }
+}
@@ -0,0 +1,156 @@
+//! HPO Navigation
+//!
+//! Walk the HPO hierarchy from a term to its parents, children, siblings, and descendants,
+//! e.g., for interactive term selection in the GUI, and compute the depth of a term below the root.
+//! Terms that are not in the ontology have no relatives.
+use std::collections::{HashMap, HashSet, VecDeque};
+
+use ontolius::{ontology::{csr::FullCsrOntology, HierarchyWalks}, TermId};
+
+
+pub(crate) fn children(hpo: &FullCsrOntology, term_id: &TermId) -> Vec<TermId> {
+    hpo.iter_child_ids(term_id).cloned().collect()
+}
+
+pub(crate) fn parents(hpo: &FullCsrOntology, term_id: &TermId) -> Vec<TermId> {
+    hpo.iter_parent_ids(term_id).cloned().collect()
+}
+
+/// The other children of all parents of the term, each listed once
+pub(crate) fn siblings(hpo: &FullCsrOntology, term_id: &TermId) -> Vec<TermId> {
+    let mut seen: HashSet<&TermId> = HashSet::from([term_id]);
+    hpo.iter_parent_ids(term_id)
+        .flat_map(|parent| hpo.iter_child_ids(parent))
+        .filter(|tid| seen.insert(*tid))
+        .cloned()
+        .collect()
+}
+
+/// All transitive children of the term in depth-first (pre-)order, each listed once
+pub(crate) fn descendants(hpo: &FullCsrOntology, term_id: &TermId) -> Vec<TermId> {
+    let mut seen: HashSet<&TermId> = HashSet::new();
+    let mut descendants = Vec::new();
+    let mut stack: Vec<&TermId> = hpo.iter_child_ids(term_id).collect();
+    stack.reverse();
+    while let Some(tid) = stack.pop() {
+        if !seen.insert(tid) {
+            continue;
+        }
+        descendants.push(tid.clone());
+        let mut children: Vec<&TermId> = hpo.iter_child_ids(tid).collect();
+        children.reverse();
+        stack.extend(children);
+    }
+    descendants
+}
+
+/// Length of the shortest path (number of is_a edges) from the term up to the root; 0 for the root
+pub(crate) fn min_depth(hpo: &FullCsrOntology, term_id: &TermId) -> usize {
+    // breadth-first search towards the root, so the first term without parents is the closest one
+    let mut seen: HashSet<&TermId> = HashSet::from([term_id]);
+    let mut queue: VecDeque<(&TermId, usize)> = VecDeque::from([(term_id, 0)]);
+    while let Some((tid, depth)) = queue.pop_front() {
+        let mut is_root = true;
+        for parent in hpo.iter_parent_ids(tid) {
+            is_root = false;
+            if seen.insert(parent) {
+                queue.push_back((parent, depth + 1));
+            }
+        }
+        if is_root {
+            return depth;
+        }
+    }
+    0
+}
+
+/// Length of the longest path from the term up to the root; 0 for the root
+pub(crate) fn max_depth(hpo: &FullCsrOntology, term_id: &TermId) -> usize {
+    fn max_depth_memo<'a>(hpo: &'a FullCsrOntology, tid: &'a TermId, memo: &mut HashMap<&'a TermId, usize>) -> usize {
+        if let Some(depth) = memo.get(tid) {
+            return *depth;
+        }
+        let depth = hpo.iter_parent_ids(tid)
+            .map(|parent| max_depth_memo(hpo, parent, memo) + 1)
+            .max()
+            .unwrap_or(0);
+        memo.insert(tid, depth);
+        depth
+    }
+    max_depth_memo(hpo, term_id, &mut HashMap::new())
+}
+
+pub(crate) fn is_leaf(hpo: &FullCsrOntology, term_id: &TermId) -> bool {
+    hpo.iter_child_ids(term_id).next().is_none()
+}
+
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use std::{str::FromStr, sync::Arc};
+    use rstest::rstest;
+    use crate::test_utils::fixtures::hpo;
+
+    fn tid(id: &str) -> TermId {
+        TermId::from_str(id).unwrap()
+    }
+
+    fn sorted(term_ids: Vec<TermId>) -> Vec<String> {
+        let mut ids: Vec<String> = term_ids.iter().map(|t| t.to_string()).collect();
+        ids.sort();
+        ids
+    }
+
+    #[rstest]
+    fn test_parents_and_children(hpo: Arc<FullCsrOntology>) {
+        // Arachnodactyly is both a Slender finger and a Long finger
+        assert_eq!(vec!["HP:0001238", "HP:0100807"], sorted(parents(&hpo, &tid("HP:0001166"))));
+        assert_eq!(vec!["HP:0001166", "HP:0001182"], sorted(children(&hpo, &tid("HP:0100807"))));
+        assert!(children(&hpo, &tid("HP:0001166")).is_empty());
+    }
+
+    #[rstest]
+    fn test_root_has_no_parents(hpo: Arc<FullCsrOntology>) {
+        assert!(parents(&hpo, &tid("HP:0000001")).is_empty());
+        assert!(siblings(&hpo, &tid("HP:0000001")).is_empty());
+    }
+
+    #[rstest]
+    fn test_siblings(hpo: Arc<FullCsrOntology>) {
+        // Tapered finger (child of Long fingers); Slender finger has no other children
+        assert_eq!(vec!["HP:0001182"], sorted(siblings(&hpo, &tid("HP:0001166"))));
+    }
+
+    #[rstest]
+    fn test_descendants(hpo: Arc<FullCsrOntology>) {
+        assert_eq!(vec!["HP:0001166", "HP:0001182"], sorted(descendants(&hpo, &tid("HP:0100807"))));
+        let finger = descendants(&hpo, &tid("HP:0001167"));
+        // Arachnodactyly is reachable via two parents but listed only once
+        assert_eq!(1, finger.iter().filter(|t| **t == tid("HP:0001166")).count());
+        assert_eq!(finger.len(), finger.iter().collect::<HashSet<_>>().len());
+        let expected: HashSet<&TermId> = hpo.iter_descendant_ids(&tid("HP:0001167")).collect();
+        assert_eq!(expected, finger.iter().collect::<HashSet<_>>());
+        assert!(descendants(&hpo, &tid("HP:0001166")).is_empty());
+    }
+
+    #[rstest]
+    #[case::root("HP:0000001", 0, 0)]
+    #[case::phenotypic_abnormality("HP:0000118", 1, 1)]
+    #[case::musculoskeletal("HP:0033127", 2, 2)]
+    #[case::finger("HP:0001167", 5, 8)]
+    #[case::long_fingers("HP:0100807", 6, 9)]
+    #[case::arachnodactyly("HP:0001166", 7, 10)]
+    fn test_depth(hpo: Arc<FullCsrOntology>, #[case] hpo_id: &str, #[case] min: usize, #[case] max: usize) {
+        assert_eq!(min, min_depth(&hpo, &tid(hpo_id)));
+        assert_eq!(max, max_depth(&hpo, &tid(hpo_id)));
+    }
+
+    #[rstest]
+    #[case("HP:0001166", true)]
+    #[case("HP:0100807", false)]
+    #[case("HP:0000001", false)]
+    fn test_is_leaf(hpo: Arc<FullCsrOntology>, #[case] hpo_id: &str, #[case] expected: bool) {
+        assert_eq!(expected, is_leaf(&hpo, &tid(hpo_id)));
+    }
+}
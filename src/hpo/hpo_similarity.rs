@@ -0,0 +1,133 @@
+//! HPO Similarity
+//!
+//! Phenotypic similarity between the individuals of a cohort. Each individual is represented by the set
+//! of observed HPO terms together with all of their ancestors below *Phenotypic abnormality* (HP:0000118).
+//! The similarity of two individuals is the Jaccard index of these sets, so that individuals with
+//! related (but not identical) features are still recognized as similar.
+use std::{collections::HashSet, str::FromStr, sync::Arc};
+
+use ontolius::{common::hpo::PHENOTYPIC_ABNORMALITY, ontology::{csr::FullCsrOntology, HierarchyQueries, HierarchyWalks}, TermId};
+
+use crate::dto::cohort_dto::CohortData;
+
+
+pub struct HpoSimilarity {
+    /// Pairs of individual identifier and the annotation set (observed terms and their ancestors)
+    annotations: Vec<(String, HashSet<TermId>)>,
+}
+
+impl HpoSimilarity {
+    /// Individuals without any observed term are skipped because there is nothing to compare.
+    pub fn new(cohort: &CohortData, hpo: Arc<FullCsrOntology>) -> Self {
+        let annotations = cohort.rows
+            .iter()
+            .map(|row| {
+                let mut term_set: HashSet<TermId> = HashSet::new();
+                for (duplet, cell) in cohort.hpo_headers.iter().zip(&row.hpo_data) {
+                    if !cell.is_present() {
+                        continue;
+                    }
+                    let Ok(tid) = TermId::from_str(duplet.hpo_id()) else {
+                        continue;
+                    };
+                    term_set.extend(
+                        hpo.iter_ancestor_ids(&tid)
+                            .filter(|anc| hpo.is_descendant_of(*anc, &PHENOTYPIC_ABNORMALITY))
+                            .cloned());
+                    term_set.insert(tid);
+                }
+                (row.individual_data.individual_id.clone(), term_set)
+            })
+            .filter(|(_, term_set)| !term_set.is_empty())
+            .collect();
+        Self { annotations }
+    }
+
+    fn jaccard(a: &HashSet<TermId>, b: &HashSet<TermId>) -> f64 {
+        let union = a.union(b).count();
+        if union == 0 {
+            return 0.0;
+        }
+        a.intersection(b).count() as f64 / union as f64
+    }
+
+    /// All pairs of individuals (i < j) with their similarity
+    fn pairwise_similarities(&self) -> Vec<(String, String, f64)> {
+        let mut similarities = Vec::new();
+        for (i, (id_a, set_a)) in self.annotations.iter().enumerate() {
+            for (id_b, set_b) in self.annotations.iter().skip(i + 1) {
+                similarities.push((id_a.clone(), id_b.clone(), Self::jaccard(set_a, set_b)));
+            }
+        }
+        similarities
+    }
+
+    /// Mean pairwise dissimilarity (1 - similarity). Zero if there are fewer than two individuals to compare.
+    pub fn heterogeneity(&self) -> f64 {
+        let similarities = self.pairwise_similarities();
+        if similarities.is_empty() {
+            return 0.0;
+        }
+        let total: f64 = similarities.iter().map(|(_, _, sim)| 1.0 - sim).sum();
+        total / similarities.len() as f64
+    }
+
+    /// Pairs of individuals whose similarity is at least `threshold`, most similar first
+    pub fn similar_pairs(&self, threshold: f64) -> Vec<(String, String, f64)> {
+        let mut pairs: Vec<(String, String, f64)> = self.pairwise_similarities()
+            .into_iter()
+            .filter(|(_, _, sim)| *sim >= threshold)
+            .collect();
+        pairs.sort_by(|a, b| b.2.total_cmp(&a.2));
+        pairs
+    }
+}
+
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use rstest::rstest;
+    use crate::dto::hpo_term_dto::CellValue;
+    use crate::test_utils::fixtures::{fbn1_cohort, hpo};
+
+    /// Set all HPO cells of the cohort to the given values (one vector per row)
+    fn with_values(mut cohort: CohortData, values: Vec<Vec<&str>>) -> CohortData {
+        for (row, row_values) in cohort.rows.iter_mut().zip(values) {
+            row.hpo_data = row_values.iter().map(|v| v.parse::<CellValue>().unwrap()).collect();
+        }
+        cohort
+    }
+
+    #[rstest]
+    fn test_uniform_cohort(fbn1_cohort: CohortData, hpo: Arc<FullCsrOntology>) {
+        let row = vec!["observed", "observed", "excluded", "na"];
+        let cohort = with_values(fbn1_cohort, vec![row.clone(), row.clone(), row]);
+        let similarity = HpoSimilarity::new(&cohort, hpo);
+        assert_eq!(0.0, similarity.heterogeneity());
+        assert_eq!(3, similarity.similar_pairs(1.0).len());
+    }
+
+    #[rstest]
+    fn test_maximally_heterogeneous_cohort(fbn1_cohort: CohortData, hpo: Arc<FullCsrOntology>) {
+        // Atrial septal defect and Short stature (growth abnormality) share no ancestors below Phenotypic abnormality
+        let cohort = with_values(fbn1_cohort, vec![
+            vec!["na", "observed", "na", "na"],
+            vec!["na", "na", "na", "observed"],
+            vec!["excluded", "excluded", "excluded", "excluded"],
+        ]);
+        let similarity = HpoSimilarity::new(&cohort, hpo);
+        assert!((similarity.heterogeneity() - 1.0).abs() < 1e-9);
+        assert!(similarity.similar_pairs(0.01).is_empty());
+    }
+
+    #[rstest]
+    fn test_similar_pairs(fbn1_cohort: CohortData, hpo: Arc<FullCsrOntology>) {
+        let similarity = HpoSimilarity::new(&fbn1_cohort, hpo);
+        let heterogeneity = similarity.heterogeneity();
+        assert!(heterogeneity > 0.0 && heterogeneity < 1.0);
+        let pairs = similarity.similar_pairs(0.0);
+        assert_eq!(3, pairs.len());
+        assert!(pairs.windows(2).all(|w| w[0].2 >= w[1].2));
+    }
+}
@@ -4,7 +4,7 @@
 //!
 //! Objects of this class are created to perform a DSF to find a good way of arranging HPO term columns
 //! We do not need to take ownership of the ontology, therefore indicate explicit lifetime
-use std::{collections::HashSet, str::FromStr, sync::Arc};
+use std::{collections::{HashMap, HashSet}, str::FromStr, sync::Arc};
 
 use ontolius::{
     common::hpo::PHENOTYPIC_ABNORMALITY, ontology::{csr::FullCsrOntology, HierarchyQueries, HierarchyWalks, OntologyTerms}, term::MinimalTerm, TermId
@@ -107,6 +107,44 @@ impl HpoTermArranger {
         hpo_terms_for_curation: &Vec<TermId>)
     -> std::result::Result<Vec<HpoTermDuplet>, String> {
         let arranged_tids = self.arrange_term_ids(hpo_terms_for_curation);
+        self.to_duplets(arranged_tids)
+    }
+
+    /// Arrange terms by decreasing frequency (e.g., the fraction of individuals in whom the term was observed)
+    ///
+    /// Terms with the same frequency keep their DFS order (see [`Self::arrange_term_ids`]), and terms
+    /// that are missing from `frequency_map` are treated as having a frequency of zero. As with the DFS,
+    /// terms outside of the Phenotypic abnormality subhierarchy are not included.
+    ///
+    /// # Arguments
+    ///
+    /// * `terms` - TermIds of the terms to be arranged
+    /// * `frequency_map` - frequency of each term
+    /// * `hpo` - reference to an Ontolius HPO ontology
+    pub fn arrange_by_frequency(
+        terms: &[TermId],
+        frequency_map: &HashMap<TermId, f64>,
+        hpo: Arc<FullCsrOntology>
+    ) -> Vec<TermId> {
+        let mut arranger = HpoTermArranger::new(hpo);
+        let mut arranged_tids = arranger.arrange_term_ids(&terms.to_vec());
+        let frequency = |tid: &TermId| frequency_map.get(tid).copied().unwrap_or(0.0);
+        // sort_by is stable, so ties remain in DFS order
+        arranged_tids.sort_by(|a, b| frequency(b).total_cmp(&frequency(a)));
+        arranged_tids
+    }
+
+    /// Like [`Self::arrange_by_frequency`], but returning the ids and labels of the terms
+    pub fn arrange_terms_by_frequency(
+        &self,
+        terms: &[TermId],
+        frequency_map: &HashMap<TermId, f64>
+    ) -> std::result::Result<Vec<HpoTermDuplet>, String> {
+        let arranged_tids = Self::arrange_by_frequency(terms, frequency_map, self.hpo.clone());
+        self.to_duplets(arranged_tids)
+    }
+
+    fn to_duplets(&self, arranged_tids: Vec<TermId>) -> std::result::Result<Vec<HpoTermDuplet>, String> {
         let mut arranged_terms: Vec<HpoTermDuplet> = Vec::new();
         for tid in arranged_tids {
             match self.hpo.term_by_id(&tid) {
@@ -130,6 +168,48 @@ mod tests {
 
     use super::*;
 
+    fn tid(id: &str) -> TermId {
+        TermId::from_str(id).unwrap()
+    }
+
+    #[rstest]
+    fn test_arrange_by_frequency(hpo: Arc<FullCsrOntology>) {
+        // Arachnodactyly, Ventricular septal defect, Global developmental delay
+        let terms = vec![tid("HP:0001166"), tid("HP:0001629"), tid("HP:0001263")];
+        let frequency_map = HashMap::from([
+            (tid("HP:0001166"), 0.25),
+            (tid("HP:0001629"), 0.75),
+            (tid("HP:0001263"), 0.5),
+        ]);
+        let ordered = HpoTermArranger::arrange_by_frequency(&terms, &frequency_map, hpo);
+        assert_eq!(vec![tid("HP:0001629"), tid("HP:0001263"), tid("HP:0001166")], ordered);
+    }
+
+    #[rstest]
+    fn test_dfs_order_is_tiebreaker(hpo: Arc<FullCsrOntology>) {
+        let terms = vec![tid("HP:0001166"), tid("HP:0001629"), tid("HP:0001263"), tid("HP:0004322")];
+        let dfs_order = HpoTermArranger::new(hpo.clone()).arrange_term_ids(&terms);
+        // all terms have the same frequency
+        let frequency_map: HashMap<TermId, f64> = terms.iter().map(|t| (t.clone(), 0.5)).collect();
+        let ordered = HpoTermArranger::arrange_by_frequency(&terms, &frequency_map, hpo.clone());
+        assert_eq!(dfs_order, ordered);
+        // the most frequent term comes first, the others keep their DFS order
+        let mut frequency_map = frequency_map;
+        frequency_map.insert(tid("HP:0004322"), 0.9);
+        let ordered = HpoTermArranger::arrange_by_frequency(&terms, &frequency_map, hpo);
+        let mut expected = vec![tid("HP:0004322")];
+        expected.extend(dfs_order.into_iter().filter(|t| t != &tid("HP:0004322")));
+        assert_eq!(expected, ordered);
+    }
+
+    #[rstest]
+    fn test_missing_frequency_is_last(hpo: Arc<FullCsrOntology>) {
+        let terms = vec![tid("HP:0001166"), tid("HP:0001629")];
+        let frequency_map = HashMap::from([(tid("HP:0001166"), 0.1)]);
+        let ordered = HpoTermArranger::arrange_by_frequency(&terms, &frequency_map, hpo);
+        assert_eq!(vec![tid("HP:0001166"), tid("HP:0001629")], ordered);
+    }
+
     #[rstest]
     #[ignore]
     fn test_term_rerrange(hpo: Arc<FullCsrOntology>) {
@@ -109,13 +109,15 @@ impl HpoUtil {
                 Ok(tid) => tid,
                 Err(_) => { return Err(format!("Failed to parse TermId from row2: {} (converting duplet: {:?})", duplet.hpo_id(), duplet)); },
             };
-            if let Some(term) = self.hpo.term_by_id(&tid) {
+            let term = self.hpo.term_by_id(&tid);
+            tracing::debug!(hpo_id = %tid, result = term.map(|t| t.name()), "HPO lookup");
+            if let Some(term) = term {
                 if term.name() != duplet.hpo_label() {
                     // This usually happens if the name of the HPO term was changed after the Excel template
                     // was created. If the user chooses to update labels, this is fixed automatically here.
                     let err_str = format!("{}: expected '{}' but got '{}'", duplet.hpo_id(), term.name(), duplet.hpo_label());
                     updated_duplets.push(HpoTermDuplet::new(term.name(), tid.to_string()));
-                    print!("[INFO] Updating HPO label {err_str}"); // Output to shell, this is expected behavior.
+                    tracing::info!("Updating HPO label {err_str}");
                     // consider sending a signal to update user
                 } else {
                     updated_duplets.push(HpoTermDuplet::new(term.name(), tid.to_string()));
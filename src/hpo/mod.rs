@@ -1,18 +1,27 @@
 //! HPO module
 //! 
 //! Convenience functions for working with HPO data
-use std::{collections::HashMap, str::FromStr, sync::Arc};
+use std::{collections::{BTreeMap, HashMap, HashSet}, path::Path, str::FromStr, sync::Arc};
 
-use ontolius::{ontology::csr::FullCsrOntology, TermId};
+use ontolius::{ontology::{csr::FullCsrOntology, HierarchyWalks, OntologyTerms}, Identified, TermId};
 
-use crate::{dto::{cohort_dto::CohortData, hpo_term_dto::{HpoTermData, HpoTermDuplet}}, hpo::{hpo_term_arranger::HpoTermArranger, hpo_util::HpoUtil}};
+use crate::{dto::{cohort_dto::{CohortData, DiseaseData}, hpo_term_dto::{HpoTermData, HpoTermDuplet}}, hpo::{hpo_similarity::HpoSimilarity, hpo_term_arranger::HpoTermArranger, hpo_util::HpoUtil}};
+use crate::hpoa::hpoa_lookup;
 
+mod hpo_cooccurrence;
+mod hpo_frequency;
 mod hpo_hierarchizer;
+mod hpo_lookup;
+mod hpo_navigation;
+mod hpo_similarity;
 mod hpo_term_arranger;
 mod hpo_util;
 mod hpo_modifiers;
 
-pub use hpo_modifiers::get_modifiers;
+pub use hpo_frequency::HpoTermFrequency;
+pub(crate) use hpo_frequency::count_observed_and_measured;
+pub use hpo_lookup::{HpoTermInfo, TermStatus};
+pub use hpo_modifiers::{get_modifiers, validate_modifier_ids};
 
 /// Arrange a list of HPO terms into a curator-friendly order using depth-first search (DFS).
 ///
@@ -70,6 +79,31 @@ pub fn hpo_terms_to_dfs_order_duplets(
 
 
 
+/// Arrange a list of HPO terms by decreasing frequency, returning term duplets (ID + label).
+///
+/// Terms with the same frequency are arranged in DFS order (see [`hpo_terms_to_dfs_order`]), so that
+/// related terms stay together. Terms that are missing from `frequency_map` are placed last.
+///
+/// # Arguments
+///
+/// * `hpo` - A reference to the full HPO ontology.
+/// * `terms` - The list of HPO term IDs to be arranged.
+/// * `frequency_map` - The frequency of each term, e.g., the fraction of individuals with the term.
+///
+/// # Returns
+///
+/// * `Ok(Vec<HpoTermDuplet>)` — the input terms arranged by frequency, with both ID and label.
+/// * `Err(String)` — if any of the term IDs cannot be resolved in the ontology.
+pub fn hpo_terms_to_frequency_order_duplets(
+    hpo: Arc<FullCsrOntology>,
+    terms: &[TermId],
+    frequency_map: &HashMap<TermId, f64>)
+-> Result<Vec<HpoTermDuplet>, String> {
+    let term_arranger = HpoTermArranger::new(hpo);
+    term_arranger.arrange_terms_by_frequency(terms, frequency_map)
+}
+
+
 /// Build a mapping from HPO term IDs to their human-readable labels.
 ///
 /// This function takes a list of [`HpoTermData`] DTOs and returns a
@@ -235,4 +269,315 @@ pub fn get_hpo_terms_by_toplevel(
     hpo: Arc<FullCsrOntology>
 ) -> Result<HashMap<String, Vec<HpoTermDuplet>>, String> {
     hpo_hierarchizer::get_hpo_terms_by_toplevel(cohort_dto.hpo_headers, hpo)
-}
\ No newline at end of file
+}
+
+/// Groups the HPO terms of a cohort by organ system, ordered by the name of the organ system.
+///
+/// The organ systems are the top-level terms of the HPO (direct children of
+/// *HP:0000118 Phenotypic abnormality*), as in [`get_hpo_terms_by_toplevel`]. Within each
+/// organ system, the terms are kept in the order of the cohort's HPO columns. A term can be
+/// listed under more than one organ system (e.g., *Arachnodactyly* is both a skeletal and a limb abnormality).
+///
+/// # Arguments
+///
+/// * `cohort` — The cohort whose HPO headers will be grouped.
+/// * `hpo` — A shared [`FullCsrOntology`] instance.
+///
+/// # Returns
+///
+/// A [`BTreeMap`] with the label of the organ system as key and the cohort terms that
+/// descend from it as value.
+///
+/// # Errors
+///
+/// Returns an error if an HPO id of the cohort is malformed or cannot be found in the ontology.
+pub fn group_hpo_terms_by_organ_system(
+    cohort: &CohortData,
+    hpo: Arc<FullCsrOntology>
+) -> Result<BTreeMap<String, Vec<HpoTermDuplet>>, String> {
+    let by_top_level = hpo_hierarchizer::get_hpo_terms_by_toplevel(cohort.hpo_headers.clone(), hpo)?;
+    Ok(by_top_level.into_iter().collect())
+}
+
+
+/// Compute the phenotypic heterogeneity of a cohort.
+///
+/// Each individual is represented by its observed HPO terms (including terms with an onset age)
+/// together with their ancestors below *Phenotypic abnormality*. The similarity of two individuals
+/// is the Jaccard index of these sets, and the heterogeneity is the mean pairwise dissimilarity
+/// (1 - similarity) across all pairs of individuals.
+///
+/// # Arguments
+///
+/// * `cohort` — The cohort to be analyzed.
+/// * `hpo` — A shared [`FullCsrOntology`] instance.
+///
+/// # Returns
+///
+/// A value between 0 (all individuals have the same features) and 1 (no two individuals share
+/// any feature). Individuals without observed terms are ignored; if fewer than two individuals
+/// remain, the function returns 0.
+pub fn compute_phenotypic_heterogeneity(
+    cohort: &CohortData,
+    hpo: Arc<FullCsrOntology>
+) -> f64 {
+    HpoSimilarity::new(cohort, hpo).heterogeneity()
+}
+
+/// Find pairs of individuals with similar phenotypes.
+///
+/// Uses the same similarity measure as [`compute_phenotypic_heterogeneity`].
+///
+/// # Arguments
+///
+/// * `cohort` — The cohort to be analyzed.
+/// * `hpo` — A shared [`FullCsrOntology`] instance.
+/// * `threshold` — Minimum similarity (between 0 and 1) for a pair to be reported.
+///
+/// # Returns
+///
+/// A vector of `(id_a, id_b, score)` tuples with the individual identifiers and their similarity,
+/// sorted by descending similarity.
+pub fn find_phenotypically_similar_individuals(
+    cohort: &CohortData,
+    hpo: Arc<FullCsrOntology>,
+    threshold: f64
+) -> Vec<(String, String, f64)> {
+    HpoSimilarity::new(cohort, hpo).similar_pairs(threshold)
+}
+
+
+/// Return the N most frequent HPO terms of a cohort.
+///
+/// The frequency of a term is the percentage of ascertained individuals (term observed or excluded)
+/// in whom the term was observed. Terms that were ascertained in fewer than `min_ascertainment`
+/// individuals are excluded, because their frequencies are not informative.
+///
+/// # Arguments
+///
+/// * `cohort` — The cohort to be analyzed.
+/// * `n` — Maximum number of terms to return.
+/// * `min_ascertainment` — Minimum number of ascertained individuals for a term to be ranked.
+///
+/// # Returns
+///
+/// Up to `n` pairs of term and [`HpoTermFrequency`], sorted by descending frequency. Ties are broken by
+/// the number of ascertained individuals (more first) and then by HPO id, so the ranking is stable.
+pub fn top_n_hpo_terms(
+    cohort: &CohortData,
+    n: usize,
+    min_ascertainment: usize
+) -> Vec<(HpoTermDuplet, HpoTermFrequency)> {
+    hpo_frequency::top_n(cohort, n, min_ascertainment)
+}
+
+/// Return the N least frequent HPO terms of a cohort.
+///
+/// Counterpart of [`top_n_hpo_terms`]; the terms are sorted by ascending frequency with the same tie-breaking rules.
+pub fn bottom_n_hpo_terms(
+    cohort: &CohortData,
+    n: usize,
+    min_ascertainment: usize
+) -> Vec<(HpoTermDuplet, HpoTermFrequency)> {
+    hpo_frequency::bottom_n(cohort, n, min_ascertainment)
+}
+
+/// Pairs of HPO terms that are observed together in the individuals of a cohort more often than expected by chance.
+///
+/// For each pair of HPO columns, the p-value is the probability of k or more co-occurrences under the
+/// hypergeometric distribution, given N individuals, n with term A, and m with term B. The p-values are
+/// Bonferroni-corrected for the number of tested pairs. Pairs in which one term is an ancestor of the other
+/// are not tested.
+///
+/// # Arguments
+///
+/// * `cohort` — The cohort to be analyzed.
+/// * `hpo` — A reference to the full HPO ontology.
+///
+/// # Returns
+///
+/// The significant pairs (corrected p < 0.05) with their corrected p-values; the terms of each pair are in
+/// the order of the HPO columns.
+pub fn compute_co_occurrence_pvalues(
+    cohort: &CohortData,
+    hpo: Arc<FullCsrOntology>
+) -> HashMap<(HpoTermDuplet, HpoTermDuplet), f64> {
+    hpo_cooccurrence::co_occurrence_pvalues(cohort, hpo)
+}
+
+
+/// Retrieve the label and synonyms of an HPO term by its identifier.
+///
+/// # Arguments
+///
+/// * `hpo` — A shared [`FullCsrOntology`] instance.
+/// * `hpo_id` — The primary or an alternative identifier of the term, e.g., `HP:0001166`.
+///
+/// # Returns
+///
+/// The primary identifier, label, and synonyms of the term as [`HpoTermInfo`].
+///
+/// # Errors
+///
+/// Returns an error if the identifier is malformed or not found in the ontology.
+pub fn get_hpo_term_info(hpo: Arc<FullCsrOntology>, hpo_id: &str) -> Result<HpoTermInfo, String> {
+    hpo_lookup::lookup_term(&hpo, hpo_id)
+}
+
+/// Look up an HPO term by its identifier or by its exact primary label.
+///
+/// The query is first interpreted as an identifier; an alternative (obsolete) identifier is
+/// redirected to the current term. Otherwise, the term whose label equals the query is returned.
+///
+/// # Arguments
+///
+/// * `hpo` — A shared [`FullCsrOntology`] instance.
+/// * `query` — An identifier such as `HP:0001166` or a label such as `Arachnodactyly`.
+///
+/// # Returns
+///
+/// The current identifier and label of the term, or `None` if no term matches.
+pub fn lookup_hpo_term(hpo: Arc<FullCsrOntology>, query: &str) -> Option<HpoTermDuplet> {
+    hpo_lookup::lookup_by_id_or_label(&hpo, query)
+}
+
+/// Same as [`lookup_hpo_term`], but returns an error if no term matches.
+pub fn lookup_hpo_term_or_error(hpo: Arc<FullCsrOntology>, query: &str) -> Result<HpoTermDuplet, String> {
+    lookup_hpo_term(hpo, query).ok_or_else(|| format!("Could not find HPO term with id or label '{query}'"))
+}
+
+/// Search for HPO terms whose label or one of whose synonyms starts with `query` (case-insensitive).
+///
+/// Terms that match by label come first, followed by terms that match only by synonym;
+/// within each group, the terms are sorted by label.
+pub fn search_hpo_terms(hpo: Arc<FullCsrOntology>, query: &str) -> Vec<HpoTermInfo> {
+    hpo_lookup::search_terms(&hpo, query)
+}
+
+/// Return up to `max_results` terms whose primary label starts with `prefix` (case-insensitive), sorted by label.
+///
+/// Intended for real-time autocompletion: the search uses a sorted index of the labels and synonyms that is
+/// built at the first call and cached for subsequent calls with the same HPO version.
+pub fn search_hpo_terms_by_prefix(hpo: Arc<FullCsrOntology>, prefix: &str, max_results: usize) -> Vec<HpoTermDuplet> {
+    hpo_lookup::search_by_prefix(&hpo, prefix, max_results, false)
+}
+
+/// Same as [`search_hpo_terms_by_prefix`], but terms whose synonyms start with `prefix` also match.
+/// Terms that match by label are listed before terms that match only by synonym.
+pub fn search_hpo_terms_by_prefix_or_synonym(hpo: Arc<FullCsrOntology>, prefix: &str, max_results: usize) -> Vec<HpoTermDuplet> {
+    hpo_lookup::search_by_prefix(&hpo, prefix, max_results, true)
+}
+
+
+/// The direct children of an HPO term (empty for leaf terms).
+pub fn get_children(hpo: Arc<FullCsrOntology>, term_id: &TermId) -> Vec<TermId> {
+    hpo_navigation::children(&hpo, term_id)
+}
+
+/// The direct parents of an HPO term (empty for the root term *HP:0000001 All*).
+pub fn get_parents(hpo: Arc<FullCsrOntology>, term_id: &TermId) -> Vec<TermId> {
+    hpo_navigation::parents(&hpo, term_id)
+}
+
+/// The terms that share at least one parent with an HPO term, excluding the term itself.
+pub fn get_siblings(hpo: Arc<FullCsrOntology>, term_id: &TermId) -> Vec<TermId> {
+    hpo_navigation::siblings(&hpo, term_id)
+}
+
+/// All descendants (transitive children) of an HPO term, in depth-first order and without duplicates.
+pub fn get_descendants(hpo: Arc<FullCsrOntology>, term_id: &TermId) -> Vec<TermId> {
+    hpo_navigation::descendants(&hpo, term_id)
+}
+
+/// The depth of an HPO term, i.e., the number of edges on the shortest path from the root *HP:0000001 All*
+/// to the term (0 for the root, 1 for *HP:0000118 Phenotypic abnormality*).
+pub fn get_term_depth(hpo: Arc<FullCsrOntology>, term_id: &TermId) -> usize {
+    hpo_navigation::min_depth(&hpo, term_id)
+}
+
+/// The number of edges on the longest path from the root to an HPO term.
+/// Terms with several parents can be much deeper than [`get_term_depth`] suggests.
+pub fn get_term_max_depth(hpo: Arc<FullCsrOntology>, term_id: &TermId) -> usize {
+    hpo_navigation::max_depth(&hpo, term_id)
+}
+
+/// True if the HPO term has no children.
+pub fn is_leaf_term(hpo: Arc<FullCsrOntology>, term_id: &TermId) -> bool {
+    hpo_navigation::is_leaf(&hpo, term_id)
+}
+
+/// Check whether an HPO identifier is current, was replaced by another identifier, or is unknown.
+///
+/// Used when importing legacy data: a replaced identifier (an alternative id of a current term) can be
+/// updated automatically, whereas a deleted (or malformed) identifier needs the attention of the curator.
+pub fn get_term_status(hpo: Arc<FullCsrOntology>, id: &str) -> TermStatus {
+    hpo_lookup::term_status(&hpo, id)
+}
+
+
+/// The diseases annotated with an HPO term in an HPOA file (e.g., `phenotype.hpoa`), for differential diagnosis.
+///
+/// Only positive annotations of phenotypic abnormalities count (see [`get_hpo_terms_for_disease`]); annotations
+/// with an obsolete identifier of the term are included. If `include_ancestors` is true, diseases annotated with a
+/// more general term (an ancestor of `hpo_term`, e.g., *Long fingers* for *Arachnodactyly*) are also returned.
+///
+/// # Returns
+///
+/// * `Ok(Vec<DiseaseData>)` with the id and label of each disease (without genes), in the order of the file.
+/// * `Err(String)` if the term is not in the ontology or the HPOA file cannot be read or parsed.
+pub fn get_diseases_for_hpo_term(
+    hpo: Arc<FullCsrOntology>,
+    hpo_term: &TermId,
+    hpoa_path: &Path,
+    include_ancestors: bool
+) -> Result<Vec<DiseaseData>, String> {
+    let term = hpo.term_by_id(hpo_term)
+        .ok_or_else(|| format!("Could not find HPO term {hpo_term}"))?;
+    let mut query_ids: HashSet<&TermId> = HashSet::from([term.identifier()]);
+    if include_ancestors {
+        query_ids.extend(hpo.iter_ancestor_ids(term.identifier()));
+    }
+    let associations = read_hpoa_associations(hpoa_path)?;
+    Ok(hpoa_lookup::diseases_with_annotation(&associations, |hpo_id| {
+        TermId::from_str(hpo_id).ok()
+            .and_then(|tid| hpo.term_by_id(&tid))
+            .is_some_and(|t| query_ids.contains(t.identifier()))
+    }))
+}
+
+/// The HPO terms annotated to a disease (e.g., OMIM:154700) in an HPOA file, in the order of the file.
+///
+/// Negated (NOT) annotations and annotations of modes of inheritance, onset, or clinical course are skipped.
+/// Obsolete identifiers are replaced by the current term.
+///
+/// # Returns
+///
+/// * `Ok(Vec<HpoTermDuplet>)` with each term listed once.
+/// * `Err(String)` if the disease has no phenotype annotations in the file, the file cannot be read or parsed,
+///   or an annotated term is not in the ontology.
+pub fn get_hpo_terms_for_disease(
+    hpo: Arc<FullCsrOntology>,
+    disease_id: &str,
+    hpoa_path: &Path
+) -> Result<Vec<HpoTermDuplet>, String> {
+    let associations = read_hpoa_associations(hpoa_path)?;
+    let mut terms: Vec<HpoTermDuplet> = Vec::new();
+    for assoc in associations.iter().filter(|a| a.disease_id == disease_id) {
+        let duplet = hpo_lookup::lookup_by_id_or_label(&hpo, &assoc.hpo_id)
+            .ok_or_else(|| format!("Unknown HPO term '{}' annotated to {disease_id}", assoc.hpo_id))?;
+        if !terms.contains(&duplet) {
+            terms.push(duplet);
+        }
+    }
+    if terms.is_empty() {
+        return Err(format!("No phenotype annotations for {disease_id} in {}", hpoa_path.display()));
+    }
+    Ok(terms)
+}
+
+fn read_hpoa_associations(hpoa_path: &Path) -> Result<Vec<hpoa_lookup::HpoaAssociation>, String> {
+    let contents = std::fs::read_to_string(hpoa_path)
+        .map_err(|e| format!("Could not read HPOA file {}: {}", hpoa_path.display(), e))?;
+    hpoa_lookup::parse_associations(&contents)
+}
+
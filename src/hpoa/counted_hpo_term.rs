@@ -12,7 +12,9 @@ pub struct CountedHpoTerm {
     hpo_label: String,
     numerator: usize,
     denominator: usize,
-    pmid: String
+    pmid: String,
+    /// HPO frequency class of a population-level annotation, which takes precedence over the counts
+    frequency_class: Option<String>,
 }
 
 impl CountedHpoTerm {
@@ -26,7 +28,8 @@ impl CountedHpoTerm {
             hpo_label: label.to_string(), 
             numerator: num, 
             denominator: denom,
-            pmid: pmid.to_string()
+            pmid: pmid.to_string(),
+            frequency_class: None,
         }
     }
 
@@ -45,6 +48,7 @@ impl CountedHpoTerm {
             CellValueInner::Excluded => { self.increment_excluded(); },
             CellValueInner::Na => {},
             CellValueInner::OnsetAge(_) => { self.increment_observed(); }
+            CellValueInner::HpoFrequency(freq) => { self.frequency_class = Some(freq.clone()); }
         }
     }
 
@@ -82,7 +86,16 @@ impl CountedHpoTerm {
         &self.pmid
     }
 
+    /// True if this is a population-level annotation with an HPO frequency class
+    pub fn has_frequency_class(&self) -> bool {
+        self.frequency_class.is_some()
+    }
+
+    /// The frequency column of the HPOA file: n/m, or the HPO frequency class of a population-level annotation
     pub fn freq_string(&self) -> String {
-        format!("{}/{}", self.numerator(), self.denominator())
+        match &self.frequency_class {
+            Some(freq) => freq.clone(),
+            None => format!("{}/{}", self.numerator(), self.denominator()),
+        }
     }
 }
\ No newline at end of file
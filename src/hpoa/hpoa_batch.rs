@@ -0,0 +1,69 @@
+//! HPOA export of all cohorts of a repository
+//!
+//! Each Mendelian cohort file (`*_individuals.json`) of the repository is converted to an HPOA table. Several
+//! cohorts may describe the same disease (e.g., different publications); their tables are merged with the same
+//! rules as [`super::merge_hpoa_files`], so that there is one table per disease. Unlike there, rows of different
+//! publications are kept, because the table of each cohort has one row per publication and HPO term.
+
+use std::{collections::BTreeMap, path::Path, sync::Arc};
+
+use ontolius::ontology::csr::FullCsrOntology;
+
+use crate::hpoa::{hpoa_merge, hpoa_table::HpoaTable};
+
+
+/// The lines (header first) of the HPOA table of each disease of the repository, sorted by disease ID.
+/// Cohorts that are not Mendelian are skipped. `progress_cb` is called as (processed, total) after each cohort.
+pub(super) fn hpoa_lines_by_disease<F>(
+    store_dir: &Path,
+    hpo: Arc<FullCsrOntology>,
+    biocurator: &str,
+    mut progress_cb: F
+) -> Result<BTreeMap<String, Vec<String>>, String>
+where F: FnMut(u32, u32) {
+    let cohorts = crate::repo::load_repository_cohorts(&store_dir.to_path_buf())?;
+    let total = cohorts.len() as u32;
+    let mut tables: BTreeMap<String, Vec<String>> = BTreeMap::new();
+    for (i, cohort) in cohorts.into_iter().enumerate() {
+        let acronym = cohort.acronym();
+        if !cohort.is_mendelian() {
+            tracing::warn!(cohort = %acronym, cohort_type = ?cohort.cohort_type, "Skipping HPOA export of non-Mendelian cohort");
+        } else {
+            let disease_id = cohort.disease_list
+                .first()
+                .map(|d| d.disease_id.clone())
+                .ok_or_else(|| format!("Cohort {acronym} has no disease"))?;
+            let hpoa = HpoaTable::new(cohort, hpo.clone(), biocurator)
+                .map_err(|e| format!("{e}: cohort {acronym}"))?;
+            hpoa.validate_evidence_codes()
+                .map_err(|e| format!("{e}: cohort {acronym}"))?;
+            let contents: Vec<String> = hpoa.get_dataframe().iter().map(|row| row.join("\t")).collect();
+            tables.entry(disease_id).or_default().push(contents.join("\n"));
+        }
+        progress_cb(i as u32 + 1, total);
+    }
+    tables
+        .into_iter()
+        .map(|(disease_id, contents)| {
+            let lines = hpoa_merge::merge_hpoa(&contents, true).map_err(|e| format!("{e}: {disease_id}"))?;
+            Ok((disease_id, lines))
+        })
+        .collect()
+}
+
+/// Name of the HPOA file of a disease, e.g., `OMIM-154700.tab` (the convention of the small files)
+pub(super) fn hpoa_file_name(disease_id: &str) -> String {
+    format!("{}.tab", disease_id.replace(':', "-"))
+}
+
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use rstest::rstest;
+
+    #[rstest]
+    fn test_hpoa_file_name() {
+        assert_eq!("OMIM-154700.tab", hpoa_file_name("OMIM:154700"));
+    }
+}
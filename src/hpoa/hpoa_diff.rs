@@ -0,0 +1,135 @@
+//! Comparison of two versions of an HPOA file
+//!
+//! Rows are matched by disease, HPO term, and publication. A matched row is modified if any other
+//! field differs; the biocuration field is ignored because it contains the date of the export.
+
+use std::collections::HashMap;
+
+use serde::{Deserialize, Serialize};
+
+use crate::hpoa::hpoa_import::HpoaColumns;
+
+
+/// Differences between an old and a new version of an HPOA file
+#[derive(Clone, Debug, Default, Deserialize, Serialize, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct HpoaDiff {
+    /// Number of rows that are only in the new file
+    pub added_rows: usize,
+    /// Number of rows that are only in the old file
+    pub removed_rows: usize,
+    /// Rows whose content changed (old row, new row), in the order of the old file
+    pub modified_rows: Vec<(String, String)>,
+}
+
+impl HpoaDiff {
+    pub fn is_empty(&self) -> bool {
+        self.added_rows == 0 && self.removed_rows == 0 && self.modified_rows.is_empty()
+    }
+}
+
+
+/// One data row of an HPOA file
+struct HpoaLine<'a> {
+    /// disease, HPO term, publication, and the number of previous rows with the same values
+    key: (&'a str, &'a str, &'a str, usize),
+    /// the row without the biocuration field
+    content: Vec<&'a str>,
+    line: &'a str,
+}
+
+fn parse_lines(contents: &str) -> Result<Vec<HpoaLine<'_>>, String> {
+    let mut columns: Option<HpoaColumns> = None;
+    let mut occurrences: HashMap<(&str, &str, &str), usize> = HashMap::new();
+    let mut lines = Vec::new();
+    for (i, line) in contents.lines().enumerate() {
+        if line.trim().is_empty() {
+            continue;
+        }
+        if HpoaColumns::is_header(line) {
+            columns = Some(HpoaColumns::from_header(line)?);
+            continue;
+        }
+        if line.starts_with('#') {
+            continue;
+        }
+        let cols = columns.as_ref().ok_or_else(|| "HPOA file does not have a header line".to_string())?;
+        let fields: Vec<&str> = line.split('\t').collect();
+        if fields.len() != cols.n_columns {
+            return Err(format!("HPOA line {} has {} fields but the header has {}", i + 1, fields.len(), cols.n_columns));
+        }
+        let publication = cols.publication.map(|j| fields[j]).unwrap_or_default();
+        let id = (fields[cols.disease_id], fields[cols.phenotype_id], publication);
+        let n = occurrences.entry(id).or_insert(0);
+        let key = (id.0, id.1, id.2, *n);
+        *n += 1;
+        let content = fields.iter().enumerate()
+            .filter(|(j, _)| Some(*j) != cols.biocuration)
+            .map(|(_, f)| *f)
+            .collect();
+        lines.push(HpoaLine { key, content, line });
+    }
+    Ok(lines)
+}
+
+/// Compare the contents of two HPOA files
+pub(crate) fn diff_hpoa(old_contents: &str, new_contents: &str) -> Result<HpoaDiff, String> {
+    let old_lines = parse_lines(old_contents)?;
+    let new_lines = parse_lines(new_contents)?;
+    let new_by_key: HashMap<_, &HpoaLine> = new_lines.iter().map(|l| (l.key, l)).collect();
+    let mut diff = HpoaDiff::default();
+    let mut n_matched = 0;
+    for old in &old_lines {
+        match new_by_key.get(&old.key) {
+            Some(new) => {
+                n_matched += 1;
+                if old.content != new.content {
+                    diff.modified_rows.push((old.line.to_string(), new.line.to_string()));
+                }
+            }
+            None => diff.removed_rows += 1,
+        }
+    }
+    diff.added_rows = new_lines.len() - n_matched;
+    Ok(diff)
+}
+
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use rstest::rstest;
+
+    const HEADER: &str = "#diseaseID\tdiseaseName\tphenotypeID\tphenotypeName\tfrequency\tpublication\tevidence\tbiocuration";
+
+    fn hpoa(rows: &[&str]) -> String {
+        let mut lines = vec![HEADER];
+        lines.extend_from_slice(rows);
+        lines.join("\n")
+    }
+
+    const ARACHNODACTYLY: &str = "OMIM:154700\tMarfan syndrome\tHP:0001166\tArachnodactyly\t2/3\tPMID:29999999\tPCS\tORCID:0000-0002-0736-9199[2025-01-09]";
+    const SHORT_STATURE: &str = "OMIM:154700\tMarfan syndrome\tHP:0004322\tShort stature\t1/3\tPMID:29999999\tPCS\tORCID:0000-0002-0736-9199[2025-01-09]";
+
+    #[rstest]
+    fn test_identical_except_biocuration() {
+        let new_row = ARACHNODACTYLY.replace("2025-01-09", "2025-06-01");
+        let diff = diff_hpoa(&hpoa(&[ARACHNODACTYLY]), &hpoa(&[&new_row])).unwrap();
+        assert!(diff.is_empty());
+    }
+
+    #[rstest]
+    fn test_added_removed_modified() {
+        let modified = ARACHNODACTYLY.replace("2/3", "3/3");
+        let ectopia_lentis = "OMIM:154700\tMarfan syndrome\tHP:0001083\tEctopia lentis\t1/3\tPMID:29999999\tPCS\tORCID:0000-0002-0736-9199[2025-01-09]";
+        let diff = diff_hpoa(&hpoa(&[ARACHNODACTYLY, SHORT_STATURE]), &hpoa(&[&modified, ectopia_lentis])).unwrap();
+        assert_eq!(1, diff.added_rows);
+        assert_eq!(1, diff.removed_rows);
+        assert_eq!(vec![(ARACHNODACTYLY.to_string(), modified)], diff.modified_rows);
+    }
+
+    #[rstest]
+    fn test_missing_header() {
+        assert!(diff_hpoa(ARACHNODACTYLY, &hpoa(&[ARACHNODACTYLY])).is_err());
+    }
+}
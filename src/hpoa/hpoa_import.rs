@@ -0,0 +1,217 @@
+//! Import of HPO annotation (HPOA) files
+//!
+//! We read the small-file HPOA format written by [`super::write_hpoa_tsv`] (header `#diseaseID`, `phenotypeID`, ...)
+//! as well as the format of the `phenotype.hpoa` release file (header `database_id`, `hpo_id`, ...).
+//! Each disease becomes a cohort with one row per publication, so that the annotations can be curated further.
+//! Mode of inheritance annotations are added to the disease data; onset annotations are skipped.
+
+use std::{collections::HashMap, sync::Arc};
+
+use ontolius::ontology::{csr::FullCsrOntology, MetadataAware};
+
+use crate::age::hpo_age::HpoTermAge;
+use crate::dto::cohort_dto::{CohortData, CohortType, DiseaseData, IndividualData, ModeOfInheritance, RowData};
+use crate::dto::hpo_term_dto::{CellValue, HpoTermDuplet};
+use crate::hpoa::hpoa_table_row::VALID_MODES_OF_INHERITANCE;
+
+const NOT_AVAILABLE: &str = "na";
+
+
+/// Column indices of an HPOA file
+pub(super) struct HpoaColumns {
+    pub disease_id: usize,
+    pub disease_name: usize,
+    pub phenotype_id: usize,
+    pub negation: Option<usize>,
+    pub frequency: Option<usize>,
+    pub publication: Option<usize>,
+    pub aspect: Option<usize>,
+    pub evidence: Option<usize>,
+    pub biocuration: Option<usize>,
+    pub n_columns: usize,
+}
+
+impl HpoaColumns {
+    pub fn from_header(header: &str) -> Result<Self, String> {
+        let names: Vec<&str> = header.trim_start_matches('#').split('\t').map(str::trim).collect();
+        let find = |alternatives: &[&str]| names.iter().position(|n| alternatives.contains(n));
+        let required = |alternatives: &[&str]| find(alternatives)
+            .ok_or_else(|| format!("HPOA header lacks the {} column", alternatives[0]));
+        Ok(Self {
+            disease_id: required(&["diseaseID", "database_id"])?,
+            disease_name: required(&["diseaseName", "disease_name"])?,
+            phenotype_id: required(&["phenotypeID", "hpo_id"])?,
+            negation: find(&["negation", "qualifier"]),
+            frequency: find(&["frequency"]),
+            publication: find(&["publication", "reference"]),
+            aspect: find(&["aspect"]),
+            evidence: find(&["evidence"]),
+            biocuration: find(&["biocuration"]),
+            n_columns: names.len(),
+        })
+    }
+
+    pub fn is_header(line: &str) -> bool {
+        let first = line.trim_start_matches('#').split('\t').next().unwrap_or_default();
+        first == "diseaseID" || first == "database_id"
+    }
+}
+
+
+/// The annotations of one disease, with publications and HPO terms in the order of the file
+#[derive(Default)]
+struct DiseaseAnnotations {
+    disease: Option<DiseaseData>,
+    hpo_terms: Vec<HpoTermDuplet>,
+    publications: Vec<String>,
+    /// (publication, HPO id) -> observed (true) or excluded (false)
+    annotations: HashMap<(String, String), bool>,
+}
+
+impl DiseaseAnnotations {
+    fn add_annotation(&mut self, publication: &str, term: HpoTermDuplet, observed: bool) {
+        if !self.publications.iter().any(|p| p == publication) {
+            self.publications.push(publication.to_string());
+        }
+        let key = (publication.to_string(), term.hpo_id().to_string());
+        if !self.hpo_terms.contains(&term) {
+            self.hpo_terms.push(term);
+        }
+        // an observation takes precedence over an exclusion of the same term in the same publication
+        *self.annotations.entry(key).or_insert(observed) |= observed;
+    }
+
+    fn into_cohort(self, hpo: Arc<FullCsrOntology>) -> Result<CohortData, String> {
+        let disease = self.disease.ok_or_else(|| "Missing disease data".to_string())?;
+        let hpo_headers = crate::hpo::arrange_hpo_duplets(hpo.clone(), &self.hpo_terms)?;
+        let mut rows = Vec::new();
+        for publication in &self.publications {
+            let hpo_data = hpo_headers
+                .iter()
+                .map(|term| match self.annotations.get(&(publication.clone(), term.hpo_id().to_string())) {
+                    Some(true) => CellValue::observed(),
+                    Some(false) => CellValue::excluded(),
+                    None => CellValue::na(),
+                })
+                .collect();
+            rows.push(RowData {
+                individual_data: IndividualData::new(publication, "", publication, "imported from HPOA",
+                    NOT_AVAILABLE, NOT_AVAILABLE, NOT_AVAILABLE, "U"),
+                disease_id_list: vec![disease.disease_id.clone()],
+                allele_count_map: HashMap::new(),
+                hpo_data,
+            });
+        }
+        Ok(CohortData {
+            cohort_type: CohortType::Mendelian,
+            disease_list: vec![disease],
+            hpo_headers,
+            rows,
+            hgvs_variants: HashMap::new(),
+            structural_variants: HashMap::new(),
+            intergenic_variants: HashMap::new(),
+            phetools_schema_version: CohortData::get_phetools_schema_version(),
+            hpo_version: hpo.version().to_string(),
+            cohort_acronym: None,
+            curation_history: vec![],
+            trios: vec![],
+        })
+    }
+}
+
+
+/// Parse the contents of an HPOA file into one cohort per disease (in the order of the file)
+pub(crate) fn parse_hpoa(contents: &str, hpo: Arc<FullCsrOntology>) -> Result<Vec<CohortData>, String> {
+    let mut columns: Option<HpoaColumns> = None;
+    let mut diseases: Vec<DiseaseAnnotations> = Vec::new();
+    let mut disease_index: HashMap<String, usize> = HashMap::new();
+    for (i, line) in contents.lines().enumerate() {
+        if line.trim().is_empty() {
+            continue;
+        }
+        if HpoaColumns::is_header(line) {
+            columns = Some(HpoaColumns::from_header(line)?);
+            continue;
+        }
+        if line.starts_with('#') {
+            continue;
+        }
+        let cols = columns.as_ref().ok_or_else(|| "HPOA file does not have a header line".to_string())?;
+        let fields: Vec<&str> = line.split('\t').map(str::trim).collect();
+        if fields.len() != cols.n_columns {
+            return Err(format!("HPOA line {} has {} fields but the header has {}", i + 1, fields.len(), cols.n_columns));
+        }
+        let field = |idx: Option<usize>| idx.map(|j| fields[j]).unwrap_or_default();
+        let disease_id = fields[cols.disease_id];
+        let idx = *disease_index.entry(disease_id.to_string()).or_insert_with(|| {
+            diseases.push(DiseaseAnnotations::default());
+            diseases.len() - 1
+        });
+        let annotations = &mut diseases[idx];
+        let disease = annotations.disease
+            .get_or_insert_with(|| DiseaseData::new(disease_id, fields[cols.disease_name]));
+        let phenotype_id = fields[cols.phenotype_id];
+        let term = crate::hpo::lookup_hpo_term(hpo.clone(), phenotype_id)
+            .ok_or_else(|| format!("Unknown HPO term '{}' in HPOA line {}", phenotype_id, i + 1))?;
+        let publication = field(cols.publication);
+        let aspect = field(cols.aspect);
+        if aspect == "I" || VALID_MODES_OF_INHERITANCE.contains_key(term.hpo_id()) {
+            if !disease.mode_of_inheritance_list.iter().any(|moi| moi.hpo_id == term.hpo_id()) {
+                disease.mode_of_inheritance_list.push(ModeOfInheritance {
+                    hpo_id: term.hpo_id().to_string(),
+                    hpo_label: term.hpo_label().to_string(),
+                    citation: publication.split(';').next().unwrap_or_default().to_string(),
+                });
+            }
+            continue;
+        }
+        if aspect == "C" || HpoTermAge::is_valid(term.hpo_label()) {
+            tracing::debug!(disease_id, hpo_id = %term.hpo_id(), "Skipping onset annotation");
+            continue;
+        }
+        let observed = field(cols.negation) != "NOT" && !field(cols.frequency).starts_with("0/");
+        let publications: Vec<&str> = publication.split(';').map(str::trim).filter(|p| !p.is_empty()).collect();
+        if publications.is_empty() {
+            annotations.add_annotation(disease_id, term, observed);
+        } else {
+            for p in publications {
+                annotations.add_annotation(p, term.clone(), observed);
+            }
+        }
+    }
+    diseases.into_iter().map(|d| d.into_cohort(hpo.clone())).collect()
+}
+
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use rstest::rstest;
+    use crate::test_utils::fixtures::hpo;
+
+    #[rstest]
+    fn test_release_file_format(hpo: Arc<FullCsrOntology>) {
+        let contents = "#description: \"HPO annotations for rare diseases\"\n\
+            #version: 2025-03-03\n\
+            database_id\tdisease_name\tqualifier\thpo_id\treference\tevidence\tonset\tfrequency\tsex\tmodifier\taspect\tbiocuration\n\
+            OMIM:154700\tMarfan syndrome\t\tHP:0001166\tOMIM:154700\tTAS\t\t\t\t\tP\tHPO:curator[2009-02-17]\n\
+            OMIM:154700\tMarfan syndrome\tNOT\tHP:0001631\tOMIM:154700\tTAS\t\t\t\t\tP\tHPO:curator[2009-02-17]\n\
+            OMIM:154700\tMarfan syndrome\t\tHP:0000006\tOMIM:154700\tTAS\t\t\t\t\tI\tHPO:curator[2009-02-17]\n";
+        let cohorts = parse_hpoa(contents, hpo).unwrap();
+        assert_eq!(1, cohorts.len());
+        let cohort = &cohorts[0];
+        assert_eq!(1, cohort.disease_list[0].mode_of_inheritance_list.len());
+        assert_eq!(2, cohort.hpo_headers.len());
+        assert_eq!(1, cohort.rows.len());
+        assert_eq!("OMIM:154700", cohort.rows[0].individual_data.pmid);
+    }
+
+    #[rstest]
+    #[case::no_header("OMIM:154700\tMarfan syndrome\tHP:0001166\n")]
+    #[case::unknown_term("#diseaseID\tdiseaseName\tphenotypeID\nOMIM:154700\tMarfan syndrome\tHP:9999999\n")]
+    #[case::wrong_field_count("#diseaseID\tdiseaseName\tphenotypeID\nOMIM:154700\tHP:0001166\n")]
+    #[case::missing_column("#diseaseID\tphenotypeID\nOMIM:154700\tHP:0001166\n")]
+    fn test_invalid_hpoa(hpo: Arc<FullCsrOntology>, #[case] contents: &str) {
+        assert!(parse_hpoa(contents, hpo).is_err());
+    }
+}
@@ -0,0 +1,123 @@
+//! Lookup of disease–phenotype associations in HPO annotation (HPOA) files
+//!
+//! We read the same formats as [`super::hpoa_import`] (the small-file format and the `phenotype.hpoa` release file).
+//! Only positive annotations of phenotypic abnormalities are associations: negated (NOT) annotations and annotations
+//! of the other aspects (mode of inheritance, onset, clinical course) are skipped.
+
+use crate::dto::cohort_dto::DiseaseData;
+use crate::hpoa::hpoa_import::HpoaColumns;
+use crate::hpoa::hpoa_table_row::VALID_MODES_OF_INHERITANCE;
+
+
+/// A positive phenotype annotation of a disease
+#[derive(Clone, Debug, PartialEq)]
+pub(crate) struct HpoaAssociation {
+    pub disease_id: String,
+    pub disease_name: String,
+    pub hpo_id: String,
+}
+
+
+/// The positive phenotype annotations of an HPOA file, in the order of the file
+pub(crate) fn parse_associations(contents: &str) -> Result<Vec<HpoaAssociation>, String> {
+    let mut columns: Option<HpoaColumns> = None;
+    let mut associations = Vec::new();
+    for (i, line) in contents.lines().enumerate() {
+        if line.trim().is_empty() {
+            continue;
+        }
+        if HpoaColumns::is_header(line) {
+            columns = Some(HpoaColumns::from_header(line)?);
+            continue;
+        }
+        if line.starts_with('#') {
+            continue;
+        }
+        let cols = columns.as_ref().ok_or_else(|| "HPOA file does not have a header line".to_string())?;
+        let fields: Vec<&str> = line.split('\t').map(str::trim).collect();
+        if fields.len() != cols.n_columns {
+            return Err(format!("HPOA line {} has {} fields but the header has {}", i + 1, fields.len(), cols.n_columns));
+        }
+        let field = |idx: Option<usize>| idx.map(|j| fields[j]).unwrap_or_default();
+        let hpo_id = fields[cols.phenotype_id];
+        let aspect = field(cols.aspect);
+        let is_phenotype = if aspect.is_empty() {
+            // the small-file format has no aspect column, but modes of inheritance are annotated like phenotypes
+            !VALID_MODES_OF_INHERITANCE.contains_key(hpo_id)
+        } else {
+            aspect == "P"
+        };
+        if !is_phenotype || field(cols.negation) == "NOT" {
+            continue;
+        }
+        associations.push(HpoaAssociation {
+            disease_id: fields[cols.disease_id].to_string(),
+            disease_name: fields[cols.disease_name].to_string(),
+            hpo_id: hpo_id.to_string(),
+        });
+    }
+    Ok(associations)
+}
+
+/// The diseases with at least one annotation that satisfies `is_match` (each disease listed once, in the order of the file)
+pub(crate) fn diseases_with_annotation(
+    associations: &[HpoaAssociation],
+    is_match: impl Fn(&str) -> bool
+) -> Vec<DiseaseData> {
+    let mut diseases: Vec<DiseaseData> = Vec::new();
+    for assoc in associations.iter().filter(|a| is_match(&a.hpo_id)) {
+        if !diseases.iter().any(|d| d.disease_id == assoc.disease_id) {
+            diseases.push(DiseaseData::new(&assoc.disease_id, &assoc.disease_name));
+        }
+    }
+    diseases
+}
+
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use rstest::rstest;
+
+    const RELEASE_FILE: &str = "#version: 2025-03-03\n\
+        database_id\tdisease_name\tqualifier\thpo_id\treference\tevidence\tonset\tfrequency\tsex\tmodifier\taspect\tbiocuration\n\
+        OMIM:154700\tMarfan syndrome\t\tHP:0001166\tOMIM:154700\tTAS\t\t\t\t\tP\tHPO:curator[2009-02-17]\n\
+        OMIM:154700\tMarfan syndrome\tNOT\tHP:0001631\tOMIM:154700\tTAS\t\t\t\t\tP\tHPO:curator[2009-02-17]\n\
+        OMIM:154700\tMarfan syndrome\t\tHP:0000006\tOMIM:154700\tTAS\t\t\t\t\tI\tHPO:curator[2009-02-17]\n\
+        OMIM:108800\tAtrial septal defect 1\t\tHP:0001631\tOMIM:108800\tTAS\t\t\t\t\tP\tHPO:curator[2009-02-17]\n\
+        OMIM:108800\tAtrial septal defect 1\t\tHP:0001166\tOMIM:108800\tTAS\t\t\t\t\tP\tHPO:curator[2009-02-17]\n";
+
+    #[rstest]
+    fn test_parse_release_file() {
+        let associations = parse_associations(RELEASE_FILE).unwrap();
+        // the NOT annotation and the mode of inheritance are skipped
+        assert_eq!(3, associations.len());
+        assert_eq!("Marfan syndrome", associations[0].disease_name);
+        assert_eq!("HP:0001166", associations[0].hpo_id);
+    }
+
+    #[rstest]
+    fn test_parse_small_file() {
+        let contents = "#diseaseID\tdiseaseName\tphenotypeID\tphenotypeName\tnegation\n\
+            OMIM:154700\tMarfan syndrome\tHP:0001166\tArachnodactyly\t\n\
+            OMIM:154700\tMarfan syndrome\tHP:0000006\tAutosomal dominant inheritance\t\n";
+        let associations = parse_associations(contents).unwrap();
+        assert_eq!(1, associations.len());
+    }
+
+    #[rstest]
+    fn test_diseases_with_annotation() {
+        let associations = parse_associations(RELEASE_FILE).unwrap();
+        let diseases = diseases_with_annotation(&associations, |id| id == "HP:0001166");
+        let ids: Vec<&str> = diseases.iter().map(|d| d.disease_id.as_str()).collect();
+        assert_eq!(vec!["OMIM:154700", "OMIM:108800"], ids);
+        let diseases = diseases_with_annotation(&associations, |id| id == "HP:0001631");
+        assert_eq!(1, diseases.len());
+        assert_eq!("Atrial septal defect 1", diseases[0].disease_label);
+    }
+
+    #[rstest]
+    fn test_missing_header() {
+        assert!(parse_associations("OMIM:154700\tMarfan syndrome\tHP:0001166\n").is_err());
+    }
+}
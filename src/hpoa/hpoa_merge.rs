@@ -0,0 +1,148 @@
+//! Merging of several HPOA files
+//!
+//! All files must have the same header line. Rows are identified by disease and HPO term. Rows with the same
+//! identity and evidence code are duplicates, of which we keep the first one; if the evidence codes differ, we keep
+//! the row with the highest-quality code (TAS > PCS > IEA) at the position of the first occurrence.
+//!
+//! The HPOA tables of cohorts have one row per publication for each HPO term. When such tables are combined
+//! (see [`super::hpoa_batch`]), the publication is therefore also part of the identity of a row.
+
+use std::collections::HashMap;
+use std::str::FromStr;
+
+use crate::hpoa::{hpoa_import::HpoaColumns, hpoa_table_row::EvidenceCode};
+
+
+/// Merge the contents of HPOA files and return the header line followed by the merged rows.
+/// If `per_publication` is true, rows of the same disease and HPO term from different publications are kept.
+///
+/// Of several rows with the same identity, the first one wins unless a later row has a higher-quality evidence
+/// code. The other rows are discarded, even if they differ in other fields (e.g., publication or frequency);
+/// each discarded row that is not identical to the kept row is logged as a warning.
+pub(crate) fn merge_hpoa(contents: &[String], per_publication: bool) -> Result<Vec<String>, String> {
+    let mut header: Option<&str> = None;
+    let mut rows: Vec<(&str, EvidenceCode)> = Vec::new();
+    let mut row_index: HashMap<(&str, &str, &str), usize> = HashMap::new();
+    for (file_index, file_contents) in contents.iter().enumerate() {
+        let mut columns: Option<HpoaColumns> = None;
+        for (i, line) in file_contents.lines().enumerate() {
+            if line.trim().is_empty() {
+                continue;
+            }
+            if HpoaColumns::is_header(line) {
+                match header {
+                    Some(h) if h != line => {
+                        return Err(format!("HPOA file {} has a different header than the first file", file_index + 1));
+                    }
+                    _ => header = Some(line),
+                }
+                columns = Some(HpoaColumns::from_header(line)?);
+                continue;
+            }
+            if line.starts_with('#') {
+                continue;
+            }
+            let cols = columns.as_ref()
+                .ok_or_else(|| format!("HPOA file {} does not have a header line", file_index + 1))?;
+            let fields: Vec<&str> = line.split('\t').collect();
+            if fields.len() != cols.n_columns {
+                return Err(format!("Line {} of HPOA file {} has {} fields but the header has {}",
+                    i + 1, file_index + 1, fields.len(), cols.n_columns));
+            }
+            let evidence_column = cols.evidence
+                .ok_or_else(|| format!("HPOA file {} does not have an evidence column", file_index + 1))?;
+            let evidence = EvidenceCode::from_str(fields[evidence_column])
+                .map_err(|e| format!("{e} in line {} of HPOA file {}", i + 1, file_index + 1))?;
+            let publication = match cols.publication {
+                Some(j) if per_publication => fields[j],
+                _ => "",
+            };
+            let key = (fields[cols.disease_id], fields[cols.phenotype_id], publication);
+            match row_index.get(&key) {
+                Some(&idx) => {
+                    let discarded = if evidence.quality() > rows[idx].1.quality() {
+                        tracing::debug!(disease_id = key.0, hpo_id = key.1, evidence = evidence.as_str(),
+                            "Replacing HPOA annotation with higher-quality evidence");
+                        std::mem::replace(&mut rows[idx], (line, evidence)).0
+                    } else {
+                        line
+                    };
+                    if discarded != rows[idx].0 {
+                        tracing::warn!(disease_id = key.0, hpo_id = key.1, kept = rows[idx].0, discarded,
+                            "Discarding HPOA annotation of the same disease and HPO term");
+                    }
+                }
+                None => {
+                    row_index.insert(key, rows.len());
+                    rows.push((line, evidence));
+                }
+            }
+        }
+    }
+    let header = header.ok_or_else(|| "No HPOA header line found".to_string())?;
+    let mut merged = vec![header.to_string()];
+    merged.extend(rows.into_iter().map(|(line, _)| line.to_string()));
+    Ok(merged)
+}
+
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use rstest::rstest;
+
+    const HEADER: &str = "#diseaseID\tdiseaseName\tphenotypeID\tphenotypeName\tfrequency\tpublication\tevidence\tbiocuration";
+    const ARACHNODACTYLY_PCS: &str = "OMIM:154700\tMarfan syndrome\tHP:0001166\tArachnodactyly\t2/3\tOMIM:154700\tPCS\tORCID:0000-0002-0736-9199[2025-01-09]";
+    const ARACHNODACTYLY_TAS: &str = "OMIM:154700\tMarfan syndrome\tHP:0001166\tArachnodactyly\t\tOMIM:154700\tTAS\tHPO:curator[2009-02-17]";
+    const SHORT_STATURE: &str = "OMIM:154700\tMarfan syndrome\tHP:0004322\tShort stature\t1/3\tPMID:29999999\tPCS\tORCID:0000-0002-0736-9199[2025-01-09]";
+
+    fn hpoa(rows: &[&str]) -> String {
+        let mut lines = vec![HEADER];
+        lines.extend_from_slice(rows);
+        lines.join("\n")
+    }
+
+    #[rstest]
+    fn test_duplicate_rows() {
+        let merged = merge_hpoa(&[hpoa(&[ARACHNODACTYLY_PCS, SHORT_STATURE]), hpoa(&[SHORT_STATURE])], false).unwrap();
+        assert_eq!(vec![HEADER, ARACHNODACTYLY_PCS, SHORT_STATURE], merged);
+    }
+
+    #[rstest]
+    #[case::higher_quality_second(ARACHNODACTYLY_PCS, ARACHNODACTYLY_TAS)]
+    #[case::higher_quality_first(ARACHNODACTYLY_TAS, ARACHNODACTYLY_PCS)]
+    fn test_conflicting_evidence(#[case] first: &str, #[case] second: &str) {
+        let merged = merge_hpoa(&[hpoa(&[first, SHORT_STATURE]), hpoa(&[second])], false).unwrap();
+        assert_eq!(vec![HEADER, ARACHNODACTYLY_TAS, SHORT_STATURE], merged);
+    }
+
+    #[rstest]
+    fn test_different_publications_are_merged() {
+        let other_publication = SHORT_STATURE.replace("PMID:29999999", "PMID:30000000").replace("PCS", "TAS");
+        let merged = merge_hpoa(&[hpoa(&[SHORT_STATURE]), hpoa(&[&other_publication])], false).unwrap();
+        assert_eq!(vec![HEADER, other_publication.as_str()], merged);
+    }
+
+    #[rstest]
+    fn test_different_publications_first_wins() {
+        let other_publication = SHORT_STATURE.replace("PMID:29999999", "PMID:30000000");
+        let merged = merge_hpoa(&[hpoa(&[SHORT_STATURE]), hpoa(&[&other_publication])], false).unwrap();
+        assert_eq!(vec![HEADER, SHORT_STATURE], merged);
+    }
+
+    #[rstest]
+    fn test_different_publications_are_kept_per_publication() {
+        let other_publication = SHORT_STATURE.replace("PMID:29999999", "PMID:30000000");
+        let merged = merge_hpoa(&[hpoa(&[SHORT_STATURE]), hpoa(&[&other_publication])], true).unwrap();
+        assert_eq!(vec![HEADER, SHORT_STATURE, other_publication.as_str()], merged);
+    }
+
+    #[rstest]
+    #[case::different_header(vec![hpoa(&[SHORT_STATURE]), "#diseaseID\tdiseaseName\tphenotypeID\tevidence\n".to_string()])]
+    #[case::no_header(vec![SHORT_STATURE.to_string()])]
+    #[case::invalid_evidence(vec![hpoa(&[&SHORT_STATURE.replace("PCS", "XYZ")])])]
+    #[case::empty(vec![])]
+    fn test_invalid_input(#[case] contents: Vec<String>) {
+        assert!(merge_hpoa(&contents, false).is_err());
+    }
+}
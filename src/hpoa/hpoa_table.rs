@@ -1,15 +1,19 @@
-use std::{fs::File, io::{BufWriter, Write}, path::PathBuf, sync::Arc};
+use std::{fs::{File, OpenOptions}, io::{BufWriter, Write}, path::{Path, PathBuf}, str::FromStr, sync::{Arc, LazyLock}};
 
 
 use chrono::Local;
-use ontolius::ontology::csr::FullCsrOntology;
-use regex::Regex;
+use ontolius::{ontology::{csr::FullCsrOntology, HierarchyQueries}, TermId};
 
-use crate::{dto::cohort_dto::{CohortData, DiseaseData}, hpoa::{counted_hpo_term::CountedHpoTerm, hpoa_onset_calculator::HpoaOnsetCalculator, hpoa_table_row::HpoaTableRow, hpo_term_counter::HpoTermCounter}};
+use crate::{dto::{cohort_dto::{CohortData, DiseaseData}, hpo_term_dto::CellValueInner, orcid::validate_orcid}, hpoa::{counted_hpo_term::CountedHpoTerm, hpoa_onset_calculator::HpoaOnsetCalculator, hpoa_table_row::{self, EvidenceCode, HpoaTableRow}, hpo_term_counter::HpoTermCounter}};
 
 
 
 
+/// HPO term Frequency (HP:0040279)
+static FREQUENCY: LazyLock<TermId> = LazyLock::new(|| {
+    "HP:0040279".parse().expect("Failed to parse static HP:0040279")
+});
+
 
 pub struct HpoaTable {
     hpoa_row_list: Vec<HpoaTableRow>,
@@ -23,27 +27,26 @@ impl HpoaTable {
         hpo: Arc<FullCsrOntology>,
         biocurator: &str) -> Result<Self, String>{
         let todays_date = Local::now().format("%Y-%m-%d").to_string();
-        if ! Self::is_valid_orcid(biocurator) {
-            return Err(format!("Malformed biocurator string ({biocurator}). Must be the ORCID 16-digit identifier (only)."));
-        }
-        let biocurator = format!("ORCID:{biocurator}[{todays_date}]");
+        let biocurator = format!("{}[{todays_date}]", validate_orcid(biocurator)?);
         if ! cohort.is_mendelian() {
             return Err(format!("Can only export Mendelian HPOA table, but this cohort is {:?}", 
                 cohort.cohort_type));
         }
+        Self::validate_frequency_classes(&cohort, hpo.clone())?;
         let onset_term_list: Vec<CountedHpoTerm> = HpoaOnsetCalculator::pmid_to_onset_freq_d(&cohort)?;
         let hpo_counted_term_list = HpoTermCounter::pmid_term_count_list(&cohort)?;
         let disease_data = Self::get_disease_data(&cohort)?;
+        let evidence = hpoa_table_row::infer_evidence_code(&cohort);
         let mut hpoa_rows = Vec::new();
         for counted_term in hpo_counted_term_list {
-            let row = HpoaTableRow::from_counted_term(&disease_data, counted_term, &biocurator)?;
+            let row = HpoaTableRow::from_counted_term(&disease_data, counted_term, evidence, &biocurator)?;
             hpoa_rows.push(row);
         }
         for counted_onset in onset_term_list {
-            let row = HpoaTableRow::from_counted_term(&disease_data,counted_onset, &biocurator)?;
+            let row = HpoaTableRow::from_counted_term(&disease_data,counted_onset, evidence, &biocurator)?;
             hpoa_rows.push(row);
         }
-        let moi_list = Self::get_disease_mode_of_inheritance(&cohort, &biocurator)?;
+        let moi_list = Self::get_disease_mode_of_inheritance(&cohort, evidence, &biocurator)?;
         hpoa_rows.extend(moi_list);
         let file_name = Self::get_hpoa_filename(&cohort)?;
         Ok(Self{
@@ -67,12 +70,30 @@ impl HpoaTable {
         }
     }
 
-    /// Regex for ORCID IDs: 0000-0000-0000-0000 where the last char can be a digit or X
-    fn is_valid_orcid(orcid: &str) -> bool {
-        let re = Regex::new(r"^\d{4}-\d{4}-\d{4}-\d{3}[\dX]$").unwrap();
-        re.is_match(orcid)
+    /// Population-level annotations must use a subclass of Frequency (HP:0040279)
+    fn validate_frequency_classes(cohort: &CohortData, hpo: Arc<FullCsrOntology>) -> Result<(), String> {
+        for row in &cohort.rows {
+            for cell in &row.hpo_data {
+                if let CellValueInner::HpoFrequency(freq) = &cell.entry {
+                    let tid = TermId::from_str(freq)
+                        .map_err(|_| format!("Invalid HPO frequency '{freq}'"))?;
+                    if !hpo.is_descendant_of(&tid, &*FREQUENCY) {
+                        return Err(format!("{freq} is not an HPO frequency term ({})", row.individual_data.individual_id));
+                    }
+                }
+            }
+        }
+        Ok(())
+    }
+
+    /// Check that all rows have a valid evidence code (IEA, PCS, or TAS)
+    pub fn validate_evidence_codes(&self) -> Result<(), String> {
+        for row in &self.hpoa_row_list {
+            row.evidence_code()?;
+        }
+        Ok(())
     }
-    
+
     pub fn get_dataframe(&self) -> Vec<Vec<String>> {
         let mut rows:  Vec<Vec<String>> = Vec::new();
         rows.push(HpoaTableRow::header_fields());
@@ -115,8 +136,29 @@ impl HpoaTable {
         Ok(())
     }
 
+    /// Write the HPOA table to the file `path`. If `append` is true, the rows are appended to an
+    /// existing file and the header line is only written if the file is empty. Returns the number
+    /// of annotations (rows without the header) that were written.
+    pub fn write_tsv_file(&self, path: &Path, append: bool) -> std::io::Result<usize> {
+        let skip_header = append && path.exists() && path.metadata()?.len() > 0;
+        let file = OpenOptions::new()
+            .create(true)
+            .write(true)
+            .append(append)
+            .truncate(!append)
+            .open(path)?;
+        let mut writer = BufWriter::new(file);
+        let dataframe = self.get_dataframe();
+        for row in dataframe.iter().skip(if skip_header { 1 } else { 0 }) {
+            writeln!(writer, "{}", row.join("\t"))?;
+        }
+        writer.flush()?;
+        Ok(dataframe.len().saturating_sub(1))
+    }
+
     pub fn get_disease_mode_of_inheritance(
         cohort: &CohortData,
+        evidence: EvidenceCode,
         biocurator: &str
     ) -> Result<Vec<HpoaTableRow>, String> {
         let mut moi_list: Vec<HpoaTableRow> = Vec::new();
@@ -128,7 +170,7 @@ impl HpoaTable {
             return Err("Disease must have at least one mode-of-inheritance entry".to_string());
         }
         for moi in &disease.mode_of_inheritance_list {
-            let hpoa_row = HpoaTableRow::from_moi(&disease, moi, biocurator)?;
+            let hpoa_row = HpoaTableRow::from_moi(&disease, moi, evidence, biocurator)?;
             moi_list.push(hpoa_row);
         }
         Ok(moi_list)
@@ -144,12 +186,13 @@ mod test {
 
     use super::*;
     use std::fs;
-    use crate::test_utils::fixtures::hpo;
+    use crate::dto::hpo_term_dto::CellValue;
+    use crate::test_utils::fixtures::{fbn1_cohort, hpo};
     use rstest::{fixture, rstest};
 
     #[fixture]
     fn biocurator() -> String {
-        "0000-0002-0736-9199".to_string()
+        "ORCID:0000-0002-0736-9199".to_string()
     }
 
      #[fixture]
@@ -165,6 +208,104 @@ mod test {
      }
 
 
+     /// Add a population-level annotation from a separate publication to the FBN1 cohort
+     fn cohort_with_frequency(mut cohort: CohortData, frequency: &str) -> CohortData {
+        let mut row = cohort.rows[0].clone();
+        row.individual_data.pmid = "PMID:31000000".to_string();
+        row.individual_data.individual_id = "Population study".to_string();
+        row.hpo_data = vec![
+            CellValue::from_str(frequency).unwrap(), 
+            CellValue::na(), 
+            CellValue::na(), 
+            CellValue::na()];
+        cohort.rows.push(row);
+        cohort
+     }
+
+     #[rstest]
+     fn test_population_frequency(
+        hpo: Arc<FullCsrOntology>,
+        biocurator: String,
+        fbn1_cohort: CohortData
+     ) {
+        let cohort = cohort_with_frequency(fbn1_cohort, "HP:0040281");
+        let hpoa = HpoaTable::new(cohort, hpo, &biocurator).unwrap();
+        let matrix = hpoa.get_dataframe();
+        // columns: phenotypeID (2), frequency (6), publication (11)
+        let arachnodactyly: Vec<(&str, &str)> = matrix.iter()
+            .filter(|row| row[2] == "HP:0001166")
+            .map(|row| (row[11].as_str(), row[6].as_str()))
+            .collect();
+        assert!(arachnodactyly.contains(&("PMID:31000000", "HP:0040281")));
+        assert!(arachnodactyly.contains(&("PMID:29999999", "2/2")));
+        // all rows except the mode of inheritance have a valid HPOA frequency
+        assert!(matrix.iter().skip(1)
+            .filter(|row| !row[6].is_empty())
+            .all(|row| hpoa_table_row::HpoaFrequency::from_str(&row[6]).is_ok()));
+        // column evidence (12)
+        assert!(matrix.iter().skip(1).all(|row| row[12] == "PCS"));
+        assert!(hpoa.validate_evidence_codes().is_ok());
+     }
+
+     #[rstest]
+     fn test_frequency_class_without_publications(
+        hpo: Arc<FullCsrOntology>,
+        biocurator: String,
+        mut fbn1_cohort: CohortData
+     ) {
+        for row in &mut fbn1_cohort.rows {
+            row.individual_data.pmid = "unpublished".to_string();
+        }
+        let hpoa = HpoaTable::new(fbn1_cohort, hpo, &biocurator).unwrap();
+        let matrix = hpoa.get_dataframe();
+        // all rows except the mode of inheritance are IEA and use a frequency class rather than n/m
+        let frequencies: Vec<&str> = matrix.iter().skip(1)
+            .filter(|row| !row[6].is_empty())
+            .map(|row| row[6].as_str())
+            .collect();
+        assert!(!frequencies.is_empty());
+        assert!(frequencies.iter().all(|freq| freq.starts_with("HP:004028")));
+     }
+
+     #[rstest]
+     fn test_write_tsv_file_append(
+        hpo: Arc<FullCsrOntology>,
+        biocurator: String,
+        fbn1_cohort: CohortData
+     ) {
+        let hpoa = HpoaTable::new(fbn1_cohort, hpo, &biocurator).unwrap();
+        let path = std::env::temp_dir().join(format!("phetools_test_hpoa_append_{}.tab", std::process::id()));
+        let written = hpoa.write_tsv_file(&path, false);
+        let appended = hpoa.write_tsv_file(&path, true);
+        let contents = std::fs::read_to_string(&path);
+        let _ = std::fs::remove_file(&path);
+        let n = written.unwrap();
+        assert_eq!(n, appended.unwrap());
+        let contents = contents.unwrap();
+        // the header is written once
+        assert_eq!(1, contents.lines().filter(|line| line.starts_with("#diseaseID")).count());
+        assert_eq!(2 * n + 1, contents.lines().count());
+     }
+
+     #[rstest]
+     #[case("0000-0002-0736-9199")]
+     #[case("ORCID:0000-0002-0736-9198")]
+     fn test_invalid_biocurator(hpo: Arc<FullCsrOntology>, fbn1_cohort: CohortData, #[case] biocurator: &str) {
+        assert!(HpoaTable::new(fbn1_cohort, hpo, biocurator).is_err());
+     }
+
+     #[rstest]
+     fn test_invalid_frequency_class(
+        hpo: Arc<FullCsrOntology>,
+        biocurator: String,
+        fbn1_cohort: CohortData
+     ) {
+        let mut cohort = cohort_with_frequency(fbn1_cohort, "HP:0040281");
+        // not a descendant of Frequency (this cannot be parsed from a string, but could come from JSON)
+        cohort.rows[3].hpo_data[0] = CellValueInner::HpoFrequency("HP:0001166".to_string()).into();
+        assert!(HpoaTable::new(cohort, hpo, &biocurator).is_err());
+     }
+
      #[rstest]
      #[ignore = "for development, add better test once API stable for new template"]
      fn test_hpoa(
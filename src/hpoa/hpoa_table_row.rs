@@ -1,8 +1,10 @@
 
 
 use std::collections::HashMap;
+use std::fmt;
+use std::str::FromStr;
 
-use crate::{dto::cohort_dto::{DiseaseData, ModeOfInheritance}, hpoa::counted_hpo_term::CountedHpoTerm};
+use crate::{dto::cohort_dto::{CohortData, DiseaseData, ModeOfInheritance}, hpoa::counted_hpo_term::CountedHpoTerm};
 use std::sync::LazyLock;
 
 
@@ -29,7 +31,140 @@ pub static VALID_MODES_OF_INHERITANCE: LazyLock<HashMap<String, String>> = LazyL
 
 
 
+/// Evidence codes of the HPOA format
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum EvidenceCode {
+    /// Inferred from electronic annotation
+    Iea,
+    /// Published clinical study
+    Pcs,
+    /// Traceable author statement, e.g., from OMIM
+    Tas,
+}
+
+impl FromStr for EvidenceCode {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, String> {
+        match s {
+            "IEA" => Ok(Self::Iea),
+            "PCS" => Ok(Self::Pcs),
+            "TAS" => Ok(Self::Tas),
+            _ => Err(format!("Invalid HPOA evidence code '{s}' (must be IEA, PCS, or TAS)")),
+        }
+    }
+}
+
+impl EvidenceCode {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Self::Iea => "IEA",
+            Self::Pcs => "PCS",
+            Self::Tas => "TAS",
+        }
+    }
+
+    /// Quality of the evidence (TAS > PCS > IEA), used to choose between conflicting annotations
+    pub(crate) fn quality(&self) -> u8 {
+        match self {
+            Self::Iea => 0,
+            Self::Pcs => 1,
+            Self::Tas => 2,
+        }
+    }
+}
+
+/// Annotations of cohorts with individuals from publications (PMIDs) are published clinical studies (PCS);
+/// otherwise we regard them as inferred from electronic annotation (IEA).
+pub fn infer_evidence_code(cohort: &CohortData) -> EvidenceCode {
+    if cohort.rows.iter().any(|row| row.individual_data.pmid.starts_with("PMID:")) {
+        EvidenceCode::Pcs
+    } else {
+        EvidenceCode::Iea
+    }
+}
+
+
+/// HPO frequency classes (subclasses of Frequency, HP:0040279) with the lower bound of their range
+/// (Obligate, Very frequent, Frequent, Occasional, Very rare, Excluded)
+const FREQUENCY_CLASSES: [(&str, f64); 6] = [
+    ("HP:0040280", 1.0),
+    ("HP:0040281", 0.8),
+    ("HP:0040282", 0.3),
+    ("HP:0040283", 0.05),
+    (VERY_RARE, 0.01),
+    (EXCLUDED, 0.0),
+];
+const VERY_RARE: &str = "HP:0040284";
+const EXCLUDED: &str = "HP:0040285";
+
+/// Value of the frequency column of the HPOA format
+#[derive(Clone, Debug, PartialEq)]
+pub enum HpoaFrequency {
+    /// HPO frequency class, e.g., HP:0040281 (Very frequent)
+    HpoCode(String),
+    /// n of m individuals, e.g., 3/4
+    Fraction { num: u32, denom: u32 },
+    /// Percentage of individuals, e.g., 75%
+    Percentage(f64),
+}
+
+impl FromStr for HpoaFrequency {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, String> {
+        if s.starts_with("HP:") {
+            return if FREQUENCY_CLASSES.iter().any(|(hpo_id, _)| *hpo_id == s) {
+                Ok(Self::HpoCode(s.to_string()))
+            } else {
+                Err(format!("'{s}' is not an HPO frequency class"))
+            };
+        }
+        if let Some(p) = s.strip_suffix('%') {
+            return match p.parse::<f64>() {
+                Ok(p) if (0.0..=100.0).contains(&p) => Ok(Self::Percentage(p)),
+                _ => Err(format!("Invalid HPOA frequency percentage '{s}'")),
+            };
+        }
+        let invalid = || format!("Invalid HPOA frequency '{s}' (must be n/m, a percentage, or an HPO frequency class)");
+        let (num, denom) = s.split_once('/').ok_or_else(invalid)?;
+        let num: u32 = num.parse().map_err(|_| invalid())?;
+        let denom: u32 = denom.parse().map_err(|_| invalid())?;
+        if denom == 0 || num > denom {
+            return Err(invalid());
+        }
+        Ok(Self::Fraction { num, denom })
+    }
+}
+
+impl fmt::Display for HpoaFrequency {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::HpoCode(hpo_id) => write!(f, "{hpo_id}"),
+            Self::Fraction { num, denom } => write!(f, "{num}/{denom}"),
+            Self::Percentage(p) => write!(f, "{p}%"),
+        }
+    }
+}
 
+/// The HPO frequency class of `observed` out of `total` individuals, e.g., 4/5 is Very frequent (80-99%).
+/// Frequencies between the ranges of two classes (e.g., 0.5% or 4.5%) are assigned to the lower class,
+/// except that any observation is at least Very rare. If `total` is zero, there is no frequency class.
+pub fn classify_as_hpoa_frequency(observed: usize, total: usize) -> Option<HpoaFrequency> {
+    if total == 0 {
+        return None;
+    }
+    if observed == 0 {
+        return Some(HpoaFrequency::HpoCode(EXCLUDED.to_string()));
+    }
+    let ratio = observed as f64 / total as f64;
+    let class = FREQUENCY_CLASSES
+        .iter()
+        .find(|(hpo_id, lower_bound)| ratio >= *lower_bound && *hpo_id != EXCLUDED)
+        .map(|(hpo_id, _)| *hpo_id)
+        .unwrap_or(VERY_RARE);
+    Some(HpoaFrequency::HpoCode(class.to_string()))
+}
 
 
 /// The default frequency is the empty string
@@ -62,7 +197,10 @@ impl HpoaTableRow {
         term_label: &str, 
         freq_string: &str,
         pmid: &str,
+        evidence: &str,
         biocurator: &str) -> Result<Self, String> {
+        EvidenceCode::from_str(evidence)
+            .map_err(|e| format!("{e} for {term_label} ({term_id})"))?;
         Ok(Self { 
             disease_id: disease.disease_id.to_string(), 
             disease_name: disease.disease_label.to_string(), 
@@ -76,14 +214,30 @@ impl HpoaTableRow {
             modifier: "".to_string(), 
             description: "".to_string(), 
             publication: pmid.to_string(), 
-            evidence: "PCS".to_string(), 
+            evidence: evidence.to_string(), 
             biocuration: biocurator.to_string() 
         })
     }
 
 
-    pub fn from_counted_term(disease: &DiseaseData, cterm: CountedHpoTerm, biocurator: &str) -> Result<Self, String> {
-        HpoaTableRow::new(disease, cterm.hpo_id(), cterm.hpo_label(), &cterm.freq_string(), cterm.pmid(), biocurator)
+    /// Counts of individuals from publications (PCS) are reported as n/m. Without publications (IEA), we report
+    /// the HPO frequency class of the counts instead, as in the annotations derived from Orphanet.
+    /// Population-level annotations keep their frequency class in both cases.
+    pub fn from_counted_term(disease: &DiseaseData, cterm: CountedHpoTerm, evidence: EvidenceCode, biocurator: &str) -> Result<Self, String> {
+        let frequency = if evidence == EvidenceCode::Iea && !cterm.has_frequency_class() {
+            classify_as_hpoa_frequency(cterm.numerator(), cterm.denominator())
+                .ok_or_else(|| format!("No individuals annotated for {} ({}) in {}", cterm.hpo_label(), cterm.hpo_id(), cterm.pmid()))?
+        } else {
+            HpoaFrequency::from_str(&cterm.freq_string())
+                .map_err(|e| format!("{e} for {} ({}) in {}", cterm.hpo_label(), cterm.hpo_id(), cterm.pmid()))?
+        };
+        HpoaTableRow::new(disease, cterm.hpo_id(), cterm.hpo_label(), &frequency.to_string(), cterm.pmid(), evidence.as_str(), biocurator)
+    }
+
+    /// The evidence code of the row, or an error if it is not one of IEA, PCS, or TAS
+    pub fn evidence_code(&self) -> Result<EvidenceCode, String> {
+        EvidenceCode::from_str(&self.evidence)
+            .map_err(|e| format!("{e} for {} ({})", self.phenotype_name, self.phenotype_id))
     }
 
 
@@ -129,6 +283,7 @@ impl HpoaTableRow {
     pub fn from_moi(
         disease: &DiseaseData,
         moi: &ModeOfInheritance,
+        evidence: EvidenceCode,
         biocurator: &str
     ) -> Result<Self, String> {
         if VALID_MODES_OF_INHERITANCE
@@ -136,7 +291,7 @@ impl HpoaTableRow {
             .map(|s| s == &moi.hpo_label)
             .unwrap_or(false)
         {
-             HpoaTableRow::new(disease, &moi.hpo_id, &moi.hpo_label, DEFAULT_FREQ, &moi.citation, biocurator)
+             HpoaTableRow::new(disease, &moi.hpo_id, &moi.hpo_label, DEFAULT_FREQ, &moi.citation, evidence.as_str(), biocurator)
         } else {
             Err(format!("Invalid mode of inheritance data: '{}' / '{}' ", moi.hpo_label, moi.hpo_id))
         }
@@ -149,6 +304,7 @@ impl HpoaTableRow {
 mod tests {
     use super::*;
     use rstest::{fixture, rstest};
+    use crate::test_utils::fixtures::fbn1_cohort;
 
     #[fixture]
     fn disease_data() -> DiseaseData {
@@ -172,7 +328,7 @@ mod tests {
             hpo_label: label.to_string(), 
             citation: pmid 
         };
-        let result = HpoaTableRow::from_moi(&disease_data, &moi, biocurator);
+        let result = HpoaTableRow::from_moi(&disease_data, &moi, EvidenceCode::Pcs, biocurator);
         assert!(result.is_ok());
         let hpoa_row = result.unwrap();
         assert_eq!(hpo_id, hpoa_row.phenotype_id);
@@ -196,9 +352,86 @@ mod tests {
             hpo_label: label.to_string(), 
             citation: pmid 
         };
-        let result = HpoaTableRow::from_moi(&disease_data, &moi, biocurator);
+        let result = HpoaTableRow::from_moi(&disease_data, &moi, EvidenceCode::Pcs, biocurator);
         assert!(result.is_err());
     }
 
+    #[rstest]
+    #[case("IEA", EvidenceCode::Iea)]
+    #[case("PCS", EvidenceCode::Pcs)]
+    #[case("TAS", EvidenceCode::Tas)]
+    fn test_valid_evidence_code(#[case] code: &str, #[case] expected: EvidenceCode, disease_data: DiseaseData) {
+        assert_eq!(Ok(expected), EvidenceCode::from_str(code));
+        assert_eq!(code, expected.as_str());
+        let row = HpoaTableRow::new(&disease_data, "HP:0001166", "Arachnodactyly", "1/2", "PMID:123", code, "0000-0000-0000-0001").unwrap();
+        assert_eq!(Ok(expected), row.evidence_code());
+        assert_eq!(code, row.row()[12]);
+    }
+
+    #[rstest]
+    #[case("")]
+    #[case("pcs")]
+    #[case("ICE")]
+    #[case("PCS ")]
+    #[case("IEA;PCS")]
+    fn test_invalid_evidence_code(#[case] code: &str, disease_data: DiseaseData) {
+        assert!(EvidenceCode::from_str(code).is_err());
+        assert!(HpoaTableRow::new(&disease_data, "HP:0001166", "Arachnodactyly", "1/2", "PMID:123", code, "0000-0000-0000-0001").is_err());
+    }
+
+    #[rstest]
+    fn test_infer_evidence_code(mut fbn1_cohort: CohortData) {
+        assert_eq!(EvidenceCode::Pcs, infer_evidence_code(&fbn1_cohort));
+        for row in fbn1_cohort.rows.iter_mut() {
+            row.individual_data.pmid = String::new();
+        }
+        assert_eq!(EvidenceCode::Iea, infer_evidence_code(&fbn1_cohort));
+    }
+
+    #[rstest]
+    #[case("HP:0040281", HpoaFrequency::HpoCode("HP:0040281".to_string()))]
+    #[case("3/4", HpoaFrequency::Fraction { num: 3, denom: 4 })]
+    #[case("0/4", HpoaFrequency::Fraction { num: 0, denom: 4 })]
+    #[case("75%", HpoaFrequency::Percentage(75.0))]
+    #[case("12.5%", HpoaFrequency::Percentage(12.5))]
+    fn test_valid_hpoa_frequency(#[case] freq: &str, #[case] expected: HpoaFrequency) {
+        let parsed = HpoaFrequency::from_str(freq).unwrap();
+        assert_eq!(expected, parsed);
+        assert_eq!(freq, parsed.to_string());
+    }
+
+    #[rstest]
+    #[case("")]
+    #[case("HP:0001166")]
+    #[case("5/4")]
+    #[case("0/0")]
+    #[case("3 / 4")]
+    #[case("101%")]
+    #[case("frequent")]
+    fn test_invalid_hpoa_frequency(#[case] freq: &str) {
+        assert!(HpoaFrequency::from_str(freq).is_err());
+    }
+
+    #[rstest]
+    #[case(5, 5, "HP:0040280")]
+    #[case(99, 100, "HP:0040281")]
+    #[case(4, 5, "HP:0040281")]
+    #[case(79, 100, "HP:0040282")]
+    #[case(3, 10, "HP:0040282")]
+    #[case(29, 100, "HP:0040283")]
+    #[case(1, 20, "HP:0040283")]
+    #[case(9, 200, "HP:0040284")]
+    #[case(1, 100, "HP:0040284")]
+    #[case(1, 1000, "HP:0040284")]
+    #[case(0, 7, "HP:0040285")]
+    fn test_classify_as_hpoa_frequency(#[case] observed: usize, #[case] total: usize, #[case] expected: &str) {
+        assert_eq!(Some(HpoaFrequency::HpoCode(expected.to_string())), classify_as_hpoa_frequency(observed, total));
+    }
+
+    #[rstest]
+    fn test_classify_without_individuals() {
+        assert_eq!(None, classify_as_hpoa_frequency(0, 0));
+    }
+
 
 }
@@ -3,7 +3,7 @@
 //! This module contains functions for writing a TSV file in the [HPOA format](https://hpo.jax.org/app/help/annotations).
 //! 
 
-use std::{path::PathBuf, sync::Arc};
+use std::{path::Path, sync::Arc};
 
 use ontolius::ontology::csr::FullCsrOntology;
 
@@ -11,11 +11,19 @@ use crate::{dto::cohort_dto::CohortData, hpoa::hpoa_table::HpoaTable};
 
 
 mod counted_hpo_term;
+mod hpoa_batch;
+mod hpoa_diff;
+mod hpoa_import;
+pub(crate) mod hpoa_lookup;
+mod hpoa_merge;
 mod hpoa_onset_calculator;
 mod hpoa_table;
 mod hpoa_table_row;
 mod hpo_term_counter;
 
+pub use hpoa_diff::HpoaDiff;
+pub use hpoa_table_row::{classify_as_hpoa_frequency, infer_evidence_code, EvidenceCode, HpoaFrequency};
+
 
 
 
@@ -26,21 +34,36 @@ mod hpo_term_counter;
 ///
 /// * `cohort` - The cohort data to be annotated.
 /// * `hpo` - The ontology used to resolve HPO terms.
-/// * `biocurator` - The biocurator identifier to record in the output.
+/// * `biocurator` - The ORCID of the biocurator, e.g., `ORCID:0000-0002-1825-0097`, to record in the output.
 /// * `path` - The file path where the TSV should be written.
+/// * `append` - If true, append the annotations to an existing file (the header line is only written to an empty file).
 ///
 /// # Returns
 ///
-/// * `Ok(())` if the file was successfully written.
-/// * `Err(String)` if an error occurred during table creation or file writing.
+/// * `Ok(n)` with the number of disease-HPO annotations written.
+/// * `Err(String)` if the biocurator is not a valid ORCID (see [`validate_orcid`](crate::dto::orcid::validate_orcid)),
+///   if an error occurred during table creation or file writing, or if a row has an invalid evidence code.
 pub fn write_hpoa_tsv(
     cohort: CohortData, 
     hpo: Arc<FullCsrOntology>,
     biocurator: &str,
-    path: &PathBuf
-) -> std::result::Result<(), String> {
+    path: &Path,
+    append: bool,
+) -> std::result::Result<usize, String> {
     let hpoa = HpoaTable::new(cohort, hpo, biocurator)?;
-    hpoa.write_tsv(path).map_err(|e| e.to_string())
+    hpoa.validate_evidence_codes()?;
+    hpoa.write_tsv_file(path, append).map_err(|e| e.to_string())
+}
+
+/// Summary of an HPOA export, e.g., as reported by `phetools hpoa`
+#[derive(Clone, Debug, serde::Deserialize, serde::Serialize, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct HpoaExportSummary {
+    pub output_path: String,
+    /// Number of disease-HPO annotations (rows without the header)
+    pub n_annotations: usize,
+    /// True if the annotations were appended to an existing file
+    pub appended: bool,
 }
 
 /// Generate an in-memory dataframe representation of HPO annotations
@@ -53,7 +76,7 @@ pub fn write_hpoa_tsv(
 ///
 /// * `cohort` - The cohort data to be annotated.
 /// * `hpo` - The ontology used to resolve HPO terms.
-/// * `biocurator` - The biocurator identifier to include in the annotation.
+/// * `biocurator` - The ORCID of the biocurator (`ORCID:XXXX-XXXX-XXXX-XXXX`) to include in the annotation.
 ///
 /// # Returns
 ///
@@ -69,3 +92,147 @@ pub fn get_hpoa_dataframe(
 }
 
 
+/// Import an existing HPOA file as a starting point for curation.
+///
+/// Both the small-file format written by [`write_hpoa_tsv`] and the format of the `phenotype.hpoa`
+/// release file are supported. One [`CohortData`] is created per disease ID found in the file, with
+/// one row per publication. Annotations with the negation `NOT` (or a frequency of zero) are
+/// excluded, all others are observed; terms that are not annotated for a publication are `na`.
+/// Mode of inheritance annotations are added to the disease data and onset annotations are skipped.
+///
+/// # Arguments
+///
+/// * `hpoa_path` - Path to the HPOA file.
+/// * `hpo` - The ontology used to resolve the HPO terms (obsolete identifiers are replaced).
+///
+/// # Returns
+///
+/// * `Ok(Vec<CohortData>)` with one cohort per disease, in the order of the file.
+/// * `Err(String)` if the file cannot be read, lacks a header, or contains an unknown HPO term.
+pub fn import_from_hpoa(
+    hpoa_path: &Path,
+    hpo: Arc<FullCsrOntology>,
+) -> std::result::Result<Vec<CohortData>, String> {
+    let contents = std::fs::read_to_string(hpoa_path)
+        .map_err(|e| format!("Could not read HPOA file {}: {}", hpoa_path.display(), e))?;
+    hpoa_import::parse_hpoa(&contents, hpo)
+}
+
+
+/// Compare two versions of an HPOA file, e.g., before and after an HPO release.
+///
+/// Rows are matched by disease ID, HPO term ID, and publication; a matched row is modified if any
+/// other field except the biocuration (which contains the date of the export) differs.
+///
+/// # Returns
+///
+/// * `Ok(HpoaDiff)` with the number of added and removed rows and the modified rows (old, new).
+/// * `Err(String)` if a file cannot be read or is not in the HPOA format.
+pub fn diff_hpoa_files(
+    old_path: &Path,
+    new_path: &Path,
+) -> std::result::Result<HpoaDiff, String> {
+    let read = |path: &Path| std::fs::read_to_string(path)
+        .map_err(|e| format!("Could not read HPOA file {}: {}", path.display(), e));
+    hpoa_diff::diff_hpoa(&read(old_path)?, &read(new_path)?)
+}
+
+
+/// Merge several HPOA files, e.g., annotations of different disease subsets, into one file.
+///
+/// All files must have the same header line. Rows are identified by disease ID and HPO term ID.
+/// Identical annotations (same evidence code) are written once; if the evidence codes differ, the row with the
+/// highest-quality code (TAS > PCS > IEA) is kept. Otherwise, the first row wins and the other rows of the same
+/// disease and HPO term (e.g., with a different publication or frequency) are discarded with a warning in the log.
+/// Rows are written in the order of their first occurrence.
+///
+/// # Returns
+///
+/// * `Ok(usize)` with the number of rows written (without the header).
+/// * `Err(String)` if a file cannot be read, the headers differ, or a row has an invalid evidence code.
+pub fn merge_hpoa_files(
+    paths: &[&Path],
+    output_path: &Path,
+) -> std::result::Result<usize, String> {
+    let contents = paths
+        .iter()
+        .map(|path| std::fs::read_to_string(path)
+            .map_err(|e| format!("Could not read HPOA file {}: {}", path.display(), e)))
+        .collect::<Result<Vec<String>, String>>()?;
+    let merged = hpoa_merge::merge_hpoa(&contents, false)?;
+    let mut output = merged.join("\n");
+    output.push('\n');
+    std::fs::write(output_path, output)
+        .map_err(|e| format!("Could not write HPOA file {}: {}", output_path.display(), e))?;
+    Ok(merged.len() - 1)
+}
+
+
+/// Write the HPO annotations of all Mendelian cohorts of a repository, one file per disease.
+///
+/// All cohort files (`*_individuals.json`) below `store_dir` are read. The files are written to `output_dir`
+/// (which is created if needed) and named by disease ID, e.g., `OMIM-154700.tab`; the annotations of several
+/// cohorts of the same disease are merged as in [`merge_hpoa_files`]. Cohorts that are not Mendelian are skipped.
+///
+/// # Returns
+///
+/// * `Ok(usize)` with the number of files written.
+/// * `Err(String)` if a cohort file cannot be read, the HPOA table of a cohort cannot be created (e.g., if the
+///   biocurator is not a valid ORCID), or a file cannot be written.
+pub fn write_hpoa_for_all_cohorts(
+    store_dir: &Path,
+    hpo: Arc<FullCsrOntology>,
+    biocurator: &str,
+    output_dir: &Path,
+) -> std::result::Result<usize, String> {
+    write_hpoa_for_all_cohorts_with_progress(store_dir, hpo, biocurator, output_dir, |_, _| {})
+}
+
+/// Same as [`write_hpoa_for_all_cohorts`], but `progress_cb` is called as (processed, total) after each cohort.
+pub fn write_hpoa_for_all_cohorts_with_progress<F>(
+    store_dir: &Path,
+    hpo: Arc<FullCsrOntology>,
+    biocurator: &str,
+    output_dir: &Path,
+    progress_cb: F,
+) -> std::result::Result<usize, String>
+where F: FnMut(u32, u32) {
+    let tables = hpoa_batch::hpoa_lines_by_disease(store_dir, hpo, biocurator, progress_cb)?;
+    std::fs::create_dir_all(output_dir)
+        .map_err(|e| format!("Could not create {}: {}", output_dir.display(), e))?;
+    for (disease_id, lines) in &tables {
+        let path = output_dir.join(hpoa_batch::hpoa_file_name(disease_id));
+        let mut output = lines.join("\n");
+        output.push('\n');
+        std::fs::write(&path, output)
+            .map_err(|e| format!("Could not write HPOA file {}: {}", path.display(), e))?;
+    }
+    Ok(tables.len())
+}
+
+/// Write the HPO annotations of all Mendelian cohorts of a repository to a single file, sorted by disease ID.
+///
+/// The cohorts are processed as in [`write_hpoa_for_all_cohorts`]; the file has one header line.
+///
+/// # Returns
+///
+/// * `Ok(usize)` with the number of rows written (without the header).
+/// * `Err(String)` if a cohort file cannot be read, the HPOA table of a cohort cannot be created, or the file
+///   cannot be written.
+pub fn write_merged_hpoa_for_all_cohorts(
+    store_dir: &Path,
+    hpo: Arc<FullCsrOntology>,
+    biocurator: &str,
+    output_path: &Path,
+) -> std::result::Result<usize, String> {
+    let tables = hpoa_batch::hpoa_lines_by_disease(store_dir, hpo, biocurator, |_, _| {})?;
+    let mut lines = vec![hpoa_table_row::HpoaTableRow::header_fields().join("\t")];
+    for disease_lines in tables.values() {
+        lines.extend(disease_lines.iter().skip(1).cloned());
+    }
+    let mut output = lines.join("\n");
+    output.push('\n');
+    std::fs::write(output_path, output)
+        .map_err(|e| format!("Could not write HPOA file {}: {}", output_path.display(), e))?;
+    Ok(lines.len() - 1)
+}
@@ -13,15 +13,11 @@
 //! - API for graphical user interface (GUI) curation tools
 
 
-// for development allow this
-//#![allow(dead_code)]
-//#![allow(unused_variables)]
-//#![allow(unused_imports)]
-
 mod header;
 
 pub mod age;
 pub mod dto;
+pub mod error;
 pub mod etl;
 pub mod export;
 pub mod hpo;
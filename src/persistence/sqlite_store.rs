@@ -0,0 +1,160 @@
+//! SQLite storage backend for cohorts
+//!
+//! Each cohort is stored as one row with the full [`CohortData`] JSON as a blob.
+//! The cohort identifier, the gene symbol(s) and the modification time are stored
+//! in separate (indexed) columns so that we can list cohorts without deserializing them.
+
+use std::path::Path;
+
+use chrono::Local;
+use rusqlite::{params, Connection, OptionalExtension};
+use serde::{Deserialize, Serialize};
+
+use crate::dto::cohort_dto::CohortData;
+
+
+/// Lightweight description of a stored cohort, used to list the contents of the store
+#[derive(Clone, Debug, Deserialize, Serialize, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct CohortSummary {
+    pub cohort_id: String,
+    pub gene_symbol: String,
+    pub cohort_type: String,
+    pub n_individuals: usize,
+    pub last_modified: String,
+}
+
+
+pub struct SqliteCohortStore {
+    connection: Connection,
+}
+
+
+impl SqliteCohortStore {
+    /// Open the SQLite database at the indicated location; if it does not exist, create it.
+    pub fn new<P: AsRef<Path>>(db_path: P) -> Result<Self, String> {
+        let connection = Connection::open(db_path).map_err(|e| e.to_string())?;
+        Self::from_connection(connection)
+    }
+
+    /// Create a store that is only kept in memory (mainly for testing).
+    pub fn in_memory() -> Result<Self, String> {
+        let connection = Connection::open_in_memory().map_err(|e| e.to_string())?;
+        Self::from_connection(connection)
+    }
+
+    fn from_connection(connection: Connection) -> Result<Self, String> {
+        connection.execute_batch(
+            "CREATE TABLE IF NOT EXISTS cohort (
+                cohort_id     TEXT PRIMARY KEY NOT NULL,
+                gene_symbol   TEXT NOT NULL,
+                cohort_type   TEXT NOT NULL,
+                n_individuals INTEGER NOT NULL,
+                last_modified TEXT NOT NULL,
+                cohort_json   BLOB NOT NULL
+            );
+            CREATE INDEX IF NOT EXISTS idx_cohort_gene_symbol ON cohort (gene_symbol);
+            CREATE INDEX IF NOT EXISTS idx_cohort_last_modified ON cohort (last_modified);"
+        ).map_err(|e| e.to_string())?;
+        Ok(Self { connection })
+    }
+
+    /// The identifier of a cohort is built from the gene symbol(s) and the cohort acronym,
+    /// e.g., FBN1_MFS, which matches the names we use for the template files.
+    pub fn cohort_id(cohort: &CohortData) -> Result<String, String> {
+        let acronym = cohort.cohort_acronym
+            .as_ref()
+            .ok_or_else(|| "Cannot store cohort without acronym".to_string())?;
+        Ok(format!("{}_{}", Self::gene_symbol(cohort), acronym))
+    }
+
+    /// Gene symbol(s) of the cohort. For melded and digenic cohorts, the symbols
+    /// are sorted and joined with a hyphen.
+    fn gene_symbol(cohort: &CohortData) -> String {
+        let mut genes = cohort
+            .disease_list
+            .iter()
+            .flat_map(|dx| dx.gene_transcript_list.iter().map(|gt| gt.gene_symbol.as_str()))
+            .collect::<Vec<_>>();
+        genes.sort_unstable();
+        genes.dedup();
+        genes.join("-")
+    }
+
+    /// Insert the cohort into the store, replacing any previous version with the same identifier.
+    /// Returns the cohort identifier.
+    pub fn save_cohort(&self, cohort: &CohortData) -> Result<String, String> {
+        let cohort_id = Self::cohort_id(cohort)?;
+        let cohort_json = serde_json::to_vec(cohort).map_err(|e| e.to_string())?;
+        let last_modified = Local::now().format("%Y-%m-%dT%H:%M:%S%.3f").to_string();
+        self.connection.execute(
+            "INSERT INTO cohort (cohort_id, gene_symbol, cohort_type, n_individuals, last_modified, cohort_json)
+             VALUES (?1, ?2, ?3, ?4, ?5, ?6)
+             ON CONFLICT(cohort_id) DO UPDATE SET
+                gene_symbol = excluded.gene_symbol,
+                cohort_type = excluded.cohort_type,
+                n_individuals = excluded.n_individuals,
+                last_modified = excluded.last_modified,
+                cohort_json = excluded.cohort_json",
+            params![
+                cohort_id,
+                Self::gene_symbol(cohort),
+                cohort.cohort_type.to_string(),
+                cohort.rows.len() as i64,
+                last_modified,
+                cohort_json
+            ],
+        ).map_err(|e| e.to_string())?;
+        Ok(cohort_id)
+    }
+
+    /// Retrieve the cohort with the indicated identifier.
+    pub fn load_cohort(&self, cohort_id: &str) -> Result<CohortData, String> {
+        let cohort_json: Option<Vec<u8>> = self.connection
+            .query_row(
+                "SELECT cohort_json FROM cohort WHERE cohort_id = ?1",
+                params![cohort_id],
+                |row| row.get(0),
+            )
+            .optional()
+            .map_err(|e| e.to_string())?;
+        match cohort_json {
+            Some(blob) => serde_json::from_slice(&blob)
+                .map_err(|e| format!("Could not deserialize cohort {cohort_id}: {e}")),
+            None => Err(format!("Cohort '{cohort_id}' not found")),
+        }
+    }
+
+    /// List all stored cohorts, most recently modified first.
+    pub fn list_cohorts(&self) -> Result<Vec<CohortSummary>, String> {
+        let mut stmt = self.connection
+            .prepare(
+                "SELECT cohort_id, gene_symbol, cohort_type, n_individuals, last_modified
+                 FROM cohort ORDER BY last_modified DESC, cohort_id")
+            .map_err(|e| e.to_string())?;
+        let rows = stmt
+            .query_map([], |row| {
+                let n_individuals: i64 = row.get(3)?;
+                Ok(CohortSummary {
+                    cohort_id: row.get(0)?,
+                    gene_symbol: row.get(1)?,
+                    cohort_type: row.get(2)?,
+                    n_individuals: n_individuals as usize,
+                    last_modified: row.get(4)?,
+                })
+            })
+            .map_err(|e| e.to_string())?;
+        rows.collect::<Result<Vec<_>, _>>().map_err(|e| e.to_string())
+    }
+
+    /// Remove the cohort with the indicated identifier from the store.
+    pub fn delete_cohort(&self, cohort_id: &str) -> Result<(), String> {
+        let n_deleted = self.connection
+            .execute("DELETE FROM cohort WHERE cohort_id = ?1", params![cohort_id])
+            .map_err(|e| e.to_string())?;
+        if n_deleted == 0 {
+            return Err(format!("Cohort '{cohort_id}' not found"));
+        }
+        Ok(())
+    }
+}
@@ -3,25 +3,30 @@
 //! Contains functions to create phenopackets from templates and a public function to
 //! export a collection of phenopackets to an indicated directory.
 
-use std::{fs::OpenOptions, path::PathBuf, sync::Arc};
+use std::{fs::{File, OpenOptions}, io::{BufWriter, Write}, path::{Path, PathBuf}, sync::Arc};
 
 use ontolius::ontology::csr::FullCsrOntology;
-use phenopackets::schema::v2::Phenopacket;
+use phenopackets::schema::v2::core::pedigree::person::AffectedStatus;
+use phenopackets::schema::v2::core::pedigree::Person;
+use phenopackets::schema::v2::core::Pedigree;
+use phenopackets::schema::v2::{Family, Phenopacket};
 
-use crate::{dto::cohort_dto::CohortData, ppkt::ppkt_exporter::PpktExporter};
+use crate::{dto::cohort_dto::{CohortData, RowData}, error::PheToolsError, ppkt::ppkt_exporter::PpktExporter};
 
 mod ppkt_variant_exporter;
+mod ppkt_diff;
 pub mod ppkt_exporter;
 pub mod ppkt_row;
 mod ppkt_utils;
 pub use ppkt_utils::get_gene_symbol_from_interpretation;
+pub use ppkt_diff::{are_phenopackets_semantically_equivalent, diff_phenopackets, PpktFieldDiff};
 
 
 /// Write all `Phenopacket`s derived from a given `CohortData` to disk.
 ///
 /// This function uses the provided [`CohortData`] and [`FullCsrOntology`]
 /// to generate one phenopacket per individual (via [`PpktExporter`]),
-/// and writes each packet as a pretty-printed JSON file to the specified output directory.
+/// sorted by PMID and individual id, and writes each packet as a pretty-printed JSON file to the specified output directory.
 ///
 /// # Arguments
 ///
@@ -29,7 +34,7 @@ pub use ppkt_utils::get_gene_symbol_from_interpretation;
 ///   to be exported into phenopackets.
 /// * `dir` - A [`PathBuf`] representing the output directory where each phenopacket
 ///   JSON file will be written.
-/// * `orcid` - The ORCID identifier of the submitting researcher. This will be 
+/// * `orcid` - The ORCID of the submitting researcher (`ORCID:XXXX-XXXX-XXXX-XXXX`). This will be
 ///   embedded in each phenopacket.
 /// * `hpo` - An `Arc<FullCsrOntology>` instance of the HPO ontology used to 
 ///   annotate the phenopackets.
@@ -39,11 +44,14 @@ pub use ppkt_utils::get_gene_symbol_from_interpretation;
 /// On success, returns a [`String`] containing a summary message, e.g.
 /// `"Wrote 12 phenopackets to directory /path/to/output"`.
 ///
-/// On error, returns a [`String`] describing the failure (e.g. file I/O or serialization error).
+/// On error, returns a [`PheToolsError`] describing the failure (e.g. file I/O or serialization error).
 ///
 /// # Errors
 ///
 /// This function will return an error if:
+/// * the ORCID is invalid (see [`validate_orcid`](crate::dto::orcid::validate_orcid))
+/// * the phenopackets of two individuals would have the same file name
+///   (see [`PpktExporter::check_for_filename_collisions`]); nothing is written in this case
 /// * phenopacket construction fails (via `PpktExporter`)
 /// * writing to the target directory fails (e.g. permission issues)
 /// * JSON serialization fails
@@ -60,7 +68,7 @@ pub use ppkt_utils::get_gene_symbol_from_interpretation;
 /// let cohort = CohortData::new(); // example
 /// let ontology: Arc<FullCsrOntology> = get_hpo_ontology(); // need to get HPO from an appopriate place 
 /// let dir = PathBuf::from("./output");
-/// let orcid = "0000-0002-1825-0097".to_string();
+/// let orcid = "ORCID:0000-0002-1825-0097".to_string();
 /// let overwrite = false;
 ///
 /// match write_phenopackets(cohort, dir, orcid, ontology, overwrite) {
@@ -74,21 +82,43 @@ pub fn write_phenopackets(
     orcid: String,
     hpo: Arc<FullCsrOntology>,
     overwrite: bool) 
--> Result<usize, String> {
+-> Result<usize, PheToolsError> {
+    write_phenopackets_with_progress(cohort_dto, dir, orcid, hpo, overwrite, |_, _| {})
+}
+
+/// Same as [`write_phenopackets`], but `progress_cb` is called as (processed, total) after each phenopacket,
+/// including phenopackets that were skipped because the file already exists.
+pub fn write_phenopackets_with_progress<F>(
+    mut cohort_dto: CohortData, 
+    dir: PathBuf,
+    orcid: String,
+    hpo: Arc<FullCsrOntology>,
+    overwrite: bool,
+    mut progress_cb: F) 
+-> Result<usize, PheToolsError> 
+where F: FnMut(u32, u32) {
+    // write the phenopackets in a reproducible order
+    cohort_dto.sort_by(|row| (row.individual_data.pmid.clone(), row.individual_data.individual_id.clone()));
+    let collisions = PpktExporter::check_for_filename_collisions(&cohort_dto.rows);
+    if !collisions.is_empty() {
+        let pairs: Vec<String> = collisions.iter().map(|(a, b)| format!("'{a}' and '{b}'")).collect();
+        return Err(PheToolsError::PhenopacketExportFailed(
+            format!("Phenopackets of these individuals would be written to the same file: {}", pairs.join(", "))));
+    }
     let acronym = cohort_dto.acronym();
-    let exporter = PpktExporter::new(hpo.clone(), &orcid, cohort_dto);
+    let exporter = PpktExporter::new(hpo.clone(), &orcid, cohort_dto).map_err(PheToolsError::PhenopacketExportFailed)?;
     let ppkt_list: Vec<Phenopacket> = exporter.get_all_phenopackets()
-        .map_err(|e| format!("{}: cohort {}", e, acronym))?;
+        .map_err(|e| PheToolsError::PhenopacketExportFailed(format!("{}: cohort {}", e, acronym)))?;
+    let total = ppkt_list.len() as u32;
     let mut n_phenopackets = 0;
-    for ppkt in ppkt_list {
-        let title = ppkt.id.clone() + ".json";
+    for (i, ppkt) in ppkt_list.into_iter().enumerate() {
         let file_name = format!("{}.json", ppkt.id);
         let file_path = dir.join(&file_name);
-        if file_path.exists() && !overwrite {
-            continue;
+        if !file_path.exists() || overwrite {
+            n_phenopackets += 1;
+            write_ppkt(&ppkt, file_path)?;
         }
-        n_phenopackets += 1;
-        write_ppkt(&ppkt, file_path)?;
+        progress_cb(i as u32 + 1, total);
     }
     Ok(n_phenopackets)
 }
@@ -105,19 +135,17 @@ pub fn write_phenopackets(
 /// # Errors
 ///
 /// Returns an error if the file cannot be opened/created, or if JSON serialization fails.
-fn write_ppkt(ppkt: &Phenopacket, file_path: PathBuf) -> Result<(), String> {
+fn write_ppkt(ppkt: &Phenopacket, file_path: PathBuf) -> Result<(), PheToolsError> {
     let file = OpenOptions::new()
         .write(true)
         .create(true)
         .truncate(true)
-        .open(&file_path)
-        .map_err(|e| e.to_string())?;
+        .open(&file_path)?;
 
-    let mut json_value = serde_json::to_value(ppkt).map_err(|e| e.to_string())?;
+    let mut json_value = serde_json::to_value(ppkt)?;
     PpktExporter::strip_phenopacket_defaults(&mut json_value);
     
-    serde_json::to_writer_pretty(file, &json_value)
-        .map_err(|e| e.to_string())?; 
+    serde_json::to_writer_pretty(file, &json_value)?; 
     Ok(())
 }
 
@@ -143,7 +171,7 @@ fn write_ppkt(ppkt: &Phenopacket, file_path: PathBuf) -> Result<(), String> {
 /// On success, returns a vector of `Phenopacket` objects generated from
 /// the cohort.
 ///
-/// On error, returns a [`String`] describing the failure (e.g. if the exporter fails).
+/// On error, returns a [`PheToolsError`] describing the failure (e.g. if the exporter fails).
 ///
 /// # Errors
 ///
@@ -153,8 +181,241 @@ pub fn get_phenopackets(
     cohort_dto: CohortData, 
     orcid: String,
     hpo: Arc<FullCsrOntology>) 
--> Result<Vec<Phenopacket>, String> { 
-    let exporter = PpktExporter::new(hpo.clone(), &orcid, cohort_dto);
-    let ppkt_list: Vec<Phenopacket> = exporter.get_all_phenopackets()?;
+-> Result<Vec<Phenopacket>, PheToolsError> { 
+    let exporter = PpktExporter::new(hpo.clone(), &orcid, cohort_dto).map_err(PheToolsError::PhenopacketExportFailed)?;
+    let ppkt_list: Vec<Phenopacket> = exporter.get_all_phenopackets().map_err(PheToolsError::PhenopacketExportFailed)?;
     Ok(ppkt_list)
-}
\ No newline at end of file
+}
+
+/// Write all phenopackets of the cohort to a single file as a JSON array (`[{ppkt1}, {ppkt2}, ...]`).
+/// The phenopackets are sorted by PMID and individual id, as in [`write_phenopackets`].
+///
+/// Returns the number of phenopackets written.
+pub fn export_phenopackets_as_json_array(
+    cohort: CohortData,
+    output_path: &Path,
+    orcid: &str,
+    hpo: Arc<FullCsrOntology>,
+) -> Result<usize, PheToolsError> {
+    let packets = stripped_phenopacket_values(cohort, orcid, hpo)?;
+    let file = create_output_file(output_path)?;
+    serde_json::to_writer_pretty(BufWriter::new(file), &packets)?;
+    Ok(packets.len())
+}
+
+/// Write all phenopackets of the cohort to a single file as newline-delimited JSON (one phenopacket per line).
+/// The phenopackets are sorted by PMID and individual id, as in [`write_phenopackets`].
+///
+/// Returns the number of phenopackets written.
+pub fn export_phenopackets_as_ndjson(
+    cohort: CohortData,
+    output_path: &Path,
+    orcid: &str,
+    hpo: Arc<FullCsrOntology>,
+) -> Result<usize, PheToolsError> {
+    let packets = stripped_phenopacket_values(cohort, orcid, hpo)?;
+    let file = create_output_file(output_path)?;
+    let mut writer = BufWriter::new(file);
+    for packet in &packets {
+        serde_json::to_writer(&mut writer, packet)?;
+        writeln!(writer)?;
+    }
+    writer.flush()?;
+    Ok(packets.len())
+}
+
+/// The JSON representation of the phenopackets of the cohort (without unset default values)
+fn stripped_phenopacket_values(
+    mut cohort: CohortData,
+    orcid: &str,
+    hpo: Arc<FullCsrOntology>,
+) -> Result<Vec<serde_json::Value>, PheToolsError> {
+    cohort.sort_by(|row| (row.individual_data.pmid.clone(), row.individual_data.individual_id.clone()));
+    let exporter = PpktExporter::new(hpo, orcid, cohort).map_err(PheToolsError::PhenopacketExportFailed)?;
+    exporter.get_all_phenopackets().map_err(PheToolsError::PhenopacketExportFailed)?
+        .iter()
+        .map(|ppkt| {
+            let mut json_value = serde_json::to_value(ppkt)?;
+            PpktExporter::strip_phenopacket_defaults(&mut json_value);
+            Ok(json_value)
+        })
+        .collect()
+}
+
+fn create_output_file(path: &Path) -> Result<File, PheToolsError> {
+    let file = OpenOptions::new()
+        .write(true)
+        .create(true)
+        .truncate(true)
+        .open(path)
+        .map_err(|e| std::io::Error::new(e.kind(), format!("Could not open {}: {}", path.display(), e)))?;
+    Ok(file)
+}
+
+
+/// Create a GA4GH [`Family`] message for the proband `proband_id` and (up to two) parents, all of whom
+/// must be individuals of the cohort. The father and mother are distinguished by their sex (M/F).
+///
+/// The family contains the phenopacket of the proband, the phenopackets of the parents as relatives,
+/// and a pedigree in which an individual is affected if any HPO term was observed.
+///
+/// # Errors
+///
+/// Returns [`PheToolsError::InvalidCohort`] if an individual is not part of the cohort or if the sex of the
+/// parents does not allow us to tell father and mother apart, and [`PheToolsError::PhenopacketExportFailed`]
+/// if phenopacket construction fails.
+pub fn export_as_family(
+    proband_id: &str,
+    parent_ids: &[String],
+    cohort: &CohortData,
+    hpo: Arc<FullCsrOntology>,
+    orcid: &str,
+) -> Result<Family, PheToolsError> {
+    if parent_ids.len() > 2 {
+        return Err(PheToolsError::InvalidCohort(
+            format!("A proband can have at most two parents but got {}", parent_ids.len())));
+    }
+    let proband_row = cohort.get_individual_by_id(proband_id)
+        .ok_or_else(|| PheToolsError::InvalidCohort(format!("Could not find proband '{proband_id}' in cohort")))?;
+    let exporter = PpktExporter::new(hpo, orcid, cohort.clone()).map_err(PheToolsError::PhenopacketExportFailed)?;
+    let errors = exporter.validate_before_export();
+    if !errors.is_empty() {
+        let messages: Vec<String> = errors.iter().map(|e| e.to_string()).collect();
+        return Err(PheToolsError::PhenopacketExportFailed(format!("Cannot export family: {}", messages.join("; "))));
+    }
+    let proband = exporter.extract_phenopacket_from_row(proband_row).map_err(PheToolsError::PhenopacketExportFailed)?;
+    let family_id = format!("{}-family", proband.id);
+    let mut paternal_id = String::default();
+    let mut maternal_id = String::default();
+    let mut relatives: Vec<Phenopacket> = Vec::with_capacity(parent_ids.len());
+    let mut persons: Vec<Person> = Vec::with_capacity(parent_ids.len() + 1);
+    for parent_id in parent_ids {
+        let parent_row = cohort.get_individual_by_id(parent_id)
+            .filter(|_| parent_id != proband_id)
+            .ok_or_else(|| PheToolsError::InvalidCohort(format!("Could not find parent '{parent_id}' in cohort")))?;
+        match parent_row.individual_data.sex.as_str() {
+            "M" if paternal_id.is_empty() => paternal_id = parent_id.clone(),
+            "F" if maternal_id.is_empty() => maternal_id = parent_id.clone(),
+            other => {
+                return Err(PheToolsError::InvalidCohort(
+                    format!("Cannot determine whether '{parent_id}' (sex: '{other}') is the father or the mother of '{proband_id}'")));
+            }
+        }
+        persons.push(pedigree_person(&exporter, &family_id, parent_row, "", "").map_err(PheToolsError::PhenopacketExportFailed)?);
+        relatives.push(exporter.extract_phenopacket_from_row(parent_row).map_err(PheToolsError::PhenopacketExportFailed)?);
+    }
+    persons.insert(0, pedigree_person(&exporter, &family_id, proband_row, &paternal_id, &maternal_id).map_err(PheToolsError::PhenopacketExportFailed)?);
+    Ok(Family {
+        id: family_id,
+        meta_data: proband.meta_data.clone(),
+        proband: Some(proband),
+        relatives,
+        consanguinous_parents: false,
+        pedigree: Some(Pedigree { persons }),
+        files: vec![],
+    })
+}
+
+fn pedigree_person(
+    exporter: &PpktExporter,
+    family_id: &str,
+    row: &RowData,
+    paternal_id: &str,
+    maternal_id: &str,
+) -> Result<Person, String> {
+    let affected_status = if row.hpo_data.iter().any(|cell| cell.is_present()) {
+        AffectedStatus::Affected
+    } else if row.hpo_data.iter().any(|cell| cell.is_excluded()) {
+        AffectedStatus::Unaffected
+    } else {
+        AffectedStatus::Missing
+    };
+    Ok(Person {
+        family_id: family_id.to_string(),
+        individual_id: row.individual_data.individual_id.clone(),
+        paternal_id: paternal_id.to_string(),
+        maternal_id: maternal_id.to_string(),
+        sex: exporter.extract_individual(row)?.sex,
+        affected_status: affected_status.into(),
+    })
+}
+
+/// Write a [`Family`] to a JSON file on disk (overwriting an existing file).
+/// As for individual phenopackets, default values that were not actually set are removed from the output.
+pub fn write_family_phenopacket(family: &Family, path: &Path) -> Result<(), PheToolsError> {
+    let file = create_output_file(path)?;
+    let mut json_value = serde_json::to_value(family)?;
+    if let Some(proband) = json_value.get_mut("proband") {
+        PpktExporter::strip_phenopacket_defaults(proband);
+    }
+    if let Some(serde_json::Value::Array(relatives)) = json_value.get_mut("relatives") {
+        relatives.iter_mut().for_each(PpktExporter::strip_phenopacket_defaults);
+    }
+    serde_json::to_writer_pretty(file, &json_value)?;
+    Ok(())
+}
+
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use rstest::rstest;
+    use crate::test_utils::fixtures::{fbn1_cohort, hpo};
+
+    #[rstest]
+    fn test_progress_callback(hpo: Arc<FullCsrOntology>, fbn1_cohort: CohortData) {
+        let dir = std::env::temp_dir().join(format!("{}_{}", "phetools_test_write_progress", std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+        let mut calls = vec![];
+        let first = write_phenopackets_with_progress(fbn1_cohort.clone(), dir.clone(), "ORCID:0000-0002-0736-9199".to_string(), hpo.clone(), true, |p, q| calls.push((p, q)));
+        // existing files are not overwritten but are still reported as progress
+        let mut n_calls = 0;
+        let second = write_phenopackets_with_progress(fbn1_cohort, dir.clone(), "ORCID:0000-0002-0736-9199".to_string(), hpo, false, |_, _| n_calls += 1);
+        std::fs::remove_dir_all(&dir).unwrap();
+        assert_eq!(3, first.unwrap());
+        assert_eq!(vec![(1, 3), (2, 3), (3, 3)], calls);
+        assert_eq!(0, second.unwrap());
+        assert_eq!(3, n_calls);
+    }
+
+    #[rstest]
+    fn test_filename_collision_is_rejected(hpo: Arc<FullCsrOntology>, mut fbn1_cohort: CohortData) {
+        fbn1_cohort.rows[1].individual_data.individual_id = "Individual_1".to_string();
+        let dir = std::env::temp_dir().join(format!("phetools_test_write_collision_{}", std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+        let result = write_phenopackets(fbn1_cohort, dir.clone(), "ORCID:0000-0002-0736-9199".to_string(), hpo, true);
+        let n_files = std::fs::read_dir(&dir).unwrap().count();
+        std::fs::remove_dir_all(&dir).unwrap();
+        let err = result.unwrap_err().to_string();
+        assert!(err.contains("'Individual 1' and 'Individual_1'"), "unexpected error: {err}");
+        assert_eq!(0, n_files);
+    }
+
+    #[rstest]
+    fn test_export_json_array(hpo: Arc<FullCsrOntology>, fbn1_cohort: CohortData) {
+        let path = std::env::temp_dir().join(format!("phetools_test_ppkt_array_{}.json", std::process::id()));
+        let n = export_phenopackets_as_json_array(fbn1_cohort, &path, "ORCID:0000-0002-0736-9199", hpo).unwrap();
+        let contents = std::fs::read_to_string(&path).unwrap();
+        std::fs::remove_file(&path).unwrap();
+        assert_eq!(3, n);
+        let packets: Vec<Phenopacket> = serde_json::from_str(&contents).unwrap();
+        assert_eq!(3, packets.len());
+        assert_eq!("Individual 1", packets[0].subject.as_ref().unwrap().id);
+        assert!(!contents.contains("UNKNOWN_KARYOTYPE"));
+    }
+
+    #[rstest]
+    fn test_export_ndjson(hpo: Arc<FullCsrOntology>, fbn1_cohort: CohortData) {
+        let path = std::env::temp_dir().join(format!("phetools_test_ppkt_{}.ndjson", std::process::id()));
+        let n = export_phenopackets_as_ndjson(fbn1_cohort, &path, "ORCID:0000-0002-0736-9199", hpo).unwrap();
+        let contents = std::fs::read_to_string(&path).unwrap();
+        std::fs::remove_file(&path).unwrap();
+        assert_eq!(3, n);
+        let lines: Vec<&str> = contents.lines().collect();
+        assert_eq!(3, lines.len());
+        for line in lines {
+            let ppkt: Phenopacket = serde_json::from_str(line).unwrap();
+            assert!(!ppkt.phenotypic_features.is_empty());
+        }
+    }
+}
@@ -0,0 +1,279 @@
+//! Phenopacket diff
+//!
+//! Field-level comparison of two phenopackets, e.g., to check which phenopackets of a cohort change
+//! after an HPO version update or after curation corrections. The metadata (creation time, resources)
+//! is not compared, because it changes whenever the phenopackets are exported again.
+use std::collections::BTreeMap;
+use std::fmt;
+
+use phenopackets::schema::v2::core::genomic_interpretation::Call;
+use phenopackets::schema::v2::core::{PhenotypicFeature, VariationDescriptor};
+use phenopackets::schema::v2::Phenopacket;
+use serde::Serialize;
+
+
+/// A difference between two phenopackets `a` and `b` ("added" means present in `b` but not in `a`)
+#[derive(Clone, Debug, PartialEq)]
+pub enum PpktFieldDiff {
+    /// The phenopacket ids differ
+    Id { a: String, b: String },
+    /// A field of the subject (e.g., sex or age at last encounter) differs
+    Subject { field: String, a: String, b: String },
+    AddedFeature { hpo_id: String, label: String },
+    RemovedFeature { hpo_id: String, label: String },
+    /// The feature is present in both phenopackets, but e.g. its onset, modifiers, or exclusion status differ
+    ChangedFeature { hpo_id: String, label: String },
+    AddedVariant { variant_id: String },
+    RemovedVariant { variant_id: String },
+    /// The variant is present in both phenopackets, but e.g. its allelic state differs
+    ChangedVariant { variant_id: String },
+    AddedDisease { disease_id: String },
+    RemovedDisease { disease_id: String },
+}
+
+impl fmt::Display for PpktFieldDiff {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            PpktFieldDiff::Id { a, b } => write!(f, "id: '{a}' -> '{b}'"),
+            PpktFieldDiff::Subject { field, a, b } => write!(f, "subject.{field}: '{a}' -> '{b}'"),
+            PpktFieldDiff::AddedFeature { hpo_id, label } => write!(f, "+ feature {label} ({hpo_id})"),
+            PpktFieldDiff::RemovedFeature { hpo_id, label } => write!(f, "- feature {label} ({hpo_id})"),
+            PpktFieldDiff::ChangedFeature { hpo_id, label } => write!(f, "~ feature {label} ({hpo_id})"),
+            PpktFieldDiff::AddedVariant { variant_id } => write!(f, "+ variant {variant_id}"),
+            PpktFieldDiff::RemovedVariant { variant_id } => write!(f, "- variant {variant_id}"),
+            PpktFieldDiff::ChangedVariant { variant_id } => write!(f, "~ variant {variant_id}"),
+            PpktFieldDiff::AddedDisease { disease_id } => write!(f, "+ disease {disease_id}"),
+            PpktFieldDiff::RemovedDisease { disease_id } => write!(f, "- disease {disease_id}"),
+        }
+    }
+}
+
+
+/// Compare two phenopackets field by field. The order of repeated elements (features, variants, diseases)
+/// does not matter. Returns an empty list if the phenopackets are equivalent.
+pub fn diff_phenopackets(a: &Phenopacket, b: &Phenopacket) -> Vec<PpktFieldDiff> {
+    let mut diffs = Vec::new();
+    if a.id != b.id {
+        diffs.push(PpktFieldDiff::Id { a: a.id.clone(), b: b.id.clone() });
+    }
+    diffs.extend(diff_subject(a, b));
+    diffs.extend(diff_features(a, b));
+    diffs.extend(diff_variants(a, b));
+    let diseases_a = keyed(a.diseases.iter().map(|d| (d.term.as_ref().map(|t| t.id.clone()).unwrap_or_default(), d)));
+    let diseases_b = keyed(b.diseases.iter().map(|d| (d.term.as_ref().map(|t| t.id.clone()).unwrap_or_default(), d)));
+    for disease_id in diseases_a.keys().filter(|id| !diseases_b.contains_key(*id)) {
+        diffs.push(PpktFieldDiff::RemovedDisease { disease_id: disease_id.clone() });
+    }
+    for disease_id in diseases_b.keys().filter(|id| !diseases_a.contains_key(*id)) {
+        diffs.push(PpktFieldDiff::AddedDisease { disease_id: disease_id.clone() });
+    }
+    diffs
+}
+
+/// True if the phenopackets have the same content, regardless of the order of repeated elements
+/// and of the metadata.
+pub fn are_phenopackets_semantically_equivalent(a: &Phenopacket, b: &Phenopacket) -> bool {
+    normalize(a) == normalize(b)
+}
+
+
+fn diff_subject(a: &Phenopacket, b: &Phenopacket) -> Vec<PpktFieldDiff> {
+    let (Some(subject_a), Some(subject_b)) = (&a.subject, &b.subject) else {
+        if a.subject.is_some() != b.subject.is_some() {
+            return vec![PpktFieldDiff::Subject {
+                field: "subject".to_string(),
+                a: a.subject.as_ref().map(|s| s.id.clone()).unwrap_or_default(),
+                b: b.subject.as_ref().map(|s| s.id.clone()).unwrap_or_default(),
+            }];
+        }
+        return vec![];
+    };
+    let fields = [
+        ("id", to_json(&subject_a.id), to_json(&subject_b.id)),
+        ("sex", to_json(&subject_a.sex), to_json(&subject_b.sex)),
+        ("timeAtLastEncounter", to_json(&subject_a.time_at_last_encounter), to_json(&subject_b.time_at_last_encounter)),
+        ("vitalStatus", to_json(&subject_a.vital_status), to_json(&subject_b.vital_status)),
+        ("dateOfBirth", to_json(&subject_a.date_of_birth), to_json(&subject_b.date_of_birth)),
+    ];
+    fields
+        .into_iter()
+        .filter(|(_, value_a, value_b)| value_a != value_b)
+        .map(|(field, a, b)| PpktFieldDiff::Subject { field: field.to_string(), a, b })
+        .collect()
+}
+
+fn diff_features(a: &Phenopacket, b: &Phenopacket) -> Vec<PpktFieldDiff> {
+    let features_a = keyed(a.phenotypic_features.iter().map(|pf| (feature_id(pf), pf)));
+    let features_b = keyed(b.phenotypic_features.iter().map(|pf| (feature_id(pf), pf)));
+    let mut diffs = Vec::new();
+    for (hpo_id, pf_a) in &features_a {
+        match features_b.get(hpo_id) {
+            None => diffs.push(PpktFieldDiff::RemovedFeature { hpo_id: hpo_id.clone(), label: feature_label(pf_a) }),
+            Some(pf_b) if normalized_feature(pf_a) != normalized_feature(pf_b) => {
+                diffs.push(PpktFieldDiff::ChangedFeature { hpo_id: hpo_id.clone(), label: feature_label(pf_b) });
+            }
+            Some(_) => {}
+        }
+    }
+    for (hpo_id, pf_b) in &features_b {
+        if !features_a.contains_key(hpo_id) {
+            diffs.push(PpktFieldDiff::AddedFeature { hpo_id: hpo_id.clone(), label: feature_label(pf_b) });
+        }
+    }
+    diffs
+}
+
+fn diff_variants(a: &Phenopacket, b: &Phenopacket) -> Vec<PpktFieldDiff> {
+    let variants_a = keyed(variation_descriptors(a).map(|vd| (vd.id.clone(), vd)));
+    let variants_b = keyed(variation_descriptors(b).map(|vd| (vd.id.clone(), vd)));
+    let mut diffs = Vec::new();
+    for (variant_id, vd_a) in &variants_a {
+        match variants_b.get(variant_id) {
+            None => diffs.push(PpktFieldDiff::RemovedVariant { variant_id: variant_id.clone() }),
+            Some(vd_b) if to_json(vd_a) != to_json(vd_b) => {
+                diffs.push(PpktFieldDiff::ChangedVariant { variant_id: variant_id.clone() });
+            }
+            Some(_) => {}
+        }
+    }
+    for variant_id in variants_b.keys().filter(|id| !variants_a.contains_key(*id)) {
+        diffs.push(PpktFieldDiff::AddedVariant { variant_id: variant_id.clone() });
+    }
+    diffs
+}
+
+fn variation_descriptors(ppkt: &Phenopacket) -> impl Iterator<Item = &VariationDescriptor> {
+    ppkt.interpretations.iter()
+        .filter_map(|interpretation| interpretation.diagnosis.as_ref())
+        .flat_map(|diagnosis| diagnosis.genomic_interpretations.iter())
+        .filter_map(|gi| match &gi.call {
+            Some(Call::VariantInterpretation(vi)) => vi.variation_descriptor.as_ref(),
+            _ => None,
+        })
+}
+
+fn feature_id(pf: &PhenotypicFeature) -> String {
+    pf.r#type.as_ref().map(|t| t.id.clone()).unwrap_or_default()
+}
+
+fn feature_label(pf: &PhenotypicFeature) -> String {
+    pf.r#type.as_ref().map(|t| t.label.clone()).unwrap_or_default()
+}
+
+/// The feature with its modifiers in a fixed order
+fn normalized_feature(pf: &PhenotypicFeature) -> PhenotypicFeature {
+    let mut pf = pf.clone();
+    pf.modifiers.sort_by(|x, y| x.id.cmp(&y.id));
+    pf
+}
+
+fn keyed<'a, T>(items: impl Iterator<Item = (String, &'a T)>) -> BTreeMap<String, &'a T> {
+    items.collect()
+}
+
+fn to_json<T: Serialize>(value: &T) -> String {
+    serde_json::to_string(value).unwrap_or_default()
+}
+
+fn sort_by_json<T: Serialize>(items: &mut [T]) {
+    items.sort_by_cached_key(to_json);
+}
+
+/// Copy of the phenopacket without metadata and with all repeated elements in a canonical order
+fn normalize(ppkt: &Phenopacket) -> Phenopacket {
+    let mut ppkt = ppkt.clone();
+    ppkt.meta_data = None;
+    ppkt.phenotypic_features = ppkt.phenotypic_features.iter().map(normalized_feature).collect();
+    sort_by_json(&mut ppkt.phenotypic_features);
+    for interpretation in &mut ppkt.interpretations {
+        if let Some(diagnosis) = interpretation.diagnosis.as_mut() {
+            sort_by_json(&mut diagnosis.genomic_interpretations);
+        }
+    }
+    sort_by_json(&mut ppkt.interpretations);
+    sort_by_json(&mut ppkt.diseases);
+    sort_by_json(&mut ppkt.measurements);
+    sort_by_json(&mut ppkt.biosamples);
+    sort_by_json(&mut ppkt.medical_actions);
+    sort_by_json(&mut ppkt.files);
+    ppkt
+}
+
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use std::sync::Arc;
+    use ontolius::ontology::csr::FullCsrOntology;
+    use phenopackets::schema::v2::core::Sex;
+    use rstest::rstest;
+    use crate::dto::cohort_dto::CohortData;
+    use crate::ppkt::ppkt_exporter::PpktExporter;
+    use crate::test_utils::fixtures::{fbn1_cohort, hpo};
+
+    fn phenopackets(hpo: Arc<FullCsrOntology>, cohort: CohortData) -> Vec<Phenopacket> {
+        PpktExporter::new(hpo, "ORCID:0000-0002-0736-9199", cohort)
+            .unwrap()
+            .get_all_phenopackets()
+            .unwrap()
+    }
+
+    #[rstest]
+    fn test_same_cohort_is_equivalent(hpo: Arc<FullCsrOntology>, fbn1_cohort: CohortData) {
+        let first = phenopackets(hpo.clone(), fbn1_cohort.clone());
+        let second = phenopackets(hpo, fbn1_cohort);
+        for (a, b) in first.iter().zip(second.iter()) {
+            assert!(diff_phenopackets(a, b).is_empty());
+            assert!(are_phenopackets_semantically_equivalent(a, b));
+        }
+        assert!(!are_phenopackets_semantically_equivalent(&first[0], &first[1]));
+    }
+
+    #[rstest]
+    fn test_order_is_ignored(hpo: Arc<FullCsrOntology>, fbn1_cohort: CohortData) {
+        let a = phenopackets(hpo, fbn1_cohort).remove(0);
+        let mut b = a.clone();
+        b.phenotypic_features.reverse();
+        assert_ne!(a, b);
+        assert!(are_phenopackets_semantically_equivalent(&a, &b));
+        assert!(diff_phenopackets(&a, &b).is_empty());
+    }
+
+    #[rstest]
+    fn test_feature_diffs(hpo: Arc<FullCsrOntology>, fbn1_cohort: CohortData) {
+        let a = phenopackets(hpo, fbn1_cohort).remove(0);
+        let mut b = a.clone();
+        // Individual 1: Arachnodactyly, Atrial septal defect, Long hallux (excluded)
+        let removed = b.phenotypic_features.remove(0);
+        b.phenotypic_features[0].excluded = true;
+        let diffs = diff_phenopackets(&a, &b);
+        assert_eq!(2, diffs.len());
+        assert!(diffs.contains(&PpktFieldDiff::RemovedFeature {
+            hpo_id: feature_id(&removed),
+            label: feature_label(&removed),
+        }));
+        assert!(diffs.contains(&PpktFieldDiff::ChangedFeature {
+            hpo_id: "HP:0001631".to_string(),
+            label: "Atrial septal defect".to_string(),
+        }));
+        let reverse = diff_phenopackets(&b, &a);
+        assert!(reverse.iter().any(|d| matches!(d, PpktFieldDiff::AddedFeature { hpo_id, .. } if hpo_id == "HP:0001166")));
+    }
+
+    #[rstest]
+    fn test_subject_and_variant_diffs(hpo: Arc<FullCsrOntology>, fbn1_cohort: CohortData) {
+        let packets = phenopackets(hpo, fbn1_cohort);
+        let a = &packets[0];
+        let mut b = a.clone();
+        b.subject.as_mut().unwrap().sex = Sex::Female.into();
+        let diffs = diff_phenopackets(a, &b);
+        assert_eq!(1, diffs.len());
+        assert!(matches!(&diffs[0], PpktFieldDiff::Subject { field, .. } if field == "sex"));
+        // Individual 3 carries the structural variant instead of the HGVS variant
+        let mut c = a.clone();
+        c.interpretations = packets[2].interpretations.clone();
+        let diffs = diff_phenopackets(a, &c);
+        assert!(diffs.iter().any(|d| matches!(d, PpktFieldDiff::RemovedVariant { .. })));
+        assert!(diffs.iter().any(|d| matches!(d, PpktFieldDiff::AddedVariant { .. })));
+    }
+}
@@ -1,23 +1,26 @@
 //! Module to export GA4GH Phenopackets from the information in the template.
 
 
-use std::collections::HashMap;
-use std::str::FromStr;
-use std::sync::Arc;
-use std::sync::LazyLock;
-use ontolius::term::MinimalTerm;
-use ontolius::{Identified, TermId};
+use std::collections::{HashMap, HashSet};
+use std::fmt;
+use std::sync::{Arc, LazyLock};
 use ontolius::ontology::csr::FullCsrOntology;
-use ontolius::ontology::{HierarchyQueries, MetadataAware, OntologyTerms};
+use ontolius::ontology::{MetadataAware, OntologyTerms};
+use ontolius::Identified;
 use phenopacket_tools::builders::time_elements::time_element_from_str;
 use phenopackets::schema::v2::core::{KaryotypicSex, OntologyClass};
+use phenopackets::schema::v2::core::measurement::MeasurementValue;
+use phenopackets::schema::v2::core::medical_action::Action;
+use phenopackets::schema::v2::core::value;
 use phenopackets::schema::v2::core::vital_status::Status;
-use phenopackets::schema::v2::core::{Disease, ExternalReference, Individual, MetaData, PhenotypicFeature, Sex, VitalStatus};
+use phenopackets::schema::v2::core::{Biosample, Disease, ExternalReference, Individual, MedicalAction, Measurement, MetaData, PhenotypicFeature, Quantity, Sex, Treatment, VitalStatus};
 use phenopackets::schema::v2::Phenopacket;
 use regex::Regex;
 use serde_json::Value;
+use crate::age;
 use crate::dto::cohort_dto::{CohortData, DiseaseData, RowData};
-use crate::dto::hpo_term_dto::HpoTermDuplet;
+use crate::dto::hpo_term_dto::{CurationConfidence, HpoTermData};
+use crate::dto::orcid::validate_orcid;
 use crate::ppkt::ppkt_variant_exporter::PpktVariantExporter;
 use phenopacket_tools;
 use phenopacket_tools::builders::builder::Builder;
@@ -28,58 +31,87 @@ const DEFAULT_OMIM_VERSION: &str =  "06/01/25";
 const DEFAULT_GENO_VERSION: &str =  "2025-07-25";
 const DEFAULT_SO_VERSION: &str = "2024-11-18";
 
-static CLINICAL_MODIFIER: LazyLock<TermId> = LazyLock::new(|| {
-    "HP:0012823".parse().expect("Failed to parse static HP:0012823")
-});
-/// HPO term Severity (HP:0012824)
-static SEVERITY: LazyLock<TermId> = LazyLock::new(||{
-    "HP:0012824".parse().expect("Failed to parse static HP:0012824")
-});
-/// All valid severity terms
-static SEVERITY_MAP: LazyLock<HashMap<String, OntologyClass>> = LazyLock::new(||{
-    let mut smap = HashMap::new();
-    for (label, id) in [
-        ("Borderline","HP:0012827"),
-        ("Mild", "HP:0012825"),
-        ("Moderate", "HP:0012826"),
-        ("Severe", "HP:0012828"),
-        ("Profound", "HP:0012829")
-    ] {
-        smap.insert(id.to_string(), OntologyClass{id: id.to_string(), label: label.to_string()});
-    }
-    smap
+/// Treatment entry with a CHEBI CURIE and an optional label, e.g., CHEBI:45783 imatinib
+static CHEBI_RE: LazyLock<Regex> = LazyLock::new(|| {
+    Regex::new(r"^(CHEBI:\d+)\s*(.*)$").unwrap()
 });
 
+/// Versions of the ontologies and other resources (besides the HPO, whose version is taken from the loaded
+/// ontology) that are listed in the MetaData of the exported phenopackets
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct ExportVersions {
+    pub so_version: String,
+    pub geno_version: String,
+    pub omim_version: String,
+    pub hgnc_version: String,
+}
+
+impl Default for ExportVersions {
+    fn default() -> Self {
+        Self {
+            so_version: DEFAULT_SO_VERSION.to_string(),
+            geno_version: DEFAULT_GENO_VERSION.to_string(),
+            omim_version: DEFAULT_OMIM_VERSION.to_string(),
+            hgnc_version: DEFAULT_HGNC_VERSION.to_string(),
+        }
+    }
+}
+
+/// Kinds of problems that would prevent the export of a phenopacket
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum PpktErrorType {
+    /// A variant key of a row is not one of the validated variants of the cohort
+    UnknownVariant,
+    /// A disease id of a row is not one of the diseases of the cohort
+    UnknownDisease,
+    /// An HPO column does not correspond to a current (primary) HPO term id
+    UnknownHpoTerm,
+    /// A modifier of an HPO annotation is not a current HPO term below Clinical modifier (HP:0012823)
+    InvalidModifier,
+}
+
+/// A problem found by [`PpktExporter::validate_before_export`].
+/// `individual_id` is empty for problems that concern the cohort as a whole (HPO columns).
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct PpktExportError {
+    pub individual_id: String,
+    pub error_type: PpktErrorType,
+    pub message: String,
+}
+
+impl fmt::Display for PpktExportError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if self.individual_id.is_empty() {
+            write!(f, "{}", self.message)
+        } else {
+            write!(f, "{}: {}", self.individual_id, self.message)
+        }
+    }
+}
+
 
 /// Structure to export phenopackets from a CohortData object.
 pub struct PpktExporter {
     /// Reference to the Ontolius Human Phenotype Ontology Full CSR object
     hpo: Arc<FullCsrOntology>,
-    geno_version: String,
-    omim_version: String,
-    hgnc_version: String,
-    so_version: String,
+    versions: ExportVersions,
     orcid_id: String,
     cohort_dto: CohortData,
     disease_id_map: HashMap<String, DiseaseData>,
+    /// If set, annotations with a lower curation confidence are not exported
+    min_confidence: Option<CurationConfidence>,
 }
 
 impl PpktExporter {
 
 
+    /// The creator ORCID must have the form `ORCID:XXXX-XXXX-XXXX-XXXX` (see [`validate_orcid`])
     pub fn new( 
         hpo: Arc<FullCsrOntology>,
         creator_orcid: &str,
         cohort: CohortData
-    ) -> Self {
-        Self::from_versions(
-            hpo,
-            DEFAULT_GENO_VERSION,
-            DEFAULT_OMIM_VERSION,
-            DEFAULT_HGNC_VERSION,
-            DEFAULT_SO_VERSION,
-            creator_orcid,
-            cohort)
+    ) -> Result<Self, String> {
+        Self::from_export_versions(hpo, ExportVersions::default(), creator_orcid, cohort)
     }
 
     pub fn from_versions(
@@ -90,21 +122,64 @@ impl PpktExporter {
         so_version: &str,
         creator_orcid: &str,
         cohort: CohortData
-    ) -> Self {
+    ) -> Result<Self, String> {
+        let versions = ExportVersions {
+            so_version: so_version.to_string(),
+            geno_version: geno_version.to_string(),
+            omim_version: omim_version.to_string(),
+            hgnc_version: hgnc_version.to_string(),
+        };
+        Self::from_export_versions(hpo, versions, creator_orcid, cohort)
+    }
+
+    /// Exporter that lists the given resource versions in the MetaData of the phenopackets (see [`Self::new`]
+    /// for the requirements on the creator ORCID)
+    pub fn from_export_versions(
+        hpo: Arc<FullCsrOntology>,
+        versions: ExportVersions,
+        creator_orcid: &str,
+        cohort: CohortData
+    ) -> Result<Self, String> {
+        let orcid_id = validate_orcid(creator_orcid)?;
         let mut disease_map = HashMap::new();
         for d in &cohort.disease_list {
             disease_map.insert(d.disease_id.clone(), d.clone());
         }
-        Self{ 
+        Ok(Self{ 
             hpo, 
-            geno_version: geno_version.to_string(),
-            omim_version: omim_version.to_string(), 
-            hgnc_version: hgnc_version.to_string(),
-            so_version: so_version.to_string(),
-            orcid_id: creator_orcid.to_string(),
+            versions,
+            orcid_id,
             cohort_dto: cohort,
             disease_id_map: disease_map,
-        }
+            min_confidence: None,
+        })
+    }
+
+    /// Skip HPO annotations whose curation confidence is below `min_confidence`.
+    /// Annotations without a confidence are always exported.
+    pub fn with_min_confidence(mut self, min_confidence: CurationConfidence) -> Self {
+        self.min_confidence = Some(min_confidence);
+        self
+    }
+
+    pub fn with_so_version(mut self, version: &str) -> Self {
+        self.versions.so_version = version.to_string();
+        self
+    }
+
+    pub fn with_geno_version(mut self, version: &str) -> Self {
+        self.versions.geno_version = version.to_string();
+        self
+    }
+
+    pub fn with_omim_version(mut self, version: &str) -> Self {
+        self.versions.omim_version = version.to_string();
+        self
+    }
+
+    pub fn with_hgnc_version(mut self, version: &str) -> Self {
+        self.versions.hgnc_version = version.to_string();
+        self
     }
 
 
@@ -130,6 +205,7 @@ impl PpktExporter {
         };
         let last_enc = &individual_dto.age_at_last_encounter;
         if last_enc != "na" {
+            let last_enc = &age::resolve_age(last_enc, individual_dto.age_at_last_encounter_max.as_deref())?;
             let age = time_element_from_str(last_enc)
                 .map_err(|e| format!("malformed time_element for last encounter '{}':{} for {}",last_enc, e.to_string(), idvl.id))?;
             idvl.time_at_last_encounter = Some(age);
@@ -151,19 +227,19 @@ impl PpktExporter {
     } 
 
     pub fn geno_version(&self) -> &str {
-        &self.geno_version
+        &self.versions.geno_version
     } 
 
     pub fn omim_version(&self) -> &str {
-        &self.omim_version
+        &self.versions.omim_version
     } 
 
     pub fn hgnc_version(&self) -> &str {
-        &self.hgnc_version
+        &self.versions.hgnc_version
     } 
 
     fn so_version(&self) -> &str {
-        &self.so_version
+        &self.versions.so_version
     }
 
     fn has_sequence_ontology(&self, ppkt_row: &RowData) -> bool {
@@ -183,7 +259,6 @@ impl PpktExporter {
         let geno = phenopacket_tools::builders::resources::Resources::geno_version(self.geno_version());
         let omim = phenopacket_tools::builders::resources::Resources::omim_version(self.omim_version());
         let hgnc = phenopacket_tools::builders::resources::Resources::hgnc_version(&self.hgnc_version());
-        let indvl_dto = row_dto.individual_data.individual_id.clone();
         let ext_res = ExternalReference{ 
             id: row_dto.individual_data.pmid.clone(), 
             reference: String::default(), 
@@ -206,6 +281,39 @@ impl PpktExporter {
 
     /// Generate the phenopacket identifier from the PMID and the individual identifier
     pub fn get_phenopacket_id(&self, ppkt_row: &RowData) -> String {
+        Self::phenopacket_id_for_row(ppkt_row)
+    }
+
+    /// Same as [`Self::get_phenopacket_id`], but if the identifier is already in `used_ids`, the suffix `_2`, `_3`, etc.
+    /// is appended until the identifier is unique. The returned identifier is added to `used_ids`.
+    pub fn get_unique_phenopacket_id(&self, row: &RowData, used_ids: &mut HashSet<String>) -> String {
+        let ppkt_id = self.get_phenopacket_id(row);
+        let mut unique_id = ppkt_id.clone();
+        let mut n = 1;
+        while used_ids.contains(&unique_id) {
+            n += 1;
+            unique_id = format!("{ppkt_id}_{n}");
+        }
+        used_ids.insert(unique_id.clone());
+        unique_id
+    }
+
+    /// Pairs of individual identifiers whose phenopackets would have the same identifier, and thus the same file name,
+    /// e.g., "P1" and "P-1" with the same PMID. Each individual is paired with the first individual with the same identifier.
+    pub fn check_for_filename_collisions(rows: &[RowData]) -> Vec<(String, String)> {
+        let mut first_individual: HashMap<String, &str> = HashMap::new();
+        let mut collisions = Vec::new();
+        for row in rows {
+            let individual_id = row.individual_data.individual_id.as_str();
+            match first_individual.get(&Self::phenopacket_id_for_row(row)) {
+                Some(first) => collisions.push((first.to_string(), individual_id.to_string())),
+                None => { first_individual.insert(Self::phenopacket_id_for_row(row), individual_id); }
+            }
+        }
+        collisions
+    }
+
+    fn phenopacket_id_for_row(ppkt_row: &RowData) -> String {
         let individual_dto = &ppkt_row.individual_data;
         let pmid = ppkt_row.individual_data.pmid.replace(":", "_");
         let individual_id = individual_dto.individual_id.replace(" ", "_");
@@ -250,9 +358,9 @@ impl PpktExporter {
             };
             // If we have multiple diseases, we cannot automatically say when the disease onset was (which disease has the earliest onset)
             if ! has_multiple_dx {
-                let idl_dto = ppkt_row.individual_data.individual_id.clone();
                 let onset = &ppkt_row.individual_data.age_of_onset;
                 if onset != "na" {
+                    let onset = &age::resolve_age(onset, ppkt_row.individual_data.age_of_onset_max.as_deref())?;
                     let age = time_element_from_str(onset)
                         .map_err(|e| format!("malformed time_element for onset '{}': {}", onset, e.to_string()))?;
                     disease.onset = Some(age);
@@ -269,25 +377,6 @@ impl PpktExporter {
 
 
     
-    fn get_ontology_class(&self, term: &HpoTermDuplet) -> Result<OntologyClass, String> {
-        let hpo_id = term.hpo_id();
-        let hpo_label = term.hpo_label();
-        let hpo_term_id = TermId::from_str(hpo_id).map_err(|e| e.to_string())?;
-        let hpo_term = match self.hpo.term_by_id(&hpo_term_id) {
-            Some(term) => term.clone(),
-            None => {
-                return Err(format!("Could not find HPO term for {hpo_id}"));
-            }
-        };
-        if hpo_term.identifier() != &hpo_term_id {
-            return Err(format!("{} is not the primary id ({}) for {}",
-                hpo_term_id, hpo_term.identifier(), hpo_label));
-        }
-        let hpo_term = Builder::ontology_class(term.hpo_id(), term.hpo_label())
-                .map_err(|e| format!("termid_parse_error '{:?}'", term))?;
-        Ok(hpo_term)
-    }
-
     pub fn get_phenopacket_features(&self, ppkt_row: &RowData) -> Result<Vec<PhenotypicFeature>, String> {
         let hpo_term_list = &self.cohort_dto.hpo_headers;
         let hpo_data = &ppkt_row.hpo_data;
@@ -297,51 +386,101 @@ impl PpktExporter {
         }
         let mut ppkt_feature_list: Vec<PhenotypicFeature> = Vec::with_capacity(hpo_data.len());
         for (term, cell_contents) in hpo_term_list.iter().zip(hpo_data.iter()) {
-            if ! cell_contents.is_ascertained() {
+            // population-level frequencies cannot be represented in the phenopacket of an individual
+            if ! cell_contents.is_ascertained() || cell_contents.is_hpo_frequency() {
                 continue;
             }
-            let hpo_term = self.get_ontology_class(term)?;
-            let mut pf = PhenotypicFeature{ 
-                description: String::default(), 
-                r#type: Some(hpo_term), 
-                excluded: cell_contents.is_excluded(), 
-                severity: None, 
-                modifiers: vec![], 
-                onset: None,
-                resolution: None, 
-                evidence: vec![]
-            };
-            if cell_contents.has_onset() {
-                let ost = time_element_from_str(&cell_contents.entry())
-                    .map_err(|e| format!("malformed time_element for cell '{}': {}", cell_contents, e.to_string()))?;
-                pf.onset = Some(ost);
-            }
-            if cell_contents.has_modifier() {
-                let mut mod_list: Vec<OntologyClass> = Vec::new();
-                for mod_str in cell_contents.modifers() {
-                    let mod_id: TermId = mod_str.parse().map_err(|_| format!("Could not create TermId from modifier String '{mod_str}'"))?;
-                    let term = self.hpo.term_by_id(&mod_id).ok_or_else(|| {
-                        format!("Could not retrieve Modifier term for id '{mod_id}' for {}", ppkt_row.individual_data.pmid)
-                    })?;
-                    if let Some(severity_term) = SEVERITY_MAP.get(mod_str) {
-                        pf.severity = Some(severity_term.clone());
-                    } else if self.hpo.is_descendant_of(term.identifier(), &*CLINICAL_MODIFIER) {
-                        let label = term.name().to_string();
-                        let oclass =  OntologyClass {
-                            id: label,
-                            label: mod_str.clone(),
-                        };
-                        mod_list.push(oclass);
-                    }
+            let hpo_term_data = HpoTermData::new(term.clone(), cell_contents.clone())?;
+            if let (Some(min), Some(confidence)) = (self.min_confidence, hpo_term_data.confidence()) {
+                if confidence < min {
+                    continue;
                 }
-                pf.modifiers = mod_list;
             }
+            crate::hpo::validate_modifier_ids(self.hpo.clone(), &hpo_term_data)
+                .map_err(|errors| format!("{}: {}", ppkt_row.individual_data.pmid, errors.join("; ")))?;
+            let pf = hpo_term_data.to_phenotypic_feature(self.hpo.clone())
+                .map_err(|e| format!("{}: {e}", ppkt_row.individual_data.pmid))?;
             ppkt_feature_list.push(pf);
         }
         Ok(ppkt_feature_list)
     }
 
- fn extract_phenopacket_from_row(
+    /// Create one GA4GH MedicalAction (a Treatment) for each CHEBI term in the treatment list of the individual.
+    /// An entry may consist of the CURIE alone (CHEBI:45783) or of the CURIE followed by the label (CHEBI:45783 imatinib).
+    /// Free-text entries cannot be represented as an ontology class and are skipped.
+    pub fn get_medical_actions(&self, ppkt_row: &RowData) -> Vec<MedicalAction> {
+        ppkt_row.individual_data.treatment_list
+            .iter()
+            .filter_map(|entry| {
+                let Some(caps) = CHEBI_RE.captures(entry.trim()) else {
+                    tracing::warn!(individual = %ppkt_row.individual_data.individual_id, treatment = %entry, "Skipping treatment without CHEBI identifier");
+                    return None;
+                };
+                let agent = OntologyClass {
+                    id: caps[1].to_string(),
+                    label: caps[2].trim().to_string(),
+                };
+                Some(MedicalAction {
+                    action: Some(Action::Treatment(Treatment {
+                        agent: Some(agent),
+                        ..Default::default()
+                    })),
+                    ..Default::default()
+                })
+            })
+            .collect()
+    }
+
+    /// Create a GA4GH Biosample for each sample (UBERON tissue and optional NCIT histology) of the individual
+    pub fn get_biosamples(&self, ppkt_row: &RowData) -> Vec<Biosample> {
+        ppkt_row.individual_data.biosamples
+            .iter()
+            .map(|sample| Biosample {
+                id: sample.biosample_id.clone(),
+                individual_id: ppkt_row.individual_data.individual_id.clone(),
+                sampled_tissue: Some(OntologyClass {
+                    id: sample.tissue_id.clone(),
+                    label: sample.tissue_label.clone(),
+                }),
+                histological_diagnosis: sample.histology_id.as_ref().map(|histology_id| OntologyClass {
+                    id: histology_id.clone(),
+                    label: String::default(),
+                }),
+                ..Default::default()
+            })
+            .collect()
+    }
+
+    /// Create a GA4GH Measurement (a quantity with a UCUM unit) for each measurement of the individual
+    pub fn get_measurements(&self, ppkt_row: &RowData) -> Result<Vec<Measurement>, String> {
+        let mut measurements = Vec::with_capacity(ppkt_row.individual_data.measurements.len());
+        for m in &ppkt_row.individual_data.measurements {
+            let time_observed = match &m.time_observed {
+                Some(age) => Some(time_element_from_str(age)
+                    .map_err(|e| format!("malformed time_element for measurement '{}': {} for {}", age, e, ppkt_row.individual_data.individual_id))?),
+                None => None,
+            };
+            let unit = OntologyClass {
+                id: m.unit_id.clone(),
+                label: m.unit_id.split_once(':').map(|(_, code)| code).unwrap_or(&m.unit_id).to_string(),
+            };
+            measurements.push(Measurement {
+                assay: Some(OntologyClass { id: m.assay_id.clone(), label: String::default() }),
+                measurement_value: Some(MeasurementValue::Value(phenopackets::schema::v2::core::Value {
+                    value: Some(value::Value::Quantity(Quantity {
+                        unit: Some(unit),
+                        value: m.value,
+                        reference_range: None,
+                    })),
+                })),
+                time_observed,
+                ..Default::default()
+            });
+        }
+        Ok(measurements)
+    }
+
+ pub(crate) fn extract_phenopacket_from_row(
         &self, 
         ppkt_row_dto: &RowData, 
     ) -> Result<Phenopacket, String> {
@@ -354,11 +493,11 @@ impl PpktExporter {
             id: self.get_phenopacket_id(ppkt_row_dto), 
             subject:  Some(self.extract_individual(ppkt_row_dto)?), 
             phenotypic_features: self.get_phenopacket_features(ppkt_row_dto)?, 
-            measurements: vec![], 
-            biosamples: vec![], 
+            measurements: self.get_measurements(ppkt_row_dto)?, 
+            biosamples: self.get_biosamples(ppkt_row_dto), 
             interpretations: interpretation_list, 
             diseases: self.get_disease_list(ppkt_row_dto)?, 
-            medical_actions: vec![], 
+            medical_actions: self.get_medical_actions(ppkt_row_dto), 
             files: vec![], 
             meta_data: Some(self.get_meta_data(ppkt_row_dto)?) 
         };
@@ -403,7 +542,68 @@ pub fn strip_phenopacket_defaults(root: &mut Value) {
     }
 }
 
+    /// Check the cohort for problems that would make the export fail, so that all of them can be reported at once
+    pub fn validate_before_export(&self) -> Vec<PpktExportError> {
+        let mut errors: Vec<PpktExportError> = Vec::new();
+        for duplet in &self.cohort_dto.hpo_headers {
+            let is_current = duplet.to_term_id().ok()
+                .and_then(|tid| self.hpo.term_by_id(&tid).map(|term| term.identifier() == &tid))
+                .unwrap_or(false);
+            if !is_current {
+                errors.push(PpktExportError {
+                    individual_id: String::default(),
+                    error_type: PpktErrorType::UnknownHpoTerm,
+                    message: format!("{} ({}) is not a current HPO term", duplet.hpo_label(), duplet.hpo_id()),
+                });
+            }
+        }
+        for row in &self.cohort_dto.rows {
+            let individual_id = &row.individual_data.individual_id;
+            for allele in row.allele_count_map.keys() {
+                if !self.cohort_dto.hgvs_variants.contains_key(allele)
+                    && !self.cohort_dto.structural_variants.contains_key(allele)
+                    && !self.cohort_dto.intergenic_variants.contains_key(allele) {
+                    errors.push(PpktExportError {
+                        individual_id: individual_id.clone(),
+                        error_type: PpktErrorType::UnknownVariant,
+                        message: Self::allele_not_contained(allele),
+                    });
+                }
+            }
+            for disease_id in &row.disease_id_list {
+                if !self.disease_id_map.contains_key(disease_id) {
+                    errors.push(PpktExportError {
+                        individual_id: individual_id.clone(),
+                        error_type: PpktErrorType::UnknownDisease,
+                        message: format!("Unknown disease id '{disease_id}'"),
+                    });
+                }
+            }
+            for (duplet, cell) in self.cohort_dto.hpo_headers.iter().zip(row.hpo_data.iter()) {
+                if !cell.has_modifier() {
+                    continue;
+                }
+                let Ok(hpo_term_data) = HpoTermData::new(duplet.clone(), cell.clone()) else {
+                    continue;
+                };
+                if let Err(messages) = crate::hpo::validate_modifier_ids(self.hpo.clone(), &hpo_term_data) {
+                    errors.extend(messages.into_iter().map(|message| PpktExportError {
+                        individual_id: individual_id.clone(),
+                        error_type: PpktErrorType::InvalidModifier,
+                        message,
+                    }));
+                }
+            }
+        }
+        errors
+    }
+
     pub fn get_all_phenopackets(&self) -> Result<Vec<Phenopacket>, String> {
+        let errors = self.validate_before_export();
+        if !errors.is_empty() {
+            let messages: Vec<String> = errors.iter().map(|e| e.to_string()).collect();
+            return Err(format!("Cannot export phenopackets: {}", messages.join("; ")));
+        }
         let mut ppkt_list: Vec<Phenopacket> = Vec::new();
         for row in &self.cohort_dto.rows {
            let ppkt = self.extract_phenopacket_from_row(row)?;
@@ -422,7 +622,10 @@ mod tests {
     use rstest::rstest;
     use std::path::PathBuf;  
     use serde_json::json;
-    use crate::test_utils::fixtures::hpo;
+    use crate::test_utils::fixtures::{fbn1_cohort, hpo};
+    use crate::dto::cohort_dto::BiosampleData;
+    use crate::dto::hpo_term_dto::HpoTermDuplet;
+    use phenopackets::schema::v2::core::genomic_interpretation::Call;
 
     /// Remove the redundant field while leaving all else intact
     #[test]
@@ -559,12 +762,277 @@ mod tests {
     }
 
 
+    #[rstest]
+    fn test_age_range_midpoint(hpo: Arc<FullCsrOntology>, mut fbn1_cohort: CohortData) {
+        let individual = &mut fbn1_cohort.rows[0].individual_data;
+        individual.age_of_onset = "P3Y-P5Y".to_string();
+        individual.age_at_last_encounter = "P10Y".to_string();
+        individual.age_at_last_encounter_max = Some("P11Y".to_string());
+        let row = fbn1_cohort.rows[0].clone();
+        let exporter = PpktExporter::new(hpo, "ORCID:0000-0002-0736-9199", fbn1_cohort).unwrap();
+        let diseases = exporter.get_disease_list(&row).unwrap();
+        assert_eq!(time_element_from_str("P4Y").ok(), diseases[0].onset);
+        let individual = exporter.extract_individual(&row).unwrap();
+        assert_eq!(time_element_from_str("P10Y6M").ok(), individual.time_at_last_encounter);
+    }
+
+    #[rstest]
+    fn test_medical_actions(hpo: Arc<FullCsrOntology>, mut fbn1_cohort: CohortData) {
+        fbn1_cohort.rows[0].individual_data.treatment_list = vec![
+            "CHEBI:45783 imatinib".to_string(),
+            "CHEBI:6801".to_string(),
+            "physiotherapy".to_string(),
+        ];
+        let row = fbn1_cohort.rows[0].clone();
+        let exporter = PpktExporter::new(hpo, "ORCID:0000-0002-0736-9199", fbn1_cohort).unwrap();
+        let actions = exporter.get_medical_actions(&row);
+        assert_eq!(2, actions.len());
+        let Some(Action::Treatment(treatment)) = &actions[0].action else {
+            panic!("Expected a treatment");
+        };
+        let agent = treatment.agent.as_ref().unwrap();
+        assert_eq!("CHEBI:45783", agent.id);
+        assert_eq!("imatinib", agent.label);
+        let ppkt = exporter.extract_phenopacket_from_row(&row).unwrap();
+        assert_eq!(actions, ppkt.medical_actions);
+    }
+
+    #[rstest]
+    fn test_no_medical_actions(hpo: Arc<FullCsrOntology>, fbn1_cohort: CohortData) {
+        let row = fbn1_cohort.rows[1].clone();
+        let exporter = PpktExporter::new(hpo, "ORCID:0000-0002-0736-9199", fbn1_cohort).unwrap();
+        assert!(exporter.get_medical_actions(&row).is_empty());
+        // the treatment list is optional in the JSON representation
+        let json = serde_json::to_value(&row.individual_data).unwrap();
+        assert!(json.get("treatmentList").is_none());
+    }
+
+    #[rstest]
+    fn test_biosamples(hpo: Arc<FullCsrOntology>, mut fbn1_cohort: CohortData) {
+        fbn1_cohort.rows[0].individual_data.biosamples = vec![BiosampleData {
+            biosample_id: "Individual 1-biosample-1".to_string(),
+            tissue_id: "UBERON:0002107".to_string(),
+            tissue_label: "liver".to_string(),
+            histology_id: Some("NCIT:C3099".to_string()),
+        }];
+        let row = fbn1_cohort.rows[0].clone();
+        let exporter = PpktExporter::new(hpo, "ORCID:0000-0002-0736-9199", fbn1_cohort).unwrap();
+        let ppkt = exporter.extract_phenopacket_from_row(&row).unwrap();
+        assert_eq!(1, ppkt.biosamples.len());
+        let biosample = &ppkt.biosamples[0];
+        assert_eq!("Individual 1-biosample-1", biosample.id);
+        assert_eq!("Individual 1", biosample.individual_id);
+        assert_eq!("UBERON:0002107", biosample.sampled_tissue.as_ref().unwrap().id);
+        assert_eq!("liver", biosample.sampled_tissue.as_ref().unwrap().label);
+        assert_eq!("NCIT:C3099", biosample.histological_diagnosis.as_ref().unwrap().id);
+        // individuals without samples
+        assert!(exporter.get_biosamples(&exporter.cohort_dto.rows[1]).is_empty());
+    }
+
+    #[rstest]
+    fn test_min_confidence(hpo: Arc<FullCsrOntology>, mut fbn1_cohort: CohortData) {
+        // Individual 1: Arachnodactyly observed (low confidence), HP:0001631 observed (high), Long hallux excluded
+        fbn1_cohort.rows[0].hpo_data[0].confidence = Some(CurationConfidence::Low);
+        fbn1_cohort.rows[0].hpo_data[1].confidence = Some(CurationConfidence::High);
+        let row = fbn1_cohort.rows[0].clone();
+        let exporter = PpktExporter::new(hpo.clone(), "ORCID:0000-0002-0736-9199", fbn1_cohort.clone()).unwrap();
+        assert_eq!(3, exporter.get_phenopacket_features(&row).unwrap().len());
+        let exporter = PpktExporter::new(hpo, "ORCID:0000-0002-0736-9199", fbn1_cohort).unwrap()
+            .with_min_confidence(CurationConfidence::Medium);
+        let features = exporter.get_phenopacket_features(&row).unwrap();
+        assert_eq!(2, features.len());
+        assert!(features.iter().all(|pf| pf.r#type.as_ref().unwrap().id != "HP:0001166"));
+    }
+
+    #[rstest]
+    #[case("0000-0002-0736-9199")]
+    #[case("ORCID:0000-0002-0736-919")]
+    #[case("ORCID:0000-0002-0736-9190")]
+    fn test_invalid_creator_orcid(hpo: Arc<FullCsrOntology>, fbn1_cohort: CohortData, #[case] orcid: &str) {
+        assert!(PpktExporter::new(hpo, orcid, fbn1_cohort).is_err());
+    }
+
+    #[rstest]
+    fn test_validate_before_export(hpo: Arc<FullCsrOntology>, fbn1_cohort: CohortData) {
+        let exporter = PpktExporter::new(hpo, "ORCID:0000-0002-0736-9199", fbn1_cohort).unwrap();
+        assert!(exporter.validate_before_export().is_empty());
+    }
+
+    #[rstest]
+    fn test_unknown_variant(hpo: Arc<FullCsrOntology>, mut fbn1_cohort: CohortData) {
+        fbn1_cohort.rows[1].allele_count_map.insert("c123AtoG_FBN1_NM_000138v5".to_string(), 1);
+        let exporter = PpktExporter::new(hpo, "ORCID:0000-0002-0736-9199", fbn1_cohort).unwrap();
+        let errors = exporter.validate_before_export();
+        assert_eq!(1, errors.len());
+        assert_eq!("Individual 2", errors[0].individual_id);
+        assert_eq!(PpktErrorType::UnknownVariant, errors[0].error_type);
+        assert!(exporter.get_all_phenopackets().is_err());
+    }
+
+    #[rstest]
+    fn test_unknown_disease(hpo: Arc<FullCsrOntology>, mut fbn1_cohort: CohortData) {
+        fbn1_cohort.rows[0].disease_id_list.push("OMIM:999999".to_string());
+        let exporter = PpktExporter::new(hpo, "ORCID:0000-0002-0736-9199", fbn1_cohort).unwrap();
+        let errors = exporter.validate_before_export();
+        assert_eq!(1, errors.len());
+        assert_eq!("Individual 1", errors[0].individual_id);
+        assert_eq!(PpktErrorType::UnknownDisease, errors[0].error_type);
+        assert_eq!("Individual 1: Unknown disease id 'OMIM:999999'", errors[0].to_string());
+    }
+
+    #[rstest]
+    #[case("HP:9999999")]
+    #[case("not an id")]
+    fn test_unknown_hpo_term(hpo: Arc<FullCsrOntology>, mut fbn1_cohort: CohortData, #[case] hpo_id: &str) {
+        fbn1_cohort.hpo_headers[3] = HpoTermDuplet::new("Made-up term", hpo_id);
+        let exporter = PpktExporter::new(hpo, "ORCID:0000-0002-0736-9199", fbn1_cohort).unwrap();
+        let errors = exporter.validate_before_export();
+        assert_eq!(1, errors.len());
+        assert_eq!("", errors[0].individual_id);
+        assert_eq!(PpktErrorType::UnknownHpoTerm, errors[0].error_type);
+    }
+
+    #[rstest]
+    fn test_unique_phenopacket_id(hpo: Arc<FullCsrOntology>, mut fbn1_cohort: CohortData) {
+        fbn1_cohort.rows[1].individual_data.individual_id = "Individual-1".to_string();
+        let exporter = PpktExporter::new(hpo, "ORCID:0000-0002-0736-9199", fbn1_cohort).unwrap();
+        let rows = &exporter.cohort_dto.rows;
+        assert_eq!(exporter.get_phenopacket_id(&rows[0]), exporter.get_phenopacket_id(&rows[1]));
+        let mut used_ids = HashSet::new();
+        assert_eq!("PMID_29999999_Individual_1", exporter.get_unique_phenopacket_id(&rows[0], &mut used_ids));
+        assert_eq!("PMID_29999999_Individual_1_2", exporter.get_unique_phenopacket_id(&rows[1], &mut used_ids));
+        assert_eq!("PMID_29999999_Individual_1_3", exporter.get_unique_phenopacket_id(&rows[0], &mut used_ids));
+        assert_eq!(3, used_ids.len());
+    }
+
+    #[rstest]
+    fn test_filename_collisions(mut fbn1_cohort: CohortData) {
+        assert!(PpktExporter::check_for_filename_collisions(&fbn1_cohort.rows).is_empty());
+        fbn1_cohort.rows[1].individual_data.individual_id = "Individual (1)".to_string();
+        let collisions = PpktExporter::check_for_filename_collisions(&fbn1_cohort.rows);
+        assert_eq!(vec![("Individual 1".to_string(), "Individual (1)".to_string())], collisions);
+        // Individual 3 has a different PMID
+        fbn1_cohort.rows[2].individual_data.individual_id = "Individual 1".to_string();
+        assert_eq!(1, PpktExporter::check_for_filename_collisions(&fbn1_cohort.rows).len());
+    }
+
+    #[rstest]
+    fn test_default_export_versions(hpo: Arc<FullCsrOntology>, fbn1_cohort: CohortData) {
+        let row = fbn1_cohort.rows[0].clone();
+        let exporter = PpktExporter::new(hpo, "ORCID:0000-0002-0736-9199", fbn1_cohort).unwrap();
+        let meta_data = exporter.get_meta_data(&row).unwrap();
+        let omim = meta_data.resources.iter().find(|r| r.namespace_prefix == "OMIM").unwrap();
+        assert_eq!(DEFAULT_OMIM_VERSION, omim.version);
+    }
+
+    #[rstest]
+    fn test_custom_export_versions(hpo: Arc<FullCsrOntology>, fbn1_cohort: CohortData) {
+        let versions = ExportVersions {
+            so_version: "2025-01-01".to_string(),
+            geno_version: "2025-02-02".to_string(),
+            ..ExportVersions::default()
+        };
+        let exporter = PpktExporter::from_export_versions(hpo, versions, "ORCID:0000-0002-0736-9199", fbn1_cohort)
+            .unwrap()
+            .with_omim_version("2025-03-03")
+            .with_hgnc_version("2025-04-04");
+        // Individual 3 has a structural variant, so that Sequence Ontology is listed as well
+        let ppkt = exporter.extract_phenopacket_from_row(&exporter.cohort_dto.rows[2]).unwrap();
+        let meta_data = ppkt.meta_data.unwrap();
+        let version = |prefix: &str| meta_data.resources
+            .iter()
+            .find(|r| r.namespace_prefix == prefix)
+            .map(|r| r.version.clone());
+        assert_eq!(Some("2025-01-01".to_string()), version("SO"));
+        assert_eq!(Some("2025-02-02".to_string()), version("GENO"));
+        assert_eq!(Some("2025-03-03".to_string()), version("OMIM"));
+        assert_eq!(Some("2025-04-04".to_string()), version("HGNC"));
+    }
+
+    fn round_trip_phenopackets(hpo: Arc<FullCsrOntology>, cohort: CohortData) -> Vec<(Phenopacket, Value)> {
+        let exporter = PpktExporter::new(hpo, "ORCID:0000-0002-0736-9199", cohort).unwrap();
+        exporter.get_all_phenopackets()
+            .unwrap()
+            .into_iter()
+            .map(|ppkt| {
+                let mut value = serde_json::to_value(&ppkt).unwrap();
+                PpktExporter::strip_phenopacket_defaults(&mut value);
+                (ppkt, value)
+            })
+            .collect()
+    }
+
+    /// HGVS expressions of all variants of the phenopacket
+    fn hgvs_expressions(ppkt: &Phenopacket) -> Vec<String> {
+        ppkt.interpretations.iter()
+            .filter_map(|interpretation| interpretation.diagnosis.as_ref())
+            .flat_map(|diagnosis| diagnosis.genomic_interpretations.iter())
+            .filter_map(|gi| match &gi.call {
+                Some(Call::VariantInterpretation(vi)) => vi.variation_descriptor.as_ref(),
+                _ => None,
+            })
+            .flat_map(|vd| vd.expressions.iter().map(|e| e.value.clone()))
+            .collect()
+    }
+
+    #[rstest]
+    fn test_json_round_trip(hpo: Arc<FullCsrOntology>, fbn1_cohort: CohortData) {
+        let packets = round_trip_phenopackets(hpo, fbn1_cohort);
+        assert_eq!(3, packets.len());
+        for (ppkt, value) in packets {
+            // the fields removed by strip_phenopacket_defaults have default values, so nothing is lost
+            let json = serde_json::to_string(&value).unwrap();
+            let reread: Phenopacket = serde_json::from_str(&json).unwrap();
+            assert_eq!(ppkt, reread);
+            assert_eq!(ppkt.subject, reread.subject);
+            assert_eq!(ppkt.phenotypic_features, reread.phenotypic_features);
+            assert_eq!(hgvs_expressions(&ppkt), hgvs_expressions(&reread));
+        }
+    }
+
+    #[rstest]
+    fn test_round_trip_content(hpo: Arc<FullCsrOntology>, fbn1_cohort: CohortData) {
+        let packets = round_trip_phenopackets(hpo, fbn1_cohort);
+        let (ppkt, value) = &packets[0];
+        let reread: Phenopacket = serde_json::from_value(value.clone()).unwrap();
+        let subject = reread.subject.as_ref().unwrap();
+        assert_eq!("Individual 1", subject.id);
+        assert_eq!(i32::from(Sex::Male), subject.sex);
+        assert!(subject.time_at_last_encounter.is_some());
+        // Individual 1: Arachnodactyly and Atrial septal defect observed, Long hallux excluded, Short stature na
+        let features: Vec<(&str, bool)> = reread.phenotypic_features.iter()
+            .map(|pf| (pf.r#type.as_ref().unwrap().id.as_str(), pf.excluded))
+            .collect();
+        assert_eq!(vec![("HP:0001166", false), ("HP:0001631", false), ("HP:0001847", true)], features);
+        let expressions = hgvs_expressions(&reread);
+        assert!(expressions.contains(&"NM_000138.5:c.8242G>T".to_string()));
+        assert!(expressions.contains(&"NC_000015.10:g.48411364C>A".to_string()));
+        assert_eq!(ppkt.diseases, reread.diseases);
+        assert_eq!("OMIM:154700", reread.diseases[0].term.as_ref().unwrap().id);
+    }
+
+    #[rstest]
+    fn test_stripped_fields_absent(hpo: Arc<FullCsrOntology>, fbn1_cohort: CohortData) {
+        let packets = round_trip_phenopackets(hpo, fbn1_cohort);
+        for (_, value) in &packets {
+            assert!(value["subject"].get("karyotypicSex").is_none());
+        }
+        // Individual 3 is deceased, but we do not know the survival time
+        let (ppkt, value) = &packets[2];
+        assert_eq!(Some(0), ppkt.subject.as_ref().unwrap().vital_status.as_ref().map(|vs| vs.survival_time_in_days));
+        assert_eq!("DECEASED", value["subject"]["vitalStatus"]["status"]);
+        assert!(value["subject"]["vitalStatus"].get("survivalTimeInDays").is_none());
+        let json = serde_json::to_string(value).unwrap();
+        assert!(!json.contains("UNKNOWN_KARYOTYPE"));
+        assert!(!json.contains("survivalTimeInDays"));
+    }
+
      #[rstest]
      #[ignore = "local file"]
     fn test_export_ppkt(hpo: Arc<FullCsrOntology>) {
         let input_file = "/Users/robin/GIT/mgd-ppkt/cohorts/MYH7_CMH1_PRKAG2_CMH6_individuals.json";
         let cohort = crate::factory::load_json_cohort(input_file).expect("Could not load Cohort JSON file");
-        let orcid = "0000-0000-0000-0000".to_string();
+        let orcid = "ORCID:0000-0000-0000-0001".to_string();
         let output_dir = "/Users/robin/TMP";
         let path = PathBuf::from(output_dir);
         let overwrite = true;
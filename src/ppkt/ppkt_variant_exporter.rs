@@ -147,17 +147,18 @@ impl PpktVariantExporter {
         &self, 
         ppkt_row: &RowData) 
     -> std::result::Result<Vec<Interpretation>, String> {
-        let mut v_interpretation_list: Vec<VariantInterpretation> = Vec::new();
+        // the boolean is true for intergenic variants
+        let mut v_interpretation_list: Vec<(VariantInterpretation, bool)> = Vec::new();
         for (allele, count) in &ppkt_row.allele_count_map {
             let allele_count = *count;
             if  allele_count == 0 {
                 return Err(format!("No alleles found in row {:?}", ppkt_row));
             }
             let vinterp = match self.lookup_variant(allele) {
-                Some(VariantRef::Hgvs(v)) => self.get_hgvs_variant_interpretation(v, allele_count),
-                Some(VariantRef::Sv(v)) => self.get_sv_variant_interpretation(v, allele_count),
+                Some(VariantRef::Hgvs(v)) => (self.get_hgvs_variant_interpretation(v, allele_count), false),
+                Some(VariantRef::Sv(v)) => (self.get_sv_variant_interpretation(v, allele_count), false),
                 Some(VariantRef::Intergenic(v)) =>
-                    self.get_intergenic_variant_interpretation(v, allele_count),
+                    (self.get_intergenic_variant_interpretation(v, allele_count), true),
                 None => return Err(format!("Could not find validated variant for allele {}", allele)),
             };
             v_interpretation_list.push(vinterp);
@@ -167,17 +168,22 @@ impl PpktVariantExporter {
         }
        
         let mut g_interpretation_map: HashMap<String, Vec<GenomicInterpretation>> = HashMap::new();
-        for vi in v_interpretation_list {
+        // Intergenic variants for which VariantValidator did not return a gene (e.g., in a distant enhancer)
+        let mut no_gene_interpretations: Vec<GenomicInterpretation> = Vec::new();
+        for (vi, is_intergenic) in v_interpretation_list {
             let gi = GenomicInterpretation{
                 subject_or_biosample_id: ppkt_row.individual_data.individual_id.to_string(),
                 interpretation_status: InterpretationStatus::Causative.into(),
                 call: Some(Call::VariantInterpretation(vi.clone()))
             };
-            let symbol = Self::extract_gene_symbol(&vi)?;
-            g_interpretation_map
-                .entry(symbol)
-                .or_default() 
-                .push(gi);
+            match Self::extract_gene_symbol(&vi) {
+                Ok(symbol) => g_interpretation_map.entry(symbol).or_default().push(gi),
+                Err(e) if is_intergenic => {
+                    tracing::debug!("{e}; adding the variant to all diagnoses");
+                    no_gene_interpretations.push(gi);
+                }
+                Err(e) => return Err(e),
+            }
         }
         let mut interpretation_list: Vec<Interpretation> = vec![];
         for disease in &self.disease_list {
@@ -195,6 +201,7 @@ impl PpktVariantExporter {
                     .flatten()
                     .cloned()
             );
+            diagnosis.genomic_interpretations.extend(no_gene_interpretations.iter().cloned());
             let i = Interpretation{
                 id: Self::generate_id(),
                 progress_status: ProgressStatus::Solved.into(),
@@ -227,7 +234,6 @@ impl PpktVariantExporter {
         allele_count: usize
     ) -> VariantInterpretation {
         let gene_ctxt = Self::gene_descriptor(sv.hgnc_id(), sv.gene_symbol());
-        let is_x = sv.is_x_chromosomal();
         let sv_class = sv.get_sequence_ontology_term();
         let allelic_state = self.get_genotype_term(allele_count, sv.is_x_chromosomal());
         let vdesc = VDescBuilder {
@@ -353,7 +359,15 @@ impl PpktVariantExporter {
             value: format!("{}", ig.g_hgvs()), 
             version: String::default() 
         };
-        let expression_list = vec![hgvs_g];
+        let mut expression_list = vec![hgvs_g];
+        // gene-level HGVS (e.g., NG_009292.1:g.1135G>A) if the variant lies within a gene region
+        if let Some(gene_hgvs) = ig.gene_hgvs() {
+            expression_list.push(Expression {
+                syntax: "hgvs.g".to_string(),
+                value: gene_hgvs,
+                version: String::default(),
+            });
+        }
         let allelic_state = self.get_genotype_term(allele_count, ig.is_x_chromosomal());
       
         let vdesc = VDescBuilder {
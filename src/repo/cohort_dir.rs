@@ -76,7 +76,7 @@ pub fn process_gene_directory(path: &Path) -> CohortDir {
         Ok(cohorts)
     }
 
-    fn load_phenopacket<P: AsRef<Path>>(path: P) -> Result<Phenopacket, String> {
+    pub(crate) fn load_phenopacket<P: AsRef<Path>>(path: P) -> Result<Phenopacket, String> {
         let file = File::open(path).map_err(|e| e.to_string())?;
         let reader = BufReader::new(file);
         
@@ -132,7 +132,7 @@ pub fn process_gene_directory(path: &Path) -> CohortDir {
                 let name_str = file_name.to_string_lossy();
                 fnames.push(name_str.to_string());
             } else {
-                eprintln!("Error: Could not extract filename from {:?}", pth);
+                tracing::warn!("Could not extract filename from {:?}", pth);
             }
         }
         fnames
@@ -145,4 +145,17 @@ pub fn process_gene_directory(path: &Path) -> CohortDir {
         CohortQc::new(&self.cohort_name, cohorts, phenopackets, unexpected_files)
     }
     
-}
\ No newline at end of file
+}
+
+
+/// All cohort files (`*_individuals.json`) below `root`, sorted by path
+pub(crate) fn find_cohort_files(root: &Path) -> Vec<PathBuf> {
+    let mut cohort_files: Vec<PathBuf> = WalkDir::new(root)
+        .into_iter()
+        .filter_map(|e| e.ok())
+        .filter(|e| e.file_type().is_file() && e.file_name().to_string_lossy().ends_with("_individuals.json"))
+        .map(|e| e.into_path())
+        .collect();
+    cohort_files.sort();
+    cohort_files
+}
@@ -36,9 +36,7 @@ impl CohortQc {
                     dqc.add_ppkt(ppkt);
                 },
                 None => {
-                    for (k,v) in disease_to_ppkt_d.iter() {
-                        println!("Could not find disease for id {}", disease_id);
-                    }
+                    tracing::warn!("Could not find disease for id {}", disease_id);
                 }
             }
         }
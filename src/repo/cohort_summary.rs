@@ -0,0 +1,122 @@
+//! CohortSummary: Overview of the cohort files of a phenopacket-store
+//!
+//! The phenopacket-store repository has one directory per gene below `notebooks` (e.g., `notebooks/FBN1/`),
+//! each with one or more cohort files (`*_individuals.json`). For each cohort file, we collect the gene,
+//! disease, number of individuals, schema version, and modification time.
+use std::path::{Path, PathBuf};
+
+use chrono::{DateTime, Utc};
+use serde::Serialize;
+
+use crate::dto::cohort_dto::CohortData;
+use crate::factory::migration::{is_newer_version, migrate_cohort};
+use crate::repo::cohort_dir::find_cohort_files;
+
+
+#[derive(Clone, Debug, Serialize, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct CohortSummary {
+    pub cohort_file: PathBuf,
+    /// Name of the gene directory
+    pub gene_symbol: String,
+    /// Disease id(s) of the cohort; several ids (melded cohorts) are separated by ";"
+    pub disease_id: String,
+    pub n_individuals: usize,
+    /// `phetoolsSchemaVersion` of the file ("unknown" for legacy files without a version)
+    pub schema_version: String,
+    /// Modification time of the cohort file (UTC, RFC 3339)
+    pub last_modified: String,
+}
+
+
+/// Scan the gene directories of a phenopacket-store. `path` is either the root of the store (with a `notebooks`
+/// subdirectory) or the `notebooks` directory itself. The summaries are sorted by gene and file path.
+pub fn scan_phenopacket_store(path: &Path) -> Result<Vec<CohortSummary>, String> {
+    if !path.is_dir() {
+        return Err(format!("Phenopacket store {} is not a directory", path.display()));
+    }
+    let notebooks = path.join("notebooks");
+    let notebooks = if notebooks.is_dir() { notebooks } else { path.to_path_buf() };
+    let mut gene_dirs: Vec<PathBuf> = std::fs::read_dir(&notebooks)
+        .map_err(|e| format!("Could not read {}: {}", notebooks.display(), e))?
+        .filter_map(|e| e.ok())
+        .map(|e| e.path())
+        .filter(|p| p.is_dir())
+        .collect();
+    gene_dirs.sort();
+    let mut summaries = Vec::new();
+    for gene_dir in gene_dirs {
+        let gene_symbol = gene_dir.file_name().map(|n| n.to_string_lossy().to_string()).unwrap_or_default();
+        for cohort_file in find_cohort_files(&gene_dir) {
+            summaries.push(summarize_cohort_file(&cohort_file, &gene_symbol)?);
+        }
+    }
+    Ok(summaries)
+}
+
+fn summarize_cohort_file(cohort_file: &Path, gene_symbol: &str) -> Result<CohortSummary, String> {
+    let contents = std::fs::read_to_string(cohort_file)
+        .map_err(|e| format!("Could not read {}: {}", cohort_file.display(), e))?;
+    let json: serde_json::Value = serde_json::from_str(&contents)
+        .map_err(|e| format!("Could not parse {}: {}", cohort_file.display(), e))?;
+    // Files with older schema versions are migrated; files with newer versions are read as far as possible
+    let schema_version = json.get("phetoolsSchemaVersion")
+        .and_then(|v| v.as_str())
+        .unwrap_or("unknown")
+        .to_string();
+    let cohort: CohortData = if is_newer_version(&schema_version, &CohortData::get_phetools_schema_version()) {
+        serde_json::from_value(json).map_err(|e| e.to_string())
+    } else {
+        migrate_cohort(json)
+    }.map_err(|e| format!("Could not parse {}: {}", cohort_file.display(), e))?;
+    let modified = std::fs::metadata(cohort_file)
+        .and_then(|m| m.modified())
+        .map_err(|e| format!("Could not read modification time of {}: {}", cohort_file.display(), e))?;
+    Ok(CohortSummary {
+        cohort_file: cohort_file.to_path_buf(),
+        gene_symbol: gene_symbol.to_string(),
+        disease_id: cohort.disease_list.iter().map(|d| d.disease_id.as_str()).collect::<Vec<_>>().join(";"),
+        n_individuals: cohort.rows.len(),
+        schema_version,
+        last_modified: DateTime::<Utc>::from(modified).to_rfc3339(),
+    })
+}
+
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use rstest::rstest;
+    use crate::test_utils::fixtures::fbn1_cohort;
+    use crate::test_utils::temp_store::{temp_store, write_cohort_file};
+
+    #[rstest]
+    fn test_scan_phenopacket_store(fbn1_cohort: CohortData) {
+        let root = temp_store("phetools_test_scan_store");
+        let mut fbn2_cohort = fbn1_cohort.clone();
+        fbn2_cohort.rows.truncate(1);
+        fbn2_cohort.phetools_schema_version = "0.2".to_string();
+        for (gene, cohort) in [("FBN1", &fbn1_cohort), ("FBN2", &fbn2_cohort)] {
+            write_cohort_file(&root.join("notebooks"), gene, cohort);
+            std::fs::create_dir_all(root.join("notebooks").join(gene).join("phenopackets")).unwrap();
+        }
+        let summaries = scan_phenopacket_store(&root);
+        std::fs::remove_dir_all(&root).unwrap();
+        let summaries = summaries.unwrap();
+        assert_eq!(2, summaries.len());
+        assert_eq!("FBN1", summaries[0].gene_symbol);
+        assert_eq!("OMIM:154700", summaries[0].disease_id);
+        assert_eq!(3, summaries[0].n_individuals);
+        assert_eq!(fbn1_cohort.phetools_schema_version, summaries[0].schema_version);
+        assert!(summaries[0].cohort_file.ends_with("notebooks/FBN1/FBN1_MFS_individuals.json"));
+        assert_eq!("FBN2", summaries[1].gene_symbol);
+        assert_eq!(1, summaries[1].n_individuals);
+        assert_eq!("0.2", summaries[1].schema_version);
+        assert!(DateTime::parse_from_rfc3339(&summaries[1].last_modified).is_ok());
+    }
+
+    #[test]
+    fn test_missing_store() {
+        assert!(scan_phenopacket_store(Path::new("/does/not/exist")).is_err());
+    }
+}
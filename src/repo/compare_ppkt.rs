@@ -33,7 +33,7 @@ pub fn load_phenopacket_from_path(path: PathBuf, hpo: Arc<FullCsrOntology>) -> R
     match source {
         PhenopacketSource::Direct(ppkt) => Ok(ppkt),
         PhenopacketSource::FromCohort(cohort) => {
-            let fake_orcid = "0000-0000-0000-000x".to_string();
+            let fake_orcid = "ORCID:0000-0000-0000-0001".to_string();
             let ppkt_list = crate::ppkt::get_phenopackets(
                 cohort, fake_orcid, hpo)?;
             ppkt_list.into_iter().next()
@@ -47,7 +47,7 @@ impl DiseaseQc {
             }  else if moi.is_pseudoautosomal_recessive() {
                 allowable_allele_counts.insert(2);
             } else {
-                eprintln!("Did not recognize MOI: {:?}", moi);
+                tracing::warn!("Did not recognize MOI: {:?}", moi);
             }
         }
         for ppkt in &self.ppkt_list {
@@ -89,7 +89,7 @@ impl DiseaseQc {
                             }
                     }
                 },
-                None => {println!("Could not find dx")},
+                None => {tracing::warn!("Could not find dx")},
             }
         }
 
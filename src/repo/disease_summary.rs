@@ -0,0 +1,187 @@
+//! DiseaseSummary: Per-disease statistics of a repository
+//!
+//! We scan all cohort files (`*_individuals.json`) below the repository directory and count, for each disease,
+//! the cohorts and individuals and the distinct HPO terms used to annotate them. Parsing the cohort files
+//! is by far the most expensive step, so the counts of each file are cached together with its modification
+//! time; files that did not change since the last call are not read again.
+use std::collections::{BTreeMap, BTreeSet, HashMap};
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, LazyLock, Mutex};
+use std::time::SystemTime;
+
+use serde::Serialize;
+
+use crate::dto::cohort_dto::CohortData;
+use crate::repo::cohort_dir::find_cohort_files;
+
+
+#[derive(Clone, Debug, Serialize, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct DiseaseSummary {
+    pub disease_id: String,
+    pub disease_label: String,
+    pub cohort_count: usize,
+    pub individual_count: usize,
+    pub hpo_term_count: usize,
+}
+
+impl DiseaseSummary {
+    pub fn tsv_header() -> String {
+        ["disease_id", "disease_label", "cohort_count", "individual_count", "hpo_term_count"].join("\t")
+    }
+
+    pub fn to_tsv_row(&self) -> String {
+        format!("{}\t{}\t{}\t{}\t{}",
+            self.disease_id, self.disease_label, self.cohort_count, self.individual_count, self.hpo_term_count)
+    }
+}
+
+
+/// The counts that one cohort file contributes to each of its diseases
+#[derive(Debug, Default)]
+struct CohortDiseaseCounts {
+    disease_label: String,
+    individual_count: usize,
+    hpo_ids: BTreeSet<String>,
+}
+
+type FileCounts = HashMap<String, CohortDiseaseCounts>;
+
+static FILE_CACHE: LazyLock<Mutex<HashMap<PathBuf, (SystemTime, Arc<FileCounts>)>>> =
+    LazyLock::new(|| Mutex::new(HashMap::new()));
+
+
+/// Aggregate the statistics of all cohort files below `path`, sorted by disease id
+pub fn get_disease_summary(path: &Path) -> Result<Vec<DiseaseSummary>, String> {
+    let cohort_files = find_cohort_files(path);
+    let mut summaries: BTreeMap<String, (DiseaseSummary, BTreeSet<String>)> = BTreeMap::new();
+    for file in &cohort_files {
+        let file_counts = get_file_counts(file)?;
+        for (disease_id, counts) in file_counts.iter() {
+            let (summary, hpo_ids) = summaries.entry(disease_id.clone()).or_insert_with(|| (
+                DiseaseSummary {
+                    disease_id: disease_id.clone(),
+                    disease_label: counts.disease_label.clone(),
+                    cohort_count: 0,
+                    individual_count: 0,
+                    hpo_term_count: 0,
+                },
+                BTreeSet::new(),
+            ));
+            summary.cohort_count += 1;
+            summary.individual_count += counts.individual_count;
+            hpo_ids.extend(counts.hpo_ids.iter().cloned());
+        }
+    }
+    Ok(summaries
+        .into_values()
+        .map(|(mut summary, hpo_ids)| {
+            summary.hpo_term_count = hpo_ids.len();
+            summary
+        })
+        .collect())
+}
+
+/// The counts of a cohort file, from the cache if the file was not modified since it was last read
+fn get_file_counts(path: &Path) -> Result<Arc<FileCounts>, String> {
+    let modified = std::fs::metadata(path)
+        .and_then(|m| m.modified())
+        .map_err(|e| format!("Could not read modification time of {}: {}", path.display(), e))?;
+    let mut cache = FILE_CACHE.lock().map_err(|e| e.to_string())?;
+    if let Some((cached_time, counts)) = cache.get(path) {
+        if *cached_time == modified {
+            return Ok(counts.clone());
+        }
+    }
+    tracing::debug!(path = %path.display(), "Reading cohort file for disease summary");
+    let contents = std::fs::read_to_string(path)
+        .map_err(|e| format!("Could not read {}: {}", path.display(), e))?;
+    let cohort: CohortData = serde_json::from_str(&contents)
+        .map_err(|e| format!("Could not parse {}: {}", path.display(), e))?;
+    let counts = Arc::new(count_by_disease(&cohort));
+    cache.insert(path.to_path_buf(), (modified, counts.clone()));
+    Ok(counts)
+}
+
+fn count_by_disease(cohort: &CohortData) -> FileCounts {
+    let hpo_ids: BTreeSet<String> = cohort.hpo_headers.iter().map(|h| h.hpo_id().to_string()).collect();
+    let mut counts: FileCounts = cohort.disease_list
+        .iter()
+        .map(|d| (d.disease_id.clone(), CohortDiseaseCounts {
+            disease_label: d.disease_label.clone(),
+            individual_count: 0,
+            hpo_ids: hpo_ids.clone(),
+        }))
+        .collect();
+    for row in &cohort.rows {
+        for disease_id in &row.disease_id_list {
+            counts.entry(disease_id.clone()).or_default().individual_count += 1;
+        }
+    }
+    counts
+}
+
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use rstest::rstest;
+    use crate::dto::hpo_term_dto::HpoTermDuplet;
+    use crate::test_utils::fixtures::fbn1_cohort;
+    use crate::test_utils::temp_store::{temp_store, write_cohort_file};
+
+    fn fixture_store(name: &str, fbn1_cohort: &CohortData) -> PathBuf {
+        let root = temp_store(name);
+        write_cohort_file(&root, "FBN1", fbn1_cohort);
+        // a second cohort for the same disease with one additional HPO term and two individuals
+        let mut second = fbn1_cohort.clone();
+        second.rows.truncate(2);
+        second.hpo_headers[0] = HpoTermDuplet::new("Ectopia lentis", "HP:0001083");
+        write_cohort_file(&root, "FBN1_2", &second);
+        // files that are not cohort files are ignored
+        std::fs::create_dir_all(root.join("FBN1").join("phenopackets")).unwrap();
+        std::fs::write(root.join("FBN1").join("phenopackets").join("PMID_1_Individual_1.json"), "{}").unwrap();
+        root
+    }
+
+    #[rstest]
+    fn test_disease_summary(fbn1_cohort: CohortData) {
+        let root = fixture_store("phetools_test_disease_summary", &fbn1_cohort);
+        let summary = get_disease_summary(&root);
+        std::fs::remove_dir_all(&root).unwrap();
+        let summary = summary.unwrap();
+        assert_eq!(1, summary.len());
+        assert_eq!("OMIM:154700", summary[0].disease_id);
+        assert_eq!("Marfan syndrome", summary[0].disease_label);
+        assert_eq!(2, summary[0].cohort_count);
+        assert_eq!(5, summary[0].individual_count);
+        assert_eq!(5, summary[0].hpo_term_count);
+        assert_eq!("OMIM:154700\tMarfan syndrome\t2\t5\t5", summary[0].to_tsv_row());
+    }
+
+    #[rstest]
+    fn test_modified_file_is_read_again(fbn1_cohort: CohortData) {
+        let root = fixture_store("phetools_test_disease_summary_cache", &fbn1_cohort);
+        assert_eq!(5, get_disease_summary(&root).unwrap()[0].individual_count);
+        // an unchanged modification time means the cached counts are used
+        let mut smaller = fbn1_cohort.clone();
+        smaller.rows.truncate(1);
+        let path = write_cohort_file(&root, "FBN1", &smaller);
+        let file = std::fs::File::options().write(true).open(&path).unwrap();
+        let cached_time = FILE_CACHE.lock().unwrap().get(&path).unwrap().0;
+        file.set_modified(cached_time).unwrap();
+        assert_eq!(5, get_disease_summary(&root).unwrap()[0].individual_count);
+        file.set_modified(cached_time + std::time::Duration::from_secs(10)).unwrap();
+        let summary = get_disease_summary(&root);
+        std::fs::remove_dir_all(&root).unwrap();
+        assert_eq!(3, summary.unwrap()[0].individual_count);
+    }
+
+    #[test]
+    fn test_empty_directory() {
+        let root = temp_store("phetools_test_disease_summary_empty");
+        let summary = get_disease_summary(&root);
+        std::fs::remove_dir_all(&root).unwrap();
+        assert!(summary.unwrap().is_empty());
+    }
+}
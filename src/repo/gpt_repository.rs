@@ -31,13 +31,17 @@ impl GptRepository {
             }
         }
     
-        println!("Processed {} gene directories.", all_cohorts.len());
+        tracing::info!("Processed {} gene directories.", all_cohorts.len());
         Self {
             path: root_path.into(),
             cohort_list: all_cohorts
         }
     }
 
+    pub fn cohort_dirs(&self) -> &[CohortDir] {
+        &self.cohort_list
+    }
+
     pub fn repo_qc(&self) -> Result<RepoQc, String> {
          let result: Result<Vec<CohortQc>, String> = self.cohort_list.iter()
             .map(|cl| cl.get_cohort_qc())
@@ -5,20 +5,29 @@
 
 use std::{collections::HashSet, path::PathBuf, sync::Arc};
 
-use ontolius::ontology::csr::FullCsrOntology;
+use ontolius::ontology::{csr::FullCsrOntology, MetadataAware};
 use serde::Serialize;
+use tera::Context;
 
-use crate::repo::{compare_ppkt::{get_hpo_id_set, load_phenopacket_from_path}, gpt_repository::GptRepository, repo_qc::RepoQc};
+use crate::dto::cohort_dto::CohortData;
+use crate::export::html_renderer::HtmlRenderer;
+use crate::repo::{compare_ppkt::{get_hpo_id_set, load_phenopacket_from_path}, gpt_repository::GptRepository, repo_dashboard::RepoDashboard, repo_qc::RepoQc};
 
 
 mod cohort_dir;
 mod cohort_qc;
+mod cohort_summary;
 mod disease_qc;
+mod disease_summary;
 mod gpt_repository;
 pub mod qc_report;
+pub mod repo_dashboard;
 pub mod repo_qc;
 mod compare_ppkt;
 
+pub use cohort_summary::CohortSummary;
+pub use disease_summary::DiseaseSummary;
+
 #[derive(Clone, Debug, Serialize)]
 #[serde(rename_all = "camelCase")]
 pub struct ComparisonReport {
@@ -77,4 +86,37 @@ pub fn compare_two_phenopackets(path1: String, path2: String, hpo: Arc<FullCsrOn
 pub fn get_repo_qc(path: &PathBuf) -> Result<RepoQc, String> {
      let repo = GptRepository::new(path);
      repo.repo_qc()
-}
\ No newline at end of file
+}
+
+/// Number of cohorts, individuals, and distinct HPO terms for each disease of the repository at `path`.
+/// Cohort files that were not modified since the previous call are not parsed again.
+pub fn get_disease_summary(path: &PathBuf) -> Result<Vec<DiseaseSummary>, String> {
+    disease_summary::get_disease_summary(path)
+}
+
+/// Load all cohort files (`*_individuals.json`) below `path`, sorted by file path
+pub fn load_repository_cohorts(path: &PathBuf) -> Result<Vec<CohortData>, String> {
+    cohort_dir::find_cohort_files(path)
+        .iter()
+        .map(|f| crate::factory::load_json_cohort(&f.to_string_lossy()).map_err(String::from))
+        .collect()
+}
+
+/// One [`CohortSummary`] for each cohort file of a phenopacket-store (`notebooks/{GENE}/*_individuals.json`),
+/// sorted by gene. `path` may be the root of the store or its `notebooks` directory.
+pub fn scan_phenopacket_store(path: &PathBuf) -> Result<Vec<CohortSummary>, String> {
+    cohort_summary::scan_phenopacket_store(path)
+}
+
+/// Create an HTML dashboard for the repository at `path` with the cohorts, their Q/C status,
+/// the number of phenopackets, the schema versions of the cohort files, and the most common Q/C violations.
+/// Cohorts whose HPO version differs from that of `hpo` are flagged.
+///
+/// Returns the HTML document.
+pub fn generate_repo_dashboard(path: &PathBuf, hpo: Arc<FullCsrOntology>) -> Result<String, String> {
+    let repo = GptRepository::new(path);
+    let dashboard = RepoDashboard::from_repository(&repo, hpo.version())?;
+    let mut context = Context::new();
+    context.insert("dashboard", &dashboard);
+    HtmlRenderer::new().render_dashboard(context)
+}
@@ -3,7 +3,7 @@ use std::collections::HashSet;
 
 
 
-#[derive(Debug, Clone, serde::Serialize)] 
+#[derive(Debug, Clone, PartialEq, Eq, Hash, serde::Serialize)] 
 #[serde(rename_all = "camelCase")]
 pub enum RepoErrorType {
     UnexpectedFile,
@@ -12,6 +12,18 @@ pub enum RepoErrorType {
     NoHpoTermError
 }
 
+impl RepoErrorType {
+    /// Human-readable name of the error type, e.g., for the dashboard
+    pub fn label(&self) -> &'static str {
+        match self {
+            RepoErrorType::UnexpectedFile => "Unexpected file",
+            RepoErrorType::MoiMismatch => "Mode of inheritance mismatch",
+            RepoErrorType::PpktExportError => "Phenopacket export",
+            RepoErrorType::NoHpoTermError => "No observed HPO terms",
+        }
+    }
+}
+
 
 #[derive(Debug, Clone, serde::Serialize)] 
 #[serde(rename_all = "camelCase")]
@@ -0,0 +1,125 @@
+//! RepoDashboard: Summary of a repository for the HTML dashboard
+//!
+//! For each cohort directory, we show the number of individuals and phenopackets and the Q/C status;
+//! for the entire repository, the schema versions of the cohort files and the most common Q/C violations.
+use std::collections::{BTreeMap, HashMap};
+
+use serde::Serialize;
+
+use crate::dto::cohort_dto::CohortData;
+use crate::repo::{gpt_repository::GptRepository, qc_report::QcReport};
+
+
+/// Maximum number of individual Q/C violations listed in the dashboard
+const MAX_LISTED_VIOLATIONS: usize = 50;
+
+
+#[derive(Clone, Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct CohortDashboardRow {
+    pub cohort_name: String,
+    pub n_individuals: usize,
+    pub n_phenopackets: usize,
+    pub n_errors: usize,
+    /// HPO versions of the cohort files that differ from the current HPO version
+    pub outdated_hpo_versions: Vec<String>,
+}
+
+#[derive(Clone, Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SchemaVersionCount {
+    pub schema_version: String,
+    pub n_cohort_files: usize,
+    /// True if the cohort files must be migrated to the current schema version
+    pub outdated: bool,
+}
+
+#[derive(Clone, Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ViolationCount {
+    pub error_type: String,
+    pub count: usize,
+}
+
+#[derive(Clone, Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct RepoDashboard {
+    pub repo_path: String,
+    pub hpo_version: String,
+    pub cohorts: Vec<CohortDashboardRow>,
+    pub n_individuals: usize,
+    pub n_phenopackets: usize,
+    /// Number of cohorts with at least one Q/C error
+    pub n_cohorts_with_errors: usize,
+    pub current_schema_version: String,
+    pub schema_versions: Vec<SchemaVersionCount>,
+    /// Number of cohort files with an outdated schema version
+    pub n_outdated_cohort_files: usize,
+    /// Number of Q/C violations per error type, most common first
+    pub violation_counts: Vec<ViolationCount>,
+    /// The first Q/C violations (at most [`MAX_LISTED_VIOLATIONS`])
+    pub violations: Vec<QcReport>,
+    pub n_violations: usize,
+}
+
+impl RepoDashboard {
+    pub(crate) fn from_repository(repo: &GptRepository, hpo_version: &str) -> Result<Self, String> {
+        let mut cohorts = Vec::new();
+        let mut schema_versions: BTreeMap<String, usize> = BTreeMap::new();
+        let mut errors: Vec<QcReport> = Vec::new();
+        for cohort_dir in repo.cohort_dirs() {
+            let cohort_list = cohort_dir.get_cohort_data()?;
+            let mut outdated_hpo_versions: Vec<String> = Vec::new();
+            for cohort in &cohort_list {
+                *schema_versions.entry(cohort.phetools_schema_version.clone()).or_insert(0) += 1;
+                if cohort.hpo_version != hpo_version && !outdated_hpo_versions.contains(&cohort.hpo_version) {
+                    outdated_hpo_versions.push(cohort.hpo_version.clone());
+                }
+            }
+            let cohort_errors = cohort_dir.get_cohort_qc()?.get_errors();
+            cohorts.push(CohortDashboardRow {
+                cohort_name: cohort_dir.cohort_name.clone(),
+                n_individuals: cohort_list.iter().map(|c| c.rows.len()).sum(),
+                n_phenopackets: cohort_dir.phenopackets.len(),
+                n_errors: cohort_errors.len(),
+                outdated_hpo_versions,
+            });
+            errors.extend(cohort_errors);
+        }
+        cohorts.sort_by(|a, b| a.cohort_name.cmp(&b.cohort_name));
+        let mut counts: HashMap<&'static str, usize> = HashMap::new();
+        for e in &errors {
+            *counts.entry(e.error_type.label()).or_insert(0) += 1;
+        }
+        let mut violation_counts: Vec<ViolationCount> = counts
+            .into_iter()
+            .map(|(error_type, count)| ViolationCount { error_type: error_type.to_string(), count })
+            .collect();
+        violation_counts.sort_by(|a, b| b.count.cmp(&a.count).then(a.error_type.cmp(&b.error_type)));
+        let n_violations = errors.len();
+        errors.truncate(MAX_LISTED_VIOLATIONS);
+        let current_schema_version = CohortData::get_phetools_schema_version();
+        let schema_versions: Vec<SchemaVersionCount> = schema_versions
+            .into_iter()
+            .map(|(schema_version, n_cohort_files)| SchemaVersionCount {
+                outdated: schema_version != current_schema_version,
+                schema_version,
+                n_cohort_files,
+            })
+            .collect();
+        Ok(Self {
+            repo_path: repo.path.to_string_lossy().to_string(),
+            hpo_version: hpo_version.to_string(),
+            n_individuals: cohorts.iter().map(|c| c.n_individuals).sum(),
+            n_phenopackets: cohorts.iter().map(|c| c.n_phenopackets).sum(),
+            n_cohorts_with_errors: cohorts.iter().filter(|c| c.n_errors > 0).count(),
+            cohorts,
+            n_outdated_cohort_files: schema_versions.iter().filter(|v| v.outdated).map(|v| v.n_cohort_files).sum(),
+            current_schema_version,
+            schema_versions,
+            violation_counts,
+            violations: errors,
+            n_violations,
+        })
+    }
+}
@@ -1,6 +1,12 @@
-use std::path::PathBuf;
+use std::{collections::{BTreeMap, HashMap}, path::PathBuf, str::FromStr, sync::Arc};
 
-use crate::repo::{cohort_qc::CohortQc, qc_report::QcReport};
+use ontolius::{ontology::{csr::FullCsrOntology, OntologyTerms}, TermId};
+use phenopackets::schema::v2::{core::genomic_interpretation::Call, Phenopacket};
+use walkdir::WalkDir;
+
+use crate::dto::cohort_dto::CohortData;
+use crate::factory::migration::is_newer_version;
+use crate::repo::{cohort_dir::{find_cohort_files, CohortDir}, cohort_qc::CohortQc, cohort_summary::scan_phenopacket_store, qc_report::QcReport};
 
 
 
@@ -14,6 +20,28 @@ pub struct RepoQc {
 }
 
 
+/// A problem with a phenopacket file of the repository
+#[derive(Debug, Clone, PartialEq, serde::Serialize)] 
+#[serde(rename_all = "camelCase")]
+pub struct PpktValidationError {
+    pub file_path: String,
+    /// Empty if the file could not be read as a phenopacket
+    pub phenopacket_id: String,
+    pub error: String,
+}
+
+
+/// An individual (identified by PMID and individual id) that appears in more than one cohort file
+#[derive(Debug, Clone, PartialEq, serde::Serialize)] 
+#[serde(rename_all = "camelCase")]
+pub struct DuplicateIndividual {
+    pub pmid: String,
+    pub individual_id: String,
+    /// Paths of the cohort files that contain the individual
+    pub found_in: Vec<String>,
+}
+
+
 impl RepoQc {
     pub fn new(repository_path: &PathBuf, cohort_qc_list: Vec<CohortQc>) -> Self {
         let phenopacket_count = Self::phenopacket_count(&cohort_qc_list);
@@ -46,6 +74,276 @@ impl RepoQc {
         errs
     }
 
-    
+    /// Check all phenopacket files (the JSON files in the `phenopackets` directories) of the repository:
+    /// the file must be a valid phenopacket, all HPO ids must be found in `hpo`, every variant must have
+    /// an id (variant key), and the metadata must list at least one resource.
+    pub fn check_all_phenopackets(&self, hpo: Arc<FullCsrOntology>) -> Result<Vec<PpktValidationError>, String> {
+        let root = PathBuf::from(&self.repo_path);
+        if !root.is_dir() {
+            return Err(format!("Repository directory {} does not exist", self.repo_path));
+        }
+        let mut ppkt_files: Vec<PathBuf> = WalkDir::new(&root)
+            .into_iter()
+            .filter_map(|e| e.ok())
+            .filter(|e| e.file_type().is_file())
+            .map(|e| e.into_path())
+            .filter(|p| p.extension().is_some_and(|ext| ext == "json")
+                && p.parent().and_then(|dir| dir.file_name()).is_some_and(|name| name == "phenopackets"))
+            .collect();
+        ppkt_files.sort();
+        let mut errors = Vec::new();
+        for path in ppkt_files {
+            let file_path = path.to_string_lossy().to_string();
+            match CohortDir::load_phenopacket(&path) {
+                Ok(ppkt) => {
+                    errors.extend(Self::check_phenopacket(&ppkt, &hpo)
+                        .into_iter()
+                        .map(|error| PpktValidationError {
+                            file_path: file_path.clone(),
+                            phenopacket_id: ppkt.id.clone(),
+                            error,
+                        }));
+                }
+                Err(e) => errors.push(PpktValidationError {
+                    file_path,
+                    phenopacket_id: String::default(),
+                    error: format!("Could not read phenopacket: {e}"),
+                }),
+            }
+        }
+        Ok(errors)
+    }
+
+    /// Find individuals with the same PMID and individual id in several cohort files of the repository at `path`.
+    /// The same patient may have been curated for two diseases or genes; this is not necessarily an error,
+    /// so we only report the duplicates as warnings.
+    pub fn find_duplicate_individuals(path: &PathBuf) -> Result<Vec<DuplicateIndividual>, String> {
+        let mut occurrences: BTreeMap<(String, String), Vec<String>> = BTreeMap::new();
+        for cohort_file in find_cohort_files(path) {
+            let file_path = cohort_file.to_string_lossy().to_string();
+            let cohort = crate::factory::load_json_cohort(&file_path)?;
+            for row in &cohort.rows {
+                let key = (row.individual_data.pmid.clone(), row.individual_data.individual_id.clone());
+                let found_in = occurrences.entry(key).or_default();
+                if !found_in.contains(&file_path) {
+                    found_in.push(file_path.clone());
+                }
+            }
+        }
+        let duplicates: Vec<DuplicateIndividual> = occurrences
+            .into_iter()
+            .filter(|(_, found_in)| found_in.len() > 1)
+            .map(|((pmid, individual_id), found_in)| DuplicateIndividual { pmid, individual_id, found_in })
+            .collect();
+        for dup in &duplicates {
+            tracing::warn!("{} ({}) found in {} cohort files: {}", 
+                dup.individual_id, dup.pmid, dup.found_in.len(), dup.found_in.join(", "));
+        }
+        Ok(duplicates)
+    }
+
+    /// Group the paths of all cohort files of the repository at `path` (see [`scan_phenopacket_store`]) by their
+    /// `phetools_schema_version`. Files with an older version than the current one need to be migrated, and files
+    /// with a newer version are not supported by this version of phetools; both are reported as warnings.
+    /// Files without a version are listed under "unknown".
+    pub fn check_schema_versions(path: &PathBuf) -> Result<HashMap<String, Vec<String>>, String> {
+        let current_version = CohortData::get_phetools_schema_version();
+        let mut versions: HashMap<String, Vec<String>> = HashMap::new();
+        for summary in scan_phenopacket_store(path)? {
+            let status = schema_version_status(&summary.schema_version);
+            if status != SCHEMA_CURRENT {
+                tracing::warn!("{} has schema version {} (current: {}): {}", 
+                    summary.cohort_file.display(), summary.schema_version, current_version, status);
+            }
+            versions.entry(summary.schema_version).or_default().push(summary.cohort_file.to_string_lossy().to_string());
+        }
+        Ok(versions)
+    }
+
+    fn check_phenopacket(ppkt: &Phenopacket, hpo: &FullCsrOntology) -> Vec<String> {
+        let mut errors = Vec::new();
+        for pf in &ppkt.phenotypic_features {
+            let Some(term) = &pf.r#type else {
+                errors.push("Phenotypic feature without HPO term".to_string());
+                continue;
+            };
+            let resolved = TermId::from_str(&term.id)
+                .ok()
+                .is_some_and(|tid| hpo.term_by_id(&tid).is_some());
+            if !resolved {
+                errors.push(format!("Could not find HPO term {} ({}) in the ontology", term.id, term.label));
+            }
+        }
+        let variant_ids = ppkt.interpretations.iter()
+            .filter_map(|i| i.diagnosis.as_ref())
+            .flat_map(|d| d.genomic_interpretations.iter())
+            .filter_map(|gi| match &gi.call {
+                Some(Call::VariantInterpretation(vi)) => Some(vi.variation_descriptor.as_ref().map(|vd| vd.id.as_str())),
+                _ => None,
+            });
+        for variant_id in variant_ids {
+            if variant_id.is_none_or(|id| id.trim().is_empty()) {
+                errors.push("Variant without variant key".to_string());
+            }
+        }
+        match &ppkt.meta_data {
+            Some(meta_data) if !meta_data.resources.is_empty() => {}
+            _ => errors.push("MetaData must list at least one resource".to_string()),
+        }
+        errors
+    }
+}
+
 
+const SCHEMA_CURRENT: &str = "current";
+const SCHEMA_NEEDS_MIGRATION: &str = "needs migration";
+const SCHEMA_UNSUPPORTED: &str = "unsupported (newer)";
+
+/// Status of a cohort file with the given schema version relative to the current version
+fn schema_version_status(version: &str) -> &'static str {
+    let current_version = CohortData::get_phetools_schema_version();
+    if version == current_version {
+        SCHEMA_CURRENT
+    } else if is_newer_version(version, &current_version) {
+        SCHEMA_UNSUPPORTED
+    } else {
+        SCHEMA_NEEDS_MIGRATION
+    }
+}
+
+/// A table with the number of cohort files per schema version (sorted by version); the files with
+/// outdated or unsupported (newer) versions are listed below the table.
+pub fn schema_version_summary_report(versions: &HashMap<String, Vec<String>>) -> String {
+    let current_version = CohortData::get_phetools_schema_version();
+    let sorted: BTreeMap<&String, &Vec<String>> = versions.iter().collect();
+    let mut lines = vec![format!("{:<16}{:>14}  {}", "Schema version", "Cohort files", "Status")];
+    for (version, files) in &sorted {
+        lines.push(format!("{:<16}{:>14}  {}", version, files.len(), schema_version_status(version)));
+    }
+    let files_with_status = |status: &str| -> Vec<&String> {
+        sorted.iter()
+            .filter(|(version, _)| schema_version_status(version) == status)
+            .flat_map(|(_, files)| files.iter())
+            .collect()
+    };
+    let outdated = files_with_status(SCHEMA_NEEDS_MIGRATION);
+    if !outdated.is_empty() {
+        lines.push(String::new());
+        lines.push(format!("Files to migrate to schema version {current_version}:"));
+        lines.extend(outdated.iter().map(|f| format!("  {f}")));
+    }
+    let unsupported = files_with_status(SCHEMA_UNSUPPORTED);
+    if !unsupported.is_empty() {
+        lines.push(String::new());
+        lines.push(format!("Files with a schema version newer than {current_version} (update phetools):"));
+        lines.extend(unsupported.iter().map(|f| format!("  {f}")));
+    }
+    lines.join("\n")
+}
+
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use rstest::rstest;
+    use serde_json::Value;
+    use crate::dto::cohort_dto::CohortData;
+    use crate::test_utils::fixtures::{fbn1_cohort, hpo};
+    use crate::test_utils::temp_store::{temp_store, write_cohort_file};
+
+    fn ppkt_dir_with_phenopackets(name: &str, cohort: CohortData, hpo: Arc<FullCsrOntology>) -> PathBuf {
+        let root = temp_store(name);
+        let ppkt_dir = root.join("FBN1").join("phenopackets");
+        std::fs::create_dir_all(&ppkt_dir).unwrap();
+        crate::ppkt::write_phenopackets(cohort, ppkt_dir, "ORCID:0000-0002-0736-9199".to_string(), hpo, true).unwrap();
+        root
+    }
+
+    fn repo_qc(root: &PathBuf) -> RepoQc {
+        RepoQc::new(root, vec![])
+    }
+
+    #[rstest]
+    fn test_valid_phenopackets(hpo: Arc<FullCsrOntology>, fbn1_cohort: CohortData) {
+        let root = ppkt_dir_with_phenopackets("phetools_test_check_ppkt_valid", fbn1_cohort, hpo.clone());
+        let errors = repo_qc(&root).check_all_phenopackets(hpo);
+        std::fs::remove_dir_all(&root).unwrap();
+        assert!(errors.unwrap().is_empty());
+    }
+
+    #[rstest]
+    fn test_invalid_phenopacket(hpo: Arc<FullCsrOntology>, fbn1_cohort: CohortData) {
+        let root = ppkt_dir_with_phenopackets("phetools_test_check_ppkt_invalid", fbn1_cohort, hpo.clone());
+        let ppkt_dir = root.join("FBN1").join("phenopackets");
+        let mut files: Vec<PathBuf> = std::fs::read_dir(&ppkt_dir).unwrap().map(|e| e.unwrap().path()).collect();
+        files.sort();
+        // made-up HPO term and no resources in the metadata
+        let mut ppkt: Value = serde_json::from_str(&std::fs::read_to_string(&files[0]).unwrap()).unwrap();
+        ppkt["phenotypicFeatures"][0]["type"]["id"] = Value::from("HP:9999999");
+        ppkt["metaData"]["resources"] = Value::Array(vec![]);
+        std::fs::write(&files[0], serde_json::to_string(&ppkt).unwrap()).unwrap();
+        std::fs::write(ppkt_dir.join("broken.json"), "{ not a phenopacket").unwrap();
+        let errors = repo_qc(&root).check_all_phenopackets(hpo);
+        std::fs::remove_dir_all(&root).unwrap();
+        let errors = errors.unwrap();
+        assert_eq!(3, errors.len());
+        let invalid: Vec<&PpktValidationError> = errors.iter()
+            .filter(|e| e.phenopacket_id == ppkt["id"].as_str().unwrap())
+            .collect();
+        assert_eq!(2, invalid.len());
+        assert!(invalid[0].error.contains("HP:9999999"));
+        assert!(invalid[1].error.contains("resource"));
+        let broken = errors.iter().find(|e| e.file_path.ends_with("broken.json")).unwrap();
+        assert!(broken.phenopacket_id.is_empty());
+    }
+
+    #[rstest]
+    fn test_find_duplicate_individuals(fbn1_cohort: CohortData) {
+        let root = temp_store("phetools_test_duplicate_individuals");
+        // the second cohort contains Individual 2 of the first one (same PMID) and a new individual
+        let mut second = fbn1_cohort.clone();
+        second.rows.remove(0);
+        second.rows[1].individual_data.pmid = "PMID:31111111".to_string();
+        write_cohort_file(&root, "FBN1", &fbn1_cohort);
+        write_cohort_file(&root, "FBN1_B", &second);
+        let duplicates = RepoQc::find_duplicate_individuals(&root);
+        std::fs::remove_dir_all(&root).unwrap();
+        let duplicates = duplicates.unwrap();
+        assert_eq!(1, duplicates.len());
+        assert_eq!("PMID:29999999", duplicates[0].pmid);
+        assert_eq!("Individual 2", duplicates[0].individual_id);
+        assert_eq!(2, duplicates[0].found_in.len());
+        assert!(duplicates[0].found_in[1].contains("FBN1_B"));
+    }
+
+    #[rstest]
+    fn test_check_schema_versions(fbn1_cohort: CohortData) {
+        let root = temp_store("phetools_test_schema_versions");
+        let current = CohortData::get_phetools_schema_version();
+        for (gene, version) in [("FBN1", current.as_str()), ("FBN2", "0.2"), ("FBN3", "0.2"), ("FBN4", "0.10")] {
+            let mut cohort = fbn1_cohort.clone();
+            cohort.phetools_schema_version = version.to_string();
+            write_cohort_file(&root, gene, &cohort);
+        }
+        let versions = RepoQc::check_schema_versions(&root);
+        std::fs::remove_dir_all(&root).unwrap();
+        let versions = versions.unwrap();
+        assert_eq!(3, versions.len());
+        assert_eq!(1, versions[&current].len());
+        assert_eq!(2, versions["0.2"].len());
+        let report = schema_version_summary_report(&versions);
+        assert!(report.lines().any(|l| l.starts_with("0.2") && l.ends_with("needs migration")));
+        // 0.10 is newer than the current version (compared numerically, not as strings)
+        assert!(report.lines().any(|l| l.starts_with("0.10") && l.ends_with("unsupported (newer)")));
+        assert!(report.contains("FBN4_MFS_individuals.json"));
+        assert!(report.lines().any(|l| l.starts_with(current.as_str()) && l.ends_with("current")));
+        assert!(report.contains("FBN2_MFS_individuals.json"));
+        assert!(!report.contains("FBN1_MFS_individuals.json"));
+    }
+
+    #[rstest]
+    fn test_missing_repository(hpo: Arc<FullCsrOntology>) {
+        let qc = repo_qc(&PathBuf::from("/does/not/exist"));
+        assert!(qc.check_all_phenopackets(hpo).is_err());
+    }
 }
\ No newline at end of file
@@ -1,8 +1,11 @@
 /// Fixtures needed for multiple tests
 /// We create a singleton HPO to prevent each test module from loading it anew
 
+pub mod temp_store;
+
 #[cfg(test)]
 pub mod fixtures {
+    use std::collections::HashMap;
     use std::sync::LazyLock;
     use ontolius::io::OntologyLoaderBuilder;
     use ontolius::ontology::csr::FullCsrOntology;
@@ -11,6 +14,11 @@ pub mod fixtures {
     use std::io::BufReader;
     use flate2::read::GzDecoder;
 
+    use crate::dto::cohort_dto::{CohortData, DiseaseData, GeneTranscriptData, IndividualData, ModeOfInheritance, RowData};
+    use crate::dto::hgvs_variant::HgvsVariant;
+    use crate::dto::hpo_term_dto::{CellValue, HpoTermDuplet};
+    use crate::dto::structural_variant::StructuralVariant;
+
 
     pub static HPO: LazyLock<Arc<FullCsrOntology>> = LazyLock::new(|| {
         let path = "resources/hp.v2025-03-03.json.gz";
@@ -20,9 +28,78 @@ pub mod fixtures {
         Arc::new(hpo)
     });
 
-   
+
     #[rstest::fixture]
     pub fn hpo() -> Arc<FullCsrOntology> {
         Arc::clone(&HPO)
     }
-}
\ No newline at end of file
+
+    /// Small Mendelian FBN1 cohort with three individuals, four HPO columns,
+    /// one HGVS variant and one structural variant.
+    #[rstest::fixture]
+    pub fn fbn1_cohort() -> CohortData {
+        let mut disease = DiseaseData::new("OMIM:154700", "Marfan syndrome");
+        disease.mode_of_inheritance_list.push(ModeOfInheritance {
+            hpo_id: "HP:0000006".to_string(),
+            hpo_label: "Autosomal dominant inheritance".to_string(),
+            citation: "PMID:29999999".to_string(),
+        });
+        disease.gene_transcript_list.push(GeneTranscriptData {
+            hgnc_id: "HGNC:3603".to_string(),
+            gene_symbol: "FBN1".to_string(),
+            transcript: "NM_000138.5".to_string(),
+        });
+        let hpo_headers = vec![
+            HpoTermDuplet::new("Arachnodactyly", "HP:0001166"),
+            HpoTermDuplet::new("Atrial septal defect", "HP:0001631"),
+            HpoTermDuplet::new("Long hallux", "HP:0001847"),
+            HpoTermDuplet::new("Short stature", "HP:0004322"),
+        ];
+        let hgvs = HgvsVariant::new_from_parts(
+            "hg38".to_string(),
+            "15".to_string(),
+            48411364,
+            "C".to_string(),
+            "A".to_string(),
+            "FBN1".to_string(),
+            "HGNC:3603".to_string(),
+            "c.8242G>T".to_string(),
+            "NM_000138.5".to_string(),
+            "NC_000015.10:g.48411364C>A".to_string(),
+        );
+        let sv = StructuralVariant::chromosomal_deletion(
+            "EX1-5 DEL", "FBN1", "NM_000138.5", "HGNC:3603", "15".to_string()).unwrap();
+        let hgvs_key = hgvs.variant_key();
+        let sv_key = sv.variant_key().to_string();
+        let row = |individual_data: IndividualData, allele: &str, values: [&str; 4]| RowData {
+            individual_data,
+            disease_id_list: vec!["OMIM:154700".to_string()],
+            allele_count_map: HashMap::from([(allele.to_string(), 1)]),
+            hpo_data: values.iter().map(|v| v.parse::<CellValue>().unwrap()).collect(),
+        };
+        let rows = vec![
+            row(
+                IndividualData::new("PMID:29999999", "Study A", "Individual 1", "", "P2Y", "P10Y", "no", "M"),
+                &hgvs_key,
+                ["observed", "observed", "excluded", "na"]),
+            row(
+                IndividualData::new("PMID:29999999", "Study A", "Individual 2", "", "Congenital onset", "P5Y", "no", "F"),
+                &hgvs_key,
+                ["observed", "excluded", "observed", "excluded"]),
+            row(
+                IndividualData::new("PMID:30000000", "Study B", "Individual 3", "", "na", "P30Y", "yes", "F"),
+                &sv_key,
+                ["observed", "na", "na", "observed"]),
+        ];
+        let mut cohort = CohortData::mendelian_with_variants(
+            disease,
+            hpo_headers,
+            rows,
+            "2025-03-03",
+            HashMap::from([(hgvs_key, hgvs)]),
+            HashMap::from([(sv_key, sv)]),
+        );
+        cohort.cohort_acronym = Some("MFS".to_string());
+        cohort
+    }
+}
@@ -0,0 +1,26 @@
+//! Temporary repository ("store") directories for tests: one subdirectory per gene with a cohort file.
+//!
+//! This file is also included by the integration tests (`tests/common/mod.rs`), so it only depends on std and serde.
+
+use std::path::{Path, PathBuf};
+
+
+/// An empty directory `<temp dir>/<name>_<process id>` (removed first if it exists). The process id is appended
+/// so that concurrent test runs do not share the directory; the caller removes it after use.
+pub fn temp_store(name: &str) -> PathBuf {
+    let root = std::env::temp_dir().join(format!("{}_{}", name, std::process::id()));
+    if root.exists() {
+        std::fs::remove_dir_all(&root).unwrap();
+    }
+    std::fs::create_dir_all(&root).unwrap();
+    root
+}
+
+/// Write the cohort as JSON to `<root>/<gene>/<gene>_MFS_individuals.json` and return the path of the file
+pub fn write_cohort_file<T: serde::Serialize>(root: &Path, gene: &str, cohort: &T) -> PathBuf {
+    let dir = root.join(gene);
+    std::fs::create_dir_all(&dir).unwrap();
+    let path = dir.join(format!("{gene}_MFS_individuals.json"));
+    std::fs::write(&path, serde_json::to_string(cohort).unwrap()).unwrap();
+    path
+}
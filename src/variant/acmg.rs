@@ -1,6 +1,13 @@
 use serde::{Deserialize, Serialize};
 use std::fmt;
 
+use crate::dto::hgvs_variant::HgvsVariant;
+
+/// Allele frequency above which a variant is considered benign (BA1, stand-alone evidence)
+const BA1_MIN_ALLELE_FREQUENCY: f64 = 0.05;
+/// Allele frequency up to which a variant is considered absent from controls (PM2)
+const PM2_MAX_ALLELE_FREQUENCY: f64 = 0.0001;
+
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
 #[repr(u8)]
 #[serde(rename_all = "snake_case")]
@@ -33,7 +40,7 @@ impl AcmgPathogenicityClassification {
             "likely pathogenic" | "likely_pathogenic" => Self::LikelyPathogenic,
             "pathogenic" => Self::Pathogenic,
             _ => {
-                eprintln!("Warning: Unrecognized ACMG category '{}'", acmg);
+                tracing::warn!("Unrecognized ACMG category '{}'", acmg);
                 Self::NotProvided
             }
         }
@@ -55,6 +62,66 @@ impl fmt::Display for AcmgPathogenicityClassification {
 }
 
 
+/// ACMG/AMP evidence criteria that we can assign automatically
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[allow(clippy::upper_case_acronyms)]
+pub enum AcmgEvidence {
+    /// Null variant (nonsense, frameshift, canonical splice site) in a gene where loss of function
+    /// is a known mechanism of disease (very strong pathogenic)
+    PVS1,
+    /// Allele frequency above 5% in gnomAD (stand-alone benign)
+    BA1,
+    /// Absent from controls or at extremely low frequency in gnomAD (moderate pathogenic)
+    PM2,
+}
+
+impl fmt::Display for AcmgEvidence {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let s = match self {
+            Self::PVS1 => "PVS1",
+            Self::BA1 => "BA1",
+            Self::PM2 => "PM2",
+        };
+        write!(f, "{}", s)
+    }
+}
+
+/// Collects ACMG/AMP evidence for a variant from its annotations
+pub struct AcmgClassifier;
+
+impl AcmgClassifier {
+    /// All evidence criteria that apply to the variant
+    pub fn evidence(hgvs: &HgvsVariant) -> Vec<AcmgEvidence> {
+        Self::loss_of_function_evidence(hgvs)
+            .into_iter()
+            .chain(Self::population_frequency_evidence(hgvs))
+            .collect()
+    }
+
+    /// PVS1 if the variant is likely to cause loss of function (see [`crate::variant::is_likely_loss_of_function`]).
+    /// Whether loss of function is a mechanism of the disease must be checked by the curator.
+    pub fn loss_of_function_evidence(hgvs: &HgvsVariant) -> Option<AcmgEvidence> {
+        crate::variant::is_likely_loss_of_function(hgvs).then_some(AcmgEvidence::PVS1)
+    }
+
+    /// BA1 if the gnomAD allele frequency (overall or popmax) is above 5%; PM2 if the variant
+    /// has no gnomAD frequency or the frequency is near zero.
+    pub fn population_frequency_evidence(hgvs: &HgvsVariant) -> Option<AcmgEvidence> {
+        let max_af = match (hgvs.gnomad_af(), hgvs.gnomad_popmax_af()) {
+            (None, None) => return Some(AcmgEvidence::PM2),
+            (af, popmax_af) => af.unwrap_or(0.0).max(popmax_af.unwrap_or(0.0)),
+        };
+        if max_af > BA1_MIN_ALLELE_FREQUENCY {
+            Some(AcmgEvidence::BA1)
+        } else if max_af <= PM2_MAX_ALLELE_FREQUENCY {
+            Some(AcmgEvidence::PM2)
+        } else {
+            None
+        }
+    }
+}
+
+
 // region:    --- Tests
 
 #[cfg(test)]
@@ -63,6 +130,7 @@ mod tests {
     type Result<T> = core::result::Result<T, Error>; // For tests.
 
     use super::*;
+    use rstest::rstest;
 
     #[test]
     fn test_name() -> Result<()> {
@@ -71,6 +139,45 @@ mod tests {
     
         Ok(())
     }
+
+    #[rstest]
+    #[case::common(Some(0.12), None, Some(AcmgEvidence::BA1))]
+    #[case::common_in_one_population(Some(0.01), Some(0.08), Some(AcmgEvidence::BA1))]
+    #[case::rare(Some(0.001), Some(0.002), None)]
+    #[case::near_zero(Some(0.00002), Some(0.00005), Some(AcmgEvidence::PM2))]
+    #[case::absent(None, None, Some(AcmgEvidence::PM2))]
+    fn test_population_frequency_evidence(
+        #[case] af: Option<f64>,
+        #[case] popmax_af: Option<f64>,
+        #[case] expected: Option<AcmgEvidence>
+    ) {
+        let mut var = HgvsVariant::new_from_parts("hg38".to_string(), "chr15".to_string(), 48411364,
+            "C".to_string(), "A".to_string(), "FBN1".to_string(), "HGNC:3603".to_string(),
+            "c.8242G>T".to_string(), "NM_000138.5".to_string(), "NC_000015.10:g.48411364C>A".to_string());
+        var.set_gnomad_frequency(af, popmax_af);
+        assert_eq!(expected, AcmgClassifier::population_frequency_evidence(&var));
+        assert_eq!(expected.into_iter().collect::<Vec<_>>(), AcmgClassifier::evidence(&var));
+    }
+
+    #[rstest]
+    #[case::nonsense("c.8242G>T", Some("stop_gained"), Some(AcmgEvidence::PVS1))]
+    #[case::splice_donor("c.6163+1G>A", None, Some(AcmgEvidence::PVS1))]
+    #[case::missense("c.4621C>T", None, None)]
+    fn test_loss_of_function_evidence(
+        #[case] hgvs: &str,
+        #[case] consequence: Option<&str>,
+        #[case] expected: Option<AcmgEvidence>
+    ) {
+        let mut var = HgvsVariant::new_from_parts("hg38".to_string(), "chr15".to_string(), 48411364,
+            "C".to_string(), "A".to_string(), "FBN1".to_string(), "HGNC:3603".to_string(),
+            hgvs.to_string(), "NM_000138.5".to_string(), "NC_000015.10:g.48411364C>A".to_string());
+        var.set_vep_annotation(consequence.map(str::to_string), None, None);
+        assert_eq!(expected, AcmgClassifier::loss_of_function_evidence(&var));
+        // absent from gnomAD
+        let mut evidence: Vec<AcmgEvidence> = expected.into_iter().collect();
+        evidence.push(AcmgEvidence::PM2);
+        assert_eq!(evidence, AcmgClassifier::evidence(&var));
+    }
 }
 
 // endregion: --- Tests
\ No newline at end of file
@@ -0,0 +1,201 @@
+//! # gnomAD Integration
+//!
+//! Retrieve population allele frequencies of HGVS variants from the
+//! [gnomAD GraphQL API](https://gnomad.broadinstitute.org/api).
+//!
+//! We query gnomAD v4.1 (dataset `gnomad_r4`, GRCh38), so only hg38 variants can be looked up.
+//! The allele frequency is computed from the combined exome and genome allele counts, and the
+//! popmax frequency is the highest allele frequency of the genetic ancestry groups (sex-specific
+//! subsets are ignored). Variants that are not in gnomAD have no frequency (`Ok(None)`).
+use std::collections::HashMap;
+
+use reqwest::blocking::Client;
+use serde_json::{json, Value};
+
+use crate::dto::hgvs_variant::HgvsVariant;
+
+pub(crate) const GNOMAD_API_URL: &str = "https://gnomad.broadinstitute.org/api";
+const GNOMAD_DATASET: &str = "gnomad_r4";
+
+const GNOMAD_VARIANT_QUERY: &str = "query GnomadVariant($variantId: String!, $dataset: DatasetId!) {
+  variant(variantId: $variantId, dataset: $dataset) {
+    exome { ac an populations { id ac an } }
+    genome { ac an populations { id ac an } }
+  }
+}";
+
+
+#[derive(Clone, Debug, PartialEq)]
+pub(crate) struct GnomadFrequency {
+    pub af: f64,
+    pub popmax_af: Option<f64>,
+}
+
+
+/// The gnomAD variant id, e.g., 15-48411364-C-A
+fn gnomad_variant_id(hgvs: &HgvsVariant) -> Result<String, String> {
+    if hgvs.assembly() != "hg38" {
+        return Err(format!("gnomAD {GNOMAD_DATASET} requires hg38 but {hgvs} is on {}", hgvs.assembly()));
+    }
+    let chrom = hgvs.chr().trim_start_matches("chr");
+    Ok(format!("{}-{}-{}-{}", chrom, hgvs.position(), hgvs.ref_allele(), hgvs.alt_allele()))
+}
+
+/// Query the gnomAD API at `api_url` for the allele frequencies of `hgvs`
+pub(crate) fn fetch_gnomad_frequency_from(api_url: &str, hgvs: &HgvsVariant) -> Result<Option<GnomadFrequency>, String> {
+    let variant_id = gnomad_variant_id(hgvs)?;
+    let body = json!({
+        "query": GNOMAD_VARIANT_QUERY,
+        "variables": { "variantId": variant_id, "dataset": GNOMAD_DATASET },
+    });
+    let response: Value = Client::new()
+        .post(api_url)
+        .json(&body)
+        .send()
+        .and_then(|r| r.json())
+        .map_err(|e| format!("gnomAD request for {variant_id} failed: {e}"))?;
+    tracing::debug!(variant_id, "gnomAD query");
+    parse_gnomad_response(&response)
+        .map_err(|e| format!("gnomAD request for {variant_id} failed: {e}"))
+}
+
+fn parse_gnomad_response(response: &Value) -> Result<Option<GnomadFrequency>, String> {
+    if let Some(errors) = response.get("errors").and_then(Value::as_array) {
+        let messages: Vec<&str> = errors.iter().filter_map(|e| e.get("message").and_then(Value::as_str)).collect();
+        if messages.iter().all(|m| m.to_lowercase().contains("not found")) {
+            return Ok(None);
+        }
+        return Err(messages.join("; "));
+    }
+    let variant = match response.pointer("/data/variant") {
+        Some(Value::Null) | None => return Ok(None),
+        Some(v) => v,
+    };
+    // allele count and allele number summed over the exome and genome data
+    let mut ac = 0;
+    let mut an = 0;
+    let mut populations: HashMap<String, (u64, u64)> = HashMap::new();
+    for sequencing_type in ["exome", "genome"] {
+        let Some(data) = variant.get(sequencing_type).filter(|d| !d.is_null()) else {
+            continue;
+        };
+        ac += data.get("ac").and_then(Value::as_u64).unwrap_or(0);
+        an += data.get("an").and_then(Value::as_u64).unwrap_or(0);
+        for pop in data.get("populations").and_then(Value::as_array).into_iter().flatten() {
+            let Some(id) = pop.get("id").and_then(Value::as_str) else {
+                continue;
+            };
+            // skip the XX/XY subsets
+            if id.contains('_') || id == "XX" || id == "XY" {
+                continue;
+            }
+            let counts = populations.entry(id.to_string()).or_insert((0, 0));
+            counts.0 += pop.get("ac").and_then(Value::as_u64).unwrap_or(0);
+            counts.1 += pop.get("an").and_then(Value::as_u64).unwrap_or(0);
+        }
+    }
+    if an == 0 {
+        return Ok(None);
+    }
+    let popmax_af = populations
+        .values()
+        .filter(|(_, pop_an)| *pop_an > 0)
+        .map(|(pop_ac, pop_an)| *pop_ac as f64 / *pop_an as f64)
+        .fold(None, |max: Option<f64>, af| Some(max.map_or(af, |m| m.max(af))));
+    Ok(Some(GnomadFrequency { af: ac as f64 / an as f64, popmax_af }))
+}
+
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use std::io::{BufRead, BufReader, Read, Write};
+    use std::net::TcpListener;
+    use std::thread::JoinHandle;
+    use rstest::{fixture, rstest};
+
+    #[fixture]
+    fn fbn1_variant() -> HgvsVariant {
+        HgvsVariant::new_from_parts(
+            "hg38".to_string(),
+            "chr15".to_string(),
+            48411364,
+            "C".to_string(),
+            "A".to_string(),
+            "FBN1".to_string(),
+            "HGNC:3603".to_string(),
+            "c.8242G>T".to_string(),
+            "NM_000138.5".to_string(),
+            "NC_000015.10:g.48411364C>A".to_string(),
+        )
+    }
+
+    /// Serve a single request with `response_body` and return the URL and a handle that yields the request body
+    fn mock_gnomad_api(response_body: &'static str) -> (String, JoinHandle<String>) {
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let url = format!("http://{}/api", listener.local_addr().unwrap());
+        let handle = std::thread::spawn(move || {
+            let (stream, _) = listener.accept().unwrap();
+            let mut reader = BufReader::new(stream);
+            let mut content_length = 0;
+            loop {
+                let mut line = String::new();
+                reader.read_line(&mut line).unwrap();
+                if line == "\r\n" {
+                    break;
+                }
+                if let Some((name, value)) = line.split_once(':') {
+                    if name.eq_ignore_ascii_case("content-length") {
+                        content_length = value.trim().parse().unwrap();
+                    }
+                }
+            }
+            let mut request_body = vec![0; content_length];
+            reader.read_exact(&mut request_body).unwrap();
+            let response = format!(
+                "HTTP/1.1 200 OK\r\nContent-Type: application/json\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{}",
+                response_body.len(), response_body);
+            reader.get_mut().write_all(response.as_bytes()).unwrap();
+            String::from_utf8(request_body).unwrap()
+        });
+        (url, handle)
+    }
+
+    #[rstest]
+    fn test_fetch_frequency(fbn1_variant: HgvsVariant) {
+        let (url, handle) = mock_gnomad_api(r#"{"data": {"variant": {
+            "exome": {"ac": 3, "an": 1000, "populations": [
+                {"id": "afr", "ac": 2, "an": 100}, {"id": "afr_XX", "ac": 2, "an": 50}, {"id": "nfe", "ac": 1, "an": 900}]},
+            "genome": {"ac": 1, "an": 1000, "populations": [{"id": "nfe", "ac": 1, "an": 1000}]}}}}"#);
+        let frequency = fetch_gnomad_frequency_from(&url, &fbn1_variant).unwrap().unwrap();
+        let request: Value = serde_json::from_str(&handle.join().unwrap()).unwrap();
+        assert_eq!("15-48411364-C-A", request["variables"]["variantId"]);
+        assert_eq!("gnomad_r4", request["variables"]["dataset"]);
+        assert_eq!(0.002, frequency.af);
+        assert_eq!(Some(0.02), frequency.popmax_af);
+    }
+
+    #[rstest]
+    fn test_variant_not_in_gnomad(fbn1_variant: HgvsVariant) {
+        let (url, handle) = mock_gnomad_api(r#"{"errors": [{"message": "Variant not found"}], "data": {"variant": null}}"#);
+        let frequency = fetch_gnomad_frequency_from(&url, &fbn1_variant);
+        handle.join().unwrap();
+        assert_eq!(Ok(None), frequency);
+    }
+
+    #[rstest]
+    fn test_gnomad_error(fbn1_variant: HgvsVariant) {
+        let (url, handle) = mock_gnomad_api(r#"{"errors": [{"message": "Invalid dataset"}]}"#);
+        let frequency = fetch_gnomad_frequency_from(&url, &fbn1_variant);
+        handle.join().unwrap();
+        assert!(frequency.is_err());
+    }
+
+    #[rstest]
+    fn test_hg19_variant_is_rejected() {
+        let var = HgvsVariant::new_from_parts("hg19".to_string(), "15".to_string(), 48703561, "C".to_string(),
+            "A".to_string(), "FBN1".to_string(), "HGNC:3603".to_string(), "c.8242G>T".to_string(),
+            "NM_000138.5".to_string(), "NC_000015.9:g.48703561C>A".to_string());
+        assert!(fetch_gnomad_frequency_from(GNOMAD_API_URL, &var).is_err());
+    }
+}
@@ -0,0 +1,109 @@
+//! Syntactic checks of HGVS expressions
+//!
+//! These checks do not call Variant Validator and do not check the reference sequence; they only
+//! test whether a string has the form of a (simple) HGVS expression, e.g., to guess the type of an
+//! ETL column or to validate input in the GUI while the user is typing.
+use std::sync::LazyLock;
+
+use regex::Regex;
+
+
+/// Position, possibly intronic (`123+5`) or in the UTR (`-12`, `*45`), optionally a range (`123_125`)
+const POSITION: &str = r"[-*]?\d+(?:[+-]\d+)?(?:_[-*]?\d+(?:[+-]\d+)?)?";
+
+static CODING_RE: LazyLock<Regex> = LazyLock::new(|| {
+    Regex::new(&format!(r"^c\.{POSITION}(?:[ACGT]>[ACGT]|delins[ACGT]+|del[ACGT]*|dup[ACGT]*|ins(?:[ACGT]+|\d+)|inv|=)$")).unwrap()
+});
+static NON_CODING_RE: LazyLock<Regex> = LazyLock::new(|| {
+    Regex::new(&format!(r"^n\.{POSITION}(?:[ACGT]>[ACGT]|delins[ACGT]+|del[ACGT]*|dup[ACGT]*|ins(?:[ACGT]+|\d+)|inv|=)$")).unwrap()
+});
+/// RNA nucleotides are written in lower case
+static RNA_RE: LazyLock<Regex> = LazyLock::new(|| {
+    Regex::new(&format!(r"^r\.{POSITION}(?:[acgu]>[acgu]|delins[acgu]+|del[acgu]*|dup[acgu]*|ins(?:[acgu]+|\d+)|inv|=)$")).unwrap()
+});
+/// The mitochondrial genome has no introns or UTRs
+static MITOCHONDRIAL_RE: LazyLock<Regex> = LazyLock::new(|| {
+    Regex::new(r"^m\.\d+(?:_\d+)?(?:[ACGT]>[ACGT]|delins[ACGT]+|del[ACGT]*|dup[ACGT]*|ins[ACGT]+|inv|=)$").unwrap()
+});
+
+
+/// Reference sequence type of an HGVS expression (given by its prefix)
+#[allow(clippy::upper_case_acronyms)]
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum HgvsType {
+    /// `c.`
+    Coding,
+    /// `n.`
+    NonCoding,
+    /// `r.`
+    RNA,
+    /// `m.`
+    Mitochondrial,
+}
+
+
+/// True if `s` has the form of a `c.`, `n.`, `r.`, or `m.` HGVS expression (without transcript),
+/// e.g., `c.8242G>T` or `c.1234_1236del`. No API calls are made.
+pub fn is_valid_hgvs(s: &str) -> bool {
+    classify_hgvs_type(s).is_some()
+}
+
+/// The type of the HGVS expression, or `None` if `s` is not a syntactically valid HGVS expression.
+pub fn classify_hgvs_type(s: &str) -> Option<HgvsType> {
+    let s = s.trim();
+    let (re, hgvs_type) = match s.get(..2)? {
+        "c." => (&CODING_RE, HgvsType::Coding),
+        "n." => (&NON_CODING_RE, HgvsType::NonCoding),
+        "r." => (&RNA_RE, HgvsType::RNA),
+        "m." => (&MITOCHONDRIAL_RE, HgvsType::Mitochondrial),
+        _ => return None,
+    };
+    re.is_match(s).then_some(hgvs_type)
+}
+
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use rstest::rstest;
+
+    #[rstest]
+    #[case("c.8242G>T", HgvsType::Coding)]
+    #[case("c.185_186insAGTCATGACTT", HgvsType::Coding)]
+    #[case("c.68_69del", HgvsType::Coding)]
+    #[case("c.5266dupC", HgvsType::Coding)]
+    #[case("c.1234+1G>A", HgvsType::Coding)]
+    #[case("c.-12C>T", HgvsType::Coding)]
+    #[case("c.*45delinsTT", HgvsType::Coding)]
+    #[case(" c.100_102inv ", HgvsType::Coding)]
+    #[case("n.76A>C", HgvsType::NonCoding)]
+    #[case("n.100_101ins5", HgvsType::NonCoding)]
+    #[case("r.76a>c", HgvsType::RNA)]
+    #[case("r.1234_1235delinsuu", HgvsType::RNA)]
+    #[case("m.8993T>G", HgvsType::Mitochondrial)]
+    #[case("m.3243_3244del", HgvsType::Mitochondrial)]
+    fn test_valid_hgvs(#[case] s: &str, #[case] expected: HgvsType) {
+        assert!(is_valid_hgvs(s));
+        assert_eq!(Some(expected), classify_hgvs_type(s));
+    }
+
+    #[rstest]
+    #[case("")]
+    #[case("c.")]
+    #[case("8242G>T")]
+    #[case("NM_000138.5:c.8242G>T")]
+    #[case("c.8242G>")]
+    #[case("c.G8242T")]
+    #[case("c.8242g>t")]
+    #[case("n.76A>X")]
+    #[case("r.76A>C")]
+    #[case("r.76a>t")]
+    #[case("m.8993+1T>G")]
+    #[case("m.-5T>G")]
+    #[case("g.48411364C>A")]
+    #[case("p.Arg2748Ter")]
+    fn test_invalid_hgvs(#[case] s: &str) {
+        assert!(!is_valid_hgvs(s));
+        assert_eq!(None, classify_hgvs_type(s));
+    }
+}
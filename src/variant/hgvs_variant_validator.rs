@@ -12,14 +12,29 @@
 //!   from the API response.
 
 
-use std::{collections::HashMap, mem};
+use std::{collections::HashMap, mem, sync::LazyLock};
 
+use regex::Regex;
 use reqwest::blocking::get;
 use serde_json::Value;
 use crate::{dto::{hgvs_variant::HgvsVariant, variant_dto::VariantDto}, variant::variant_validation_handler::VariantValidatorHandler};
 
 const GENOME_ASSEMBLY_HG38: &str = "hg38";
 
+/// An intronic position at offset 1 or 2 from the exon (e.g., 156+1 or 157-2), but not 156+12
+static CANONICAL_SPLICE_SITE_RE: LazyLock<Regex> = LazyLock::new(|| {
+    Regex::new(r"\d[+-][12](?:\D|$)").unwrap()
+});
+
+/// True if the c. HGVS expression (e.g., c.156+1G>T or c.157-2A>G) affects one of the intronic positions +1, +2, -1, or -2,
+/// i.e., the canonical splice donor or acceptor site. Positions in the UTRs (e.g., c.-12A>G) are not intronic.
+pub(crate) fn is_canonical_splice_site(hgvs_c: &str) -> bool {
+    match hgvs_c.split_once("c.") {
+        Some((_, position)) => CANONICAL_SPLICE_SITE_RE.is_match(position),
+        None => false,
+    }
+}
+
 pub struct HgvsVariantValidator {
     genome_assembly: String,
     /// HGVS Variants that could be validated. The key is the original allele denomination (e.g., c.1234A>T), not the variantKey
@@ -51,6 +66,30 @@ fn get_variant_validator_url(
     api_url
 }
 
+/// Same as [`get_variant_validator_url`], but for several HGVS expressions on the same transcript,
+/// which Variant Validator accepts as a pipe-delimited list in a single request
+fn get_batch_variant_validator_url(
+    genome_assembly: &str,
+    transcript: &str,
+    hgvs_list: &[&str]
+) -> String
+{
+    let encoded_hgvs = hgvs_list
+        .iter()
+        .map(|hgvs| format!("{}:{}", transcript, hgvs))
+        .collect::<Vec<_>>()
+        .join("|")
+        .replace("+", "%2B")
+        .replace(":", "%3A")
+        .replace("|", "%7C");
+    format!(
+        "https://rest.variantvalidator.org/VariantValidator/variantvalidator/{genome}/{hgvs}/{transcript}?content-type=application%2Fjson",
+        genome = genome_assembly,
+        hgvs = encoded_hgvs,
+        transcript = transcript,
+    )
+}
+
 impl HgvsVariantValidator {
     
     pub fn hg38() -> Self {
@@ -82,7 +121,65 @@ impl HgvsVariantValidator {
             return Ok(());
         }
         let url = get_variant_validator_url(&self.genome_assembly, &vv_dto.transcript, hgvs);
-        let res = get(&url).map_err(|e| format!("Network error trying to reach Variant Validator: {e}"))?;
+        let response = Self::get_response(&url, hgvs)?;
+        self.extract_variant_validator_warnings(&response)?;
+
+        if let Some(flag) = response.get("flag") {
+            if flag != "gene_variant" {
+                return Err(format!("Expecting to get a gene_variant but got {}", flag));
+            }
+        }
+        let var = self.get_variant_data(&response)?;
+        let hgvs_v = self.hgvs_variant_from_data(var, vv_dto.variant_string.clone())?;
+        self.validated_hgvs.insert(hgvs_v.variant_key().clone(), hgvs_v);
+        Ok(())
+    }
+
+    /// Validate several HGVS expressions (all on the transcript of the first one) with a single request.
+    /// Variants that Variant Validator rejects are skipped with a warning; use [`Self::get_validated_hgvs`]
+    /// to retrieve the validated variants. Expressions that were validated before are not sent again.
+    pub fn validate_batch(&mut self, vv_dtos: &[VariantDto]) -> Result<(), String> {
+        let pending: Vec<&VariantDto> = vv_dtos
+            .iter()
+            .filter(|dto| !self.validated_hgvs.contains_key(
+                &HgvsVariant::generate_variant_key(&dto.variant_string, &dto.gene_symbol, &dto.transcript)))
+            .collect();
+        let Some(first) = pending.first() else {
+            return Ok(());
+        };
+        let transcript = first.transcript.clone();
+        if pending.iter().any(|dto| dto.transcript != transcript) {
+            return Err("All variants of a batch request must have the same transcript".to_string());
+        }
+        let hgvs_list: Vec<&str> = pending.iter().map(|dto| dto.variant_string.as_str()).collect();
+        let url = get_batch_variant_validator_url(&self.genome_assembly, &transcript, &hgvs_list);
+        let response = Self::get_response(&url, &hgvs_list.join(", "))?;
+        let blocks = response.as_object()
+            .ok_or_else(|| "Response is not a JSON object".to_string())?;
+        for (key, var) in blocks.iter().filter(|(k, _)| *k != "flag" && *k != "metadata") {
+            if key.starts_with("validation_warning") {
+                tracing::warn!(warnings = %var.get("validation_warnings").unwrap_or(&Value::Null), "Variant Validator rejected variant");
+                continue;
+            }
+            // the block for NM_000138.5:c.8242G>T has "submitted_variant": "NM_000138.5:c.8242G>T"
+            let submitted = var.get("submitted_variant").and_then(|v| v.as_str()).unwrap_or(key);
+            let Some(dto) = pending.iter().find(|dto| submitted == format!("{}:{}", dto.transcript, dto.variant_string)) else {
+                tracing::warn!(%submitted, "Unexpected variant in Variant Validator batch response");
+                continue;
+            };
+            match self.hgvs_variant_from_data(var, dto.variant_string.clone()) {
+                Ok(hgvs_v) => { self.validated_hgvs.insert(hgvs_v.variant_key(), hgvs_v); }
+                Err(e) => tracing::warn!("{submitted}: {e}"),
+            }
+        }
+        Ok(())
+    }
+
+    /// Send a request to Variant Validator and return the JSON response (`label` is used for messages)
+    fn get_response(url: &str, label: &str) -> Result<Value, String> {
+        tracing::debug!(%url, "Variant Validator request");
+        let res = get(url).map_err(|e| format!("Network error trying to reach Variant Validator: {e}"))?;
+        tracing::debug!(hgvs = %label, status = %res.status(), "Variant Validator response");
         if !res.status().is_success() {
             match res.status().as_u16() {
                 503 => return Err("The Variant Validator server is currently unreachable. Please try again later.".to_string()),
@@ -91,15 +188,11 @@ impl HgvsVariantValidator {
                 _ => return Err(format!("Variant Validator returned an error server code: {}", res.status())),
             }
         }
-        let response: Value = res.json().map_err(|e| format!("Could not parse JSON for {hgvs}: {e}"))?;
-        self.extract_variant_validator_warnings(&response)?;
+        res.json().map_err(|e| format!("Could not parse JSON for {label}: {e}"))
+    }
 
-        if let Some(flag) = response.get("flag") {
-            if flag != "gene_variant" {
-                return Err(format!("Expecting to get a gene_variant but got {}", flag));
-            }
-        }
-        let var = self.get_variant_data(&response)?;
+    /// Create an [`HgvsVariant`] from the data block of one variant in a Variant Validator response
+    fn hgvs_variant_from_data(&self, var: &Value, variant_string: String) -> Result<HgvsVariant, String> {
         let hgnc = self.get_hgnc(var)
             .ok_or_else(|| "could not extract hgnc from c_hgvs".to_string())?;
         let symbol = self.get_gene_symbol(var) 
@@ -119,18 +212,16 @@ impl HgvsVariantValidator {
         let g_hgvs = self.get_genomic_hgvs(assembly)?;
         let vcf_var = self.get_vcf_var(assembly)?;
         
-        let hgvs_v = HgvsVariant::new(
+        Ok(HgvsVariant::new(
             self.genome_assembly.clone(),
             vcf_var, 
             symbol,
             hgnc,
-            vv_dto.variant_string,
+            variant_string,
             p_hgvs,
             transcript.to_string(),
             g_hgvs,
-        );
-        self.validated_hgvs.insert(hgvs_v.variant_key().clone(), hgvs_v);
-        Ok(())
+        ))
     }
 
     pub fn get_validated_hgvs(&mut self, vv_dto: &VariantDto) 
@@ -167,6 +258,24 @@ mod tests {
     use super::*;
    
 
+    #[rstest]
+    #[case::donor_plus_1("c.156+1G>T", true)]
+    #[case::donor_plus_2("c.156+2T>C", true)]
+    #[case::acceptor_minus_1("c.157-1G>A", true)]
+    #[case::acceptor_minus_2("c.157-2A>G", true)]
+    #[case::with_transcript("NM_016145.4:c.156+1G>T", true)]
+    #[case::deletion_of_donor_site("c.156+1_156+5del", true)]
+    #[case::plus_3("c.156+3A>G", false)]
+    #[case::minus_3("c.157-3C>T", false)]
+    #[case::deep_intronic("c.156+12A>G", false)]
+    #[case::deep_intronic_minus("c.157-21T>C", false)]
+    #[case::five_prime_utr("c.-12A>G", false)]
+    #[case::exonic("c.8242G>T", false)]
+    #[case::not_coding("n.64G>A", false)]
+    fn test_is_canonical_splice_site(#[case] hgvs_c: &str, #[case] expected: bool) {
+        assert_eq!(expected, is_canonical_splice_site(hgvs_c));
+    }
+
     // NM_000138.5(FBN1):c.8230C>T (p.Gln2744Ter)
     #[fixture]
     fn vvdto() -> VariantDto {
@@ -203,6 +312,13 @@ mod tests {
         assert_eq!(expected, my_url);
     }
 
+    #[rstest]
+    fn test_batch_url() {
+        let expected = "https://rest.variantvalidator.org/VariantValidator/variantvalidator/hg38/NM_000138.5%3Ac.8230C>T%7CNM_000138.5%3Ac.1468%2B5G>A/NM_000138.5?content-type=application%2Fjson";
+        let my_url = get_batch_variant_validator_url("hg38", "NM_000138.5", &["c.8230C>T", "c.1468+5G>A"]);
+        assert_eq!(expected, my_url);
+    }
+
     #[rstest]
     #[ignore = "runs with API"]
     fn test_variant_validator(vvdto: VariantDto) {
@@ -33,7 +33,7 @@ pub static VALID_HG38_CHROMOSOMES: LazyLock<HashSet<String>> = LazyLock::new(||
 });
 
 fn is_valid_chromosome(chrom: &str) -> bool {
-    println!("is_valid_chromosome '''{}'''", chrom);
+    tracing::debug!("is_valid_chromosome '{}'", chrom);
     VALID_HG38_CHROMOSOMES.contains(chrom)
 }
 
@@ -95,8 +95,11 @@ impl IntergenicHgvsValidator {
             return Err(format!("{genomic_transcript} is not a valid hg38 chromosome. Malformed DTO: {vv_dto:?}"));
         }
         let url = get_variant_validator_url(&self.genome_assembly, hgvs);
-        let response: Value = get(&url)
-            .map_err(|e| format!("Could not map intergenic {hgvs}: {e}"))?
+        tracing::debug!(%url, "Variant Validator request");
+        let res = get(&url)
+            .map_err(|e| format!("Could not map intergenic {hgvs}: {e}"))?;
+        tracing::debug!(hgvs = %hgvs, status = %res.status(), "Variant Validator response");
+        let response: Value = res
             .json()
             .map_err(|e| format!("Could not parse JSON for {hgvs}: {e}"))?;
         let mut ig = self.from_json(response)?;
@@ -295,9 +298,7 @@ response
 
 
     #[rstest]
-    fn test_url(
-        vvdto: VariantDto
-    ){
+    fn test_url() {
         let intergenic = "NC_000019.10:g.12887294G>A";
         let expected = "https://rest.variantvalidator.org/VariantValidator/variantvalidator/hg38/NC_000019.10%3Ag.12887294G%3EA/select?content-type=application%2Fjson";
         let my_url = get_variant_validator_url("hg38",  intergenic);
@@ -1,15 +1,38 @@
 //! A module to work with HGVS (small) and structural variants.
 
 use std::collections::{HashMap, HashSet};
+use std::sync::LazyLock;
 
-use crate::{dto::{cohort_dto::{CohortData, GeneTranscriptData}, hgvs_variant::HgvsVariant, intergenic_variant::IntergenicHgvsVariant, structural_variant::StructuralVariant, variant_dto::VariantDto}, variant::variant_manager::VariantManager};
+use regex::Regex;
+
+use crate::{dto::{cohort_dto::{CohortData, GeneTranscriptData}, hgvs_variant::HgvsVariant, intergenic_variant::IntergenicHgvsVariant, structural_variant::{StructuralVariant, SvType}, variant_dto::VariantDto}, variant::variant_manager::VariantManager};
 mod acmg;
+#[cfg(feature = "gnomad")]
+mod gnomad;
+mod hgvs_syntax;
 mod structural_validator;
 pub mod variant_manager;
 pub(crate) mod hgvs_variant_validator;
 pub(crate) mod intergenic_hgvs_validator;
 pub(crate) mod variant_validation_handler;
 pub(crate) mod vcf_var;
+mod vep_annotation;
+
+pub use acmg::{AcmgClassifier, AcmgEvidence};
+pub use hgvs_syntax::{classify_hgvs_type, is_valid_hgvs, HgvsType};
+
+/// Premature stop codon, e.g., p.(Arg2748Ter), p.Arg2748*, or p.R2748*
+static NONSENSE_RE: LazyLock<Regex> = LazyLock::new(|| {
+    Regex::new(r"p\.\(?([A-Z][a-z]{2}|[A-Z])\d+(Ter|\*)\)?$").unwrap()
+});
+
+/// Reference to exons or introns in the label of a partial deletion, e.g., DEL Ex5-7, deletion of exon 3, or IVS2
+static PARTIAL_DELETION_RE: LazyLock<Regex> = LazyLock::new(|| {
+    Regex::new(r"(?i)\b(exons?|introns?|ivs\d*|ex\s?\d+)\b").unwrap()
+});
+
+/// VEP consequences of loss-of-function variants, used if the variant has no protein HGVS
+const LOSS_OF_FUNCTION_CONSEQUENCES: [&str; 4] = ["stop_gained", "frameshift_variant", "splice_donor_variant", "splice_acceptor_variant"];
 
 
 /// Validates all HGVS variants in the given set of allele strings.
@@ -18,7 +41,7 @@ pub(crate) mod vcf_var;
 /// and transcript. It attempts to parse and validate every allele in `all_alleles` that
 /// appears to be an HGVS variant (strings beginning with `c.` or `n.`).
 ///
-/// During validation, progress is printed to standard output in the form:
+/// During validation, progress is reported in the form:
 ///
 /// ```text
 /// {validated}/{total} variants validated
@@ -39,7 +62,7 @@ pub(crate) mod vcf_var;
 ///
 /// # Side Effects
 ///
-/// * Prints progress updates to `stdout`.
+/// * Reports progress as `tracing` events (level INFO).
 ///
 /// # Examples
 ///
@@ -61,9 +84,22 @@ pub fn validate_all_hgvs(
     transcript: &str,
     all_alleles: &HashSet<String>
 ) -> Result<HashMap<String, HgvsVariant>, String> {
+    validate_all_hgvs_with_progress(symbol, hgnc, transcript, all_alleles, |p,q|{
+        tracing::info!("{p}/{q} variants validated"); })
+}
+
+/// Same as [`validate_all_hgvs`], but progress is passed to `progress_cb` as (validated, total)
+/// after each allele, e.g., to update a progress bar.
+pub fn validate_all_hgvs_with_progress<F>(
+    symbol: &str, 
+    hgnc: &str, 
+    transcript: &str,
+    all_alleles: &HashSet<String>,
+    progress_cb: F
+) -> Result<HashMap<String, HgvsVariant>, String> 
+where F: FnMut(u32, u32) {
     let mut vmanager = VariantManager::new(symbol, hgnc, transcript);
-    vmanager.validate_all_hgvs(all_alleles, |p,q|{
-        println!("{p}/{q} variants validated"); })?;
+    vmanager.validate_all_hgvs(all_alleles, progress_cb)?;
     Ok(vmanager.hgvs_map())
 }
 
@@ -89,6 +125,95 @@ pub fn validate_hgvs_variant(
     vmanager.get_validated_hgvs(allele)
 }
 
+/// Enrich validated HGVS variants with annotations from the tab-separated output of
+/// Variant Effect Predictor (VEP, `--tab`).
+///
+/// Lines are matched to variants by the uploaded variation, which can be the transcript HGVS
+/// (e.g., `NM_000138.5:c.8242G>T`), the genomic HGVS, or the key of `variants`. If VEP reports several
+/// transcripts for a variant, the line for the transcript of the variant is used.
+///
+/// # Arguments
+///
+/// * `vep_output_path` – Path to the VEP output file.
+/// * `variants` – Validated variants; the consequence, impact, and gnomAD allele frequency are set in place.
+///
+/// # Returns
+///
+/// * `Ok(usize)` – The number of variants that were annotated.
+/// * `Err(String)` – If the file cannot be read or is not valid VEP output.
+pub fn annotate_from_vep(
+    vep_output_path: &str,
+    variants: &mut HashMap<String, HgvsVariant>
+) -> Result<usize, String> {
+    let vep_output = std::fs::read_to_string(vep_output_path)
+        .map_err(|e| format!("Could not read VEP output {vep_output_path}: {e}"))?;
+    let n_annotated = vep_annotation::annotate_from_vep_output(&vep_output, variants)?;
+    tracing::info!("{n_annotated}/{} variants annotated from VEP output", variants.len());
+    Ok(n_annotated)
+}
+
+/// Retrieves the allele frequency of a (hg38) variant from gnomAD v4.1.
+///
+/// The frequency is computed from the combined exome and genome allele counts.
+///
+/// # Returns
+///
+/// * `Ok(Some(f64))` – The allele frequency.
+/// * `Ok(None)` – If the variant is not in gnomAD.
+/// * `Err(String)` – If the variant is not on hg38 or the gnomAD API request fails.
+#[cfg(feature = "gnomad")]
+pub fn fetch_gnomad_frequency(hgvs: &HgvsVariant) -> Result<Option<f64>, String> {
+    let frequency = gnomad::fetch_gnomad_frequency_from(gnomad::GNOMAD_API_URL, hgvs)?;
+    Ok(frequency.map(|f| f.af))
+}
+
+/// Same as [`fetch_gnomad_frequency`], but the allele frequency and the popmax allele frequency
+/// are stored in `hgvs` (both are None if the variant is not in gnomAD).
+#[cfg(feature = "gnomad")]
+pub fn annotate_gnomad_frequency(hgvs: &mut HgvsVariant) -> Result<(), String> {
+    match gnomad::fetch_gnomad_frequency_from(gnomad::GNOMAD_API_URL, hgvs)? {
+        Some(frequency) => hgvs.set_gnomad_frequency(Some(frequency.af), frequency.popmax_af),
+        None => hgvs.set_gnomad_frequency(None, None),
+    }
+    Ok(())
+}
+
+/// Heuristic check whether a small variant is likely to cause loss of function (LoF).
+///
+/// True for nonsense variants (e.g., `p.(Arg2748Ter)` or `p.R2748*`), frameshift variants (`fs` in the
+/// protein HGVS), and variants of the canonical splice sites (`c.156+1G>T`, `c.157-2A>G`).
+/// If the protein HGVS is not available, the VEP consequence (if any) is used instead.
+/// Note that the heuristic does not consider nonsense-mediated decay (e.g., stop codons in the last exon).
+pub fn is_likely_loss_of_function(hgvs: &HgvsVariant) -> bool {
+    if hgvs_variant_validator::is_canonical_splice_site(hgvs.hgvs()) {
+        return true;
+    }
+    match hgvs.p_hgvs() {
+        Some(p_hgvs) => is_protein_truncating(&p_hgvs),
+        None => hgvs.consequence()
+            .map(|csq| csq.split(['&', ',']).any(|c| LOSS_OF_FUNCTION_CONSEQUENCES.contains(&c)))
+            .unwrap_or(false),
+    }
+}
+
+fn is_protein_truncating(p_hgvs: &str) -> bool {
+    // a stop codon as the reference residue means stop loss (e.g., p.Ter2872GlnextTer?)
+    if p_hgvs.contains("p.Ter") || p_hgvs.contains("p.(Ter") || p_hgvs.contains("p.*") || p_hgvs.contains("ext") {
+        return false;
+    }
+    p_hgvs.contains("fs") || NONSENSE_RE.is_match(p_hgvs)
+}
+
+/// Heuristic check whether a structural variant is likely to cause loss of function, i.e.,
+/// a deletion of the entire gene. Deletions whose label refers to exons or introns (e.g., `DEL Ex5-7`)
+/// are partial deletions and are not regarded as loss of function.
+pub fn is_likely_loss_of_function_sv(sv: &StructuralVariant) -> bool {
+    if sv.sv_type() != SvType::Del {
+        return false;
+    }
+    !PARTIAL_DELETION_RE.is_match(sv.label())
+}
+
 /* 
 /// Validates a structural variant in the given string.
 //
@@ -152,7 +277,7 @@ pub fn validate_intergenic_variant(
 ///
 /// # Side Effects
 ///
-/// * Prints progress updates to `stdout`.
+/// * Reports progress as `tracing` events (level INFO).
 pub fn validate_all_sv(
     symbol: &str, 
     hgnc: &str, 
@@ -161,7 +286,7 @@ pub fn validate_all_sv(
 ) -> Result<HashMap<String, StructuralVariant>, String> {
     let mut vmanager = VariantManager::new(symbol, hgnc, transcript);
     vmanager.validate_all_sv(all_alleles, |p,q|{
-        println!("{p}/{q} variants validated"); })?;
+        tracing::info!("{p}/{q} variants validated"); })?;
     Ok(vmanager.sv_map())
 }
 
@@ -218,3 +343,65 @@ pub fn analyze_variants(cohort_dto: CohortData) -> Result<Vec<VariantDto>, Strin
 }
 
 
+#[cfg(test)]
+mod test {
+    use super::*;
+    use rstest::rstest;
+    use crate::variant::vcf_var::VcfVar;
+
+    fn fbn1_variant(hgvs: &str, p_hgvs: Option<&str>) -> HgvsVariant {
+        HgvsVariant::new(
+            "hg38".to_string(),
+            VcfVar::new("chr15", 48411364, "C", "A"),
+            "FBN1".to_string(),
+            "HGNC:3603".to_string(),
+            hgvs.to_string(),
+            p_hgvs.map(str::to_string),
+            "NM_000138.5".to_string(),
+            "NC_000015.10:g.48411364C>A".to_string(),
+        )
+    }
+
+    #[rstest]
+    #[case::nonsense("c.8242G>T", Some("NP_000129.3:p.(Glu2748Ter)"), true)]
+    #[case::nonsense_star("c.8242G>T", Some("p.Glu2748*"), true)]
+    #[case::nonsense_one_letter("c.8242G>T", Some("p.E2748*"), true)]
+    #[case::frameshift("c.5027_5028del", Some("NP_000129.3:p.(Leu1676ProfsTer8)"), true)]
+    #[case::frameshift_short("c.5027_5028del", Some("p.L1676fs"), true)]
+    #[case::splice_donor("c.6163+1G>A", Some("p.?"), true)]
+    #[case::splice_acceptor("c.6164-2A>G", None, true)]
+    #[case::missense("c.4621C>T", Some("NP_000129.3:p.(Arg1541Cys)"), false)]
+    #[case::synonymous("c.4620C>T", Some("p.(Arg1540=)"), false)]
+    #[case::stop_loss("c.8616A>C", Some("p.(Ter2872TyrextTer?)"), false)]
+    #[case::inframe_deletion("c.1234_1236del", Some("p.(Glu412del)"), false)]
+    #[case::near_splice_site("c.6163+5G>A", Some("p.?"), false)]
+    #[case::deep_intronic("c.6163+12G>A", None, false)]
+    fn test_is_likely_loss_of_function(#[case] hgvs: &str, #[case] p_hgvs: Option<&str>, #[case] expected: bool) {
+        assert_eq!(expected, is_likely_loss_of_function(&fbn1_variant(hgvs, p_hgvs)));
+    }
+
+    #[rstest]
+    #[case("stop_gained", true)]
+    #[case("splice_donor_variant&intron_variant", true)]
+    #[case("missense_variant", false)]
+    fn test_loss_of_function_from_vep_consequence(#[case] consequence: &str, #[case] expected: bool) {
+        let mut var = fbn1_variant("c.8242G>T", None);
+        var.set_vep_annotation(Some(consequence.to_string()), None, None);
+        assert_eq!(expected, is_likely_loss_of_function(&var));
+    }
+
+    #[rstest]
+    #[case::whole_gene_deletion(SvType::Del, "deletion of FBN1", true)]
+    #[case::exon_deletion(SvType::Del, "DEL Ex5-7", false)]
+    #[case::exon_deletion_spelled_out(SvType::Del, "deletion of exons 2-4", false)]
+    #[case::intron_deletion(SvType::Del, "DEL IVS2", false)]
+    #[case::gene_symbol_with_ex(SvType::Del, "deletion of NEXN", true)]
+    #[case::gene_symbol_with_ivs(SvType::Del, "deletion of EXT1", true)]
+    #[case::duplication(SvType::Dup, "duplication of FBN1", false)]
+    #[case::inversion(SvType::Inv, "inversion", false)]
+    fn test_is_likely_loss_of_function_sv(#[case] sv_type: SvType, #[case] label: &str, #[case] expected: bool) {
+        let sv = StructuralVariant::new(label.to_string(), "FBN1".to_string(), "NM_000138.5".to_string(),
+            "HGNC:3603".to_string(), sv_type, "chr15".to_string()).unwrap();
+        assert_eq!(expected, is_likely_loss_of_function_sv(&sv));
+    }
+}
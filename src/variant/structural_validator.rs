@@ -113,8 +113,11 @@ impl StructuralValidator {
         let api_url = format!(
             "https://rest.variantvalidator.org/VariantValidator/tools/gene2transcripts/{gene}?content-type=application%2Fjson",
         );
-        let response: Value = get(&api_url)
-                .map_err(|e| format!("Could not map {gene}: {e}"))?
+        tracing::debug!(url = %api_url, "Variant Validator request");
+        let res = get(&api_url)
+                .map_err(|e| format!("Could not map {gene}: {e}"))?;
+        tracing::debug!(gene = %gene, status = %res.status(), "Variant Validator response");
+        let response: Value = res
                 .json()
                 .map_err(|e| format!("Could not retrieve JSON for {gene}: {e}"))?;
         let transcripts = response
@@ -283,12 +286,11 @@ mod tests {
         let symbol= "HNRNPC";
         let transcript = "NM_004500.4";
         let hgnc = "HGNC:5035";
-        let expected_chr = "14";
         let dto = VariantDto{
             variant_string: cell_contents.to_string(),
             variant_key: None,
             transcript: transcript.to_string(),
-            hgnc_id:  "HGNC:5035".to_string(),
+            hgnc_id:  hgnc.to_string(),
             gene_symbol: symbol.to_string(),
             variant_type: VariantType::Sv,
             is_validated: false,
@@ -305,7 +307,6 @@ mod tests {
         let transcript = "NM_021224.6)";
         let hgnc = "HGNC:21684";
         let symbol = "ZNF462";
-         let expected_chr = "9";
         let dto = VariantDto{
             variant_string: sv.to_string(),
             variant_key: None,
@@ -27,6 +27,9 @@ use crate::{variant::hgvs_variant_validator::HgvsVariantValidator};
 use crate::dto::structural_variant::StructuralVariant;
 
 
+/// Minimum number of HGVS variants for which [`VariantManager::validate_batch_hgvs`] sends a single batch request
+const DEFAULT_BATCH_THRESHOLD: usize = 5;
+
 
 pub struct VariantManager {
     hgvs_validator: HgvsVariantValidator,
@@ -40,6 +43,8 @@ pub struct VariantManager {
     transcript: String,
     /// Set of all allele strings (e.g., c.123A>T or DEL Ex 5)
     allele_set: HashSet<String>,   
+    /// Below this number of HGVS variants, we validate them one by one instead of with a batch request
+    batch_threshold: usize,
 }
 
 
@@ -61,9 +66,19 @@ impl VariantManager {
             hgnc_id: hgnc.to_string(),
             transcript: transcript.to_string(),
             allele_set: HashSet::new(),
+            batch_threshold: DEFAULT_BATCH_THRESHOLD,
         }
     }
 
+    /// Set the minimum number of HGVS variants for which [`Self::validate_batch_hgvs`] uses a batch request
+    pub fn set_batch_threshold(&mut self, n: usize) {
+        self.batch_threshold = n;
+    }
+
+    fn use_batch_request(&self, n_variants: usize) -> bool {
+        n_variants >= self.batch_threshold
+    }
+
     /// Construct a VariantManager object for a specific gene/HGNC/transcript
     pub fn from_gene_transcript_dto(dto: &GeneTranscriptData) -> Self {
         Self::new(&dto.gene_symbol, &dto.hgnc_id, &dto.transcript)
@@ -161,7 +176,7 @@ impl VariantManager {
                 if allele.starts_with("c.") || allele.starts_with("n.") {
                     match self.validate_hgvs(allele) {
                         Ok(_) => n_validated += 1,
-                        Err(e) => {eprintln!("{e}");} 
+                        Err(e) => {tracing::warn!("{e}");} 
                     }
                 } 
                 // sleep to try to avoid network issues; (start at 250 milliseconds, increase as much in each iteration)
@@ -179,6 +194,38 @@ impl VariantManager {
    
 
 
+    /// Validate the HGVS alleles (`c.` or `n.`; other alleles are ignored) with a single Variant Validator request.
+    /// If there are fewer alleles than the batch threshold, or the batch request fails, the alleles are
+    /// validated one by one.
+    ///
+    /// # Returns
+    ///
+    /// A map from the original allele string to its [`HgvsVariant`]; alleles that could not be validated are not included.
+    pub fn validate_batch_hgvs(&mut self, alleles: &[String]) -> Result<HashMap<String, HgvsVariant>, String> {
+        let hgvs_alleles: Vec<&String> = alleles
+            .iter()
+            .filter(|a| a.starts_with("c.") || a.starts_with("n."))
+            .collect();
+        if self.use_batch_request(hgvs_alleles.len()) {
+            let dtos: Vec<VariantDto> = hgvs_alleles
+                .iter()
+                .map(|a| VariantDto::hgvs_c(a, &self.transcript, &self.hgnc_id, &self.gene_symbol))
+                .collect();
+            if let Err(e) = self.hgvs_validator.validate_batch(&dtos) {
+                tracing::warn!("Batch request failed, validating variants individually: {e}");
+            }
+        }
+        // variants validated by the batch request are cached, so only the others are requested individually
+        let mut validated = HashMap::new();
+        for allele in hgvs_alleles {
+            match self.get_validated_hgvs(allele) {
+                Ok(hgvs) => { validated.insert(allele.clone(), hgvs); }
+                Err(e) => tracing::warn!("{allele}: {e}"),
+            }
+        }
+        Ok(validated)
+    }
+
     /// Completely analogous to validate_all_sv, see there for documentation
     fn validate_hgvs(&mut self, hgvs: &str) -> Result<(), String> {
         let vv_dto = VariantDto::hgvs_c(hgvs, &self.transcript, &self.hgnc_id, &self.gene_symbol);
@@ -205,7 +252,6 @@ impl VariantManager {
     pub(crate) fn get_validated_intergenic_hgvs(&mut self, hgvs: &str) 
     -> Result<IntergenicHgvsVariant, String> {
         let vv_dto = VariantDto::hgvs_g(hgvs, &self.hgnc_id, &self.gene_symbol);
-        println!("123 - vv_dto {:?}", &vv_dto);
         self.intergenic_validator.get_validated_g_hgvs(&vv_dto)
     }
 
@@ -381,6 +427,40 @@ impl VariantManager {
 mod tests {
     use crate::{dto::{structural_variant::StructuralVariant, variant_dto::VariantType}, variant::variant_manager::VariantManager};
 
+    #[test]
+    fn test_batch_threshold() {
+        let mut manager = VariantManager::new("FBN1", "HGNC:3603", "NM_000138.5");
+        assert!(!manager.use_batch_request(4));
+        assert!(manager.use_batch_request(5));
+        manager.set_batch_threshold(2);
+        assert!(!manager.use_batch_request(1));
+        assert!(manager.use_batch_request(2));
+    }
+
+    #[test]
+    fn test_batch_without_hgvs_alleles() {
+        // structural variants are ignored, so no request is made
+        let mut manager = VariantManager::new("FBN1", "HGNC:3603", "NM_000138.5");
+        let result = manager.validate_batch_hgvs(&["DEL Ex 5".to_string()]).unwrap();
+        assert!(result.is_empty());
+    }
+
+    #[test]
+    #[ignore = "API call"]
+    fn test_batch_agrees_with_individual_calls() {
+        let alleles: Vec<String> = ["c.8230C>T", "c.8242G>T", "c.1585C>T", "c.4987T>C", "c.7754T>C", "c.1468+5G>A"]
+            .iter()
+            .map(|a| a.to_string())
+            .collect();
+        let mut batch_manager = VariantManager::new("FBN1", "HGNC:3603", "NM_000138.5");
+        let batch = batch_manager.validate_batch_hgvs(&alleles).unwrap();
+        let mut single_manager = VariantManager::new("FBN1", "HGNC:3603", "NM_000138.5");
+        single_manager.set_batch_threshold(usize::MAX);
+        let single = single_manager.validate_batch_hgvs(&alleles).unwrap();
+        assert!(!batch.is_empty());
+        assert_eq!(single, batch);
+    }
+
     
 
     #[test]
@@ -403,7 +483,6 @@ mod tests {
         let symbol = "NBAS";
         let transcript = "NM_015909.4";
         let mut manager = VariantManager::new(symbol, hgnc, transcript);
-        let sv = manager.get_validated_structural_variant(label, VariantType::Del);
         let result = manager.validate_sv(label);
         assert!(result.is_err());
         let err_msg = result.unwrap_err();
@@ -14,7 +14,7 @@ pub trait VariantValidatorHandler {
 
         // 2. Look through all keys (e.g., intergenic_variant_1, validation_warning_1, etc.)
         if let Some(obj) = response.as_object() {
-            for (key, val) in obj {
+            for val in obj.values() {
                 // We are looking for any sub-object that contains the "validation_warnings" key
                 if let Some(warnings) = val.get("validation_warnings").and_then(|w| w.as_array()) {
                     // Grab the first message if it exists
@@ -0,0 +1,189 @@
+//! Import of Variant Effect Predictor (VEP) annotations
+//!
+//! We read the tab-separated output of VEP (`--tab`) for variants that were submitted as HGVS expressions
+//! (e.g., `NM_000138.5:c.8242G>T`) or as genomic HGVS (e.g., `NC_000015.10:g.48411364C>A`) and copy the
+//! consequence, the impact, and the gnomAD allele frequency to the matching [`HgvsVariant`] objects.
+//! The impact and the allele frequency are taken from their own columns if VEP was run with `--fields`,
+//! and otherwise from the key=value pairs of the `Extra` column.
+use std::collections::HashMap;
+
+use crate::dto::hgvs_variant::HgvsVariant;
+
+/// Names of the gnomAD allele frequency fields, in order of preference (exomes, genomes, legacy)
+const GNOMAD_AF_FIELDS: [&str; 3] = ["gnomADe_AF", "gnomADg_AF", "gnomAD_AF"];
+
+/// VEP uses "-" for missing values
+const VEP_MISSING: &str = "-";
+
+
+/// One line of the VEP output, i.e., the annotation of a variant with respect to one feature (transcript)
+#[derive(Clone, Debug, PartialEq)]
+struct VepRecord {
+    feature: String,
+    consequence: Option<String>,
+    impact: Option<String>,
+    gnomad_af: Option<f64>,
+}
+
+
+/// Annotate the variants with the records of the VEP output and return the number of annotated variants
+pub(crate) fn annotate_from_vep_output(
+    vep_output: &str,
+    variants: &mut HashMap<String, HgvsVariant>
+) -> Result<usize, String> {
+    let records = parse_vep_output(vep_output)?;
+    let mut n_annotated = 0;
+    for (key, var) in variants.iter_mut() {
+        let var_records = [var.to_minimal_string(), var.g_hgvs().to_string(), key.clone()]
+            .iter()
+            .find_map(|id| records.get(id));
+        let Some(var_records) = var_records else {
+            tracing::debug!(variant = %var, "No VEP annotation found");
+            continue;
+        };
+        // VEP reports one line per overlapping feature; we prefer the line for the transcript of the variant
+        let transcript = strip_version(var.transcript());
+        let record = var_records
+            .iter()
+            .find(|r| strip_version(&r.feature) == transcript)
+            .unwrap_or(&var_records[0]);
+        var.set_vep_annotation(record.consequence.clone(), record.impact.clone(), record.gnomad_af);
+        n_annotated += 1;
+    }
+    Ok(n_annotated)
+}
+
+
+/// Parse the VEP output into a map from the uploaded variation to the records of this variation (in input order)
+fn parse_vep_output(vep_output: &str) -> Result<HashMap<String, Vec<VepRecord>>, String> {
+    let mut header: Option<Vec<&str>> = None;
+    let mut records: HashMap<String, Vec<VepRecord>> = HashMap::new();
+    for (i, line) in vep_output.lines().enumerate() {
+        if line.starts_with("##") || line.trim().is_empty() {
+            continue;
+        }
+        if let Some(column_names) = line.strip_prefix('#') {
+            header = Some(column_names.split('\t').collect());
+            continue;
+        }
+        let column_names = header.as_ref()
+            .ok_or_else(|| "VEP output does not contain a #Uploaded_variation header line".to_string())?;
+        let fields: Vec<&str> = line.split('\t').collect();
+        if fields.len() != column_names.len() {
+            return Err(format!("VEP output line {} has {} fields but the header has {}",
+                i + 1, fields.len(), column_names.len()));
+        }
+        let mut values: HashMap<&str, &str> = column_names.iter().copied().zip(fields.iter().copied()).collect();
+        if let Some(extra) = values.get("Extra").copied() {
+            for (k, v) in extra.split(';').filter_map(|kv| kv.split_once('=')) {
+                values.entry(k).or_insert(v);
+            }
+        }
+        let uploaded_variation = values.get("Uploaded_variation")
+            .ok_or_else(|| "VEP output does not have an Uploaded_variation column".to_string())?;
+        let gnomad_af = match GNOMAD_AF_FIELDS.iter().find_map(|f| value(&values, f)) {
+            Some(af) => Some(af.parse::<f64>()
+                .ok()
+                .filter(|f| f.is_finite())
+                .ok_or_else(|| format!("Invalid gnomAD allele frequency '{af}' in VEP output line {}", i + 1))?),
+            None => None,
+        };
+        records.entry(uploaded_variation.to_string()).or_default().push(VepRecord {
+            feature: value(&values, "Feature").unwrap_or_default().to_string(),
+            consequence: value(&values, "Consequence").map(str::to_string),
+            impact: value(&values, "IMPACT").map(str::to_string),
+            gnomad_af,
+        });
+    }
+    Ok(records)
+}
+
+/// The value of a column or Extra field, or None if it is missing
+fn value<'a>(values: &HashMap<&str, &'a str>, key: &str) -> Option<&'a str> {
+    values.get(key).copied().filter(|v| !v.is_empty() && *v != VEP_MISSING)
+}
+
+/// NM_000138.5 -> NM_000138
+fn strip_version(accession: &str) -> &str {
+    accession.split('.').next().unwrap_or(accession)
+}
+
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use rstest::{fixture, rstest};
+
+    /// Minimal VEP output for two FBN1 variants; the first one is annotated for two transcripts
+    const VEP_OUTPUT: &str = "\
+## ENSEMBL VARIANT EFFECT PREDICTOR v110.0
+## Output produced at 2024-03-04 10:11:12
+#Uploaded_variation\tLocation\tAllele\tGene\tFeature\tFeature_type\tConsequence\tExtra
+NM_000138.5:c.8242G>T\t15:48411364\tA\tENSG00000166147\tNM_001406716.1\tTranscript\tstop_gained\tIMPACT=HIGH;STRAND=-1
+NM_000138.5:c.8242G>T\t15:48411364\tA\tENSG00000166147\tNM_000138.5\tTranscript\tstop_gained\tIMPACT=HIGH;STRAND=-1;gnomADe_AF=3.98e-06
+NM_000138.5:c.4621C>T\t15:48460296\tA\tENSG00000166147\tNM_000138.5\tTranscript\tmissense_variant\tIMPACT=MODERATE;gnomADe_AF=-
+";
+
+    fn variant(hgvs: &str, g_hgvs: &str) -> HgvsVariant {
+        HgvsVariant::new_from_parts(
+            "hg38".to_string(),
+            "chr15".to_string(),
+            48411364,
+            "C".to_string(),
+            "A".to_string(),
+            "FBN1".to_string(),
+            "HGNC:3603".to_string(),
+            hgvs.to_string(),
+            "NM_000138.5".to_string(),
+            g_hgvs.to_string(),
+        )
+    }
+
+    #[fixture]
+    fn variants() -> HashMap<String, HgvsVariant> {
+        [
+            variant("c.8242G>T", "NC_000015.10:g.48411364C>A"),
+            variant("c.4621C>T", "NC_000015.10:g.48460296G>A"),
+            variant("c.1A>G", "NC_000015.10:g.48936966T>C"),
+        ]
+        .into_iter()
+        .map(|v| (v.variant_key(), v))
+        .collect()
+    }
+
+    #[rstest]
+    fn test_annotate(mut variants: HashMap<String, HgvsVariant>) {
+        let n_annotated = annotate_from_vep_output(VEP_OUTPUT, &mut variants).unwrap();
+        assert_eq!(2, n_annotated);
+        let stop = &variants["c8242GtoT_FBN1_NM_000138v5"];
+        assert_eq!(Some("stop_gained"), stop.consequence());
+        assert_eq!(Some("HIGH"), stop.impact());
+        assert_eq!(Some(3.98e-06), stop.gnomad_af());
+        let missense = &variants["c4621CtoT_FBN1_NM_000138v5"];
+        assert_eq!(Some("missense_variant"), missense.consequence());
+        assert_eq!(Some("MODERATE"), missense.impact());
+        assert_eq!(None, missense.gnomad_af());
+        let start_lost = &variants["c1AtoG_FBN1_NM_000138v5"];
+        assert_eq!(None, start_lost.consequence());
+    }
+
+    #[rstest]
+    fn test_annotate_by_genomic_hgvs_and_columns(mut variants: HashMap<String, HgvsVariant>) {
+        let vep_output = "#Uploaded_variation\tFeature\tConsequence\tIMPACT\tgnomADg_AF\n\
+            NC_000015.10:g.48460296G>A\tNM_000138.5\tmissense_variant\tMODERATE\t0.0002\n";
+        assert_eq!(1, annotate_from_vep_output(vep_output, &mut variants).unwrap());
+        let missense = &variants["c4621CtoT_FBN1_NM_000138v5"];
+        assert_eq!(Some("MODERATE"), missense.impact());
+        assert_eq!(Some(0.0002), missense.gnomad_af());
+    }
+
+    #[rstest]
+    #[case::no_header("NM_000138.5:c.8242G>T\tNM_000138.5\tstop_gained\n")]
+    #[case::wrong_field_count("#Uploaded_variation\tFeature\tConsequence\nNM_000138.5:c.8242G>T\tstop_gained\n")]
+    #[case::invalid_frequency("#Uploaded_variation\tgnomADe_AF\nNM_000138.5:c.8242G>T\tabc\n")]
+    #[case::nan_frequency("#Uploaded_variation\tgnomADe_AF\nNM_000138.5:c.8242G>T\tNaN\n")]
+    #[case::infinite_frequency("#Uploaded_variation\tgnomADe_AF\nNM_000138.5:c.8242G>T\tinf\n")]
+    fn test_invalid_vep_output(mut variants: HashMap<String, HgvsVariant>, #[case] vep_output: &str) {
+        assert!(annotate_from_vep_output(vep_output, &mut variants).is_err());
+    }
+}
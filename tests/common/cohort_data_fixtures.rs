@@ -184,7 +184,8 @@ pub fn cohort_data_1(
         phetools_schema_version: "0.3".to_string(),
         hpo_version: "2024-01-01".to_string(),
         cohort_acronym: Some("COHORT1".to_string()),
-        curation_history: vec![]
+        curation_history: vec![],
+        trios: vec![],
     }
 }
 
@@ -209,7 +210,49 @@ pub fn cohort_data_2(
         phetools_schema_version: "0.3".to_string(),
         hpo_version: "2024-01-01".to_string(),
         cohort_acronym: Some("COHORT2".to_string()),
-        curation_history: vec![]
+        curation_history: vec![],
+        trios: vec![],
     }
 }
 
+
+// Fixture for a family: an affected proband, his unaffected father, and his affected mother
+#[fixture]
+pub fn family_cohort(
+    disease_data: DiseaseData,
+    hpo_term_pool: Vec<HpoTermDuplet>,
+) -> CohortData {
+    let member = |individual_id: &str, sex: &str, hpo_data: Vec<CellValue>| RowData {
+        individual_data: IndividualData::new(
+            "PMID:55555555",
+            "Test Family Study",
+            individual_id,
+            "Test family",
+            "na",
+            "na",
+            "no",
+            sex,
+        ),
+        disease_id_list: vec!["OMIM:157000".to_string()],
+        allele_count_map: HashMap::new(),
+        hpo_data,
+    };
+    CohortData {
+        cohort_type: CohortType::Mendelian,
+        disease_list: vec![disease_data],
+        hpo_headers: hpo_term_pool[0..3].to_vec(),
+        rows: vec![
+            member("Proband", "M", vec![CellValue::observed(), CellValue::observed(), CellValue::na()]),
+            member("Father", "M", vec![CellValue::excluded(), CellValue::excluded(), CellValue::na()]),
+            member("Mother", "F", vec![CellValue::observed(), CellValue::excluded(), CellValue::na()]),
+        ],
+        hgvs_variants: HashMap::new(),
+        structural_variants: HashMap::new(),
+        intergenic_variants: HashMap::new(),
+        phetools_schema_version: "0.3".to_string(),
+        hpo_version: "2024-01-01".to_string(),
+        cohort_acronym: Some("FAMILY".to_string()),
+        curation_history: vec![],
+        trios: vec![],
+    }
+}
@@ -4,7 +4,11 @@
 
 pub mod cohort_data_fixtures;
 pub mod hpo_fixture;
+pub mod log_buffer;
 pub mod matrix_fixtures;
+pub mod store_fixtures;
+#[path = "../../src/test_utils/temp_store.rs"]
+pub mod temp_store;
 
 
 
@@ -0,0 +1,28 @@
+/// Helpers to create a repository ("store") directory for tests:
+/// one subdirectory per cohort with the cohort file and a `phenopackets` directory
+/// (see also [`super::temp_store`] for the store directory itself)
+
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+
+use ga4ghphetools::dto::cohort_dto::CohortData;
+use ontolius::ontology::csr::FullCsrOntology;
+
+
+/// Write the cohort to `<root>/<cohort_name>/<file_name>` and its phenopackets to `<root>/<cohort_name>/phenopackets`
+pub fn write_cohort_dir(
+    root: &Path,
+    cohort_name: &str,
+    file_name: &str,
+    cohort: &CohortData,
+    hpo: Arc<FullCsrOntology>,
+) -> PathBuf {
+    let cohort_dir = root.join(cohort_name);
+    let ppkt_dir = cohort_dir.join("phenopackets");
+    std::fs::create_dir_all(&ppkt_dir).unwrap();
+    let json = serde_json::to_string_pretty(cohort).unwrap();
+    std::fs::write(cohort_dir.join(file_name), json).unwrap();
+    ga4ghphetools::ppkt::write_phenopackets(
+        cohort.clone(), ppkt_dir, "ORCID:0000-0002-0736-9199".to_string(), hpo, true).unwrap();
+    cohort_dir
+}
@@ -0,0 +1,92 @@
+mod common;
+
+use std::sync::Arc;
+
+use ga4ghphetools::dto::cohort_dto::CohortData;
+use ontolius::ontology::csr::FullCsrOntology;
+use rstest::rstest;
+use common::hpo_fixture::hpo;
+
+use crate::common::cohort_data_fixtures::{cohort_data_1, cohort_data_2, family_cohort};
+use crate::common::temp_store::{temp_store, write_cohort_file};
+
+const ORCID: &str = "ORCID:0000-0002-0736-9199";
+
+/// Two cohorts of OMIM:157000 and one cohort of OMIM:100050
+fn write_store(name: &str, cohort_data_1: &CohortData, cohort_data_2: &CohortData, mut family_cohort: CohortData) -> std::path::PathBuf {
+    let store = temp_store(name);
+    write_cohort_file(&store, "COHORT1", cohort_data_1);
+    write_cohort_file(&store, "COHORT2", cohort_data_2);
+    family_cohort.disease_list[0].disease_id = "OMIM:100050".to_string();
+    for row in family_cohort.rows.iter_mut() {
+        row.disease_id_list = vec!["OMIM:100050".to_string()];
+    }
+    write_cohort_file(&store, "FAMILY", &family_cohort);
+    store
+}
+
+fn data_lines(contents: &str) -> Vec<&str> {
+    contents.lines().filter(|line| !line.starts_with('#')).collect()
+}
+
+
+#[rstest]
+fn test_write_hpoa_for_all_cohorts(
+    hpo: Arc<FullCsrOntology>,
+    cohort_data_1: CohortData,
+    cohort_data_2: CohortData,
+    family_cohort: CohortData,
+) {
+    let store = write_store("phetools_test_batch_hpoa_store", &cohort_data_1, &cohort_data_2, family_cohort);
+    let output_dir = store.join("hpoa");
+    let mut totals = vec![];
+    let n_files = ga4ghphetools::hpoa::write_hpoa_for_all_cohorts_with_progress(
+        &store, hpo, ORCID, &output_dir, |_, total| totals.push(total));
+    let omim_157000 = std::fs::read_to_string(output_dir.join("OMIM-157000.tab"));
+    let omim_100050 = std::fs::read_to_string(output_dir.join("OMIM-100050.tab"));
+    std::fs::remove_dir_all(&store).unwrap();
+    assert_eq!(2, n_files.unwrap());
+    assert_eq!(vec![3, 3, 3], totals);
+    let (omim_157000, omim_100050) = (omim_157000.unwrap(), omim_100050.unwrap());
+    assert!(omim_157000.starts_with("#diseaseID"));
+    assert!(data_lines(&omim_157000).iter().all(|line| line.starts_with("OMIM:157000\t")));
+    // the annotations of both cohorts (four publications)
+    for pmid in ["PMID:11111111", "PMID:22222222", "PMID:33333333", "PMID:44444444"] {
+        assert!(omim_157000.contains(pmid), "{pmid} missing");
+    }
+    assert!(data_lines(&omim_100050).iter().all(|line| line.starts_with("OMIM:100050\t")));
+}
+
+#[rstest]
+fn test_write_merged_hpoa_for_all_cohorts(
+    hpo: Arc<FullCsrOntology>,
+    cohort_data_1: CohortData,
+    cohort_data_2: CohortData,
+    family_cohort: CohortData,
+) {
+    let store = write_store("phetools_test_batch_hpoa_merged_store", &cohort_data_1, &cohort_data_2, family_cohort);
+    let output_path = store.join("merged.tab");
+    let n_rows = ga4ghphetools::hpoa::write_merged_hpoa_for_all_cohorts(&store, hpo, ORCID, &output_path);
+    let contents = std::fs::read_to_string(&output_path);
+    std::fs::remove_dir_all(&store).unwrap();
+    let (n_rows, contents) = (n_rows.unwrap(), contents.unwrap());
+    let lines = data_lines(&contents);
+    assert_eq!(n_rows, lines.len());
+    assert_eq!(1, contents.lines().filter(|line| line.starts_with("#diseaseID")).count());
+    // sorted by disease ID
+    let disease_ids: Vec<&str> = lines.iter().map(|line| line.split('\t').next().unwrap()).collect();
+    let mut sorted = disease_ids.clone();
+    sorted.sort();
+    assert_eq!(sorted, disease_ids);
+    assert_eq!("OMIM:100050", disease_ids[0]);
+    assert_eq!("OMIM:157000", disease_ids[n_rows - 1]);
+}
+
+#[rstest]
+fn test_invalid_biocurator(hpo: Arc<FullCsrOntology>, family_cohort: CohortData) {
+    let store = temp_store("phetools_test_batch_hpoa_orcid_store");
+    write_cohort_file(&store, "FAMILY", &family_cohort);
+    let result = ga4ghphetools::hpoa::write_hpoa_for_all_cohorts(&store, hpo, "0000", &store.join("hpoa"));
+    std::fs::remove_dir_all(&store).unwrap();
+    assert!(result.is_err());
+}
@@ -16,8 +16,10 @@ use ontolius::ontology::csr::FullCsrOntology;
 use ontolius::ontology::MetadataAware;
 use rstest::rstest;
 use common::hpo_fixture::hpo;
+use common::log_buffer::LogBuffer;
 
 
+use crate::common::cohort_data_fixtures::cohort_data_1;
 use crate::common::matrix_fixtures::acvr1_cohort;
 use crate::common::matrix_fixtures::acvr1_disease_data;
 use crate::common::matrix_fixtures::cell_values_two_terms;
@@ -109,3 +111,39 @@ pub fn acvr1_cohort_with_repeated_term(
 
 
 
+
+
+/// Modifiers (here, Severe HP:0012828) entered for a new individual must be kept in the new row
+#[rstest]
+fn test_add_new_row_with_modifier(
+    acvr1_cohort: CohortData,
+    mut individual_data: IndividualData,
+    hpo: Arc<FullCsrOntology>
+) {
+    individual_data.individual_id = "new case".to_string();
+    let long_hallux = HpoTermData::new(HpoTermDuplet::new("Long hallux", "HP:0001847"), CellValue::observed())
+        .unwrap()
+        .with_modifier("HP:0012828".to_string());
+    let cohort = ga4ghphetools::factory::add_new_row_to_cohort(hpo, individual_data, vec![long_hallux], vec![], acvr1_cohort).unwrap();
+    assert_eq!(2, cohort.rows.len());
+    let idx = cohort.hpo_headers.iter().position(|h| h.hpo_id() == "HP:0001847").unwrap();
+    let new_row = cohort.get_individual_by_id("new case").unwrap();
+    assert!(new_row.hpo_data[idx].is_observed());
+    assert_eq!(vec!["HP:0012828".to_string()], new_row.hpo_data[idx].modifiers);
+    let old_row = cohort.get_individual_by_id("current case").unwrap();
+    assert!(!old_row.hpo_data[idx].is_ascertained());
+}
+
+
+/// The outcome of each Q/C rule and the HPO lookups are logged at debug level only (CLI --verbose)
+#[rstest]
+fn test_qc_verbose_logging(hpo: Arc<FullCsrOntology>, cohort_data_1: CohortData) {
+    let (_, quiet) = LogBuffer::capture(tracing::Level::INFO, || ga4ghphetools::factory::qc_assessment(hpo.clone(), &cohort_data_1));
+    let (_, verbose) = LogBuffer::capture(tracing::Level::DEBUG, || ga4ghphetools::factory::qc_assessment(hpo, &cohort_data_1));
+    assert!(verbose.len() > quiet.len());
+    assert!(verbose.contains("rule=\"row_length\""));
+    assert!(verbose.contains("Q/C check passed"));
+    // the fixture has no curation history
+    assert!(verbose.contains("Q/C check failed"));
+    assert!(verbose.contains("HPO lookup"));
+}
@@ -0,0 +1,88 @@
+mod common;
+
+use std::{path::PathBuf, str::FromStr, sync::Arc};
+
+use ontolius::{ontology::csr::FullCsrOntology, TermId};
+use rstest::rstest;
+use common::hpo_fixture::hpo;
+
+/// Annotations of Marfan syndrome, Loeys-Dietz syndrome 1, and Ehlers-Danlos syndrome (classic type) in the release format
+const PHENOTYPE_HPOA: &str = "#description: \"HPO annotations for rare diseases\"\n\
+#version: 2025-03-03\n\
+database_id\tdisease_name\tqualifier\thpo_id\treference\tevidence\tonset\tfrequency\tsex\tmodifier\taspect\tbiocuration\n\
+OMIM:154700\tMarfan syndrome\t\tHP:0001166\tOMIM:154700\tTAS\t\t\t\t\tP\tHPO:curator[2009-02-17]\n\
+OMIM:154700\tMarfan syndrome\t\tHP:0001083\tOMIM:154700\tTAS\t\t\t\t\tP\tHPO:curator[2009-02-17]\n\
+OMIM:154700\tMarfan syndrome\t\tHP:0000006\tOMIM:154700\tTAS\t\t\t\t\tI\tHPO:curator[2009-02-17]\n\
+OMIM:154700\tMarfan syndrome\tNOT\tHP:0001631\tOMIM:154700\tTAS\t\t\t\t\tP\tHPO:curator[2009-02-17]\n\
+OMIM:609192\tLoeys-Dietz syndrome 1\t\tHP:0001166\tOMIM:609192\tTAS\t\t\t\t\tP\tHPO:curator[2009-02-17]\n\
+OMIM:609192\tLoeys-Dietz syndrome 1\t\tHP:0000316\tOMIM:609192\tTAS\t\t\t\t\tP\tHPO:curator[2009-02-17]\n\
+OMIM:130000\tEhlers-Danlos syndrome, classic type, 1\t\tHP:0100807\tOMIM:130000\tTAS\t\t\t\t\tP\tHPO:curator[2009-02-17]\n";
+
+/// Write the annotations to a file of the test `name`; the caller removes it after use
+fn write_hpoa(name: &str) -> PathBuf {
+    let path = std::env::temp_dir().join(format!("phetools_disease_terms_{}_{}.hpoa", name, std::process::id()));
+    std::fs::write(&path, PHENOTYPE_HPOA).unwrap();
+    path
+}
+
+fn tid(id: &str) -> TermId {
+    TermId::from_str(id).unwrap()
+}
+
+
+#[rstest]
+fn test_diseases_for_hpo_term(hpo: Arc<FullCsrOntology>) {
+    let hpoa_path = write_hpoa("term");
+    let diseases = ga4ghphetools::hpo::get_diseases_for_hpo_term(hpo, &tid("HP:0001166"), &hpoa_path, false);
+    std::fs::remove_file(&hpoa_path).unwrap();
+    let diseases = diseases.unwrap();
+    let ids: Vec<&str> = diseases.iter().map(|d| d.disease_id.as_str()).collect();
+    assert_eq!(vec!["OMIM:154700", "OMIM:609192"], ids);
+    assert_eq!("Marfan syndrome", diseases[0].disease_label);
+}
+
+#[rstest]
+fn test_diseases_for_hpo_term_with_ancestors(hpo: Arc<FullCsrOntology>) {
+    let hpoa_path = write_hpoa("ancestors");
+    // EDS is annotated with Long fingers, a parent of Arachnodactyly
+    let diseases = ga4ghphetools::hpo::get_diseases_for_hpo_term(hpo, &tid("HP:0001166"), &hpoa_path, true);
+    std::fs::remove_file(&hpoa_path).unwrap();
+    let diseases = diseases.unwrap();
+    let ids: Vec<&str> = diseases.iter().map(|d| d.disease_id.as_str()).collect();
+    assert_eq!(vec!["OMIM:154700", "OMIM:609192", "OMIM:130000"], ids);
+}
+
+#[rstest]
+fn test_negated_annotation_is_not_an_association(hpo: Arc<FullCsrOntology>) {
+    let hpoa_path = write_hpoa("negated");
+    let diseases = ga4ghphetools::hpo::get_diseases_for_hpo_term(hpo, &tid("HP:0001631"), &hpoa_path, false);
+    std::fs::remove_file(&hpoa_path).unwrap();
+    let diseases = diseases.unwrap();
+    assert!(diseases.is_empty());
+}
+
+#[rstest]
+fn test_hpo_terms_for_disease(hpo: Arc<FullCsrOntology>) {
+    let hpoa_path = write_hpoa("disease");
+    let terms = ga4ghphetools::hpo::get_hpo_terms_for_disease(hpo, "OMIM:154700", &hpoa_path);
+    std::fs::remove_file(&hpoa_path).unwrap();
+    let terms = terms.unwrap();
+    let ids: Vec<&str> = terms.iter().map(|t| t.hpo_id()).collect();
+    // neither the mode of inheritance nor the excluded Atrial septal defect
+    assert_eq!(vec!["HP:0001166", "HP:0001083"], ids);
+    assert_eq!("Arachnodactyly", terms[0].hpo_label());
+}
+
+#[rstest]
+fn test_unknown_disease(hpo: Arc<FullCsrOntology>) {
+    let hpoa_path = write_hpoa("unknown_disease");
+    let result = ga4ghphetools::hpo::get_hpo_terms_for_disease(hpo, "OMIM:135100", &hpoa_path);
+    std::fs::remove_file(&hpoa_path).unwrap();
+    assert!(result.is_err());
+}
+
+#[rstest]
+fn test_missing_hpoa_file(hpo: Arc<FullCsrOntology>) {
+    let path = std::env::temp_dir().join("phetools_no_such_file.hpoa");
+    assert!(ga4ghphetools::hpo::get_diseases_for_hpo_term(hpo, &tid("HP:0001166"), &path, false).is_err());
+}
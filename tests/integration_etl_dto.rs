@@ -11,14 +11,22 @@ use ga4ghphetools::dto::etl_dto::EtlCellValue;
 use ga4ghphetools::dto::etl_dto::EtlColumnHeader;
 use ga4ghphetools::dto::etl_dto::EtlColumnType;
 use ga4ghphetools::dto::etl_dto::EtlDto;
+use ga4ghphetools::dto::etl_dto::EtlTransform;
 use ga4ghphetools::dto::hgvs_variant::HgvsVariant;
 use ga4ghphetools::dto::hpo_term_dto::CellValueInner;
 use ga4ghphetools::dto::hpo_term_dto::HpoTermData;
 use ga4ghphetools::dto::hpo_term_dto::HpoTermDuplet;
+use ga4ghphetools::dto::hpo_term_dto::LateralityCode;
+use ga4ghphetools::dto::hpo_term_dto::SeverityCode;
 use ga4ghphetools::dto::cohort_dto::DiseaseData;
 use ga4ghphetools::dto::etl_dto::EtlCellStatus;
+use ga4ghphetools::error::PheToolsError;
+use ga4ghphetools::etl::FailureMode;
 use common::hpo_fixture::hpo;
+use common::log_buffer::LogBuffer;
 use ontolius::ontology::csr::FullCsrOntology;
+use phenopackets::schema::v2::core::measurement::MeasurementValue;
+use phenopackets::schema::v2::core::{time_element, value, Value};
 use rstest::fixture;
 use rstest::rstest;
 
@@ -44,6 +52,7 @@ fn patient_id_column_valid(
             current: None,
             column_type: EtlColumnType::PatientId,
             hpo_terms: None,
+            gene_index: None,
         },
         values: vec![family_id_1, family_id_2 ]
     }
@@ -60,6 +69,7 @@ fn variant_column_valid() -> ColumnDto {
             current: Some("Mutation (NM_016145.4)-validated".to_string()),
             column_type: EtlColumnType::Variant,
             hpo_terms: None,
+            gene_index: None,
         },
         values: vec![ v1,v2 ]
     }
@@ -76,6 +86,7 @@ fn age_eval_column_valid() -> ColumnDto {
             current: None,
             column_type: EtlColumnType::AgeAtLastEncounter,
             hpo_terms: None,
+            gene_index: None,
         },
         values: vec![a1, a2 ]
     }
@@ -92,6 +103,7 @@ fn sex_column_valid() -> ColumnDto {
             current: None,
             column_type: EtlColumnType::Sex,
             hpo_terms: None,
+            gene_index: None,
         },
         values: vec![m,f ]
     }
@@ -112,6 +124,7 @@ fn delayed_sit_column_valid() -> ColumnDto {
             hpo_terms: Some(vec![
                 HpoTermDuplet::new("Delayed ability to sit","HP:0025336" )
             ]),
+            gene_index: None,
         },
         values: vec![observed, na  ]
     }
@@ -131,6 +144,7 @@ fn delayed_gross_motor() -> ColumnDto {
             hpo_terms: Some(vec![
                 HpoTermDuplet::new("Delayed gross motor development","HP:0002194" )
             ]),
+            gene_index: None,
         },
         values: vec![observed, na ]
     }
@@ -150,6 +164,7 @@ fn gdd_column_valid() -> ColumnDto {
             hpo_terms: Some(vec![
                 HpoTermDuplet::new("Global developmental delay", "HP:0001263" )
             ]),
+            gene_index: None,
         },
         values: vec![observed.clone(), observed   ]
     }
@@ -170,6 +185,7 @@ fn hypertelorism_column_valid() -> ColumnDto {
                 HpoTermDuplet::new("Hypotelorism", "HP:0000601"),
                 HpoTermDuplet::new("Hypertelorism", "HP:0000316"),
             ]),
+            gene_index: None,
         },
         values: vec![observed, o2 ],
     }
@@ -186,6 +202,7 @@ fn column_strabismus() -> ColumnDto {
             current: Some("Strabismus - HP:0000486".to_string()),
             column_type: EtlColumnType::SingleHpoTerm,
             hpo_terms: Some(vec![HpoTermDuplet::new("Strabismus", "HP:0000486")]),
+            gene_index: None,
         },
         values: vec![observed, excluded],
     }
@@ -202,6 +219,7 @@ fn column_ptosis() -> ColumnDto {
             current: Some("Ptosis - HP:0000508".to_string()),
             column_type: EtlColumnType::SingleHpoTerm,
             hpo_terms: Some(vec![HpoTermDuplet::new("Ptosis", "HP:0000508")]),
+            gene_index: None,
         },
         values: vec![observed, excluded],
     }
@@ -218,6 +236,7 @@ fn exclude_abn_eye() -> ColumnDto {
             current: Some("Abnormality of the eye - HP:0000478".to_string()),
             column_type: EtlColumnType::SingleHpoTerm,
             hpo_terms: Some(vec![HpoTermDuplet::new("Abnormality of the eye", "HP:0000478")]),
+            gene_index: None,
         },
         values: vec![excluded.clone(), excluded],
     }
@@ -443,7 +462,7 @@ fn test_valid_etl(
     etl_dto_valid: EtlDto,
     test_orcid: CurationEvent,
     hpo: Arc<FullCsrOntology>) {
-    let result = ga4ghphetools::etl::get_cohort_data_from_etl_dto(hpo.clone(), etl_dto_valid);
+    let result = ga4ghphetools::etl::get_cohort_data_from_etl_dto(hpo.clone(), etl_dto_valid, FailureMode::FailFast);
     assert!(result.is_ok());
     let mut cohort_dto = result.unwrap();
     cohort_dto.curation_history.push(test_orcid);
@@ -459,10 +478,10 @@ fn test_invalid_column_type_raw(
 ) {
     let table = make_table(vec![column_ptosis_invalid_raw]);
     let etl = make_etl(table, disease_valid);
-    let result = ga4ghphetools::etl::get_cohort_data_from_etl_dto(hpo, etl);
+    let result = ga4ghphetools::etl::get_cohort_data_from_etl_dto(hpo, etl, FailureMode::FailFast);
     assert!(result.is_err());
     let err = result.unwrap_err();
-    assert_eq!(err, "'Ptosis' column type not set (Raw)");
+    assert_eq!(err.to_string(), "Ptosis: column type not set (Raw)");
 }
 
 #[rstest]
@@ -473,10 +492,10 @@ fn test_empty_columns(
     let table: Vec<ColumnDto> = vec![];
     let table_dto = ColumnTableDto{ file_name: "/Users/fakename.xlsx".to_string(), columns: table };
     let etl = make_etl(table_dto, disease_valid);
-    let result = ga4ghphetools::etl::get_cohort_data_from_etl_dto(hpo, etl);
+    let result = ga4ghphetools::etl::get_cohort_data_from_etl_dto(hpo, etl, FailureMode::FailFast);
     assert!(result.is_err());
     let err = result.unwrap_err();
-    assert_eq!(err, "EtlDto table with no columns");
+    assert_eq!(err.to_string(), "EtlDto table with no columns");
 }
 
 
@@ -493,10 +512,10 @@ fn test_column_type_with_leading_whitespace(
     } else {
         panic!("No first value found");
     }
-    let result = ga4ghphetools::etl::get_cohort_data_from_etl_dto(hpo, etl_dto_valid_clone);
+    let result = ga4ghphetools::etl::get_cohort_data_from_etl_dto(hpo, etl_dto_valid_clone, FailureMode::FailFast);
     assert!(result.is_err());
     let err = result.unwrap_err();
-    assert_eq!(err, "Clinical Features: leading whitespace - ' Family 1 (Turkish) BAB11420'");
+    assert_eq!(err.to_string(), "Clinical Features (row 0): leading whitespace - ' Family 1 (Turkish) BAB11420'");
 }
 
 #[rstest]
@@ -511,10 +530,10 @@ fn test_column_type_with_trailing_whitespace(
     }
     let table = make_table(vec![ws_ptosis_col]);
     let etl = make_etl(table, disease_valid);
-    let result = ga4ghphetools::etl::get_cohort_data_from_etl_dto(hpo, etl);
+    let result = ga4ghphetools::etl::get_cohort_data_from_etl_dto(hpo, etl, FailureMode::FailFast);
     assert!(result.is_err());
     let err = result.unwrap_err();
-    assert_eq!(err, "Ptosis: trailing whitespace - 'observed '");
+    assert_eq!(err.to_string(), "Ptosis (row 0): trailing whitespace - 'observed '");
 }
 
 
@@ -530,7 +549,7 @@ fn test_missing_hgvs(
         .remove("c235CtoT_WDR83OS_NM_016145v4");
     assert!(removed.is_some(), "Entry was not found in hgvs_variants"); // make sure we actually remove the variant
     // This should be an error because the Variant row has an allele key that is not in our map
-    let result = ga4ghphetools::etl::get_cohort_data_from_etl_dto(hpo, etl_dto);
+    let result = ga4ghphetools::etl::get_cohort_data_from_etl_dto(hpo, etl_dto, FailureMode::FailFast);
     println!("{:?}", result);
     assert!(result.is_ok());
 }
@@ -541,10 +560,10 @@ fn test_missing_patient_id(
     etl_dto_lacking_patient_id: EtlDto,
     hpo: Arc<FullCsrOntology>
 ) {
-    let result = ga4ghphetools::etl::get_cohort_data_from_etl_dto(hpo, etl_dto_lacking_patient_id);
+    let result = ga4ghphetools::etl::get_cohort_data_from_etl_dto(hpo, etl_dto_lacking_patient_id, FailureMode::FailFast);
     assert!(result.is_err());
     let err = result.unwrap_err();
-    assert_eq!(err, "No patient identifier column found")
+    assert_eq!(err.to_string(), "No patient identifier column found")
 }
 
 
@@ -554,10 +573,10 @@ fn test_no_hpo_column(
     etl_dto_lacking_hpo: EtlDto,
     hpo: Arc<FullCsrOntology>
 ) {
-    let result = ga4ghphetools::etl::get_cohort_data_from_etl_dto(hpo, etl_dto_lacking_hpo);
+    let result = ga4ghphetools::etl::get_cohort_data_from_etl_dto(hpo, etl_dto_lacking_hpo, FailureMode::FailFast);
     assert!(result.is_err());
     let err = result.unwrap_err();
-    assert_eq!(err, "No HPO columns found")
+    assert_eq!(err.to_string(), "No HPO columns found")
 }
 
 
@@ -569,10 +588,10 @@ fn test_no_pmid(
 ) {
     let mut etl = etl_dto_valid.clone();
     etl.pmid = None;
-    let result = ga4ghphetools::etl::get_cohort_data_from_etl_dto(hpo, etl);
+    let result = ga4ghphetools::etl::get_cohort_data_from_etl_dto(hpo, etl, FailureMode::FailFast);
     assert!(result.is_err());
     let err = result.unwrap_err();
-    assert_eq!(err, "No PMID found")
+    assert_eq!(err.to_string(), "No PMID found")
 }
 
 
@@ -584,10 +603,10 @@ fn test_malformed_pmid(
 ) {
     let mut etl = etl_dto_valid.clone();
     etl.pmid = Some("PMID: 123456".to_string());
-    let result = ga4ghphetools::etl::get_cohort_data_from_etl_dto(hpo, etl);
+    let result = ga4ghphetools::etl::get_cohort_data_from_etl_dto(hpo, etl, FailureMode::FailFast);
     assert!(result.is_err());
     let err = result.unwrap_err();
-    assert_eq!(err, "Malformed PMID found 'PMID: 123456'")
+    assert_eq!(err.to_string(), "Malformed PMID found 'PMID: 123456'")
 }
 
 #[rstest]
@@ -597,10 +616,10 @@ fn test_no_title(
 ) {
     let mut etl = etl_dto_valid.clone();
     etl.title = None;
-    let result = ga4ghphetools::etl::get_cohort_data_from_etl_dto(hpo, etl);
+    let result = ga4ghphetools::etl::get_cohort_data_from_etl_dto(hpo, etl, FailureMode::FailFast);
     assert!(result.is_err());
     let err = result.unwrap_err();
-    assert_eq!(err, "No title found")
+    assert_eq!(err.to_string(), "No title found")
 }
 
 
@@ -611,10 +630,10 @@ fn test_malformed_title (
 ) {
     let mut etl = etl_dto_valid.clone();
     etl.title = Some("a".to_string());
-    let result = ga4ghphetools::etl::get_cohort_data_from_etl_dto(hpo, etl);
+    let result = ga4ghphetools::etl::get_cohort_data_from_etl_dto(hpo, etl, FailureMode::FailFast);
     assert!(result.is_err());
     let err = result.unwrap_err();
-    assert_eq!(err, "Malformed title: 'a'")
+    assert_eq!(err.to_string(), "Malformed title: 'a'")
 }
 
 /** Tests whether we set a redundant entry to "na" 
@@ -628,7 +647,7 @@ fn test_column_type_with_redundancy(
     test_orcid: CurationEvent,
     hpo: Arc<FullCsrOntology>
 ) {
-    let result = ga4ghphetools::etl::get_cohort_data_from_etl_dto(hpo.clone(), etl_dto_with_redudancy);
+    let result = ga4ghphetools::etl::get_cohort_data_from_etl_dto(hpo.clone(), etl_dto_with_redudancy, FailureMode::FailFast);
     assert!(result.is_ok());
     let mut cohort_data = result.unwrap();
     cohort_data.curation_history.push(test_orcid);
@@ -661,7 +680,7 @@ fn test_with_excluded_redundancy(
     let  columns = vec![patient_id_column_valid, column_ptosis, column_strabismus, exclude_abn_eye];
     let table = make_table(columns);
     let etl = make_etl(table, disease_valid);
-    let mut cohort = ga4ghphetools::etl::get_cohort_data_from_etl_dto(hpo.clone(), etl).unwrap();
+    let mut cohort = ga4ghphetools::etl::get_cohort_data_from_etl_dto(hpo.clone(), etl, FailureMode::FailFast).unwrap();
     cohort.curation_history.push(test_orcid);
     let result = ga4ghphetools::factory::qc_assessment(hpo.clone(), &cohort);
     assert!(result.is_err());
@@ -707,8 +726,886 @@ fn test_from_file(hpo: Arc<FullCsrOntology>) {
                     .map_err(|e| format!("Failed to read file: {}", e)).unwrap();
     let dto: EtlDto = serde_json::from_str(&contents)
                     .map_err(|e| format!("Failed to deserialize JSON: {}", e)).unwrap(); 
-    let result = ga4ghphetools::etl::get_cohort_data_from_etl_dto(hpo.clone(), dto);
+    let result = ga4ghphetools::etl::get_cohort_data_from_etl_dto(hpo.clone(), dto, FailureMode::FailFast);
     println!("{:?}", result);
 }
 
 
+#[rstest]
+fn test_validate_before_transform(etl_dto_valid: EtlDto) {
+    assert!(ga4ghphetools::etl::validate_etl_dto_before_transform(&etl_dto_valid).is_ok());
+}
+
+#[rstest]
+fn test_validate_before_transform_duplicate_patient_id(etl_dto_valid: EtlDto) {
+    let mut etl_dto = etl_dto_valid.clone();
+    etl_dto.table.columns[0].values[1] = transformed_from_string("Family 1 (Turkish) BAB11420");
+    let errors = ga4ghphetools::etl::validate_etl_dto_before_transform(&etl_dto).unwrap_err();
+    assert_eq!(vec!["Clinical Features: duplicate patient id 'Family 1 (Turkish) BAB11420'".to_string()], errors);
+}
+
+#[rstest]
+#[case('\u{0007}', "U+0007")]
+#[case('\u{200B}', "U+200B")]
+#[case('\u{200F}', "U+200F")]
+fn test_validate_before_transform_control_character(
+    etl_dto_valid: EtlDto,
+    #[case] ch: char,
+    #[case] code: &str
+) {
+    let mut etl_dto = etl_dto_valid.clone();
+    etl_dto.table.columns[0].values[1].current.push(ch);
+    let errors = ga4ghphetools::etl::validate_etl_dto_before_transform(&etl_dto).unwrap_err();
+    assert_eq!(vec![format!("Clinical Features: invisible control character {code} in row 2")], errors);
+}
+
+#[rstest]
+fn test_validate_before_transform_empty_column(etl_dto_valid: EtlDto) {
+    let mut etl_dto = etl_dto_valid.clone();
+    for cell in etl_dto.table.columns[0].values.iter_mut() {
+        cell.current = " ".to_string();
+    }
+    let errors = ga4ghphetools::etl::validate_etl_dto_before_transform(&etl_dto).unwrap_err();
+    assert_eq!(vec!["Clinical Features: all values are empty".to_string()], errors);
+}
+
+
+#[rstest]
+fn test_partially_transformed_cells(
+    etl_dto_valid: EtlDto,
+    hpo: Arc<FullCsrOntology>
+) {
+    let mut etl_dto = etl_dto_valid.clone();
+    etl_dto.table.columns[3].values[1].status = EtlCellStatus::PartiallyTransformed;
+    assert!(etl_dto.table.columns[3].values[1].status.is_usable());
+    assert!(!EtlCellStatus::Error.is_usable());
+    let (result, output) = LogBuffer::capture(tracing::Level::WARN, || {
+        ga4ghphetools::etl::get_cohort_data_from_etl_dto(hpo, etl_dto, FailureMode::FailFast)
+    });
+    assert!(result.is_ok());
+    assert_eq!(1, output.matches("Partially transformed cell needs manual review").count());
+    assert!(output.contains("column=Sex"));
+}
+
+
+/// Second gene of a (hypothetical) digenic form of the disease
+#[fixture]
+fn hgvs_var_second_gene() -> HgvsVariant {
+    HgvsVariant::new_from_parts(
+        "hg38".to_string(),
+        "chr19".to_string(),
+        12687612,
+        "C".to_string(),
+        "T".to_string(),
+        "DHPS".to_string(),
+        "HGNC:2869".to_string(),
+        "c.518A>G".to_string(),
+        "NM_012260.4".to_string(),
+        "NC_000019.10:g.12687612C>T".to_string())
+}
+
+#[fixture]
+fn etl_dto_digenic(
+    patient_id_column_valid: ColumnDto,
+    sex_column_valid: ColumnDto,
+    column_ptosis: ColumnDto,
+    disease_valid: DiseaseData,
+    hgvs_var_1_valid: HgvsVariant,
+    hgvs_var_second_gene: HgvsVariant,
+) -> EtlDto {
+    let variant_column = |header: &str, gene_index: usize, key: &str| ColumnDto {
+        id: format!("variant-column-{gene_index}"),
+        header: EtlColumnHeader {
+            original: header.to_string(),
+            current: None,
+            column_type: EtlColumnType::Variant,
+            hpo_terms: None,
+            gene_index: Some(gene_index),
+        },
+        values: vec![transformed_from_string(key), transformed_from_string("na")],
+    };
+    let key_1 = hgvs_var_1_valid.variant_key();
+    let key_2 = hgvs_var_second_gene.variant_key();
+    let columns = vec![
+        patient_id_column_valid,
+        variant_column("WDR83OS variant", 0, &key_1),
+        variant_column("DHPS variant", 1, &key_2),
+        sex_column_valid,
+        column_ptosis,
+    ];
+    let mut disease = disease_valid;
+    disease.gene_transcript_list.push(GeneTranscriptData {
+        hgnc_id: "HGNC:2869".to_string(),
+        gene_symbol: "DHPS".to_string(),
+        transcript: "NM_012260.4".to_string(),
+    });
+    let mut etl_dto = make_etl(make_table(columns), disease);
+    etl_dto.hgvs_variants.insert(key_1, hgvs_var_1_valid);
+    etl_dto.hgvs_variants.insert(key_2, hgvs_var_second_gene);
+    etl_dto
+}
+
+#[rstest]
+fn test_digenic_variant_keys(
+    etl_dto_digenic: EtlDto,
+    hgvs_var_1_valid: HgvsVariant,
+    hgvs_var_second_gene: HgvsVariant,
+    hpo: Arc<FullCsrOntology>
+) {
+    let cohort = ga4ghphetools::etl::get_cohort_data_from_etl_dto(hpo, etl_dto_digenic, FailureMode::FailFast).unwrap();
+    let key_1 = format!("0:{}", hgvs_var_1_valid.variant_key());
+    let key_2 = format!("1:{}", hgvs_var_second_gene.variant_key());
+    let allele_count_map = &cohort.rows[0].allele_count_map;
+    assert_eq!(2, allele_count_map.len());
+    assert_eq!(Some(&1), allele_count_map.get(&key_1));
+    assert_eq!(Some(&1), allele_count_map.get(&key_2));
+    assert!(cohort.rows[1].allele_count_map.is_empty());
+    assert_eq!(hgvs_var_1_valid, cohort.hgvs_variants[&key_1]);
+    assert_eq!(hgvs_var_second_gene, cohort.hgvs_variants[&key_2]);
+    // the variants are only stored under the prefixed keys
+    assert_eq!(2, cohort.hgvs_variants.len());
+}
+
+#[rstest]
+fn test_gene_index_defaults_to_none() {
+    let header: EtlColumnHeader = serde_json::from_str(
+        r#"{"original": "Variant", "current": null, "columnType": "variant", "hpoTerms": null}"#).unwrap();
+    assert_eq!(None, header.gene_index);
+}
+
+#[rstest]
+fn test_process_allele_column_invalid_gene_index(
+    etl_dto_digenic: EtlDto,
+    hpo: Arc<FullCsrOntology>
+) {
+    let result = ga4ghphetools::etl::process_allele_column_for_gene(hpo, etl_dto_digenic, 1, 2, |_, _| {});
+    assert_eq!(Err("Invalid gene index 2 for 2 genes".to_string()), result.map(|_| ()).map_err(String::from));
+}
+
+
+fn column_with_values(header: &str, column_type: EtlColumnType, hpo_terms: Option<Vec<HpoTermDuplet>>, values: &[&str]) -> ColumnDto {
+    ColumnDto {
+        id: format!("{header}-column"),
+        header: EtlColumnHeader {
+            original: header.to_string(),
+            current: None,
+            column_type,
+            hpo_terms,
+            gene_index: None,
+        },
+        values: values.iter().map(|v| transformed_from_string(v)).collect(),
+    }
+}
+
+/// Patient P1 is listed in two rows (one per phenotype)
+#[fixture]
+fn etl_dto_duplicate_patients(
+    disease_valid: DiseaseData,
+    hgvs_map: HashMap<String, HgvsVariant>,
+    hgvs_var_1_valid: HgvsVariant,
+    hgvs_var_2_valid: HgvsVariant,
+) -> EtlDto {
+    let key_1 = hgvs_var_1_valid.variant_key();
+    let key_2 = hgvs_var_2_valid.variant_key();
+    let columns = vec![
+        column_with_values("Patient", EtlColumnType::PatientId, None, &["P1", "P1", "P2"]),
+        column_with_values("Variant", EtlColumnType::Variant, None, &[&key_1, "na", &key_2]),
+        column_with_values("Sex", EtlColumnType::Sex, None, &["M", "M", "F"]),
+        column_with_values("Ptosis", EtlColumnType::SingleHpoTerm,
+            Some(vec![HpoTermDuplet::new("Ptosis", "HP:0000508")]), &["observed", "na", "excluded"]),
+        column_with_values("Strabismus", EtlColumnType::SingleHpoTerm,
+            Some(vec![HpoTermDuplet::new("Strabismus", "HP:0000486")]), &["excluded", "observed", "observed"]),
+        column_with_values("Eyes", EtlColumnType::MultipleHpoTerm,
+            Some(vec![HpoTermDuplet::new("Hypotelorism", "HP:0000601"), HpoTermDuplet::new("Hypertelorism", "HP:0000316")]),
+            &["HP:0000601-excluded-na", "HP:0000316-observed-na", ""]),
+    ];
+    let mut etl_dto = make_etl(make_table(columns), disease_valid);
+    etl_dto.hgvs_variants = hgvs_map;
+    etl_dto
+}
+
+#[rstest]
+fn test_merge_duplicate_patient_ids(
+    etl_dto_duplicate_patients: EtlDto,
+    hgvs_var_1_valid: HgvsVariant,
+    hpo: Arc<FullCsrOntology>
+) {
+    let merged = ga4ghphetools::etl::merge_duplicate_patient_ids(hpo.clone(), etl_dto_duplicate_patients).unwrap();
+    assert_eq!(2, merged.row_count());
+    let current = |col: usize, row: usize| merged.table.columns[col].values[row].current.clone();
+    assert_eq!("P1", current(0, 0));
+    assert_eq!("P2", current(0, 1));
+    assert_eq!(hgvs_var_1_valid.variant_key(), current(1, 0));
+    assert_eq!("observed", current(3, 0));
+    assert_eq!("observed", current(4, 0));
+    assert_eq!("HP:0000601-excluded-na;HP:0000316-observed-na", current(5, 0));
+    let cohort = ga4ghphetools::etl::get_cohort_data_from_etl_dto(hpo, merged, FailureMode::FailFast).unwrap();
+    assert_eq!(2, cohort.rows.len());
+    // P1 has the union of the observations of both rows
+    let observed: Vec<&str> = cohort.hpo_headers.iter()
+        .zip(cohort.rows[0].hpo_data.iter())
+        .filter(|(_, cell)| cell.is_observed())
+        .map(|(duplet, _)| duplet.hpo_id())
+        .collect();
+    assert_eq!(3, observed.len());
+    assert!(observed.contains(&"HP:0000508"));
+    assert!(observed.contains(&"HP:0000486"));
+    assert!(observed.contains(&"HP:0000316"));
+}
+
+#[rstest]
+fn test_merge_duplicate_patient_ids_conflict(
+    mut etl_dto_duplicate_patients: EtlDto,
+    hpo: Arc<FullCsrOntology>
+) {
+    etl_dto_duplicate_patients.table.columns[2].values[1] = transformed_from_string("F");
+    let result = ga4ghphetools::etl::merge_duplicate_patient_ids(hpo, etl_dto_duplicate_patients);
+    assert_eq!(Err("P1: Conflicting values for Sex: 'M' and 'F'".to_string()), result.map(|_| ()).map_err(String::from));
+}
+
+#[rstest]
+fn test_tissue_type_column(
+    mut etl_dto_valid: EtlDto,
+    hpo: Arc<FullCsrOntology>
+) {
+    etl_dto_valid.table.columns.push(
+        column_with_values("Biopsy", EtlColumnType::TissueType, None, &["UBERON:0002107 liver", "na"]));
+    let cohort = ga4ghphetools::etl::get_cohort_data_from_etl_dto(hpo.clone(), etl_dto_valid.clone(), FailureMode::FailFast).unwrap();
+    let biosamples = &cohort.rows[0].individual_data.biosamples;
+    assert_eq!(1, biosamples.len());
+    assert_eq!("Family 1 (Turkish) BAB11420-biosample-1", biosamples[0].biosample_id);
+    assert_eq!("UBERON:0002107", biosamples[0].tissue_id);
+    assert_eq!("liver", biosamples[0].tissue_label);
+    assert!(cohort.rows[1].individual_data.biosamples.is_empty());
+    // tissue types must be UBERON terms
+    let last = etl_dto_valid.table.columns.len() - 1;
+    etl_dto_valid.table.columns[last].values[1] = transformed_from_string("liver");
+    assert!(ga4ghphetools::etl::get_cohort_data_from_etl_dto(hpo, etl_dto_valid, FailureMode::FailFast).is_err());
+}
+
+#[rstest]
+fn test_laterality_column(
+    mut etl_dto_valid: EtlDto,
+    hpo: Arc<FullCsrOntology>
+) {
+    etl_dto_valid.table.columns.push(
+        column_with_values("Side", EtlColumnType::Laterality, None, &["bilateral", "na"]));
+    let cohort = ga4ghphetools::etl::get_cohort_data_from_etl_dto(hpo.clone(), etl_dto_valid.clone(), FailureMode::FailFast).unwrap();
+    let first = &cohort.rows[0].hpo_data;
+    assert!(first.iter().any(|cell| cell.is_observed()));
+    for cell in first {
+        let expected = if cell.is_observed() || cell.has_onset() { Some(LateralityCode::Bilateral) } else { None };
+        assert_eq!(expected, cell.laterality);
+    }
+    assert!(cohort.rows[1].hpo_data.iter().all(|cell| cell.laterality.is_none()));
+    let last = etl_dto_valid.table.columns.len() - 1;
+    etl_dto_valid.table.columns[last].values[1] = transformed_from_string("both sides");
+    assert!(ga4ghphetools::etl::get_cohort_data_from_etl_dto(hpo, etl_dto_valid, FailureMode::FailFast).is_err());
+}
+
+#[rstest]
+fn test_severity_column(
+    mut etl_dto_valid: EtlDto,
+    hpo: Arc<FullCsrOntology>
+) {
+    etl_dto_valid.table.columns.push(
+        column_with_values("Severity", EtlColumnType::SeverityModifier, None, &["na", "Severe"]));
+    let cohort = ga4ghphetools::etl::get_cohort_data_from_etl_dto(hpo.clone(), etl_dto_valid.clone(), FailureMode::FailFast).unwrap();
+    assert!(cohort.rows[0].hpo_data.iter().all(|cell| cell.severity.is_none()));
+    for cell in &cohort.rows[1].hpo_data {
+        let expected = if cell.is_observed() || cell.has_onset() { Some(SeverityCode::Severe) } else { None };
+        assert_eq!(expected, cell.severity);
+    }
+    let last = etl_dto_valid.table.columns.len() - 1;
+    etl_dto_valid.table.columns[last].values[1] = transformed_from_string("very severe");
+    assert!(ga4ghphetools::etl::get_cohort_data_from_etl_dto(hpo, etl_dto_valid, FailureMode::FailFast).is_err());
+}
+
+#[rstest]
+fn test_row_context_in_error(
+    mut etl_dto_valid: EtlDto,
+    hpo: Arc<FullCsrOntology>
+) {
+    let patient_id_idx = etl_dto_valid.table.columns
+        .iter()
+        .position(|col| col.header.column_type == EtlColumnType::PatientId)
+        .unwrap();
+    etl_dto_valid.table.columns[patient_id_idx].values[1] = transformed_from_string("");
+    let err = ga4ghphetools::etl::get_cohort_data_from_etl_dto(hpo, etl_dto_valid, FailureMode::FailFast).unwrap_err();
+    assert_eq!("Row 1: Could not extract individual ID: patient ID column is empty", err.to_string());
+}
+
+#[rstest]
+fn test_row_context_in_tissue_type_error(
+    mut etl_dto_valid: EtlDto,
+    hpo: Arc<FullCsrOntology>
+) {
+    etl_dto_valid.table.columns.push(
+        column_with_values("Biopsy", EtlColumnType::TissueType, None, &["na", "liver"]));
+    let err = ga4ghphetools::etl::get_cohort_data_from_etl_dto(hpo, etl_dto_valid, FailureMode::FailFast).unwrap_err();
+    assert!(err.to_string().starts_with("Row 1: Tissue type must be an UBERON term"));
+}
+
+/// Cell-level problems in different rows and columns are all reported with FailureMode::CollectAll
+#[rstest]
+fn test_collect_all_qc_errors(
+    mut etl_dto_valid: EtlDto,
+    hpo: Arc<FullCsrOntology>
+) {
+    etl_dto_valid.table.columns[0].values[0].current.insert(0, ' ');
+    etl_dto_valid.table.columns[3].values[1] = EtlCellValue {
+        original: "female".to_string(),
+        current: "female".to_string(),
+        status: EtlCellStatus::Raw,
+        error: None,
+    };
+    etl_dto_valid.table.columns[7].values[1].current.push(' ');
+    let fail_fast = ga4ghphetools::etl::get_cohort_data_from_etl_dto(hpo.clone(), etl_dto_valid.clone(), FailureMode::FailFast);
+    assert!(matches!(fail_fast, Err(PheToolsError::EtlTransformFailed(_))));
+    let result = ga4ghphetools::etl::get_cohort_data_from_etl_dto(hpo, etl_dto_valid, FailureMode::CollectAll);
+    let Err(PheToolsError::EtlQcFailed(errors)) = result else {
+        panic!("Expected EtlQcFailed but got {:?}", result);
+    };
+    let locations: Vec<(Option<&str>, Option<usize>)> = errors
+        .iter()
+        .map(|e| (e.column.as_deref(), e.row))
+        .collect();
+    assert_eq!(vec![
+        (Some("Clinical Features"), Some(0)),
+        (Some("Sex"), Some(1)),
+        (Some("Ptosis"), Some(1)),
+    ], locations);
+    assert_eq!("Clinical Features (row 0): leading whitespace - ' Family 1 (Turkish) BAB11420'", errors[0].to_string());
+    assert_eq!("Sex (row 1): 'female' was not transformed", errors[1].to_string());
+}
+
+/// If the table passes the Q/C, all rows that cannot be transformed are reported with FailureMode::CollectAll
+#[rstest]
+fn test_collect_all_row_errors(
+    mut etl_dto_valid: EtlDto,
+    hpo: Arc<FullCsrOntology>
+) {
+    etl_dto_valid.table.columns.push(
+        column_with_values("Biopsy", EtlColumnType::TissueType, None, &["liver", "kidney"]));
+    let result = ga4ghphetools::etl::get_cohort_data_from_etl_dto(hpo, etl_dto_valid, FailureMode::CollectAll);
+    let Err(PheToolsError::EtlQcFailed(errors)) = result else {
+        panic!("Expected EtlQcFailed but got {:?}", result);
+    };
+    assert_eq!(vec![Some(0), Some(1)], errors.iter().map(|e| e.row).collect::<Vec<_>>());
+    assert!(errors[1].to_string().starts_with("Row 1: Tissue type must be an UBERON term"));
+}
+
+#[rstest]
+fn test_measurement_column(
+    mut etl_dto_valid: EtlDto,
+    hpo: Arc<FullCsrOntology>
+) {
+    etl_dto_valid.table.columns.push(
+        column_with_values("Creatinine", EtlColumnType::Measurement, None, &["LOINC:2160-0 1.2 UCUM:mg/dL P20Y", "na"]));
+    let cohort = ga4ghphetools::etl::get_cohort_data_from_etl_dto(hpo.clone(), etl_dto_valid, FailureMode::FailFast).unwrap();
+    assert_eq!(1, cohort.rows[0].individual_data.measurements.len());
+    assert!(cohort.rows[1].individual_data.measurements.is_empty());
+    let packets = ga4ghphetools::ppkt::get_phenopackets(cohort, "ORCID:0000-0002-0736-9199".to_string(), hpo).unwrap();
+    let measurement = &packets[0].measurements[0];
+    assert_eq!("LOINC:2160-0", measurement.assay.as_ref().unwrap().id);
+    assert!(measurement.time_observed.is_some());
+    let Some(MeasurementValue::Value(Value { value: Some(value::Value::Quantity(quantity)) })) = &measurement.measurement_value else {
+        panic!("Expected a quantity");
+    };
+    assert_eq!(1.2, quantity.value);
+    assert_eq!("UCUM:mg/dL", quantity.unit.as_ref().unwrap().id);
+    assert!(packets[1].measurements.is_empty());
+}
+
+#[rstest]
+fn test_age_range_column(
+    mut etl_dto_valid: EtlDto,
+    hpo: Arc<FullCsrOntology>
+) {
+    etl_dto_valid.table.columns.push(
+        column_with_values("Onset", EtlColumnType::AgeRange, None, &["P3Y-P5Y", "P2Y"]));
+    let cohort = ga4ghphetools::etl::get_cohort_data_from_etl_dto(hpo.clone(), etl_dto_valid, FailureMode::FailFast).unwrap();
+    let individual = &cohort.rows[0].individual_data;
+    assert_eq!("P3Y", individual.age_of_onset);
+    assert_eq!(Some("P5Y".to_string()), individual.age_of_onset_max);
+    assert_eq!(None, cohort.rows[1].individual_data.age_of_onset_max);
+    // the onset of the disease is the midpoint of the range
+    let packets = ga4ghphetools::ppkt::get_phenopackets(cohort, "ORCID:0000-0002-0736-9199".to_string(), hpo).unwrap();
+    let onset_age = |i: usize| match &packets[i].diseases[0].onset.as_ref().unwrap().element {
+        Some(time_element::Element::Age(age)) => age.iso8601duration.clone(),
+        other => panic!("Expected an age but got {other:?}"),
+    };
+    assert_eq!("P4Y", onset_age(0));
+    assert_eq!("P2Y", onset_age(1));
+}
+
+
+#[rstest]
+fn test_natural_language_age_columns(
+    mut etl_dto_valid: EtlDto,
+    hpo: Arc<FullCsrOntology>
+) {
+    etl_dto_valid.table.columns.push(
+        column_with_values("Onset", EtlColumnType::AgeOfOnset, None, &["in infancy", "at age 3"]));
+    etl_dto_valid.table.columns.push(
+        column_with_values("Age", EtlColumnType::AgeAtLastEncounter, None, &["in her 20s", "P12Y"]));
+    let cohort = ga4ghphetools::etl::get_cohort_data_from_etl_dto(hpo, etl_dto_valid, FailureMode::FailFast).unwrap();
+    assert_eq!("Infantile onset", cohort.rows[0].individual_data.age_of_onset);
+    assert_eq!("P3Y", cohort.rows[1].individual_data.age_of_onset);
+    assert_eq!("P20Y-P29Y", cohort.rows[0].individual_data.age_at_last_encounter);
+    assert_eq!("P12Y", cohort.rows[1].individual_data.age_at_last_encounter);
+}
+
+
+#[rstest]
+fn test_trio_columns(
+    mut etl_dto_valid: EtlDto,
+    hpo: Arc<FullCsrOntology>
+) {
+    // the first individual inherited the variant from the father, the variant of the second individual is de novo
+    etl_dto_valid.table.columns.push(
+        column_with_values("Father", EtlColumnType::FatherVariant, None, &["c235CtoT_WDR83OS_NM_016145v4", "na"]));
+    etl_dto_valid.table.columns.push(
+        column_with_values("Mother", EtlColumnType::MotherVariant, None, &["na", "na"]));
+    let cohort = ga4ghphetools::etl::get_cohort_data_from_etl_dto(hpo, etl_dto_valid, FailureMode::FailFast).unwrap();
+    assert_eq!(2, cohort.trios.len());
+    assert_eq!(Some(vec!["c235CtoT_WDR83OS_NM_016145v4".to_string()]), cohort.trios[0].father_alleles);
+    assert_eq!(Some(vec![]), cohort.trios[0].mother_alleles);
+    let de_novo = ga4ghphetools::factory::detect_de_novo_variants(&cohort).unwrap();
+    let second_individual = cohort.rows[1].individual_data.individual_id.clone();
+    assert_eq!(vec![(second_individual, "c156_1GtoT_WDR83OS_NM_016145v4".to_string())], de_novo);
+}
+
+
+#[rstest]
+#[case("N/A")]
+#[case("NA")]
+#[case("ND")]
+#[case("n/a")]
+#[case("unknown")]
+#[case("Unknown")]
+#[case("--")]
+#[case("-")]
+#[case("not reported")]
+fn test_normalize_missing_values(
+    mut etl_dto_valid: EtlDto,
+    hpo: Arc<FullCsrOntology>,
+    #[case] alias: &str
+) {
+    // the second individual has missing values in the last two HPO columns (one of which is not yet transformed)
+    let last = etl_dto_valid.table.columns.len() - 1;
+    etl_dto_valid.table.columns[last - 1].values[1] = EtlCellValue::from_string(alias);
+    etl_dto_valid.table.columns[last].values[1] = transformed_from_string(alias);
+    let normalized = ga4ghphetools::etl::normalize_missing_values(
+        etl_dto_valid.clone(), &ga4ghphetools::etl::DEFAULT_MISSING_VALUE_ALIASES);
+    for col in [last - 1, last] {
+        let cell = &normalized.table.columns[col].values[1];
+        assert_eq!("na", cell.current);
+        assert_eq!(alias, cell.original);
+        assert_eq!(EtlCellStatus::Transformed, cell.status);
+    }
+    // other cells are not changed
+    assert_eq!("observed", normalized.table.columns[last].values[0].current);
+    assert!(ga4ghphetools::etl::get_cohort_data_from_etl_dto(hpo, normalized, FailureMode::FailFast).is_ok());
+}
+
+#[rstest]
+fn test_normalize_custom_missing_values(mut etl_dto_valid: EtlDto) {
+    let last = etl_dto_valid.table.columns.len() - 1;
+    etl_dto_valid.table.columns[last].values[1] = transformed_from_string("not done");
+    let normalized = ga4ghphetools::etl::normalize_missing_values(
+        etl_dto_valid.clone(), &ga4ghphetools::etl::DEFAULT_MISSING_VALUE_ALIASES);
+    assert_eq!("not done", normalized.table.columns[last].values[1].current);
+    let normalized = ga4ghphetools::etl::normalize_missing_values(etl_dto_valid, &["not done"]);
+    assert_eq!("na", normalized.table.columns[last].values[1].current);
+    // patient ids are never normalized
+    assert_eq!("Family 1 (Turkish) BAB11420", normalized.table.columns[0].values[0].current);
+}
+
+#[rstest]
+#[case("Sex", EtlColumnType::Sex, "female", "M, F, O, U")]
+#[case("Sex", EtlColumnType::Sex, "m", "M, F, O, U")]
+#[case("Deceased", EtlColumnType::Deceased, "dead", "yes, no, na")]
+#[case("Deceased", EtlColumnType::Deceased, "Yes", "yes, no, na")]
+fn test_invalid_value_in_typed_column(
+    mut etl_dto_valid: EtlDto,
+    hpo: Arc<FullCsrOntology>,
+    #[case] header: &str,
+    #[case] column_type: EtlColumnType,
+    #[case] value: &str,
+    #[case] allowed: &str,
+) {
+    etl_dto_valid.table.columns.retain(|col| col.header.column_type != column_type);
+    etl_dto_valid.table.columns.push(column_with_values(header, column_type, None, &["na", value]));
+    let result = ga4ghphetools::etl::get_cohort_data_from_etl_dto(hpo, etl_dto_valid, FailureMode::CollectAll);
+    let Err(PheToolsError::EtlQcFailed(errors)) = result else {
+        panic!("Expected EtlQcFailed but got {:?}", result);
+    };
+    assert_eq!(1, errors.len());
+    assert_eq!(
+        format!("{header} (row 1): '{value}' is not an allowed value (allowed: {allowed})"),
+        errors[0].to_string());
+}
+
+#[rstest]
+#[case(EtlColumnType::Sex, Some(&["M", "F", "O", "U"][..]))]
+#[case(EtlColumnType::Deceased, Some(&["yes", "no", "na"][..]))]
+#[case(EtlColumnType::PatientId, None)]
+#[case(EtlColumnType::AgeOfOnset, None)]
+fn test_allowed_values(#[case] column_type: EtlColumnType, #[case] expected: Option<&[&str]>) {
+    assert_eq!(expected, column_type.allowed_values());
+}
+
+fn column_headers(etl: &EtlDto) -> Vec<String> {
+    etl.table.columns.iter().map(|col| col.header.original.clone()).collect()
+}
+
+#[rstest]
+fn test_reorder_columns(
+    etl_dto_valid: EtlDto,
+    hpo: Arc<FullCsrOntology>
+) {
+    let n = etl_dto_valid.table.columns.len();
+    let reversed: Vec<usize> = (0..n).rev().collect();
+    let reordered = ga4ghphetools::etl::reorder_columns(hpo.clone(), etl_dto_valid.clone(), &reversed).unwrap();
+    let mut expected = column_headers(&etl_dto_valid);
+    expected.reverse();
+    assert_eq!(expected, column_headers(&reordered));
+    assert_eq!(etl_dto_valid.table.columns[0].values[1].current, reordered.table.columns[n - 1].values[1].current);
+    // reversing again restores the original order
+    let restored = ga4ghphetools::etl::reorder_columns(hpo.clone(), reordered, &reversed).unwrap();
+    assert_eq!(column_headers(&etl_dto_valid), column_headers(&restored));
+    // moving the sex column to the front does not change the cohort that is created
+    let mut sex_first: Vec<usize> = (0..n).filter(|&i| i != 3).collect();
+    sex_first.insert(0, 3);
+    let moved = ga4ghphetools::etl::reorder_columns(hpo.clone(), etl_dto_valid, &sex_first).unwrap();
+    assert_eq!("Sex", moved.table.columns[0].header.original);
+    assert!(ga4ghphetools::etl::get_cohort_data_from_etl_dto(hpo, moved, FailureMode::FailFast).is_ok());
+}
+
+#[rstest]
+#[case::too_short(vec![0, 1, 2], "3 indices")]
+#[case::duplicate(vec![0, 1, 2, 3, 4, 5, 6, 7, 7], "appears more than once")]
+#[case::out_of_range(vec![0, 1, 2, 3, 4, 5, 6, 7, 9], "out of range")]
+fn test_reorder_columns_invalid_permutation(
+    etl_dto_valid: EtlDto,
+    hpo: Arc<FullCsrOntology>,
+    #[case] new_order: Vec<usize>,
+    #[case] message: &str,
+) {
+    assert_eq!(9, etl_dto_valid.table.columns.len());
+    let err = ga4ghphetools::etl::reorder_columns(hpo, etl_dto_valid, &new_order).unwrap_err();
+    assert!(matches!(err, PheToolsError::EtlTransformFailed(_)));
+    assert!(err.to_string().contains(message), "unexpected error: {err}");
+}
+
+#[rstest]
+fn test_checkpoint_round_trip(
+    etl_dto_valid: EtlDto,
+    hpo: Arc<FullCsrOntology>
+) {
+    let path = std::env::temp_dir().join(format!("phetools_etl_checkpoint_{}.json", std::process::id()));
+    ga4ghphetools::etl::save_etl_checkpoint(hpo.clone(), &etl_dto_valid, &path).unwrap();
+    let resumed = ga4ghphetools::etl::load_etl_checkpoint(hpo.clone(), &path).unwrap();
+    std::fs::remove_file(&path).unwrap();
+    assert_eq!(serde_json::to_value(&etl_dto_valid).unwrap(), serde_json::to_value(&resumed).unwrap());
+    let original = ga4ghphetools::etl::get_cohort_data_from_etl_dto(hpo.clone(), etl_dto_valid, FailureMode::FailFast).unwrap();
+    let cohort = ga4ghphetools::etl::get_cohort_data_from_etl_dto(hpo, resumed, FailureMode::FailFast).unwrap();
+    assert_eq!(original.rows.len(), cohort.rows.len());
+    assert_eq!(original.hgvs_variants.len(), cohort.hgvs_variants.len());
+}
+
+/// After resuming, cells that refer to validated variants are not sent to Variant Validator again
+#[rstest]
+fn test_resume_skips_validated_variants(
+    mut etl_dto_valid: EtlDto,
+    hgvs_var_1_valid: HgvsVariant,
+    hgvs_var_2_valid: HgvsVariant,
+    hpo: Arc<FullCsrOntology>
+) {
+    etl_dto_valid.table.columns[1].values = vec![
+        transformed_from_string(&hgvs_var_1_valid.variant_key()),
+        transformed_from_string(&hgvs_var_2_valid.variant_key()),
+    ];
+    let path = std::env::temp_dir().join(format!("phetools_etl_resume_{}.json", std::process::id()));
+    ga4ghphetools::etl::save_etl_checkpoint(hpo.clone(), &etl_dto_valid, &path).unwrap();
+    let resumed = ga4ghphetools::etl::load_etl_checkpoint(hpo.clone(), &path).unwrap();
+    std::fs::remove_file(&path).unwrap();
+    let mut n_progress_calls = 0;
+    let processed = ga4ghphetools::etl::process_allele_column(hpo, resumed, 1, |_, _| n_progress_calls += 1).unwrap();
+    assert_eq!(0, n_progress_calls);
+    assert_eq!(2, processed.hgvs_variants.len());
+    assert!(processed.table.columns[1].values.iter().all(|cell| cell.status == EtlCellStatus::Transformed));
+}
+
+/// Processing one of two allele columns keeps the variants of the other column
+#[rstest]
+fn test_second_allele_column_keeps_variants(
+    mut etl_dto_valid: EtlDto,
+    hgvs_var_1_valid: HgvsVariant,
+    hgvs_var_2_valid: HgvsVariant,
+    hpo: Arc<FullCsrOntology>
+) {
+    let key_1 = hgvs_var_1_valid.variant_key();
+    let key_2 = hgvs_var_2_valid.variant_key();
+    etl_dto_valid.table.columns[1].values = vec![transformed_from_string(&key_1), transformed_from_string(&key_1)];
+    let mut allele_2 = etl_dto_valid.table.columns[1].clone();
+    allele_2.id = "allele-2".to_string();
+    allele_2.values = vec![transformed_from_string(&key_2), transformed_from_string(&key_2)];
+    etl_dto_valid.table.columns.insert(2, allele_2);
+    let processed = ga4ghphetools::etl::process_allele_column(hpo.clone(), etl_dto_valid, 1, |_, _| {}).unwrap();
+    assert!(processed.hgvs_variants.contains_key(&key_1));
+    assert!(processed.hgvs_variants.contains_key(&key_2));
+    let processed = ga4ghphetools::etl::process_allele_column(hpo, processed, 2, |_, _| {}).unwrap();
+    assert_eq!(2, processed.hgvs_variants.len());
+}
+
+#[rstest]
+fn test_process_allele_column_saves_checkpoint(
+    mut etl_dto_valid: EtlDto,
+    hgvs_var_1_valid: HgvsVariant,
+    hgvs_var_2_valid: HgvsVariant,
+    hpo: Arc<FullCsrOntology>
+) {
+    etl_dto_valid.table.columns[1].values = vec![
+        transformed_from_string(&hgvs_var_1_valid.variant_key()),
+        transformed_from_string(&hgvs_var_2_valid.variant_key()),
+    ];
+    let path = std::env::temp_dir().join(format!("phetools_etl_batch_checkpoint_{}.json", std::process::id()));
+    let processed = ga4ghphetools::etl::process_allele_column_with_checkpoint(hpo.clone(), etl_dto_valid, 1, &path, |_, _| {})
+        .unwrap();
+    let resumed = ga4ghphetools::etl::load_etl_checkpoint(hpo, &path).unwrap();
+    std::fs::remove_file(&path).unwrap();
+    assert_eq!(2, resumed.hgvs_variants.len());
+    assert_eq!(column_headers(&processed), column_headers(&resumed));
+}
+
+#[rstest]
+fn test_missing_checkpoint(hpo: Arc<FullCsrOntology>) {
+    let path = std::env::temp_dir().join("phetools_no_such_checkpoint.json");
+    let err = ga4ghphetools::etl::load_etl_checkpoint(hpo, &path).unwrap_err();
+    assert!(matches!(err, PheToolsError::EtlTransformFailed(_)));
+}
+
+#[rstest]
+fn test_etl_session_undo_redo(
+    etl_dto_valid: EtlDto,
+    hpo: Arc<FullCsrOntology>
+) {
+    let n = etl_dto_valid.table.columns.len();
+    let original = column_headers(&etl_dto_valid);
+    let mut session = ga4ghphetools::etl::EtlSession::new(etl_dto_valid, 10);
+    assert!(!session.can_undo());
+    assert!(!session.can_redo());
+    assert!(session.undo().is_none());
+    // two transformations: reverse the columns, then move the (new) last column to the front
+    let reversed: Vec<usize> = (0..n).rev().collect();
+    session.apply(|dto| ga4ghphetools::etl::reorder_columns(hpo.clone(), dto, &reversed)).unwrap();
+    let rotated: Vec<usize> = std::iter::once(n - 1).chain(0..n - 1).collect();
+    session.apply(|dto| ga4ghphetools::etl::reorder_columns(hpo.clone(), dto, &rotated)).unwrap();
+    let after_both = column_headers(session.current());
+    assert_eq!(original[0], after_both[0]);
+    // undo both transformations
+    let after_first = column_headers(&session.undo().unwrap());
+    assert_eq!(original.iter().rev().cloned().collect::<Vec<_>>(), after_first);
+    assert_eq!(original, column_headers(&session.undo().unwrap()));
+    assert!(!session.can_undo());
+    assert!(session.can_redo());
+    // redo after undo
+    assert_eq!(after_first, column_headers(&session.redo().unwrap()));
+    assert_eq!(after_both, column_headers(&session.redo().unwrap()));
+    assert!(session.redo().is_none());
+}
+
+#[rstest]
+fn test_etl_session_new_state_discards_redo(
+    etl_dto_valid: EtlDto,
+    hpo: Arc<FullCsrOntology>
+) {
+    let n = etl_dto_valid.table.columns.len();
+    let reversed: Vec<usize> = (0..n).rev().collect();
+    let mut session = ga4ghphetools::etl::EtlSession::new(etl_dto_valid.clone(), 10);
+    session.apply(|dto| ga4ghphetools::etl::reorder_columns(hpo.clone(), dto, &reversed)).unwrap();
+    session.undo().unwrap();
+    session.apply(|dto| Ok(ga4ghphetools::etl::normalize_missing_values(dto, &["F"]))).unwrap();
+    assert!(!session.can_redo());
+    assert_eq!("na", session.current().table.columns[3].values[1].current);
+    // a failed transformation does not change the history
+    let err = session.apply(|dto| ga4ghphetools::etl::reorder_columns(hpo.clone(), dto, &[0]));
+    assert!(err.is_err());
+    assert_eq!("na", session.current().table.columns[3].values[1].current);
+    assert_eq!(column_headers(&etl_dto_valid), column_headers(&session.undo().unwrap()));
+    assert!(!session.can_undo());
+}
+
+/// Only transformations that are applied through the session can be undone
+#[rstest]
+fn test_etl_session_apply_transformations(
+    etl_dto_valid: EtlDto,
+    hpo: Arc<FullCsrOntology>
+) {
+    let original_type = etl_dto_valid.table.columns[3].header.column_type.clone();
+    assert_ne!(EtlColumnType::Raw, original_type);
+    let transforms = vec![EtlTransform::SetColumnType { col: 3, column_type: EtlColumnType::Raw }];
+    let mut session = ga4ghphetools::etl::EtlSession::new(etl_dto_valid, 10);
+    // a direct call returns the transformed table, but the session does not know about it
+    let direct = ga4ghphetools::etl::apply_transformations(hpo.clone(), session.current().clone(), transforms.clone()).unwrap();
+    assert_eq!(EtlColumnType::Raw, direct.table.columns[3].header.column_type);
+    assert!(!session.can_undo());
+    // the same step applied through the session can be undone
+    session.apply(|dto| ga4ghphetools::etl::apply_transformations(hpo.clone(), dto, transforms)).unwrap();
+    assert_eq!(EtlColumnType::Raw, session.current().table.columns[3].header.column_type);
+    assert_eq!(original_type, session.undo().unwrap().table.columns[3].header.column_type);
+}
+
+#[rstest]
+fn test_etl_session_max_history(
+    etl_dto_valid: EtlDto,
+    hpo: Arc<FullCsrOntology>
+) {
+    let n = etl_dto_valid.table.columns.len();
+    let reversed: Vec<usize> = (0..n).rev().collect();
+    let mut session = ga4ghphetools::etl::EtlSession::new(etl_dto_valid, 3);
+    for _ in 0..5 {
+        session.apply(|dto| ga4ghphetools::etl::reorder_columns(hpo.clone(), dto, &reversed)).unwrap();
+    }
+    // only the current state and the two states before it are kept
+    assert!(session.undo().is_some());
+    assert!(session.undo().is_some());
+    assert!(!session.can_undo());
+    assert!(session.undo().is_none());
+}
+
+#[rstest]
+fn test_transform_set_column_type(
+    etl_dto_valid: EtlDto,
+    hpo: Arc<FullCsrOntology>
+) {
+    let transforms = vec![EtlTransform::SetColumnType { col: 3, column_type: EtlColumnType::Raw }];
+    let dto = ga4ghphetools::etl::apply_transformations(hpo, etl_dto_valid, transforms).unwrap();
+    assert_eq!(EtlColumnType::Raw, dto.table.columns[3].header.column_type);
+    assert_eq!(EtlColumnType::PatientId, dto.table.columns[0].header.column_type);
+}
+
+#[rstest]
+fn test_transform_process_alleles(
+    mut etl_dto_valid: EtlDto,
+    hgvs_var_1_valid: HgvsVariant,
+    hgvs_var_2_valid: HgvsVariant,
+    hpo: Arc<FullCsrOntology>
+) {
+    // cells that already refer to validated variants are not sent to Variant Validator
+    etl_dto_valid.table.columns[1].values = vec![
+        transformed_from_string(&hgvs_var_1_valid.variant_key()),
+        transformed_from_string(&hgvs_var_2_valid.variant_key()),
+    ];
+    let transforms = vec![EtlTransform::ProcessAlleles { col: 1 }];
+    let dto = ga4ghphetools::etl::apply_transformations(hpo, etl_dto_valid, transforms).unwrap();
+    assert_eq!(2, dto.hgvs_variants.len());
+    assert!(dto.table.columns[1].values.iter().all(|cell| cell.status == EtlCellStatus::Transformed));
+}
+
+#[rstest]
+fn test_transform_set_single_hpo_term(
+    mut etl_dto_valid: EtlDto,
+    hpo: Arc<FullCsrOntology>
+) {
+    etl_dto_valid.table.columns[4].header.column_type = EtlColumnType::Raw;
+    etl_dto_valid.table.columns[4].header.hpo_terms = None;
+    let term = HpoTermDuplet::new("Delayed gross motor development", "HP:0002194");
+    let transforms = vec![EtlTransform::SetSingleHpoTerm { col: 4, term: term.clone() }];
+    let dto = ga4ghphetools::etl::apply_transformations(hpo, etl_dto_valid, transforms).unwrap();
+    let header = &dto.table.columns[4].header;
+    assert_eq!(EtlColumnType::SingleHpoTerm, header.column_type);
+    assert_eq!(Some(vec![term]), header.hpo_terms);
+}
+
+#[rstest]
+fn test_transform_set_multiple_hpo_terms(
+    etl_dto_valid: EtlDto,
+    hpo: Arc<FullCsrOntology>
+) {
+    let terms = vec![
+        HpoTermDuplet::new("Delayed ability to sit", "HP:0025336"),
+        HpoTermDuplet::new("Delayed gross motor development", "HP:0002194"),
+    ];
+    let transforms = vec![EtlTransform::SetMultipleHpoTerms { col: 4, terms: terms.clone() }];
+    let dto = ga4ghphetools::etl::apply_transformations(hpo.clone(), etl_dto_valid.clone(), transforms).unwrap();
+    let header = &dto.table.columns[4].header;
+    assert_eq!(EtlColumnType::MultipleHpoTerm, header.column_type);
+    assert_eq!(Some(terms), header.hpo_terms);
+    let transforms = vec![EtlTransform::SetMultipleHpoTerms { col: 4, terms: vec![] }];
+    assert!(ga4ghphetools::etl::apply_transformations(hpo, etl_dto_valid, transforms).is_err());
+}
+
+#[rstest]
+fn test_transform_set_patient_id(
+    etl_dto_valid: EtlDto,
+    hpo: Arc<FullCsrOntology>
+) {
+    let transforms = vec![EtlTransform::SetPatientId { col: 3 }];
+    let dto = ga4ghphetools::etl::apply_transformations(hpo, etl_dto_valid, transforms).unwrap();
+    // there is only one PatientId column
+    assert_eq!(EtlColumnType::PatientId, dto.table.columns[3].header.column_type);
+    assert_eq!(EtlColumnType::Raw, dto.table.columns[0].header.column_type);
+}
+
+#[rstest]
+fn test_transform_normalize_missing_values(
+    mut etl_dto_valid: EtlDto,
+    hpo: Arc<FullCsrOntology>
+) {
+    etl_dto_valid.table.columns[4].values[0].current = "N/A".to_string();
+    let transforms = vec![EtlTransform::NormalizeMissingValues];
+    let dto = ga4ghphetools::etl::apply_transformations(hpo, etl_dto_valid, transforms).unwrap();
+    assert_eq!("na", dto.table.columns[4].values[0].current);
+}
+
+#[rstest]
+fn test_transforms_are_applied_in_order(
+    etl_dto_valid: EtlDto,
+    hpo: Arc<FullCsrOntology>
+) {
+    let transforms = vec![
+        EtlTransform::SetColumnType { col: 4, column_type: EtlColumnType::Raw },
+        EtlTransform::SetSingleHpoTerm { col: 4, term: HpoTermDuplet::new("Delayed ability to sit", "HP:0025336") },
+        EtlTransform::SetPatientId { col: 3 },
+        EtlTransform::SetPatientId { col: 0 },
+    ];
+    let dto = ga4ghphetools::etl::apply_transformations(hpo, etl_dto_valid, transforms).unwrap();
+    assert_eq!(EtlColumnType::SingleHpoTerm, dto.table.columns[4].header.column_type);
+    assert_eq!(EtlColumnType::PatientId, dto.table.columns[0].header.column_type);
+    assert_eq!(EtlColumnType::Raw, dto.table.columns[3].header.column_type);
+}
+
+#[rstest]
+#[case::column_out_of_range(EtlTransform::SetPatientId { col: 42 }, "out of range")]
+#[case::outdated_label(
+    EtlTransform::SetSingleHpoTerm { col: 4, term: HpoTermDuplet::new("Delayed sitting", "HP:0025336") },
+    "Transformation 2"
+)]
+fn test_failed_transform(
+    etl_dto_valid: EtlDto,
+    hpo: Arc<FullCsrOntology>,
+    #[case] transform: EtlTransform,
+    #[case] message: &str,
+) {
+    let transforms = vec![EtlTransform::NormalizeMissingValues, transform];
+    let err = ga4ghphetools::etl::apply_transformations(hpo, etl_dto_valid, transforms).unwrap_err();
+    assert!(matches!(err, PheToolsError::EtlTransformFailed(_)));
+    assert!(err.to_string().contains(message), "unexpected error: {err}");
+}
+
+#[rstest]
+fn test_deserialize_transforms() {
+    let json = r#"[
+        {"type": "setColumnType", "col": 3, "columnType": "sex"},
+        {"type": "processAlleles", "col": 1},
+        {"type": "setSingleHpoTerm", "col": 4, "term": {"hpoLabel": "Delayed ability to sit", "hpoId": "HP:0025336"}},
+        {"type": "normalizeMissingValues"}
+    ]"#;
+    let transforms: Vec<EtlTransform> = serde_json::from_str(json).unwrap();
+    assert_eq!(EtlTransform::SetColumnType { col: 3, column_type: EtlColumnType::Sex }, transforms[0]);
+    assert_eq!(EtlTransform::ProcessAlleles { col: 1 }, transforms[1]);
+    assert_eq!(EtlTransform::NormalizeMissingValues, transforms[3]);
+}
@@ -0,0 +1,91 @@
+mod common;
+
+use std::sync::Arc;
+
+use ga4ghphetools::dto::cohort_dto::CohortData;
+use ga4ghphetools::ppkt::{export_as_family, write_family_phenopacket};
+use ontolius::ontology::csr::FullCsrOntology;
+use phenopackets::schema::v2::core::pedigree::person::AffectedStatus;
+use rstest::rstest;
+use common::hpo_fixture::hpo;
+
+use crate::common::cohort_data_fixtures::family_cohort;
+
+const ORCID: &str = "ORCID:0000-0002-0736-9199";
+
+fn parents() -> Vec<String> {
+    vec!["Father".to_string(), "Mother".to_string()]
+}
+
+#[rstest]
+fn test_family_pedigree(hpo: Arc<FullCsrOntology>, family_cohort: CohortData) {
+    let family = export_as_family("Proband", &parents(), &family_cohort, hpo, ORCID).unwrap();
+    assert_eq!("PMID_55555555_Proband-family", family.id);
+    assert_eq!(2, family.relatives.len());
+    let pedigree = family.pedigree.unwrap();
+    assert_eq!(3, pedigree.persons.len());
+    let proband = &pedigree.persons[0];
+    assert_eq!("Proband", proband.individual_id);
+    assert_eq!("Father", proband.paternal_id);
+    assert_eq!("Mother", proband.maternal_id);
+    assert_eq!(i32::from(AffectedStatus::Affected), proband.affected_status);
+    let father = pedigree.persons.iter().find(|p| p.individual_id == "Father").unwrap();
+    assert_eq!(i32::from(AffectedStatus::Unaffected), father.affected_status);
+    assert!(father.paternal_id.is_empty());
+    let mother = pedigree.persons.iter().find(|p| p.individual_id == "Mother").unwrap();
+    assert_eq!(i32::from(AffectedStatus::Affected), mother.affected_status);
+    assert!(pedigree.persons.iter().all(|p| p.family_id == family.id));
+}
+
+#[rstest]
+fn test_family_phenopackets(hpo: Arc<FullCsrOntology>, family_cohort: CohortData) {
+    let family = export_as_family("Proband", &parents(), &family_cohort, hpo, ORCID).unwrap();
+    let proband = family.proband.unwrap();
+    assert_eq!("Proband", proband.subject.unwrap().id);
+    assert_eq!(2, proband.phenotypic_features.len());
+    let relative_ids: Vec<String> = family.relatives
+        .iter()
+        .map(|ppkt| ppkt.subject.as_ref().unwrap().id.clone())
+        .collect();
+    assert_eq!(parents(), relative_ids);
+    assert!(family.meta_data.is_some());
+}
+
+#[rstest]
+fn test_single_parent(hpo: Arc<FullCsrOntology>, family_cohort: CohortData) {
+    let family = export_as_family("Proband", &["Mother".to_string()], &family_cohort, hpo, ORCID).unwrap();
+    let pedigree = family.pedigree.unwrap();
+    assert_eq!(2, pedigree.persons.len());
+    assert!(pedigree.persons[0].paternal_id.is_empty());
+    assert_eq!("Mother", pedigree.persons[0].maternal_id);
+}
+
+#[rstest]
+#[case("Unknown", vec!["Father"])]
+#[case("Proband", vec!["Unknown"])]
+#[case("Proband", vec!["Proband"])]
+#[case("Proband", vec!["Father", "Father"])]
+#[case("Proband", vec!["Father", "Mother", "Father"])]
+fn test_invalid_family(
+    hpo: Arc<FullCsrOntology>,
+    family_cohort: CohortData,
+    #[case] proband_id: &str,
+    #[case] parent_ids: Vec<&str>,
+) {
+    let parent_ids: Vec<String> = parent_ids.into_iter().map(String::from).collect();
+    assert!(export_as_family(proband_id, &parent_ids, &family_cohort, hpo, ORCID).is_err());
+}
+
+#[rstest]
+fn test_write_family(hpo: Arc<FullCsrOntology>, family_cohort: CohortData) {
+    let family = export_as_family("Proband", &parents(), &family_cohort, hpo, ORCID).unwrap();
+    let path = std::env::temp_dir().join(format!("phetools_test_family_{}.json", std::process::id()));
+    write_family_phenopacket(&family, &path).unwrap();
+    let contents = std::fs::read_to_string(&path).unwrap();
+    std::fs::remove_file(&path).unwrap();
+    let json: serde_json::Value = serde_json::from_str(&contents).unwrap();
+    assert_eq!("Proband", json["proband"]["subject"]["id"]);
+    assert!(json["proband"]["subject"].get("karyotypicSex").is_none());
+    assert_eq!(2, json["relatives"].as_array().unwrap().len());
+    assert_eq!(3, json["pedigree"]["persons"].as_array().unwrap().len());
+}
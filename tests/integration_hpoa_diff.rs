@@ -0,0 +1,45 @@
+use std::path::PathBuf;
+
+use rstest::rstest;
+
+
+const HEADER: &str = "#diseaseID\tdiseaseName\tphenotypeID\tphenotypeName\tonsetID\tonsetName\tfrequency\tsex\tnegation\tmodifier\tdescription\tpublication\tevidence\tbiocuration\n";
+
+/// The previous export; the new export differs in the frequency of Arachnodactyly and in the biocuration date
+const OLD_HPOA: &str = "\
+OMIM:154700\tMarfan syndrome\tHP:0001166\tArachnodactyly\t\t\t3/4\t\t\t\t\tPMID:29999999\tPCS\tORCID:0000-0002-0736-9199[2025-01-09]
+OMIM:154700\tMarfan syndrome\tHP:0004322\tShort stature\t\t\t\t\tNOT\t\t\tPMID:30000000\tPCS\tORCID:0000-0002-0736-9199[2025-01-09]
+";
+
+const NEW_HPOA: &str = "\
+OMIM:154700\tMarfan syndrome\tHP:0001166\tArachnodactyly\t\t\t4/5\t\t\t\t\tPMID:29999999\tPCS\tORCID:0000-0002-0736-9199[2025-06-30]
+OMIM:154700\tMarfan syndrome\tHP:0004322\tShort stature\t\t\t\t\tNOT\t\t\tPMID:30000000\tPCS\tORCID:0000-0002-0736-9199[2025-06-30]
+";
+
+fn write_hpoa(name: &str, rows: &str) -> PathBuf {
+    let path = std::env::temp_dir().join(format!("phetools_{}_{}.hpoa", name, std::process::id()));
+    std::fs::write(&path, format!("{HEADER}{rows}")).unwrap();
+    path
+}
+
+#[rstest]
+fn test_diff_hpoa_files() {
+    let old_path = write_hpoa("diff_old", OLD_HPOA);
+    let new_path = write_hpoa("diff_new", NEW_HPOA);
+    let diff = ga4ghphetools::hpoa::diff_hpoa_files(&old_path, &new_path);
+    std::fs::remove_file(&old_path).unwrap();
+    std::fs::remove_file(&new_path).unwrap();
+    let diff = diff.unwrap();
+    assert_eq!(0, diff.added_rows);
+    assert_eq!(0, diff.removed_rows);
+    assert_eq!(1, diff.modified_rows.len());
+    let (old, new) = &diff.modified_rows[0];
+    assert!(old.contains("3/4"));
+    assert!(new.contains("4/5"));
+}
+
+#[rstest]
+fn test_diff_missing_file() {
+    let old_path = std::env::temp_dir().join("phetools_does_not_exist.hpoa");
+    assert!(ga4ghphetools::hpoa::diff_hpoa_files(&old_path, &old_path).is_err());
+}
@@ -0,0 +1,61 @@
+mod common;
+
+use std::path::PathBuf;
+use std::sync::Arc;
+
+use ga4ghphetools::dto::hpo_term_dto::CellValue;
+use ontolius::ontology::csr::FullCsrOntology;
+use rstest::{fixture, rstest};
+use common::hpo_fixture::hpo;
+
+
+/// Two diseases; the first one is annotated from two publications
+const MINIMAL_HPOA: &str = "\
+#diseaseID\tdiseaseName\tphenotypeID\tphenotypeName\tonsetID\tonsetName\tfrequency\tsex\tnegation\tmodifier\tdescription\tpublication\tevidence\tbiocuration
+OMIM:154700\tMarfan syndrome\tHP:0001166\tArachnodactyly\t\t\t3/4\t\t\t\t\tPMID:29999999\tPCS\tORCID:0000-0002-0736-9199[2025-01-09]
+OMIM:154700\tMarfan syndrome\tHP:0001631\tAtrial septal defect\t\t\t0/4\t\t\t\t\tPMID:29999999\tPCS\tORCID:0000-0002-0736-9199[2025-01-09]
+OMIM:154700\tMarfan syndrome\tHP:0004322\tShort stature\t\t\t\t\tNOT\t\t\tPMID:30000000\tPCS\tORCID:0000-0002-0736-9199[2025-01-09]
+OMIM:154700\tMarfan syndrome\tHP:0003577\tCongenital onset\t\t\t2/2\t\t\t\t\tPMID:29999999\tPCS\tORCID:0000-0002-0736-9199[2025-01-09]
+OMIM:154700\tMarfan syndrome\tHP:0000006\tAutosomal dominant inheritance\t\t\t\t\t\t\t\tPMID:29999999\tPCS\tORCID:0000-0002-0736-9199[2025-01-09]
+OMIM:613795\tEctopia lentis 1, isolated, autosomal dominant\tHP:0001083\tEctopia lentis\t\t\t1/1\t\t\t\t\tPMID:31000000\tPCS\tORCID:0000-0002-0736-9199[2025-01-09]
+";
+
+#[fixture]
+fn minimal_hpoa_path() -> PathBuf {
+    let path = std::env::temp_dir().join(format!("phetools_minimal_{}.hpoa", std::process::id()));
+    std::fs::write(&path, MINIMAL_HPOA).unwrap();
+    path
+}
+
+#[rstest]
+fn test_import_from_hpoa(minimal_hpoa_path: PathBuf, hpo: Arc<FullCsrOntology>) {
+    let cohorts = ga4ghphetools::hpoa::import_from_hpoa(&minimal_hpoa_path, hpo);
+    std::fs::remove_file(&minimal_hpoa_path).unwrap();
+    let cohorts = cohorts.unwrap();
+    assert_eq!(2, cohorts.len());
+    let marfan = &cohorts[0];
+    assert_eq!("OMIM:154700", marfan.disease_list[0].disease_id);
+    assert_eq!("HP:0000006", marfan.disease_list[0].mode_of_inheritance_list[0].hpo_id);
+    // the onset and the mode of inheritance are not phenotypic features
+    assert_eq!(3, marfan.hpo_headers.len());
+    assert_eq!(2, marfan.rows.len());
+    let value = |row: usize, hpo_id: &str| {
+        let i = marfan.hpo_headers.iter().position(|h| h.hpo_id() == hpo_id).unwrap();
+        marfan.rows[row].hpo_data[i].clone()
+    };
+    assert_eq!("PMID:29999999", marfan.rows[0].individual_data.pmid);
+    assert_eq!(CellValue::observed(), value(0, "HP:0001166"));
+    assert_eq!(CellValue::excluded(), value(0, "HP:0001631"));
+    assert_eq!(CellValue::na(), value(0, "HP:0004322"));
+    assert_eq!(CellValue::excluded(), value(1, "HP:0004322"));
+    let ectopia_lentis = &cohorts[1];
+    assert_eq!("OMIM:613795", ectopia_lentis.disease_list[0].disease_id);
+    assert_eq!(1, ectopia_lentis.rows.len());
+    assert!(ectopia_lentis.disease_list[0].mode_of_inheritance_list.is_empty());
+}
+
+#[rstest]
+fn test_import_missing_file(hpo: Arc<FullCsrOntology>) {
+    let path = PathBuf::from("/nonexistent/OMIM-154700.tab");
+    assert!(ga4ghphetools::hpoa::import_from_hpoa(&path, hpo).is_err());
+}
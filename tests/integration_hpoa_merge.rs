@@ -0,0 +1,50 @@
+use std::path::{Path, PathBuf};
+
+use rstest::rstest;
+
+
+const HEADER: &str = "#diseaseID\tdiseaseName\tphenotypeID\tphenotypeName\tonsetID\tonsetName\tfrequency\tsex\tnegation\tmodifier\tdescription\tpublication\tevidence\tbiocuration\n";
+
+/// Annotations of Marfan syndrome from a cohort; Arachnodactyly is also in the second file (with TAS)
+const COHORT_HPOA: &str = "\
+OMIM:154700\tMarfan syndrome\tHP:0001166\tArachnodactyly\t\t\t3/4\t\t\t\t\tOMIM:154700\tPCS\tORCID:0000-0002-0736-9199[2025-01-09]
+OMIM:154700\tMarfan syndrome\tHP:0004322\tShort stature\t\t\t\t\tNOT\t\t\tPMID:30000000\tPCS\tORCID:0000-0002-0736-9199[2025-01-09]
+";
+
+const OMIM_HPOA: &str = "\
+OMIM:154700\tMarfan syndrome\tHP:0001166\tArachnodactyly\t\t\t\t\t\t\t\tOMIM:154700\tTAS\tHPO:probinson[2009-02-17]
+OMIM:613795\tEctopia lentis 1, isolated, autosomal dominant\tHP:0001083\tEctopia lentis\t\t\t\t\t\t\t\tOMIM:613795\tTAS\tHPO:probinson[2009-02-17]
+";
+
+fn write_hpoa(name: &str, rows: &str) -> PathBuf {
+    let path = std::env::temp_dir().join(format!("phetools_{}_{}.hpoa", name, std::process::id()));
+    std::fs::write(&path, format!("{HEADER}{rows}")).unwrap();
+    path
+}
+
+#[rstest]
+fn test_merge_hpoa_files() {
+    let cohort_path = write_hpoa("merge_cohort", COHORT_HPOA);
+    let omim_path = write_hpoa("merge_omim", OMIM_HPOA);
+    let output_path = std::env::temp_dir().join(format!("phetools_merged_{}.hpoa", std::process::id()));
+    let n_rows = ga4ghphetools::hpoa::merge_hpoa_files(&[cohort_path.as_path(), omim_path.as_path()], &output_path);
+    let merged = std::fs::read_to_string(&output_path);
+    for path in [&cohort_path, &omim_path, &output_path] {
+        let _ = std::fs::remove_file(path);
+    }
+    assert_eq!(3, n_rows.unwrap());
+    let merged = merged.unwrap();
+    let lines: Vec<&str> = merged.lines().collect();
+    assert_eq!(HEADER.trim_end(), lines[0]);
+    let arachnodactyly: Vec<&&str> = lines.iter().filter(|l| l.contains("HP:0001166")).collect();
+    assert_eq!(1, arachnodactyly.len());
+    assert!(arachnodactyly[0].contains("\tTAS\t"));
+}
+
+#[rstest]
+fn test_merge_missing_file() {
+    let output_path = std::env::temp_dir().join("phetools_merged_missing.hpoa");
+    let result = ga4ghphetools::hpoa::merge_hpoa_files(&[Path::new("/does/not/exist.hpoa")], &output_path);
+    assert!(result.is_err());
+    assert!(!output_path.exists());
+}
@@ -0,0 +1,87 @@
+mod common;
+
+use std::sync::Arc;
+
+use ga4ghphetools::dto::{cohort_dto::CohortData, intergenic_variant::IntergenicHgvsVariant};
+use ontolius::ontology::csr::FullCsrOntology;
+use phenopackets::ga4gh::vrsatile::v1::VariationDescriptor;
+use phenopackets::schema::v2::{core::genomic_interpretation::Call, Phenopacket};
+use rstest::rstest;
+use serde_json::json;
+use common::hpo_fixture::hpo;
+
+use crate::common::cohort_data_fixtures::family_cohort;
+
+const ORCID: &str = "ORCID:0000-0002-0736-9199";
+
+/// A validated promoter variant, as stored by the ETL pipeline (gene fields are absent if VariantValidator
+/// did not assign the variant to a gene)
+fn intergenic_variant(with_gene: bool) -> IntergenicHgvsVariant {
+    let (symbol, hgnc_id, gene_hgvs) = if with_gene {
+        (json!("BRCA1"), json!("HGNC:1100"), json!("NG_005905.2:g.92000G>A"))
+    } else {
+        (json!(null), json!(null), json!(null))
+    };
+    serde_json::from_value(json!({
+        "assembly": "hg38",
+        "chr": "17",
+        "position": 43125483,
+        "refAllele": "C",
+        "altAllele": "T",
+        "symbol": symbol,
+        "hgncId": hgnc_id,
+        "gHgvs": "NC_000017.11:g.43125483C>T",
+        "geneHgvs": gene_hgvs,
+        "variantKey": "NC_000017_11_g43125483CtoT",
+    })).unwrap()
+}
+
+/// Export the phenopacket of the proband, who carries the intergenic variant
+fn proband_phenopacket(cohort: CohortData, hpo: Arc<FullCsrOntology>, variant: IntergenicHgvsVariant) -> Phenopacket {
+    let mut cohort = cohort;
+    let key = variant.variant_key();
+    cohort.intergenic_variants.insert(key.clone(), variant);
+    cohort.rows[0].allele_count_map.insert(key, 1);
+    ga4ghphetools::ppkt::get_phenopackets(cohort, ORCID.to_string(), hpo)
+        .unwrap()
+        .into_iter()
+        .find(|ppkt| ppkt.id.contains("Proband"))
+        .unwrap()
+}
+
+fn variation_descriptors(ppkt: &Phenopacket) -> Vec<&VariationDescriptor> {
+    ppkt.interpretations.iter()
+        .filter_map(|i| i.diagnosis.as_ref())
+        .flat_map(|d| d.genomic_interpretations.iter())
+        .filter_map(|gi| match &gi.call {
+            Some(Call::VariantInterpretation(vi)) => vi.variation_descriptor.as_ref(),
+            _ => None,
+        })
+        .collect()
+}
+
+#[rstest]
+fn test_intergenic_variant_with_gene(hpo: Arc<FullCsrOntology>, family_cohort: CohortData) {
+    let ppkt = proband_phenopacket(family_cohort, hpo, intergenic_variant(true));
+    let descriptors = variation_descriptors(&ppkt);
+    assert_eq!(1, descriptors.len());
+    let vd = descriptors[0];
+    assert_eq!("NC_000017_11_g43125483CtoT", vd.id);
+    assert_eq!("BRCA1", vd.gene_context.as_ref().unwrap().symbol);
+    let expressions: Vec<&str> = vd.expressions.iter().map(|e| e.value.as_str()).collect();
+    assert_eq!(vec!["NC_000017.11:g.43125483C>T", "NG_005905.2:g.92000G>A"], expressions);
+    assert!(vd.expressions.iter().all(|e| e.syntax == "hgvs.g"));
+    let vcf = vd.vcf_record.as_ref().unwrap();
+    assert_eq!(43125483, vcf.pos);
+    assert_eq!("hg38", vcf.genome_assembly);
+}
+
+#[rstest]
+fn test_intergenic_variant_without_gene(hpo: Arc<FullCsrOntology>, family_cohort: CohortData) {
+    let ppkt = proband_phenopacket(family_cohort, hpo, intergenic_variant(false));
+    let descriptors = variation_descriptors(&ppkt);
+    // the variant is added to the diagnosis although it cannot be assigned to the gene of the disease
+    assert_eq!(1, descriptors.len());
+    assert!(descriptors[0].gene_context.is_none());
+    assert_eq!("NC_000017.11:g.43125483C>T", descriptors[0].expressions[0].value);
+}
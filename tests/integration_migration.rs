@@ -0,0 +1,59 @@
+use ga4ghphetools::dto::{cohort_dto::{CohortData, CohortType}, hpo_term_dto::CellValueInner};
+use ga4ghphetools::factory::{load_json_cohort, load_json_cohort_any_version};
+use rstest::rstest;
+
+
+/// A cohort file in the legacy (v1) structure with a diseaseGeneDto and plain string HPO cells
+const LEGACY_COHORT: &str = "tests/data/legacy_cohort_v1.json";
+
+#[rstest]
+fn test_legacy_cohort_requires_migration() {
+    assert!(load_json_cohort(LEGACY_COHORT).is_err());
+}
+
+#[rstest]
+fn test_migrate_legacy_cohort() {
+    let cohort = load_json_cohort_any_version(LEGACY_COHORT).unwrap();
+    assert_eq!(CohortType::Mendelian, cohort.cohort_type);
+    assert_eq!(CohortData::get_phetools_schema_version(), cohort.phetools_schema_version);
+    assert_eq!("2024-04-26", cohort.hpo_version);
+    assert_eq!(None, cohort.cohort_acronym);
+    assert_eq!(1, cohort.disease_list.len());
+    let disease = &cohort.disease_list[0];
+    assert_eq!("OMIM:154700", disease.disease_id);
+    assert!(disease.mode_of_inheritance_list.is_empty());
+    assert_eq!(1, disease.gene_transcript_list.len());
+    assert_eq!("FBN1", disease.gene_transcript_list[0].gene_symbol);
+    assert_eq!("NM_000138.5", disease.gene_transcript_list[0].transcript);
+    assert_eq!(3, cohort.hpo_headers.len());
+    assert_eq!(2, cohort.rows.len());
+    let row = &cohort.rows[0];
+    assert_eq!("II-1", row.individual_data.individual_id);
+    assert_eq!(vec!["OMIM:154700".to_string()], row.disease_id_list);
+    assert_eq!(Some(&1), row.allele_count_map.get("c8242GtoT_FBN1_NM_000138v5"));
+    let entries: Vec<&CellValueInner> = row.hpo_data.iter().map(|c| &c.entry).collect();
+    assert_eq!(vec![
+        &CellValueInner::Observed,
+        &CellValueInner::OnsetAge("P8Y".to_string()),
+        &CellValueInner::Excluded,
+    ], entries);
+    assert_eq!("sister of II-1", cohort.rows[1].individual_data.comment);
+    assert_eq!(1, cohort.hgvs_variants.len());
+}
+
+#[rstest]
+fn test_migrated_cohort_can_be_loaded_with_current_schema() {
+    let cohort = load_json_cohort_any_version(LEGACY_COHORT).unwrap();
+    let path = std::env::temp_dir().join(format!("phetools_migrated_{}.json", std::process::id()));
+    std::fs::write(&path, serde_json::to_string_pretty(&cohort).unwrap()).unwrap();
+    let reloaded = load_json_cohort(&path.to_string_lossy());
+    std::fs::remove_file(&path).unwrap();
+    let reloaded = reloaded.unwrap();
+    assert_eq!(cohort.rows.len(), reloaded.rows.len());
+    assert_eq!(cohort.disease_list, reloaded.disease_list);
+}
+
+#[rstest]
+fn test_missing_file() {
+    assert!(load_json_cohort_any_version("tests/data/does_not_exist.json").is_err());
+}
@@ -0,0 +1,89 @@
+mod common;
+
+use std::sync::Arc;
+
+use ga4ghphetools::dto::{cohort_dto::CohortData, hpo_term_dto::SeverityCode};
+use ontolius::ontology::csr::FullCsrOntology;
+use phenopackets::schema::v2::{core::PhenotypicFeature, Phenopacket};
+use rstest::rstest;
+use common::hpo_fixture::hpo;
+
+use crate::common::cohort_data_fixtures::family_cohort;
+
+const ORCID: &str = "ORCID:0000-0002-0736-9199";
+
+/// Set the modifiers of the Rigidity (HP:0002063) annotation of the proband
+fn with_rigidity_modifiers(mut cohort: CohortData, modifiers: &[&str]) -> CohortData {
+    cohort.rows[0].hpo_data[0].modifiers = modifiers.iter().map(|m| m.to_string()).collect();
+    cohort
+}
+
+fn proband_phenopacket(cohort: CohortData, hpo: Arc<FullCsrOntology>) -> Phenopacket {
+    ga4ghphetools::ppkt::get_phenopackets(cohort, ORCID.to_string(), hpo)
+        .unwrap()
+        .into_iter()
+        .find(|ppkt| ppkt.id.contains("Proband"))
+        .unwrap()
+}
+
+fn feature<'a>(ppkt: &'a Phenopacket, hpo_id: &str) -> &'a PhenotypicFeature {
+    ppkt.phenotypic_features
+        .iter()
+        .find(|pf| pf.r#type.as_ref().is_some_and(|t| t.id == hpo_id))
+        .unwrap()
+}
+
+
+#[rstest]
+fn test_modifiers_are_exported(hpo: Arc<FullCsrOntology>, family_cohort: CohortData) {
+    let cohort = with_rigidity_modifiers(family_cohort, &["HP:0012826", "HP:0012832"]);
+    let ppkt = proband_phenopacket(cohort, hpo);
+    let rigidity = feature(&ppkt, "HP:0002063");
+    // severity terms are exported as the severity of the feature, other clinical modifiers as modifiers
+    let severity = rigidity.severity.as_ref().unwrap();
+    assert_eq!("HP:0012826", severity.id);
+    assert_eq!("Moderate", severity.label);
+    assert_eq!(1, rigidity.modifiers.len());
+    assert_eq!("HP:0012832", rigidity.modifiers[0].id);
+    assert_eq!("Bilateral", rigidity.modifiers[0].label);
+    // annotations without modifiers
+    let short_stature = feature(&ppkt, "HP:0004322");
+    assert!(short_stature.severity.is_none());
+    assert!(short_stature.modifiers.is_empty());
+}
+
+#[rstest]
+#[case::phenotype_term("HP:0000343", "is not a clinical modifier")]
+#[case::unknown_term("HP:9999999", "unknown modifier")]
+fn test_invalid_modifier_is_rejected(
+    hpo: Arc<FullCsrOntology>,
+    family_cohort: CohortData,
+    #[case] modifier: &str,
+    #[case] message: &str,
+) {
+    let cohort = with_rigidity_modifiers(family_cohort, &[modifier]);
+    let err = ga4ghphetools::ppkt::get_phenopackets(cohort, ORCID.to_string(), hpo).unwrap_err();
+    let err = err.to_string();
+    assert!(err.contains(message), "unexpected error: {err}");
+    assert!(err.contains("Proband"), "unexpected error: {err}");
+}
+
+#[rstest]
+#[case(SeverityCode::Mild, "HP:0012825", "Mild")]
+#[case(SeverityCode::Moderate, "HP:0012826", "Moderate")]
+#[case(SeverityCode::Severe, "HP:0012828", "Severe")]
+#[case(SeverityCode::Profound, "HP:0012829", "Profound")]
+fn test_severity_is_exported(
+    hpo: Arc<FullCsrOntology>,
+    mut family_cohort: CohortData,
+    #[case] code: SeverityCode,
+    #[case] hpo_id: &str,
+    #[case] label: &str,
+) {
+    family_cohort.rows[0].hpo_data[0].severity = Some(code);
+    let ppkt = proband_phenopacket(family_cohort, hpo);
+    let severity = feature(&ppkt, "HP:0002063").severity.clone().unwrap();
+    assert_eq!(hpo_id, severity.id);
+    assert_eq!(label, severity.label);
+    assert!(feature(&ppkt, "HP:0004322").severity.is_none());
+}
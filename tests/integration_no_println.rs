@@ -0,0 +1,34 @@
+//! Library code must report messages with `tracing` rather than printing to stdout/stderr,
+//! so that applications (e.g., the Tauri GUI) can install their own subscriber.
+//! Test modules (at the end of each file, after `#[cfg(test)]`) and doc comments are not checked.
+use std::fs;
+
+use walkdir::WalkDir;
+
+
+const FORBIDDEN: [&str; 4] = ["println!", "eprintln!", "print!(", "eprint!("];
+
+#[test]
+fn test_no_print_macros_in_library_code() {
+    let mut offending: Vec<String> = Vec::new();
+    for entry in WalkDir::new("src").into_iter().filter_map(Result::ok) {
+        let path = entry.path();
+        if path.extension().and_then(|e| e.to_str()) != Some("rs") || path.ends_with("test_utils.rs") {
+            continue;
+        }
+        let contents = fs::read_to_string(path).unwrap();
+        for (i, line) in contents.lines().enumerate() {
+            let trimmed = line.trim_start();
+            if trimmed.starts_with("#[cfg(test)]") {
+                break;
+            }
+            if trimmed.starts_with("//") {
+                continue;
+            }
+            if FORBIDDEN.iter().any(|m| trimmed.contains(m)) {
+                offending.push(format!("{}:{}: {}", path.display(), i + 1, trimmed));
+            }
+        }
+    }
+    assert!(offending.is_empty(), "Use tracing instead of print macros:\n{}", offending.join("\n"));
+}
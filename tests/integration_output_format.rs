@@ -0,0 +1,59 @@
+//! The `--output-format json` option of the CLI serializes library types with `serde_json::to_string_pretty`.
+//! These tests check that the JSON can be read back into the same type, so that the output can be used in pipelines.
+mod common;
+
+use std::sync::Arc;
+
+use ga4ghphetools::dto::cohort_dto::CohortData;
+use ga4ghphetools::export::CohortStats;
+use ga4ghphetools::factory::QcReport;
+use ga4ghphetools::hpoa::HpoaExportSummary;
+use ontolius::ontology::csr::FullCsrOntology;
+use rstest::rstest;
+
+use crate::common::cohort_data_fixtures::cohort_data_1;
+use crate::common::hpo_fixture::hpo;
+
+
+/// etl
+#[rstest]
+fn test_cohort_data_json_roundtrip(cohort_data_1: CohortData) {
+    let json = serde_json::to_string_pretty(&cohort_data_1).unwrap();
+    let parsed: CohortData = serde_json::from_str(&json).unwrap();
+    assert_eq!(
+        serde_json::to_value(&cohort_data_1).unwrap(),
+        serde_json::to_value(&parsed).unwrap()
+    );
+}
+
+/// stats
+#[rstest]
+fn test_cohort_stats_json_roundtrip(cohort_data_1: CohortData) {
+    let stats = ga4ghphetools::export::compute_cohort_stats(&cohort_data_1, 10, 1);
+    let json = serde_json::to_string_pretty(&stats).unwrap();
+    let parsed: CohortStats = serde_json::from_str(&json).unwrap();
+    assert_eq!(stats, parsed);
+}
+
+/// hpoa
+#[rstest]
+fn test_hpoa_summary_json_roundtrip() {
+    let summary = HpoaExportSummary {
+        output_path: "OMIM-154700.tab".to_string(),
+        n_annotations: 42,
+        appended: false,
+    };
+    let json = serde_json::to_string_pretty(&summary).unwrap();
+    let parsed: HpoaExportSummary = serde_json::from_str(&json).unwrap();
+    assert_eq!(summary, parsed);
+}
+
+/// validate
+#[rstest]
+fn test_qc_report_json_roundtrip(cohort_data_1: CohortData, hpo: Arc<FullCsrOntology>) {
+    let report = ga4ghphetools::factory::qc_report(hpo, &cohort_data_1, "cohort_data_1.json");
+    let json = serde_json::to_string_pretty(&report).unwrap();
+    let parsed: QcReport = serde_json::from_str(&json).unwrap();
+    assert_eq!(report, parsed);
+    assert_eq!(report.passed, report.error.is_none());
+}
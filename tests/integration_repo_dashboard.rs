@@ -0,0 +1,42 @@
+mod common;
+
+use std::sync::Arc;
+
+use ga4ghphetools::dto::cohort_dto::CohortData;
+use ontolius::ontology::csr::FullCsrOntology;
+use rstest::rstest;
+use common::hpo_fixture::hpo;
+
+use crate::common::cohort_data_fixtures::family_cohort;
+use crate::common::store_fixtures::write_cohort_dir;
+use crate::common::temp_store::temp_store;
+
+
+#[rstest]
+fn test_generate_dashboard(hpo: Arc<FullCsrOntology>, family_cohort: CohortData) {
+    let store = temp_store("phetools_test_dashboard_store");
+    write_cohort_dir(&store, "BRCA1", "BRCA1_TD_individuals.json", &family_cohort, hpo.clone());
+    let html = ga4ghphetools::repo::generate_repo_dashboard(&store, hpo);
+    std::fs::remove_dir_all(&store).unwrap();
+    let html = html.unwrap();
+    assert!(html.contains("<td>BRCA1</td>"));
+    // three individuals and three phenopackets
+    assert!(html.contains("Phenopackets: <b>3</b>"));
+    assert!(html.contains("Individuals: <b>3</b>"));
+    // the fixture was curated with an older HPO version and lacks variants (mode of inheritance mismatch)
+    assert!(html.contains("2024-01-01"));
+    assert!(html.contains("Mode of inheritance mismatch"));
+    assert!(html.contains("<td>0.3</td>"));
+    assert!(html.contains("Cohort files to migrate: <b>1</b>"));
+    assert!(html.contains("Needs migration"));
+}
+
+#[rstest]
+fn test_dashboard_empty_store(hpo: Arc<FullCsrOntology>) {
+    let store = temp_store("phetools_test_dashboard_empty_store");
+    let html = ga4ghphetools::repo::generate_repo_dashboard(&store, hpo);
+    std::fs::remove_dir_all(&store).unwrap();
+    let html = html.unwrap();
+    assert!(html.contains("Cohorts: <b>0</b>"));
+    assert!(html.contains("No Q/C violations found."));
+}
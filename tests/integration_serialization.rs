@@ -0,0 +1,339 @@
+//! JSON round trips of the CohortData and ETL DTOs
+//!
+//! The front end and the cohort files depend on the JSON representation, so every field must survive
+//! serialization and deserialization unchanged. Several DTOs do not implement PartialEq, so we compare
+//! them field by field.
+
+use std::collections::HashMap;
+
+use ga4ghphetools::dto::{
+    cohort_dto::{BiosampleData, CohortData, CohortType, CurationEvent, DiseaseData, GeneTranscriptData,
+        IndividualData, MeasurementData, ModeOfInheritance, RowData, TrioData},
+    etl_dto::{ColumnDto, ColumnTableDto, EtlCellStatus, EtlCellValue, EtlColumnHeader, EtlColumnType, EtlDto},
+    hgvs_variant::HgvsVariant,
+    hpo_term_dto::{CellValue, CellValueInner, CurationConfidence, HpoTermData, HpoTermDuplet},
+    intergenic_variant::IntergenicHgvsVariant,
+    structural_variant::StructuralVariant,
+};
+use rstest::{fixture, rstest};
+use serde_json::json;
+
+
+fn roundtrip<T: serde::Serialize + serde::de::DeserializeOwned>(value: &T) -> T {
+    let json = serde_json::to_string(value).unwrap();
+    serde_json::from_str(&json).unwrap()
+}
+
+fn hpo_headers() -> Vec<HpoTermDuplet> {
+    vec![
+        HpoTermDuplet::new("Arachnodactyly", "HP:0001166"),
+        HpoTermDuplet::new("Atrial septal defect", "HP:0001631"),
+        HpoTermDuplet::new("Long hallux", "HP:0001847"),
+        HpoTermDuplet::new("Short stature", "HP:0004322"),
+        HpoTermDuplet::new("Ectopia lentis", "HP:0001083"),
+    ]
+}
+
+/// One cell of each kind, with modifiers and curation confidence
+fn hpo_cells() -> Vec<CellValue> {
+    let mut severe = CellValue::observed();
+    severe.modifiers = vec!["HP:0012828".to_string(), "HP:0012832".to_string()];
+    let mut uncertain = CellValue::excluded();
+    uncertain.confidence = Some(CurationConfidence::Low);
+    vec![
+        severe,
+        uncertain,
+        CellValue::na(),
+        CellValueInner::OnsetAge("P10Y".to_string()).into(),
+        CellValueInner::HpoFrequency("HP:0040281".to_string()).into(),
+    ]
+}
+
+fn hgvs_variant() -> HgvsVariant {
+    let mut var: HgvsVariant = serde_json::from_value(json!({
+        "assembly": "hg38",
+        "chr": "chr15",
+        "position": 48411364,
+        "refAllele": "C",
+        "altAllele": "A",
+        "symbol": "FBN1",
+        "hgncId": "HGNC:3603",
+        "hgvs": "c.8242G>T",
+        "pHgvs": "NP_000129.3:p.(Glu2748Ter)",
+        "transcript": "NM_000138.5",
+        "gHgvs": "NC_000015.10:g.48411364C>A",
+        "variantKey": "c8242GtoT_FBN1_NM_000138v5",
+    })).unwrap();
+    // powers of two, so that the comparison does not depend on the precision of float parsing
+    var.set_vep_annotation(Some("stop_gained".to_string()), Some("HIGH".to_string()), Some(0.25));
+    var.set_gnomad_frequency(Some(0.25), Some(0.5));
+    var
+}
+
+fn intergenic_variant() -> IntergenicHgvsVariant {
+    serde_json::from_value(json!({
+        "assembly": "hg38",
+        "chr": "15",
+        "position": 48937000,
+        "refAllele": "G",
+        "altAllele": "T",
+        "symbol": null,
+        "hgncId": null,
+        "gHgvs": "NC_000015.10:g.48937000G>T",
+        "geneHgvs": null,
+        "variantKey": "NC_000015_10_g48937000GtoT",
+    })).unwrap()
+}
+
+/// An individual with all optional fields set
+fn individual(individual_id: &str) -> IndividualData {
+    let mut individual = IndividualData::new("PMID:29834", "A study of FBN1", individual_id,
+        "comment", "P3Y", "P12Y", "no", "F");
+    individual.age_of_onset_max = Some("P5Y".to_string());
+    individual.age_at_last_encounter_max = Some("P13Y".to_string());
+    individual.treatment_list = vec!["CHEBI:45783 imatinib".to_string()];
+    individual.biosamples = vec![BiosampleData {
+        biosample_id: format!("{individual_id}-liver"),
+        tissue_id: "UBERON:0002107".to_string(),
+        tissue_label: "liver".to_string(),
+        histology_id: Some("NCIT:C3058".to_string()),
+    }];
+    individual.measurements = vec![
+        "LOINC:2160-0 1.5 UCUM:mg/dL P3Y".parse::<MeasurementData>().unwrap(),
+        "LOINC:2160-0 2 UCUM:mg/dL".parse::<MeasurementData>().unwrap(),
+    ];
+    individual
+}
+
+#[fixture]
+fn full_cohort() -> CohortData {
+    let mut disease = DiseaseData::new("OMIM:154700", "Marfan syndrome");
+    disease.mode_of_inheritance_list.push(ModeOfInheritance {
+        hpo_id: "HP:0000006".to_string(),
+        hpo_label: "Autosomal dominant inheritance".to_string(),
+        citation: "PMID:1852208".to_string(),
+    });
+    disease.gene_transcript_list.push(GeneTranscriptData {
+        hgnc_id: "HGNC:3603".to_string(),
+        gene_symbol: "FBN1".to_string(),
+        transcript: "NM_000138.5".to_string(),
+    });
+    let hgvs = hgvs_variant();
+    let sv = StructuralVariant::chromosomal_deletion("DEL Ex5-7", "FBN1", "NM_000138.5", "HGNC:3603", "15".to_string())
+        .unwrap();
+    let intergenic = intergenic_variant();
+    let mut cohort = CohortData::mendelian(disease, hpo_headers(), vec![], "2025-03-03");
+    cohort.rows = ["P1", "P2"]
+        .iter()
+        .map(|id| RowData {
+            individual_data: individual(id),
+            disease_id_list: vec!["OMIM:154700".to_string()],
+            allele_count_map: HashMap::from([
+                (hgvs.variant_key(), 1),
+                (sv.variant_key().to_string(), 1),
+            ]),
+            hpo_data: hpo_cells(),
+        })
+        .collect();
+    cohort.hgvs_variants.insert(hgvs.variant_key(), hgvs);
+    cohort.structural_variants.insert(sv.variant_key().to_string(), sv);
+    cohort.intergenic_variants.insert("NC_000015_10_g48937000GtoT".to_string(), intergenic);
+    cohort.cohort_acronym = Some("MFS".to_string());
+    cohort.curation_history = vec![CurationEvent {
+        orcid: "ORCID:0000-0002-0736-9199".to_string(),
+        date: "2025-03-04".to_string(),
+    }];
+    cohort.trios = vec![TrioData {
+        proband_id: "P1".to_string(),
+        father_id: "F1".to_string(),
+        mother_id: "M1".to_string(),
+        father_alleles: Some(vec!["na".to_string()]),
+        mother_alleles: None,
+    }];
+    cohort
+}
+
+fn assert_individual_eq(expected: &IndividualData, actual: &IndividualData) {
+    assert_eq!(expected.pmid, actual.pmid);
+    assert_eq!(expected.title, actual.title);
+    assert_eq!(expected.individual_id, actual.individual_id);
+    assert_eq!(expected.comment, actual.comment);
+    assert_eq!(expected.age_of_onset, actual.age_of_onset);
+    assert_eq!(expected.age_at_last_encounter, actual.age_at_last_encounter);
+    assert_eq!(expected.age_of_onset_max, actual.age_of_onset_max);
+    assert_eq!(expected.age_at_last_encounter_max, actual.age_at_last_encounter_max);
+    assert_eq!(expected.deceased, actual.deceased);
+    assert_eq!(expected.sex, actual.sex);
+    assert_eq!(expected.treatment_list, actual.treatment_list);
+    assert_eq!(expected.biosamples, actual.biosamples);
+    assert_eq!(expected.measurements, actual.measurements);
+}
+
+fn assert_row_eq(expected: &RowData, actual: &RowData) {
+    assert_individual_eq(&expected.individual_data, &actual.individual_data);
+    assert_eq!(expected.disease_id_list, actual.disease_id_list);
+    assert_eq!(expected.allele_count_map, actual.allele_count_map);
+    assert_eq!(expected.hpo_data, actual.hpo_data);
+}
+
+
+#[rstest]
+fn test_cohort_roundtrip(full_cohort: CohortData) {
+    let cohort = roundtrip(&full_cohort);
+    assert_eq!(full_cohort.cohort_type, cohort.cohort_type);
+    assert_eq!(full_cohort.disease_list, cohort.disease_list);
+    assert_eq!(full_cohort.hpo_headers, cohort.hpo_headers);
+    assert_eq!(full_cohort.rows.len(), cohort.rows.len());
+    for (expected, actual) in full_cohort.rows.iter().zip(cohort.rows.iter()) {
+        assert_row_eq(expected, actual);
+    }
+    assert_eq!(full_cohort.hgvs_variants, cohort.hgvs_variants);
+    assert_eq!(full_cohort.structural_variants, cohort.structural_variants);
+    assert_eq!(full_cohort.intergenic_variants, cohort.intergenic_variants);
+    assert_eq!(full_cohort.phetools_schema_version, cohort.phetools_schema_version);
+    assert_eq!(full_cohort.hpo_version, cohort.hpo_version);
+    assert_eq!(full_cohort.cohort_acronym, cohort.cohort_acronym);
+    assert_eq!(full_cohort.curation_history, cohort.curation_history);
+    assert_eq!(full_cohort.trios, cohort.trios);
+}
+
+/// Serializing the deserialized cohort again gives the same JSON
+#[rstest]
+fn test_cohort_json_is_stable(full_cohort: CohortData) {
+    let json = serde_json::to_value(&full_cohort).unwrap();
+    let cohort: CohortData = serde_json::from_value(json.clone()).unwrap();
+    assert_eq!(json, serde_json::to_value(&cohort).unwrap());
+}
+
+/// The HGVS variants are stored in a map keyed by the variant key; the keys and all optional fields must survive
+#[rstest]
+fn test_hgvs_variant_map_roundtrip(full_cohort: CohortData) {
+    let variants = roundtrip(&full_cohort.hgvs_variants);
+    let var = &variants["c8242GtoT_FBN1_NM_000138v5"];
+    assert_eq!(Some("NP_000129.3:p.(Glu2748Ter)".to_string()), var.p_hgvs());
+    assert_eq!(Some("stop_gained"), var.consequence());
+    assert_eq!(Some("HIGH"), var.impact());
+    assert_eq!(Some(0.25), var.gnomad_af());
+    assert_eq!(Some(0.5), var.gnomad_popmax_af());
+    let json = serde_json::to_value(&full_cohort.hgvs_variants).unwrap();
+    assert_eq!(json!(48411364), json["c8242GtoT_FBN1_NM_000138v5"]["position"]);
+}
+
+/// Unset optional fields of the HGVS variant are left out of the JSON and deserialize as None
+#[rstest]
+fn test_hgvs_variant_without_annotation() {
+    let var = HgvsVariant::new_from_parts("hg38".to_string(), "chr15".to_string(), 48411364, "C".to_string(),
+        "A".to_string(), "FBN1".to_string(), "HGNC:3603".to_string(), "c.8242G>T".to_string(),
+        "NM_000138.5".to_string(), "NC_000015.10:g.48411364C>A".to_string());
+    let json = serde_json::to_value(&var).unwrap();
+    assert!(json.get("gnomadAf").is_none());
+    assert!(json.get("consequence").is_none());
+    assert_eq!(var, roundtrip(&var));
+}
+
+/// The HPO headers are serialized as a list of objects in the order of the template
+#[rstest]
+fn test_hpo_duplets_roundtrip() {
+    let headers = hpo_headers();
+    let json = serde_json::to_value(&headers).unwrap();
+    assert_eq!(json!({"hpoLabel": "Arachnodactyly", "hpoId": "HP:0001166"}), json[0]);
+    assert_eq!(headers, roundtrip(&headers));
+}
+
+#[rstest]
+fn test_cell_value_json() {
+    let json: Vec<serde_json::Value> = hpo_cells().iter().map(|c| serde_json::to_value(c).unwrap()).collect();
+    assert_eq!(json!({"type": "Observed", "modifiers": ["HP:0012828", "HP:0012832"]}), json[0]);
+    assert_eq!(json!({"type": "Excluded", "modifiers": [], "confidence": "low"}), json[1]);
+    assert_eq!(json!({"type": "Na", "modifiers": []}), json[2]);
+    assert_eq!(json!({"type": "OnsetAge", "data": "P10Y", "modifiers": []}), json[3]);
+    assert_eq!(json!({"type": "HpoFrequency", "data": "HP:0040281", "modifiers": []}), json[4]);
+}
+
+#[rstest]
+fn test_hpo_term_data_roundtrip() {
+    for (duplet, cell) in hpo_headers().into_iter().zip(hpo_cells()) {
+        let data = HpoTermData::new(duplet, cell).unwrap();
+        let restored = roundtrip(&data);
+        assert_eq!(data.term_duplet, restored.term_duplet);
+        assert_eq!(data.entry, restored.entry);
+        assert_eq!(data.confidence(), restored.confidence());
+    }
+}
+
+#[rstest]
+fn test_etl_dto_roundtrip(full_cohort: CohortData) {
+    let cell = |original: &str, current: &str, status: EtlCellStatus, error: Option<&str>| EtlCellValue {
+        original: original.to_string(),
+        current: current.to_string(),
+        status,
+        error: error.map(str::to_string),
+    };
+    let mut hpo_header = EtlColumnHeader::new_raw("Arachnodactyly");
+    hpo_header.current = Some("HP:0001166".to_string());
+    hpo_header.column_type = EtlColumnType::SingleHpoTerm;
+    hpo_header.hpo_terms = Some(vec![HpoTermDuplet::new("Arachnodactyly", "HP:0001166")]);
+    let mut variant_header = EtlColumnHeader::new_raw("Mutation");
+    variant_header.column_type = EtlColumnType::Variant;
+    variant_header.gene_index = Some(0);
+    let mut hpo_column = ColumnDto::new_raw("Arachnodactyly", 2);
+    hpo_column.header = hpo_header;
+    hpo_column.values = vec![
+        cell("+", "observed", EtlCellStatus::Transformed, None),
+        cell("?", "?", EtlCellStatus::Error, Some("Unrecognized value '?'")),
+    ];
+    let mut variant_column = ColumnDto::new_raw("Mutation", 2);
+    variant_column.header = variant_header;
+    variant_column.values = vec![
+        cell("c.8242G>T", "c8242GtoT_FBN1_NM_000138v5", EtlCellStatus::PartiallyTransformed, None),
+        cell("", "", EtlCellStatus::Ignored, None),
+    ];
+    let dto = EtlDto {
+        table: ColumnTableDto {
+            file_name: "supplement.xlsx".to_string(),
+            columns: vec![hpo_column, variant_column],
+        },
+        disease: full_cohort.disease_list.first().cloned(),
+        pmid: Some("PMID:29834".to_string()),
+        title: Some("A study of FBN1".to_string()),
+        hgvs_variants: full_cohort.hgvs_variants.clone(),
+        structural_variants: full_cohort.structural_variants.clone(),
+        intergenic_variants: full_cohort.intergenic_variants.clone(),
+    };
+    let restored = roundtrip(&dto);
+    assert_eq!(dto.table.file_name, restored.table.file_name);
+    assert_eq!(dto.table.columns.len(), restored.table.columns.len());
+    for (expected, actual) in dto.table.columns.iter().zip(restored.table.columns.iter()) {
+        assert_eq!(expected.id, actual.id);
+        assert_eq!(expected.header.original, actual.header.original);
+        assert_eq!(expected.header.current, actual.header.current);
+        assert_eq!(expected.header.column_type, actual.header.column_type);
+        assert_eq!(expected.header.hpo_terms, actual.header.hpo_terms);
+        assert_eq!(expected.header.gene_index, actual.header.gene_index);
+        assert_eq!(expected.values.len(), actual.values.len());
+        for (e, a) in expected.values.iter().zip(actual.values.iter()) {
+            assert_eq!(e.original, a.original);
+            assert_eq!(e.current, a.current);
+            assert_eq!(e.status, a.status);
+            assert_eq!(e.error, a.error);
+        }
+    }
+    assert_eq!(dto.disease, restored.disease);
+    assert_eq!(dto.pmid, restored.pmid);
+    assert_eq!(dto.title, restored.title);
+    assert_eq!(dto.hgvs_variants, restored.hgvs_variants);
+    assert_eq!(dto.structural_variants, restored.structural_variants);
+    assert_eq!(dto.intergenic_variants, restored.intergenic_variants);
+    let json = serde_json::to_value(&dto).unwrap();
+    assert_eq!(json!("partiallyTransformed"), json["table"]["columns"][1]["values"][0]["status"]);
+    assert_eq!(json!("singleHpoTerm"), json["table"]["columns"][0]["header"]["columnType"]);
+}
+
+#[rstest]
+fn test_cohort_type_roundtrip() {
+    for cohort_type in [CohortType::Mendelian, CohortType::Melded, CohortType::Digenic] {
+        let json = serde_json::to_value(cohort_type).unwrap();
+        assert_eq!(json!(cohort_type.to_string()), json);
+        assert_eq!(cohort_type, roundtrip(&cohort_type));
+    }
+}
@@ -0,0 +1,71 @@
+#![cfg(feature = "sqlite")]
+mod common;
+
+use ga4ghphetools::dto::cohort_dto::CohortData;
+use ga4ghphetools::persistence::sqlite_store::SqliteCohortStore;
+use rstest::rstest;
+
+use crate::common::cohort_data_fixtures::cohort_data_1;
+use crate::common::cohort_data_fixtures::cohort_data_2;
+
+
+#[rstest]
+fn test_save_and_load_cohort(cohort_data_1: CohortData) {
+    let store = SqliteCohortStore::in_memory().unwrap();
+    let cohort_id = store.save_cohort(&cohort_data_1).unwrap();
+    assert_eq!("BRCA1_COHORT1", cohort_id);
+    let loaded = store.load_cohort(&cohort_id).unwrap();
+    assert_eq!(cohort_data_1.rows.len(), loaded.rows.len());
+    assert_eq!(cohort_data_1.hpo_headers, loaded.hpo_headers);
+    assert_eq!(cohort_data_1.cohort_acronym, loaded.cohort_acronym);
+    assert_eq!(
+        serde_json::to_value(&cohort_data_1).unwrap(),
+        serde_json::to_value(&loaded).unwrap()
+    );
+}
+
+#[rstest]
+fn test_save_replaces_existing_cohort(mut cohort_data_1: CohortData) {
+    let store = SqliteCohortStore::in_memory().unwrap();
+    store.save_cohort(&cohort_data_1).unwrap();
+    cohort_data_1.rows.truncate(1);
+    let cohort_id = store.save_cohort(&cohort_data_1).unwrap();
+    let summaries = store.list_cohorts().unwrap();
+    assert_eq!(1, summaries.len());
+    assert_eq!(1, summaries[0].n_individuals);
+    assert_eq!(1, store.load_cohort(&cohort_id).unwrap().rows.len());
+}
+
+#[rstest]
+fn test_list_cohorts(cohort_data_1: CohortData, cohort_data_2: CohortData) {
+    let store = SqliteCohortStore::in_memory().unwrap();
+    store.save_cohort(&cohort_data_1).unwrap();
+    store.save_cohort(&cohort_data_2).unwrap();
+    let summaries = store.list_cohorts().unwrap();
+    assert_eq!(2, summaries.len());
+    let mut ids: Vec<String> = summaries.iter().map(|s| s.cohort_id.clone()).collect();
+    ids.sort();
+    assert_eq!(vec!["BRCA1_COHORT1".to_string(), "BRCA1_COHORT2".to_string()], ids);
+    for summary in &summaries {
+        assert_eq!("BRCA1", summary.gene_symbol);
+        assert_eq!("mendelian", summary.cohort_type);
+        assert_eq!(2, summary.n_individuals);
+    }
+}
+
+#[rstest]
+fn test_delete_cohort(cohort_data_1: CohortData) {
+    let store = SqliteCohortStore::in_memory().unwrap();
+    let cohort_id = store.save_cohort(&cohort_data_1).unwrap();
+    store.delete_cohort(&cohort_id).unwrap();
+    assert!(store.list_cohorts().unwrap().is_empty());
+    assert!(store.load_cohort(&cohort_id).is_err());
+    assert!(store.delete_cohort(&cohort_id).is_err());
+}
+
+#[rstest]
+fn test_cohort_without_acronym_is_rejected(mut cohort_data_1: CohortData) {
+    let store = SqliteCohortStore::in_memory().unwrap();
+    cohort_data_1.cohort_acronym = None;
+    assert!(store.save_cohort(&cohort_data_1).is_err());
+}